@@ -48,6 +48,7 @@ pub const CMD_FORMAT: &str = "format";
 pub const CMD_TEST: &str = "test";
 pub const CMD_GLUE: &str = "glue";
 pub const CMD_GEN_STUB_LIB: &str = "gen-stub-lib";
+pub const CMD_INSPECT_HOST: &str = "inspect-host";
 
 pub const FLAG_DEBUG: &str = "debug";
 pub const FLAG_BUNDLE: &str = "bundle";
@@ -66,10 +67,13 @@ pub const FLAG_STDIN: &str = "stdin";
 pub const FLAG_STDOUT: &str = "stdout";
 pub const FLAG_WASM_STACK_SIZE_KB: &str = "wasm-stack-size-kb";
 pub const FLAG_OUTPUT: &str = "output";
+pub const FLAG_WARNINGS_AS_ERRORS: &str = "warnings-as-errors";
+pub const FLAG_APP: &str = "app";
 pub const ROC_FILE: &str = "ROC_FILE";
 pub const ROC_DIR: &str = "ROC_DIR";
 pub const GLUE_DIR: &str = "GLUE_DIR";
 pub const GLUE_SPEC: &str = "GLUE_SPEC";
+pub const PREPROCESSED_HOST: &str = "PREPROCESSED_HOST";
 pub const DIRECTORY_OR_FILES: &str = "DIRECTORY_OR_FILES";
 pub const ARGS_FOR_APP: &str = "ARGS_FOR_APP";
 
@@ -103,7 +107,7 @@ pub fn build_app() -> Command {
 
     let flag_debug = Arg::new(FLAG_DEBUG)
         .long(FLAG_DEBUG)
-        .help("Store LLVM debug information in the generated program")
+        .help("Store debug information in the generated program, so debuggers like gdb can step through its Roc source")
         .action(ArgAction::SetTrue)
         .required(false);
 
@@ -115,7 +119,7 @@ pub fn build_app() -> Command {
 
     let flag_linker = Arg::new(FLAG_LINKER)
         .long(FLAG_LINKER)
-        .help("Set which linker to use\n(The surgical linker is enabled by default only when building for wasm32 or x86_64 Linux, because those are the only targets it currently supports. Otherwise the legacy linker is used by default.)")
+        .help("Set which linker to use\n(The surgical linker is enabled by default only when building for wasm32 or x86_64 Linux, because those are the only targets it currently supports. Otherwise the legacy linker is used by default. On macOS, the surgical linker is experimental and only used with --linker=surgical.)")
         .value_parser(["surgical", "legacy"])
         .required(false);
 
@@ -166,7 +170,7 @@ pub fn build_app() -> Command {
             .arg(flag_time.clone())
             .arg(flag_linker.clone())
             .arg(flag_prebuilt.clone())
-            .arg(flag_wasm_stack_size_kb)
+            .arg(flag_wasm_stack_size_kb.clone())
             .arg(
                 Arg::new(FLAG_TARGET)
                     .long(FLAG_TARGET)
@@ -250,6 +254,15 @@ pub fn build_app() -> Command {
             .arg(flag_time.clone())
             .arg(flag_linker.clone())
             .arg(flag_prebuilt.clone())
+            .arg(flag_wasm_stack_size_kb)
+            .arg(
+                Arg::new(FLAG_TARGET)
+                    .long(FLAG_TARGET)
+                    .help("Choose a different target\n(wasm32 apps are built with the dev backend and run in Roc's wasm interpreter.)")
+                    .default_value(Into::<&'static str>::into(Target::default()))
+                    .value_parser(build_target_values_parser.clone())
+                    .required(false),
+            )
             .arg(roc_file_to_run.clone())
             .arg(args_for_app.clone().last(true))
         )
@@ -289,6 +302,13 @@ pub fn build_app() -> Command {
             .about("Check the code for problems, but don’t build or run it")
             .arg(flag_time.clone())
             .arg(flag_max_threads.clone())
+            .arg(
+                Arg::new(FLAG_WARNINGS_AS_ERRORS)
+                    .long(FLAG_WARNINGS_AS_ERRORS)
+                    .help("Report warnings as errors")
+                    .action(ArgAction::SetTrue)
+                    .required(false),
+            )
             .arg(
                 Arg::new(ROC_FILE)
                     .help("The .roc file of an app to check")
@@ -354,6 +374,30 @@ pub fn build_app() -> Command {
                     .required(false),
             )
         )
+        .subcommand(Command::new(CMD_INSPECT_HOST)
+            .about("Print what the surgical linker knows about a preprocessed host (a .rh file).\nUse this to find out why surgical linking failed.")
+            .arg(
+                Arg::new(PREPROCESSED_HOST)
+                    .help("The preprocessed host, e.g. platform/x86_64-unknown-linux-gnu.rh")
+                    .value_parser(value_parser!(PathBuf))
+                    .required(true)
+            )
+            .arg(
+                Arg::new(FLAG_APP)
+                    .long(FLAG_APP)
+                    .help("An app using the platform. Check that it exposes every symbol the host expects.")
+                    .value_parser(value_parser!(PathBuf))
+                    .required(false)
+            )
+            .arg(
+                Arg::new(FLAG_TARGET)
+                    .long(FLAG_TARGET)
+                    .help("The target to load the app for")
+                    .default_value(Into::<&'static str>::into(Target::default()))
+                    .value_parser(build_target_values_parser)
+                    .required(false),
+            )
+        )
         .arg(flag_optimize)
         .arg(flag_max_threads)
         .arg(flag_opt_size)
@@ -395,7 +439,9 @@ pub fn test(_matches: &ArgMatches, _triple: Triple) -> io::Result<i32> {
 #[cfg(not(windows))]
 pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
     use roc_build::program::report_problems_monomorphized;
-    use roc_load::{ExecutionMode, FunctionKind, LoadConfig, LoadMonomorphizedError};
+    use roc_load::{
+        ExecutionMode, FunctionKind, LoadConfig, LoadMonomorphizedError, MonoOptimizations,
+    };
     use roc_packaging::cache;
     use roc_target::TargetInfo;
 
@@ -449,6 +495,8 @@ pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
         palette: roc_reporting::report::DEFAULT_PALETTE,
         threading,
         exec_mode: ExecutionMode::Test,
+        mono_optimizations: MonoOptimizations::NONE,
+        emit_debug_info: false,
     };
     let load_result = roc_load::load_and_monomorphize(
         arena,
@@ -661,14 +709,15 @@ pub fn build(
         opt_level_from_flags(matches)
     };
 
+    // `roc dev` on wasm32 always uses the dev backend, because that is what lets us
+    // run the result in the interpreter without going through zig to link it.
+    let dev_on_wasm = matches!(config, BuildConfig::BuildAndRunIfNoErrors)
+        && matches!(triple.architecture, Architecture::Wasm32);
+
     // Note: This allows using `--dev` with `--optimize`.
     // This means frontend optimizations and dev backend.
-    let code_gen_backend = if matches.get_flag(FLAG_DEV) {
-        if matches!(triple.architecture, Architecture::Wasm32) {
-            CodeGenBackend::Wasm
-        } else {
-            CodeGenBackend::Assembly(AssemblyBackendMode::Binary)
-        }
+    let code_gen_backend = if matches.get_flag(FLAG_DEV) || dev_on_wasm {
+        CodeGenBackend::dev(&triple, AssemblyBackendMode::Binary)
     } else {
         let backend_mode = match opt_level {
             OptLevel::Development => LlvmBackendMode::BinaryDev,
@@ -690,11 +739,15 @@ pub fn build(
 
     let wasm_dev_backend = matches!(code_gen_backend, CodeGenBackend::Wasm);
 
+    let linker = matches.get_one::<String>(FLAG_LINKER).map(|s| s.as_str());
     let linking_strategy = if wasm_dev_backend {
         LinkingStrategy::Additive
-    } else if !roc_linker::supported(link_type, &triple)
-        || matches.get_one::<String>(FLAG_LINKER).map(|s| s.as_str()) == Some("legacy")
-    {
+    } else if emit_debug_info {
+        // the surgical linker drops the .debug sections of the app object
+        LinkingStrategy::Legacy
+    } else if linker == Some("surgical") && roc_linker::supported_on_request(link_type, &triple) {
+        LinkingStrategy::Surgical
+    } else if !roc_linker::supported(link_type, &triple) || linker == Some("legacy") {
         LinkingStrategy::Legacy
     } else {
         LinkingStrategy::Surgical
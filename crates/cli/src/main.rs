@@ -4,9 +4,10 @@ use roc_build::link::LinkType;
 use roc_build::program::{check_file, CodeGenBackend};
 use roc_cli::{
     build_app, format_files, format_src, test, BuildConfig, FormatMode, CMD_BUILD, CMD_CHECK,
-    CMD_DEV, CMD_DOCS, CMD_FORMAT, CMD_GEN_STUB_LIB, CMD_GLUE, CMD_REPL, CMD_RUN, CMD_TEST,
-    CMD_VERSION, DIRECTORY_OR_FILES, FLAG_CHECK, FLAG_DEV, FLAG_LIB, FLAG_NO_LINK, FLAG_OUTPUT,
-    FLAG_STDIN, FLAG_STDOUT, FLAG_TARGET, FLAG_TIME, GLUE_DIR, GLUE_SPEC, ROC_FILE,
+    CMD_DEV, CMD_DOCS, CMD_FORMAT, CMD_GEN_STUB_LIB, CMD_GLUE, CMD_INSPECT_HOST, CMD_REPL, CMD_RUN,
+    CMD_TEST, CMD_VERSION, DIRECTORY_OR_FILES, FLAG_APP, FLAG_CHECK, FLAG_DEV, FLAG_LIB,
+    FLAG_NO_LINK, FLAG_OUTPUT, FLAG_STDIN, FLAG_STDOUT, FLAG_TARGET, FLAG_TIME,
+    FLAG_WARNINGS_AS_ERRORS, GLUE_DIR, GLUE_SPEC, PREPROCESSED_HOST, ROC_FILE,
 };
 use roc_docs::generate_docs_html;
 use roc_error_macros::user_error;
@@ -85,11 +86,16 @@ fn main() -> io::Result<()> {
         }
         Some((CMD_DEV, matches)) => {
             if matches.contains_id(ROC_FILE) {
+                let target = matches
+                    .get_one::<String>(FLAG_TARGET)
+                    .and_then(|s| Target::from_str(s).ok())
+                    .unwrap_or_default();
+
                 build(
                     matches,
                     &subcommands,
                     BuildConfig::BuildAndRunIfNoErrors,
-                    Triple::host(),
+                    target.to_triple(),
                     None,
                     RocCacheDir::Persistent(cache::roc_cache_dir().as_path()),
                     LinkType::Executable,
@@ -133,6 +139,21 @@ fn main() -> io::Result<()> {
                 function_kind,
             )
         }
+        Some((CMD_INSPECT_HOST, matches)) => {
+            let host_path = matches.get_one::<PathBuf>(PREPROCESSED_HOST).unwrap();
+            let app_path = matches.get_one::<PathBuf>(FLAG_APP);
+            let target = matches
+                .get_one::<String>(FLAG_TARGET)
+                .and_then(|s| Target::from_str(s).ok())
+                .unwrap_or_default();
+            roc_linker::inspect_host(
+                host_path,
+                app_path.map(PathBuf::as_path),
+                RocCacheDir::Persistent(cache::roc_cache_dir().as_path()),
+                &target.to_triple(),
+                FunctionKind::LambdaSet,
+            )
+        }
         Some((CMD_BUILD, matches)) => {
             let target = matches
                 .get_one::<String>(FLAG_TARGET)
@@ -162,6 +183,7 @@ fn main() -> io::Result<()> {
             let arena = Bump::new();
 
             let emit_timings = matches.get_flag(FLAG_TIME);
+            let warnings_as_errors = matches.get_flag(FLAG_WARNINGS_AS_ERRORS);
             let roc_file_path = matches.get_one::<PathBuf>(ROC_FILE).unwrap();
             let threading = match matches.get_one::<usize>(roc_cli::FLAG_MAX_THREADS) {
                 None => Threading::AllAvailable,
@@ -177,7 +199,11 @@ fn main() -> io::Result<()> {
                 RocCacheDir::Persistent(cache::roc_cache_dir().as_path()),
                 threading,
             ) {
-                Ok((problems, total_time)) => {
+                Ok((mut problems, total_time)) => {
+                    if warnings_as_errors {
+                        problems.promote_warnings();
+                    }
+
                     println!(
                         "\x1B[{}m{}\x1B[39m {} and \x1B[{}m{}\x1B[39m {} found in {} ms.",
                         if problems.errors == 0 {
@@ -144,6 +144,7 @@ pub fn spec_program<'a, 'r, I1, I2>(
     entry_point: roc_mono::ir::EntryPoint<'a>,
     procs: I1,
     hels: I2,
+    glue_getters: &[(Symbol, ProcLayout<'a>)],
 ) -> Result<morphic_lib::Solutions>
 where
     I1: Iterator<Item = &'r Proc<'a>>,
@@ -210,6 +211,14 @@ where
             }
         }
 
+        // the host reads values whose size only the app knows through these
+        for (symbol, proc_layout) in glue_getters {
+            let it = proc_layout.arguments.iter().copied();
+            let bytes = func_name_bytes_help(*symbol, it, Niche::NONE, proc_layout.result);
+
+            host_exposed_functions.push((bytes, proc_layout.arguments));
+        }
+
         // all other functions
         for proc in procs {
             let bytes = func_name_bytes(proc);
@@ -606,6 +615,7 @@ fn stmt_spec<'a>(
 
             builder.add_choice(block, &cases)
         }
+        Located { remainder, .. } => stmt_spec(builder, interner, env, block, layout, remainder),
         Dbg { remainder, .. } => stmt_spec(builder, interner, env, block, layout, remainder),
         Expect { remainder, .. } => stmt_spec(builder, interner, env, block, layout, remainder),
         ExpectFx { remainder, .. } => stmt_spec(builder, interner, env, block, layout, remainder),
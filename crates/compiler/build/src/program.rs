@@ -3,13 +3,13 @@ use crate::link::{
 };
 use bumpalo::Bump;
 use inkwell::memory_buffer::MemoryBuffer;
-use roc_error_macros::internal_error;
+use roc_error_macros::{internal_error, user_error};
 use roc_gen_dev::AssemblyBackendMode;
 use roc_gen_llvm::llvm::build::{module_from_builtins, LlvmBackendMode};
 use roc_gen_llvm::llvm::externs::add_default_roc_externs;
 use roc_load::{
     EntryPoint, ExecutionMode, ExpectMetadata, FunctionKind, LoadConfig, LoadMonomorphizedError,
-    LoadedModule, LoadingProblem, MonomorphizedModule, Threading,
+    LoadedModule, LoadingProblem, MonoOptimizations, MonomorphizedModule, Threading,
 };
 use roc_mono::ir::{OptLevel, SingleEntryPoint};
 use roc_packaging::cache::RocCacheDir;
@@ -25,7 +25,7 @@ use std::{
     thread::JoinHandle,
     time::{Duration, Instant},
 };
-use target_lexicon::Triple;
+use target_lexicon::{Architecture, Triple};
 
 #[cfg(feature = "target-wasm32")]
 use roc_collections::all::MutSet;
@@ -80,6 +80,17 @@ pub enum CodeGenBackend {
     Wasm,
 }
 
+impl CodeGenBackend {
+    /// The backend that compiles fastest for the given target: `roc_gen_wasm` for wasm32,
+    /// and `roc_gen_dev` for everything else.
+    pub fn dev(target: &Triple, mode: AssemblyBackendMode) -> Self {
+        match target.architecture {
+            Architecture::Wasm32 => CodeGenBackend::Wasm,
+            _ => CodeGenBackend::Assembly(mode),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct CodeGenOptions {
     pub backend: CodeGenBackend,
@@ -103,23 +114,22 @@ pub fn gen_from_mono_module<'a>(
     let debug = code_gen_options.emit_debug_info;
     let opt = code_gen_options.opt_level;
 
-    match code_gen_options.backend {
-        CodeGenBackend::Wasm => gen_from_mono_module_dev(
-            arena,
-            loaded,
-            target,
-            preprocessed_host_path,
-            wasm_dev_stack_bytes,
-            AssemblyBackendMode::Binary, // dummy value, unused in practice
-        ),
-        CodeGenBackend::Assembly(backend_mode) => gen_from_mono_module_dev(
+    let backend = match code_gen_options.backend {
+        // asking for assembly on wasm32 means asking for the wasm dev backend
+        CodeGenBackend::Assembly(backend_mode) => CodeGenBackend::dev(target, backend_mode),
+        backend => backend,
+    };
+
+    match backend {
+        CodeGenBackend::Wasm => gen_from_mono_module_dev_wasm32(
             arena,
             loaded,
-            target,
             preprocessed_host_path,
             wasm_dev_stack_bytes,
-            backend_mode,
         ),
+        CodeGenBackend::Assembly(backend_mode) => {
+            gen_from_mono_module_dev_assembly(arena, loaded, target, backend_mode, debug)
+        }
         CodeGenBackend::Llvm(backend_mode) => {
             gen_from_mono_module_llvm(arena, loaded, path, target, opt, backend_mode, debug)
         }
@@ -143,6 +153,7 @@ fn gen_from_mono_module_llvm<'a>(
     use inkwell::context::Context;
     use inkwell::module::Linkage;
     use inkwell::targets::{FileType, RelocMode};
+    use roc_gen_llvm::llvm::debug_info::DebugSources;
 
     let all_code_gen_start = Instant::now();
 
@@ -190,12 +201,24 @@ fn gen_from_mono_module_llvm<'a>(
     let (dibuilder, compile_unit) = roc_gen_llvm::llvm::build::Env::new_debug_info(module);
     let (mpm, _fpm) = roc_gen_llvm::llvm::build::construct_optimization_passes(module, opt_level);
 
+    let debug_sources = if emit_debug_info {
+        Some(DebugSources::new(
+            module,
+            &dibuilder,
+            &loaded.sources,
+            loaded.definition_regions,
+        ))
+    } else {
+        None
+    };
+
     // Compile and add all the Procs before adding main
     let env = roc_gen_llvm::llvm::build::Env {
         arena,
         builder: &builder,
         dibuilder: &dibuilder,
         compile_unit: &compile_unit,
+        debug_sources: debug_sources.as_ref(),
         context: &context,
         interns: loaded.interns,
         module,
@@ -245,8 +268,10 @@ fn gen_from_mono_module_llvm<'a>(
 
     env.dibuilder.finalize();
 
-    // we don't use the debug info, and it causes weird errors.
-    module.strip_debug_info();
+    if !emit_debug_info {
+        // we don't use the debug info, and it causes weird errors.
+        module.strip_debug_info();
+    }
 
     // Uncomment this to see the module's optimized LLVM instruction output:
     // env.module.print_to_stderr();
@@ -359,69 +384,15 @@ fn gen_from_mono_module_llvm<'a>(
 
         assert!(bc_to_object.status.success(), "{bc_to_object:#?}");
 
-        MemoryBuffer::create_from_file(&app_o_file).expect("memory buffer creation works")
-    } else if emit_debug_info {
-        module.strip_debug_info();
-
-        let mut app_ll_dbg_file = PathBuf::from(roc_file_path);
-        app_ll_dbg_file.set_extension("dbg.ll");
-
-        let mut app_o_file = PathBuf::from(roc_file_path);
-        app_o_file.set_extension("o");
-
-        use std::process::Command;
-
-        // write the ll code to a file, so we can modify it
-        module.print_to_file(&app_ll_file).unwrap();
-
-        // run the debugir https://github.com/vaivaswatha/debugir tool
-        match Command::new("debugir")
-            .args(["-instnamer", app_ll_file.to_str().unwrap()])
-            .output()
-        {
-            Ok(_) => {}
-            Err(error) => {
-                use std::io::ErrorKind;
-                match error.kind() {
-                    ErrorKind::NotFound => internal_error!(
-                        r"I could not find the `debugir` tool on the PATH, install it from https://github.com/vaivaswatha/debugir"
-                    ),
-                    _ => internal_error!("{:?}", error),
-                }
-            }
-        }
-
-        use target_lexicon::Architecture;
-        match target.architecture {
-            Architecture::X86_64
-            | Architecture::X86_32(_)
-            | Architecture::Aarch64(_)
-            | Architecture::Wasm32 => {
-                // write the .o file. Note that this builds the .o for the local machine,
-                // and ignores the `target_machine` entirely.
-                //
-                // different systems name this executable differently, so we shotgun for
-                // the most common ones and then give up.
-                let ll_to_object = Command::new("llc")
-                    .args([
-                        "-relocation-model=pic",
-                        "-filetype=obj",
-                        app_ll_dbg_file.to_str().unwrap(),
-                        "-o",
-                        app_o_file.to_str().unwrap(),
-                    ])
-                    .output()
-                    .unwrap();
-
-                assert!(ll_to_object.stderr.is_empty(), "{ll_to_object:#?}");
-            }
-            _ => unreachable!(),
-        }
-
         MemoryBuffer::create_from_file(&app_o_file).expect("memory buffer creation works")
     } else {
+        // With --debug, this .o file carries the source-level debug info that was built along
+        // with the IR (see `DebugSources`). We used to strip that and instead run the external
+        // `debugir` tool, which attributed the code to lines of the .ll file rather than of the
+        // .roc source; it is no longer needed, and `roc build --debug` no longer requires
+        // `debugir` and `llc` on the PATH.
+        //
         // Emit the .o file
-        use target_lexicon::Architecture;
         match target.architecture {
             Architecture::X86_64 | Architecture::X86_32(_) | Architecture::Aarch64(_) => {
                 let reloc = RelocMode::PIC;
@@ -462,48 +433,14 @@ fn gen_from_mono_module_llvm<'a>(
     )
 }
 
-#[cfg(feature = "target-wasm32")]
-fn gen_from_mono_module_dev<'a>(
-    arena: &'a bumpalo::Bump,
-    loaded: MonomorphizedModule<'a>,
-    target: &target_lexicon::Triple,
-    preprocessed_host_path: &Path,
-    wasm_dev_stack_bytes: Option<u32>,
-    backend_mode: AssemblyBackendMode,
-) -> GenFromMono<'a> {
-    use target_lexicon::Architecture;
-
-    match target.architecture {
-        Architecture::Wasm32 => gen_from_mono_module_dev_wasm32(
-            arena,
-            loaded,
-            preprocessed_host_path,
-            wasm_dev_stack_bytes,
-        ),
-        Architecture::X86_64 | Architecture::Aarch64(_) => {
-            gen_from_mono_module_dev_assembly(arena, loaded, target, backend_mode)
-        }
-        _ => todo!(),
-    }
-}
-
 #[cfg(not(feature = "target-wasm32"))]
-pub fn gen_from_mono_module_dev<'a>(
-    arena: &'a bumpalo::Bump,
-    loaded: MonomorphizedModule<'a>,
-    target: &target_lexicon::Triple,
-    _host_input_path: &Path,
+fn gen_from_mono_module_dev_wasm32<'a>(
+    _arena: &'a bumpalo::Bump,
+    _loaded: MonomorphizedModule<'a>,
+    _preprocessed_host_path: &Path,
     _wasm_dev_stack_bytes: Option<u32>,
-    backend_mode: AssemblyBackendMode,
 ) -> GenFromMono<'a> {
-    use target_lexicon::Architecture;
-
-    match target.architecture {
-        Architecture::X86_64 | Architecture::Aarch64(_) => {
-            gen_from_mono_module_dev_assembly(arena, loaded, target, backend_mode)
-        }
-        _ => todo!(),
-    }
+    internal_error!("this compiler was built without the `target-wasm32` feature")
 }
 
 #[cfg(feature = "target-wasm32")]
@@ -585,7 +522,15 @@ fn gen_from_mono_module_dev_assembly<'a>(
     loaded: MonomorphizedModule<'a>,
     target: &target_lexicon::Triple,
     backend_mode: AssemblyBackendMode,
+    emit_debug_info: bool,
 ) -> GenFromMono<'a> {
+    match target.architecture {
+        Architecture::X86_64 | Architecture::Aarch64(_) => {}
+        other => user_error!(
+            "The dev backend can't generate code for {other} yet; it supports x86_64 and aarch64.\n\nTo build for {other}, leave off the `--dev` flag to use the LLVM backend."
+        ),
+    }
+
     let all_code_gen_start = Instant::now();
 
     let lazy_literals = true;
@@ -596,6 +541,8 @@ fn gen_from_mono_module_dev_assembly<'a>(
         mut interns,
         exposed_to_host,
         mut layout_interner,
+        sources,
+        definition_regions,
         ..
     } = loaded;
 
@@ -605,6 +552,11 @@ fn gen_from_mono_module_dev_assembly<'a>(
         exposed_to_host: exposed_to_host.top_level_values.keys().copied().collect(),
         lazy_literals,
         mode: backend_mode,
+        debug_sources: if emit_debug_info {
+            Some(roc_gen_dev::DebugSources::new(&sources, definition_regions))
+        } else {
+            None
+        },
     };
 
     let module_object =
@@ -757,6 +709,8 @@ pub fn standard_load_config(
         palette: DEFAULT_PALETTE,
         threading,
         exec_mode,
+        mono_optimizations: MonoOptimizations::ALL,
+        emit_debug_info: false,
     }
 }
 
@@ -778,6 +732,10 @@ pub fn build_file<'a>(
     let compilation_start = Instant::now();
 
     // Step 1: compile the app and generate the .o file
+    let load_config = LoadConfig {
+        emit_debug_info: code_gen_options.emit_debug_info,
+        ..load_config
+    };
     let loaded =
         roc_load::load_and_monomorphize(arena, app_module_path.clone(), roc_cache_dir, load_config)
             .map_err(|e| BuildFileError::from_mono_error(e, compilation_start))?;
@@ -1255,6 +1213,8 @@ pub fn check_file<'a>(
         palette: DEFAULT_PALETTE,
         threading,
         exec_mode: ExecutionMode::Check,
+        mono_optimizations: MonoOptimizations::NONE,
+        emit_debug_info: false,
     };
     let mut loaded =
         roc_load::load_and_typecheck(arena, roc_file_path, roc_cache_dir, load_config)?;
@@ -32,10 +32,13 @@ interface Inspect
         custom,
         apply,
         toInspector,
+        inspectList,
+        inspectNat,
+        toStr,
     ]
     imports [
         Bool.{ Bool },
-        Num.{ U8, U16, U32, U64, U128, I8, I16, I32, I64, I128, F32, F64, Dec },
+        Num.{ Num, Nat, U8, U16, U32, U64, U128, I8, I16, I32, I64, I128, F32, F64, Dec },
         List,
         Str,
     ]
@@ -92,3 +95,215 @@ inspect : val -> f where val implements Inspect, f implements InspectFormatter
 inspect = \val ->
     (@Inspector valFn) = toInspector val
     valFn (init {})
+
+# Builtin implementations of `toInspector`, used by the auto-deriver for types whose structure is
+# not visible on the surface (lists) or that the formatter has no member for (Nat).
+
+inspectList : List elem -> Inspector f where elem implements Inspect, f implements InspectFormatter
+inspectList = \lst -> list lst List.walk toInspector
+
+inspectNat : Nat -> Inspector f where f implements InspectFormatter
+inspectNat = \n -> u64 (Num.toU64 n)
+
+## Renders any value that implements [Inspect] as a string that reads like the Roc expression
+## which would produce it. This is what `"\{value}"` string interpolation uses.
+## ```
+## expect Inspect.toStr { name: "Rocco", age: 2 } == "{ age: 2, name: \"Rocco\" }"
+## ```
+toStr : val -> Str where val implements Inspect
+toStr = \val ->
+    val
+    |> inspect
+    |> toDbgStr
+
+# The formatter behind [toStr]. Values are rendered as the Roc expressions that would produce
+# them, so tag payloads are only parenthesized when they are themselves nested in a tag payload.
+#
+# This is deliberately not exposed, and `dbg` and failed `expect`s do not go through it: they
+# are still rendered from the value's layout by `roc_repl_expect` (via `jit_to_ast`). Rendering
+# them through [Inspect] would require every value passed to `dbg` or captured by an `expect`
+# to implement [Inspect], which functions and opaque types without the ability do not.
+DbgFormatter := { data : Str, parenthesize : Bool }
+    implements [
+        InspectFormatter {
+            init: dbgInit,
+            list: dbgList,
+            set: dbgSet,
+            dict: dbgDict,
+            tag: dbgTag,
+            tuple: dbgTuple,
+            record: dbgRecord,
+            bool: dbgBool,
+            str: dbgStr,
+            opaque: dbgOpaque,
+            u8: dbgU8,
+            i8: dbgI8,
+            u16: dbgU16,
+            i16: dbgI16,
+            u32: dbgU32,
+            i32: dbgI32,
+            u64: dbgU64,
+            i64: dbgI64,
+            u128: dbgU128,
+            i128: dbgI128,
+            f32: dbgF32,
+            f64: dbgF64,
+            dec: dbgDec,
+        },
+    ]
+
+toDbgStr : DbgFormatter -> Str
+toDbgStr = \@DbgFormatter { data } -> data
+
+dbgInit : {} -> DbgFormatter
+dbgInit = \{} -> @DbgFormatter { data: "", parenthesize: Bool.false }
+
+dbgWrite : DbgFormatter, Str -> DbgFormatter
+dbgWrite = \@DbgFormatter { data, parenthesize }, added ->
+    @DbgFormatter { data: Str.concat data added, parenthesize }
+
+# Runs an inner inspector, telling it whether it sits in a tag payload.
+dbgNested : DbgFormatter, Inspector DbgFormatter, Bool -> DbgFormatter
+dbgNested = \@DbgFormatter { data, parenthesize }, inspector, inPayload ->
+    (@DbgFormatter inner) = apply inspector (@DbgFormatter { data, parenthesize: inPayload })
+
+    @DbgFormatter { data: inner.data, parenthesize }
+
+dbgElems : DbgFormatter, collection, ElemWalker (DbgFormatter, Bool) collection elem, (elem -> Inspector DbgFormatter) -> DbgFormatter
+dbgElems = \f0, content, walkFn, toDbgInspector ->
+    (f1, _) =
+        walkFn content (f0, Bool.false) \(f, prependSep), elem ->
+            f2 = if prependSep then dbgWrite f ", " else f
+
+            (dbgNested f2 (toDbgInspector elem) Bool.false, Bool.true)
+
+    f1
+
+dbgList : list, ElemWalker (DbgFormatter, Bool) list elem, (elem -> Inspector DbgFormatter) -> Inspector DbgFormatter
+dbgList = \content, walkFn, toDbgInspector ->
+    custom \f0 ->
+        dbgWrite f0 "["
+        |> dbgElems content walkFn toDbgInspector
+        |> dbgWrite "]"
+
+dbgSet : set, ElemWalker (DbgFormatter, Bool) set elem, (elem -> Inspector DbgFormatter) -> Inspector DbgFormatter
+dbgSet = \content, walkFn, toDbgInspector ->
+    custom \f0 ->
+        dbgWrite f0 "Set.fromList ["
+        |> dbgElems content walkFn toDbgInspector
+        |> dbgWrite "]"
+
+dbgDict : dict, KeyValWalker (DbgFormatter, Bool) dict key value, (key -> Inspector DbgFormatter), (value -> Inspector DbgFormatter) -> Inspector DbgFormatter
+dbgDict = \d, walkFn, keyToInspector, valueToInspector ->
+    custom \f0 ->
+        (f1, _) =
+            walkFn d (dbgWrite f0 "Dict.fromList [", Bool.false) \(f, prependSep), key, value ->
+                f2 = if prependSep then dbgWrite f ", " else f
+                f3 = dbgNested (dbgWrite f2 "(") (keyToInspector key) Bool.false
+                f4 = dbgNested (dbgWrite f3 ", ") (valueToInspector value) Bool.false
+
+                (dbgWrite f4 ")", Bool.true)
+
+        dbgWrite f1 "]"
+
+dbgTag : Str, List (Inspector DbgFormatter) -> Inspector DbgFormatter
+dbgTag = \name, fields ->
+    custom \f0 ->
+        if List.isEmpty fields then
+            dbgWrite f0 name
+        else
+            (@DbgFormatter { parenthesize }) = f0
+            f1 = if parenthesize then dbgWrite f0 "(" else f0
+            f2 =
+                List.walk fields (dbgWrite f1 name) \f, inspector ->
+                    dbgNested (dbgWrite f " ") inspector Bool.true
+
+            if parenthesize then dbgWrite f2 ")" else f2
+
+dbgTuple : List (Inspector DbgFormatter) -> Inspector DbgFormatter
+dbgTuple = \fields ->
+    custom \f0 ->
+        dbgWrite f0 "("
+        |> dbgElems fields List.walk (\inspector -> inspector)
+        |> dbgWrite ")"
+
+dbgRecord : List { key : Str, value : Inspector DbgFormatter } -> Inspector DbgFormatter
+dbgRecord = \fields ->
+    custom \f0 ->
+        if List.isEmpty fields then
+            dbgWrite f0 "{}"
+        else
+            (f1, _) =
+                List.walk fields (dbgWrite f0 "{ ", Bool.false) \(f, prependSep), { key, value } ->
+                    f2 = if prependSep then dbgWrite f ", " else f
+
+                    (dbgNested (dbgWrite f2 "\(key): ") value Bool.false, Bool.true)
+
+            dbgWrite f1 " }"
+
+dbgBool : Bool -> Inspector DbgFormatter
+dbgBool = \b ->
+    if b then
+        custom \f0 -> dbgWrite f0 "Bool.true"
+    else
+        custom \f0 -> dbgWrite f0 "Bool.false"
+
+dbgStr : Str -> Inspector DbgFormatter
+dbgStr = \s ->
+    escaped =
+        s
+        |> Str.replaceEach "\\" "\\\\"
+        |> Str.replaceEach "\"" "\\\""
+        |> Str.replaceEach "\n" "\\n"
+
+    custom \f0 ->
+        dbgWrite f0 "\""
+        |> dbgWrite escaped
+        |> dbgWrite "\""
+
+dbgOpaque : Str -> Inspector DbgFormatter
+dbgOpaque = \name ->
+    custom \f0 -> dbgWrite f0 "<\(name)>"
+
+dbgNum : Num * -> Inspector DbgFormatter
+dbgNum = \num ->
+    custom \f0 -> dbgWrite f0 (Num.toStr num)
+
+dbgU8 : U8 -> Inspector DbgFormatter
+dbgU8 = dbgNum
+
+dbgI8 : I8 -> Inspector DbgFormatter
+dbgI8 = dbgNum
+
+dbgU16 : U16 -> Inspector DbgFormatter
+dbgU16 = dbgNum
+
+dbgI16 : I16 -> Inspector DbgFormatter
+dbgI16 = dbgNum
+
+dbgU32 : U32 -> Inspector DbgFormatter
+dbgU32 = dbgNum
+
+dbgI32 : I32 -> Inspector DbgFormatter
+dbgI32 = dbgNum
+
+dbgU64 : U64 -> Inspector DbgFormatter
+dbgU64 = dbgNum
+
+dbgI64 : I64 -> Inspector DbgFormatter
+dbgI64 = dbgNum
+
+dbgU128 : U128 -> Inspector DbgFormatter
+dbgU128 = dbgNum
+
+dbgI128 : I128 -> Inspector DbgFormatter
+dbgI128 = dbgNum
+
+dbgF32 : F32 -> Inspector DbgFormatter
+dbgF32 = dbgNum
+
+dbgF64 : F64 -> Inspector DbgFormatter
+dbgF64 = dbgNum
+
+dbgDec : Dec -> Inspector DbgFormatter
+dbgDec = dbgNum
@@ -67,11 +67,14 @@ interface List
         walkBackwardsUntil,
         countIf,
         chunksOf,
+        sort,
+        compare,
     ]
     imports [
         Bool.{ Bool, Eq },
         Result.{ Result },
         Num.{ Nat, Num, Int },
+        Sort.{ Sort },
     ]
 
 ## ## Types
@@ -902,6 +905,43 @@ sortAsc = \list -> List.sortWith list Num.compare
 sortDesc : List (Num a) -> List (Num a)
 sortDesc = \list -> List.sortWith list (\a, b -> Num.compare b a)
 
+## Sorts a list in ascending order, using the element type's [Sort] implementation.
+## ```
+## expect List.sort [3, 1, 2] == [1, 2, 3]
+## expect List.sort ["b", "c", "a"] == ["a", "b", "c"]
+## ```
+## To sort with a custom comparison function, use [List.sortWith] instead.
+sort : List a -> List a where a implements Sort
+sort = \list -> List.sortWith list Sort.compare
+
+## Compares two lists lexicographically, using the element type's [Sort] implementation.
+##
+## Elements are compared pairwise from the start of both lists; the first pair
+## that is not `EQ` decides the result. If one list is a prefix of the other,
+## the shorter list is ordered first.
+## ```
+## expect List.compare [1, 2] [1, 3] == LT
+## expect List.compare [1, 2, 3] [1, 2] == GT
+## expect List.compare [] [] == EQ
+## ```
+compare : List a, List a -> [LT, EQ, GT] where a implements Sort
+compare = \left, right ->
+    compareHelp left right 0 (Num.min (List.len left) (List.len right))
+
+compareHelp : List a, List a, Nat, Nat -> [LT, EQ, GT] where a implements Sort
+compareHelp = \left, right, index, shortest ->
+    if index < shortest then
+        when Sort.compare (List.getUnsafe left index) (List.getUnsafe right index) is
+            EQ -> compareHelp left right (index + 1) shortest
+            unequal -> unequal
+    else
+        Num.compare (List.len left) (List.len right)
+
+expect List.sort [3, 1, 2] == [1, 2, 3]
+expect List.compare [1, 2] [1, 3] == LT
+expect List.compare [1, 2, 3] [1, 2] == GT
+expect List.compare [1, 2] [1, 2] == EQ
+
 swap : List a, Nat, Nat -> List a
 
 ## Returns the first element in the list, or `ListWasEmpty` if it was empty.
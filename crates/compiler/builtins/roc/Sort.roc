@@ -0,0 +1,48 @@
+interface Sort
+    exposes [
+        Sort,
+        compare,
+        compareBool,
+    ]
+    imports [
+        Bool.{ Bool, isEq },
+    ]
+
+## Defines a type whose values can be put in a total order.
+##
+## A total order means that any two values `a` and `b` of the type can be
+## compared, and that `compare a b` is `EQ` if and only if `a == b`.
+##
+## Not all types support a total order. For example, [`F32`](../Num#F32) and [`F64`](../Num#F64)
+## can be a `NaN` ([Not a Number](https://en.wikipedia.org/wiki/NaN)), which is
+## neither less than, equal to, nor greater than any other number.
+Sort implements
+    ## Compares two values, returning `LT` if the first is less than the
+    ## second, `EQ` if they are equal, and `GT` if the first is greater.
+    ##
+    ## **Note** that when `compare` is determined by the Roc compiler, values are
+    ## compared structurally. The rules for this are as follows:
+    ##
+    ## 1. Records are compared field by field, in alphabetical order of field names.
+    ## 2. Tuples are compared element by element, from first to last.
+    ## 3. The tags of an opaque type are ordered the way they are declared, so
+    ## `Size := [Small, Medium, Large] implements [Sort]` puts `Small` first.
+    ## Structural tag unions have no declaration order, because `[A, B]` and
+    ## `[B, A]` are the same type, so their tags are ordered by name instead.
+    ## Two values of the same tag are compared by their payloads, from first to last.
+    ## 4. [List] values are compared lexicographically, and so are [Str] values,
+    ## byte by byte in their UTF-8 encoding.
+    ## 5. Integers and [Dec] values are compared numerically.
+    ## 6. Functions and floating-point numbers cannot be put in a total order,
+    ## therefore Roc cannot derive `compare` for types that contain them.
+    compare : a, a -> [LT, EQ, GT] where a implements Sort
+
+## Compares two [Bool] values, ordering `Bool.false` before `Bool.true`.
+compareBool : Bool, Bool -> [LT, EQ, GT]
+compareBool = \a, b ->
+    if a == b then
+        EQ
+    else if a then
+        GT
+    else
+        LT
@@ -136,6 +136,7 @@ interface Str
         withPrefix,
         graphemes,
         contains,
+        compare,
     ]
     imports [
         Bool.{ Bool, Eq },
@@ -1008,3 +1009,15 @@ contains = \haystack, needle ->
     when firstMatch haystack needle is
         Some _index -> Bool.true
         None -> Bool.false
+
+## Compares two strings lexicographically, byte by byte in their UTF-8 encoding.
+##
+## This ordering does not take locale-specific collation rules into account,
+## but it is a total order, and it is the one used by [Sort.compare] on [Str].
+## ```
+## expect Str.compare "apple" "banana" == LT
+## expect Str.compare "apple" "app" == GT
+## expect Str.compare "Roc" "Roc" == EQ
+## ```
+compare : Str, Str -> [LT, EQ, GT]
+compare = \a, b -> List.compare (Str.toUtf8 a) (Str.toUtf8 b)
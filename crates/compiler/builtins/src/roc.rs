@@ -16,6 +16,7 @@ pub fn module_source(module_id: ModuleId) -> &'static str {
         ModuleId::DECODE => DECODE,
         ModuleId::HASH => HASH,
         ModuleId::INSPECT => INSPECT,
+        ModuleId::SORT => SORT,
         ModuleId::JSON => JSON,
         _ => internal_error!(
             "ModuleId {:?} is not part of the standard library",
@@ -36,4 +37,5 @@ const ENCODE: &str = include_str!("../roc/Encode.roc");
 const DECODE: &str = include_str!("../roc/Decode.roc");
 const HASH: &str = include_str!("../roc/Hash.roc");
 const INSPECT: &str = include_str!("../roc/Inspect.roc");
+const SORT: &str = include_str!("../roc/Sort.roc");
 const JSON: &str = include_str!("../roc/TotallyNotJson.roc");
@@ -78,6 +78,44 @@ impl AbilityMemberData<Resolved> {
     }
 }
 
+/// The default implementation of an ability member, which is used for opaque types that
+/// implement the ability without giving their own implementation of the member.
+///
+/// The default is a generalized top-level def in the ability's module. For an opaque type `O`
+/// that uses the default, we synthesize an implementation that fixes the default to `O`,
+/// through the arguments or return value of the member that have exactly the type `O`. For
+/// example, given
+///
+///   Eq implements
+///     isEq : a, a -> Bool where a implements Eq
+///     isNotEq : a, a -> Bool where a implements Eq
+///     isNotEq = \x, y -> !(isEq x y)
+///
+/// an opaque `O` implementing `Eq {isEq}` gets the implementation
+///
+///   \@O p0, @O p1 -> #isNotEq_default (@O p0) (@O p1)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct MemberDefault {
+    /// The def holding the default implementation.
+    pub symbol: Symbol,
+    /// Bit `i` is set if argument `i` of the member has exactly the implementing type.
+    pub implementor_args: u32,
+    /// The number of arguments of the member, or zero if the member is not a function.
+    pub arity: u8,
+    /// Whether the member returns exactly the implementing type.
+    pub returns_implementor: bool,
+}
+
+impl MemberDefault {
+    /// The maximum number of arguments a member with a default implementation may have.
+    pub const MAX_ARITY: usize = u32::BITS as usize;
+
+    pub fn arg_is_implementor(&self, index: usize) -> bool {
+        self.implementor_args & (1 << index) != 0
+    }
+}
+
 /// Solved lambda sets for an ability member specialization. For example, if we have
 ///
 ///   Default implements default : {} -[[] + a:default:1]-> a where a implements Default
@@ -139,6 +177,14 @@ pub enum ResolvedImpl {
 pub struct IAbilitiesStore<Phase: ResolvePhase> {
     /// Maps an ability to the members defining it.
     members_of_ability: MutMap<Symbol, Vec<Symbol>>,
+    /// Maps an ability to the abilities it requires its implementors to also implement. For
+    /// example, in
+    ///
+    ///   Ord implements Eq
+    ///     compare : a, a -> [LT, EQ, GT] where a implements Ord
+    ///
+    /// we keep the mapping Ord->[Eq]. Abilities without parents are not present.
+    parents_of_ability: MutMap<Symbol, Vec<Symbol>>,
     /// Map of symbols that specialize an ability member to the root ability symbol name,
     /// and the type the specialization claims to implement the ability for.
     ///
@@ -155,6 +201,9 @@ pub struct IAbilitiesStore<Phase: ResolvePhase> {
     /// Information about all members composing abilities.
     ability_members: MutMap<Symbol, AbilityMemberData<Phase>>,
 
+    /// Default implementations of ability members, for the members that have one.
+    member_defaults: MutMap<Symbol, MemberDefault>,
+
     /// Maps a tuple (member, type) specifying that `type` implements an ability
     /// member `member`, to how that implementation is defined.
     declared_implementations: MutMap<ImplKey, MemberImpl>,
@@ -173,8 +222,10 @@ impl<Phase: ResolvePhase> Default for IAbilitiesStore<Phase> {
     fn default() -> Self {
         Self {
             members_of_ability: Default::default(),
+            parents_of_ability: Default::default(),
             specialization_to_root: Default::default(),
             ability_members: Default::default(),
+            member_defaults: Default::default(),
             declared_implementations: Default::default(),
             specializations: Default::default(),
             next_specialization_id:
@@ -209,6 +260,59 @@ impl<Phase: ResolvePhase> IAbilitiesStore<Phase> {
         );
     }
 
+    /// Records the abilities that implementors of `ability` must also implement.
+    pub fn register_ability_parents(&mut self, ability: Symbol, parents: Vec<Symbol>) {
+        if parents.is_empty() {
+            return;
+        }
+        let old_parents = self.parents_of_ability.insert(ability, parents);
+        debug_assert!(old_parents.is_none(), "Replacing existing ability parents");
+    }
+
+    /// Returns the abilities directly required by `ability`.
+    pub fn parents_of_ability(&self, ability: Symbol) -> &[Symbol] {
+        self.parents_of_ability
+            .get(&ability)
+            .map(|parents| parents.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Returns all abilities transitively required by `ability`, not including `ability` itself
+    /// unless it (erroneously) requires itself.
+    pub fn ancestors_of_ability(&self, ability: Symbol) -> VecSet<Symbol> {
+        let mut ancestors = VecSet::default();
+        let mut stack = self.parents_of_ability(ability).to_vec();
+
+        while let Some(parent) = stack.pop() {
+            if ancestors.insert(parent) {
+                // Already visited
+                continue;
+            }
+            stack.extend(self.parents_of_ability(parent));
+        }
+
+        ancestors
+    }
+
+    /// Records the default implementation of an ability member.
+    pub fn register_member_default(&mut self, member: Symbol, default: MemberDefault) {
+        debug_assert!(self.ability_members.contains_key(&member));
+        let old_default = self.member_defaults.insert(member, default);
+        debug_assert!(old_default.is_none(), "Replacing existing member default");
+    }
+
+    /// Returns the default implementation of an ability member, if it has one.
+    pub fn member_default(&self, member: Symbol) -> Option<&MemberDefault> {
+        self.member_defaults.get(&member)
+    }
+
+    /// Returns whether `symbol` holds the default implementation of some ability member.
+    pub fn is_member_default(&self, symbol: Symbol) -> bool {
+        self.member_defaults
+            .values()
+            .any(|default| default.symbol == symbol)
+    }
+
     /// Checks if `name` is a root ability member symbol name.
     /// Note that this will return `false` for specializations of an ability member, which have
     /// different symbols from the root.
@@ -314,7 +418,9 @@ impl<Phase: ResolvePhase> IAbilitiesStore<Phase> {
     pub fn closure_from_imported(&self, symbols: &VecSet<Symbol>) -> PendingAbilitiesStore {
         let Self {
             members_of_ability,
+            parents_of_ability,
             ability_members,
+            member_defaults,
             declared_implementations,
             specializations,
 
@@ -340,6 +446,13 @@ impl<Phase: ResolvePhase> IAbilitiesStore<Phase> {
             }
         });
 
+        // Implementors of an ability must implement its parents too, so we need those as well.
+        let ancestors: Vec<_> = abilities_to_introduce
+            .iter()
+            .flat_map(|ability| self.ancestors_of_ability(*ability))
+            .collect();
+        abilities_to_introduce.extend(ancestors);
+
         // 2. Add each ability, and any specializations of its members we know about.
         for ability in abilities_to_introduce.into_iter() {
             let members = members_of_ability.get(&ability).unwrap();
@@ -364,6 +477,16 @@ impl<Phase: ResolvePhase> IAbilitiesStore<Phase> {
 
             new.register_ability(ability, imported_member_data);
 
+            if let Some(parents) = parents_of_ability.get(&ability) {
+                new.register_ability_parents(ability, parents.clone());
+            }
+
+            for member in members {
+                if let Some(default) = member_defaults.get(member) {
+                    new.register_member_default(*member, *default);
+                }
+            }
+
             // Add any specializations of the ability's members we know about.
             declared_implementations
                 .iter()
@@ -534,7 +657,9 @@ impl IAbilitiesStore<Pending> {
     pub fn union(&mut self, other: Self) {
         let Self {
             members_of_ability: other_members_of_ability,
+            parents_of_ability: other_parents_of_ability,
             ability_members: mut other_ability_members,
+            member_defaults: other_member_defaults,
             specialization_to_root,
             declared_implementations,
             next_specialization_id,
@@ -555,6 +680,16 @@ impl IAbilitiesStore<Pending> {
             self.register_ability(ability, member_data);
         }
 
+        for (ability, parents) in other_parents_of_ability.into_iter() {
+            let old_parents = self.parents_of_ability.insert(ability, parents.clone());
+            debug_assert!(old_parents.is_none() || old_parents.unwrap() == parents);
+        }
+
+        for (member, default) in other_member_defaults.into_iter() {
+            let old_default = self.member_defaults.insert(member, default);
+            debug_assert!(old_default.is_none() || old_default.unwrap() == default);
+        }
+
         for (specialization, member) in specialization_to_root.into_iter() {
             let old_root = self.specialization_to_root.insert(specialization, member);
             debug_assert!(old_root.is_none() || old_root.unwrap() == member);
@@ -594,7 +729,9 @@ impl IAbilitiesStore<Pending> {
     {
         let Self {
             members_of_ability,
+            parents_of_ability,
             ability_members,
+            member_defaults,
             specialization_to_root,
             declared_implementations,
             next_specialization_id,
@@ -672,7 +809,9 @@ impl IAbilitiesStore<Pending> {
 
         AbilitiesStore {
             members_of_ability,
+            parents_of_ability,
             ability_members,
+            member_defaults,
             specialization_to_root,
             declared_implementations,
             next_specialization_id,
@@ -693,8 +832,8 @@ mod serialize {
     };
 
     use super::{
-        AbilitiesStore, AbilityMemberData, ImplKey, MemberSpecializationInfo, Resolved,
-        ResolvedImpl, ResolvedImplementations, ResolvedMemberType, SpecializationId,
+        AbilitiesStore, AbilityMemberData, ImplKey, MemberDefault, MemberSpecializationInfo,
+        Resolved, ResolvedImpl, ResolvedImplementations, ResolvedMemberType, SpecializationId,
     };
 
     use std::io::{self, Write};
@@ -703,8 +842,10 @@ mod serialize {
     #[derive(Clone, Copy, Debug)]
     struct Header {
         members_of_ability: u64,
+        parents_of_ability: u64,
         specialization_to_root: u64,
         ability_members: u64,
+        member_defaults: u64,
         declared_implementations: u64,
         specializations: u64,
         next_specialization_id: u64,
@@ -715,8 +856,10 @@ mod serialize {
         fn from_store(store: &AbilitiesStore) -> Self {
             let AbilitiesStore {
                 members_of_ability,
+                parents_of_ability,
                 specialization_to_root,
                 ability_members,
+                member_defaults,
                 declared_implementations,
                 specializations,
                 next_specialization_id,
@@ -725,8 +868,10 @@ mod serialize {
 
             Self {
                 members_of_ability: members_of_ability.len() as _,
+                parents_of_ability: parents_of_ability.len() as _,
                 specialization_to_root: specialization_to_root.len() as _,
                 ability_members: ability_members.len() as _,
+                member_defaults: member_defaults.len() as _,
                 declared_implementations: declared_implementations.len() as _,
                 specializations: specializations.len() as _,
                 next_specialization_id: next_specialization_id.get() as _,
@@ -752,8 +897,10 @@ mod serialize {
 
         let AbilitiesStore {
             members_of_ability,
+            parents_of_ability,
             specialization_to_root,
             ability_members,
+            member_defaults,
             declared_implementations,
             specializations,
             next_specialization_id: _, // written in the header
@@ -761,8 +908,10 @@ mod serialize {
         } = store;
 
         let written = serialize_members_of_ability(members_of_ability, writer, written)?;
+        let written = serialize_members_of_ability(parents_of_ability, writer, written)?;
         let written = serialize_specializations_to_root(specialization_to_root, writer, written)?;
         let written = serialize_ability_members(ability_members, writer, written)?;
+        let written = serialize_member_defaults(member_defaults, writer, written)?;
         let written =
             serialize_declared_implementations(declared_implementations, writer, written)?;
         let written = serialize_specializations(specializations, writer, written)?;
@@ -781,10 +930,14 @@ mod serialize {
 
         let (members_of_ability, offset) =
             deserialize_members_of_ability(bytes, header.members_of_ability as _, offset);
+        let (parents_of_ability, offset) =
+            deserialize_members_of_ability(bytes, header.parents_of_ability as _, offset);
         let (specialization_to_root, offset) =
             deserialize_specialization_to_root(bytes, header.specialization_to_root as _, offset);
         let (ability_members, offset) =
             deserialize_ability_members(bytes, header.ability_members as _, offset);
+        let (member_defaults, offset) =
+            deserialize_member_defaults(bytes, header.member_defaults as _, offset);
         let (declared_implementations, offset) = deserialize_declared_implementations(
             bytes,
             header.declared_implementations as _,
@@ -801,8 +954,10 @@ mod serialize {
         (
             AbilitiesStore {
                 members_of_ability,
+                parents_of_ability,
                 specialization_to_root,
                 ability_members,
+                member_defaults,
                 declared_implementations,
                 specializations,
                 next_specialization_id: (header.next_specialization_id as u32).try_into().unwrap(),
@@ -950,6 +1105,34 @@ mod serialize {
         )
     }
 
+    fn serialize_member_defaults(
+        member_defaults: &MutMap<Symbol, MemberDefault>,
+        writer: &mut impl Write,
+        written: usize,
+    ) -> io::Result<usize> {
+        bytes::serialize_map(
+            member_defaults,
+            bytes::serialize_slice,
+            bytes::serialize_slice,
+            writer,
+            written,
+        )
+    }
+
+    fn deserialize_member_defaults(
+        bytes: &[u8],
+        length: usize,
+        offset: usize,
+    ) -> (MutMap<Symbol, MemberDefault>, usize) {
+        bytes::deserialize_map(
+            bytes,
+            bytes::deserialize_vec,
+            bytes::deserialize_vec,
+            length,
+            offset,
+        )
+    }
+
     #[derive(Clone, Copy)]
     #[repr(C)]
     enum SerMemberImpl {
@@ -1250,7 +1433,8 @@ mod test {
     use roc_types::{subs::Variable, types::MemberImpl};
 
     use super::{
-        AbilitiesStore, AbilityMemberData, ImplKey, MemberSpecializationInfo, ResolvedMemberType,
+        AbilitiesStore, AbilityMemberData, ImplKey, MemberDefault, MemberSpecializationInfo,
+        ResolvedMemberType,
     };
 
     #[test]
@@ -1290,6 +1474,17 @@ mod test {
                 )],
             );
 
+            store.register_ability_parents(Symbol::ARG_4, vec![Symbol::ARG_1]);
+            store.register_member_default(
+                Symbol::ARG_5,
+                MemberDefault {
+                    symbol: Symbol::ARG_6,
+                    implementor_args: 0b10,
+                    arity: 2,
+                    returns_implementor: false,
+                },
+            );
+
             store.register_declared_implementations(
                 Symbol::ATTR_ATTR,
                 [
@@ -1350,8 +1545,10 @@ mod test {
 
         let AbilitiesStore {
             members_of_ability,
+            parents_of_ability,
             specialization_to_root,
             ability_members,
+            member_defaults,
             declared_implementations,
             specializations,
             next_specialization_id,
@@ -1363,8 +1560,10 @@ mod test {
         assert_eq!(bytes.len(), offset);
 
         assert_eq!(members_of_ability, de_store.members_of_ability);
+        assert_eq!(parents_of_ability, de_store.parents_of_ability);
         assert_eq!(specialization_to_root, de_store.specialization_to_root);
         assert_eq!(ability_members, de_store.ability_members);
+        assert_eq!(member_defaults, de_store.member_defaults);
         assert_eq!(declared_implementations, de_store.declared_implementations);
         assert_eq!(specializations, de_store.specializations);
         assert_eq!(next_specialization_id, de_store.next_specialization_id);
@@ -513,7 +513,7 @@ pub fn find_type_def_symbols(
                     }
                 }
             }
-            Inferred | Wildcard | Malformed(_) => {}
+            Inferred | TypeHole(_) | Wildcard | Malformed(_) => {}
         }
     }
 
@@ -1026,6 +1026,16 @@ fn can_annotation_help(
 
             Type::Variable(var)
         }
+        TypeHole(name) => {
+            // A type hole is inferred just like `_`, but its type is reported after solving.
+            let var = var_store.fresh();
+
+            introduced_variables.insert_inferred(Loc::at(region, var));
+            env.annotation_type_holes
+                .push(((*name).into(), Loc::at(region, var)));
+
+            Type::Variable(var)
+        }
         Where(_annotation, clauses) => {
             debug_assert!(!clauses.is_empty());
 
@@ -1104,6 +1114,15 @@ fn canonicalize_has_clause(
         }
     }
 
+    // A variable implementing an ability also implements everything the ability requires.
+    let ancestors: Vec<_> = can_abilities
+        .sorted_iter()
+        .flat_map(|&ability| scope.abilities_store.ancestors_of_ability(ability))
+        .collect();
+    for ancestor in ancestors {
+        can_abilities.insert(ancestor);
+    }
+
     if let Some(shadowing) = introduced_variables.named_var_by_name(&var_name) {
         let var_name_ident = var_name.to_string().into();
         let shadow = Loc::at(region, var_name_ident);
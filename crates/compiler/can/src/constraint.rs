@@ -6,7 +6,7 @@ use crate::abilities::SpecializationId;
 use crate::exhaustive::{ExhaustiveContext, SketchedRows};
 use crate::expected::{Expected, PExpected};
 use roc_collections::soa::{EitherIndex, Index, Slice};
-use roc_module::ident::TagName;
+use roc_module::ident::{Lowercase, TagName};
 use roc_module::symbol::{ModuleId, Symbol};
 use roc_region::all::{Loc, Region};
 use roc_types::subs::{ExhaustiveMark, IllegalCycleMark, Variable};
@@ -28,6 +28,7 @@ pub struct Constraints {
     pub eq: Vec<Eq>,
     pub pattern_eq: Vec<PatternEq>,
     pub cycles: Vec<Cycle>,
+    pub type_holes: Vec<TypeHole>,
 }
 
 impl std::fmt::Debug for Constraints {
@@ -49,6 +50,7 @@ impl std::fmt::Debug for Constraints {
             .field("eq", &self.eq)
             .field("pattern_eq", &self.pattern_eq)
             .field("cycles", &self.cycles)
+            .field("type_holes", &self.type_holes)
             .finish()
     }
 }
@@ -80,6 +82,7 @@ impl Constraints {
         let eq = Vec::new();
         let pattern_eq = Vec::new();
         let cycles = Vec::new();
+        let type_holes = Vec::new();
 
         categories.extend([
             Category::Record,
@@ -128,6 +131,7 @@ impl Constraints {
             eq,
             pattern_eq,
             cycles,
+            type_holes,
         }
     }
 
@@ -602,6 +606,7 @@ impl Constraints {
             | Constraint::Exhaustive { .. }
             | Constraint::Resolve(..)
             | Constraint::IngestedFile(..)
+            | Constraint::TypeHole(..)
             | Constraint::CheckCycle(..) => false,
         }
     }
@@ -685,6 +690,23 @@ impl Constraints {
     ) -> Constraint {
         Constraint::IngestedFile(type_index, file_path, bytes)
     }
+
+    pub fn type_hole(
+        &mut self,
+        type_index: TypeOrVar,
+        name: Option<Lowercase>,
+        region: Region,
+        suggest_values: bool,
+    ) -> Constraint {
+        let hole = TypeHole {
+            name,
+            region,
+            suggest_values,
+        };
+        let hole_index = Index::push_new(&mut self.type_holes, hole);
+
+        Constraint::TypeHole(type_index, hole_index)
+    }
 }
 
 roc_error_macros::assert_sizeof_default!(Constraint, 3 * 8);
@@ -787,6 +809,8 @@ pub enum Constraint {
     CheckCycle(Index<Cycle>, IllegalCycleMark),
 
     IngestedFile(TypeOrVar, Box<PathBuf>, Arc<Vec<u8>>),
+    /// Report the type at a type hole once solving is done.
+    TypeHole(TypeOrVar, Index<TypeHole>),
 }
 
 #[derive(Debug, Clone, Copy, Default)]
@@ -822,6 +846,15 @@ pub struct IncludesTag {
     pub region: Region,
 }
 
+/// A `_` or `?name` written where an expression or type was expected.
+#[derive(Debug, Clone)]
+pub struct TypeHole {
+    pub name: Option<Lowercase>,
+    pub region: Region,
+    /// Whether to look for in-scope values that fit the hole; only meaningful for expressions.
+    pub suggest_values: bool,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct Cycle {
     pub def_names: Slice<(Symbol, Region)>,
@@ -865,6 +898,9 @@ impl std::fmt::Debug for Constraint {
             Self::IngestedFile(arg0, arg1, arg2) => {
                 write!(f, "IngestedFile({arg0:?}, {arg1:?}, {arg2:?})")
             }
+            Self::TypeHole(arg0, arg1) => {
+                write!(f, "TypeHole({arg0:?}, {arg1:?})")
+            }
         }
     }
 }
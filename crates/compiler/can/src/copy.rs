@@ -681,7 +681,7 @@ fn deep_copy_expr_help<C: CopyEnv>(env: &mut C, copied: &mut Vec<Variable>, expr
             symbol: *symbol,
         },
 
-        TypedHole(v) => TypedHole(sub!(*v)),
+        TypedHole(v, name) => TypedHole(sub!(*v), name.clone()),
 
         RuntimeError(err) => RuntimeError(err.clone()),
     }
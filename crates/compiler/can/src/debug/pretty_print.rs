@@ -427,7 +427,7 @@ fn expr<'a>(c: &Ctx, p: EPrec, f: &'a Arena<'a>, e: &'a Expr) -> DocBuilder<'a,
         Dbg { .. } => todo!(),
         Expect { .. } => todo!(),
         ExpectFx { .. } => todo!(),
-        TypedHole(_) => todo!(),
+        TypedHole(..) => todo!(),
         RuntimeError(_) => todo!(),
     }
 }
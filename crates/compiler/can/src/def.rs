@@ -1,5 +1,6 @@
 use crate::abilities::AbilityMemberData;
 use crate::abilities::ImplKey;
+use crate::abilities::MemberDefault;
 use crate::abilities::MemberVariables;
 use crate::abilities::PendingMemberType;
 use crate::annotation::canonicalize_annotation;
@@ -174,6 +175,8 @@ impl PendingValueDef<'_> {
 struct PendingAbilityMember<'a> {
     name: Loc<Symbol>,
     typ: Loc<ast::TypeAnnotation<'a>>,
+    /// The def holding the default implementation of the member, if it has one.
+    default: Option<Symbol>,
 }
 
 #[derive(Debug, Clone)]
@@ -196,7 +199,10 @@ enum PendingTypeDef<'a> {
 
     Ability {
         name: Loc<Symbol>,
+        parents: &'a [ast::AbilityName<'a>],
         members: Vec<PendingAbilityMember<'a>>,
+        /// Default implementations of members, to be canonicalized as regular value defs.
+        defaults: Vec<Loc<PendingValueDef<'a>>>,
     },
 
     /// An invalid alias, that is ignored in the rest of the pipeline
@@ -671,9 +677,65 @@ fn separate_implemented_and_required_members(
 
 type DerivedDef<'a> = Loc<PendingValue<'a>>;
 
+fn resolve_ability_parents(
+    env: &mut Env,
+    output: &mut Output,
+    scope: &mut Scope,
+    pending_abilities_in_scope: &PendingAbilitiesInScope,
+    parents: &[ast::AbilityName],
+) -> Vec<Symbol> {
+    let mut resolved = Vec::with_capacity(parents.len());
+
+    for parent in parents {
+        let region = parent.region;
+
+        let symbol = match parent.value.extract_spaces().item {
+            ast::TypeAnnotation::Apply(module_name, ident, []) => {
+                match make_apply_symbol(env, region, scope, module_name, ident) {
+                    Ok(symbol) => symbol,
+                    Err(_) => {
+                        // This is bad apply; an error will have been reported for it already.
+                        continue;
+                    }
+                }
+            }
+            _ => {
+                env.problem(Problem::NotAnAbility(region));
+                continue;
+            }
+        };
+
+        if !pending_abilities_in_scope.contains_key(&symbol)
+            && !scope.abilities_store.is_ability(symbol)
+        {
+            env.problem(Problem::NotAnAbility(region));
+            continue;
+        }
+
+        output.references.insert_type_lookup(symbol);
+
+        if !resolved.contains(&symbol) {
+            resolved.push(symbol);
+        }
+    }
+
+    resolved
+}
+
+/// Members an opaque type does not implement itself, which must be filled in by the defaults of
+/// the ability. Those are only known once all abilities in scope are resolved.
+struct PendingDefaultImpls<'a> {
+    opaque: Symbol,
+    opaque_name: &'a str,
+    ability: Symbol,
+    region: Region,
+    members: Vec<Symbol>,
+}
+
 struct CanonicalizedOpaque<'a> {
     opaque_def: Alias,
     derived_defs: Vec<DerivedDef<'a>>,
+    default_impls: Vec<PendingDefaultImpls<'a>>,
 }
 
 #[inline(always)]
@@ -704,10 +766,12 @@ fn canonicalize_opaque<'a>(
     )?;
 
     let mut derived_defs = Vec::new();
+    let mut default_impls = Vec::new();
     if let Some(has_abilities) = has_abilities {
         let has_abilities = has_abilities.value.collection();
 
         let mut derived_abilities = vec![];
+        let mut implemented_abilities: VecMap<Symbol, Region> = VecMap::default();
 
         for has_ability in has_abilities.items {
             let region = has_ability.region;
@@ -751,6 +815,18 @@ fn canonicalize_opaque<'a>(
                 }
             };
 
+            // Did the user already list this ability? (e.g. implements [Eq, Eq {isEq}])
+            // If so, keep the first implementation and drop this one.
+            if let Some(&original) = implemented_abilities.get(&ability) {
+                env.problem(Problem::DuplicateAbilityImplementation {
+                    ability,
+                    original,
+                    duplicate: region,
+                });
+                continue;
+            }
+            implemented_abilities.insert(ability, region);
+
             if let Some(impls) = opt_impls {
                 let mut impl_map: VecMap<Symbol, Loc<MemberImpl>> = VecMap::default();
 
@@ -824,16 +900,14 @@ fn canonicalize_opaque<'a>(
                 }
 
                 if !not_implemented.is_empty() {
-                    // We'll generate runtime errors for the members that are needed but
-                    // unspecified.
-                    for sym in not_implemented.iter() {
-                        impl_map.insert(*sym, Loc::at_zero(MemberImpl::Error));
-                    }
-
-                    env.problem(Problem::DoesNotImplementAbility {
-                        region,
+                    // Members that are needed but unspecified may have default implementations,
+                    // which we can only tell once the ability is resolved.
+                    default_impls.push(PendingDefaultImpls {
+                        opaque: name.value,
+                        opaque_name: name_str,
                         ability,
-                        not_implemented,
+                        region,
+                        members: not_implemented,
                     });
                 }
 
@@ -852,7 +926,7 @@ fn canonicalize_opaque<'a>(
                 let mut impls = Vec::with_capacity(num_members);
                 for &member in members.iter() {
                     let (derived_impl, impl_pat, impl_body) =
-                        derive::synthesize_member_impl(env, scope, name_str, member);
+                        derive::synthesize_member_impl(env, scope, name_str, &ann.value, member);
 
                     let derived_def = Loc::at(
                         derive::DERIVED_REGION,
@@ -916,9 +990,67 @@ fn canonicalize_opaque<'a>(
     Ok(CanonicalizedOpaque {
         opaque_def: alias,
         derived_defs,
+        default_impls,
     })
 }
 
+/// Implements the members opaque types left unspecified with the members' defaults, if they have
+/// any. Must be called after all abilities in scope have been resolved.
+fn resolve_default_impls<'a>(
+    env: &mut Env<'a>,
+    scope: &mut Scope,
+    default_impls: Vec<PendingDefaultImpls<'a>>,
+) -> Vec<DerivedDef<'a>> {
+    let mut derived_defs = Vec::new();
+
+    for PendingDefaultImpls {
+        opaque,
+        opaque_name,
+        ability,
+        region,
+        members,
+    } in default_impls
+    {
+        let mut impls = Vec::with_capacity(members.len());
+        let mut not_implemented = Vec::new();
+
+        for member in members {
+            match scope.abilities_store.member_default(member).copied() {
+                Some(default) => {
+                    let (impl_symbol, impl_pat, impl_body) =
+                        derive::synthesize_default_impl(env, scope, opaque_name, default);
+
+                    derived_defs.push(Loc::at(
+                        derive::DERIVED_REGION,
+                        PendingValue::Def(PendingValueDef::Body(impl_pat, impl_body)),
+                    ));
+                    impls.push((member, MemberImpl::Impl(impl_symbol)));
+                }
+                None => {
+                    // We'll generate runtime errors for the members that are needed but
+                    // unspecified.
+                    impls.push((member, MemberImpl::Error));
+                    not_implemented.push(member);
+                }
+            }
+        }
+
+        if !not_implemented.is_empty() {
+            env.problem(Problem::DoesNotImplementAbility {
+                region,
+                ability,
+                not_implemented,
+            });
+        }
+
+        scope
+            .abilities_store
+            .register_declared_implementations(opaque, impls);
+    }
+
+    derived_defs
+}
+
 #[inline(always)]
 pub(crate) fn canonicalize_defs<'a>(
     env: &mut Env<'a>,
@@ -957,17 +1089,62 @@ pub(crate) fn canonicalize_defs<'a>(
     for (_, either_index) in loc_defs.tags.iter().enumerate() {
         if let Ok(type_index) = either_index.split() {
             let type_def = &loc_defs.type_defs[type_index.index()];
-            let pending_type_def = to_pending_type_def(env, type_def, scope, pattern_type);
-            if let PendingTypeDef::Ability { name, members } = &pending_type_def {
+            let mut pending_type_def = to_pending_type_def(env, type_def, scope, pattern_type);
+            if let PendingTypeDef::Ability {
+                name,
+                members,
+                defaults,
+                ..
+            } = &mut pending_type_def
+            {
                 pending_abilities_in_scope.insert(
                     name.value,
                     members.iter().map(|mem| mem.name.value).collect(),
                 );
+
+                // Default implementations are canonicalized like any other value def.
+                pending_value_defs.extend(
+                    defaults
+                        .drain(..)
+                        .map(|default| default.map_owned(PendingValue::Def)),
+                );
             }
             pending_type_defs.push(pending_type_def);
         }
     }
 
+    // Abilities may require abilities defined later on, so resolve the parents only once we know
+    // about every ability in scope.
+    for pending_type_def in pending_type_defs.iter() {
+        if let PendingTypeDef::Ability { name, parents, .. } = pending_type_def {
+            let parents = resolve_ability_parents(
+                env,
+                &mut output,
+                scope,
+                &pending_abilities_in_scope,
+                parents,
+            );
+            scope
+                .abilities_store
+                .register_ability_parents(name.value, parents);
+        }
+    }
+
+    for pending_type_def in pending_type_defs.iter() {
+        if let PendingTypeDef::Ability { name, .. } = pending_type_def {
+            if scope
+                .abilities_store
+                .ancestors_of_ability(name.value)
+                .contains(&name.value)
+            {
+                env.problem(Problem::AbilityRequiresItself {
+                    ability: name.value,
+                    region: name.region,
+                });
+            }
+        }
+    }
+
     for (index, either_index) in loc_defs.tags.iter().enumerate() {
         if let Err(value_index) = either_index.split() {
             let value_def = &loc_defs.value_defs[value_index.index()];
@@ -1241,7 +1418,7 @@ fn canonicalize_type_defs<'a>(
                     TypeDef::Opaque(name_str, name, vars, ann, derived),
                 );
             }
-            PendingTypeDef::Ability { name, members } => {
+            PendingTypeDef::Ability { name, members, .. } => {
                 let mut referenced_symbols = Vec::with_capacity(2);
 
                 for member in members.iter() {
@@ -1266,6 +1443,7 @@ fn canonicalize_type_defs<'a>(
     let mut aliases = VecMap::default();
     let mut abilities = MutMap::default();
     let mut all_derived_defs = Vec::new();
+    let mut all_default_impls = Vec::new();
 
     for type_name in sorted {
         match type_defs.remove(&type_name).unwrap() {
@@ -1304,10 +1482,12 @@ fn canonicalize_type_defs<'a>(
                 if let Ok(CanonicalizedOpaque {
                     opaque_def,
                     derived_defs,
+                    default_impls,
                 }) = alias_and_derives
                 {
                     aliases.insert(name.value, opaque_def);
                     all_derived_defs.extend(derived_defs);
+                    all_default_impls.extend(default_impls);
                 }
             }
 
@@ -1344,6 +1524,10 @@ fn canonicalize_type_defs<'a>(
         pending_abilities_in_scope,
     );
 
+    // With the abilities resolved, we know what defaults are available to the opaques that need
+    // them.
+    all_derived_defs.extend(resolve_default_impls(env, scope, all_default_impls));
+
     CanonicalizedTypeDefs {
         aliases,
         symbols_introduced,
@@ -1363,6 +1547,7 @@ fn resolve_abilities(
 ) {
     for (ability, members) in abilities {
         let mut can_members = Vec::with_capacity(members.len());
+        let mut defaults = Vec::new();
 
         for PendingAbilityMember {
             name:
@@ -1371,6 +1556,7 @@ fn resolve_abilities(
                     region: member_name_region,
                 },
             typ,
+            default,
         } in members
         {
             let member_annot = canonicalize_annotation(
@@ -1448,6 +1634,21 @@ fn resolve_abilities(
                 flex_vars: iv.collect_flex(),
             };
 
+            if let Some(default_sym) = default {
+                match member_default(default_sym, &member_annot.typ, var_bound_to_ability) {
+                    Some(member_default) => defaults.push((member_sym, member_default)),
+                    None => {
+                        env.problem(Problem::AbilityMemberDefaultNotSpecializable {
+                            member: member_sym,
+                            ability,
+                            region: member_name_region,
+                        });
+                        // The default is still type-checked, but don't also report it as unused.
+                        output.references.insert_value_lookup(default_sym);
+                    }
+                }
+            }
+
             let signature = {
                 let mut signature = member_annot.typ;
                 signature
@@ -1471,6 +1672,57 @@ fn resolve_abilities(
 
         // Store what symbols a type must define implementations for to have this ability.
         scope.abilities_store.register_ability(ability, can_members);
+
+        for (member, member_default) in defaults {
+            scope
+                .abilities_store
+                .register_member_default(member, member_default);
+        }
+    }
+}
+
+/// Determines how the default implementation of a member can be specialized to an implementing
+/// type, given the member's signature. Returns `None` if the signature never mentions the
+/// implementing type on its own, in which case there is no way to pin the default to that type.
+fn member_default(
+    default_sym: Symbol,
+    signature: &Type,
+    var_bound_to_ability: Variable,
+) -> Option<MemberDefault> {
+    let is_implementor =
+        |typ: &Type| matches!(typ, Type::Variable(v) if *v == var_bound_to_ability);
+
+    let member_default = match signature {
+        Type::Function(args, _, ret) => {
+            if args.len() > MemberDefault::MAX_ARITY {
+                return None;
+            }
+
+            let implementor_args = args
+                .iter()
+                .enumerate()
+                .filter(|(_, arg)| is_implementor(arg))
+                .fold(0, |mask, (i, _)| mask | (1 << i));
+
+            MemberDefault {
+                symbol: default_sym,
+                implementor_args,
+                arity: args.len() as u8,
+                returns_implementor: is_implementor(ret),
+            }
+        }
+        other => MemberDefault {
+            symbol: default_sym,
+            implementor_args: 0,
+            arity: 0,
+            returns_implementor: is_implementor(other),
+        },
+    };
+
+    if member_default.implementor_args == 0 && !member_default.returns_implementor {
+        None
+    } else {
+        Some(member_default)
     }
 }
 
@@ -2677,6 +2929,7 @@ fn to_pending_type_def<'a>(
         Ability {
             header: TypeHeader { name, vars },
             members,
+            parents,
             loc_implements: _,
         } => {
             let name = match scope
@@ -2708,6 +2961,7 @@ fn to_pending_type_def<'a>(
             }
 
             let mut named_members = Vec::with_capacity(members.len());
+            let mut defaults = Vec::new();
 
             for member in *members {
                 let name_region = member.name.region;
@@ -2726,9 +2980,33 @@ fn to_pending_type_def<'a>(
                     }
                 };
 
+                let opt_default = member.default.map(|default| {
+                    // The default is an ordinary def annotated with the member's signature; it is
+                    // never referenced by name in source, so it gets a name users cannot write.
+                    let default_sym = scope
+                        .introduce_str(&format!("#{member_name}_default"), name_region)
+                        .expect("this name is not unique");
+
+                    let ann_pattern = env
+                        .arena
+                        .alloc(Loc::at(name_region, ast::Pattern::Identifier(member_name)));
+                    defaults.push(Loc::at(
+                        Region::span_across(&name_region, &default.body.region),
+                        PendingValueDef::TypedBody(
+                            ann_pattern,
+                            Loc::at(name_region, Pattern::Identifier(default_sym)),
+                            &member.typ,
+                            default.body,
+                        ),
+                    ));
+
+                    default_sym
+                });
+
                 named_members.push(PendingAbilityMember {
                     name: Loc::at(name_region, member_sym),
                     typ: member.typ,
+                    default: opt_default,
                 });
 
                 if pattern_type == PatternType::TopLevelDef {
@@ -2738,7 +3016,9 @@ fn to_pending_type_def<'a>(
 
             PendingTypeDef::Ability {
                 name,
+                parents,
                 members: named_members,
+                defaults,
             }
         }
     }
@@ -6,12 +6,13 @@
 //!   - Derived impls for opaques are typically very small, effectively deferring the
 //!     implementation to the value they wrap.
 
+use bumpalo::Bump;
 use roc_error_macros::internal_error;
 use roc_module::{called_via::CalledVia, symbol::Symbol};
 use roc_parse::ast;
 use roc_region::all::{Loc, Region};
 
-use crate::{env::Env, pattern::Pattern, scope::Scope};
+use crate::{abilities::MemberDefault, env::Env, pattern::Pattern, scope::Scope};
 
 fn to_encoder<'a>(env: &mut Env<'a>, at_opaque: &'a str) -> ast::Expr<'a> {
     let alloc_pat = |it| env.arena.alloc(Loc::at(DERIVED_REGION, it));
@@ -49,6 +50,42 @@ fn to_encoder<'a>(env: &mut Env<'a>, at_opaque: &'a str) -> ast::Expr<'a> {
     )
 }
 
+fn to_inspector<'a>(env: &mut Env<'a>, at_opaque: &'a str) -> ast::Expr<'a> {
+    let alloc_pat = |it| env.arena.alloc(Loc::at(DERIVED_REGION, it));
+    let alloc_expr = |it| env.arena.alloc(Loc::at(DERIVED_REGION, it));
+
+    let payload = "#payload";
+
+    // \@Opaq payload
+    let opaque_ref = alloc_pat(ast::Pattern::OpaqueRef(at_opaque));
+    let opaque_apply_pattern = ast::Pattern::Apply(
+        opaque_ref,
+        &*env
+            .arena
+            .alloc([Loc::at(DERIVED_REGION, ast::Pattern::Identifier(payload))]),
+    );
+
+    // Inspect.toInspector payload
+    let call_member = alloc_expr(ast::Expr::Apply(
+        alloc_expr(ast::Expr::Var {
+            module_name: "Inspect",
+            ident: "toInspector",
+        }),
+        &*env.arena.alloc([&*alloc_expr(ast::Expr::Var {
+            module_name: "",
+            ident: payload,
+        })]),
+        roc_module::called_via::CalledVia::Space,
+    ));
+
+    // \@Opaq payload -> Inspect.toInspector payload
+    ast::Expr::Closure(
+        env.arena
+            .alloc([Loc::at(DERIVED_REGION, opaque_apply_pattern)]),
+        call_member,
+    )
+}
+
 fn decoder<'a>(env: &mut Env<'a>, at_opaque: &'a str) -> ast::Expr<'a> {
     let alloc_expr = |it| env.arena.alloc(Loc::at(DERIVED_REGION, it));
 
@@ -213,12 +250,188 @@ fn is_eq<'a>(env: &mut Env<'a>, at_opaque: &'a str) -> ast::Expr<'a> {
     )
 }
 
+fn compare<'a>(
+    env: &mut Env<'a>,
+    at_opaque: &'a str,
+    ann: &ast::TypeAnnotation<'a>,
+) -> ast::Expr<'a> {
+    let alloc_pat = |it| env.arena.alloc(Loc::at(DERIVED_REGION, it));
+    let alloc_expr = |it| env.arena.alloc(Loc::at(DERIVED_REGION, it));
+
+    let payload1 = "#payload1";
+    let payload2 = "#payload2";
+
+    let opaque_ref = alloc_pat(ast::Pattern::OpaqueRef(at_opaque));
+    // \@Opaq payload1
+    let opaque1 = ast::Pattern::Apply(
+        opaque_ref,
+        &*env
+            .arena
+            .alloc([Loc::at(DERIVED_REGION, ast::Pattern::Identifier(payload1))]),
+    );
+    // \@Opaq payload2
+    let opaque2 = ast::Pattern::Apply(
+        opaque_ref,
+        &*env
+            .arena
+            .alloc([Loc::at(DERIVED_REGION, ast::Pattern::Identifier(payload2))]),
+    );
+
+    // Sort.compare payload1 payload2
+    let call_member = alloc_expr(ast::Expr::Apply(
+        alloc_expr(ast::Expr::Var {
+            module_name: "Sort",
+            ident: "compare",
+        }),
+        &*env.arena.alloc([
+            &*alloc_expr(ast::Expr::Var {
+                module_name: "",
+                ident: payload1,
+            }),
+            &*alloc_expr(ast::Expr::Var {
+                module_name: "",
+                ident: payload2,
+            }),
+        ]),
+        roc_module::called_via::CalledVia::Space,
+    ));
+
+    // An opaque type is nominal, so unlike a structural tag union it has a declaration order
+    // for its tags, and that is the order we compare them in. Only payloads of the same tag are
+    // left to the structural comparison.
+    let body = match declared_tags(ann) {
+        Some(tags) if tags.len() > 1 => {
+            let ordering = "#ordering";
+
+            // Num.compare (tag rank of payload1) (tag rank of payload2)
+            let compare_ranks = alloc_expr(ast::Expr::Apply(
+                alloc_expr(ast::Expr::Var {
+                    module_name: "Num",
+                    ident: "compare",
+                }),
+                &*env.arena.alloc([
+                    &*alloc_expr(tag_rank(env.arena, payload1, &tags)),
+                    &*alloc_expr(tag_rank(env.arena, payload2, &tags)),
+                ]),
+                roc_module::called_via::CalledVia::Space,
+            ));
+
+            // when Num.compare (rank payload1) (rank payload2) is
+            //     EQ -> Sort.compare payload1 payload2
+            //     ordering -> ordering
+            let same_tag = &*env.arena.alloc(ast::WhenBranch {
+                patterns: env
+                    .arena
+                    .alloc([Loc::at(DERIVED_REGION, ast::Pattern::Tag("EQ"))]),
+                value: *call_member,
+                guard: None,
+            });
+            let different_tags = &*env.arena.alloc(ast::WhenBranch {
+                patterns: env
+                    .arena
+                    .alloc([Loc::at(DERIVED_REGION, ast::Pattern::Identifier(ordering))]),
+                value: Loc::at(
+                    DERIVED_REGION,
+                    ast::Expr::Var {
+                        module_name: "",
+                        ident: ordering,
+                    },
+                ),
+                guard: None,
+            });
+
+            alloc_expr(ast::Expr::When(
+                compare_ranks,
+                env.arena.alloc([same_tag, different_tags]),
+            ))
+        }
+        _ => call_member,
+    };
+
+    // \@Opaq payload1, @Opaq payload2 -> <body>
+    ast::Expr::Closure(
+        env.arena.alloc([
+            Loc::at(DERIVED_REGION, opaque1),
+            Loc::at(DERIVED_REGION, opaque2),
+        ]),
+        body,
+    )
+}
+
+/// The names and payload arities of a closed tag union annotation's tags, in declaration order.
+fn declared_tags<'a>(mut ann: &ast::TypeAnnotation<'a>) -> Option<Vec<(&'a str, usize)>> {
+    let tags = loop {
+        match ann {
+            ast::TypeAnnotation::SpaceBefore(nested, _)
+            | ast::TypeAnnotation::SpaceAfter(nested, _) => ann = *nested,
+            ast::TypeAnnotation::TagUnion { ext: None, tags } => break tags,
+            _ => return None,
+        }
+    };
+
+    tags.iter()
+        .map(|loc_tag| {
+            let mut tag = &loc_tag.value;
+            loop {
+                match tag {
+                    ast::Tag::Apply { name, args } => return Some((name.value, args.len())),
+                    ast::Tag::SpaceBefore(nested, _) | ast::Tag::SpaceAfter(nested, _) => {
+                        tag = *nested
+                    }
+                    ast::Tag::Malformed(_) => return None,
+                }
+            }
+        })
+        .collect()
+}
+
+/// when payload is
+///     Tag0 .. -> 0
+///     Tag1 .. -> 1
+///     ...
+fn tag_rank<'a>(arena: &'a Bump, payload: &'a str, tags: &[(&'a str, usize)]) -> ast::Expr<'a> {
+    let branches =
+        arena.alloc_slice_fill_iter(tags.iter().enumerate().map(|(rank, &(name, arity))| {
+            let tag_pattern = if arity == 0 {
+                ast::Pattern::Tag(name)
+            } else {
+                ast::Pattern::Apply(
+                    arena.alloc(Loc::at(DERIVED_REGION, ast::Pattern::Tag(name))),
+                    arena.alloc_slice_fill_iter(
+                        (0..arity).map(|_| Loc::at(DERIVED_REGION, ast::Pattern::Underscore(""))),
+                    ),
+                )
+            };
+
+            &*arena.alloc(ast::WhenBranch {
+                patterns: arena.alloc([Loc::at(DERIVED_REGION, tag_pattern)]),
+                value: Loc::at(
+                    DERIVED_REGION,
+                    ast::Expr::Num(arena.alloc_str(&rank.to_string())),
+                ),
+                guard: None,
+            })
+        }));
+
+    ast::Expr::When(
+        arena.alloc(Loc::at(
+            DERIVED_REGION,
+            ast::Expr::Var {
+                module_name: "",
+                ident: payload,
+            },
+        )),
+        branches,
+    )
+}
+
 pub const DERIVED_REGION: Region = Region::zero();
 
 pub(crate) fn synthesize_member_impl<'a>(
     env: &mut Env<'a>,
     scope: &mut Scope,
     opaque_name: &'a str,
+    ann: &ast::TypeAnnotation<'a>,
     ability_member: Symbol,
 ) -> (Symbol, Loc<Pattern>, &'a Loc<ast::Expr<'a>>) {
     // @Opaq
@@ -232,6 +445,14 @@ pub(crate) fn synthesize_member_impl<'a>(
         Symbol::DECODE_DECODER => (format!("#{opaque_name}_decoder"), decoder(env, at_opaque)),
         Symbol::HASH_HASH => (format!("#{opaque_name}_hash"), hash(env, at_opaque)),
         Symbol::BOOL_IS_EQ => (format!("#{opaque_name}_isEq"), is_eq(env, at_opaque)),
+        Symbol::SORT_COMPARE => (
+            format!("#{opaque_name}_compare"),
+            compare(env, at_opaque, ann),
+        ),
+        Symbol::INSPECT_TO_INSPECTOR => (
+            format!("#{opaque_name}_toInspector"),
+            to_inspector(env, at_opaque),
+        ),
         other => internal_error!("{:?} is not a derivable ability member!", other),
     };
 
@@ -247,3 +468,128 @@ pub(crate) fn synthesize_member_impl<'a>(
         env.arena.alloc(Loc::at(DERIVED_REGION, def_body)),
     )
 }
+
+/// Synthesizes an implementation of an ability member for an opaque type that falls back to the
+/// member's default implementation; see [MemberDefault].
+pub(crate) fn synthesize_default_impl<'a>(
+    env: &mut Env<'a>,
+    scope: &mut Scope,
+    opaque_name: &'a str,
+    default: MemberDefault,
+) -> (Symbol, Loc<Pattern>, &'a Loc<ast::Expr<'a>>) {
+    let alloc_pat = |it| env.arena.alloc(Loc::at(DERIVED_REGION, it));
+    let alloc_expr = |it| env.arena.alloc(Loc::at(DERIVED_REGION, it));
+
+    // @Opaq
+    let at_opaque = env.arena.alloc_str(&format!("@{opaque_name}"));
+
+    let impl_symbol = scope.gen_unique_symbol();
+
+    // The default may live in another module, and need not be exposed from it, so we can't refer
+    // to it by name. Bring it into scope under a name of our own instead.
+    let default_name: &str = env.arena.alloc_str(&format!(
+        "#{opaque_name}_default{}",
+        impl_symbol.ident_id().index()
+    ));
+    scope
+        .import(default_name.into(), default.symbol, DERIVED_REGION)
+        .expect("this name is not unique");
+    let default_var = alloc_expr(ast::Expr::Var {
+        module_name: "",
+        ident: default_name,
+    });
+
+    let opaque_ref = alloc_pat(ast::Pattern::OpaqueRef(at_opaque));
+
+    let mut arg_patterns = Vec::with_capacity(default.arity as usize);
+    let mut call_args = Vec::with_capacity(default.arity as usize);
+    for i in 0..default.arity as usize {
+        let arg = env.arena.alloc_str(&format!("#arg{i}"));
+        let arg_pat = ast::Pattern::Identifier(arg);
+        let arg_var = ast::Expr::Var {
+            module_name: "",
+            ident: arg,
+        };
+
+        if default.arg_is_implementor(i) {
+            // \@Opaq arg -> ... (@Opaq arg)
+            arg_patterns.push(Loc::at(
+                DERIVED_REGION,
+                ast::Pattern::Apply(
+                    opaque_ref,
+                    &*env.arena.alloc([Loc::at(DERIVED_REGION, arg_pat)]),
+                ),
+            ));
+            call_args.push(&*alloc_expr(ast::Expr::Apply(
+                alloc_expr(ast::Expr::OpaqueRef(at_opaque)),
+                &*env.arena.alloc([&*alloc_expr(arg_var)]),
+                CalledVia::Space,
+            )));
+        } else {
+            arg_patterns.push(Loc::at(DERIVED_REGION, arg_pat));
+            call_args.push(&*alloc_expr(arg_var));
+        }
+    }
+
+    // default arg0 (@Opaq arg1) ...
+    let mut body = if default.arity == 0 {
+        *default_var
+    } else {
+        Loc::at(
+            DERIVED_REGION,
+            ast::Expr::Apply(
+                default_var,
+                env.arena.alloc_slice_copy(&call_args),
+                CalledVia::Space,
+            ),
+        )
+    };
+
+    if default.implementor_args == 0 {
+        // Nothing pins the default to the opaque type but the return value, so unwrap and rewrap
+        // it to make sure the default is specialized to the opaque type.
+        //
+        //   when default arg0 ... is @Opaq result -> @Opaq result
+        let result = "#result";
+        let result_pat = ast::Pattern::Apply(
+            opaque_ref,
+            &*env
+                .arena
+                .alloc([Loc::at(DERIVED_REGION, ast::Pattern::Identifier(result))]),
+        );
+        let rewrap = ast::Expr::Apply(
+            alloc_expr(ast::Expr::OpaqueRef(at_opaque)),
+            &*env.arena.alloc([&*alloc_expr(ast::Expr::Var {
+                module_name: "",
+                ident: result,
+            })]),
+            CalledVia::Space,
+        );
+        let branch = env.arena.alloc(ast::WhenBranch {
+            patterns: env.arena.alloc([Loc::at(DERIVED_REGION, result_pat)]),
+            value: Loc::at(DERIVED_REGION, rewrap),
+            guard: None,
+        });
+
+        body = Loc::at(
+            DERIVED_REGION,
+            ast::Expr::When(env.arena.alloc(body), env.arena.alloc([&*branch])),
+        );
+    }
+
+    let def_body = if default.arity == 0 {
+        body.value
+    } else {
+        // \arg0, @Opaq arg1, ... -> body
+        ast::Expr::Closure(
+            env.arena.alloc_slice_copy(&arg_patterns),
+            env.arena.alloc(body),
+        )
+    };
+
+    (
+        impl_symbol,
+        Loc::at(DERIVED_REGION, Pattern::Identifier(impl_symbol)),
+        env.arena.alloc(Loc::at(DERIVED_REGION, def_body)),
+    )
+}
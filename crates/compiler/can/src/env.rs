@@ -6,6 +6,7 @@ use roc_module::ident::{Ident, Lowercase, ModuleName};
 use roc_module::symbol::{IdentIdsByModule, ModuleId, ModuleIds, Symbol};
 use roc_problem::can::{Problem, RuntimeError};
 use roc_region::all::{Loc, Region};
+use roc_types::subs::Variable;
 
 /// The canonicalization environment for a particular module.
 pub struct Env<'a> {
@@ -34,6 +35,10 @@ pub struct Env<'a> {
 
     pub top_level_symbols: VecSet<Symbol>,
 
+    /// Named type holes (e.g. `?elem`) found in type annotations, whose types are reported after
+    /// solving.
+    pub annotation_type_holes: Vec<(Lowercase, Loc<Variable>)>,
+
     pub arena: &'a Bump,
 }
 
@@ -55,6 +60,7 @@ impl<'a> Env<'a> {
             qualified_type_lookups: VecSet::default(),
             tailcallable_symbol: None,
             top_level_symbols: VecSet::default(),
+            annotation_type_holes: Vec::new(),
         }
     }
 
@@ -275,8 +275,8 @@ pub enum Expr {
         symbol: Symbol,
     },
 
-    /// Rendered as empty box in editor
-    TypedHole(Variable),
+    /// A type hole, written `_` or `?name`; its inferred type is reported by the type checker
+    TypedHole(Variable, Option<Lowercase>),
 
     /// Compiles, but will crash if reached
     RuntimeError(RuntimeError),
@@ -345,7 +345,7 @@ impl Expr {
             Self::Dbg { .. } => Category::Expect,
 
             // these nodes place no constraints on the expression's type
-            Self::TypedHole(_) | Self::RuntimeError(..) => Category::Unknown,
+            Self::TypedHole(..) | Self::RuntimeError(..) => Category::Unknown,
         }
     }
 }
@@ -1017,19 +1017,19 @@ pub fn canonicalize_expr<'a>(
         ast::Expr::Var { module_name, ident } => {
             canonicalize_var_lookup(env, var_store, scope, module_name, ident, region)
         }
+        ast::Expr::Underscore("") => {
+            // A lone underscore is a type hole; the type checker reports its type.
+            (TypedHole(var_store.fresh(), None), Output::default())
+        }
         ast::Expr::Underscore(name) => {
-            // we parse underscores, but they are not valid expression syntax
+            // we parse underscored identifiers, but they are not valid expression syntax
 
             let problem = roc_problem::can::RuntimeError::MalformedIdentifier(
                 (*name).into(),
-                if name.is_empty() {
-                    roc_parse::ident::BadIdent::UnderscoreAlone(region.start())
-                } else {
-                    roc_parse::ident::BadIdent::UnderscoreAtStart {
-                        position: region.start(),
-                        // Check if there's an ignored identifier with this name in scope (for better error messages)
-                        declaration_region: scope.lookup_ignored_local(name),
-                    }
+                roc_parse::ident::BadIdent::UnderscoreAtStart {
+                    position: region.start(),
+                    // Check if there's an ignored identifier with this name in scope (for better error messages)
+                    declaration_region: scope.lookup_ignored_local(name),
                 },
                 region,
             );
@@ -1038,6 +1038,10 @@ pub fn canonicalize_expr<'a>(
 
             (RuntimeError(problem), Output::default())
         }
+        ast::Expr::TypeHole(name) => (
+            TypedHole(var_store.fresh(), Some((*name).into())),
+            Output::default(),
+        ),
         ast::Expr::Crash => {
             // Naked crashes aren't allowed; we'll admit this with our own message, but yield an
             // error.
@@ -2389,6 +2393,7 @@ pub fn is_valid_interpolation(expr: &ast::Expr<'_>) -> bool {
         | ast::Expr::AccessorFunction(_)
         | ast::Expr::Crash
         | ast::Expr::Underscore(_)
+        | ast::Expr::TypeHole(_)
         | ast::Expr::MalformedIdent(_, _)
         | ast::Expr::Tag(_)
         | ast::Expr::OpaqueRef(_)
@@ -2411,7 +2416,7 @@ pub fn is_valid_interpolation(expr: &ast::Expr<'_>) -> bool {
                 | ast::StrSegment::Plaintext(_) => true,
                 // Disallow nested interpolation. Alternatively, we could allow it but require
                 // a comment above it apologizing to the next person who has to read the code.
-                ast::StrSegment::Interpolated(_) => false,
+                ast::StrSegment::Interpolated(_) | ast::StrSegment::Inspected(_) => false,
             })
         }
         ast::Expr::Record(fields) => fields.iter().all(|loc_field| match loc_field.value {
@@ -2569,6 +2574,9 @@ fn flatten_str_lines<'a>(
                         );
                     }
                 }
+                Inspected(_) => {
+                    internal_error!("Inspected string segments should have been desugared by now")
+                }
                 EscapedChar(escaped) => buf.push(escaped.unescape()),
             }
         }
@@ -3196,7 +3204,7 @@ pub(crate) fn get_lookup_symbols(expr: &Expr) -> Vec<ExpectLookup> {
             | Expr::RecordAccessor(_)
             | Expr::SingleQuote(..)
             | Expr::EmptyRecord
-            | Expr::TypedHole(_)
+            | Expr::TypedHole(..)
             | Expr::RuntimeError(_)
             | Expr::OpaqueWrapFunction(_) => {}
         }
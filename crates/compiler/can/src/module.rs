@@ -8,6 +8,7 @@ use crate::expr::{
 };
 use crate::pattern::{BindingsFromPattern, Pattern};
 use crate::scope::Scope;
+use crate::traverse::{walk_decl, walk_decls, walk_def, walk_expr, DeclarationInfo, Visitor};
 use bumpalo::Bump;
 use roc_collections::{MutMap, SendMap, VecMap, VecSet};
 use roc_error_macros::internal_error;
@@ -158,6 +159,7 @@ pub struct ModuleOutput {
     pub scope: Scope,
     pub loc_expects: VecMap<Region, Vec<ExpectLookup>>,
     pub loc_dbgs: VecMap<Symbol, DbgLookup>,
+    pub annotation_type_holes: Vec<(Lowercase, Loc<Variable>)>,
 }
 
 fn validate_generate_with<'a>(
@@ -386,6 +388,7 @@ pub fn canonicalize_module_defs<'a>(
         if !output.references.has_type_or_value_lookup(symbol)
             && !exposed_symbols.contains(&symbol)
             && !scope.abilities_store.is_specialization_name(symbol)
+            && !scope.abilities_store.is_member_default(symbol)
             && !symbol.is_exposed_for_builtin_derivers()
         {
             env.problem(Problem::UnusedDef(symbol, region));
@@ -792,6 +795,10 @@ pub fn canonicalize_module_defs<'a>(
         }
     }
 
+    if !home.is_builtin() {
+        report_unused_record_fields(&mut env, &declarations, &exposed_symbols);
+    }
+
     let collected = declarations.expects();
 
     ModuleOutput {
@@ -808,6 +815,105 @@ pub fn canonicalize_module_defs<'a>(
         loc_expects: collected.expects,
         loc_dbgs: collected.dbgs,
         exposed_symbols,
+        annotation_type_holes: env.annotation_type_holes,
+    }
+}
+
+/// Records bound to a name, and how the module uses them.
+#[derive(Default)]
+struct RecordFieldUses {
+    /// Records bound directly to a name, with the region of each of their fields.
+    records: VecMap<Symbol, Vec<(Lowercase, Region)>>,
+    /// Fields read through `record.field`.
+    accessed: VecSet<(Symbol, Lowercase)>,
+    /// Records used other than by accessing a field, e.g. by being passed to a function.
+    escaped: VecSet<Symbol>,
+}
+
+impl RecordFieldUses {
+    fn add_record(&mut self, symbol: Symbol, expr: &Expr) {
+        if let Expr::Record { fields, .. } = expr {
+            let mut fields: Vec<_> = fields
+                .iter()
+                .map(|(label, field)| (label.clone(), field.region))
+                .collect();
+            fields.sort_by_key(|(_, region)| region.start());
+
+            self.records.insert(symbol, fields);
+        }
+    }
+}
+
+impl Visitor for RecordFieldUses {
+    fn visit_decl(&mut self, decl: DeclarationInfo<'_>) {
+        if let DeclarationInfo::Value {
+            loc_symbol,
+            loc_expr,
+            ..
+        } = &decl
+        {
+            self.add_record(loc_symbol.value, &loc_expr.value);
+        }
+
+        walk_decl(self, decl);
+    }
+
+    fn visit_def(&mut self, def: &Def) {
+        if let Pattern::Identifier(symbol) = def.loc_pattern.value {
+            self.add_record(symbol, &def.loc_expr.value);
+        }
+
+        walk_def(self, def);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr, _region: Region, var: Variable) {
+        match expr {
+            Expr::RecordAccess {
+                loc_expr, field, ..
+            } => {
+                if let Expr::Var(symbol, _) = loc_expr.value {
+                    self.accessed.insert((symbol, field.clone()));
+                    return;
+                }
+            }
+            Expr::Var(symbol, _) | Expr::RecordUpdate { symbol, .. } => {
+                self.escaped.insert(*symbol);
+            }
+            _ => {}
+        }
+
+        walk_expr(self, expr, var);
+    }
+}
+
+/// Reports fields of records that are bound to a name, only ever have their fields accessed, and
+/// yet never have these particular fields read. Records that are exposed, or that escape in any
+/// other way, may have their fields read elsewhere, so they are left alone.
+fn report_unused_record_fields(
+    env: &mut Env,
+    declarations: &Declarations,
+    exposed_symbols: &VecSet<Symbol>,
+) {
+    let mut uses = RecordFieldUses::default();
+    walk_decls(&mut uses, declarations);
+
+    for (record, fields) in uses.records.iter() {
+        let never_accessed = !uses.accessed.iter().any(|(symbol, _)| symbol == record);
+
+        // An unused record is already reported as an unused def.
+        if never_accessed || uses.escaped.contains(record) || exposed_symbols.contains(record) {
+            continue;
+        }
+
+        for (field, region) in fields {
+            if !uses.accessed.contains(&(*record, field.clone())) {
+                env.problem(Problem::UnusedRecordField {
+                    record: *record,
+                    field: field.clone(),
+                    region: *region,
+                });
+            }
+        }
     }
 }
 
@@ -8,7 +8,8 @@ use roc_module::called_via::{BinOp, CalledVia};
 use roc_module::ident::ModuleName;
 use roc_parse::ast::Expr::{self, *};
 use roc_parse::ast::{
-    AssignedField, Collection, RecordBuilderField, StrLiteral, StrSegment, ValueDef, WhenBranch,
+    AbilityMember, AbilityMemberDefault, AssignedField, Collection, RecordBuilderField, StrLiteral,
+    StrSegment, TypeDef, ValueDef, WhenBranch,
 };
 use roc_region::all::{Loc, Region};
 
@@ -118,10 +119,43 @@ fn desugar_value_def<'a>(arena: &'a Bump, def: &'a ValueDef<'a>) -> ValueDef<'a>
     }
 }
 
+fn desugar_type_def<'a>(arena: &'a Bump, def: &TypeDef<'a>) -> TypeDef<'a> {
+    match *def {
+        TypeDef::Ability {
+            header,
+            loc_implements,
+            parents,
+            members,
+        } => {
+            // Default implementations of ability members become ordinary defs in
+            // canonicalization, so they need to be desugared like any other def body.
+            let members = arena.alloc_slice_fill_iter(members.iter().map(|member| AbilityMember {
+                default: member.default.map(|default| AbilityMemberDefault {
+                    spaces_before: default.spaces_before,
+                    body: desugar_expr(arena, default.body),
+                }),
+                ..*member
+            }));
+
+            TypeDef::Ability {
+                header,
+                loc_implements,
+                parents,
+                members,
+            }
+        }
+        alias_or_opaque => alias_or_opaque,
+    }
+}
+
 pub fn desugar_defs<'a>(arena: &'a Bump, defs: &mut roc_parse::ast::Defs<'a>) {
     for value_def in defs.value_defs.iter_mut() {
         *value_def = desugar_value_def(arena, arena.alloc(*value_def));
     }
+
+    for type_def in defs.type_defs.iter_mut() {
+        *type_def = desugar_type_def(arena, type_def);
+    }
 }
 
 /// Reorder the expression tree based on operator precedence and associativity rules,
@@ -135,6 +169,7 @@ pub fn desugar_expr<'a>(arena: &'a Bump, loc_expr: &'a Loc<Expr<'a>>) -> &'a Loc
         | AccessorFunction(_)
         | Var { .. }
         | Underscore { .. }
+        | TypeHole(_)
         | MalformedIdent(_, _)
         | MalformedClosure
         | PrecedenceConflict { .. }
@@ -489,6 +524,34 @@ fn desugar_str_segments<'a>(
                     value: arena.alloc(loc_desugared.value),
                 })
             }
+            StrSegment::Inspected(loc_expr) => {
+                let loc_desugared = desugar_expr(
+                    arena,
+                    arena.alloc(Loc {
+                        region: loc_expr.region,
+                        value: *loc_expr.value,
+                    }),
+                );
+
+                // "\{x}" is sugar for "\(Inspect.toStr x)"
+                let inspect_to_str = arena.alloc(Loc {
+                    region: loc_expr.region,
+                    value: Var {
+                        module_name: ModuleName::INSPECT,
+                        ident: "toStr",
+                    },
+                });
+                let apply = Apply(
+                    inspect_to_str,
+                    arena.alloc([loc_desugared]),
+                    CalledVia::StringInterpolation,
+                );
+
+                StrSegment::Interpolated(Loc {
+                    region: loc_expr.region,
+                    value: arena.alloc(apply),
+                })
+            }
         }),
         arena,
     )
@@ -1,5 +1,6 @@
 //! Traversals over the can ast.
 
+use roc_collections::MutMap;
 use roc_module::{ident::Lowercase, symbol::Symbol};
 use roc_region::all::{Loc, Position, Region};
 use roc_types::{subs::Variable, types::MemberImpl};
@@ -164,7 +165,7 @@ pub fn walk_decls<V: Visitor>(visitor: &mut V, decls: &Declarations) {
     }
 }
 
-fn walk_decl<V: Visitor>(visitor: &mut V, decl: DeclarationInfo<'_>) {
+pub fn walk_decl<V: Visitor>(visitor: &mut V, decl: DeclarationInfo<'_>) {
     use DeclarationInfo::*;
 
     match decl {
@@ -398,7 +399,7 @@ pub fn walk_expr<V: Visitor>(visitor: &mut V, expr: &Expr, var: Variable) {
                 Variable::NULL,
             );
         }
-        Expr::TypedHole(_) => { /* terminal */ }
+        Expr::TypedHole(..) => { /* terminal */ }
         Expr::RuntimeError(..) => { /* terminal */ }
     }
 }
@@ -961,3 +962,47 @@ pub fn find_declaration(symbol: Symbol, decls: &'_ Declarations) -> Option<Found
         }
     }
 }
+
+/// Finds where the named values in `decls` are defined: the symbols bound by patterns, and the
+/// names of closures.
+pub fn find_definition_regions(decls: &Declarations) -> MutMap<Symbol, Region> {
+    let mut visitor = DefinitionRegions {
+        regions: MutMap::default(),
+    };
+    visitor.visit_decls(decls);
+    return visitor.regions;
+
+    struct DefinitionRegions {
+        regions: MutMap<Symbol, Region>,
+    }
+
+    impl Visitor for DefinitionRegions {
+        fn visit_decl(&mut self, decl: DeclarationInfo<'_>) {
+            match &decl {
+                DeclarationInfo::Value { loc_symbol, .. }
+                | DeclarationInfo::Function { loc_symbol, .. } => {
+                    self.regions.insert(loc_symbol.value, loc_symbol.region);
+                }
+                DeclarationInfo::Expectation { .. } | DeclarationInfo::Destructure { .. } => {}
+            }
+
+            walk_decl(self, decl);
+        }
+
+        fn visit_pattern(&mut self, pattern: &Pattern, region: Region, _opt_var: Option<Variable>) {
+            if let Pattern::Identifier(symbol) = pattern {
+                self.regions.entry(*symbol).or_insert(region);
+            }
+
+            walk_pattern(self, pattern);
+        }
+
+        fn visit_expr(&mut self, expr: &Expr, region: Region, var: Variable) {
+            if let Expr::Closure(ClosureData { name, .. }) = expr {
+                self.regions.entry(*name).or_insert(region);
+            }
+
+            walk_expr(self, expr, var);
+        }
+    }
+}
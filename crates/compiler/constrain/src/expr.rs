@@ -1721,14 +1721,22 @@ pub fn constrain_expr(
             arg_cons.push(eq);
             constraints.exists_many(vars, arg_cons)
         }
-        TypedHole(var) => {
+        TypedHole(var, name) => {
             // store the expected type for this position
-            constraints.equal_types_var(
+            let store_con = constraints.equal_types_var(
                 *var,
                 expected,
                 Category::Storage(std::file!(), std::line!()),
                 region,
-            )
+            );
+
+            // and report it, along with the values in scope that would fit, once solved
+            let hole_type = constraints.push_variable(*var);
+            let hole_con = constraints.type_hole(hole_type, name.clone(), region, true);
+
+            let and = constraints.and_constraint([store_con, hole_con]);
+
+            constraints.exists([*var], and)
         }
         RuntimeError(_) => {
             // Runtime Errors are always going to crash, so they don't introduce any new
@@ -4107,7 +4115,7 @@ fn is_generalizable_expr(mut expr: &Expr) -> bool {
             | Expect { .. }
             | ExpectFx { .. }
             | Dbg { .. }
-            | TypedHole(_)
+            | TypedHole(..)
             | RuntimeError(..)
             | ZeroArgumentTag { .. }
             | Tag { .. }
@@ -4,8 +4,10 @@ use roc_can::constraint::{Constraint, Constraints, Generalizable};
 use roc_can::expected::Expected;
 use roc_can::expr::Declarations;
 use roc_can::pattern::Pattern;
+use roc_module::ident::Lowercase;
 use roc_module::symbol::{ModuleId, Symbol};
 use roc_region::all::{Loc, Region};
+use roc_types::subs::Variable;
 use roc_types::types::{AnnotationSource, Category, Type, Types};
 
 pub fn constrain_module(
@@ -14,6 +16,7 @@ pub fn constrain_module(
     symbols_from_requires: Vec<(Loc<Symbol>, Loc<Type>)>,
     abilities_store: &PendingAbilitiesStore,
     declarations: &Declarations,
+    annotation_type_holes: &[(Lowercase, Loc<Variable>)],
     home: ModuleId,
 ) -> Constraint {
     let constraint = crate::expr::constrain_decls(types, constraints, home, declarations);
@@ -26,6 +29,8 @@ pub fn constrain_module(
     );
     let constraint =
         frontload_ability_constraints(types, constraints, abilities_store, home, constraint);
+    let constraint =
+        constrain_annotation_type_holes(constraints, annotation_type_holes, constraint);
 
     // The module constraint should always save the environment at the end.
     debug_assert!(constraints.contains_save_the_environment(&constraint));
@@ -33,6 +38,33 @@ pub fn constrain_module(
     constraint
 }
 
+/// Type holes in annotations are solved along with the rest of the annotation; we only need to
+/// report their types once the whole module has been solved.
+fn constrain_annotation_type_holes(
+    constraints: &mut Constraints,
+    annotation_type_holes: &[(Lowercase, Loc<Variable>)],
+    constraint: Constraint,
+) -> Constraint {
+    if annotation_type_holes.is_empty() {
+        return constraint;
+    }
+
+    let mut and_constraints = Vec::with_capacity(annotation_type_holes.len() + 1);
+    and_constraints.push(constraint);
+
+    for (name, loc_var) in annotation_type_holes {
+        let hole_type = constraints.push_variable(loc_var.value);
+        and_constraints.push(constraints.type_hole(
+            hole_type,
+            Some(name.clone()),
+            loc_var.region,
+            false,
+        ));
+    }
+
+    constraints.and_constraint(and_constraints)
+}
+
 fn constrain_symbols_from_requires(
     types: &mut Types,
     constraints: &mut Constraints,
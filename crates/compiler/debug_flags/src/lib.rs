@@ -135,6 +135,16 @@ flags! {
     /// instructions.
     ROC_PRINT_IR_AFTER_REFCOUNT
 
+    /// Writes a pretty-printed mono IR to stderr after small procedures have been inlined.
+    ROC_PRINT_IR_AFTER_INLINING
+
+    /// Writes a pretty-printed mono IR to stderr after constant folding.
+    ROC_PRINT_IR_AFTER_CONSTANT_FOLDING
+
+    /// Writes a pretty-printed mono IR to stderr after heap cells that do not escape their
+    /// procedure have been removed.
+    ROC_PRINT_IR_AFTER_ESCAPE_ANALYSIS
+
     /// Writes a pretty-printed mono IR to stderr after the tail recursion (modulo cons)
     /// has been applied.
     ROC_PRINT_IR_AFTER_TRMC
@@ -0,0 +1,673 @@
+//! Derivers for the `Sort` ability.
+
+use std::iter::once;
+
+use roc_can::{
+    expr::{AnnotatedMark, ClosureData, Expr, Recursive, WhenBranch, WhenBranchPattern},
+    pattern::Pattern,
+};
+use roc_derive_key::sort::FlatSortKey;
+use roc_module::{
+    called_via::CalledVia,
+    ident::{Lowercase, TagName},
+    symbol::Symbol,
+};
+use roc_region::all::{Loc, Region};
+use roc_types::{
+    subs::{
+        Content, ExhaustiveMark, FlatType, GetSubsSlice, LambdaSet, OptVariable, RecordFields,
+        RedundantMark, SubsSlice, TagExt, TupleElems, UnionLambdas, UnionTags, Variable,
+        VariableSubsSlice,
+    },
+    types::RecordField,
+};
+
+use crate::{
+    synth_var,
+    util::{Env, ExtensionKind},
+    DerivedBody,
+};
+
+pub(crate) fn derive_compare(
+    env: &mut Env<'_>,
+    key: FlatSortKey,
+    def_symbol: Symbol,
+) -> DerivedBody {
+    let (body_type, body) = match key {
+        FlatSortKey::Record(fields) => compare_record(env, def_symbol, fields),
+        FlatSortKey::Tuple(arity) => compare_tuple(env, def_symbol, arity),
+        FlatSortKey::TagUnion(tags) => {
+            if tags.len() == 1 {
+                compare_newtype_tag_union(env, def_symbol, tags.into_iter().next().unwrap())
+            } else {
+                compare_tag_union(env, def_symbol, tags)
+            }
+        }
+    };
+
+    let specialization_lambda_sets =
+        env.get_specialization_lambda_sets(body_type, Symbol::SORT_COMPARE);
+
+    DerivedBody {
+        body,
+        body_type,
+        specialization_lambda_sets,
+    }
+}
+
+fn compare_record(env: &mut Env<'_>, fn_name: Symbol, fields: Vec<Lowercase>) -> (Variable, Expr) {
+    // Suppose rcd = { f1, ..., fn }.
+    // Build a generalized type t_rcd = { f1: t1, ..., fn: tn }, with fresh t1, ..., tn,
+    // so that we can re-use the derived impl for many records of the same fields.
+    let (record_var, record_fields) = {
+        let flex_fields = fields
+            .into_iter()
+            .map(|name| {
+                (
+                    name,
+                    RecordField::Required(env.subs.fresh_unnamed_flex_var()),
+                )
+            })
+            .collect::<Vec<(Lowercase, _)>>();
+        let fields = RecordFields::insert_into_subs(env.subs, flex_fields);
+        let record_var = synth_var(
+            env.subs,
+            Content::Structure(FlatType::Record(fields, Variable::EMPTY_RECORD)),
+        );
+
+        (record_var, fields)
+    };
+
+    // Now, a comparator for this record is
+    //
+    // compare_rcd : { f1: t1, ..., fn: tn }, { f1: t1, ..., fn: tn } -> [LT, EQ, GT]
+    // compare_rcd = \rcd1, rcd2 ->
+    //   when Sort.compare rcd1.f1 rcd2.f1 is
+    //     EQ ->
+    //       ...
+    //         when Sort.compare rcd1.fn rcd2.fn is
+    //           EQ -> EQ
+    //           ordering -> ordering
+    //       ...
+    //     ordering -> ordering
+    //
+    // So, build a fold travelling backwards from the last field.
+    let rcd1_sym = env.new_symbol("rcd1");
+    let rcd2_sym = env.new_symbol("rcd2");
+
+    let ordering_var = ordering_var(env);
+
+    let field_accesses: Vec<_> = record_fields
+        .iter_all()
+        .map(|(field_name, field_var, _)| {
+            let field_name = env.subs[field_name].clone();
+            let field_var = env.subs[field_var];
+
+            let mut access = |rcd_sym| Expr::RecordAccess {
+                record_var,
+                field_var,
+                ext_var: env.subs.fresh_unnamed_flex_var(),
+                loc_expr: Box::new(Loc::at_zero(Expr::Var(
+                    rcd_sym,
+                    env.subs.fresh_unnamed_flex_var(),
+                ))),
+                field: field_name.clone(),
+            };
+
+            let left = access(rcd1_sym);
+            let right = access(rcd2_sym);
+
+            (field_var, left, right)
+        })
+        .collect();
+
+    let body = chain_comparisons(env, ordering_var, field_accesses);
+
+    // Finally, build the closure
+    // \rcd1, rcd2 -> body
+    build_outer_derived_closure(
+        env,
+        fn_name,
+        record_var,
+        (Pattern::Identifier(rcd1_sym), Pattern::Identifier(rcd2_sym)),
+        (ordering_var, body),
+    )
+}
+
+fn compare_tuple(env: &mut Env<'_>, fn_name: Symbol, arity: u32) -> (Variable, Expr) {
+    // Suppose tup = (v1, ..., vn).
+    // Build a generalized type t_tup = (t1, ..., tn), with fresh t1, ..., tn,
+    // so that we can re-use the derived impl for many tuples of the same arity.
+    let (tuple_var, tuple_elems) = {
+        let flex_elems: Vec<_> = (0..arity)
+            .map(|i| (i as usize, env.subs.fresh_unnamed_flex_var()))
+            .collect();
+        let elems = TupleElems::insert_into_subs(env.subs, flex_elems);
+        let tuple_var = synth_var(
+            env.subs,
+            Content::Structure(FlatType::Tuple(elems, Variable::EMPTY_TUPLE)),
+        );
+
+        (tuple_var, elems)
+    };
+
+    // Now, a comparator for this tuple is
+    //
+    // compare_tup : (t1, ..., tn), (t1, ..., tn) -> [LT, EQ, GT]
+    // compare_tup = \tup1, tup2 ->
+    //   when Sort.compare tup1.0 tup2.0 is
+    //     EQ -> ... (when Sort.compare tup1.n tup2.n is EQ -> EQ; ordering -> ordering)
+    //     ordering -> ordering
+    let tup1_sym = env.new_symbol("tup1");
+    let tup2_sym = env.new_symbol("tup2");
+
+    let ordering_var = ordering_var(env);
+
+    let elem_accesses: Vec<_> = tuple_elems
+        .iter_all()
+        .map(|(elem_idx, elem_var)| {
+            let index = env.subs[elem_idx];
+            let elem_var = env.subs[elem_var];
+
+            let mut access = |tup_sym| Expr::TupleAccess {
+                tuple_var,
+                elem_var,
+                ext_var: env.subs.fresh_unnamed_flex_var(),
+                loc_expr: Box::new(Loc::at_zero(Expr::Var(
+                    tup_sym,
+                    env.subs.fresh_unnamed_flex_var(),
+                ))),
+                index,
+            };
+
+            let left = access(tup1_sym);
+            let right = access(tup2_sym);
+
+            (elem_var, left, right)
+        })
+        .collect();
+
+    let body = chain_comparisons(env, ordering_var, elem_accesses);
+
+    // Finally, build the closure
+    // \tup1, tup2 -> body
+    build_outer_derived_closure(
+        env,
+        fn_name,
+        tuple_var,
+        (Pattern::Identifier(tup1_sym), Pattern::Identifier(tup2_sym)),
+        (ordering_var, body),
+    )
+}
+
+/// Build a `compare` implementation for a non-singleton tag union.
+fn compare_tag_union(
+    env: &mut Env<'_>,
+    fn_name: Symbol,
+    tags: Vec<(TagName, u16)>,
+) -> (Variable, Expr) {
+    // Suppose tags = [ A p11 .. p1n, ..., Q pq1 .. pqm ]
+    // Build a generalized type t_tags = [ A t11 .. t1n, ..., Q tq1 .. tqm ],
+    // with fresh t1, ..., tqm, so that we can re-use the derived impl for many
+    // unions of the same tags and payloads.
+    let (union_var, union_tags) = {
+        let flex_tag_labels = tags
+            .into_iter()
+            .map(|(label, arity)| {
+                let variables_slice = VariableSubsSlice::reserve_into_subs(env.subs, arity.into());
+                for var_index in variables_slice {
+                    env.subs[var_index] = env.subs.fresh_unnamed_flex_var();
+                }
+                (label, variables_slice)
+            })
+            .collect::<Vec<_>>();
+        let union_tags = UnionTags::insert_slices_into_subs(env.subs, flex_tag_labels);
+        let tag_union_var = synth_var(
+            env.subs,
+            Content::Structure(FlatType::TagUnion(
+                union_tags,
+                TagExt::Any(Variable::EMPTY_TAG_UNION),
+            )),
+        );
+
+        (tag_union_var, union_tags)
+    };
+
+    // Now, a comparator for this tag union is
+    //
+    // compare_union : [ A t11 .. t1n, ..., Q tq1 .. tqm ], [ A t11 .. t1n, ..., Q tq1 .. tqm ] -> [LT, EQ, GT]
+    // compare_union = \union1, union2 ->
+    //   when union1 is
+    //      A x11 .. x1n ->
+    //        when union2 is
+    //          A y11 .. y1n -> <compare x11 with y11, ..., x1n with y1n>
+    //          B _ .. _ | ... | Q _ .. _ -> LT
+    //      ...
+    //      Q xq1 .. xqm ->
+    //        when union2 is
+    //          A _ .. _ | ... | P _ .. _ -> GT
+    //          Q yq1 .. yqm -> <compare xq1 with yq1, ..., xqm with yqm>
+    //
+    // where tags are ordered by their position in the (sorted) tag union.
+    let union1_sym = env.new_symbol("union1");
+    let union2_sym = env.new_symbol("union2");
+
+    let ordering_var = ordering_var(env);
+
+    let tags: Vec<(TagName, Vec<Variable>)> = union_tags
+        .iter_all()
+        .map(|(tag, payloads)| {
+            let tag_name = env.subs[tag].clone();
+            let payload_vars = env.subs.get_subs_slice(env.subs[payloads]).to_vec();
+            (tag_name, payload_vars)
+        })
+        .collect();
+
+    let outer_branches = tags
+        .iter()
+        .enumerate()
+        .map(|(outer_rank, (outer_tag, payload_vars))| {
+            let left_syms: Vec<_> = std::iter::repeat_with(|| env.unique_symbol())
+                .take(payload_vars.len())
+                .collect();
+            let right_syms: Vec<_> = std::iter::repeat_with(|| env.unique_symbol())
+                .take(payload_vars.len())
+                .collect();
+
+            // A y11 .. y1n -> <compare payloads>
+            let same_tag_branch = {
+                let comparisons = payload_vars
+                    .iter()
+                    .zip(left_syms.iter().zip(right_syms.iter()))
+                    .map(|(var, (left, right))| {
+                        (*var, Expr::Var(*left, *var), Expr::Var(*right, *var))
+                    })
+                    .collect();
+
+                WhenBranch {
+                    patterns: vec![tag_pattern(
+                        union_var,
+                        outer_tag.clone(),
+                        payload_vars,
+                        Some(&right_syms[..]),
+                    )],
+                    value: Loc::at_zero(chain_comparisons(env, ordering_var, comparisons)),
+                    guard: None,
+                    redundant: RedundantMark::known_non_redundant(),
+                }
+            };
+
+            // A _ .. _ | ... -> GT, for all tags before this one
+            // B _ .. _ | ... -> LT, for all tags after this one
+            let (before, after) = tags.split_at(outer_rank);
+            let mut other_tags_branch = |others: &[(TagName, Vec<Variable>)], ordering: &str| {
+                if others.is_empty() {
+                    return None;
+                }
+
+                let patterns = others
+                    .iter()
+                    .map(|(tag, vars)| tag_pattern(union_var, tag.clone(), vars, None))
+                    .collect();
+
+                Some(WhenBranch {
+                    patterns,
+                    value: Loc::at_zero(ordering_tag(env, ordering_var, ordering)),
+                    guard: None,
+                    redundant: RedundantMark::known_non_redundant(),
+                })
+            };
+
+            let inner_branches: Vec<_> = other_tags_branch(before, "GT")
+                .into_iter()
+                .chain(once(same_tag_branch))
+                .chain(other_tags_branch(&after[1..], "LT"))
+                .collect();
+
+            let inner_when = Expr::When {
+                loc_cond: Box::new(Loc::at_zero(Expr::Var(union2_sym, union_var))),
+                cond_var: union_var,
+                expr_var: ordering_var,
+                region: Region::zero(),
+                branches: inner_branches,
+                branches_cond_var: union_var,
+                exhaustive: ExhaustiveMark::known_exhaustive(),
+            };
+
+            WhenBranch {
+                patterns: vec![tag_pattern(
+                    union_var,
+                    outer_tag.clone(),
+                    payload_vars,
+                    Some(&left_syms[..]),
+                )],
+                value: Loc::at_zero(inner_when),
+                guard: None,
+                redundant: RedundantMark::known_non_redundant(),
+            }
+        })
+        .collect();
+
+    // when union1 is
+    //   ...
+    let when_expr = Expr::When {
+        loc_cond: Box::new(Loc::at_zero(Expr::Var(union1_sym, union_var))),
+        cond_var: union_var,
+        expr_var: ordering_var,
+        region: Region::zero(),
+        branches: outer_branches,
+        branches_cond_var: union_var,
+        exhaustive: ExhaustiveMark::known_exhaustive(),
+    };
+
+    // Finally, build the closure
+    // \union1, union2 -> body
+    build_outer_derived_closure(
+        env,
+        fn_name,
+        union_var,
+        (
+            Pattern::Identifier(union1_sym),
+            Pattern::Identifier(union2_sym),
+        ),
+        (ordering_var, when_expr),
+    )
+}
+
+/// Build a `compare` implementation for a newtype (singleton) tag union.
+/// If a tag union is a newtype, we only need to compare its payloads.
+fn compare_newtype_tag_union(
+    env: &mut Env<'_>,
+    fn_name: Symbol,
+    tag: (TagName, u16),
+) -> (Variable, Expr) {
+    // Suppose tags = [ A p1 .. pn ]
+    // Build a generalized type t_tags = [ A t1 .. tn ],
+    // with fresh t1, ..., tn, so that we can re-use the derived impl for many
+    // unions of the same tag and payload arity.
+    let (tag_name, arity) = tag;
+
+    let variables_slice = VariableSubsSlice::reserve_into_subs(env.subs, arity.into());
+    for var_index in variables_slice {
+        env.subs[var_index] = env.subs.fresh_unnamed_flex_var();
+    }
+
+    let union_tags =
+        UnionTags::insert_slices_into_subs(env.subs, once((tag_name.clone(), variables_slice)));
+    let union_var = synth_var(
+        env.subs,
+        Content::Structure(FlatType::TagUnion(
+            union_tags,
+            TagExt::Any(Variable::EMPTY_TAG_UNION),
+        )),
+    );
+    let payload_vars = env.subs.get_subs_slice(variables_slice).to_vec();
+
+    // Now, a comparator for this tag union is
+    //
+    // compare_union : [ A t1 .. tn ], [ A t1 .. tn ] -> [LT, EQ, GT]
+    // compare_union = \A x1 .. xn, A y1 .. yn -> <compare x1 with y1, ..., xn with yn>
+    let ordering_var = ordering_var(env);
+
+    let left_syms: Vec<_> = std::iter::repeat_with(|| env.unique_symbol())
+        .take(payload_vars.len())
+        .collect();
+    let right_syms: Vec<_> = std::iter::repeat_with(|| env.unique_symbol())
+        .take(payload_vars.len())
+        .collect();
+
+    let comparisons = payload_vars
+        .iter()
+        .zip(left_syms.iter().zip(right_syms.iter()))
+        .map(|(var, (left, right))| (*var, Expr::Var(*left, *var), Expr::Var(*right, *var)))
+        .collect();
+    let body = chain_comparisons(env, ordering_var, comparisons);
+
+    let left_pattern = tag_pattern(
+        union_var,
+        tag_name.clone(),
+        &payload_vars,
+        Some(&left_syms[..]),
+    );
+    let right_pattern = tag_pattern(union_var, tag_name, &payload_vars, Some(&right_syms[..]));
+
+    // Finally, build the closure
+    // \A x1 .. xn, A y1 .. yn -> body
+    build_outer_derived_closure(
+        env,
+        fn_name,
+        union_var,
+        (left_pattern.pattern.value, right_pattern.pattern.value),
+        (ordering_var, body),
+    )
+}
+
+/// Creates the closed `[LT, EQ, GT]` type that `compare` returns.
+fn ordering_var(env: &mut Env<'_>) -> Variable {
+    let tags = UnionTags::insert_slices_into_subs(
+        env.subs,
+        ["EQ", "GT", "LT"]
+            .into_iter()
+            .map(|tag| (TagName::from(tag), VariableSubsSlice::default())),
+    );
+
+    synth_var(
+        env.subs,
+        Content::Structure(FlatType::TagUnion(
+            tags,
+            TagExt::Any(Variable::EMPTY_TAG_UNION),
+        )),
+    )
+}
+
+/// Builds the `EQ`, `LT` or `GT` tag.
+fn ordering_tag(env: &mut Env<'_>, ordering_var: Variable, ordering: &str) -> Expr {
+    Expr::Tag {
+        tag_union_var: ordering_var,
+        ext_var: env.new_ext_var(ExtensionKind::TagUnion),
+        name: TagName::from(ordering),
+        arguments: Vec::new(),
+    }
+}
+
+/// Builds the pattern `A x1 .. xn`, or `A _ .. _` if no payload symbols are given.
+fn tag_pattern(
+    union_var: Variable,
+    tag_name: TagName,
+    payload_vars: &[Variable],
+    payload_syms: Option<&[Symbol]>,
+) -> WhenBranchPattern {
+    let arguments = payload_vars
+        .iter()
+        .enumerate()
+        .map(|(i, var)| {
+            let pattern = match payload_syms {
+                Some(syms) => Pattern::Identifier(syms[i]),
+                None => Pattern::Underscore,
+            };
+            (*var, Loc::at_zero(pattern))
+        })
+        .collect();
+
+    WhenBranchPattern {
+        pattern: Loc::at_zero(Pattern::AppliedTag {
+            whole_var: union_var,
+            tag_name,
+            ext_var: Variable::EMPTY_TAG_UNION,
+            arguments,
+        }),
+        degenerate: false,
+    }
+}
+
+/// Folds up a list of `(type, left, right)` comparisons into
+///
+/// ```text
+/// when Sort.compare left1 right1 is
+///     EQ -> ... (when Sort.compare leftn rightn is EQ -> EQ; ordering -> ordering)
+///     ordering -> ordering
+/// ```
+///
+/// so that the first comparison that is not `EQ` decides the result.
+fn chain_comparisons(
+    env: &mut Env<'_>,
+    ordering_var: Variable,
+    comparisons: Vec<(Variable, Expr, Expr)>,
+) -> Expr {
+    let all_equal = ordering_tag(env, ordering_var, "EQ");
+
+    comparisons
+        .into_iter()
+        .rev()
+        .fold(all_equal, |rest, (val_var, left, right)| {
+            let (compare_var, compare_expr) =
+                call_sort_compare(env, (val_var, left), (val_var, right));
+            env.unify(compare_var, ordering_var);
+
+            let ordering_sym = env.new_symbol("ordering");
+
+            let equal_branch = WhenBranch {
+                patterns: vec![WhenBranchPattern {
+                    pattern: Loc::at_zero(Pattern::AppliedTag {
+                        whole_var: ordering_var,
+                        tag_name: "EQ".into(),
+                        ext_var: Variable::EMPTY_TAG_UNION,
+                        arguments: Vec::new(),
+                    }),
+                    degenerate: false,
+                }],
+                value: Loc::at_zero(rest),
+                guard: None,
+                redundant: RedundantMark::known_non_redundant(),
+            };
+            let unequal_branch = WhenBranch {
+                patterns: vec![WhenBranchPattern {
+                    pattern: Loc::at_zero(Pattern::Identifier(ordering_sym)),
+                    degenerate: false,
+                }],
+                value: Loc::at_zero(Expr::Var(ordering_sym, ordering_var)),
+                guard: None,
+                redundant: RedundantMark::known_non_redundant(),
+            };
+
+            Expr::When {
+                loc_cond: Box::new(Loc::at_zero(compare_expr)),
+                cond_var: ordering_var,
+                expr_var: ordering_var,
+                region: Region::zero(),
+                branches: vec![equal_branch, unequal_branch],
+                branches_cond_var: ordering_var,
+                exhaustive: ExhaustiveMark::known_exhaustive(),
+            }
+        })
+}
+
+fn call_sort_compare(
+    env: &mut Env<'_>,
+    left: (Variable, Expr),
+    right: (Variable, Expr),
+) -> (Variable, Expr) {
+    let (left_var, left_expr) = left;
+    let (right_var, right_expr) = right;
+
+    // build `Sort.compare ...` function type.
+    //
+    // a, a -[uls]-> [LT, EQ, GT] where a implements Sort
+    let exposed_compare_fn_var = env.import_builtin_symbol_var(Symbol::SORT_COMPARE);
+
+    // (typeof left), (typeof right) -[clos]-> ordering
+    let this_arguments_slice = VariableSubsSlice::insert_into_subs(env.subs, [left_var, right_var]);
+    let this_compare_clos_var = env.subs.fresh_unnamed_flex_var();
+    let this_ordering_var = env.subs.fresh_unnamed_flex_var();
+    let this_compare_fn_var = synth_var(
+        env.subs,
+        Content::Structure(FlatType::Func(
+            this_arguments_slice,
+            this_compare_clos_var,
+            this_ordering_var,
+        )),
+    );
+
+    //   a,             a              -[uls]->  [LT, EQ, GT] where a implements Sort
+    // ~ (typeof left), (typeof right) -[clos]-> ordering
+    env.unify(exposed_compare_fn_var, this_compare_fn_var);
+
+    // Sort.compare : (typeof left), (typeof right) -[clos]-> [LT, EQ, GT]
+    let compare_fn_head = Expr::AbilityMember(Symbol::SORT_COMPARE, None, this_compare_fn_var);
+    let compare_fn_data = Box::new((
+        this_compare_fn_var,
+        Loc::at_zero(compare_fn_head),
+        this_compare_clos_var,
+        this_ordering_var,
+    ));
+
+    let compare_arguments = vec![
+        (left_var, Loc::at_zero(left_expr)),
+        (right_var, Loc::at_zero(right_expr)),
+    ];
+    let call_compare = Expr::Call(compare_fn_data, compare_arguments, CalledVia::Space);
+
+    (this_ordering_var, call_compare)
+}
+
+fn build_outer_derived_closure(
+    env: &mut Env<'_>,
+    fn_name: Symbol,
+    val_var: Variable,
+    patterns: (Pattern, Pattern),
+    body: (Variable, Expr),
+) -> (Variable, Expr) {
+    let (left_pattern, right_pattern) = patterns;
+    let (body_var, body_expr) = body;
+
+    let (fn_var, fn_clos_var) = {
+        // Create fn_var for ambient capture; we fix it up below.
+        let fn_var = synth_var(env.subs, Content::Error);
+
+        // -[fn_name]->
+        let fn_captures = vec![];
+        let fn_name_labels = UnionLambdas::insert_into_subs(env.subs, once((fn_name, fn_captures)));
+        let fn_clos_var = synth_var(
+            env.subs,
+            Content::LambdaSet(LambdaSet {
+                solved: fn_name_labels,
+                recursion_var: OptVariable::NONE,
+                unspecialized: SubsSlice::default(),
+                ambient_function: fn_var,
+            }),
+        );
+
+        // val_var, val_var -[fn_name]-> (ordering = body_var)
+        let args_slice = SubsSlice::insert_into_subs(env.subs, [val_var, val_var]);
+        env.subs.set_content(
+            fn_var,
+            Content::Structure(FlatType::Func(args_slice, fn_clos_var, body_var)),
+        );
+
+        (fn_var, fn_clos_var)
+    };
+
+    let clos_expr = Expr::Closure(ClosureData {
+        function_type: fn_var,
+        closure_type: fn_clos_var,
+        return_type: body_var,
+        name: fn_name,
+        captured_symbols: vec![],
+        recursive: Recursive::NotRecursive,
+        arguments: vec![
+            (
+                val_var,
+                AnnotatedMark::known_exhaustive(),
+                Loc::at_zero(left_pattern),
+            ),
+            (
+                val_var,
+                AnnotatedMark::known_exhaustive(),
+                Loc::at_zero(right_pattern),
+            ),
+        ],
+        loc_body: Box::new(Loc::at_zero(body_expr)),
+    });
+
+    (fn_var, clos_expr)
+}
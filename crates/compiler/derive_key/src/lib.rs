@@ -9,6 +9,10 @@
 //!   between e.g. required and optional record fields.
 //! - `Decoding` is like encoding, but has some differences. For one, it *does* need to distinguish
 //!   between required and optional record fields.
+//! - `Inspect` is like encoding, but renders values rather than serializing them, so it keys on
+//!   the same surface information.
+//! - `Sort` only needs the shape of a type - its field names, tuple arity, or tag names and
+//!   payload arities - since the ordering of nested values is deferred to their own `compare`.
 //!
 //! For these reasons the content keying is based on a strategy as well, which are the variants of
 //! [`DeriveKey`].
@@ -16,11 +20,15 @@
 pub mod decoding;
 pub mod encoding;
 pub mod hash;
+pub mod inspect;
+pub mod sort;
 mod util;
 
 use decoding::{FlatDecodable, FlatDecodableKey};
 use encoding::{FlatEncodable, FlatEncodableKey};
 use hash::{FlatHash, FlatHashKey};
+use inspect::{FlatInspectable, FlatInspectableKey};
+use sort::{FlatSort, FlatSortKey};
 
 use roc_module::symbol::Symbol;
 use roc_types::subs::{Subs, Variable};
@@ -40,6 +48,8 @@ pub enum DeriveKey {
     ToEncoder(FlatEncodableKey),
     Decoder(FlatDecodableKey),
     Hash(FlatHashKey),
+    Compare(FlatSortKey),
+    ToInspector(FlatInspectableKey),
 }
 
 impl DeriveKey {
@@ -48,6 +58,8 @@ impl DeriveKey {
             DeriveKey::ToEncoder(key) => format!("toEncoder_{}", key.debug_name()),
             DeriveKey::Decoder(key) => format!("decoder_{}", key.debug_name()),
             DeriveKey::Hash(key) => format!("hash_{}", key.debug_name()),
+            DeriveKey::Compare(key) => format!("compare_{}", key.debug_name()),
+            DeriveKey::ToInspector(key) => format!("toInspector_{}", key.debug_name()),
         }
     }
 }
@@ -77,6 +89,8 @@ pub enum DeriveBuiltin {
     Decoder,
     Hash,
     IsEq,
+    Compare,
+    ToInspector,
 }
 
 impl TryFrom<Symbol> for DeriveBuiltin {
@@ -88,6 +102,8 @@ impl TryFrom<Symbol> for DeriveBuiltin {
             Symbol::DECODE_DECODER => Ok(DeriveBuiltin::Decoder),
             Symbol::HASH_HASH => Ok(DeriveBuiltin::Hash),
             Symbol::BOOL_IS_EQ => Ok(DeriveBuiltin::IsEq),
+            Symbol::SORT_COMPARE => Ok(DeriveBuiltin::Compare),
+            Symbol::INSPECT_TO_INSPECTOR => Ok(DeriveBuiltin::ToInspector),
             _ => Err(value),
         }
     }
@@ -121,6 +137,16 @@ impl Derived {
                     Symbol::BOOL_STRUCTURAL_EQ,
                 ))
             }
+            DeriveBuiltin::Compare => match sort::FlatSort::from_var(subs, var)? {
+                FlatSort::SingleLambdaSetImmediate(imm) => {
+                    Ok(Derived::SingleLambdaSetImmediate(imm))
+                }
+                FlatSort::Key(repr) => Ok(Derived::Key(DeriveKey::Compare(repr))),
+            },
+            DeriveBuiltin::ToInspector => match inspect::FlatInspectable::from_var(subs, var)? {
+                FlatInspectable::Immediate(imm) => Ok(Derived::Immediate(imm)),
+                FlatInspectable::Key(repr) => Ok(Derived::Key(DeriveKey::ToInspector(repr))),
+            },
         }
     }
 
@@ -151,6 +177,18 @@ impl Derived {
                     Symbol::BOOL_STRUCTURAL_EQ,
                 ))
             }
+            DeriveBuiltin::Compare => match sort::FlatSort::from_builtin_symbol(symbol)? {
+                FlatSort::SingleLambdaSetImmediate(imm) => {
+                    Ok(Derived::SingleLambdaSetImmediate(imm))
+                }
+                FlatSort::Key(repr) => Ok(Derived::Key(DeriveKey::Compare(repr))),
+            },
+            DeriveBuiltin::ToInspector => {
+                match inspect::FlatInspectable::from_builtin_symbol(symbol)? {
+                    FlatInspectable::Immediate(imm) => Ok(Derived::Immediate(imm)),
+                    FlatInspectable::Key(repr) => Ok(Derived::Key(DeriveKey::ToInspector(repr))),
+                }
+            }
         }
     }
 }
@@ -0,0 +1,177 @@
+use roc_module::{
+    ident::{Lowercase, TagName},
+    symbol::Symbol,
+};
+use roc_types::subs::{Content, FlatType, GetSubsSlice, Subs, Variable};
+
+use crate::{
+    util::{check_derivable_ext_var, debug_name_record, debug_name_tag, debug_name_tuple},
+    DeriveError,
+};
+
+#[derive(Hash)]
+pub enum FlatSort {
+    // `compare` is always of form `a, a -> [LT, EQ, GT]`, and all the immediates below are
+    // top-level functions that capture nothing, so they have exactly one lambda set.
+    SingleLambdaSetImmediate(Symbol),
+    Key(FlatSortKey),
+}
+
+#[derive(Hash, PartialEq, Eq, Debug, Clone)]
+pub enum FlatSortKey {
+    Record(Vec<Lowercase>),
+    Tuple(u32),
+    TagUnion(Vec<(TagName, u16)>),
+}
+
+impl FlatSortKey {
+    pub(crate) fn debug_name(&self) -> String {
+        match self {
+            FlatSortKey::Record(fields) => debug_name_record(fields),
+            FlatSortKey::Tuple(arity) => debug_name_tuple(*arity),
+            FlatSortKey::TagUnion(tags) => debug_name_tag(tags),
+        }
+    }
+}
+
+impl FlatSort {
+    pub(crate) fn from_var(subs: &Subs, var: Variable) -> Result<FlatSort, DeriveError> {
+        use DeriveError::*;
+        use FlatSort::*;
+        match *subs.get_content_without_compacting(var) {
+            Content::Structure(flat_type) => match flat_type {
+                FlatType::Apply(sym, _) => match sym {
+                    Symbol::LIST_LIST => Ok(SingleLambdaSetImmediate(Symbol::LIST_COMPARE)),
+                    Symbol::STR_STR => Ok(SingleLambdaSetImmediate(Symbol::STR_COMPARE)),
+                    _ => Err(Underivable),
+                },
+                FlatType::Record(fields, ext) => {
+                    let (fields_iter, ext) = fields.unsorted_iterator_and_ext(subs, ext);
+
+                    check_derivable_ext_var(subs, ext, |ext| {
+                        matches!(ext, Content::Structure(FlatType::EmptyRecord))
+                    })?;
+
+                    let mut field_names = Vec::with_capacity(fields.len());
+                    for (field_name, record_field) in fields_iter {
+                        if record_field.is_optional() {
+                            // Optional fields are compile-time-polymorphic, so there is nothing to
+                            // compare at runtime.
+                            return Err(Underivable);
+                        }
+                        field_names.push(field_name.clone());
+                    }
+
+                    // The field order decides the comparison order, so it must be stable.
+                    field_names.sort();
+
+                    Ok(Key(FlatSortKey::Record(field_names)))
+                }
+                FlatType::Tuple(elems, ext) => {
+                    let (elems_iter, ext) = elems.sorted_iterator_and_ext(subs, ext);
+
+                    check_derivable_ext_var(subs, ext, |ext| {
+                        matches!(ext, Content::Structure(FlatType::EmptyTuple))
+                    })?;
+
+                    Ok(Key(FlatSortKey::Tuple(elems_iter.count() as _)))
+                }
+                FlatType::TagUnion(tags, ext) | FlatType::RecursiveTagUnion(_, tags, ext) => {
+                    // As with `Hash`, the recursion var doesn't matter, because the derived
+                    // implementation only looks at the surface of the tag union type.
+                    let (tags_iter, ext) = tags.unsorted_tags_and_ext(subs, ext);
+
+                    check_derivable_ext_var(subs, ext.var(), |ext| {
+                        matches!(ext, Content::Structure(FlatType::EmptyTagUnion))
+                    })?;
+
+                    let mut tag_names_and_payload_sizes: Vec<_> = tags_iter
+                        .tags
+                        .into_iter()
+                        .map(|(name, payload_slice)| {
+                            let payload_size = payload_slice.len();
+                            (name.clone(), payload_size as _)
+                        })
+                        .collect();
+
+                    // The position of a tag in this list is its rank in the derived ordering.
+                    tag_names_and_payload_sizes.sort_by(|(t1, _), (t2, _)| t1.cmp(t2));
+
+                    Ok(Key(FlatSortKey::TagUnion(tag_names_and_payload_sizes)))
+                }
+                FlatType::FunctionOrTagUnion(names_index, _, _) => {
+                    let mut tag_names: Vec<_> = subs
+                        .get_subs_slice(names_index)
+                        .iter()
+                        .map(|t| (t.clone(), 0))
+                        .collect();
+
+                    tag_names.sort_by(|(t1, _), (t2, _)| t1.cmp(t2));
+
+                    Ok(Key(FlatSortKey::TagUnion(tag_names)))
+                }
+                FlatType::EmptyRecord => Ok(Key(FlatSortKey::Record(vec![]))),
+                FlatType::EmptyTuple => Ok(Key(FlatSortKey::Tuple(0))),
+                FlatType::EmptyTagUnion => Ok(Key(FlatSortKey::TagUnion(vec![]))),
+                //
+                FlatType::Func(..) => Err(Underivable),
+            },
+            Content::Alias(sym, _, real_var, _) => match builtin_symbol_to_sort_lambda(sym) {
+                Some(lambda) => Ok(lambda),
+                // NB: as with `Hash`, it is okay to unwrap opaques here because derivers are only
+                // used by the backend, which treats opaques like structural aliases.
+                None => Self::from_var(subs, real_var),
+            },
+            Content::RangedNumber(_) => {
+                // Every number type we can compile a ranged number to is compared with
+                // `Num.compare`, so there is no need to pick the default width here.
+                Ok(SingleLambdaSetImmediate(Symbol::NUM_COMPARE))
+            }
+            //
+            Content::RecursionVar { structure, .. } => Self::from_var(subs, structure),
+            //
+            Content::Error => Err(Underivable),
+            Content::FlexVar(_)
+            | Content::RigidVar(_)
+            | Content::FlexAbleVar(_, _)
+            | Content::RigidAbleVar(_, _) => Err(UnboundVar),
+            Content::LambdaSet(_) | Content::ErasedLambda => Err(Underivable),
+        }
+    }
+
+    pub fn from_builtin_symbol(symbol: Symbol) -> Result<FlatSort, DeriveError> {
+        builtin_symbol_to_sort_lambda(symbol).ok_or(DeriveError::Underivable)
+    }
+}
+
+const fn builtin_symbol_to_sort_lambda(symbol: Symbol) -> Option<FlatSort> {
+    use FlatSort::*;
+    match symbol {
+        Symbol::BOOL_BOOL => Some(SingleLambdaSetImmediate(Symbol::SORT_COMPARE_BOOL)),
+        Symbol::NUM_U8
+        | Symbol::NUM_UNSIGNED8
+        | Symbol::NUM_U16
+        | Symbol::NUM_UNSIGNED16
+        | Symbol::NUM_U32
+        | Symbol::NUM_UNSIGNED32
+        | Symbol::NUM_U64
+        | Symbol::NUM_UNSIGNED64
+        | Symbol::NUM_U128
+        | Symbol::NUM_UNSIGNED128
+        | Symbol::NUM_I8
+        | Symbol::NUM_SIGNED8
+        | Symbol::NUM_I16
+        | Symbol::NUM_SIGNED16
+        | Symbol::NUM_I32
+        | Symbol::NUM_SIGNED32
+        | Symbol::NUM_I64
+        | Symbol::NUM_SIGNED64
+        | Symbol::NUM_I128
+        | Symbol::NUM_SIGNED128
+        | Symbol::NUM_NAT
+        | Symbol::NUM_NATURAL
+        | Symbol::NUM_DEC
+        | Symbol::NUM_DECIMAL => Some(SingleLambdaSetImmediate(Symbol::NUM_COMPARE)),
+        _ => None,
+    }
+}
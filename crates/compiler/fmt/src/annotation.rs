@@ -147,7 +147,7 @@ impl<'a> Formattable for TypeAnnotation<'a> {
                 true
             }
 
-            Wildcard | Inferred | BoundVariable(_) | Malformed(_) => false,
+            Wildcard | Inferred | TypeHole(_) | BoundVariable(_) | Malformed(_) => false,
             Function(args, result) => {
                 result.value.is_multiline()
                     || args.iter().any(|loc_arg| loc_arg.value.is_multiline())
@@ -310,6 +310,11 @@ impl<'a> Formattable for TypeAnnotation<'a> {
                 buf.indent(indent);
                 buf.push('_')
             }
+            TypeHole(name) => {
+                buf.indent(indent);
+                buf.push('?');
+                buf.push_str(name)
+            }
 
             TagUnion { tags, ext } => {
                 fmt_collection(buf, indent, Braces::Square, *tags, newlines);
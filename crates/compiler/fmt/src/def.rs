@@ -53,7 +53,9 @@ impl<'a> Formattable for TypeDef<'a> {
         match self {
             Alias { ann, .. } => ann.is_multiline(),
             Opaque { typ, .. } => typ.is_multiline(),
-            Ability { members, .. } => members.iter().any(|d| d.is_multiline()),
+            Ability {
+                parents, members, ..
+            } => !parents.is_empty() || members.iter().any(|d| d.is_multiline()),
         }
     }
 
@@ -124,6 +126,7 @@ impl<'a> Formattable for TypeDef<'a> {
             Ability {
                 header: TypeHeader { name, vars },
                 loc_implements: _,
+                parents,
                 members,
             } => {
                 buf.indent(indent);
@@ -136,6 +139,15 @@ impl<'a> Formattable for TypeDef<'a> {
                 buf.spaces(1);
                 buf.push_str(roc_parse::keyword::IMPLEMENTS);
 
+                for (i, parent) in parents.iter().enumerate() {
+                    if i > 0 {
+                        buf.spaces(1);
+                        buf.push('&');
+                    }
+                    buf.spaces(1);
+                    parent.format(buf, indent);
+                }
+
                 if !self.is_multiline() {
                     debug_assert_eq!(members.len(), 1);
                     buf.spaces(1);
@@ -428,7 +440,7 @@ pub fn fmt_body<'a>(buf: &mut Buf, pattern: &'a Pattern<'a>, body: &'a Expr<'a>,
 
 impl<'a> Formattable for AbilityMember<'a> {
     fn is_multiline(&self) -> bool {
-        self.name.value.is_multiline() || self.typ.is_multiline()
+        self.name.value.is_multiline() || self.typ.is_multiline() || self.default.is_some()
     }
 
     fn format_with_options(
@@ -447,5 +459,13 @@ impl<'a> Formattable for AbilityMember<'a> {
         buf.push(':');
         buf.spaces(1);
         self.typ.value.format(buf, indent + INDENT);
+
+        if let Some(default) = &self.default {
+            // The default implementation always goes on the line after the signature.
+            fmt_spaces(buf, default.spaces_before.iter(), indent);
+            buf.ensure_ends_with_newline();
+
+            fmt_body(buf, &Pattern::Identifier(item), &default.body.value, indent);
+        }
     }
 }
@@ -41,6 +41,7 @@ impl<'a> Formattable for Expr<'a> {
             | TupleAccess(_, _)
             | Var { .. }
             | Underscore { .. }
+            | TypeHole(_)
             | MalformedIdent(_, _)
             | MalformedClosure
             | Tag(_)
@@ -178,6 +179,11 @@ impl<'a> Formattable for Expr<'a> {
                 buf.push('_');
                 buf.push_str(name);
             }
+            TypeHole(name) => {
+                buf.indent(indent);
+                buf.push('?');
+                buf.push_str(name);
+            }
             Crash => {
                 buf.indent(indent);
                 buf.push_str("crash");
@@ -617,6 +623,17 @@ fn format_str_segment(seg: &StrSegment, buf: &mut Buf, indent: u16) {
             );
             buf.push(')');
         }
+        Inspected(loc_expr) => {
+            buf.push_str("\\{");
+            // e.g. {point} in "At \{point}"
+            loc_expr.value.format_with_options(
+                buf,
+                Parens::NotNeeded,
+                Newlines::No, // Interpolations can never have newlines
+                indent,
+            );
+            buf.push('}');
+        }
     }
 }
 
@@ -3,10 +3,10 @@ use bumpalo::Bump;
 use roc_module::called_via::{BinOp, UnaryOp};
 use roc_parse::{
     ast::{
-        AbilityImpls, AbilityMember, AssignedField, Collection, CommentOrNewline, Defs, Expr,
-        Header, Implements, ImplementsAbilities, ImplementsAbility, ImplementsClause, Module,
-        Pattern, RecordBuilderField, Spaced, Spaces, StrLiteral, StrSegment, Tag, TypeAnnotation,
-        TypeDef, TypeHeader, ValueDef, WhenBranch,
+        AbilityImpls, AbilityMember, AbilityMemberDefault, AssignedField, Collection,
+        CommentOrNewline, Defs, Expr, Header, Implements, ImplementsAbilities, ImplementsAbility,
+        ImplementsClause, Module, Pattern, RecordBuilderField, Spaced, Spaces, StrLiteral,
+        StrSegment, Tag, TypeAnnotation, TypeDef, TypeHeader, ValueDef, WhenBranch,
     },
     header::{
         AppHeader, ExposedName, HostedHeader, ImportsEntry, InterfaceHeader, KeywordItem,
@@ -508,6 +508,7 @@ impl<'a> RemoveSpaces<'a> for TypeDef<'a> {
             Ability {
                 header: TypeHeader { name, vars },
                 loc_implements: loc_has,
+                parents,
                 members,
             } => Ability {
                 header: TypeHeader {
@@ -515,6 +516,7 @@ impl<'a> RemoveSpaces<'a> for TypeDef<'a> {
                     vars: vars.remove_spaces(arena),
                 },
                 loc_implements: loc_has.remove_spaces(arena),
+                parents: parents.remove_spaces(arena),
                 members: members.remove_spaces(arena),
             },
         }
@@ -580,6 +582,10 @@ impl<'a> RemoveSpaces<'a> for AbilityMember<'a> {
         AbilityMember {
             name: self.name.remove_spaces(arena),
             typ: self.typ.remove_spaces(arena),
+            default: self.default.map(|default| AbilityMemberDefault {
+                spaces_before: &[],
+                body: arena.alloc(default.body.remove_spaces(arena)),
+            }),
         }
     }
 }
@@ -656,6 +662,7 @@ impl<'a> RemoveSpaces<'a> for StrSegment<'a> {
             StrSegment::Unicode(t) => StrSegment::Unicode(t.remove_spaces(arena)),
             StrSegment::EscapedChar(c) => StrSegment::EscapedChar(c),
             StrSegment::Interpolated(t) => StrSegment::Interpolated(t.remove_spaces(arena)),
+            StrSegment::Inspected(t) => StrSegment::Inspected(t.remove_spaces(arena)),
         }
     }
 }
@@ -689,6 +696,7 @@ impl<'a> RemoveSpaces<'a> for Expr<'a> {
             Expr::Tuple(a) => Expr::Tuple(a.remove_spaces(arena)),
             Expr::Var { module_name, ident } => Expr::Var { module_name, ident },
             Expr::Underscore(a) => Expr::Underscore(a),
+            Expr::TypeHole(a) => Expr::TypeHole(a),
             Expr::Tag(a) => Expr::Tag(a),
             Expr::OpaqueRef(a) => Expr::OpaqueRef(a),
             Expr::Closure(a, b) => Expr::Closure(
@@ -858,6 +866,7 @@ impl<'a> RemoveSpaces<'a> for TypeAnnotation<'a> {
                 tags: tags.remove_spaces(arena),
             },
             TypeAnnotation::Inferred => TypeAnnotation::Inferred,
+            TypeAnnotation::TypeHole(a) => TypeAnnotation::TypeHole(a),
             TypeAnnotation::Wildcard => TypeAnnotation::Wildcard,
             TypeAnnotation::Where(annot, has_clauses) => TypeAnnotation::Where(
                 arena.alloc(annot.remove_spaces(arena)),
@@ -0,0 +1,543 @@
+//! DWARF debug info for the objects we emit, so that debuggers and profilers like gdb and perf
+//! can attribute machine code to the Roc source it came from.
+//!
+//! For now we only describe procedures: each one gets a `DW_TAG_subprogram`, and a line table
+//! sequence that starts at its definition and moves on to each named definition in its body
+//! where the code for that begins. The encoding is small enough to write by hand.
+use std::path::{Path, PathBuf};
+
+use object::write::{self, Object, SectionId, StandardSegment, SymbolId};
+use object::{BinaryFormat, RelocationEncoding, RelocationKind, SectionKind};
+use roc_collections::all::MutMap;
+use roc_error_macros::internal_error;
+use roc_module::symbol::{ModuleId, Symbol};
+use roc_region::all::{LineColumn, LineInfo, Region};
+
+const DWARF_VERSION: u16 = 4;
+const ADDRESS_SIZE: u8 = 8;
+
+// Tags, attributes and forms
+const DW_TAG_COMPILE_UNIT: u64 = 0x11;
+const DW_TAG_SUBPROGRAM: u64 = 0x2e;
+const DW_CHILDREN_NO: u8 = 0x00;
+const DW_CHILDREN_YES: u8 = 0x01;
+const DW_AT_NAME: u64 = 0x03;
+const DW_AT_STMT_LIST: u64 = 0x10;
+const DW_AT_LOW_PC: u64 = 0x11;
+const DW_AT_HIGH_PC: u64 = 0x12;
+const DW_AT_LANGUAGE: u64 = 0x13;
+const DW_AT_COMP_DIR: u64 = 0x1b;
+const DW_AT_PRODUCER: u64 = 0x25;
+const DW_AT_DECL_FILE: u64 = 0x3a;
+const DW_AT_DECL_LINE: u64 = 0x3b;
+const DW_FORM_ADDR: u64 = 0x01;
+const DW_FORM_DATA2: u64 = 0x05;
+const DW_FORM_DATA4: u64 = 0x06;
+const DW_FORM_STRING: u64 = 0x08;
+const DW_FORM_UDATA: u64 = 0x0f;
+const DW_FORM_SEC_OFFSET: u64 = 0x17;
+
+// There is no language code for Roc; the LLVM backend claims to be C too.
+const DW_LANG_C: u16 = 0x0002;
+
+// Abbreviation codes of the two kinds of DIE we emit
+const ABBREV_COMPILE_UNIT: u64 = 1;
+const ABBREV_SUBPROGRAM: u64 = 2;
+
+// Line number program opcodes
+const DW_LNS_COPY: u8 = 0x01;
+const DW_LNS_ADVANCE_PC: u8 = 0x02;
+const DW_LNS_ADVANCE_LINE: u8 = 0x03;
+const DW_LNS_SET_FILE: u8 = 0x04;
+const DW_LNS_SET_COLUMN: u8 = 0x05;
+const DW_LNE_END_SEQUENCE: u8 = 0x01;
+const DW_LNE_SET_ADDRESS: u8 = 0x02;
+
+// We never use special opcodes, but the header has to describe them anyway.
+const LINE_BASE: i8 = -5;
+const LINE_RANGE: u8 = 14;
+const OPCODE_BASE: u8 = 13;
+const STANDARD_OPCODE_LENGTHS: [u8; OPCODE_BASE as usize - 1] =
+    [0, 1, 1, 1, 1, 0, 0, 0, 1, 0, 0, 1];
+
+/// The source files of a program, and where its procedures are defined in them.
+pub struct DebugSources {
+    files: MutMap<ModuleId, (PathBuf, LineInfo)>,
+    definition_regions: MutMap<Symbol, Region>,
+}
+
+impl DebugSources {
+    pub fn new(
+        sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
+        definition_regions: MutMap<Symbol, Region>,
+    ) -> Self {
+        let files = sources
+            .iter()
+            .map(|(module_id, (path, src))| (*module_id, (path.clone(), LineInfo::new(src))))
+            .collect();
+
+        Self {
+            files,
+            definition_regions,
+        }
+    }
+
+    /// The file, line and column (both 1-based, as DWARF wants them) where `symbol` is defined.
+    fn position(&self, symbol: Symbol) -> Option<(&Path, u64, u64)> {
+        let region = self.definition_regions.get(&symbol)?;
+
+        self.position_of(symbol.module_id(), *region)
+    }
+
+    fn position_of(&self, module_id: ModuleId, region: Region) -> Option<(&Path, u64, u64)> {
+        let (path, line_info) = self.files.get(&module_id)?;
+        let LineColumn { line, column } = line_info.convert_pos(region.start());
+
+        Some((path, line as u64 + 1, column as u64 + 1))
+    }
+}
+
+/// The machine code we emitted for a procedure.
+pub(crate) struct DebugProc {
+    pub fn_name: String,
+    pub symbol: Symbol,
+    pub proc_id: SymbolId,
+    pub size: u64,
+    /// Where the code for the definitions in the body starts, from [roc_mono::ir::Stmt::Located].
+    pub locations: Vec<(u64, Symbol, Region)>,
+}
+
+/// A row of the line table: from `offset` into a procedure on, the code comes from `line` and
+/// `column` of the file with index `file`.
+struct LineRow {
+    offset: u64,
+    file: u64,
+    line: u64,
+    column: u64,
+}
+
+/// Adds `.debug_abbrev`, `.debug_info` and `.debug_line` sections describing `procs` to `output`.
+/// Procedures whose definition we don't know are left out.
+pub(crate) fn add_debug_sections(output: &mut Object, sources: &DebugSources, procs: &[DebugProc]) {
+    // COFF objects carry CodeView rather than DWARF
+    if !matches!(output.format(), BinaryFormat::Elf | BinaryFormat::MachO) {
+        return;
+    }
+
+    let mut files: Vec<&Path> = Vec::new();
+    let mut file_number = |path| {
+        let index = match files.iter().position(|file| *file == path) {
+            Some(index) => index,
+            None => {
+                files.push(path);
+                files.len() - 1
+            }
+        };
+
+        // file numbers in the line table are 1-based
+        index as u64 + 1
+    };
+
+    let mut described = Vec::with_capacity(procs.len());
+
+    for proc in procs {
+        let Some((path, line, column)) = sources.position(proc.symbol) else {
+            continue;
+        };
+
+        let mut rows = vec![LineRow {
+            offset: 0,
+            file: file_number(path),
+            line,
+            column,
+        }];
+
+        for (offset, symbol, region) in proc.locations.iter() {
+            if let Some((path, line, column)) = sources.position_of(symbol.module_id(), *region) {
+                rows.push(LineRow {
+                    offset: *offset,
+                    file: file_number(path),
+                    line,
+                    column,
+                });
+            }
+        }
+
+        described.push((proc, rows));
+    }
+
+    if described.is_empty() {
+        return;
+    }
+
+    let debug_abbrev = add_debug_section(output, "abbrev");
+    let debug_info = add_debug_section(output, "info");
+    let debug_line = add_debug_section(output, "line");
+
+    let mut abbrev = DwarfWriter::default();
+    abbrev.uleb128(ABBREV_COMPILE_UNIT);
+    abbrev.uleb128(DW_TAG_COMPILE_UNIT);
+    abbrev.u8(DW_CHILDREN_YES);
+    abbrev.attribute(DW_AT_PRODUCER, DW_FORM_STRING);
+    abbrev.attribute(DW_AT_LANGUAGE, DW_FORM_DATA2);
+    abbrev.attribute(DW_AT_NAME, DW_FORM_STRING);
+    abbrev.attribute(DW_AT_COMP_DIR, DW_FORM_STRING);
+    abbrev.attribute(DW_AT_STMT_LIST, DW_FORM_SEC_OFFSET);
+    abbrev.attribute(0, 0);
+    abbrev.uleb128(ABBREV_SUBPROGRAM);
+    abbrev.uleb128(DW_TAG_SUBPROGRAM);
+    abbrev.u8(DW_CHILDREN_NO);
+    abbrev.attribute(DW_AT_NAME, DW_FORM_STRING);
+    abbrev.attribute(DW_AT_DECL_FILE, DW_FORM_UDATA);
+    abbrev.attribute(DW_AT_DECL_LINE, DW_FORM_UDATA);
+    abbrev.attribute(DW_AT_LOW_PC, DW_FORM_ADDR);
+    // in DWARF 4, a constant high_pc is the size of the code rather than an address
+    abbrev.attribute(DW_AT_HIGH_PC, DW_FORM_DATA4);
+    abbrev.attribute(0, 0);
+    abbrev.u8(0);
+
+    let mut info = DwarfWriter::default();
+    let unit_start = info.unit_header();
+    info.u16(DWARF_VERSION);
+    info.section_offset(output, debug_abbrev);
+    info.u8(ADDRESS_SIZE);
+    info.uleb128(ABBREV_COMPILE_UNIT);
+    info.string("roc dev backend");
+    info.u16(DW_LANG_C);
+    info.string("roc_app");
+    info.string(".");
+    info.section_offset(output, debug_line);
+
+    for (proc, rows) in described.iter() {
+        info.uleb128(ABBREV_SUBPROGRAM);
+        info.string(&proc.fn_name);
+        info.uleb128(rows[0].file);
+        info.uleb128(rows[0].line);
+        info.address(proc.proc_id);
+        info.u32(proc.size as u32);
+    }
+
+    // end of the compile unit's children
+    info.u8(0);
+    info.finish_unit(unit_start);
+
+    let mut lines = DwarfWriter::default();
+    let unit_start = lines.unit_header();
+    lines.u16(DWARF_VERSION);
+    let header_length = lines.placeholder_u32();
+    let header_start = lines.data.len();
+    lines.u8(1); // minimum_instruction_length
+    lines.u8(1); // maximum_operations_per_instruction
+    lines.u8(1); // default_is_stmt
+    lines.u8(LINE_BASE as u8);
+    lines.u8(LINE_RANGE);
+    lines.u8(OPCODE_BASE);
+    lines.data.extend_from_slice(&STANDARD_OPCODE_LENGTHS);
+
+    // no include_directories; file names are either absolute or relative to the comp_dir
+    lines.u8(0);
+
+    for file in files {
+        lines.string(&file.to_string_lossy());
+        lines.uleb128(0); // directory
+        lines.uleb128(0); // modification time
+        lines.uleb128(0); // length
+    }
+    lines.u8(0);
+    lines.patch_u32(header_length, (lines.data.len() - header_start) as u32);
+
+    // Procedures live in sections of their own, so each one is a sequence of its own.
+    for (proc, rows) in described {
+        lines.extended_opcode(1 + ADDRESS_SIZE as u64, DW_LNE_SET_ADDRESS);
+        lines.address(proc.proc_id);
+
+        // the registers every sequence starts with
+        let (mut offset, mut file, mut line) = (0, 1, 1);
+
+        for row in rows {
+            if row.offset != offset {
+                lines.u8(DW_LNS_ADVANCE_PC);
+                lines.uleb128(row.offset - offset);
+                offset = row.offset;
+            }
+            if row.file != file {
+                lines.u8(DW_LNS_SET_FILE);
+                lines.uleb128(row.file);
+                file = row.file;
+            }
+            if row.line != line {
+                lines.u8(DW_LNS_ADVANCE_LINE);
+                lines.sleb128(row.line as i64 - line as i64);
+                line = row.line;
+            }
+            lines.u8(DW_LNS_SET_COLUMN);
+            lines.uleb128(row.column);
+            lines.u8(DW_LNS_COPY);
+        }
+
+        lines.u8(DW_LNS_ADVANCE_PC);
+        lines.uleb128(proc.size - offset);
+        lines.extended_opcode(1, DW_LNE_END_SEQUENCE);
+    }
+    lines.finish_unit(unit_start);
+
+    abbrev.write_to(output, debug_abbrev);
+    info.write_to(output, debug_info);
+    lines.write_to(output, debug_line);
+}
+
+fn add_debug_section(output: &mut Object, name: &str) -> SectionId {
+    let section_name = match output.format() {
+        BinaryFormat::MachO => format!("__debug_{name}"),
+        _ => format!(".debug_{name}"),
+    };
+
+    output.add_section(
+        output.segment_name(StandardSegment::Debug).to_vec(),
+        section_name.into_bytes(),
+        SectionKind::Debug,
+    )
+}
+
+/// The contents of a debug section, and the relocations the linker needs to apply to them.
+#[derive(Default)]
+struct DwarfWriter {
+    data: Vec<u8>,
+    relocations: Vec<write::Relocation>,
+}
+
+impl DwarfWriter {
+    fn u8(&mut self, value: u8) {
+        self.data.push(value);
+    }
+
+    fn u16(&mut self, value: u16) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn uleb128(&mut self, mut value: u64) {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+
+            if value == 0 {
+                self.data.push(byte);
+                return;
+            }
+
+            self.data.push(byte | 0x80);
+        }
+    }
+
+    fn sleb128(&mut self, mut value: i64) {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+
+            let sign_bit_clear = byte & 0x40 == 0;
+            if (value == 0 && sign_bit_clear) || (value == -1 && !sign_bit_clear) {
+                self.data.push(byte);
+                return;
+            }
+
+            self.data.push(byte | 0x80);
+        }
+    }
+
+    fn string(&mut self, value: &str) {
+        self.data.extend_from_slice(value.as_bytes());
+        self.data.push(0);
+    }
+
+    fn attribute(&mut self, name: u64, form: u64) {
+        self.uleb128(name);
+        self.uleb128(form);
+    }
+
+    fn extended_opcode(&mut self, length: u64, opcode: u8) {
+        self.u8(0);
+        self.uleb128(length);
+        self.u8(opcode);
+    }
+
+    fn placeholder_u32(&mut self) -> usize {
+        let offset = self.data.len();
+        self.u32(0);
+
+        offset
+    }
+
+    fn patch_u32(&mut self, offset: usize, value: u32) {
+        self.data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    /// Starts a unit with a placeholder for its length, which `finish_unit` fills in.
+    fn unit_header(&mut self) -> usize {
+        self.placeholder_u32()
+    }
+
+    fn finish_unit(&mut self, unit_start: usize) {
+        let unit_length = self.data.len() - unit_start - 4;
+        self.patch_u32(unit_start, unit_length as u32);
+    }
+
+    /// The address of `symbol`, filled in by the linker.
+    fn address(&mut self, symbol: SymbolId) {
+        self.relocations.push(write::Relocation {
+            offset: self.data.len() as u64,
+            size: ADDRESS_SIZE * 8,
+            kind: RelocationKind::Absolute,
+            encoding: RelocationEncoding::Generic,
+            symbol,
+            addend: 0,
+        });
+
+        self.data.extend_from_slice(&[0; ADDRESS_SIZE as usize]);
+    }
+
+    /// An offset into another debug section. Linkers concatenate the debug sections of ELF
+    /// objects, so there it needs a relocation. Mach-O debug info stays in the object files,
+    /// where debuggers find it through the debug map, so the offset is final.
+    fn section_offset(&mut self, output: &mut Object, section: SectionId) {
+        if output.format() == BinaryFormat::Elf {
+            self.relocations.push(write::Relocation {
+                offset: self.data.len() as u64,
+                size: 32,
+                kind: RelocationKind::Absolute,
+                encoding: RelocationEncoding::Generic,
+                symbol: output.section_symbol(section),
+                addend: 0,
+            });
+        }
+
+        self.u32(0);
+    }
+
+    fn write_to(self, output: &mut Object, section: SectionId) {
+        output.set_section_data(section, self.data, 1);
+
+        for relocation in self.relocations {
+            if let Err(e) = output.add_relocation(section, relocation) {
+                internal_error!("{:?}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gimli::{ColumnType, EndianSlice, LittleEndian};
+    use object::write::{StandardSection, SymbolSection};
+    use object::{Architecture, Endianness, ObjectSection, SymbolFlags, SymbolKind, SymbolScope};
+    use roc_module::symbol::{IdentIds, ModuleIds};
+    use roc_region::all::Position;
+
+    use super::*;
+
+    const SRC: &str =
+        "app \"test\" provides [main] to \"./platform\"\n\nmain =\n    x = 1\n\n    x + 2\n";
+
+    fn region_of(needle: &str) -> Region {
+        Region::from_pos(Position::new(SRC.find(needle).unwrap() as u32))
+    }
+
+    #[test]
+    fn debug_sections_round_trip() {
+        let home = ModuleIds::default().get_or_insert(&"Test".into());
+        let mut ident_ids = IdentIds::default();
+        let main = Symbol::new(home, ident_ids.add_str("main"));
+        let x = Symbol::new(home, ident_ids.add_str("x"));
+
+        let mut sources = MutMap::default();
+        sources.insert(home, (PathBuf::from("Test.roc"), SRC.into()));
+
+        let mut definition_regions = MutMap::default();
+        definition_regions.insert(main, region_of("main ="));
+
+        let sources = DebugSources::new(&sources, definition_regions);
+
+        let mut output = Object::new(BinaryFormat::Elf, Architecture::X86_64, Endianness::Little);
+        let text = output.section_id(StandardSection::Text);
+        let proc_id = output.add_symbol(write::Symbol {
+            name: b"roc_main".to_vec(),
+            value: 0,
+            size: 0,
+            kind: SymbolKind::Text,
+            scope: SymbolScope::Linkage,
+            weak: false,
+            section: SymbolSection::Undefined,
+            flags: SymbolFlags::None,
+        });
+        output.add_symbol_data(proc_id, text, &[0x90; 16], 16);
+
+        let procs = [DebugProc {
+            fn_name: "roc_main".to_string(),
+            symbol: main,
+            proc_id,
+            size: 16,
+            locations: vec![(4, x, region_of("x = 1"))],
+        }];
+
+        add_debug_sections(&mut output, &sources, &procs);
+
+        let bytes = output.write().unwrap();
+        let file = object::File::parse(&*bytes).unwrap();
+        let dwarf = gimli::Dwarf::load(|id| -> Result<_, gimli::Error> {
+            let data = object::Object::section_by_name(&file, id.name())
+                .and_then(|section| section.data().ok())
+                .unwrap_or(&[]);
+
+            Ok(EndianSlice::new(data, LittleEndian))
+        })
+        .unwrap();
+
+        let header = dwarf.units().next().unwrap().unwrap();
+        let unit = dwarf.unit(header).unwrap();
+        let string = |value| {
+            let string = dwarf.attr_string(&unit, value).unwrap();
+            string.to_string().unwrap().to_string()
+        };
+
+        let mut subprograms = Vec::new();
+        let mut entries = unit.entries();
+        while let Some((_, entry)) = entries.next_dfs().unwrap() {
+            if entry.tag() == gimli::DW_TAG_subprogram {
+                let name = entry.attr_value(gimli::DW_AT_name).unwrap().unwrap();
+                let line = entry.attr_value(gimli::DW_AT_decl_line).unwrap().unwrap();
+
+                subprograms.push((string(name), line.udata_value().unwrap()));
+            }
+        }
+
+        assert_eq!(subprograms, [("roc_main".to_string(), 3)]);
+
+        let mut rows = unit.line_program.clone().unwrap().rows();
+        let mut lines = Vec::new();
+        while let Some((header, row)) = rows.next_row().unwrap() {
+            if row.end_sequence() {
+                assert_eq!(row.address(), 16);
+                continue;
+            }
+
+            let file = string(row.file(header).unwrap().path_name());
+            let column = match row.column() {
+                ColumnType::LeftEdge => 0,
+                ColumnType::Column(column) => column.get(),
+            };
+
+            lines.push((file, row.address(), row.line().unwrap().get(), column));
+        }
+
+        assert_eq!(
+            lines,
+            [
+                ("Test.roc".to_string(), 0, 3, 1),
+                ("Test.roc".to_string(), 4, 4, 5),
+            ]
+        );
+    }
+}
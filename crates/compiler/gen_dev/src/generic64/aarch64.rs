@@ -303,7 +303,7 @@ impl CallConv<AArch64GeneralReg, AArch64FloatReg, AArch64Assembler> for AArch64C
         AArch64GeneralReg::X14,
         // AArch64GeneralReg::X15, used in our assembly as a temporary register
         AArch64GeneralReg::IP0,
-        AArch64GeneralReg::IP1,
+        // AArch64GeneralReg::IP1, used as the temporary register when X15 is already in use
     ];
 
     // The first eight registers, v0-v7, are used to pass argument values
@@ -1248,13 +1248,7 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         src: AArch64GeneralReg,
         imm32: i32,
     ) {
-        if imm32 < 0 {
-            Self::sub_reg64_reg64_imm32(buf, dst, src, -imm32);
-        } else if imm32 < 0xFFF {
-            add_reg64_reg64_imm12(buf, dst, src, imm32 as u16);
-        } else {
-            todo!("immediate additions with values greater than 12bits");
-        }
+        Self::add_reg64_reg64_imm64(buf, dst, src, imm32 as i64);
     }
     #[inline(always)]
     fn add_reg64_reg64_reg64(
@@ -1336,6 +1330,23 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         });
     }
 
+    #[inline(always)]
+    fn local_data_pointer(
+        buf: &mut Vec<'_, u8>,
+        relocs: &mut Vec<'_, Relocation>,
+        data: std::vec::Vec<u8>,
+        dst: AArch64GeneralReg,
+    ) {
+        // the same `adrp` and `add` as in `data_pointer`
+        buf.extend((0x9000_0000u32 | dst.id() as u32).to_le_bytes());
+        Self::add_reg64_reg64_imm32(buf, dst, dst, 0);
+
+        relocs.push(Relocation::LocalData {
+            offset: buf.len() as u64 - 8,
+            data,
+        });
+    }
+
     #[inline(always)]
     fn imul_reg64_reg64_reg64(
         buf: &mut Vec<'_, u8>,
@@ -1455,10 +1466,15 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
 
     #[inline(always)]
     fn jmp_imm32(buf: &mut Vec<'_, u8>, offset: i32) -> usize {
+        // A jump must always have the same size so that it can be patched in place, so we cannot
+        // switch to a longer sequence once the real offset is known. `B` reaches +/-128MiB.
         if (-(1 << 27)..(1 << 27)).contains(&offset) {
             b_imm26(buf, offset);
         } else {
-            todo!("jump offsets over 27 bits for AArch64: {:#x}", offset);
+            internal_error!(
+                "jump offset {:#x} does not fit in 28 bits; the function is larger than 128MiB",
+                offset
+            );
         }
 
         // on aarch64, jumps are calculated from the start of the jmp instruction
@@ -1491,14 +1507,11 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
             cmp_reg64_reg64(buf, reg, tmp);
         }
 
-        if (-(1 << 20)..(1 << 20)).contains(&offset) {
-            b_cond_imm19(buf, ConditionCode::NE, offset);
-        } else {
-            todo!("jump offsets over 20 bits for AArch64: {:#x}", offset);
-        }
-
-        // on aarch64, jumps are calculated from the start of the jmp instruction
-        buf.len() - 4
+        // `B.cond` only reaches +/-1MiB, which large functions easily exceed. We do not know the
+        // final offset when this jump is first emitted, so always use an inverted-condition
+        // trampoline: skip over an unconditional `B` (+/-128MiB) if the values are equal.
+        b_cond_imm19(buf, ConditionCode::EQ, 8);
+        Self::jmp_imm32(buf, offset)
     }
 
     #[inline(always)]
@@ -1647,13 +1660,12 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
     ) {
         if (-256..256).contains(&offset) {
             ldur_reg_reg_imm9(buf, register_width, dst, src, offset as i16);
-        } else if (0..=u16::MAX as i32).contains(&offset) {
-            debug_assert!(offset % 8 == 0);
-            ldr_reg_reg_imm12(buf, register_width, dst, src, (offset as u16) >> 3);
+        } else if let Some(imm12) = scaled_offset_imm12(offset, register_width as u8) {
+            ldr_reg_reg_imm12(buf, register_width, dst, src, imm12);
         } else {
             let tmp = AArch64GeneralReg::X15;
             Self::mov_reg64_imm64(buf, tmp, offset as i64);
-            Self::add_reg64_reg64_reg64(buf, tmp, tmp, src);
+            add_reg64_reg64_reg64_extended(buf, tmp, src, tmp);
             ldr_reg_reg_imm12(buf, register_width, dst, tmp, 0);
         }
     }
@@ -1705,13 +1717,16 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
     ) {
         if (-256..256).contains(&offset) {
             stur_reg_reg_imm9(buf, register_width, src, dst, offset as i16);
-        } else if (0..=u16::MAX as i32).contains(&offset) {
-            debug_assert_eq!(offset % 8, 0);
-            str_reg_reg_imm12(buf, register_width, src, dst, (offset as u16) >> 3);
+        } else if let Some(imm12) = scaled_offset_imm12(offset, register_width as u8) {
+            str_reg_reg_imm12(buf, register_width, src, dst, imm12);
         } else {
-            let tmp = AArch64GeneralReg::X15;
+            // X15 is also our usual temporary for values, so it may well be the register we store
+            let tmp = match src {
+                AArch64GeneralReg::X15 => AArch64GeneralReg::IP1,
+                _ => AArch64GeneralReg::X15,
+            };
             Self::mov_reg64_imm64(buf, tmp, offset as i64);
-            Self::add_reg64_reg64_reg64(buf, tmp, tmp, dst);
+            add_reg64_reg64_reg64_extended(buf, tmp, dst, tmp);
             str_reg_reg_imm12(buf, register_width, src, tmp, 0);
         }
     }
@@ -1725,13 +1740,12 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
     ) {
         if (-256..256).contains(&offset) {
             stur_freg64_reg64_imm9(buf, src, dst, offset as i16)
-        } else if (0..=u16::MAX as i32).contains(&offset) {
-            debug_assert!(offset % 8 == 0);
-            str_freg64_reg64_imm12(buf, src, dst, (offset as u16) >> 3);
+        } else if let Some(imm12) = scaled_offset_imm12(offset, 3) {
+            str_freg64_reg64_imm12(buf, src, dst, imm12);
         } else {
             let tmp = AArch64GeneralReg::X15;
             Self::mov_reg64_imm64(buf, tmp, offset as i64);
-            Self::add_reg64_reg64_reg64(buf, tmp, tmp, dst);
+            add_reg64_reg64_reg64_extended(buf, tmp, dst, tmp);
             str_freg64_reg64_imm12(buf, src, tmp, 0);
         }
     }
@@ -1796,11 +1810,11 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
     #[inline(always)]
     fn mov_stack32_reg(
         buf: &mut Vec<'_, u8>,
-        _register_width: RegisterWidth,
+        register_width: RegisterWidth,
         offset: i32,
         src: AArch64GeneralReg,
     ) {
-        Self::mov_mem64_offset32_reg64(buf, AArch64GeneralReg::ZRSP, offset, src)
+        Self::mov_mem_offset32_reg(buf, register_width, AArch64GeneralReg::ZRSP, offset, src)
     }
     #[inline(always)]
     fn neg_reg64_reg64(buf: &mut Vec<'_, u8>, dst: AArch64GeneralReg, src: AArch64GeneralReg) {
@@ -1814,13 +1828,7 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         src: AArch64GeneralReg,
         imm32: i32,
     ) {
-        if imm32 < 0 {
-            Self::add_reg64_reg64_imm32(buf, dst, src, -imm32)
-        } else if imm32 < 0xFFF {
-            sub_reg64_reg64_imm12(buf, dst, src, imm32 as u16);
-        } else {
-            todo!("immediate subtractions with values greater than 12bits");
-        }
+        Self::add_reg64_reg64_imm64(buf, dst, src, -(imm32 as i64));
     }
     #[inline(always)]
     fn sub_reg64_reg64_reg64(
@@ -2057,13 +2065,12 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
     ) {
         if (-256..256).contains(&offset) {
             ldur_freg64_reg64_imm9(buf, dst, src, offset as i16)
-        } else if (0..=u16::MAX as i32).contains(&offset) {
-            debug_assert!(offset % 8 == 0);
-            ldr_freg64_reg64_imm12(buf, dst, src, (offset as u16) >> 3);
+        } else if let Some(imm12) = scaled_offset_imm12(offset, 3) {
+            ldr_freg64_reg64_imm12(buf, dst, src, imm12);
         } else {
             let tmp = AArch64GeneralReg::X15;
             Self::mov_reg64_imm64(buf, tmp, offset as i64);
-            Self::add_reg64_reg64_reg64(buf, tmp, tmp, src);
+            add_reg64_reg64_reg64_extended(buf, tmp, src, tmp);
             ldr_freg64_reg64_imm12(buf, dst, tmp, 0);
         }
     }
@@ -2074,18 +2081,67 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         src: AArch64GeneralReg,
         offset: i32,
     ) {
-        if offset < 0 {
-            ldur_freg64_reg64_imm9(buf, dst, src, offset as i16)
-        } else if (0..=u16::MAX as i32).contains(&offset) {
-            debug_assert!(offset % 8 == 0);
-            ldr_freg64_reg64_imm12(buf, dst, src, (offset as u16) >> 3);
+        Self::mov_freg64_mem64_offset32(buf, dst, src, offset)
+    }
+}
+
+impl AArch64Assembler {
+    /// Adds a signed immediate of any size to src and places the result into dst.
+    /// Register 31 is SP (not XZR) for both dst and src, so this can be used to adjust the stack.
+    fn add_reg64_reg64_imm64(
+        buf: &mut Vec<'_, u8>,
+        dst: AArch64GeneralReg,
+        src: AArch64GeneralReg,
+        imm: i64,
+    ) {
+        let magnitude = imm.unsigned_abs();
+
+        if magnitude <= 0xFFF {
+            if imm < 0 {
+                sub_reg64_reg64_imm12(buf, dst, src, magnitude as u16);
+            } else {
+                add_reg64_reg64_imm12(buf, dst, src, magnitude as u16);
+            }
+        } else if magnitude <= 0xFF_FFFF {
+            // split into a shifted upper and a plain lower 12-bit immediate
+            let upper = (magnitude >> 12) as u16;
+            let lower = (magnitude & 0xFFF) as u16;
+
+            if imm < 0 {
+                sub_reg64_reg64_imm12_lsl12(buf, dst, src, upper);
+                if lower != 0 {
+                    sub_reg64_reg64_imm12(buf, dst, dst, lower);
+                }
+            } else {
+                add_reg64_reg64_imm12_lsl12(buf, dst, src, upper);
+                if lower != 0 {
+                    add_reg64_reg64_imm12(buf, dst, dst, lower);
+                }
+            }
         } else {
-            todo!("base offsets over 32k for AArch64");
+            // X15 is our usual temporary, so it may well be the register we add to
+            let tmp = match src {
+                AArch64GeneralReg::X15 => AArch64GeneralReg::IP1,
+                _ => AArch64GeneralReg::X15,
+            };
+
+            Self::mov_reg64_imm64(buf, tmp, imm);
+            add_reg64_reg64_reg64_extended(buf, dst, src, tmp);
         }
     }
 }
 
-impl AArch64Assembler {}
+/// The `LDR`/`STR` unsigned offset form scales its 12-bit immediate by the access size.
+/// Returns that immediate if `offset` can be encoded for an access of `1 << size_log2` bytes.
+fn scaled_offset_imm12(offset: i32, size_log2: u8) -> Option<u16> {
+    let alignment_mask = (1 << size_log2) - 1;
+
+    if offset >= 0 && offset & alignment_mask == 0 && (offset >> size_log2) <= 0xFFF {
+        Some((offset >> size_log2) as u16)
+    } else {
+        None
+    }
+}
 
 // Instructions
 // ARM manual section C3
@@ -2282,6 +2338,87 @@ impl ArithmeticShifted {
     }
 }
 
+#[derive(Clone, Copy)]
+#[allow(dead_code)]
+enum ExtendType {
+    UXTB = 0b000,
+    UXTH = 0b001,
+    UXTW = 0b010,
+    UXTX = 0b011,
+    SXTB = 0b100,
+    SXTH = 0b101,
+    SXTW = 0b110,
+    SXTX = 0b111,
+}
+
+impl ExtendType {
+    #[inline(always)]
+    fn id(&self) -> u8 {
+        *self as u8
+    }
+}
+
+#[derive(PackedStruct)]
+#[packed_struct(endian = "msb")]
+pub struct ArithmeticExtended {
+    sf: bool,
+    op: bool, // add or subtract
+    s: bool,
+    fixed: Integer<u8, packed_bits::Bits<5>>, // = 0b01011,
+    opt: Integer<u8, packed_bits::Bits<2>>,   // = 0b00,
+    fixed2: bool,                             // = 0b1,
+    reg_m: Integer<u8, packed_bits::Bits<5>>,
+    option: Integer<u8, packed_bits::Bits<3>>,
+    imm3: Integer<u8, packed_bits::Bits<3>>,
+    reg_n: Integer<u8, packed_bits::Bits<5>>,
+    reg_d: Integer<u8, packed_bits::Bits<5>>,
+}
+
+impl Aarch64Bytes for ArithmeticExtended {}
+
+pub struct ArithmeticExtendedParams {
+    op: bool,
+    s: bool,
+    extend: ExtendType,
+    imm3: u8,
+    rm: AArch64GeneralReg,
+    rn: AArch64GeneralReg,
+    rd: AArch64GeneralReg,
+}
+
+impl ArithmeticExtended {
+    #[inline(always)]
+    fn new(
+        ArithmeticExtendedParams {
+            op,
+            s,
+            extend,
+            imm3,
+            rm,
+            rn,
+            rd,
+        }: ArithmeticExtendedParams,
+    ) -> Self {
+        debug_assert!(imm3 <= 0b100);
+
+        Self {
+            // true for 64 bit addition
+            // false for 32 bit addition
+            sf: true,
+            op,
+            s,
+            fixed: 0b01011.into(),
+            opt: 0b00.into(),
+            fixed2: true,
+            reg_m: rm.id().into(),
+            option: extend.id().into(),
+            imm3: imm3.into(),
+            reg_n: rn.id().into(),
+            reg_d: rd.id().into(),
+        }
+    }
+}
+
 // ARM manual section C1.2.4
 #[derive(Copy, Clone, PartialEq)]
 #[allow(dead_code)]
@@ -3177,6 +3314,26 @@ fn add_reg64_reg64_imm12(
     buf.extend(inst.bytes());
 }
 
+/// `ADD Xd, Xn, imm12, LSL #12` -> Add Xn and imm12 shifted left by 12 and place the result into Xd.
+#[inline(always)]
+fn add_reg64_reg64_imm12_lsl12(
+    buf: &mut Vec<'_, u8>,
+    dst: AArch64GeneralReg,
+    src: AArch64GeneralReg,
+    imm12: u16,
+) {
+    let inst = ArithmeticImmediate::new(ArithmeticImmediateParams {
+        op: false,
+        s: false,
+        sh: true,
+        imm12,
+        rd: dst,
+        rn: src,
+    });
+
+    buf.extend(inst.bytes());
+}
+
 /// `ADD Xd, Xm, Xn` -> Add Xm and Xn and place the result into Xd.
 #[inline(always)]
 fn add_reg64_reg64_reg64(
@@ -3198,6 +3355,28 @@ fn add_reg64_reg64_reg64(
     buf.extend(inst.bytes());
 }
 
+/// `ADD Xd, Xn, Xm, UXTX` -> Add Xn and Xm and place the result into Xd.
+/// Unlike the shifted register form, ZRSP is SP for both Xd and Xn.
+#[inline(always)]
+fn add_reg64_reg64_reg64_extended(
+    buf: &mut Vec<'_, u8>,
+    dst: AArch64GeneralReg,
+    src1: AArch64GeneralReg,
+    src2: AArch64GeneralReg,
+) {
+    let inst = ArithmeticExtended::new(ArithmeticExtendedParams {
+        op: false,
+        s: false,
+        extend: ExtendType::UXTX,
+        imm3: 0,
+        rm: src2,
+        rn: src1,
+        rd: dst,
+    });
+
+    buf.extend(inst.bytes());
+}
+
 /// `AND Xd, Xn, Xm` -> Bitwise AND Xn and Xm and place the result into Xd.
 #[inline(always)]
 fn and_reg64_reg64_reg64(
@@ -3748,6 +3927,26 @@ fn sub_reg64_reg64_imm12(
     buf.extend(inst.bytes());
 }
 
+/// `SUB Xd, Xn, imm12, LSL #12` -> Subtract imm12 shifted left by 12 from Xn and place the result into Xd.
+#[inline(always)]
+fn sub_reg64_reg64_imm12_lsl12(
+    buf: &mut Vec<'_, u8>,
+    dst: AArch64GeneralReg,
+    src: AArch64GeneralReg,
+    imm12: u16,
+) {
+    let inst = ArithmeticImmediate::new(ArithmeticImmediateParams {
+        op: true,
+        s: false,
+        rd: dst,
+        rn: src,
+        imm12,
+        sh: true,
+    });
+
+    buf.extend(inst.bytes());
+}
+
 /// `SUB Xd, Xm, Xn` -> Subtract Xm and Xn and place the result into Xd.
 #[inline(always)]
 fn sub_reg64_reg64_reg64(
@@ -4449,6 +4648,92 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_add_reg64_reg64_imm12_lsl12() {
+        disassembler_test!(
+            add_reg64_reg64_imm12_lsl12,
+            |reg1: AArch64GeneralReg, reg2: AArch64GeneralReg, imm| format!(
+                "add {}, {}, #0x{:x}, lsl #12",
+                reg1.capstone_string(UsesSP),
+                reg2.capstone_string(UsesSP),
+                imm
+            ),
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS,
+            [0x123]
+        );
+    }
+
+    #[test]
+    fn test_add_reg64_reg64_reg64_extended() {
+        disassembler_test!(
+            add_reg64_reg64_reg64_extended,
+            |reg1: AArch64GeneralReg, reg2: AArch64GeneralReg, reg3: AArch64GeneralReg| {
+                // UXTX with no shift is printed as a plain add when SP is involved
+                if reg1 == AArch64GeneralReg::ZRSP || reg2 == AArch64GeneralReg::ZRSP {
+                    format!(
+                        "add {}, {}, {}",
+                        reg1.capstone_string(UsesSP),
+                        reg2.capstone_string(UsesSP),
+                        reg3.capstone_string(UsesZR)
+                    )
+                } else {
+                    format!(
+                        "add {}, {}, {}, uxtx",
+                        reg1.capstone_string(UsesSP),
+                        reg2.capstone_string(UsesSP),
+                        reg3.capstone_string(UsesZR)
+                    )
+                }
+            },
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS
+        );
+    }
+
+    #[test]
+    fn test_add_reg64_reg64_imm32() {
+        disassembler_test!(
+            AArch64Assembler::add_reg64_reg64_imm32,
+            |reg1: AArch64GeneralReg, reg2: AArch64GeneralReg, imm: i32| {
+                let (op, magnitude) = if imm < 0 {
+                    ("sub", imm.unsigned_abs())
+                } else {
+                    ("add", imm as u32)
+                };
+                let reg1 = reg1.capstone_string(UsesSP);
+                let reg2 = reg2.capstone_string(UsesSP);
+
+                if magnitude <= 0xFFF {
+                    format!("{op} {reg1}, {reg2}, #0x{magnitude:x}")
+                } else if magnitude <= 0xFF_FFFF {
+                    format!(
+                        "{op} {reg1}, {reg2}, #0x{:x}, lsl #12\n{op} {reg1}, {reg1}, #0x{:x}",
+                        magnitude >> 12,
+                        magnitude & 0xFFF
+                    )
+                } else {
+                    // the immediate goes through a temporary, which must not be the source
+                    let tmp = if reg2 == "x15" { "x17" } else { "x15" };
+                    let extend = if reg1 == "sp" || reg2 == "sp" {
+                        ""
+                    } else {
+                        ", uxtx"
+                    };
+                    format!(
+                        "mov {tmp}, #0x{:x}\nmovk {tmp}, #0x{:x}, lsl #16\nadd {reg1}, {reg2}, {tmp}{extend}",
+                        magnitude & 0xFFFF,
+                        magnitude >> 16
+                    )
+                }
+            },
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS,
+            [0x123, -0x123, 0xFFF, 0x12_3456, -0x12_3456, 0x1234_5678]
+        );
+    }
+
     #[test]
     fn test_and_reg64_reg64_reg64() {
         disassembler_test!(
@@ -4999,6 +5284,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_sub_reg64_reg64_imm12_lsl12() {
+        disassembler_test!(
+            sub_reg64_reg64_imm12_lsl12,
+            |reg1: AArch64GeneralReg, reg2: AArch64GeneralReg, imm| format!(
+                "sub {}, {}, #0x{:x}, lsl #12",
+                reg1.capstone_string(UsesSP),
+                reg2.capstone_string(UsesSP),
+                imm
+            ),
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS,
+            [0x123]
+        );
+    }
+
     #[test]
     fn test_sub_reg64_reg64_reg64() {
         disassembler_test!(
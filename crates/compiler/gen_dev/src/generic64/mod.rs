@@ -9,14 +9,15 @@ use roc_error_macros::{internal_error, todo_lambda_erasure};
 use roc_module::symbol::{Interns, ModuleId, Symbol};
 use roc_mono::code_gen_help::{CallerProc, CodeGenHelp, HelperOp};
 use roc_mono::ir::{
-    BranchInfo, HigherOrderLowLevel, JoinPointId, ListLiteralElement, Literal, Param, ProcLayout,
-    SelfRecursive, Stmt,
+    static_list_bytes, BranchInfo, HigherOrderLowLevel, JoinPointId, ListLiteralElement, Literal,
+    Param, ProcLayout, SelfRecursive, Stmt,
 };
 use roc_mono::layout::{
     Builtin, InLayout, LambdaName, Layout, LayoutIds, LayoutInterner, LayoutRepr, STLayoutInterner,
     TagIdIntType, UnionLayout,
 };
 use roc_mono::low_level::HigherOrder;
+use roc_region::all::Region;
 use roc_target::TargetInfo;
 use std::marker::PhantomData;
 
@@ -252,6 +253,14 @@ pub trait Assembler<GeneralReg: RegTrait, FloatReg: RegTrait>: Sized + Copy {
         dst: GeneralReg,
     );
 
+    /// Loads a pointer to `data`, which is stored in the data section of the object file.
+    fn local_data_pointer(
+        buf: &mut Vec<'_, u8>,
+        relocs: &mut Vec<'_, Relocation>,
+        data: std::vec::Vec<u8>,
+        dst: GeneralReg,
+    );
+
     /// Jumps by an offset of offset bytes unconditionally.
     /// It should always generate the same number of bytes to enable replacement if offset changes.
     /// It returns the base offset to calculate the jump from (generally the instruction after the jump).
@@ -746,6 +755,8 @@ pub struct Backend64Bit<
     caller_procs: Vec<'a, CallerProc<'a>>,
     buf: Vec<'a, u8>,
     relocs: Vec<'a, Relocation>,
+    /// Where the code for the definitions in the procedure starts; see [Backend::mark_location].
+    locations: Vec<'a, (u64, Symbol, Region)>,
     proc_name: Option<String>,
     is_self_recursive: Option<SelfRecursive>,
 
@@ -786,6 +797,7 @@ pub fn new_backend_64bit<
         is_self_recursive: None,
         buf: bumpalo::vec![in env.arena],
         relocs: bumpalo::vec![in env.arena],
+        locations: bumpalo::vec![in env.arena],
         last_seen_map: MutMap::default(),
         layout_map: MutMap::default(),
         free_map: MutMap::default(),
@@ -873,6 +885,7 @@ impl<
         self.join_map.clear();
         self.free_map.clear();
         self.buf.clear();
+        self.locations.clear();
         self.storage_manager.reset();
     }
 
@@ -959,6 +972,12 @@ impl<
         // Add function body.
         out.extend(&self.buf[..self.buf.len() - end_jmp_size]);
 
+        // Locations are offsets into the body, which now starts after the stack setup.
+        let body_end = ret_offset as u64;
+        for (offset, _, _) in self.locations.iter_mut() {
+            *offset = Ord::min(*offset, body_end) + setup_offset as u64;
+        }
+
         // Cleanup stack.
         CC::cleanup_stack(
             &mut out,
@@ -994,6 +1013,16 @@ impl<
         (out, out_relocs)
     }
 
+    fn mark_location(&mut self, symbol: Symbol, region: Region) {
+        if self.env.debug_sources.is_some() {
+            self.locations.push((self.buf.len() as u64, symbol, region));
+        }
+    }
+
+    fn take_locations(&mut self) -> Vec<'a, (u64, Symbol, Region)> {
+        std::mem::replace(&mut self.locations, bumpalo::vec![in self.env.arena])
+    }
+
     fn load_args(&mut self, args: &'a [(InLayout<'a>, Symbol)], ret_layout: &InLayout<'a>) {
         CC::load_args(
             &mut self.buf,
@@ -1293,6 +1322,24 @@ impl<
                 let src_reg = self.storage_manager.load_to_float_reg(&mut self.buf, src);
                 ASM::abs_freg64_freg64(&mut self.buf, &mut self.relocs, dst_reg, src_reg);
             }
+            LayoutRepr::Builtin(Builtin::Int(int_width)) => {
+                self.build_fn_call(
+                    dst,
+                    bitcode::NUM_ABS_INT[int_width].to_string(),
+                    &[*src],
+                    &[*layout],
+                    layout,
+                );
+            }
+            LayoutRepr::Builtin(Builtin::Decimal) => {
+                self.build_fn_call(
+                    dst,
+                    bitcode::DEC_ABS.to_string(),
+                    &[*src],
+                    &[*layout],
+                    layout,
+                );
+            }
             x => todo!("NumAbs: layout, {:?}", x),
         }
     }
@@ -1321,6 +1368,15 @@ impl<
                 let src2_reg = self.storage_manager.load_to_float_reg(&mut self.buf, src2);
                 ASM::add_freg32_freg32_freg32(&mut self.buf, dst_reg, src1_reg, src2_reg);
             }
+            LayoutRepr::Builtin(Builtin::Int(int_width @ (IntWidth::I128 | IntWidth::U128))) => {
+                self.build_fn_call(
+                    dst,
+                    bitcode::NUM_ADD_OR_PANIC_INT[int_width].to_string(),
+                    &[*src1, *src2],
+                    &[*layout, *layout],
+                    layout,
+                );
+            }
             LayoutRepr::Builtin(Builtin::Decimal) => {
                 self.build_fn_call(
                     dst,
@@ -1330,7 +1386,29 @@ impl<
                     &Layout::DEC,
                 );
             }
-            x => todo!("NumAdd: layout, {:?}", x),
+            x => internal_error!("NumAdd: layout, {:?}", x),
+        }
+    }
+
+    fn build_num_add_wrap(
+        &mut self,
+        dst: &Symbol,
+        src1: &Symbol,
+        src2: &Symbol,
+        layout: &InLayout<'a>,
+    ) {
+        match self.layout_interner.get_repr(*layout) {
+            LayoutRepr::Builtin(Builtin::Int(int_width @ (IntWidth::I128 | IntWidth::U128))) => {
+                self.build_fn_call(
+                    dst,
+                    bitcode::NUM_ADD_WRAP_INT[int_width].to_string(),
+                    &[*src1, *src2],
+                    &[*layout, *layout],
+                    layout,
+                );
+            }
+            // smaller integers wrap in their registers anyway
+            _ => self.build_num_add(dst, src1, src2, layout),
         }
     }
 
@@ -1342,7 +1420,7 @@ impl<
         layout: InLayout<'a>,
     ) {
         match self.layout_interner.get_repr(layout) {
-            LayoutRepr::Builtin(Builtin::Int(width @ quadword_and_smaller!())) => {
+            LayoutRepr::Builtin(Builtin::Int(width)) => {
                 let intrinsic = bitcode::NUM_ADD_SATURATED_INT[width].to_string();
                 self.build_fn_call(&dst, intrinsic, &[src1, src2], &[layout, layout], &layout);
             }
@@ -1362,7 +1440,7 @@ impl<
                 let intrinsic = bitcode::DEC_ADD_SATURATED.to_string();
                 self.build_fn_call(&dst, intrinsic, &[src1, src2], &[layout, layout], &layout);
             }
-            x => todo!("NumAddSaturated: layout, {:?}", x),
+            x => internal_error!("NumAddSaturated: layout, {:?}", x),
         }
     }
 
@@ -1415,9 +1493,30 @@ impl<
     }
 
     fn build_num_mul(&mut self, dst: &Symbol, src1: &Symbol, src2: &Symbol, layout: &InLayout<'a>) {
-        // for the time being, `num_mul` is implemented as wrapping multiplication. In roc, the normal
-        // `mul` should panic on overflow, but we just don't do that yet
-        self.build_num_mul_wrap(dst, src1, src2, layout)
+        match self.layout_interner.get_repr(*layout) {
+            LayoutRepr::Builtin(Builtin::Int(int_width @ (IntWidth::I128 | IntWidth::U128))) => {
+                self.build_fn_call(
+                    dst,
+                    bitcode::NUM_MUL_OR_PANIC_INT[int_width].to_string(),
+                    &[*src1, *src2],
+                    &[*layout, *layout],
+                    layout,
+                );
+            }
+            LayoutRepr::Builtin(Builtin::Decimal) => {
+                self.build_fn_call(
+                    dst,
+                    bitcode::DEC_MUL_OR_PANIC.to_string(),
+                    &[*src1, *src2],
+                    &[*layout, *layout],
+                    layout,
+                );
+            }
+            // for the time being, `num_mul` is implemented as wrapping multiplication for the
+            // other numbers. In roc, the normal `mul` should panic on overflow, but we just don't
+            // do that yet
+            _ => self.build_num_mul_wrap(dst, src1, src2, layout),
+        }
     }
 
     fn build_num_mul_wrap(
@@ -1461,13 +1560,7 @@ impl<
                     src2_reg,
                 );
             }
-            LayoutRepr::Builtin(Builtin::Int(IntWidth::I128 | IntWidth::U128)) => {
-                let int_width = match *layout {
-                    Layout::I128 => IntWidth::I128,
-                    Layout::U128 => IntWidth::U128,
-                    _ => unreachable!(),
-                };
-
+            LayoutRepr::Builtin(Builtin::Int(int_width @ (IntWidth::I128 | IntWidth::U128))) => {
                 self.build_fn_call(
                     dst,
                     bitcode::NUM_MUL_WRAP_INT[int_width].to_string(),
@@ -1488,7 +1581,7 @@ impl<
                 let src2_reg = self.storage_manager.load_to_float_reg(&mut self.buf, src2);
                 ASM::mul_freg32_freg32_freg32(&mut self.buf, dst_reg, src1_reg, src2_reg);
             }
-            x => todo!("NumMulWrap: layout, {:?}", x),
+            x => internal_error!("NumMulWrap: layout, {:?}", x),
         }
     }
 
@@ -1500,7 +1593,7 @@ impl<
         layout: InLayout<'a>,
     ) {
         match self.layout_interner.get_repr(layout) {
-            LayoutRepr::Builtin(Builtin::Int(width @ quadword_and_smaller!())) => {
+            LayoutRepr::Builtin(Builtin::Int(width)) => {
                 let intrinsic = bitcode::NUM_MUL_SATURATED_INT[width].to_string();
                 self.build_fn_call(&dst, intrinsic, &[src1, src2], &[layout, layout], &layout);
             }
@@ -1520,7 +1613,7 @@ impl<
                 let intrinsic = bitcode::DEC_MUL_SATURATED.to_string();
                 self.build_fn_call(&dst, intrinsic, &[src1, src2], &[layout, layout], &layout);
             }
-            x => todo!("NumMulSaturated: layout, {:?}", x),
+            x => internal_error!("NumMulSaturated: layout, {:?}", x),
         }
     }
 
@@ -1600,7 +1693,25 @@ impl<
                 let src2_reg = self.storage_manager.load_to_float_reg(&mut self.buf, src2);
                 ASM::div_freg32_freg32_freg32(&mut self.buf, dst_reg, src1_reg, src2_reg);
             }
-            x => todo!("NumDiv: layout, {:?}", x),
+            LayoutRepr::Builtin(Builtin::Int(int_width @ (IntWidth::I128 | IntWidth::U128))) => {
+                self.build_fn_call(
+                    dst,
+                    bitcode::NUM_DIV_TRUNC_INT[int_width].to_string(),
+                    &[*src1, *src2],
+                    &[*layout, *layout],
+                    layout,
+                );
+            }
+            LayoutRepr::Builtin(Builtin::Decimal) => {
+                self.build_fn_call(
+                    dst,
+                    bitcode::DEC_DIV.to_string(),
+                    &[*src1, *src2],
+                    &[*layout, *layout],
+                    layout,
+                );
+            }
+            x => internal_error!("NumDiv: layout, {:?}", x),
         }
     }
 
@@ -1619,7 +1730,7 @@ impl<
                 &[*layout, *layout],
                 layout,
             ),
-            x => todo!("NumDivCeilUnchecked: layout, {:?}", x),
+            x => internal_error!("NumDivCeilUnchecked: layout, {:?}", x),
         }
     }
 
@@ -1663,25 +1774,73 @@ impl<
                     src2_reg,
                 );
             }
-            x => todo!("NumDiv: layout, {:?}", x),
+            LayoutRepr::Builtin(Builtin::Int(int_width @ (IntWidth::I128 | IntWidth::U128))) => {
+                self.build_fn_call(
+                    dst,
+                    bitcode::NUM_REM_INT[int_width].to_string(),
+                    &[*src1, *src2],
+                    &[*layout, *layout],
+                    layout,
+                );
+            }
+            x => internal_error!("NumRem: layout, {:?}", x),
         }
     }
 
     fn build_num_neg(&mut self, dst: &Symbol, src: &Symbol, layout: &InLayout<'a>) {
         match self.layout_interner.get_repr(*layout) {
-            LayoutRepr::Builtin(Builtin::Int(IntWidth::I64 | IntWidth::U64)) => {
+            LayoutRepr::Builtin(Builtin::Int(quadword_and_smaller!())) => {
                 let dst_reg = self.storage_manager.claim_general_reg(&mut self.buf, dst);
                 let src_reg = self.storage_manager.load_to_general_reg(&mut self.buf, src);
                 ASM::neg_reg64_reg64(&mut self.buf, dst_reg, src_reg);
             }
+            LayoutRepr::Builtin(Builtin::Int(int_width @ (IntWidth::I128 | IntWidth::U128))) => {
+                self.build_fn_call(
+                    dst,
+                    bitcode::NUM_NEGATE_INT[int_width].to_string(),
+                    &[*src],
+                    &[*layout],
+                    layout,
+                );
+            }
+            LayoutRepr::Builtin(Builtin::Decimal) => {
+                self.build_fn_call(
+                    dst,
+                    bitcode::DEC_NEGATE.to_string(),
+                    &[*src],
+                    &[*layout],
+                    layout,
+                );
+            }
             x => todo!("NumNeg: layout, {:?}", x),
         }
     }
 
     fn build_num_sub(&mut self, dst: &Symbol, src1: &Symbol, src2: &Symbol, layout: &InLayout<'a>) {
-        // for the time being, `num_sub` is implemented as wrapping subtraction. In roc, the normal
-        // `sub` should panic on overflow, but we just don't do that yet
-        self.build_num_sub_wrap(dst, src1, src2, layout)
+        match self.layout_interner.get_repr(*layout) {
+            LayoutRepr::Builtin(Builtin::Int(int_width @ (IntWidth::I128 | IntWidth::U128))) => {
+                self.build_fn_call(
+                    dst,
+                    bitcode::NUM_SUB_OR_PANIC_INT[int_width].to_string(),
+                    &[*src1, *src2],
+                    &[*layout, *layout],
+                    layout,
+                );
+            }
+            LayoutRepr::Builtin(Builtin::Decimal) => {
+                self.build_fn_call(
+                    dst,
+                    bitcode::DEC_SUB_OR_PANIC.to_string(),
+                    &[*src1, *src2],
+                    &[*layout, *layout],
+                    layout,
+                );
+            }
+            // for the time being, `num_sub` is implemented as wrapping subtraction for the
+            // other numbers. In roc, the normal `sub` should panic on overflow, but we just don't
+            // do that yet
+            _ => self.build_num_sub_wrap(dst, src1, src2, layout),
+        }
     }
 
     fn build_num_sub_wrap(
@@ -1702,6 +1861,15 @@ impl<
                     .load_to_general_reg(&mut self.buf, src2);
                 ASM::sub_reg64_reg64_reg64(&mut self.buf, dst_reg, src1_reg, src2_reg);
             }
+            LayoutRepr::Builtin(Builtin::Int(int_width @ (IntWidth::I128 | IntWidth::U128))) => {
+                self.build_fn_call(
+                    dst,
+                    bitcode::NUM_SUB_WRAP_INT[int_width].to_string(),
+                    &[*src1, *src2],
+                    &[*layout, *layout],
+                    layout,
+                );
+            }
             x => todo!("NumSubWrap: layout, {:?}", x),
         }
     }
@@ -1920,7 +2088,7 @@ impl<
 
                 ASM::mov_reg64_reg64(&mut self.buf, dst_reg, src_reg);
             }
-            x => todo!("Not: layout, {:?}", x),
+            x => internal_error!("Not: layout, {:?}", x),
         }
     }
 
@@ -1936,7 +2104,7 @@ impl<
             Layout::F64 => self.num_to_f64(dst, src, arg_layout),
             Layout::DEC => self.num_to_dec(dst, src, arg_layout),
 
-            other => todo!("NumToFrac: layout {other:?} is not Frac"),
+            other => internal_error!("NumToFrac: layout {other:?} is not Frac"),
         }
     }
 
@@ -3164,6 +3332,26 @@ impl<
         let element_width = self.layout_interner.stack_size(*element_in_layout) as u64;
         let element_alignment = self.layout_interner.alignment_bytes(*element_in_layout) as u64;
 
+        if let Some(bytes) = static_list_bytes(self.layout_interner, *element_in_layout, elements) {
+            // All elements are known numbers, so the list lives in the data section. It is
+            // preceded by a refcount of 0, which marks it as a constant that is never freed.
+            let header_size = Ord::max(8, element_alignment) as usize;
+            let mut data = vec![0; header_size];
+            data.extend(bytes);
+
+            let data_symbol = self.debug_symbol("list_data");
+            let reg = self
+                .storage_manager
+                .claim_general_reg(&mut self.buf, &data_symbol);
+            ASM::local_data_pointer(&mut self.buf, &mut self.relocs, data, reg);
+            ASM::add_reg64_reg64_imm32(&mut self.buf, reg, reg, header_size as i32);
+
+            self.store_list_on_stack(sym, reg, elements.len(), element_alignment);
+            self.free_symbol(&data_symbol);
+
+            return;
+        }
+
         // load the total size of the data we want to store (excludes refcount)
         let data_bytes_symbol = self.debug_symbol("data_bytes");
         let data_bytes = element_width * elements.len() as u64;
@@ -3243,20 +3431,7 @@ impl<
             }
         }
 
-        // Setup list on stack.
-        self.storage_manager.with_tmp_general_reg(
-            &mut self.buf,
-            |storage_manager, buf, tmp_reg| {
-                let alignment = Ord::max(8, element_alignment) as u32;
-                let base_offset =
-                    storage_manager.claim_stack_area_with_alignment(*sym, 24, alignment);
-                ASM::mov_base32_reg64(buf, base_offset, ptr_reg);
-
-                ASM::mov_reg64_imm64(buf, tmp_reg, elements.len() as i64);
-                ASM::mov_base32_reg64(buf, base_offset + 8, tmp_reg);
-                ASM::mov_base32_reg64(buf, base_offset + 16, tmp_reg);
-            },
-        );
+        self.store_list_on_stack(sym, ptr_reg, elements.len(), element_alignment);
         self.free_symbol(&allocation_symbol);
     }
 
@@ -4340,8 +4515,8 @@ impl<
                         },
                     );
                 } else {
-                    // load large string (pretend it's a `List U8`). We should move this data into
-                    // the binary eventually because our RC algorithm won't free this value
+                    // load large string (pretend it's a `List U8`), which puts its bytes in
+                    // the data section
                     let elements: Vec<_> = x
                         .as_bytes()
                         .iter()
@@ -4422,7 +4597,9 @@ impl<
         let buf = &mut self.buf;
 
         match int_width {
-            IntWidth::U128 | IntWidth::I128 => todo!(),
+            IntWidth::U128 | IntWidth::I128 => {
+                self.build_128bit_halves_op(dst, src1, src2, ASM::and_reg64_reg64_reg64)
+            }
             _ => {
                 let dst_reg = self.storage_manager.claim_general_reg(buf, dst);
                 let src1_reg = self.storage_manager.load_to_general_reg(buf, src1);
@@ -4442,7 +4619,9 @@ impl<
         let buf = &mut self.buf;
 
         match int_width {
-            IntWidth::U128 | IntWidth::I128 => todo!(),
+            IntWidth::U128 | IntWidth::I128 => {
+                self.build_128bit_halves_op(dst, src1, src2, ASM::or_reg64_reg64_reg64)
+            }
             _ => {
                 let dst_reg = self.storage_manager.claim_general_reg(buf, dst);
                 let src1_reg = self.storage_manager.load_to_general_reg(buf, src1);
@@ -4462,7 +4641,9 @@ impl<
         let buf = &mut self.buf;
 
         match int_width {
-            IntWidth::U128 | IntWidth::I128 => todo!(),
+            IntWidth::U128 | IntWidth::I128 => {
+                self.build_128bit_halves_op(dst, src1, src2, ASM::xor_reg64_reg64_reg64)
+            }
             _ => {
                 let dst_reg = self.storage_manager.claim_general_reg(buf, dst);
                 let src1_reg = self.storage_manager.load_to_general_reg(buf, src1);
@@ -4482,7 +4663,21 @@ impl<
         let buf = &mut self.buf;
 
         match int_width {
-            IntWidth::U128 | IntWidth::I128 => todo!(),
+            IntWidth::U128 | IntWidth::I128 => {
+                let layout = match int_width {
+                    IntWidth::I128 => Layout::I128,
+                    IntWidth::U128 => Layout::U128,
+                    _ => unreachable!(),
+                };
+
+                self.build_fn_call(
+                    dst,
+                    bitcode::NUM_SHIFT_LEFT[int_width].to_string(),
+                    &[*src1, *src2],
+                    &[layout, Layout::U8],
+                    &layout,
+                );
+            }
             _ => {
                 let dst_reg = self.storage_manager.claim_general_reg(buf, dst);
                 let src1_reg = self.storage_manager.load_to_general_reg(buf, src1);
@@ -4509,7 +4704,21 @@ impl<
         let buf = &mut self.buf;
 
         match int_width {
-            IntWidth::U128 | IntWidth::I128 => todo!(),
+            IntWidth::U128 | IntWidth::I128 => {
+                let layout = match int_width {
+                    IntWidth::I128 => Layout::I128,
+                    IntWidth::U128 => Layout::U128,
+                    _ => unreachable!(),
+                };
+
+                self.build_fn_call(
+                    dst,
+                    bitcode::NUM_SHIFT_RIGHT[int_width].to_string(),
+                    &[*src1, *src2],
+                    &[layout, Layout::U8],
+                    &layout,
+                );
+            }
             _ => {
                 let dst_reg = self.storage_manager.claim_general_reg(buf, dst);
                 let src1_reg = self.storage_manager.load_to_general_reg(buf, src1);
@@ -4587,7 +4796,7 @@ impl<
                     dst,
                     bitcode::NUM_SHIFT_RIGHT_ZERO_FILL[int_width].to_string(),
                     &[*src1, *src2],
-                    &[layout, layout],
+                    &[layout, Layout::U8],
                     &layout,
                 );
             }
@@ -4628,20 +4837,33 @@ impl<
     ) {
         use IntWidth::*;
 
-        let buf = &mut self.buf;
-
         match (source, target) {
-            (U128, U64) => {
-                let dst_reg = self.storage_manager.claim_general_reg(buf, dst);
+            (U128 | I128, U128 | I128) => {
+                // the bits stay the same, only their interpretation changes
+                self.copy_128bit(dst, src);
 
+                return;
+            }
+            (U128 | I128, _) => {
+                // keep the lower 8 bytes, and then truncate those like any 64-bit integer
                 let (offset, _size) = self.storage_manager.stack_offset_and_size(src);
 
-                ASM::mov_reg64_base32(buf, dst_reg, offset + 8);
+                let lower = self.debug_symbol("int_cast_lower_half");
+                let lower_reg = self
+                    .storage_manager
+                    .claim_general_reg(&mut self.buf, &lower);
+                ASM::mov_reg64_base32(&mut self.buf, lower_reg, offset);
+
+                self.build_num_int_cast(dst, &lower, U64, target);
+                self.free_symbol(&lower);
 
                 return;
             }
-            (U64, U128) => {
-                let src_reg = self.storage_manager.load_to_general_reg(buf, src);
+            (_, U128 | I128) => {
+                // extend to 64 bits first, then fill the upper 8 bytes with copies of the sign bit
+                let wide_width = if source.is_signed() { I64 } else { U64 };
+                let wide = self.debug_symbol("int_cast_wide");
+                self.build_num_int_cast(&wide, src, source, wide_width);
 
                 let base_offset = self.storage_manager.claim_stack_area_layout(
                     self.layout_interner,
@@ -4649,77 +4871,74 @@ impl<
                     Layout::U128,
                 );
 
-                let tmp = Symbol::DEV_TMP;
-                let tmp_reg = self.storage_manager.claim_general_reg(buf, &tmp);
-
-                // move a zero into the lower 8 bytes
-                ASM::mov_reg64_imm64(buf, tmp_reg, 0x0);
-                ASM::mov_base32_reg64(buf, base_offset, tmp_reg);
+                let buf = &mut self.buf;
+                let wide_reg = self.storage_manager.load_to_general_reg(buf, &wide);
+                ASM::mov_base32_reg64(buf, base_offset, wide_reg);
 
-                ASM::mov_base32_reg64(buf, base_offset + 8, src_reg);
+                if source.is_signed() {
+                    self.storage_manager.with_tmp_general_reg(
+                        buf,
+                        |storage_manager, buf, tmp_reg| {
+                            ASM::mov_reg64_imm64(buf, tmp_reg, 63);
+                            ASM::sar_reg64_reg64_reg64(
+                                buf,
+                                storage_manager,
+                                wide_reg,
+                                wide_reg,
+                                tmp_reg,
+                            );
+                        },
+                    );
+                } else {
+                    ASM::mov_reg64_imm64(buf, wide_reg, 0);
+                }
+                ASM::mov_base32_reg64(buf, base_offset + 8, wide_reg);
 
-                self.free_symbol(&tmp);
+                self.free_symbol(&wide);
 
                 return;
             }
-
             _ => {}
         }
 
+        let buf = &mut self.buf;
+
         let dst_reg = self.storage_manager.claim_general_reg(buf, dst);
         let src_reg = self.storage_manager.load_to_general_reg(buf, src);
 
-        if source.stack_size() == target.stack_size() {
-            match source.stack_size() {
+        match source.stack_size().cmp(&target.stack_size()) {
+            std::cmp::Ordering::Equal => match source.stack_size() {
                 8 => ASM::mov_reg64_reg64(buf, dst_reg, src_reg),
                 4 => ASM::mov_reg32_reg32(buf, dst_reg, src_reg),
                 2 => ASM::mov_reg16_reg16(buf, dst_reg, src_reg),
                 1 => ASM::mov_reg8_reg8(buf, dst_reg, src_reg),
-                _ => todo!("int cast from {source:?} to {target:?}"),
-            }
-        } else {
-            match (source, target) {
+                _ => internal_error!("128-bit int casts are handled above"),
+            },
+            std::cmp::Ordering::Less => {
                 // -- CASTING UP --
-                (I8 | U8, U16 | U32 | U64) => {
-                    // zero  out the register
-                    ASM::xor_reg64_reg64_reg64(buf, dst_reg, dst_reg, dst_reg);
-
-                    // move the 8-bit integer
-                    ASM::mov_reg_reg(buf, RegisterWidth::W8, dst_reg, src_reg);
-                }
-                (U16, U32 | U64) => {
-                    // zero  out the register
-                    ASM::xor_reg64_reg64_reg64(buf, dst_reg, dst_reg, dst_reg);
-
-                    // move the 16-bit integer
-                    ASM::mov_reg_reg(buf, RegisterWidth::W16, dst_reg, src_reg);
-                }
-                (U32, U64) => {
-                    // zero  out the register
-                    ASM::xor_reg64_reg64_reg64(buf, dst_reg, dst_reg, dst_reg);
+                // extend according to the source's signedness, so every value that fits is preserved
+                let source_width = match source.stack_size() {
+                    4 => RegisterWidth::W32,
+                    2 => RegisterWidth::W16,
+                    _ => RegisterWidth::W8,
+                };
 
-                    // move the 32-bit integer
-                    ASM::mov_reg_reg(buf, RegisterWidth::W32, dst_reg, src_reg);
-                }
-                (I8, I16 | I32 | I64) => {
-                    ASM::movsx_reg_reg(buf, RegisterWidth::W8, dst_reg, src_reg)
+                if source.is_signed() {
+                    ASM::movsx_reg_reg(buf, source_width, dst_reg, src_reg);
+                } else {
+                    ASM::movzx_reg_reg(buf, source_width, dst_reg, src_reg);
                 }
-                (I16, I32 | I64) => ASM::movsx_reg_reg(buf, RegisterWidth::W16, dst_reg, src_reg),
-                (I32, I64) => ASM::movsx_reg_reg(buf, RegisterWidth::W32, dst_reg, src_reg),
+            }
+            std::cmp::Ordering::Greater => {
                 // -- CASTING DOWN --
-                (U64 | I64, I32 | U32) => {
-                    // move as a 32-bit integer (leaving any other bits behind)
-                    ASM::mov_reg_reg(buf, RegisterWidth::W32, dst_reg, src_reg);
-                }
-                (U64 | I64 | U32 | I32, I16 | U16) => {
-                    // move as a 16-bit integer (leaving any other bits behind)
-                    ASM::mov_reg_reg(buf, RegisterWidth::W16, dst_reg, src_reg);
-                }
-                (U64 | I64 | U32 | I32 | U16 | I16, I8 | U8) => {
-                    // move as an 8-bit integer (leaving any other bits behind)
-                    ASM::mov_reg_reg(buf, RegisterWidth::W8, dst_reg, src_reg);
-                }
-                _ => todo!("int cast from {source:?} to {target:?}"),
+                // move only the target's bits (leaving any other bits behind)
+                let target_width = match target.stack_size() {
+                    4 => RegisterWidth::W32,
+                    2 => RegisterWidth::W16,
+                    _ => RegisterWidth::W8,
+                };
+
+                ASM::mov_reg_reg(buf, target_width, dst_reg, src_reg);
             }
         }
     }
@@ -4785,9 +5004,48 @@ impl<
     }
 
     fn num_to_f32(&mut self, dst: &Symbol, src: &Symbol, arg_layout: &InLayout<'a>) {
+        match self.layout_interner.get_repr(*arg_layout) {
+            LayoutRepr::Builtin(Builtin::Int(
+                int_width @ (IntWidth::I8
+                | IntWidth::I16
+                | IntWidth::I32
+                | IntWidth::U8
+                | IntWidth::U16
+                | IntWidth::U32),
+            )) => {
+                // these all fit in an I64, which we can convert with a single instruction
+                let wide = self.debug_symbol("int_to_float_wide");
+                self.build_num_int_cast(&wide, src, int_width, IntWidth::I64);
+                self.num_to_f32(dst, &wide, &Layout::I64);
+                self.free_symbol(&wide);
+                return;
+            }
+            LayoutRepr::Builtin(Builtin::Int(
+                int_width @ (IntWidth::U64 | IntWidth::I128 | IntWidth::U128),
+            )) => {
+                self.build_fn_call(
+                    dst,
+                    bitcode::NUM_INT_TO_F32[int_width].to_string(),
+                    &[*src],
+                    &[*arg_layout],
+                    &Layout::F32,
+                );
+                return;
+            }
+            LayoutRepr::Builtin(Builtin::Decimal) => {
+                // the builtins can only turn a Dec into an F64
+                let as_f64 = self.debug_symbol("dec_as_f64");
+                self.num_to_f64(&as_f64, src, arg_layout);
+                self.num_to_f32(dst, &as_f64, &Layout::F64);
+                self.free_symbol(&as_f64);
+                return;
+            }
+            _ => {}
+        }
+
         let dst_reg = self.storage_manager.claim_float_reg(&mut self.buf, dst);
         match self.layout_interner.get_repr(*arg_layout) {
-            LayoutRepr::Builtin(Builtin::Int(IntWidth::I32 | IntWidth::I64)) => {
+            LayoutRepr::Builtin(Builtin::Int(IntWidth::I64)) => {
                 let src_reg = self.storage_manager.load_to_general_reg(&mut self.buf, src);
                 ASM::to_float_freg32_reg64(&mut self.buf, dst_reg, src_reg);
             }
@@ -4799,14 +5057,55 @@ impl<
                 let src_reg = self.storage_manager.load_to_float_reg(&mut self.buf, src);
                 ASM::mov_freg64_freg64(&mut self.buf, dst_reg, src_reg);
             }
-            arg => todo!("NumToFrac: layout, arg {arg:?}, ret {:?}", Layout::F32),
+            arg => internal_error!("NumToFrac: layout, arg {arg:?}, ret {:?}", Layout::F32),
         }
     }
 
     fn num_to_f64(&mut self, dst: &Symbol, src: &Symbol, arg_layout: &InLayout<'a>) {
+        match self.layout_interner.get_repr(*arg_layout) {
+            LayoutRepr::Builtin(Builtin::Int(
+                int_width @ (IntWidth::I8
+                | IntWidth::I16
+                | IntWidth::I32
+                | IntWidth::U8
+                | IntWidth::U16
+                | IntWidth::U32),
+            )) => {
+                // these all fit in an I64, which we can convert with a single instruction
+                let wide = self.debug_symbol("int_to_float_wide");
+                self.build_num_int_cast(&wide, src, int_width, IntWidth::I64);
+                self.num_to_f64(dst, &wide, &Layout::I64);
+                self.free_symbol(&wide);
+                return;
+            }
+            LayoutRepr::Builtin(Builtin::Int(
+                int_width @ (IntWidth::U64 | IntWidth::I128 | IntWidth::U128),
+            )) => {
+                self.build_fn_call(
+                    dst,
+                    bitcode::NUM_INT_TO_F64[int_width].to_string(),
+                    &[*src],
+                    &[*arg_layout],
+                    &Layout::F64,
+                );
+                return;
+            }
+            LayoutRepr::Builtin(Builtin::Decimal) => {
+                self.build_fn_call(
+                    dst,
+                    bitcode::DEC_TO_F64.to_string(),
+                    &[*src],
+                    &[*arg_layout],
+                    &Layout::F64,
+                );
+                return;
+            }
+            _ => {}
+        }
+
         let dst_reg = self.storage_manager.claim_float_reg(&mut self.buf, dst);
         match self.layout_interner.get_repr(*arg_layout) {
-            LayoutRepr::Builtin(Builtin::Int(IntWidth::I32 | IntWidth::I64)) => {
+            LayoutRepr::Builtin(Builtin::Int(IntWidth::I64)) => {
                 let src_reg = self.storage_manager.load_to_general_reg(&mut self.buf, src);
                 ASM::to_float_freg64_reg64(&mut self.buf, dst_reg, src_reg);
             }
@@ -4818,7 +5117,7 @@ impl<
                 let src_reg = self.storage_manager.load_to_float_reg(&mut self.buf, src);
                 ASM::mov_freg64_freg64(&mut self.buf, dst_reg, src_reg);
             }
-            arg => todo!("NumToFrac: layout, arg {arg:?}, ret {:?}", Layout::F64),
+            arg => internal_error!("NumToFrac: layout, arg {arg:?}, ret {:?}", Layout::F64),
         }
     }
 
@@ -4833,9 +5132,69 @@ impl<
                     &Layout::DEC,
                 );
             }
+            LayoutRepr::Builtin(Builtin::Float(float_width)) => {
+                self.build_fn_call(
+                    dst,
+                    bitcode::DEC_FROM_FLOAT[float_width].to_string(),
+                    &[*src],
+                    &[*arg_layout],
+                    &Layout::DEC,
+                );
+            }
+            LayoutRepr::Builtin(Builtin::Decimal) => self.copy_128bit(dst, src),
+            arg => internal_error!("NumToFrac: layout, arg {arg:?}, ret {:?}", Layout::DEC),
+        }
+    }
 
-            arg => todo!("NumToFrac: layout, arg {arg:?}, ret {:?}", Layout::DEC),
+    /// Copies a 128-bit value (an I128, U128 or Dec) into a fresh stack slot for `dst`.
+    fn copy_128bit(&mut self, dst: &Symbol, src: &Symbol) {
+        let base_offset =
+            self.storage_manager
+                .claim_stack_area_layout(self.layout_interner, *dst, Layout::U128);
+
+        self.storage_manager.copy_symbol_to_stack_offset(
+            self.layout_interner,
+            &mut self.buf,
+            base_offset,
+            src,
+            &Layout::U128,
+        );
+    }
+
+    /// Applies a 64-bit operation to the lower and upper halves of two 128-bit values
+    /// separately. That is only correct for operations that treat every bit on its own, like
+    /// the bitwise ones.
+    fn build_128bit_halves_op(
+        &mut self,
+        dst: &Symbol,
+        src1: &Symbol,
+        src2: &Symbol,
+        op: fn(&mut Vec<'a, u8>, GeneralReg, GeneralReg, GeneralReg),
+    ) {
+        let (src1_offset, _) = self.storage_manager.stack_offset_and_size(src1);
+        let (src2_offset, _) = self.storage_manager.stack_offset_and_size(src2);
+
+        let base_offset =
+            self.storage_manager
+                .claim_stack_area_layout(self.layout_interner, *dst, Layout::U128);
+
+        let tmp1_symbol = self.debug_symbol("halves_tmp1");
+        let tmp2_symbol = self.debug_symbol("halves_tmp2");
+
+        let buf = &mut self.buf;
+
+        let tmp1 = self.storage_manager.claim_general_reg(buf, &tmp1_symbol);
+        let tmp2 = self.storage_manager.claim_general_reg(buf, &tmp2_symbol);
+
+        for half_offset in [0, 8] {
+            ASM::mov_reg64_base32(buf, tmp1, src1_offset + half_offset);
+            ASM::mov_reg64_base32(buf, tmp2, src2_offset + half_offset);
+            op(buf, tmp1, tmp1, tmp2);
+            ASM::mov_base32_reg64(buf, base_offset + half_offset, tmp1);
         }
+
+        self.storage_manager.free_symbol(&tmp1_symbol);
+        self.storage_manager.free_symbol(&tmp2_symbol);
     }
 
     fn compare_128bit(
@@ -4919,10 +5278,33 @@ impl<
             LayoutRepr::Builtin(Builtin::Decimal) => {
                 self.compare_128bit(op, dst, src1, src2, IntWidth::I128);
             }
-            x => todo!("NumLt: layout, {:?}", x),
+            x => internal_error!("NumLt: layout, {:?}", x),
         }
     }
 
+    /// Setup a list with the given elements pointer and length on the stack.
+    fn store_list_on_stack(
+        &mut self,
+        sym: &Symbol,
+        ptr_reg: GeneralReg,
+        length: usize,
+        element_alignment: u64,
+    ) {
+        self.storage_manager.with_tmp_general_reg(
+            &mut self.buf,
+            |storage_manager, buf, tmp_reg| {
+                let alignment = Ord::max(8, element_alignment) as u32;
+                let base_offset =
+                    storage_manager.claim_stack_area_with_alignment(*sym, 24, alignment);
+                ASM::mov_base32_reg64(buf, base_offset, ptr_reg);
+
+                ASM::mov_reg64_imm64(buf, tmp_reg, length as i64);
+                ASM::mov_base32_reg64(buf, base_offset + 8, tmp_reg);
+                ASM::mov_base32_reg64(buf, base_offset + 16, tmp_reg);
+            },
+        );
+    }
+
     fn allocate_with_refcount(
         &mut self,
         dst: Symbol,
@@ -2044,6 +2044,21 @@ impl Assembler<X86_64GeneralReg, X86_64FloatReg> for X86_64Assembler {
         X86_64Assembler::mov_reg64_mem64_offset32(buf, dst, dst, 0);
     }
 
+    #[inline(always)]
+    fn local_data_pointer(
+        buf: &mut Vec<'_, u8>,
+        relocs: &mut Vec<'_, Relocation>,
+        data: std::vec::Vec<u8>,
+        dst: X86_64GeneralReg,
+    ) {
+        lea_reg64(buf, dst);
+
+        relocs.push(Relocation::LocalData {
+            offset: buf.len() as u64 - 4,
+            data,
+        });
+    }
+
     #[inline(always)]
     fn imul_reg64_reg64_reg64(
         buf: &mut Vec<'_, u8>,
@@ -24,9 +24,12 @@ use roc_mono::layout::{
     TagIdIntType, UnionLayout,
 };
 use roc_mono::list_element_layout;
+use roc_region::all::Region;
 
+mod debug_info;
 mod generic64;
 mod object_builder;
+pub use debug_info::DebugSources;
 pub use object_builder::build_module;
 use roc_target::TargetInfo;
 mod run_roc;
@@ -65,6 +68,8 @@ pub struct Env<'a> {
     pub exposed_to_host: MutSet<Symbol>,
     pub lazy_literals: bool,
     pub mode: AssemblyBackendMode,
+    /// When present, the object gets DWARF sections that map procedures to their source.
+    pub debug_sources: Option<DebugSources>,
 }
 
 // These relocations likely will need a length.
@@ -268,6 +273,7 @@ impl<'a> LastSeenMap<'a> {
                 }
             }
 
+            Stmt::Located { remainder, .. } => self.scan_ast_help(remainder),
             Stmt::Dbg { .. } => todo!("dbg not implemented in the dev backend"),
             Stmt::Expect { .. } => todo!("expect is not implemented in the dev backend"),
             Stmt::ExpectFx { .. } => todo!("expect-fx is not implemented in the dev backend"),
@@ -470,6 +476,13 @@ trait Backend<'a> {
     /// finalize is run at the end of build_proc when all internal code is finalized.
     fn finalize(&mut self) -> (Vec<u8>, Vec<Relocation>);
 
+    /// mark_location records that the code emitted from now on comes from the definition of `symbol` at `region`.
+    /// Locations are only kept when the module is built with debug info.
+    fn mark_location(&mut self, symbol: Symbol, region: Region);
+
+    /// take_locations returns the locations marked in the last procedure, as offsets into the bytes returned by finalize.
+    fn take_locations(&mut self) -> Vec<'a, (u64, Symbol, Region)>;
+
     // load_args is used to let the backend know what the args are.
     // The backend should track these args so it can use them as needed.
     fn load_args(&mut self, args: &'a [(InLayout<'a>, Symbol)], ret_layout: &InLayout<'a>);
@@ -646,6 +659,14 @@ trait Backend<'a> {
                 self.build_jump(id, args, arg_layouts.into_bump_slice(), ret_layout);
                 self.free_symbols(stmt);
             }
+            Stmt::Located {
+                symbol,
+                region,
+                remainder,
+            } => {
+                self.mark_location(*symbol, *region);
+                self.build_stmt(layout_ids, remainder, ret_layout)
+            }
             Stmt::Crash(msg, crash_tag) => self.roc_panic(*msg, *crash_tag),
             x => todo!("the statement, {:?}", x),
         }
@@ -987,7 +1008,7 @@ trait Backend<'a> {
                     arg_layouts[0], *ret_layout,
                     "NumAdd: expected to have the same argument and return layout"
                 );
-                self.build_num_add(sym, &args[0], &args[1], ret_layout)
+                self.build_num_add_wrap(sym, &args[0], &args[1], ret_layout)
             }
             LowLevel::NumAddChecked => {
                 self.build_num_add_checked(sym, &args[0], &args[1], &arg_layouts[0], ret_layout)
@@ -1111,10 +1132,13 @@ trait Backend<'a> {
                     // saturated sub is just normal sub
                     self.build_num_sub(sym, &args[0], &args[1], ret_layout)
                 }
-                LayoutRepr::Builtin(Builtin::Decimal) => {
-                    // self.load_args_and_call_zig(backend, bitcode::DEC_SUB_SATURATED)
-                    todo!()
-                }
+                LayoutRepr::Builtin(Builtin::Decimal) => self.build_fn_call(
+                    sym,
+                    bitcode::DEC_SUB_SATURATED.to_string(),
+                    args,
+                    arg_layouts,
+                    ret_layout,
+                ),
                 _ => internal_error!("invalid return type"),
             },
             LowLevel::NumBitwiseAnd => {
@@ -2032,6 +2056,15 @@ trait Backend<'a> {
     /// build_num_add stores the sum of src1 and src2 into dst.
     fn build_num_add(&mut self, dst: &Symbol, src1: &Symbol, src2: &Symbol, layout: &InLayout<'a>);
 
+    /// build_num_add_wrap stores the sum of src1 and src2 into dst, wrapping on overflow.
+    fn build_num_add_wrap(
+        &mut self,
+        dst: &Symbol,
+        src1: &Symbol,
+        src2: &Symbol,
+        layout: &InLayout<'a>,
+    );
+
     /// build_num_add_saturated stores the sum of src1 and src2 into dst.
     fn build_num_add_saturated(
         &mut self,
@@ -1,3 +1,4 @@
+use crate::debug_info::{add_debug_sections, DebugProc};
 use crate::generic64::{aarch64, new_backend_64bit, x86_64};
 use crate::{AssemblyBackendMode, Backend, Env, Relocation};
 use bumpalo::collections::Vec;
@@ -131,6 +132,12 @@ pub fn build_module<'a, 'r>(
                 ),
             )
         }
+        Triple {
+            architecture: TargetArch::Wasm32,
+            ..
+        } => internal_error!(
+            "wasm32 modules are built by roc_gen_wasm; use `CodeGenBackend::dev` to pick a backend"
+        ),
         x => unimplemented!("the target, {:?}", x),
     }
 }
@@ -365,7 +372,9 @@ fn create_relocation(target_info: TargetInfo, symbol: SymbolId, offset: u64) ->
                 )
             }
         }
-        roc_target::Architecture::Wasm32 => todo!(),
+        roc_target::Architecture::Wasm32 => {
+            internal_error!("wasm32 modules are built by roc_gen_wasm, not roc_gen_dev")
+        }
         roc_target::Architecture::X86_32 => todo!(),
         roc_target::Architecture::X86_64 => (
             RelocationEncoding::X86Branch,
@@ -578,18 +587,28 @@ fn build_object<'a, B: Backend<'a>>(
 
     // Build procedures from user code
     let mut relocations = bumpalo::vec![in arena];
+    let mut debug_procs = std::vec::Vec::new();
     for (fn_name, section_id, proc_id, proc) in procs {
-        build_proc(
+        let symbol = proc.name.name();
+        let size = build_proc(
             &mut output,
             &mut backend,
             &mut relocations,
             &mut layout_ids,
             data_section,
-            fn_name,
+            fn_name.clone(),
             section_id,
             proc_id,
             proc,
-        )
+        );
+
+        debug_procs.push(DebugProc {
+            fn_name,
+            symbol,
+            proc_id,
+            size,
+            locations: backend.take_locations().into_iter().collect(),
+        });
     }
 
     // Generate IR for specialized helper procs (refcounting & equality)
@@ -680,7 +699,7 @@ fn build_object<'a, B: Backend<'a>>(
             section_id,
             proc_id,
             proc,
-        )
+        );
     }
 
     // Relocations for all procedures (user code & helpers)
@@ -690,6 +709,11 @@ fn build_object<'a, B: Backend<'a>>(
             Err(e) => internal_error!("{:?}", e),
         }
     }
+
+    if let Some(debug_sources) = &backend.env().debug_sources {
+        add_debug_sections(&mut output, debug_sources, &debug_procs);
+    }
+
     output
 }
 
@@ -887,11 +911,12 @@ fn build_proc<'a, B: Backend<'a>>(
     section_id: SectionId,
     proc_id: SymbolId,
     proc: Proc<'a>,
-) {
+) -> u64 {
     let mut local_data_index = 0;
     let target_info = backend.target_info();
     let (proc_data, relocs, rc_proc_names) = backend.build_proc(proc, layout_ids);
     let proc_offset = output.add_symbol_data(proc_id, section_id, &proc_data, 16);
+    let proc_size = proc_data.len() as u64;
     for reloc in relocs.iter() {
         let elfreloc = match reloc {
             Relocation::LocalData { offset, data } => {
@@ -909,14 +934,65 @@ fn build_proc<'a, B: Backend<'a>>(
                 };
                 local_data_index += 1;
                 let data_id = output.add_symbol(data_symbol);
-                output.add_symbol_data(data_id, data_section, data, 4);
-                write::Relocation {
-                    offset: offset + proc_offset,
-                    size: 32,
-                    kind: RelocationKind::Relative,
-                    encoding: RelocationEncoding::Generic,
-                    symbol: data_id,
-                    addend: -4,
+                // static lists need their elements aligned, which can take up to 16 bytes
+                output.add_symbol_data(data_id, data_section, data, 16);
+
+                if cfg!(all(target_arch = "aarch64", target_os = "linux")) {
+                    // an `adrp` and an `add`, like for `LinkedData`
+                    let r = write::Relocation {
+                        offset: proc_offset + offset,
+                        size: 21,
+                        kind: RelocationKind::Elf(object::elf::R_AARCH64_ADR_PREL_PG_HI21),
+                        encoding: RelocationEncoding::Generic,
+                        symbol: data_id,
+                        addend: 0,
+                    };
+
+                    relocations.push((section_id, r));
+
+                    write::Relocation {
+                        offset: proc_offset + offset + 4,
+                        size: 12,
+                        kind: RelocationKind::Elf(object::elf::R_AARCH64_ADD_ABS_LO12_NC),
+                        encoding: RelocationEncoding::Generic,
+                        symbol: data_id,
+                        addend: 0,
+                    }
+                } else if cfg!(all(target_arch = "aarch64", target_os = "macos")) {
+                    let r = write::Relocation {
+                        offset: proc_offset + offset,
+                        size: 21,
+                        kind: RelocationKind::MachO {
+                            value: object::macho::ARM64_RELOC_PAGE21,
+                            relative: true,
+                        },
+                        encoding: RelocationEncoding::Generic,
+                        symbol: data_id,
+                        addend: 0,
+                    };
+
+                    relocations.push((section_id, r));
+
+                    write::Relocation {
+                        offset: proc_offset + offset + 4,
+                        size: 12,
+                        kind: RelocationKind::MachO {
+                            value: object::macho::ARM64_RELOC_PAGEOFF12,
+                            relative: false,
+                        },
+                        encoding: RelocationEncoding::Generic,
+                        symbol: data_id,
+                        addend: 0,
+                    }
+                } else {
+                    write::Relocation {
+                        offset: offset + proc_offset,
+                        size: 32,
+                        kind: RelocationKind::Relative,
+                        encoding: RelocationEncoding::Generic,
+                        symbol: data_id,
+                        addend: -4,
+                    }
                 }
             }
             Relocation::LinkedData { offset, name } => {
@@ -1019,6 +1095,8 @@ fn build_proc<'a, B: Backend<'a>>(
         };
         relocations.push((section_id, elfreloc));
     }
+
+    proc_size
 }
 
 fn add_undefined_rc_proc(
@@ -1,12 +1,13 @@
-use crate::llvm::bitcode::call_bitcode_fn;
+use crate::llvm::bitcode::{build_dec_wrapper, build_inc_wrapper, call_bitcode_fn};
 use crate::llvm::build_list::{self, allocate_list, empty_polymorphic_list};
 use crate::llvm::convert::{
     argument_type_from_layout, basic_type_from_builtin, basic_type_from_layout, zig_str_type,
 };
+use crate::llvm::debug_info::{self, DebugSources};
 use crate::llvm::expect::{clone_to_shared_memory, SharedMemoryPointer};
 use crate::llvm::memcpy::build_memcpy;
 use crate::llvm::refcounting::{
-    build_reset, decrement_refcount_layout, increment_refcount_layout, PointerToRefcount,
+    build_reset, decrement_refcount_layout, increment_refcount_layout, Mode, PointerToRefcount,
 };
 use crate::llvm::struct_::{struct_from_fields, RocStruct};
 use crate::llvm::{erased, fn_ptr};
@@ -735,6 +736,9 @@ pub struct Env<'a, 'ctx, 'env> {
     pub builder: &'env Builder<'ctx>,
     pub dibuilder: &'env DebugInfoBuilder<'ctx>,
     pub compile_unit: &'env DICompileUnit<'ctx>,
+    /// Where to place procedures and values in the source code; without it, all debug info
+    /// points at line 0.
+    pub debug_sources: Option<&'env DebugSources<'ctx>>,
     pub module: &'ctx Module<'ctx>,
     pub interns: Interns,
     pub target_info: TargetInfo,
@@ -2887,163 +2891,105 @@ fn list_literal<'a, 'ctx>(
     let list_length = elems.len();
     let list_length_intval = env.ptr_int().const_int(list_length as _, false);
 
-    // TODO re-enable, currently causes morphic segfaults because it tries to update
-    // constants in-place...
-    // if element_type.is_int_type() {
-    if false {
-        let element_type = element_type.into_int_type();
+    let is_static = matches!(
+        layout_interner.get_repr(element_layout),
+        LayoutRepr::Builtin(Builtin::Int(_) | Builtin::Float(_) | Builtin::Decimal)
+    ) && elems
+        .iter()
+        .all(|element| matches!(element, ListLiteralElement::Literal(_)));
+
+    if is_static {
+        // All elements are known numbers, so the whole list can live in the constants section.
+        // Alias analysis knows that such a list is not unique, so it is never updated in place.
         let element_width = layout_interner.stack_size(element_layout);
-        let size = list_length * element_width as usize;
         let alignment = layout_interner
             .alignment_bytes(element_layout)
             .max(env.target_info.ptr_width() as u32);
 
-        let mut is_all_constant = true;
+        // Zero elements in front of the list make up its refcount. A refcount of 0 marks
+        // the list as a constant, so it is never incremented, decremented or freed.
         let zero_elements =
             (env.target_info.ptr_width() as u8 as f64 / element_width as f64).ceil() as usize;
 
-        // runtime-evaluated elements
-        let mut runtime_evaluated_elements = Vec::with_capacity_in(list_length, env.arena);
-
-        // set up a global that contains all the literal elements of the array
-        // any variables or expressions are represented as `undef`
-        let global = {
-            let mut global_elements = Vec::with_capacity_in(list_length, env.arena);
+        let mut global_elements = Vec::with_capacity_in(zero_elements + list_length, env.arena);
 
-            // Add zero bytes that represent the refcount
-            //
-            // - if all elements are const, then we store the whole list as a constant.
-            //      It then needs a refcount before the first element.
-            // - but if the list is not all constants, then we will just copy the constant values,
-            //      and we do not need that refcount at the start
-            //
-            // In the latter case, we won't store the zeros in the globals
-            // (we slice them off again below)
-            for _ in 0..zero_elements {
-                global_elements.push(element_type.const_zero());
-            }
-
-            // Copy the elements from the list literal into the array
-            for (index, element) in elems.iter().enumerate() {
-                match element {
-                    ListLiteralElement::Literal(literal) => {
-                        let val = build_exp_literal(
-                            env,
-                            layout_interner,
-                            parent,
-                            element_layout,
-                            literal,
-                        );
-                        global_elements.push(val.into_int_value());
-                    }
-                    ListLiteralElement::Symbol(symbol) => {
-                        let val = scope.load_symbol(symbol);
-
-                        // here we'd like to furthermore check for intval.is_const().
-                        // if all elements are const for LLVM, we could make the array a constant.
-                        // BUT morphic does not know about this, and could allow us to modify that
-                        // array in-place. That would cause a segfault. So, we'll have to find
-                        // constants ourselves and cannot lean on LLVM here.
-
-                        is_all_constant = false;
-
-                        runtime_evaluated_elements.push((index, val));
+        for _ in 0..zero_elements {
+            global_elements.push(element_type.const_zero());
+        }
 
-                        global_elements.push(element_type.get_undef());
-                    }
-                };
+        for element in elems.iter() {
+            if let ListLiteralElement::Literal(literal) = element {
+                global_elements.push(build_exp_literal(
+                    env,
+                    layout_interner,
+                    parent,
+                    element_layout,
+                    literal,
+                ));
             }
+        }
 
-            let const_elements = if is_all_constant {
-                global_elements.into_bump_slice()
-            } else {
-                &global_elements[zero_elements..]
-            };
-
-            // use None for the address space (e.g. Const does not work)
-            let typ = element_type.array_type(const_elements.len() as u32);
-            let global = env.module.add_global(typ, None, "roc__list_literal");
-
-            global.set_constant(true);
-            global.set_alignment(alignment);
-            global.set_unnamed_addr(true);
-            global.set_linkage(inkwell::module::Linkage::Private);
-
-            global.set_initializer(&element_type.const_array(const_elements));
-            global.as_pointer_value()
+        let initializer = match element_type {
+            BasicTypeEnum::IntType(int_type) => int_type.const_array(&Vec::from_iter_in(
+                global_elements.iter().map(|value| value.into_int_value()),
+                env.arena,
+            )),
+            BasicTypeEnum::FloatType(float_type) => float_type.const_array(&Vec::from_iter_in(
+                global_elements.iter().map(|value| value.into_float_value()),
+                env.arena,
+            )),
+            _ => unreachable!("number layouts are represented by int and float types"),
         };
 
-        if is_all_constant {
-            // all elements are constants, so we can use the memory in the constants section directly
-            // here we make a pointer to the first actual element (skipping the 0 bytes that
-            // represent the refcount)
-            let zero = env.ptr_int().const_zero();
-            let offset = env.ptr_int().const_int(zero_elements as _, false);
-
-            let ptr = unsafe {
-                env.builder.new_build_in_bounds_gep(
-                    element_type,
-                    global,
-                    &[zero, offset],
-                    "first_element_pointer",
-                )
-            };
-
-            super::build_list::store_list(env, ptr, list_length_intval).into()
-        } else {
-            // some of our elements are non-constant, so we must allocate space on the heap
-            let ptr = allocate_list(env, layout_interner, element_layout, list_length_intval);
+        let typ = element_type.array_type(global_elements.len() as u32);
+        let global = env.module.add_global(typ, None, "roc__list_literal");
+
+        global.set_constant(true);
+        global.set_alignment(alignment);
+        global.set_unnamed_addr(true);
+        global.set_linkage(inkwell::module::Linkage::Private);
+        global.set_initializer(&initializer);
+
+        // point at the first actual element, skipping the refcount
+        let zero = env.ptr_int().const_zero();
+        let offset = env.ptr_int().const_int(zero_elements as _, false);
+
+        let ptr = unsafe {
+            builder.new_build_in_bounds_gep(
+                typ,
+                global.as_pointer_value(),
+                &[zero, offset],
+                "first_element_pointer",
+            )
+        };
 
-            // then, copy the relevant segment from the constant section into the heap
-            env.builder
-                .build_memcpy(
-                    ptr,
-                    alignment,
-                    global,
-                    alignment,
-                    env.ptr_int().const_int(size as _, false),
-                )
-                .unwrap();
+        return super::build_list::store_list(env, ptr, list_length_intval).into();
+    }
 
-            // then replace the `undef`s with the values that we evaluate at runtime
-            for (index, val) in runtime_evaluated_elements {
-                let index_val = ctx.i64_type().const_int(index as u64, false);
-                let elem_ptr = unsafe {
-                    builder.new_build_in_bounds_gep(element_type, ptr, &[index_val], "index")
-                };
+    let ptr = allocate_list(env, layout_interner, element_layout, list_length_intval);
 
-                builder.new_build_store(elem_ptr, val);
+    // Copy the elements from the list literal into the array
+    for (index, element) in elems.iter().enumerate() {
+        let val = match element {
+            ListLiteralElement::Literal(literal) => {
+                build_exp_literal(env, layout_interner, parent, element_layout, literal)
             }
+            ListLiteralElement::Symbol(symbol) => scope.load_symbol(symbol),
+        };
+        let index_val = ctx.i64_type().const_int(index as u64, false);
+        let elem_ptr =
+            unsafe { builder.new_build_in_bounds_gep(element_type, ptr, &[index_val], "index") };
 
-            super::build_list::store_list(env, ptr, list_length_intval).into()
-        }
-    } else {
-        let ptr = allocate_list(env, layout_interner, element_layout, list_length_intval);
-
-        // Copy the elements from the list literal into the array
-        for (index, element) in elems.iter().enumerate() {
-            let val = match element {
-                ListLiteralElement::Literal(literal) => {
-                    build_exp_literal(env, layout_interner, parent, element_layout, literal)
-                }
-                ListLiteralElement::Symbol(symbol) => scope.load_symbol(symbol),
-            };
-            let index_val = ctx.i64_type().const_int(index as u64, false);
-            let elem_ptr = unsafe {
-                builder.new_build_in_bounds_gep(element_type, ptr, &[index_val], "index")
-            };
-
-            store_roc_value(
-                env,
-                layout_interner,
-                layout_interner.get_repr(element_layout),
-                elem_ptr,
-                val,
-            );
-        }
-
-        super::build_list::store_list(env, ptr, list_length_intval).into()
+        store_roc_value(
+            env,
+            layout_interner,
+            layout_interner.get_repr(element_layout),
+            elem_ptr,
+            val,
+        );
     }
+
+    super::build_list::store_list(env, ptr, list_length_intval).into()
 }
 
 pub fn load_roc_value<'a, 'ctx>(
@@ -3177,6 +3123,8 @@ pub(crate) fn build_exp_stmt<'a, 'ctx>(
                     expr,
                 );
 
+                debug_info::declare_variable(env, layout_interner, parent, *symbol, *layout, val);
+
                 // Make a new scope which includes the binding we just encountered.
                 // This should be done *after* compiling the bound expr, since any
                 // recursive (in the LetRec sense) bindings should already have
@@ -3496,6 +3444,24 @@ pub(crate) fn build_exp_stmt<'a, 'ctx>(
             }
         }
 
+        Located {
+            symbol,
+            region,
+            remainder,
+        } => {
+            debug_info::set_location(env, parent, *symbol, *region);
+
+            build_exp_stmt(
+                env,
+                layout_interner,
+                layout_ids,
+                func_spec_solutions,
+                scope,
+                parent,
+                remainder,
+            )
+        }
+
         Dbg {
             symbol,
             variable: specialized_var,
@@ -5426,14 +5392,17 @@ pub fn build_procedures<'a>(
     debug_output_file: Option<&Path>,
     glue_layouts: &GlueLayouts<'a>,
 ) {
+    let mut layout_ids = LayoutIds::default();
     let mod_solutions = build_procedures_help(
         env,
         layout_interner,
         opt_level,
         procedures,
         host_exposed_lambda_sets,
+        &glue_layouts.getters,
         entry_point,
         debug_output_file,
+        &mut layout_ids,
     );
 
     let niche = Niche::NONE;
@@ -5470,6 +5439,22 @@ pub fn build_procedures<'a>(
             top_level.result,
             getter_name,
         );
+
+        // The host owns the value this getter returns, so it needs to know how to align it and
+        // how to release the values it references.
+        build_host_exposed_alignment(
+            env,
+            &format!("{getter_name}_alignment"),
+            layout_interner.alignment_bytes(top_level.result),
+        );
+        build_host_exposed_refcount(
+            env,
+            layout_interner,
+            &mut layout_ids,
+            &format!("{getter_name}_dec"),
+            top_level.result,
+            Mode::Dec,
+        );
     }
 }
 
@@ -5486,8 +5471,10 @@ pub fn build_wasm_test_wrapper<'a, 'ctx>(
         opt_level,
         procedures,
         vec![],
+        &[],
         EntryPoint::Single(entry_point),
         Some(&std::env::temp_dir().join("test.ll")),
+        &mut LayoutIds::default(),
     );
 
     promote_to_wasm_test_wrapper(
@@ -5513,8 +5500,10 @@ pub fn build_procedures_return_main<'a, 'ctx>(
         opt_level,
         procedures,
         host_exposed_lambda_sets,
+        &[],
         EntryPoint::Single(entry_point),
         Some(&std::env::temp_dir().join("test.ll")),
+        &mut LayoutIds::default(),
     );
 
     promote_to_main_function(
@@ -5541,8 +5530,10 @@ pub fn build_procedures_expose_expects<'a>(
         opt_level,
         procedures,
         vec![],
+        &[],
         entry_point,
         Some(&std::env::temp_dir().join("test.ll")),
+        &mut LayoutIds::default(),
     );
 
     let captures_niche = Niche::NONE;
@@ -5604,10 +5595,11 @@ fn build_procedures_help<'a>(
     opt_level: OptLevel,
     procedures: MutMap<(Symbol, ProcLayout<'a>), roc_mono::ir::Proc<'a>>,
     host_exposed_lambda_sets: HostExposedLambdaSets<'a>,
+    glue_getters: &[(Symbol, ProcLayout<'a>)],
     entry_point: EntryPoint<'a>,
     debug_output_file: Option<&Path>,
+    layout_ids: &mut LayoutIds<'a>,
 ) -> &'a ModSolutions {
-    let mut layout_ids = roc_mono::layout::LayoutIds::default();
     let mut scope = Scope::default();
 
     let it1 = procedures.iter().map(|x| x.1);
@@ -5620,6 +5612,7 @@ fn build_procedures_help<'a>(
         entry_point,
         it1,
         it2,
+        glue_getters,
     ) {
         Err(e) => panic!("Error in alias analysis: {e}"),
         Ok(solutions) => solutions,
@@ -5640,7 +5633,7 @@ fn build_procedures_help<'a>(
         mod_solutions,
         procedures,
         &mut scope,
-        &mut layout_ids,
+        layout_ids,
     );
 
     let (_, function_pass) = construct_optimization_passes(env.module, opt_level);
@@ -5657,7 +5650,7 @@ fn build_procedures_help<'a>(
             build_proc(
                 env,
                 layout_interner,
-                &mut layout_ids,
+                layout_ids,
                 func_spec_solutions,
                 scope.clone(),
                 &proc,
@@ -5712,6 +5705,7 @@ fn build_procedures_help<'a>(
                 expose_alias_to_host(
                     env,
                     layout_interner,
+                    layout_ids,
                     mod_solutions,
                     &fn_name,
                     *alias_name,
@@ -5797,7 +5791,7 @@ fn build_proc_header<'a, 'ctx>(
         Linkage::Internal,
     );
 
-    let subprogram = env.new_subprogram(&fn_name);
+    let subprogram = debug_info::proc_subprogram(env, &fn_name, symbol);
     fn_val.set_subprogram(subprogram);
 
     if env.exposed_to_host.contains(&symbol) {
@@ -5834,6 +5828,7 @@ fn build_proc_header<'a, 'ctx>(
 fn expose_alias_to_host<'a>(
     env: &Env<'a, '_, '_>,
     layout_interner: &STLayoutInterner<'a>,
+    layout_ids: &mut LayoutIds<'a>,
     mod_solutions: &'a ModSolutions,
     fn_name: &str,
     alias_symbol: Symbol,
@@ -5877,6 +5872,7 @@ fn expose_alias_to_host<'a>(
             build_closure_caller(
                 env,
                 layout_interner,
+                layout_ids,
                 fn_name,
                 evaluator,
                 alias_symbol,
@@ -5910,6 +5906,7 @@ fn expose_alias_to_host<'a>(
 fn build_closure_caller<'a, 'ctx>(
     env: &Env<'a, 'ctx, '_>,
     layout_interner: &STLayoutInterner<'a>,
+    layout_ids: &mut LayoutIds<'a>,
     def_name: &str,
     evaluator: FunctionValue<'ctx>,
     alias_symbol: Symbol,
@@ -6032,6 +6029,97 @@ fn build_closure_caller<'a, 'ctx>(
         alias_symbol,
         lambda_set.runtime_representation(),
     );
+
+    // STEP 5: build functions that give the alignment of the closure, and that increment and
+    // decrement its captured values, so the host can share and free closures it was handed
+    let closure_layout = lambda_set.runtime_representation();
+
+    build_host_exposed_alignment(
+        env,
+        &format!("roc__{def_name}_alignment"),
+        layout_interner.alignment_bytes(closure_layout),
+    );
+    build_host_exposed_refcount(
+        env,
+        layout_interner,
+        layout_ids,
+        &format!("roc__{def_name}_inc"),
+        closure_layout,
+        Mode::Inc,
+    );
+    build_host_exposed_refcount(
+        env,
+        layout_interner,
+        layout_ids,
+        &format!("roc__{def_name}_dec"),
+        closure_layout,
+        Mode::Dec,
+    );
+}
+
+/// e.g. `roc__mainForHost_0_alignment() -> i64`
+fn build_host_exposed_alignment(env: &Env<'_, '_, '_>, function_name: &str, alignment: u32) {
+    let builder = env.builder;
+
+    let i64 = env.context.i64_type().as_basic_type_enum();
+    let function_spec = FunctionSpec::cconv(env, CCReturn::Return, Some(i64), &[]);
+
+    let function_value = add_func(
+        env.context,
+        env.module,
+        function_name,
+        function_spec,
+        Linkage::External,
+    );
+
+    let entry = env.context.append_basic_block(function_value, "entry");
+    builder.position_at_end(entry);
+
+    let alignment = env.context.i64_type().const_int(alignment as u64, false);
+    builder.new_build_return(Some(&alignment));
+}
+
+/// e.g. `roc__mainForHost_0_dec(*u8)`, which increments or decrements the refcounted values
+/// stored behind the given pointer.
+fn build_host_exposed_refcount<'a, 'ctx>(
+    env: &Env<'a, 'ctx, '_>,
+    layout_interner: &STLayoutInterner<'a>,
+    layout_ids: &mut LayoutIds<'a>,
+    function_name: &str,
+    layout: InLayout<'a>,
+    mode: Mode,
+) {
+    let builder = env.builder;
+
+    let arg_type = env.context.i8_type().ptr_type(AddressSpace::default());
+    let function_spec = FunctionSpec::cconv(env, CCReturn::Void, None, &[arg_type.into()]);
+
+    let function_value = add_func(
+        env.context,
+        env.module,
+        function_name,
+        function_spec,
+        Linkage::External,
+    );
+
+    let subprogram = env.new_subprogram(function_name);
+    function_value.set_subprogram(subprogram);
+
+    let entry = env.context.append_basic_block(function_value, "entry");
+    builder.position_at_end(entry);
+
+    debug_info_init!(env, function_value);
+
+    let rc_wrapper = match mode {
+        Mode::Inc => build_inc_wrapper(env, layout_interner, layout_ids, layout),
+        Mode::Dec => build_dec_wrapper(env, layout_interner, layout_ids, layout),
+    };
+
+    let value_ptr = function_value.get_nth_param(0).unwrap();
+    let call = builder.new_build_call(rc_wrapper, &[value_ptr.into()], "");
+    call.set_call_convention(C_CALL_CONV);
+
+    builder.new_build_return(None);
 }
 
 fn build_host_exposed_alias_size<'a, 'r>(
@@ -6103,6 +6191,7 @@ fn build_proc<'a, 'ctx>(
     builder.position_at_end(entry);
 
     debug_info_init!(env, fn_val);
+    debug_info::set_location_at_definition(env, fn_val, proc.name.name());
 
     // Add args to scope
     for (arg_val, (layout, arg_symbol)) in fn_val.get_param_iter().zip(args) {
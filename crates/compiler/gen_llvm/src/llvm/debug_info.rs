@@ -0,0 +1,274 @@
+//! Source-level debug info, so that debuggers like gdb and lldb can map the machine code of a
+//! program back to the Roc source it came from.
+//!
+//! The tests in test_gen only check the emitted LLVM IR. After changing this module, check by
+//! hand on Linux that gdb still understands the result. Save this as
+//! `examples/platform-switching/debugMe.roc`:
+//!
+//! ```roc
+//! app "debugMe"
+//!     packages { pf: "c-platform/main.roc" }
+//!     imports []
+//!     provides [main] to pf
+//!
+//! main =
+//!     count = 41 + 1
+//!     greeting = "Roc counted to \(Num.toStr count)\n"
+//!     greeting
+//! ```
+//!
+//! Then build it with `roc build --debug examples/platform-switching/debugMe.roc` and run
+//! `gdb examples/platform-switching/debugMe`. After `break debugMe.roc:8` and `run`, gdb should
+//! stop at line 8 of `debugMe.roc`, `list` should show the Roc source around it, and
+//! `print count` should print `42`.
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+
+use inkwell::debug_info::{
+    AsDIScope, DIFile, DIFlags, DIFlagsConstants, DISubprogram, DIType, DebugInfoBuilder,
+};
+use inkwell::module::{FlagBehavior, Module};
+use inkwell::values::{BasicValueEnum, FunctionValue};
+use roc_collections::all::MutMap;
+use roc_module::symbol::{ModuleId, Symbol};
+use roc_mono::layout::{Builtin, InLayout, LayoutInterner, LayoutRepr, STLayoutInterner};
+use roc_region::all::{LineColumn, LineInfo, Region};
+
+use super::build::{create_entry_block_alloca, BuilderExt, Env};
+
+// DWARF base type encodings
+const DW_ATE_BOOLEAN: u32 = 0x02;
+const DW_ATE_FLOAT: u32 = 0x04;
+const DW_ATE_SIGNED: u32 = 0x05;
+const DW_ATE_UNSIGNED: u32 = 0x07;
+
+/// The source files of a program, and where its named values are defined in them.
+pub struct DebugSources<'ctx> {
+    files: MutMap<ModuleId, (DIFile<'ctx>, LineInfo)>,
+    definition_regions: MutMap<Symbol, Region>,
+    /// The regions of the [roc_mono::ir::Stmt::Located] statements built so far.
+    located_regions: RefCell<MutMap<Symbol, Region>>,
+}
+
+impl<'ctx> DebugSources<'ctx> {
+    /// Also marks `module` as carrying debug info; without that, LLVM drops it.
+    pub fn new(
+        module: &Module<'ctx>,
+        dibuilder: &DebugInfoBuilder<'ctx>,
+        sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
+        definition_regions: MutMap<Symbol, Region>,
+    ) -> Self {
+        let i32_type = module.get_context().i32_type();
+        module.add_basic_value_flag(
+            "Debug Info Version",
+            FlagBehavior::Warning,
+            i32_type.const_int(inkwell::debug_info::debug_metadata_version() as u64, false),
+        );
+        module.add_basic_value_flag(
+            "Dwarf Version",
+            FlagBehavior::Warning,
+            i32_type.const_int(4, false),
+        );
+
+        let files = sources
+            .iter()
+            .map(|(module_id, (path, src))| {
+                let filename = path.file_name().map_or(path.as_os_str(), |name| name);
+                let directory = match path.parent() {
+                    Some(parent) if parent != Path::new("") => parent,
+                    _ => Path::new("."),
+                };
+                let file = dibuilder
+                    .create_file(&filename.to_string_lossy(), &directory.to_string_lossy());
+
+                (*module_id, (file, LineInfo::new(src)))
+            })
+            .collect();
+
+        Self {
+            files,
+            definition_regions,
+            located_regions: RefCell::default(),
+        }
+    }
+
+    /// The file, line and column (both 1-based, as DWARF wants them) where `symbol` is defined.
+    fn position(&self, symbol: Symbol) -> Option<(DIFile<'ctx>, u32, u32)> {
+        let region = match self.located_regions.borrow().get(&symbol) {
+            Some(region) => *region,
+            None => *self.definition_regions.get(&symbol)?,
+        };
+
+        self.position_of(symbol.module_id(), region)
+    }
+
+    fn position_of(&self, module_id: ModuleId, region: Region) -> Option<(DIFile<'ctx>, u32, u32)> {
+        let (file, line_info) = self.files.get(&module_id)?;
+        let LineColumn { line, column } = line_info.convert_pos(region.start());
+
+        Some((*file, line + 1, column + 1))
+    }
+}
+
+/// Creates the subprogram for a procedure named `symbol`, placed at its definition if we know it.
+pub(crate) fn proc_subprogram<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    function_name: &str,
+    symbol: Symbol,
+) -> DISubprogram<'ctx> {
+    let position = env
+        .debug_sources
+        .and_then(|sources| sources.position(symbol));
+
+    let Some((file, line, _)) = position else {
+        return env.new_subprogram(function_name);
+    };
+
+    let subroutine_type = env.dibuilder.create_subroutine_type(
+        file,
+        /* return type */ None,
+        &[],
+        DIFlags::PUBLIC,
+    );
+
+    env.dibuilder.create_function(
+        /* scope */ file.as_debug_info_scope(),
+        /* func name */ function_name,
+        /* linkage_name */ None,
+        /* file */ file,
+        /* line_no */ line,
+        /* DIType */ subroutine_type,
+        /* is_local_to_unit */ true,
+        /* is_definition */ true,
+        /* scope_line */ line,
+        /* flags */ DIFlags::PUBLIC,
+        /* is_optimized */ false,
+    )
+}
+
+/// Attributes the code built from now on in `parent` to the definition of `symbol`, if we know
+/// where that is. Otherwise, the code keeps the location it already had.
+pub(crate) fn set_location_at_definition<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    parent: FunctionValue<'ctx>,
+    symbol: Symbol,
+) {
+    let position = env
+        .debug_sources
+        .and_then(|sources| sources.position(symbol));
+
+    set_location_at_position(env, parent, position);
+}
+
+/// Attributes the code built from now on in `parent` to `region`, where `symbol` is defined.
+/// This is what a [roc_mono::ir::Stmt::Located] statement asks for.
+pub(crate) fn set_location<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    parent: FunctionValue<'ctx>,
+    symbol: Symbol,
+    region: Region,
+) {
+    let Some(sources) = env.debug_sources else {
+        return;
+    };
+
+    sources.located_regions.borrow_mut().insert(symbol, region);
+
+    set_location_at_position(env, parent, sources.position_of(symbol.module_id(), region));
+}
+
+fn set_location_at_position<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    parent: FunctionValue<'ctx>,
+    position: Option<(DIFile<'ctx>, u32, u32)>,
+) {
+    if let (Some((_, line, column)), Some(subprogram)) = (position, parent.get_subprogram()) {
+        let loc = env.dibuilder.create_debug_location(
+            env.context,
+            line,
+            column,
+            subprogram.as_debug_info_scope(),
+            /* inlined_at */ None,
+        );
+        env.builder.set_current_debug_location(loc);
+    }
+}
+
+/// Tells debuggers about the value of `symbol`, if it is a named value whose type they can
+/// display. The value is spilled to the stack, where debuggers can always find it.
+pub(crate) fn declare_variable<'a, 'ctx>(
+    env: &Env<'a, 'ctx, '_>,
+    layout_interner: &STLayoutInterner<'a>,
+    parent: FunctionValue<'ctx>,
+    symbol: Symbol,
+    layout: InLayout<'a>,
+    value: BasicValueEnum<'ctx>,
+) {
+    let Some(sources) = env.debug_sources else {
+        return;
+    };
+    let Some((file, line, column)) = sources.position(symbol) else {
+        return;
+    };
+    let Some(subprogram) = parent.get_subprogram() else {
+        return;
+    };
+    let Some(di_type) = basic_di_type(env, layout_interner, layout) else {
+        return;
+    };
+    let Some(block) = env.builder.get_insert_block() else {
+        return;
+    };
+
+    let name = symbol.as_str(&env.interns);
+    let scope = subprogram.as_debug_info_scope();
+
+    let variable = env.dibuilder.create_auto_variable(
+        scope,
+        name,
+        file,
+        line,
+        di_type,
+        /* always_preserve */ true,
+        DIFlags::ZERO,
+        /* align_in_bits */ 0,
+    );
+
+    let storage = create_entry_block_alloca(env, parent, value.get_type(), name);
+    env.builder.new_build_store(storage, value);
+
+    let loc = env
+        .dibuilder
+        .create_debug_location(env.context, line, column, scope, None);
+    env.dibuilder
+        .insert_declare_at_end(storage, Some(variable), None, loc, block);
+}
+
+/// The debug info type of values of `layout`; only numbers and booleans are described for now.
+fn basic_di_type<'a, 'ctx>(
+    env: &Env<'a, 'ctx, '_>,
+    layout_interner: &STLayoutInterner<'a>,
+    layout: InLayout<'a>,
+) -> Option<DIType<'ctx>> {
+    let (name, size_in_bytes, encoding) = match layout_interner.get_repr(layout) {
+        LayoutRepr::Builtin(Builtin::Bool) => ("Bool".to_string(), 1, DW_ATE_BOOLEAN),
+        LayoutRepr::Builtin(Builtin::Int(width)) => {
+            let encoding = if width.is_signed() {
+                DW_ATE_SIGNED
+            } else {
+                DW_ATE_UNSIGNED
+            };
+
+            (format!("{width:?}"), width.stack_size(), encoding)
+        }
+        LayoutRepr::Builtin(Builtin::Float(width)) => {
+            (format!("{width:?}"), width.stack_size(), DW_ATE_FLOAT)
+        }
+        _ => return None,
+    };
+
+    env.dibuilder
+        .create_basic_type(&name, size_in_bytes as u64 * 8, encoding, DIFlags::PUBLIC)
+        .ok()
+        .map(|basic_type| basic_type.as_type())
+}
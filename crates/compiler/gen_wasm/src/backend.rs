@@ -8,8 +8,8 @@ use roc_module::low_level::{LowLevel, LowLevelWrapperType};
 use roc_module::symbol::{Interns, Symbol};
 use roc_mono::code_gen_help::{CodeGenHelp, HelperOp, REFCOUNT_MAX};
 use roc_mono::ir::{
-    BranchInfo, CallType, CrashTag, Expr, JoinPointId, ListLiteralElement, Literal, ModifyRc,
-    Param, Proc, ProcLayout, Stmt,
+    static_list_bytes, BranchInfo, CallType, CrashTag, Expr, JoinPointId, ListLiteralElement,
+    Literal, ModifyRc, Param, Proc, ProcLayout, Stmt,
 };
 use roc_mono::layout::{
     Builtin, InLayout, Layout, LayoutIds, LayoutInterner, LayoutRepr, STLayoutInterner,
@@ -735,9 +735,16 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
                 _ => self.stmt_refcounting(modify, following),
             },
 
-            Stmt::Dbg { .. } => todo!("dbg is not implemented in the wasm backend"),
-            Stmt::Expect { .. } => todo!("expect is not implemented in the wasm backend"),
-            Stmt::ExpectFx { .. } => todo!("expect-fx is not implemented in the wasm backend"),
+            // Native dev builds send `dbg` and failed `expect` values to a parent process, which
+            // renders them using the types in `ExpectMetadata`. Nothing like that runs alongside
+            // a wasm module yet, so refuse to compile them rather than ignore them.
+            Stmt::Dbg { .. } => todo!("`dbg` is not supported by the wasm backend yet"),
+            Stmt::Expect { .. } | Stmt::ExpectFx { .. } => {
+                todo!("`expect` is not supported by the wasm backend yet")
+            }
+
+            // wasm modules don't carry source-level debug info yet
+            Stmt::Located { remainder, .. } => self.stmt(remainder),
 
             Stmt::Crash(sym, tag) => self.stmt_crash(*sym, *tag),
         }
@@ -852,9 +859,9 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
         branches: &'a [(u64, BranchInfo<'a>, Stmt<'a>)],
         default_branch: &(BranchInfo<'a>, &'a Stmt<'a>),
     ) {
-        // NOTE currently implemented as a series of conditional jumps
+        // NOTE implemented as a jump table when the values are small and dense,
+        // and otherwise as a series of conditional jumps.
         // We may be able to improve this in the future with `Select`
-        // or `BrTable`
 
         // Ensure the condition value is not stored only in the VM stack
         // Otherwise we can't reach it from inside the block
@@ -870,40 +877,65 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
         let is_bool = matches!(cond_layout, Layout::BOOL);
         let cond_type = WasmLayout::new(self.layout_interner, cond_layout).arg_types()[0];
 
-        // then, we jump whenever the value under scrutiny is equal to the value of a branch
-        for (i, (value, _, _)) in branches.iter().enumerate() {
-            // put the cond_symbol on the top of the stack
+        let max_value = branches
+            .iter()
+            .map(|(value, _, _)| *value)
+            .max()
+            .unwrap_or(0);
+        let is_dense = branches.len() >= 3 && max_value < 2 * branches.len() as u64;
+
+        if !is_bool && cond_type == ValueType::I32 && is_dense {
+            // Jump straight to the matching branch with a single `br_table`.
+            // The extra innermost block is for the default branch.
+            self.start_block();
+
+            let mut targets = bumpalo::vec![in self.env.arena; 0; max_value as usize + 1];
+            // Iterate in reverse so that the first of any duplicate values wins, like `br_if` does
+            for (i, (value, _, _)) in branches.iter().enumerate().rev() {
+                targets[*value as usize] = i as u32 + 1;
+            }
+
             self.storage
                 .load_symbols(&mut self.code_builder, &[cond_symbol]);
+            self.code_builder.br_table(&targets, 0);
 
-            if is_bool {
-                // We already have a bool, don't need to compare against a const to get one
-                if *value == 0 {
-                    self.code_builder.i32_eqz();
-                }
-            } else {
-                match cond_type {
-                    ValueType::I32 => {
-                        self.code_builder.i32_const(*value as i32);
-                        self.code_builder.i32_eq();
-                    }
-                    ValueType::I64 => {
-                        self.code_builder.i64_const(*value as i64);
-                        self.code_builder.i64_eq();
-                    }
-                    ValueType::F32 => {
-                        self.code_builder.f32_const(f32::from_bits(*value as u32));
-                        self.code_builder.f32_eq();
+            self.end_block();
+        } else {
+            // jump whenever the value under scrutiny is equal to the value of a branch
+            for (i, (value, _, _)) in branches.iter().enumerate() {
+                // put the cond_symbol on the top of the stack
+                self.storage
+                    .load_symbols(&mut self.code_builder, &[cond_symbol]);
+
+                if is_bool {
+                    // We already have a bool, don't need to compare against a const to get one
+                    if *value == 0 {
+                        self.code_builder.i32_eqz();
                     }
-                    ValueType::F64 => {
-                        self.code_builder.f64_const(f64::from_bits(*value));
-                        self.code_builder.f64_eq();
+                } else {
+                    match cond_type {
+                        ValueType::I32 => {
+                            self.code_builder.i32_const(*value as i32);
+                            self.code_builder.i32_eq();
+                        }
+                        ValueType::I64 => {
+                            self.code_builder.i64_const(*value as i64);
+                            self.code_builder.i64_eq();
+                        }
+                        ValueType::F32 => {
+                            self.code_builder.f32_const(f32::from_bits(*value as u32));
+                            self.code_builder.f32_eq();
+                        }
+                        ValueType::F64 => {
+                            self.code_builder.f64_const(f64::from_bits(*value));
+                            self.code_builder.f64_eq();
+                        }
                     }
                 }
-            }
 
-            // "break" out of `i` surrounding blocks
-            self.code_builder.br_if(i as u32);
+                // "break" out of `i` surrounding blocks
+                self.code_builder.br_if(i as u32);
+            }
         }
 
         // if we never jumped because a value matched, we're in the default case
@@ -1260,7 +1292,7 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
             self.code_builder.i32_store(Align::Bytes4, offset + 8);
         } else {
             let bytes = string.as_bytes();
-            let elements_addr = self.store_bytes_in_data_section(bytes);
+            let elements_addr = self.store_bytes_in_data_section(bytes, 1);
 
             // ptr
             self.code_builder.get_local(local_id);
@@ -1279,12 +1311,15 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
         };
     }
 
-    /// Create a string constant in the module data section
-    /// Return the data we need for code gen: linker symbol index and memory address
-    fn store_bytes_in_data_section(&mut self, bytes: &[u8]) -> u32 {
-        // Place the segment at a 4-byte aligned offset
-        let segment_addr = round_up_to_alignment!(self.module.data.end_addr, PTR_SIZE);
-        let elements_addr = segment_addr + PTR_SIZE;
+    /// Create a string or list constant in the module data section
+    /// Return the memory address of its first element, which is aligned to `alignment`
+    fn store_bytes_in_data_section(&mut self, bytes: &[u8], alignment: u32) -> u32 {
+        // Place the refcount right before the elements
+        let elements_addr = round_up_to_alignment!(
+            self.module.data.end_addr + PTR_SIZE,
+            alignment.max(PTR_SIZE)
+        );
+        let segment_addr = elements_addr - PTR_SIZE;
         let length_with_refcount = 4 + bytes.len();
         self.module.data.end_addr = segment_addr + length_with_refcount as u32;
 
@@ -1601,21 +1636,37 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
         elems: &'a [ListLiteralElement<'a>],
     ) {
         if let StoredValue::StackMemory { location, .. } = storage {
-            let size = self.layout_interner.stack_size(elem_layout) * (elems.len() as u32);
-
-            // Allocate heap space and store its address in a local variable
-            let heap_local_id = self.storage.create_anonymous_local(PTR_TYPE);
-            let heap_alignment = self.layout_interner.alignment_bytes(elem_layout);
-            self.allocate_with_refcount(Some(size), heap_alignment, 1);
-            self.code_builder.set_local(heap_local_id);
-
             let (stack_local_id, stack_offset) =
                 location.local_and_offset(self.storage.stack_frame_pointer);
 
+            let static_bytes = static_list_bytes(self.layout_interner, elem_layout, elems);
+
             // elements pointer
-            self.code_builder.get_local(stack_local_id);
-            self.code_builder.get_local(heap_local_id);
-            self.code_builder.i32_store(Align::Bytes4, stack_offset);
+            let heap_local_id = if let Some(bytes) = static_bytes {
+                // All elements are known numbers, so the list lives in the data section
+                let alignment = self.layout_interner.alignment_bytes(elem_layout);
+                let elements_addr = self.store_bytes_in_data_section(&bytes, alignment);
+
+                self.code_builder.get_local(stack_local_id);
+                self.code_builder.i32_const(elements_addr as i32);
+                self.code_builder.i32_store(Align::Bytes4, stack_offset);
+
+                None
+            } else {
+                let size = self.layout_interner.stack_size(elem_layout) * (elems.len() as u32);
+
+                // Allocate heap space and store its address in a local variable
+                let heap_local_id = self.storage.create_anonymous_local(PTR_TYPE);
+                let heap_alignment = self.layout_interner.alignment_bytes(elem_layout);
+                self.allocate_with_refcount(Some(size), heap_alignment, 1);
+                self.code_builder.set_local(heap_local_id);
+
+                self.code_builder.get_local(stack_local_id);
+                self.code_builder.get_local(heap_local_id);
+                self.code_builder.i32_store(Align::Bytes4, stack_offset);
+
+                Some(heap_local_id)
+            };
 
             // length of the list
             self.code_builder.get_local(stack_local_id);
@@ -1629,6 +1680,11 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
             self.code_builder
                 .i32_store(Align::Bytes4, stack_offset + 4 * Builtin::WRAPPER_CAPACITY);
 
+            // a list in the data section already holds its elements
+            let Some(heap_local_id) = heap_local_id else {
+                return;
+            };
+
             let mut elem_offset = 0;
 
             for (i, elem) in elems.iter().enumerate() {
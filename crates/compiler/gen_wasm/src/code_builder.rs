@@ -611,9 +611,23 @@ impl<'a> CodeBuilder<'a> {
         // where the branch was not taken. So we only pop 1 value, the condition.
         self.inst_imm32(BRIF, 1, false, levels);
     }
-    #[allow(dead_code)]
-    fn br_table() {
-        todo!("br instruction");
+    /// Pop an i32 and use it to index into `targets`, breaking out of that many blocks.
+    /// Out-of-range values break out of `default` blocks.
+    pub fn br_table(&mut self, targets: &[u32], default: u32) {
+        self.inst_base(BRTABLE, 1, false);
+        self.code.encode_u32(targets.len() as u32);
+        for target in targets {
+            self.code.encode_u32(*target);
+        }
+        self.code.encode_u32(default);
+
+        log_instruction!(
+            "{:10}\t{:?} {}\t{:?}",
+            format!("{BRTABLE:?}"),
+            targets,
+            default,
+            self.vm_block_stack
+        );
     }
 
     instruction_no_args!(return_, RETURN, 0, false);
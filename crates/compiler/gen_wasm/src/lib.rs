@@ -14,6 +14,7 @@ use bumpalo::collections::Vec;
 use bumpalo::{self, Bump};
 
 use roc_collections::all::{MutMap, MutSet};
+use roc_error_macros::internal_error;
 use roc_module::symbol::{Interns, ModuleId, Symbol};
 use roc_mono::code_gen_help::CodeGenHelp;
 use roc_mono::ir::{Proc, ProcLayout};
@@ -78,9 +79,27 @@ pub fn build_app_binary<'a, 'r>(
 
     let mut buffer = std::vec::Vec::with_capacity(wasm_module.size());
     wasm_module.serialize(&mut buffer);
+    debug_validate_binary(env.arena, &buffer);
     buffer
 }
 
+/// In debug builds, check that our output is valid Wasm.
+/// Runtimes tend to give very vague errors for invalid modules, so it's better to catch them here.
+pub fn debug_validate_binary(arena: &Bump, bytes: &[u8]) {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+    let result = WasmModule::preload(arena, bytes, false)
+        .map_err(|e| format!("Failed to parse: {} at offset 0x{:x}", e.message, e.offset))
+        .and_then(|module| module.validate().map_err(|e| e.to_string()));
+    if let Err(message) = result {
+        internal_error!(
+            "gen_wasm generated an invalid module.\n{}\nSet ROC_WRITE_FINAL_WASM to inspect it.",
+            message
+        );
+    }
+}
+
 /// Generate an unserialized Wasm module
 /// Shared by all consumers of gen_wasm: roc_build, roc_repl_wasm, and test_gen
 /// (roc_repl_wasm and test_gen will add more generated code for a wrapper function
@@ -207,27 +226,34 @@ pub fn copy_memory(code_builder: &mut CodeBuilder, config: CopyMemoryConfig) {
         return;
     }
 
-    let alignment = Align::from(config.alignment_bytes);
+    // Alignment hints must not be larger than the size of the access
+    let align_8 = Align::from(config.alignment_bytes);
+    let align_4 = if align_8 == Align::Bytes8 {
+        Align::Bytes4
+    } else {
+        align_8
+    };
+
     let mut i = 0;
     while config.size - i >= 8 {
         code_builder.get_local(config.to_ptr);
         code_builder.get_local(config.from_ptr);
-        code_builder.i64_load(alignment, i + config.from_offset);
-        code_builder.i64_store(alignment, i + config.to_offset);
+        code_builder.i64_load(align_8, i + config.from_offset);
+        code_builder.i64_store(align_8, i + config.to_offset);
         i += 8;
     }
     if config.size - i >= 4 {
         code_builder.get_local(config.to_ptr);
         code_builder.get_local(config.from_ptr);
-        code_builder.i32_load(alignment, i + config.from_offset);
-        code_builder.i32_store(alignment, i + config.to_offset);
+        code_builder.i32_load(align_4, i + config.from_offset);
+        code_builder.i32_store(align_4, i + config.to_offset);
         i += 4;
     }
     while config.size - i > 0 {
         code_builder.get_local(config.to_ptr);
         code_builder.get_local(config.from_ptr);
-        code_builder.i32_load8_u(alignment, i + config.from_offset);
-        code_builder.i32_store8(alignment, i + config.to_offset);
+        code_builder.i32_load8_u(Align::Bytes1, i + config.from_offset);
+        code_builder.i32_store8(Align::Bytes1, i + config.to_offset);
         i += 1;
     }
 }
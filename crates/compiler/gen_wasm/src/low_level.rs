@@ -10,6 +10,7 @@ use roc_mono::layout::{Builtin, InLayout, Layout, LayoutInterner, LayoutRepr, Un
 use roc_mono::low_level::HigherOrder;
 
 use crate::backend::{ProcLookupData, ProcSource, WasmBackend};
+use crate::code_builder::CodeBuilder;
 use crate::layout::{StackMemoryFormat, WasmLayout};
 use crate::storage::{AddressValue, StackMemoryLocation, StoredValue};
 use crate::PTR_TYPE;
@@ -117,6 +118,14 @@ fn layout_is_signed_int(layout: InLayout) -> bool {
     )
 }
 
+/// Frame pointer and offset of a 128-bit number. These are always stored in stack memory.
+fn i128_location(storage: &StoredValue, stack_frame_pointer: Option<LocalId>) -> (LocalId, u32) {
+    match storage {
+        StoredValue::StackMemory { location, .. } => location.local_and_offset(stack_frame_pointer),
+        _ => internal_error!("I128 should be in stack memory"),
+    }
+}
+
 fn symbol_is_signed_int(backend: &WasmBackend<'_, '_>, symbol: Symbol) -> bool {
     layout_is_signed_int(backend.storage.symbol_layouts[&symbol])
 }
@@ -171,6 +180,141 @@ impl<'a> LowLevelCall<'a> {
         }
     }
 
+    fn ret_int_width(&self) -> IntWidth {
+        match self.ret_layout_raw {
+            LayoutRepr::Builtin(Builtin::Int(width)) => width,
+            x => internal_error!("{:?} does not return an integer: {:?}", self.lowlevel, x),
+        }
+    }
+
+    fn arg_int_width(&self, backend: &WasmBackend<'a, '_>, index: usize) -> IntWidth {
+        let layout = backend.storage.symbol_layouts[&self.arguments[index]];
+        match backend.layout_interner.get_repr(layout) {
+            LayoutRepr::Builtin(Builtin::Int(width)) => width,
+            x => internal_error!("{:?} expects an integer argument: {:?}", self.lowlevel, x),
+        }
+    }
+
+    /// Push the argument onto the value stack, converted to a float with layout `float_layout`.
+    /// The lowlevel itself may return something else, like the result struct of a checked cast.
+    fn load_arg_as_float(&self, backend: &mut WasmBackend<'a, '_>, float_layout: InLayout<'a>) {
+        use CodeGenNumType::*;
+
+        let ret_type = CodeGenNumType::from(float_layout);
+        let call_zig = |backend: &mut WasmBackend<'a, '_>, name: &'a str| {
+            let (num_wasm_args, has_return_val) = backend.storage.load_symbols_for_call(
+                backend.env.arena,
+                &mut backend.code_builder,
+                self.arguments,
+                self.ret_symbol,
+                &WasmLayout::new(backend.layout_interner, float_layout),
+            );
+            backend.call_host_fn_after_loading_args(name, num_wasm_args, has_return_val);
+        };
+
+        let arg_layout = backend.storage.symbol_layouts[&self.arguments[0]];
+        let arg_signed = match backend.layout_interner.get_repr(arg_layout) {
+            LayoutRepr::Builtin(Builtin::Int(w)) => w.is_signed(),
+            LayoutRepr::Builtin(Builtin::Float(_)) => true, // unused
+            LayoutRepr::Builtin(Builtin::Decimal) => true,
+            x => internal_error!("Num.intCast is not defined for {:?}", x),
+        };
+        let arg_type = CodeGenNumType::from(arg_layout);
+
+        match (ret_type, arg_type) {
+            (F32, F32) | (F64, F64) => backend
+                .storage
+                .load_symbols(&mut backend.code_builder, self.arguments),
+            (F32, F64) => {
+                backend
+                    .storage
+                    .load_symbols(&mut backend.code_builder, self.arguments);
+                backend.code_builder.f32_demote_f64();
+            }
+            (F32, I32) => {
+                backend
+                    .storage
+                    .load_symbols(&mut backend.code_builder, self.arguments);
+                if arg_signed {
+                    backend.code_builder.f32_convert_s_i32()
+                } else {
+                    backend.code_builder.f32_convert_u_i32()
+                }
+            }
+            (F32, I64) => {
+                backend
+                    .storage
+                    .load_symbols(&mut backend.code_builder, self.arguments);
+                if arg_signed {
+                    backend.code_builder.f32_convert_s_i64()
+                } else {
+                    backend.code_builder.f32_convert_u_i64()
+                }
+            }
+            (F64, I32) => {
+                backend
+                    .storage
+                    .load_symbols(&mut backend.code_builder, self.arguments);
+                if arg_signed {
+                    backend.code_builder.f64_convert_s_i32()
+                } else {
+                    backend.code_builder.f64_convert_u_i32()
+                }
+            }
+            (F64, I64) => {
+                backend
+                    .storage
+                    .load_symbols(&mut backend.code_builder, self.arguments);
+                if arg_signed {
+                    backend.code_builder.f64_convert_s_i64()
+                } else {
+                    backend.code_builder.f64_convert_u_i64()
+                }
+            }
+            (F32, I128) => {
+                let width = self.arg_int_width(backend, 0);
+                call_zig(backend, &bitcode::NUM_INT_TO_F32[width]);
+            }
+            (F64, I128) => {
+                let width = self.arg_int_width(backend, 0);
+                call_zig(backend, &bitcode::NUM_INT_TO_F64[width]);
+            }
+            (F32, Decimal) => {
+                call_zig(backend, bitcode::DEC_TO_F64);
+                backend.code_builder.f32_demote_f64();
+            }
+            (F64, Decimal) => call_zig(backend, bitcode::DEC_TO_F64),
+            _ => internal_error!(
+                "{:?} is not defined for {:?} -> {:?}",
+                self.lowlevel,
+                arg_layout,
+                ret_type
+            ),
+        }
+    }
+
+    /// Apply a 64-bit bitwise instruction to both halves of two 128-bit integers in stack memory.
+    /// Bitwise ops don't carry between the halves, so there's no need to call out to Zig.
+    fn i128_bitwise_op(&self, backend: &mut WasmBackend<'a, '_>, op: fn(&mut CodeBuilder<'a>)) {
+        let frame_ptr = backend.storage.stack_frame_pointer;
+        let (ret_ptr, ret_offset) = i128_location(&self.ret_storage, frame_ptr);
+        let (lhs_ptr, lhs_offset) =
+            i128_location(backend.storage.get(&self.arguments[0]), frame_ptr);
+        let (rhs_ptr, rhs_offset) =
+            i128_location(backend.storage.get(&self.arguments[1]), frame_ptr);
+
+        let code_builder = &mut backend.code_builder;
+        for half in [0, 8] {
+            code_builder.get_local(ret_ptr);
+            code_builder.get_local(lhs_ptr);
+            code_builder.i64_load(Align::Bytes8, lhs_offset + half);
+            code_builder.get_local(rhs_ptr);
+            code_builder.i64_load(Align::Bytes8, rhs_offset + half);
+            op(code_builder);
+            code_builder.i64_store(Align::Bytes8, ret_offset + half);
+        }
+    }
+
     ///  Main entrypoint from WasmBackend
     pub fn generate(&self, backend: &mut WasmBackend<'a, '_>) {
         use CodeGenNumType::*;
@@ -1050,9 +1194,9 @@ impl<'a> LowLevelCall<'a> {
                 }
             }
             NumGt => {
-                self.load_args(backend);
                 match CodeGenNumType::for_symbol(backend, self.arguments[0]) {
                     I32 => {
+                        self.load_args(backend);
                         if symbol_is_signed_int(backend, self.arguments[0]) {
                             backend.code_builder.i32_gt_s()
                         } else {
@@ -1060,16 +1204,22 @@ impl<'a> LowLevelCall<'a> {
                         }
                     }
                     I64 => {
+                        self.load_args(backend);
                         if symbol_is_signed_int(backend, self.arguments[0]) {
                             backend.code_builder.i64_gt_s()
                         } else {
                             backend.code_builder.i64_gt_u()
                         }
                     }
-                    F32 => backend.code_builder.f32_gt(),
-                    F64 => backend.code_builder.f64_gt(),
-                    I128 => {
+                    F32 => {
+                        self.load_args(backend);
+                        backend.code_builder.f32_gt()
+                    }
+                    F64 => {
                         self.load_args(backend);
+                        backend.code_builder.f64_gt()
+                    }
+                    I128 => {
                         let intrinsic = if symbol_is_signed_int(backend, self.arguments[0]) {
                             &bitcode::NUM_GREATER_THAN[IntWidth::I128]
                         } else {
@@ -1088,9 +1238,9 @@ impl<'a> LowLevelCall<'a> {
                 }
             }
             NumGte => {
-                self.load_args(backend);
                 match CodeGenNumType::for_symbol(backend, self.arguments[0]) {
                     I32 => {
+                        self.load_args(backend);
                         if symbol_is_signed_int(backend, self.arguments[0]) {
                             backend.code_builder.i32_ge_s()
                         } else {
@@ -1098,16 +1248,22 @@ impl<'a> LowLevelCall<'a> {
                         }
                     }
                     I64 => {
+                        self.load_args(backend);
                         if symbol_is_signed_int(backend, self.arguments[0]) {
                             backend.code_builder.i64_ge_s()
                         } else {
                             backend.code_builder.i64_ge_u()
                         }
                     }
-                    F32 => backend.code_builder.f32_ge(),
-                    F64 => backend.code_builder.f64_ge(),
-                    I128 => {
+                    F32 => {
+                        self.load_args(backend);
+                        backend.code_builder.f32_ge()
+                    }
+                    F64 => {
                         self.load_args(backend);
+                        backend.code_builder.f64_ge()
+                    }
+                    I128 => {
                         let intrinsic = if symbol_is_signed_int(backend, self.arguments[0]) {
                             &bitcode::NUM_GREATER_THAN_OR_EQUAL[IntWidth::I128]
                         } else {
@@ -1126,9 +1282,9 @@ impl<'a> LowLevelCall<'a> {
                 }
             }
             NumLt => {
-                self.load_args(backend);
                 match CodeGenNumType::for_symbol(backend, self.arguments[0]) {
                     I32 => {
+                        self.load_args(backend);
                         if symbol_is_signed_int(backend, self.arguments[0]) {
                             backend.code_builder.i32_lt_s()
                         } else {
@@ -1136,16 +1292,22 @@ impl<'a> LowLevelCall<'a> {
                         }
                     }
                     I64 => {
+                        self.load_args(backend);
                         if symbol_is_signed_int(backend, self.arguments[0]) {
                             backend.code_builder.i64_lt_s()
                         } else {
                             backend.code_builder.i64_lt_u()
                         }
                     }
-                    F32 => backend.code_builder.f32_lt(),
-                    F64 => backend.code_builder.f64_lt(),
-                    I128 => {
+                    F32 => {
                         self.load_args(backend);
+                        backend.code_builder.f32_lt()
+                    }
+                    F64 => {
+                        self.load_args(backend);
+                        backend.code_builder.f64_lt()
+                    }
+                    I128 => {
                         let intrinsic = if symbol_is_signed_int(backend, self.arguments[0]) {
                             &bitcode::NUM_LESS_THAN[IntWidth::I128]
                         } else {
@@ -1164,10 +1326,10 @@ impl<'a> LowLevelCall<'a> {
                 }
             }
             NumLte => {
-                self.load_args(backend);
                 let layout = backend.storage.symbol_layouts[&self.arguments[0]];
                 match CodeGenNumType::from(layout) {
                     I32 => {
+                        self.load_args(backend);
                         if layout_is_signed_int(layout) {
                             backend.code_builder.i32_le_s()
                         } else {
@@ -1175,16 +1337,22 @@ impl<'a> LowLevelCall<'a> {
                         }
                     }
                     I64 => {
+                        self.load_args(backend);
                         if layout_is_signed_int(layout) {
                             backend.code_builder.i64_le_s()
                         } else {
                             backend.code_builder.i64_le_u()
                         }
                     }
-                    F32 => backend.code_builder.f32_le(),
-                    F64 => backend.code_builder.f64_le(),
-                    I128 => {
+                    F32 => {
+                        self.load_args(backend);
+                        backend.code_builder.f32_le()
+                    }
+                    F64 => {
                         self.load_args(backend);
+                        backend.code_builder.f64_le()
+                    }
+                    I128 => {
                         let intrinsic = if symbol_is_signed_int(backend, self.arguments[0]) {
                             &bitcode::NUM_LESS_THAN_OR_EQUAL[IntWidth::I128]
                         } else {
@@ -1249,8 +1417,17 @@ impl<'a> LowLevelCall<'a> {
                         backend.code_builder.f64_lt();
                         backend.code_builder.i32_add();
                     }
-                    I128 | Decimal => {
-                        self.load_args(backend);
+                    I128 => {
+                        let intrinsic = if is_signed {
+                            &bitcode::NUM_COMPARE[IntWidth::I128]
+                        } else {
+                            &bitcode::NUM_COMPARE[IntWidth::U128]
+                        };
+
+                        self.load_args_and_call_zig(backend, intrinsic);
+                    }
+                    Decimal => {
+                        // same as i128
                         self.load_args_and_call_zig(backend, &bitcode::NUM_COMPARE[IntWidth::I128]);
                     }
                 }
@@ -1261,7 +1438,7 @@ impl<'a> LowLevelCall<'a> {
                     F32 => backend.code_builder.f32_div(),
                     F64 => backend.code_builder.f64_div(),
                     Decimal => self.load_args_and_call_zig(backend, bitcode::DEC_DIV),
-                    x => todo!("{:?} for {:?}", self.lowlevel, x),
+                    _ => panic_ret_type(),
                 }
             }
             NumDivTruncUnchecked => {
@@ -1282,7 +1459,11 @@ impl<'a> LowLevelCall<'a> {
                             backend.code_builder.i64_div_u()
                         }
                     }
-                    x => todo!("{:?} for {:?}", self.lowlevel, x),
+                    I128 => self.load_args_and_call_zig(
+                        backend,
+                        &bitcode::NUM_DIV_TRUNC_INT[self.ret_int_width()],
+                    ),
+                    _ => panic_ret_type(),
                 }
             }
             NumDivCeilUnchecked => match self.ret_layout_raw {
@@ -1297,7 +1478,11 @@ impl<'a> LowLevelCall<'a> {
                 match CodeGenNumType::for_symbol(backend, self.arguments[0]) {
                     I32 => backend.code_builder.i32_rem_s(),
                     I64 => backend.code_builder.i64_rem_s(),
-                    _ => todo!("{:?} for {:?}", self.lowlevel, self.ret_layout),
+                    I128 => self.load_args_and_call_zig(
+                        backend,
+                        &bitcode::NUM_REM_INT[self.ret_int_width()],
+                    ),
+                    _ => panic_ret_type(),
                 }
             }
             NumIsMultipleOf => {
@@ -1460,7 +1645,11 @@ impl<'a> LowLevelCall<'a> {
                     }
                     F32 => backend.code_builder.f32_abs(),
                     F64 => backend.code_builder.f64_abs(),
-                    _ => todo!("{:?} for {:?}", self.lowlevel, self.ret_layout),
+                    I128 => self.load_args_and_call_zig(
+                        backend,
+                        &bitcode::NUM_ABS_INT[self.ret_int_width()],
+                    ),
+                    Decimal => self.load_args_and_call_zig(backend, bitcode::DEC_ABS),
                 }
             }
             NumNeg => {
@@ -1493,7 +1682,11 @@ impl<'a> LowLevelCall<'a> {
                     }
                     F32 => backend.code_builder.f32_neg(),
                     F64 => backend.code_builder.f64_neg(),
-                    _ => todo!("{:?} for {:?}", self.lowlevel, self.ret_layout),
+                    I128 => self.load_args_and_call_zig(
+                        backend,
+                        &bitcode::NUM_NEGATE_INT[self.ret_int_width()],
+                    ),
+                    Decimal => self.load_args_and_call_zig(backend, bitcode::DEC_NEGATE),
                 }
             }
             NumSin => match self.ret_layout_raw {
@@ -1551,11 +1744,24 @@ impl<'a> LowLevelCall<'a> {
                     (F32, I64) => backend.code_builder.f32_convert_s_i64(),
                     (F32, F32) => {}
                     (F32, F64) => backend.code_builder.f32_demote_f64(),
+                    (F32, I128) => self.load_args_and_call_zig(
+                        backend,
+                        &bitcode::NUM_INT_TO_F32[self.arg_int_width(backend, 0)],
+                    ),
+                    (F32, Decimal) => {
+                        self.load_args_and_call_zig(backend, bitcode::DEC_TO_F64);
+                        backend.code_builder.f32_demote_f64();
+                    }
 
                     (F64, I32) => backend.code_builder.f64_convert_s_i32(),
                     (F64, I64) => backend.code_builder.f64_convert_s_i64(),
                     (F64, F32) => backend.code_builder.f64_promote_f32(),
                     (F64, F64) => {}
+                    (F64, I128) => self.load_args_and_call_zig(
+                        backend,
+                        &bitcode::NUM_INT_TO_F64[self.arg_int_width(backend, 0)],
+                    ),
+                    (F64, Decimal) => self.load_args_and_call_zig(backend, bitcode::DEC_TO_F64),
 
                     (Decimal, I32) => {
                         let int_width = match arg_is_signed {
@@ -1573,6 +1779,10 @@ impl<'a> LowLevelCall<'a> {
 
                         self.load_args_and_call_zig(backend, &bitcode::DEC_FROM_INT[int_width]);
                     }
+                    (Decimal, I128) => self.load_args_and_call_zig(
+                        backend,
+                        &bitcode::DEC_FROM_INT[self.arg_int_width(backend, 0)],
+                    ),
                     (Decimal, F32) => {
                         self.load_args_and_call_zig(
                             backend,
@@ -1587,7 +1797,7 @@ impl<'a> LowLevelCall<'a> {
                     }
                     (Decimal, Decimal) => {}
 
-                    _ => todo!("{:?}: {:?} -> {:?}", self.lowlevel, arg_type, ret_type),
+                    _ => panic_ret_type(),
                 }
             }
             NumPow => match self.ret_layout_raw {
@@ -1638,7 +1848,7 @@ impl<'a> LowLevelCall<'a> {
                 let width = match ret_type {
                     CodeGenNumType::I32 => IntWidth::I32,
                     CodeGenNumType::I64 => IntWidth::I64,
-                    CodeGenNumType::I128 => todo!("{:?} for I128", self.lowlevel),
+                    CodeGenNumType::I128 => self.ret_int_width(),
                     _ => internal_error!("Invalid return type for round: {:?}", ret_type),
                 };
 
@@ -1673,7 +1883,16 @@ impl<'a> LowLevelCall<'a> {
                     (I32, F64) => backend.code_builder.i32_trunc_s_f64(),
                     (I64, F32) => backend.code_builder.i64_trunc_s_f32(),
                     (I64, F64) => backend.code_builder.i64_trunc_s_f64(),
-                    (I128, _) => todo!("{:?} for I128", self.lowlevel),
+                    (I128, F32) => {
+                        // The value is already integral, so rounding it converts it exactly.
+                        // The return address and the float are the 2 Wasm args on the stack.
+                        let name = &bitcode::NUM_ROUND_F32[self.ret_int_width()];
+                        backend.call_host_fn_after_loading_args(name, 2, false);
+                    }
+                    (I128, F64) => {
+                        let name = &bitcode::NUM_ROUND_F64[self.ret_int_width()];
+                        backend.call_host_fn_after_loading_args(name, 2, false);
+                    }
                     _ => panic_ret_type(),
                 }
             }
@@ -1689,7 +1908,7 @@ impl<'a> LowLevelCall<'a> {
                 let width = match ret_type {
                     CodeGenNumType::I32 => IntWidth::I32,
                     CodeGenNumType::I64 => IntWidth::I64,
-                    CodeGenNumType::I128 => todo!("{:?} for I128", self.lowlevel),
+                    CodeGenNumType::I128 => self.ret_int_width(),
                     _ => internal_error!("Invalid return type for pow: {:?}", ret_type),
                 };
 
@@ -1731,46 +1950,63 @@ impl<'a> LowLevelCall<'a> {
             NumBytesToU32 => self.load_args_and_call_zig(backend, bitcode::NUM_BYTES_TO_U32),
             NumBytesToU64 => self.load_args_and_call_zig(backend, bitcode::NUM_BYTES_TO_U64),
             NumBytesToU128 => self.load_args_and_call_zig(backend, bitcode::NUM_BYTES_TO_U128),
-            NumBitwiseAnd => {
-                self.load_args(backend);
-                match CodeGenNumType::from(self.ret_layout) {
-                    I32 => backend.code_builder.i32_and(),
-                    I64 => backend.code_builder.i64_and(),
-                    I128 => todo!("{:?} for I128", self.lowlevel),
-                    _ => panic_ret_type(),
+            NumBitwiseAnd => match CodeGenNumType::from(self.ret_layout) {
+                I32 => {
+                    self.load_args(backend);
+                    backend.code_builder.i32_and();
                 }
-            }
-            NumBitwiseXor => {
-                self.load_args(backend);
-                match CodeGenNumType::from(self.ret_layout) {
-                    I32 => backend.code_builder.i32_xor(),
-                    I64 => backend.code_builder.i64_xor(),
-                    I128 => todo!("{:?} for I128", self.lowlevel),
-                    _ => panic_ret_type(),
+                I64 => {
+                    self.load_args(backend);
+                    backend.code_builder.i64_and();
                 }
-            }
-            NumBitwiseOr => {
-                self.load_args(backend);
-                match CodeGenNumType::from(self.ret_layout) {
-                    I32 => backend.code_builder.i32_or(),
-                    I64 => backend.code_builder.i64_or(),
-                    I128 => todo!("{:?} for I128", self.lowlevel),
-                    _ => panic_ret_type(),
+                I128 => self.i128_bitwise_op(backend, CodeBuilder::i64_and),
+                _ => panic_ret_type(),
+            },
+            NumBitwiseXor => match CodeGenNumType::from(self.ret_layout) {
+                I32 => {
+                    self.load_args(backend);
+                    backend.code_builder.i32_xor();
                 }
-            }
+                I64 => {
+                    self.load_args(backend);
+                    backend.code_builder.i64_xor();
+                }
+                I128 => self.i128_bitwise_op(backend, CodeBuilder::i64_xor),
+                _ => panic_ret_type(),
+            },
+            NumBitwiseOr => match CodeGenNumType::from(self.ret_layout) {
+                I32 => {
+                    self.load_args(backend);
+                    backend.code_builder.i32_or();
+                }
+                I64 => {
+                    self.load_args(backend);
+                    backend.code_builder.i64_or();
+                }
+                I128 => self.i128_bitwise_op(backend, CodeBuilder::i64_or),
+                _ => panic_ret_type(),
+            },
             NumShiftLeftBy => {
                 let num = self.arguments[0];
                 let bits = self.arguments[1];
-                backend
-                    .storage
-                    .load_symbols(&mut backend.code_builder, &[num, bits]);
                 match CodeGenNumType::from(self.ret_layout) {
-                    I32 => backend.code_builder.i32_shl(),
+                    I32 => {
+                        backend
+                            .storage
+                            .load_symbols(&mut backend.code_builder, &[num, bits]);
+                        backend.code_builder.i32_shl();
+                    }
                     I64 => {
+                        backend
+                            .storage
+                            .load_symbols(&mut backend.code_builder, &[num, bits]);
                         backend.code_builder.i64_extend_u_i32();
                         backend.code_builder.i64_shl();
                     }
-                    I128 => todo!("{:?} for I128", self.lowlevel),
+                    I128 => self.load_args_and_call_zig(
+                        backend,
+                        &bitcode::NUM_SHIFT_LEFT[self.ret_int_width()],
+                    ),
                     _ => panic_ret_type(),
                 }
             }
@@ -1817,7 +2053,10 @@ impl<'a> LowLevelCall<'a> {
                         backend.code_builder.i64_extend_u_i32();
                         backend.code_builder.i64_shr_s();
                     }
-                    I128 => todo!("{:?} for I128", self.lowlevel),
+                    I128 => self.load_args_and_call_zig(
+                        backend,
+                        &bitcode::NUM_SHIFT_RIGHT[self.ret_int_width()],
+                    ),
                     _ => panic_ret_type(),
                 }
             }
@@ -1888,8 +2127,13 @@ impl<'a> LowLevelCall<'a> {
                         self.wrap_small_int(backend, ret_width);
                     }
                     (I32, I128) => {
-                        self.load_args(backend);
-                        backend.code_builder.i32_load(Align::Bytes4, 0);
+                        let (frame_ptr, offset) = i128_location(
+                            backend.storage.get(&self.arguments[0]),
+                            backend.storage.stack_frame_pointer,
+                        );
+                        backend.code_builder.get_local(frame_ptr);
+                        backend.code_builder.i32_load(Align::Bytes4, offset);
+                        self.wrap_small_int(backend, ret_width);
                     }
                     (I64, I32) => {
                         self.load_args(backend);
@@ -1903,82 +2147,64 @@ impl<'a> LowLevelCall<'a> {
                         self.load_args(backend);
                     }
                     (I64, I128) => {
-                        let (frame_ptr, offset) = match backend.storage.get(&self.arguments[0]) {
-                            StoredValue::StackMemory { location, .. } => {
-                                location.local_and_offset(backend.storage.stack_frame_pointer)
-                            }
-                            _ => internal_error!("I128 should be in stack memory"),
-                        };
+                        let (frame_ptr, offset) = i128_location(
+                            backend.storage.get(&self.arguments[0]),
+                            backend.storage.stack_frame_pointer,
+                        );
                         backend.code_builder.get_local(frame_ptr);
                         backend.code_builder.i64_load(Align::Bytes8, offset);
                     }
-                    (I128, I64) => {
-                        // Symbols are loaded as if for a call, so the i128 "return address" and i64 value are on the value stack
-                        self.load_args(backend);
-                        backend.code_builder.i64_store(Align::Bytes8, 0);
+                    (I128, I32) | (I128, I64) => {
+                        let arg = self.arguments[0];
+                        let (frame_ptr, offset) =
+                            i128_location(&self.ret_storage, backend.storage.stack_frame_pointer);
 
-                        // Zero the most significant 64 bits
-                        let (frame_ptr, offset) = match &self.ret_storage {
-                            StoredValue::StackMemory { location, .. } => {
-                                location.local_and_offset(backend.storage.stack_frame_pointer)
-                            }
-                            _ => internal_error!("I128 should be in stack memory"),
-                        };
+                        // Least significant 64 bits
                         backend.code_builder.get_local(frame_ptr);
-                        backend.code_builder.i64_const(0);
-                        backend.code_builder.i64_store(Align::Bytes8, offset + 8);
-                    }
-
-                    _ => todo!("{:?}: {:?} -> {:?}", self.lowlevel, arg_type, ret_type),
-                }
-            }
-            NumToFloatCast => {
-                self.load_args(backend);
-                let arg_layout = backend.storage.symbol_layouts[&self.arguments[0]];
-                let arg_signed = match backend.layout_interner.get_repr(arg_layout) {
-                    LayoutRepr::Builtin(Builtin::Int(w)) => w.is_signed(),
-                    LayoutRepr::Builtin(Builtin::Float(_)) => true, // unused
-                    LayoutRepr::Builtin(Builtin::Decimal) => true,
-                    x => internal_error!("Num.intCast is not defined for {:?}", x),
-                };
-                let ret_type = CodeGenNumType::from(self.ret_layout);
-                let arg_type = CodeGenNumType::from(arg_layout);
-
-                match (ret_type, arg_type) {
-                    (F32, F32) => {}
-                    (F32, F64) => backend.code_builder.f32_demote_f64(),
-                    (F32, I32) => {
-                        if arg_signed {
-                            backend.code_builder.f32_convert_s_i32()
-                        } else {
-                            backend.code_builder.f32_convert_u_i32()
-                        }
-                    }
-                    (F32, I64) => {
-                        if arg_signed {
-                            backend.code_builder.f32_convert_s_i64()
-                        } else {
-                            backend.code_builder.f32_convert_u_i64()
+                        backend
+                            .storage
+                            .load_symbols(&mut backend.code_builder, &[arg]);
+                        if arg_type == I32 {
+                            if arg_width.is_signed() {
+                                backend.code_builder.i64_extend_s_i32();
+                            } else {
+                                backend.code_builder.i64_extend_u_i32();
+                            }
                         }
-                    }
-                    (F64, F64) => {}
-                    (F64, I32) => {
-                        if arg_signed {
-                            backend.code_builder.f64_convert_s_i32()
+                        backend.code_builder.i64_store(Align::Bytes8, offset);
+
+                        // Most significant 64 bits: copies of the sign bit, or zero if unsigned
+                        backend.code_builder.get_local(frame_ptr);
+                        if arg_width.is_signed() {
+                            backend
+                                .storage
+                                .load_symbols(&mut backend.code_builder, &[arg]);
+                            if arg_type == I32 {
+                                backend.code_builder.i64_extend_s_i32();
+                            }
+                            backend.code_builder.i64_const(63);
+                            backend.code_builder.i64_shr_s();
                         } else {
-                            backend.code_builder.f64_convert_u_i32()
+                            backend.code_builder.i64_const(0);
                         }
+                        backend.code_builder.i64_store(Align::Bytes8, offset + 8);
                     }
-                    (F64, I64) => {
-                        if arg_signed {
-                            backend.code_builder.f64_convert_s_i64()
-                        } else {
-                            backend.code_builder.f64_convert_u_i64()
-                        }
+                    (I128, I128) => {
+                        // Same bits, different signedness
+                        let (frame_ptr, offset) =
+                            i128_location(&self.ret_storage, backend.storage.stack_frame_pointer);
+                        backend.storage.copy_value_to_memory(
+                            &mut backend.code_builder,
+                            frame_ptr,
+                            offset,
+                            self.arguments[0],
+                        );
                     }
-                    _ => todo!("{:?}: {:?} -> {:?}", self.lowlevel, arg_type, ret_type),
+
+                    _ => panic_ret_type(),
                 }
             }
+            NumToFloatCast => self.load_arg_as_float(backend, self.ret_layout),
             NumToIntChecked => {
                 let arg_layout = backend.storage.symbol_layouts[&self.arguments[0]];
 
@@ -2021,7 +2247,69 @@ impl<'a> LowLevelCall<'a> {
                 }
             }
             NumToFloatChecked => {
-                todo!("implement toF32Checked and toF64Checked");
+                // The result is { a: F32 or F64, b: Bool }, where `b` is set if the value is out of
+                // the float's range. Fields are sorted by alignment, so the float comes first.
+                let float_layout = match self.ret_layout_raw {
+                    LayoutRepr::Struct(&[float_layout, _]) => float_layout,
+                    x => internal_error!("NumToFloatChecked is not defined for {:?}", x),
+                };
+                let (ret_local, ret_offset) = match &self.ret_storage {
+                    StoredValue::StackMemory { location, .. } => {
+                        location.local_and_offset(backend.storage.stack_frame_pointer)
+                    }
+                    _ => internal_error!("Invalid return value storage for NumToFloatChecked"),
+                };
+                let float_type = CodeGenNumType::from(float_layout);
+                let arg_type = CodeGenNumType::for_symbol(backend, self.arguments[0]);
+
+                backend.code_builder.get_local(ret_local);
+                self.load_arg_as_float(backend, float_layout);
+                match float_type {
+                    F32 => backend.code_builder.f32_store(Align::Bytes4, ret_offset),
+                    _ => backend.code_builder.f64_store(Align::Bytes8, ret_offset),
+                }
+
+                // A value out of range converts to infinity, but a float may already have been one
+                backend.code_builder.get_local(ret_local);
+                backend.code_builder.get_local(ret_local);
+                let flag_offset = match float_type {
+                    F32 => {
+                        backend.code_builder.f32_load(Align::Bytes4, ret_offset);
+                        backend.code_builder.f32_abs();
+                        backend.code_builder.f32_const(f32::INFINITY);
+                        backend.code_builder.f32_eq();
+                        ret_offset + 4
+                    }
+                    _ => {
+                        backend.code_builder.f64_load(Align::Bytes8, ret_offset);
+                        backend.code_builder.f64_abs();
+                        backend.code_builder.f64_const(f64::INFINITY);
+                        backend.code_builder.f64_eq();
+                        ret_offset + 8
+                    }
+                };
+                match arg_type {
+                    F32 => {
+                        backend
+                            .storage
+                            .load_symbols(&mut backend.code_builder, self.arguments);
+                        backend.code_builder.f32_abs();
+                        backend.code_builder.f32_const(f32::INFINITY);
+                        backend.code_builder.f32_ne();
+                        backend.code_builder.i32_and();
+                    }
+                    F64 => {
+                        backend
+                            .storage
+                            .load_symbols(&mut backend.code_builder, self.arguments);
+                        backend.code_builder.f64_abs();
+                        backend.code_builder.f64_const(f64::INFINITY);
+                        backend.code_builder.f64_ne();
+                        backend.code_builder.i32_and();
+                    }
+                    I32 | I64 | I128 | Decimal => {}
+                }
+                backend.code_builder.i32_store8(Align::Bytes1, flag_offset);
             }
             I128OfDec => self.load_args_and_call_zig(backend, bitcode::DEC_TO_I128),
             And => {
@@ -26,6 +26,7 @@ const MODULES: &[(ModuleId, &str)] = &[
     (ModuleId::DECODE, "Decode.roc"),
     (ModuleId::HASH, "Hash.roc"),
     (ModuleId::INSPECT, "Inspect.roc"),
+    (ModuleId::SORT, "Sort.roc"),
     (ModuleId::JSON, "TotallyNotJson.roc"),
 ];
 
@@ -18,8 +18,8 @@ const SKIP_SUBS_CACHE: bool = {
 
 pub use roc_load_internal::docs;
 pub use roc_load_internal::file::{
-    ExecutionMode, ExpectMetadata, LoadConfig, LoadResult, LoadStart, LoadingProblem, Phase,
-    Threading,
+    ExecutionMode, ExpectMetadata, LoadConfig, LoadResult, LoadStart, LoadingProblem,
+    MonoOptimizations, Phase, Threading,
 };
 pub use roc_load_internal::module::{
     CheckedModule, EntryPoint, Expectations, ExposedToHost, LoadedModule, MonomorphizedModule,
@@ -67,6 +67,8 @@ pub fn load_single_threaded<'a>(
         exposed_types,
         target_info,
         function_kind,
+        MonoOptimizations::NONE,
+        false,
         cached_subs,
         render,
         palette,
@@ -242,6 +244,7 @@ fn read_cached_types() -> MutMap<ModuleId, TypeState> {
     let mod_decode = include_bytes_align_as!(u128, concat!(env!("OUT_DIR"), "/Decode.dat"));
     let mod_hash = include_bytes_align_as!(u128, concat!(env!("OUT_DIR"), "/Hash.dat"));
     let mod_inspect = include_bytes_align_as!(u128, concat!(env!("OUT_DIR"), "/Inspect.dat"));
+    let mod_sort = include_bytes_align_as!(u128, concat!(env!("OUT_DIR"), "/Sort.dat"));
 
     let mut output = MutMap::default();
 
@@ -265,6 +268,7 @@ fn read_cached_types() -> MutMap<ModuleId, TypeState> {
 
         output.insert(ModuleId::HASH, deserialize_help(mod_hash));
         output.insert(ModuleId::INSPECT, deserialize_help(mod_inspect));
+        output.insert(ModuleId::SORT, deserialize_help(mod_sort));
     }
 
     output
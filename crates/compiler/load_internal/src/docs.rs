@@ -386,7 +386,7 @@ fn contains_unexposed_type(
                     contains_unexposed_type(&loc_arg.value, exposed_module_ids, module_ids)
                 })
         }
-        Malformed(_) | Inferred | Wildcard | BoundVariable(_) => false,
+        Malformed(_) | Inferred | TypeHole(_) | Wildcard | BoundVariable(_) => false,
         Function(loc_args, loc_ret) => {
             contains_unexposed_type(&loc_ret.value, exposed_module_ids, module_ids)
                 || loc_args.iter().any(|loc_arg| {
@@ -607,9 +607,9 @@ fn type_to_docs(in_func_type_ann: bool, type_annotation: ast::TypeAnnotation) ->
                 })
                 .collect(),
         },
-        ast::TypeAnnotation::Malformed(_) | ast::TypeAnnotation::Inferred => {
-            TypeAnnotation::NoTypeAnn
-        }
+        ast::TypeAnnotation::Malformed(_)
+        | ast::TypeAnnotation::Inferred
+        | ast::TypeAnnotation::TypeHole(_) => TypeAnnotation::NoTypeAnn,
     }
 }
 
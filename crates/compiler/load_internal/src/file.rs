@@ -25,7 +25,8 @@ use roc_constrain::module::constrain_module;
 use roc_debug_flags::dbg_do;
 #[cfg(debug_assertions)]
 use roc_debug_flags::{
-    ROC_CHECK_MONO_IR, ROC_PRINT_IR_AFTER_DROP_SPECIALIZATION, ROC_PRINT_IR_AFTER_REFCOUNT,
+    ROC_CHECK_MONO_IR, ROC_PRINT_IR_AFTER_CONSTANT_FOLDING, ROC_PRINT_IR_AFTER_DROP_SPECIALIZATION,
+    ROC_PRINT_IR_AFTER_ESCAPE_ANALYSIS, ROC_PRINT_IR_AFTER_INLINING, ROC_PRINT_IR_AFTER_REFCOUNT,
     ROC_PRINT_IR_AFTER_RESET_REUSE, ROC_PRINT_IR_AFTER_SPECIALIZATION, ROC_PRINT_IR_AFTER_TRMC,
     ROC_PRINT_LOAD_LOG,
 };
@@ -38,14 +39,14 @@ use roc_module::symbol::{
     PackageQualified, Symbol,
 };
 use roc_mono::ir::{
-    CapturedSymbols, ExternalSpecializations, GlueLayouts, HostExposedLambdaSets, PartialProc,
-    Proc, ProcLayout, Procs, ProcsBase, UpdateModeIds, UsageTrackingMap,
+    CapturedSymbols, ExternalSpecializations, GlueGetters, GlueLayouts, HostExposedLambdaSets,
+    PartialProc, Proc, ProcLayout, Procs, ProcsBase, UpdateModeIds, UsageTrackingMap,
 };
 use roc_mono::layout::{
     GlobalLayoutInterner, LambdaName, Layout, LayoutCache, LayoutProblem, Niche, STLayoutInterner,
 };
 use roc_mono::reset_reuse;
-use roc_mono::{drop_specialization, inc_dec};
+use roc_mono::{constant_folding, drop_specialization, escape, inc_dec, inline};
 use roc_packaging::cache::RocCacheDir;
 use roc_parse::ast::{
     self, CommentOrNewline, Expr, ExtractSpaces, Pattern, Spaced, StrLiteral, ValueDef,
@@ -102,6 +103,8 @@ macro_rules! log {
     ($($arg:tt)*) => (dbg_do!(ROC_PRINT_LOAD_LOG, println!($($arg)*)))
 }
 
+pub use roc_mono::ir::MonoOptimizations;
+
 #[derive(Debug)]
 pub struct LoadConfig {
     pub target_info: TargetInfo,
@@ -110,6 +113,9 @@ pub struct LoadConfig {
     pub threading: Threading,
     pub exec_mode: ExecutionMode,
     pub function_kind: FunctionKind,
+    pub mono_optimizations: MonoOptimizations,
+    /// Whether mono should record where definitions come from, so backends can emit debug info.
+    pub emit_debug_info: bool,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -403,6 +409,7 @@ fn start_phase<'a>(
                     derived_module,
                     expectations,
                     build_expects,
+                    emit_debug_info: state.emit_debug_info,
                 }
             }
             Phase::MakeSpecializations => {
@@ -537,6 +544,7 @@ fn start_phase<'a>(
                     exposed_by_module: state.exposed_types.clone(),
                     derived_module,
                     expectations,
+                    emit_debug_info: state.emit_debug_info,
                 }
             }
         }
@@ -601,6 +609,7 @@ enum Msg<'a> {
         abilities_store: AbilitiesStore,
         toplevel_expects: ToplevelExpects,
         expectations: Option<Expectations>,
+        definition_regions: MutMap<Symbol, Region>,
     },
     MadeSpecializations {
         module_id: ModuleId,
@@ -610,10 +619,12 @@ enum Msg<'a> {
         procs_base: ProcsBase<'a>,
         procedures: MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
         host_exposed_lambda_sets: HostExposedLambdaSets<'a>,
+        glue_getters: GlueGetters<'a>,
         update_mode_ids: UpdateModeIds,
         module_timing: ModuleTiming,
         subs: Subs,
         expectations: Option<Expectations>,
+        call_regions: MutMap<Symbol, Region>,
     },
 
     /// The task is to only typecheck AND monomorphize modules
@@ -696,6 +707,8 @@ struct State<'a> {
     pub platform_path: PlatformPath<'a>,
     pub target_info: TargetInfo,
     pub(self) function_kind: FunctionKind,
+    pub(self) mono_optimizations: MonoOptimizations,
+    pub(self) emit_debug_info: bool,
 
     /// Note: only packages and platforms actually expose any modules;
     /// for all others, this will be empty.
@@ -705,6 +718,7 @@ struct State<'a> {
     pub dependencies: Dependencies<'a>,
     pub procedures: MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
     pub host_exposed_lambda_sets: HostExposedLambdaSets<'a>,
+    pub glue_getters: GlueGetters<'a>,
     pub toplevel_expects: ToplevelExpects,
     pub exposed_to_host: ExposedToHost,
 
@@ -759,6 +773,8 @@ impl<'a> State<'a> {
         opt_platform_shorthand: Option<&'a str>,
         target_info: TargetInfo,
         function_kind: FunctionKind,
+        mono_optimizations: MonoOptimizations,
+        emit_debug_info: bool,
         exposed_types: ExposedByModule,
         arc_modules: Arc<Mutex<PackageModuleIds<'a>>>,
         ident_ids_by_module: SharedIdentIdsByModule,
@@ -779,12 +795,15 @@ impl<'a> State<'a> {
             cache_dir,
             target_info,
             function_kind,
+            mono_optimizations,
+            emit_debug_info,
             platform_data: None,
             platform_path: PlatformPath::NotSpecified,
             module_cache: ModuleCache::default(),
             dependencies,
             procedures: MutMap::default(),
             host_exposed_lambda_sets: std::vec::Vec::new(),
+            glue_getters: std::vec::Vec::new(),
             toplevel_expects: ToplevelExpects::default(),
             exposed_to_host: ExposedToHost::default(),
             exposed_modules: &[],
@@ -910,6 +929,7 @@ enum BuildTask<'a> {
         derived_module: SharedDerivedModule,
         expectations: Option<Expectations>,
         build_expects: bool,
+        emit_debug_info: bool,
     },
     MakeSpecializations {
         module_id: ModuleId,
@@ -923,6 +943,7 @@ enum BuildTask<'a> {
         world_abilities: WorldAbilities,
         derived_module: SharedDerivedModule,
         expectations: Option<Expectations>,
+        emit_debug_info: bool,
     },
 }
 
@@ -1056,6 +1077,8 @@ pub fn load_and_typecheck_str<'a>(
         threading,
         exec_mode: ExecutionMode::Check,
         function_kind,
+        mono_optimizations: MonoOptimizations::NONE,
+        emit_debug_info: false,
     };
 
     match load(
@@ -1317,6 +1340,8 @@ pub fn load<'a>(
             exposed_types,
             load_config.target_info,
             load_config.function_kind,
+            load_config.mono_optimizations,
+            load_config.emit_debug_info,
             cached_types,
             load_config.render,
             load_config.palette,
@@ -1329,6 +1354,8 @@ pub fn load<'a>(
             exposed_types,
             load_config.target_info,
             load_config.function_kind,
+            load_config.mono_optimizations,
+            load_config.emit_debug_info,
             cached_types,
             load_config.render,
             load_config.palette,
@@ -1346,6 +1373,8 @@ pub fn load_single_threaded<'a>(
     exposed_types: ExposedByModule,
     target_info: TargetInfo,
     function_kind: FunctionKind,
+    mono_optimizations: MonoOptimizations,
+    emit_debug_info: bool,
     cached_types: MutMap<ModuleId, TypeState>,
     render: RenderTarget,
     palette: Palette,
@@ -1374,6 +1403,8 @@ pub fn load_single_threaded<'a>(
         opt_platform_shorthand,
         target_info,
         function_kind,
+        mono_optimizations,
+        emit_debug_info,
         exposed_types,
         arc_modules,
         ident_ids_by_module,
@@ -1670,6 +1701,8 @@ fn load_multi_threaded<'a>(
     exposed_types: ExposedByModule,
     target_info: TargetInfo,
     function_kind: FunctionKind,
+    mono_optimizations: MonoOptimizations,
+    emit_debug_info: bool,
     cached_types: MutMap<ModuleId, TypeState>,
     render: RenderTarget,
     palette: Palette,
@@ -1714,6 +1747,8 @@ fn load_multi_threaded<'a>(
         opt_platform_shorthand,
         target_info,
         function_kind,
+        mono_optimizations,
+        emit_debug_info,
         exposed_types,
         arc_modules,
         ident_ids_by_module,
@@ -2168,6 +2203,103 @@ fn report_unused_imported_modules(
     }
 }
 
+/// Constant folding only knows the symbols of the overflowing operations; point at the
+/// definition they are part of, or at the whole top-level definition if they are temporaries.
+fn report_constant_overflows(
+    module_cache: &mut ModuleCache<'_>,
+    overflows: std::vec::Vec<constant_folding::ConstantOverflow>,
+) {
+    for overflow in overflows {
+        let module_id = overflow.proc_name.module_id();
+
+        if module_id.is_builtin() {
+            continue;
+        }
+
+        let region = module_cache
+            .call_regions
+            .get(&overflow.symbol)
+            .or_else(|| module_cache.definition_regions.get(&overflow.proc_name))
+            .copied()
+            .unwrap_or_else(Region::zero);
+
+        module_cache
+            .can_problems
+            .entry(module_id)
+            .or_default()
+            .push(roc_problem::can::Problem::ConstantOverflow {
+                op: overflow.op,
+                region,
+            });
+    }
+}
+
+/// Remember what an app's own modules expose, and what every module uses from other modules, so
+/// that exposed values nobody uses can be reported once all modules are canonicalized.
+fn record_exports_and_uses(
+    state: &mut State<'_>,
+    module_id: ModuleId,
+    constrained_module: &ConstrainedModule,
+) {
+    let module = &constrained_module.module;
+
+    state.module_cache.used_exports.extend(
+        module
+            .referenced_values
+            .iter()
+            .filter(|symbol| symbol.module_id() != module_id),
+    );
+
+    let is_app_module = module_id != state.root_id
+        && matches!(state.platform_path, PlatformPath::Valid(_))
+        && state
+            .arc_modules
+            .lock()
+            .package_eq(module_id, state.root_id)
+            .unwrap_or(false);
+
+    if !is_app_module {
+        return;
+    }
+
+    let declarations = &constrained_module.declarations;
+    let exported_values = (0..declarations.len())
+        .filter(|index| {
+            use roc_can::expr::DeclarationTag::*;
+
+            matches!(
+                declarations.declarations[*index],
+                Value | Function(_) | Recursive(_) | TailRecursive(_)
+            )
+        })
+        .map(|index| declarations.symbols[index])
+        .filter(|loc_symbol| module.exposed_symbols.contains(&loc_symbol.value))
+        .collect();
+
+    state
+        .module_cache
+        .exported_values
+        .insert(module_id, exported_values);
+}
+
+/// Report values exposed by an app's own modules that no other module uses
+fn report_unused_exports(module_cache: &mut ModuleCache) {
+    for (module_id, exported_values) in module_cache.exported_values.drain() {
+        let unused = exported_values
+            .into_iter()
+            .filter(|loc_symbol| !module_cache.used_exports.contains(&loc_symbol.value))
+            .map(|loc_symbol| {
+                roc_problem::can::Problem::UnusedExport(loc_symbol.value, loc_symbol.region)
+            });
+
+        module_cache
+            .can_problems
+            .entry(module_id)
+            .or_default()
+            .extend(unused);
+    }
+}
+
 fn extend_header_with_builtin(header: &mut ModuleHeader, module: ModuleId) {
     header
         .package_qualified_imported_modules
@@ -2419,6 +2551,7 @@ fn update<'a>(
                 extend_header_with_builtin(header, ModuleId::DECODE);
                 extend_header_with_builtin(header, ModuleId::HASH);
                 extend_header_with_builtin(header, ModuleId::INSPECT);
+                extend_header_with_builtin(header, ModuleId::SORT);
             }
 
             state
@@ -2495,6 +2628,7 @@ fn update<'a>(
             }
 
             report_unused_imported_modules(&mut state, module_id, &constrained_module);
+            record_exports_and_uses(&mut state, module_id, &constrained_module);
 
             state
                 .module_cache
@@ -2537,10 +2671,31 @@ fn update<'a>(
             log!("solved types for {:?}", module_id);
             module_timing.end_time = Instant::now();
 
+            let mut type_problems = solved_module.problems;
+
+            // An erroneous module may well have meant to use an implementation somewhere.
+            let is_not_warning = |severity| severity != Severity::Warning;
+            let has_errors = type_problems
+                .iter()
+                .any(|problem| is_not_warning(problem.severity()))
+                || state
+                    .module_cache
+                    .can_problems
+                    .get(&module_id)
+                    .map_or(false, |problems| {
+                        problems
+                            .iter()
+                            .any(|problem| is_not_warning(problem.severity()))
+                    });
+            if has_errors {
+                type_problems
+                    .retain(|problem| !matches!(problem, TypeError::UnusedImplementation { .. }));
+            }
+
             state
                 .module_cache
                 .type_problems
-                .insert(module_id, solved_module.problems);
+                .insert(module_id, type_problems);
 
             let should_include_expects = (!loc_expects.is_empty() || !loc_dbgs.is_empty()) && {
                 let modules = state.arc_modules.lock();
@@ -2733,6 +2888,7 @@ fn update<'a>(
             abilities_store,
             toplevel_expects,
             expectations,
+            definition_regions,
         } => {
             log!("found specializations for {:?}", module_id);
 
@@ -2741,6 +2897,11 @@ fn update<'a>(
             state.toplevel_expects.pure.extend(toplevel_expects.pure);
             state.toplevel_expects.fx.extend(toplevel_expects.fx);
 
+            state
+                .module_cache
+                .definition_regions
+                .extend(definition_regions);
+
             state
                 .module_cache
                 .top_level_thunks
@@ -2779,11 +2940,12 @@ fn update<'a>(
             procs_base,
             procedures,
             host_exposed_lambda_sets,
+            glue_getters,
             external_specializations_requested,
             module_timing,
             layout_cache,
             expectations,
-            ..
+            call_regions,
         } => {
             debug_assert!(
                 state.goal_phase() == Phase::MakeSpecializations
@@ -2796,9 +2958,11 @@ fn update<'a>(
             let _ = layout_cache;
 
             state.procedures.extend(procedures);
+            state.module_cache.call_regions.extend(call_regions);
             state
                 .host_exposed_lambda_sets
                 .extend(host_exposed_lambda_sets);
+            state.glue_getters.extend(glue_getters);
             state.module_cache.late_specializations.insert(
                 module_id,
                 LateSpecializationsModule {
@@ -2916,6 +3080,46 @@ fn update<'a>(
 
                     let ident_ids = state.constrained_ident_ids.get_mut(&module_id).unwrap();
 
+                    if state.mono_optimizations.inline_procs {
+                        inline::inline_procs(
+                            arena,
+                            module_id,
+                            ident_ids,
+                            &mut update_mode_ids,
+                            &mut state.procedures,
+                        );
+
+                        debug_print_ir!(state, &layout_interner, ROC_PRINT_IR_AFTER_INLINING);
+                    }
+
+                    if state.mono_optimizations.elide_local_allocations {
+                        escape::elide_local_allocations(arena, &mut state.procedures);
+
+                        debug_print_ir!(
+                            state,
+                            &layout_interner,
+                            ROC_PRINT_IR_AFTER_ESCAPE_ANALYSIS
+                        );
+                    }
+
+                    if state.mono_optimizations.fold_constants {
+                        let overflows = constant_folding::fold_constants(
+                            arena,
+                            &layout_interner,
+                            module_id,
+                            ident_ids,
+                            &mut state.procedures,
+                        );
+
+                        report_constant_overflows(&mut state.module_cache, overflows);
+
+                        debug_print_ir!(
+                            state,
+                            &layout_interner,
+                            ROC_PRINT_IR_AFTER_CONSTANT_FOLDING
+                        );
+                    }
+
                     roc_mono::tail_recursion::apply_trmc(
                         arena,
                         &mut layout_interner,
@@ -3116,10 +3320,10 @@ fn log_layout_stats(module_id: ModuleId, layout_cache: &LayoutCache) {
 
 fn finish_specialization<'a>(
     arena: &'a Bump,
-    state: State<'a>,
+    mut state: State<'a>,
     subs: Subs,
     layout_interner: STLayoutInterner<'a>,
-    exposed_to_host: ExposedToHost,
+    mut exposed_to_host: ExposedToHost,
     module_expectations: VecMap<ModuleId, Expectations>,
 ) -> Result<MonomorphizedModule<'a>, LoadingProblem<'a>> {
     if false {
@@ -3128,6 +3332,9 @@ fn finish_specialization<'a>(
             roc_types::types::get_type_clone_count()
         );
     }
+
+    report_unused_exports(&mut state.module_cache);
+
     let module_ids = Arc::try_unwrap(state.arc_modules)
         .unwrap_or_else(|_| panic!("There were still outstanding Arc references to module_ids"))
         .into_inner()
@@ -3219,15 +3426,24 @@ fn finish_specialization<'a>(
         toplevel_expects,
         procedures,
         host_exposed_lambda_sets,
+        glue_getters,
         module_cache,
         platform_data,
         ..
     } = state;
 
+    // The host calls these directly, so the stub library it is linked against needs them too
+    exposed_to_host.lambda_sets = host_exposed_lambda_sets
+        .iter()
+        .map(|(proc_name, _, hels)| (proc_name.name(), hels.id))
+        .collect();
+    exposed_to_host.getters = glue_getters.iter().map(|(symbol, _)| *symbol).collect();
+
     let ModuleCache {
         type_problems,
         can_problems,
         sources,
+        definition_regions,
         ..
     } = module_cache;
 
@@ -3257,9 +3473,12 @@ fn finish_specialization<'a>(
         host_exposed_lambda_sets,
         entry_point,
         sources,
+        definition_regions,
         timings: state.timings,
         toplevel_expects,
-        glue_layouts: GlueLayouts { getters: vec![] },
+        glue_layouts: GlueLayouts {
+            getters: glue_getters,
+        },
         uses_prebuilt_platform,
     })
 }
@@ -3296,6 +3515,8 @@ fn finish(
     //
     #[cfg(debug_assertions)] checkmate: Option<roc_checkmate::Collector>,
 ) -> LoadedModule {
+    report_unused_exports(&mut state.module_cache);
+
     let module_ids = Arc::try_unwrap(state.arc_modules)
         .unwrap_or_else(|_| panic!("There were still outstanding Arc references to module_ids"))
         .into_inner()
@@ -3620,6 +3841,7 @@ fn load_module<'a>(
         "Decode", ModuleId::DECODE
         "Hash", ModuleId::HASH
         "Inspect", ModuleId::INSPECT
+        "Sort", ModuleId::SORT
         "TotallyNotJson", ModuleId::JSON
     }
 
@@ -4941,14 +5163,16 @@ fn run_solve_solve(
                 })
         };
 
-        // Expose anything that is explicitly exposed by the header, or is a specialization of an
-        // ability.
+        // Expose anything that is explicitly exposed by the header, is a specialization of an
+        // ability, or is the default implementation of an ability member, which opaques in other
+        // modules may fall back to.
         let exposed_vars_by_symbol: Vec<_> = solve_output
             .scope
             .vars_by_symbol()
             .filter(|(k, _)| {
                 exposed_symbols.contains(k)
                     || is_specialization_symbol(*k)
+                    || solve_output.resolved_abilities_store.is_member_default(*k)
                     || k.is_exposed_for_builtin_derivers()
             })
             .collect();
@@ -4959,13 +5183,37 @@ fn run_solve_solve(
     let roc_solve::module::SolveOutput {
         subs,
         scope: _,
-        errors,
+        mut errors,
         resolved_abilities_store,
+        undemanded_implementations,
 
         #[cfg(debug_assertions)]
         checkmate,
     } = solve_output;
 
+    if !module.module_id.is_builtin() && !undemanded_implementations.is_empty() {
+        // Other modules may need the implementations of any opaque type they can see.
+        let mut reachable_opaques = VecSet::default();
+        for (symbol, var) in exposed_vars_by_symbol.iter() {
+            if exposed_symbols.contains(symbol) {
+                subs.inner().collect_aliases(*var, &mut reachable_opaques);
+            }
+        }
+
+        for (opaque, ability) in undemanded_implementations {
+            if exposed_symbols.contains(&opaque) || reachable_opaques.contains(&opaque) {
+                continue;
+            }
+            if let Some((_, alias)) = aliases.get(&opaque) {
+                errors.push(TypeError::UnusedImplementation {
+                    region: alias.region,
+                    opaque,
+                    ability,
+                });
+            }
+        }
+    }
+
     SolveResult {
         solved: subs,
         solved_implementations,
@@ -5347,6 +5595,7 @@ fn canonicalize_and_constrain<'a>(
             module_output.symbols_from_requires,
             &module_output.scope.abilities_store,
             &module_output.declarations,
+            &module_output.annotation_type_holes,
             module_id,
         )
     };
@@ -5384,6 +5633,7 @@ fn canonicalize_and_constrain<'a>(
                         | ModuleId::SET
                         | ModuleId::HASH
                         | ModuleId::INSPECT
+                        | ModuleId::SORT
                 );
 
                 if !name.is_builtin() || should_include_builtin {
@@ -5603,6 +5853,7 @@ fn make_specializations<'a>(
     exposed_by_module: &ExposedByModule,
     derived_module: SharedDerivedModule,
     mut expectations: Option<Expectations>,
+    emit_debug_info: bool,
 ) -> Msg<'a> {
     let make_specializations_start = Instant::now();
     let mut update_mode_ids = UpdateModeIds::new();
@@ -5621,6 +5872,8 @@ fn make_specializations<'a>(
         exposed_by_module,
         derived_module: &derived_module,
         struct_indexing: UsageTrackingMap::default(),
+        emit_debug_info,
+        call_regions: MutMap::default(),
     };
 
     let mut procs = Procs::new_in(arena);
@@ -5649,12 +5902,14 @@ fn make_specializations<'a>(
     );
 
     let external_specializations_requested = procs.externals_we_need.clone();
-    let (procedures, host_exposed_lambda_sets, restored_procs_base) =
+    let (procedures, host_exposed_lambda_sets, glue_getters, restored_procs_base) =
         procs.get_specialized_procs_without_rc();
 
     // Turn `Bytes.Decode.IdentId(238)` into `Bytes.Decode.238`, we rely on this in mono tests
     mono_env.home.register_debug_idents(mono_env.ident_ids);
 
+    let call_regions = std::mem::take(&mut mono_env.call_regions);
+
     let make_specializations_end = Instant::now();
     module_timing
         .make_specializations
@@ -5667,11 +5922,13 @@ fn make_specializations<'a>(
         procs_base: restored_procs_base,
         procedures,
         host_exposed_lambda_sets,
+        glue_getters,
         update_mode_ids,
         subs,
         expectations,
         external_specializations_requested,
         module_timing,
+        call_regions,
     }
 }
 
@@ -5691,12 +5948,21 @@ fn build_pending_specializations<'a>(
     derived_module: SharedDerivedModule,
     mut expectations: Option<Expectations>,
     build_expects: bool,
+    emit_debug_info: bool,
 ) -> Msg<'a> {
     let find_specializations_start = Instant::now();
 
     let mut module_thunks = bumpalo::collections::Vec::new_in(arena);
     let mut toplevel_expects = ToplevelExpects::default();
 
+    // Debug info maps procedures and values back to their definitions in the source code.
+    // Nobody steps through the builtins, so don't bother for those.
+    let definition_regions = if home.is_builtin() {
+        MutMap::default()
+    } else {
+        roc_can::traverse::find_definition_regions(&declarations)
+    };
+
     let mut procs_base = ProcsBase {
         partial_procs: BumpMap::default(),
         module_thunks: &[],
@@ -5724,6 +5990,9 @@ fn build_pending_specializations<'a>(
         exposed_by_module,
         derived_module: &derived_module,
         struct_indexing: UsageTrackingMap::default(),
+        // See `definition_regions` above.
+        emit_debug_info: emit_debug_info && !home.is_builtin(),
+        call_regions: MutMap::default(),
     };
 
     let layout_cache_snapshot = layout_cache.snapshot();
@@ -6123,6 +6392,7 @@ fn build_pending_specializations<'a>(
         abilities_store,
         toplevel_expects,
         expectations,
+        definition_regions,
     }
 }
 
@@ -6206,6 +6476,9 @@ fn load_derived_partial_procs<'a>(
             exposed_by_module,
             derived_module,
             struct_indexing: UsageTrackingMap::default(),
+            // Derived implementations have no source to point at.
+            emit_debug_info: false,
+            call_regions: MutMap::default(),
         };
 
         let partial_proc = match derived_expr {
@@ -6365,6 +6638,7 @@ fn run_task<'a>(
             derived_module,
             expectations,
             build_expects,
+            emit_debug_info,
         } => Ok(build_pending_specializations(
             arena,
             solved_subs,
@@ -6381,6 +6655,7 @@ fn run_task<'a>(
             derived_module,
             expectations,
             build_expects,
+            emit_debug_info,
         )),
         MakeSpecializations {
             module_id,
@@ -6394,6 +6669,7 @@ fn run_task<'a>(
             exposed_by_module,
             derived_module,
             expectations,
+            emit_debug_info,
         } => Ok(make_specializations(
             arena,
             module_id,
@@ -6408,6 +6684,7 @@ fn run_task<'a>(
             &exposed_by_module,
             derived_module,
             expectations,
+            emit_debug_info,
         )),
     }?;
 
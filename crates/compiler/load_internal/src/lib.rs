@@ -26,5 +26,6 @@ pub const BUILTIN_MODULES: &[(ModuleId, &str)] = &[
     (ModuleId::DECODE, "Decode"),
     (ModuleId::HASH, "Hash"),
     (ModuleId::INSPECT, "Inspect"),
+    (ModuleId::SORT, "Sort"),
     (ModuleId::JSON, "TotallyNotJson"),
 ];
@@ -178,6 +178,8 @@ pub struct MonomorphizedModule<'a> {
     pub entry_point: EntryPoint<'a>,
     pub exposed_to_host: ExposedToHost,
     pub sources: MutMap<ModuleId, (PathBuf, Box<str>)>,
+    /// Where the named values of each (non-builtin) module are defined
+    pub definition_regions: MutMap<Symbol, Region>,
     pub timings: MutMap<ModuleId, ModuleTiming>,
     pub expectations: VecMap<ModuleId, Expectations>,
     pub uses_prebuilt_platform: bool,
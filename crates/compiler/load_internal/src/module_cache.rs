@@ -9,6 +9,7 @@ use roc_module::ident::ModuleName;
 use roc_module::symbol::{ModuleId, PQModuleName, Symbol};
 use roc_mono::ir::ExternalSpecializations;
 use roc_problem::Severity;
+use roc_region::all::{Loc, Region};
 use roc_solve_problem::TypeError;
 use roc_types::types::Alias;
 use std::path::PathBuf;
@@ -38,7 +39,16 @@ pub(crate) struct ModuleCache<'a> {
     pub(crate) can_problems: MutMap<ModuleId, Vec<roc_problem::can::Problem>>,
     pub(crate) type_problems: MutMap<ModuleId, Vec<TypeError>>,
 
+    /// Values exposed by the app's own modules, and the values modules use from one another;
+    /// together they tell us which exposed values nobody imports.
+    pub(crate) exported_values: MutMap<ModuleId, Vec<Loc<Symbol>>>,
+    pub(crate) used_exports: MutSet<Symbol>,
+
     pub(crate) sources: MutMap<ModuleId, (PathBuf, &'a str)>,
+    /// Where the named values of each module are defined, for debug info
+    pub(crate) definition_regions: MutMap<Symbol, Region>,
+    /// Where the operations whose results are bound to these symbols are, for constant overflows
+    pub(crate) call_regions: MutMap<Symbol, Region>,
 }
 
 impl<'a> ModuleCache<'a> {
@@ -87,6 +97,7 @@ impl Default for ModuleCache<'_> {
             DECODE,
             HASH,
             INSPECT,
+            SORT,
             JSON,
         }
 
@@ -107,7 +118,11 @@ impl Default for ModuleCache<'_> {
             documentation: Default::default(),
             can_problems: Default::default(),
             type_problems: Default::default(),
+            exported_values: Default::default(),
+            used_exports: Default::default(),
             sources: Default::default(),
+            definition_regions: Default::default(),
+            call_regions: Default::default(),
         }
     }
 }
@@ -18,7 +18,7 @@ use crate::helpers::fixtures_dir;
 use bumpalo::Bump;
 use roc_can::module::ExposedByModule;
 use roc_load_internal::file::{
-    ExecutionMode, LoadConfig, LoadResult, LoadStart, LoadingProblem, Threading,
+    ExecutionMode, LoadConfig, LoadResult, LoadStart, LoadingProblem, MonoOptimizations, Threading,
 };
 use roc_load_internal::module::LoadedModule;
 use roc_module::ident::ModuleName;
@@ -59,6 +59,8 @@ fn load_and_typecheck(
         palette: DEFAULT_PALETTE,
         threading: Threading::Single,
         exec_mode: ExecutionMode::Check,
+        mono_optimizations: MonoOptimizations::NONE,
+        emit_debug_info: false,
     };
 
     match roc_load_internal::file::load(
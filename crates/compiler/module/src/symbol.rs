@@ -53,6 +53,11 @@ pub const DERIVABLE_ABILITIES: &[(Symbol, &[Symbol])] = &[
     (Symbol::DECODE_DECODING, &[Symbol::DECODE_DECODER]),
     (Symbol::HASH_HASH_ABILITY, &[Symbol::HASH_HASH]),
     (Symbol::BOOL_EQ, &[Symbol::BOOL_IS_EQ]),
+    (Symbol::SORT_SORT_ABILITY, &[Symbol::SORT_COMPARE]),
+    (
+        Symbol::INSPECT_INSPECT_ABILITY,
+        &[Symbol::INSPECT_TO_INSPECTOR],
+    ),
 ];
 
 /// In Debug builds only, Symbol has a name() method that lets
@@ -1347,6 +1352,7 @@ define_builtins! {
         57 STR_RELEASE_EXCESS_CAPACITY: "releaseExcessCapacity"
         58 STR_WALK_UTF8: "walkUtf8"
         59 STR_CONTAINS: "contains"
+        60 STR_COMPARE: "compare"
     }
     6 LIST: "List" => {
         0 LIST_LIST: "List" exposed_apply_type=true // the List.List type alias
@@ -1433,6 +1439,8 @@ define_builtins! {
         81 LIST_RELEASE_EXCESS_CAPACITY: "releaseExcessCapacity"
         82 LIST_UPDATE: "update"
         83 LIST_WALK_WITH_INDEX: "walkWithIndex"
+        84 LIST_SORT: "sort"
+        85 LIST_COMPARE: "compare"
     }
     7 RESULT: "Result" => {
         0 RESULT_RESULT: "Result" exposed_type=true // the Result.Result type alias
@@ -1623,6 +1631,9 @@ define_builtins! {
         29 INSPECT_CUSTOM: "custom"
         30 INSPECT_APPLY: "apply"
         31 INSPECT_TO_INSPECTOR: "toInspector"
+        32 INSPECT_INSPECT_LIST: "inspectList"
+        33 INSPECT_INSPECT_NAT: "inspectNat"
+        34 INSPECT_TO_STR: "toStr"
     }
     15 JSON: "TotallyNotJson" => {
         0 JSON_JSON: "TotallyNotJson"
@@ -1633,6 +1644,11 @@ define_builtins! {
         5 JSON_ARRAY_CLOSING_STATE: "ArrayClosingState"
         6 JSON_OBJECT_STATE: "ObjectState"
     }
+    16 SORT: "Sort" => {
+        0 SORT_SORT_ABILITY: "Sort" exposed_type=true
+        1 SORT_COMPARE: "compare"
+        2 SORT_COMPARE_BOOL: "compareBool"
+    }
 
-    num_modules: 16 // Keep this count up to date by hand! (TODO: see the mut_map! macro for how we could determine this count correctly in the macro)
+    num_modules: 17 // Keep this count up to date by hand! (TODO: see the mut_map! macro for how we could determine this count correctly in the macro)
 }
@@ -0,0 +1,771 @@
+//! Constant folding for the mono IR.
+//!
+//! Low-level operations whose arguments are all literals are evaluated at compile time, and a
+//! `Switch` on a known value is replaced by the branch it would take. Top-level constants
+//! (thunks whose body only builds literals, records and lists) are inlined at their use sites,
+//! so that their values can be folded further in turn. Known list elements are written into the
+//! list literal itself, so that backends emit the list as static data, like string literals,
+//! instead of building it on the heap at every use.
+//!
+//! Operations that always overflow are left in place, so they still crash at runtime, and are
+//! returned to the caller to be reported.
+
+use bumpalo::collections::vec::Vec;
+use bumpalo::Bump;
+
+use roc_builtins::bitcode::{FloatWidth, IntWidth};
+use roc_collections::MutMap;
+use roc_module::low_level::LowLevel;
+use roc_module::symbol::{IdentIds, ModuleId, Symbol};
+
+use crate::ir::{Call, CallType, Expr, ListLiteralElement, Literal, Proc, ProcLayout, Stmt};
+use crate::layout::{Builtin, InLayout, LayoutInterner, LayoutRepr, STLayoutInterner};
+
+/// Top-level constants that take more bindings than this to build are not inlined.
+const MAX_INLINED_CONSTANT_SIZE: usize = 32;
+
+/// An operation on constants whose result does not fit in its type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConstantOverflow {
+    /// The procedure that contains the operation.
+    pub proc_name: Symbol,
+    /// The symbol that the result of the operation is bound to.
+    pub symbol: Symbol,
+    pub op: LowLevel,
+}
+
+/**
+Fold operations on literals, and inline small top-level constants at their use sites.
+
+This runs until no more top-level constants are discovered, because folding can turn
+a thunk like `x = y + 1` into a constant once `y` has been inlined into it.
+*/
+pub fn fold_constants<'a>(
+    arena: &'a Bump,
+    layout_interner: &STLayoutInterner<'a>,
+    home: ModuleId,
+    ident_ids: &mut IdentIds,
+    procs: &mut MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+) -> std::vec::Vec<ConstantOverflow> {
+    let mut constants = MutMap::default();
+    let mut overflows = std::vec::Vec::new();
+
+    loop {
+        for proc in procs.values_mut() {
+            let mut env = FoldEnv {
+                arena,
+                layout_interner,
+                home,
+                ident_ids: &mut *ident_ids,
+                constants: &constants,
+                literals: MutMap::default(),
+                proc_name: proc.name.name(),
+                overflows: &mut overflows,
+            };
+
+            let new_body = env.fold_stmt(&proc.body);
+            proc.body = new_body.clone();
+        }
+
+        let mut found_new_constant = false;
+
+        for (key, proc) in procs.iter() {
+            if !constants.contains_key(key) && is_constant_thunk(proc) {
+                constants.insert(*key, &*arena.alloc(proc.body.clone()));
+                found_new_constant = true;
+            }
+        }
+
+        if !found_new_constant {
+            return overflows;
+        }
+    }
+}
+
+/// A thunk that only binds literals, and records and lists of those, and returns one of them.
+fn is_constant_thunk(proc: &Proc) -> bool {
+    proc.args.is_empty()
+        && proc.closure_data_layout.is_none()
+        && !proc.is_erased
+        && matches!(constant_size(&proc.body), Some(size) if size <= MAX_INLINED_CONSTANT_SIZE)
+}
+
+fn constant_size(body: &Stmt) -> Option<usize> {
+    let mut bound = std::vec::Vec::new();
+    let mut size = 0;
+    let mut stmt = body;
+
+    loop {
+        match stmt {
+            Stmt::Let(symbol, expr, _, continuation) => {
+                match expr {
+                    Expr::Literal(_) | Expr::EmptyArray => {}
+                    Expr::Struct(fields) => {
+                        if !fields.iter().all(|field| bound.contains(field)) {
+                            return None;
+                        }
+                    }
+                    Expr::Array { elems, .. } => {
+                        let is_closed = elems.iter().all(|elem| match elem {
+                            ListLiteralElement::Literal(_) => true,
+                            ListLiteralElement::Symbol(symbol) => bound.contains(symbol),
+                        });
+
+                        if !is_closed {
+                            return None;
+                        }
+
+                        size += elems.len();
+                    }
+                    _ => return None,
+                }
+
+                bound.push(*symbol);
+                size += 1;
+                stmt = *continuation;
+            }
+            Stmt::Located { remainder, .. } => stmt = *remainder,
+            Stmt::Ret(symbol) if bound.contains(symbol) => return Some(size),
+            _ => return None,
+        }
+    }
+}
+
+/// A known value, as used by the folding arithmetic.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Value {
+    /// Integers are sign- or zero-extended to 128 bits, except that U128 values are stored
+    /// as their bit pattern.
+    Int(i128, IntWidth),
+    Float(f64, FloatWidth),
+    Dec(i128),
+    Bool(bool),
+}
+
+impl Value {
+    fn to_literal<'a>(self) -> Literal<'a> {
+        match self {
+            Value::Int(value, IntWidth::U128) => Literal::U128((value as u128).to_ne_bytes()),
+            Value::Int(value, _) => Literal::Int(value.to_ne_bytes()),
+            Value::Float(value, _) => Literal::Float(value),
+            Value::Dec(value) => Literal::Decimal(value.to_ne_bytes()),
+            Value::Bool(value) => Literal::Bool(value),
+        }
+    }
+}
+
+enum Folded {
+    Value(Value),
+    /// The operation always overflows; it must be kept so that it crashes at runtime.
+    Overflow,
+    /// The operation cannot be evaluated at compile time.
+    Unknown,
+}
+
+struct FoldEnv<'a, 'r> {
+    arena: &'a Bump,
+    layout_interner: &'r STLayoutInterner<'a>,
+    home: ModuleId,
+    ident_ids: &'r mut IdentIds,
+    constants: &'r MutMap<(Symbol, ProcLayout<'a>), &'a Stmt<'a>>,
+    /// Symbols of the current procedure that are bound to a literal.
+    literals: MutMap<Symbol, (Literal<'a>, InLayout<'a>)>,
+    proc_name: Symbol,
+    overflows: &'r mut std::vec::Vec<ConstantOverflow>,
+}
+
+impl<'a, 'r> FoldEnv<'a, 'r> {
+    fn fold_stmt(&mut self, stmt: &Stmt<'a>) -> &'a Stmt<'a> {
+        let arena = self.arena;
+
+        match stmt {
+            Stmt::Let(symbol, expr, layout, continuation) => {
+                if let Some(inlined) = self.inline_constant(*symbol, expr, continuation) {
+                    return self.fold_stmt(inlined);
+                }
+
+                let expr = match self.fold_expr(*symbol, expr, *layout) {
+                    Some(literal) => {
+                        self.literals.insert(*symbol, (literal, *layout));
+                        Expr::Literal(literal)
+                    }
+                    None => self.literal_elements(expr),
+                };
+
+                let continuation = self.fold_stmt(continuation);
+
+                arena.alloc(Stmt::Let(*symbol, expr, *layout, continuation))
+            }
+            Stmt::Switch {
+                cond_symbol,
+                cond_layout,
+                branches,
+                default_branch,
+                ret_layout,
+            } => {
+                if let Some(value) = self.switch_value(*cond_symbol) {
+                    let taken = branches
+                        .iter()
+                        .find(|(branch_value, _, _)| *branch_value == value)
+                        .map_or(default_branch.1, |(_, _, branch)| branch);
+
+                    return self.fold_stmt(taken);
+                }
+
+                let mut new_branches = Vec::with_capacity_in(branches.len(), arena);
+
+                for (value, info, branch) in branches.iter() {
+                    new_branches.push((*value, info.clone(), self.fold_stmt(branch).clone()));
+                }
+
+                let new_default = (default_branch.0.clone(), self.fold_stmt(default_branch.1));
+
+                arena.alloc(Stmt::Switch {
+                    cond_symbol: *cond_symbol,
+                    cond_layout: *cond_layout,
+                    branches: new_branches.into_bump_slice(),
+                    default_branch: new_default,
+                    ret_layout: *ret_layout,
+                })
+            }
+            Stmt::Refcounting(modify_rc, continuation) => {
+                let continuation = self.fold_stmt(continuation);
+
+                arena.alloc(Stmt::Refcounting(*modify_rc, continuation))
+            }
+            Stmt::Expect {
+                condition,
+                region,
+                lookups,
+                variables,
+                remainder,
+            } => {
+                let remainder = self.fold_stmt(remainder);
+
+                arena.alloc(Stmt::Expect {
+                    condition: *condition,
+                    region: *region,
+                    lookups,
+                    variables,
+                    remainder,
+                })
+            }
+            Stmt::ExpectFx {
+                condition,
+                region,
+                lookups,
+                variables,
+                remainder,
+            } => {
+                let remainder = self.fold_stmt(remainder);
+
+                arena.alloc(Stmt::ExpectFx {
+                    condition: *condition,
+                    region: *region,
+                    lookups,
+                    variables,
+                    remainder,
+                })
+            }
+            Stmt::Located {
+                symbol,
+                region,
+                remainder,
+            } => {
+                let remainder = self.fold_stmt(remainder);
+
+                arena.alloc(Stmt::Located {
+                    symbol: *symbol,
+                    region: *region,
+                    remainder,
+                })
+            }
+            Stmt::Dbg {
+                symbol,
+                variable,
+                remainder,
+            } => {
+                let remainder = self.fold_stmt(remainder);
+
+                arena.alloc(Stmt::Dbg {
+                    symbol: *symbol,
+                    variable: *variable,
+                    remainder,
+                })
+            }
+            Stmt::Join {
+                id,
+                parameters,
+                body,
+                remainder,
+            } => {
+                let body = self.fold_stmt(body);
+                let remainder = self.fold_stmt(remainder);
+
+                arena.alloc(Stmt::Join {
+                    id: *id,
+                    parameters,
+                    body,
+                    remainder,
+                })
+            }
+            Stmt::Ret(_) | Stmt::Jump(_, _) | Stmt::Crash(_, _) => arena.alloc(stmt.clone()),
+        }
+    }
+
+    fn fold_expr(
+        &mut self,
+        symbol: Symbol,
+        expr: &Expr<'a>,
+        layout: InLayout<'a>,
+    ) -> Option<Literal<'a>> {
+        match expr {
+            Expr::Literal(literal) => Some(*literal),
+            Expr::Call(Call {
+                call_type,
+                arguments,
+            }) => {
+                let op = match call_type.clone().replace_lowlevel_wrapper() {
+                    CallType::LowLevel { op, .. } => op,
+                    _ => return None,
+                };
+
+                let mut values = std::vec::Vec::with_capacity(arguments.len());
+
+                for argument in arguments.iter() {
+                    values.push(self.value_of(*argument)?);
+                }
+
+                let folded = match values.as_slice() {
+                    [value] => fold_unary(op, *value, self.int_width(layout)),
+                    [lhs, rhs] => fold_binary(op, *lhs, *rhs),
+                    _ => Folded::Unknown,
+                };
+
+                match folded {
+                    Folded::Value(value) => Some(value.to_literal()),
+                    Folded::Overflow => {
+                        let overflow = ConstantOverflow {
+                            proc_name: self.proc_name,
+                            symbol,
+                            op,
+                        };
+
+                        if !self.overflows.contains(&overflow) {
+                            self.overflows.push(overflow);
+                        }
+
+                        None
+                    }
+                    Folded::Unknown => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Uses the literals that list elements are bound to as the elements themselves. A list of
+    /// literals is emitted as static data by the backends, instead of being built at runtime.
+    fn literal_elements(&self, expr: &Expr<'a>) -> Expr<'a> {
+        match expr {
+            Expr::Array { elem_layout, elems } => {
+                let elems = elems.iter().map(|elem| match elem {
+                    ListLiteralElement::Symbol(symbol) => match self.literals.get(symbol) {
+                        Some((literal, _)) => ListLiteralElement::Literal(*literal),
+                        None => *elem,
+                    },
+                    ListLiteralElement::Literal(_) => *elem,
+                });
+
+                Expr::Array {
+                    elem_layout: *elem_layout,
+                    elems: Vec::from_iter_in(elems, self.arena).into_bump_slice(),
+                }
+            }
+            _ => expr.clone(),
+        }
+    }
+
+    /// Replaces `let symbol = CallByName constant;` by the bindings that build the constant.
+    fn inline_constant(
+        &mut self,
+        symbol: Symbol,
+        expr: &Expr<'a>,
+        continuation: &'a Stmt<'a>,
+    ) -> Option<&'a Stmt<'a>> {
+        let name = match expr {
+            Expr::Call(Call {
+                call_type:
+                    CallType::ByName {
+                        name,
+                        ret_layout,
+                        arg_layouts: [],
+                        ..
+                    },
+                arguments: [],
+            }) => {
+                let proc_layout = ProcLayout {
+                    arguments: &[],
+                    result: *ret_layout,
+                    niche: name.niche(),
+                };
+
+                (name.name(), proc_layout)
+            }
+            _ => return None,
+        };
+
+        let body = *self.constants.get(&name)?;
+
+        let mut bindings = std::vec::Vec::new();
+        let mut stmt = body;
+
+        let returned = loop {
+            match stmt {
+                Stmt::Let(bound, expr, layout, next) => {
+                    bindings.push((*bound, expr, *layout));
+                    stmt = *next;
+                }
+                // the inlined constant is attributed to where it is used
+                Stmt::Located { remainder, .. } => stmt = *remainder,
+                Stmt::Ret(returned) => break *returned,
+                _ => unreachable!("constants are built by a chain of let-bindings"),
+            }
+        };
+
+        let mut renamed = MutMap::default();
+
+        for (bound, _, _) in bindings.iter() {
+            let new_symbol = if *bound == returned {
+                symbol
+            } else {
+                Symbol::new(self.home, self.ident_ids.gen_unique())
+            };
+
+            renamed.insert(*bound, new_symbol);
+        }
+
+        let arena = self.arena;
+        let rename = |symbol: &Symbol| renamed[symbol];
+        let mut inlined = continuation;
+
+        for (bound, expr, layout) in bindings.into_iter().rev() {
+            let expr = match expr {
+                Expr::Struct(fields) => Expr::Struct(
+                    Vec::from_iter_in(fields.iter().map(rename), arena).into_bump_slice(),
+                ),
+                Expr::Array { elem_layout, elems } => {
+                    let elems = elems.iter().map(|elem| match elem {
+                        ListLiteralElement::Literal(literal) => {
+                            ListLiteralElement::Literal(*literal)
+                        }
+                        ListLiteralElement::Symbol(symbol) => {
+                            ListLiteralElement::Symbol(rename(symbol))
+                        }
+                    });
+
+                    Expr::Array {
+                        elem_layout: *elem_layout,
+                        elems: Vec::from_iter_in(elems, arena).into_bump_slice(),
+                    }
+                }
+                other => other.clone(),
+            };
+
+            inlined = arena.alloc(Stmt::Let(rename(&bound), expr, layout, inlined));
+        }
+
+        Some(inlined)
+    }
+
+    fn value_of(&self, symbol: Symbol) -> Option<Value> {
+        let (literal, layout) = self.literals.get(&symbol)?;
+
+        let value = match (literal, self.layout_interner.get_repr(*layout)) {
+            (Literal::Int(bytes), LayoutRepr::Builtin(Builtin::Int(width))) => {
+                Value::Int(i128::from_ne_bytes(*bytes), width)
+            }
+            (Literal::U128(bytes), LayoutRepr::Builtin(Builtin::Int(width))) => {
+                Value::Int(u128::from_ne_bytes(*bytes) as i128, width)
+            }
+            (Literal::Float(value), LayoutRepr::Builtin(Builtin::Float(width))) => {
+                Value::Float(*value, width)
+            }
+            (Literal::Decimal(bytes), LayoutRepr::Builtin(Builtin::Decimal)) => {
+                Value::Dec(i128::from_ne_bytes(*bytes))
+            }
+            (Literal::Bool(value), LayoutRepr::Builtin(Builtin::Bool)) => Value::Bool(*value),
+            _ => return None,
+        };
+
+        Some(value)
+    }
+
+    fn int_width(&self, layout: InLayout<'a>) -> Option<IntWidth> {
+        match self.layout_interner.get_repr(layout) {
+            LayoutRepr::Builtin(Builtin::Int(width)) => Some(width),
+            _ => None,
+        }
+    }
+
+    /// The value a `Switch` on this symbol matches against, in the encoding of the branches.
+    fn switch_value(&self, symbol: Symbol) -> Option<u64> {
+        match self.literals.get(&symbol)? {
+            (Literal::Int(bytes), _) => Some(i128::from_ne_bytes(*bytes) as u64),
+            (Literal::U128(bytes), _) => Some(u128::from_ne_bytes(*bytes) as u64),
+            (Literal::Bool(value), _) => Some(*value as u64),
+            (Literal::Byte(value), _) => Some(*value as u64),
+            _ => None,
+        }
+    }
+}
+
+fn fold_unary(op: LowLevel, value: Value, ret_width: Option<IntWidth>) -> Folded {
+    use LowLevel::*;
+
+    match (op, value) {
+        (Not, Value::Bool(b)) => Folded::Value(Value::Bool(!b)),
+        (NumNeg, Value::Int(0, _)) => Folded::Value(value),
+        (NumNeg, Value::Int(n, width)) if width.is_signed() => checked_int(n.checked_neg(), width),
+        (NumAbs, Value::Int(n, width)) if width.is_signed() => checked_int(n.checked_abs(), width),
+        (NumAbs, Value::Int(_, _)) => Folded::Value(value),
+        (NumNeg, Value::Float(x, width)) => Folded::Value(Value::Float(-x, width)),
+        (NumAbs, Value::Float(x, width)) => Folded::Value(Value::Float(x.abs(), width)),
+        (NumNeg, Value::Dec(d)) => d.checked_neg().map_or(Folded::Overflow, dec),
+        (NumAbs, Value::Dec(d)) => d.checked_abs().map_or(Folded::Overflow, dec),
+        (NumIntCast, Value::Int(n, _)) => match ret_width {
+            Some(width) => int(wrap(n, width), width),
+            None => Folded::Unknown,
+        },
+        _ => Folded::Unknown,
+    }
+}
+
+fn fold_binary(op: LowLevel, lhs: Value, rhs: Value) -> Folded {
+    use LowLevel::*;
+
+    match (lhs, rhs) {
+        (Value::Int(n, width), Value::Int(amount, _))
+            if matches!(op, NumShiftLeftBy | NumShiftRightBy | NumShiftRightZfBy) =>
+        {
+            fold_shift(op, n, amount, width)
+        }
+        (Value::Int(_, IntWidth::U128), Value::Int(_, IntWidth::U128)) => {
+            fold_u128(op, u128_bits(lhs), u128_bits(rhs))
+        }
+        (Value::Int(a, width), Value::Int(b, rhs_width)) if width == rhs_width => {
+            fold_int(op, a, b, width)
+        }
+        (Value::Float(a, width), Value::Float(b, rhs_width)) if width == rhs_width => {
+            fold_float(op, a, b, width)
+        }
+        (Value::Dec(a), Value::Dec(b)) => fold_dec(op, a, b),
+        (Value::Bool(a), Value::Bool(b)) => match op {
+            And => boolean(a && b),
+            Or => boolean(a || b),
+            Eq => boolean(a == b),
+            NotEq => boolean(a != b),
+            _ => Folded::Unknown,
+        },
+        _ => Folded::Unknown,
+    }
+}
+
+fn fold_int(op: LowLevel, a: i128, b: i128, width: IntWidth) -> Folded {
+    use LowLevel::*;
+
+    let (min, max) = int_range(width);
+    let saturated = |result: Option<i128>, overflows_up: bool| match result {
+        Some(n) => int(n.clamp(min, max), width),
+        None if overflows_up => int(max, width),
+        None => int(min, width),
+    };
+
+    match op {
+        NumAdd => checked_int(a.checked_add(b), width),
+        NumSub => checked_int(a.checked_sub(b), width),
+        NumMul => checked_int(a.checked_mul(b), width),
+        NumAddWrap => int(wrap(a.wrapping_add(b), width), width),
+        NumSubWrap => int(wrap(a.wrapping_sub(b), width), width),
+        NumMulWrap => int(wrap(a.wrapping_mul(b), width), width),
+        NumAddSaturated => saturated(a.checked_add(b), b > 0),
+        NumSubSaturated => saturated(a.checked_sub(b), b < 0),
+        NumMulSaturated => saturated(a.checked_mul(b), (a < 0) == (b < 0)),
+        // division by zero and `MIN // -1` are left for the runtime
+        NumDivTruncUnchecked => match a.checked_div(b) {
+            Some(n) if wrap(n, width) == n => int(n, width),
+            _ => Folded::Unknown,
+        },
+        NumRemUnchecked => match a.checked_rem(b) {
+            Some(n) => int(n, width),
+            None => Folded::Unknown,
+        },
+        NumBitwiseAnd => int(a & b, width),
+        NumBitwiseXor => int(a ^ b, width),
+        NumBitwiseOr => int(a | b, width),
+        NumGt => boolean(a > b),
+        NumGte => boolean(a >= b),
+        NumLt => boolean(a < b),
+        NumLte => boolean(a <= b),
+        Eq => boolean(a == b),
+        NotEq => boolean(a != b),
+        _ => Folded::Unknown,
+    }
+}
+
+fn fold_u128(op: LowLevel, a: u128, b: u128) -> Folded {
+    use LowLevel::*;
+
+    let unsigned = |n: u128| int(n as i128, IntWidth::U128);
+    let checked = |result: Option<u128>| result.map_or(Folded::Overflow, unsigned);
+
+    match op {
+        NumAdd => checked(a.checked_add(b)),
+        NumSub => checked(a.checked_sub(b)),
+        NumMul => checked(a.checked_mul(b)),
+        NumAddWrap => unsigned(a.wrapping_add(b)),
+        NumSubWrap => unsigned(a.wrapping_sub(b)),
+        NumMulWrap => unsigned(a.wrapping_mul(b)),
+        NumAddSaturated => unsigned(a.saturating_add(b)),
+        NumSubSaturated => unsigned(a.saturating_sub(b)),
+        NumMulSaturated => unsigned(a.saturating_mul(b)),
+        NumDivTruncUnchecked => a.checked_div(b).map_or(Folded::Unknown, unsigned),
+        NumRemUnchecked => a.checked_rem(b).map_or(Folded::Unknown, unsigned),
+        NumBitwiseAnd => unsigned(a & b),
+        NumBitwiseXor => unsigned(a ^ b),
+        NumBitwiseOr => unsigned(a | b),
+        NumGt => boolean(a > b),
+        NumGte => boolean(a >= b),
+        NumLt => boolean(a < b),
+        NumLte => boolean(a <= b),
+        Eq => boolean(a == b),
+        NotEq => boolean(a != b),
+        _ => Folded::Unknown,
+    }
+}
+
+/// Shifts work on the bit pattern of the value, like the backends do.
+fn fold_shift(op: LowLevel, n: i128, amount: i128, width: IntWidth) -> Folded {
+    let bits = 8 * width.stack_size();
+
+    // shifting by the full width or more is left for the runtime
+    if amount < 0 || amount >= bits as i128 {
+        return Folded::Unknown;
+    }
+
+    let amount = amount as u32;
+    let unused_bits = 128 - bits;
+    let pattern = ((n as u128) << unused_bits) >> unused_bits;
+
+    let shifted = match op {
+        LowLevel::NumShiftLeftBy => (pattern << amount) as i128,
+        LowLevel::NumShiftRightBy => (((pattern << unused_bits) as i128) >> unused_bits) >> amount,
+        LowLevel::NumShiftRightZfBy => (pattern >> amount) as i128,
+        _ => unreachable!("{:?} is not a shift", op),
+    };
+
+    int(wrap(shifted, width), width)
+}
+
+fn fold_float(op: LowLevel, a: f64, b: f64, width: FloatWidth) -> Folded {
+    use LowLevel::*;
+
+    // Rounding the exact f64 result of an f32 operation gives the same value as computing it
+    // in f32 directly.
+    let round = |x: f64| match width {
+        FloatWidth::F32 => x as f32 as f64,
+        FloatWidth::F64 => x,
+    };
+    let (a, b) = (round(a), round(b));
+    let float = |x: f64| {
+        let x = round(x);
+
+        // keep infinities and NaN out of the literals
+        if x.is_finite() {
+            Folded::Value(Value::Float(x, width))
+        } else {
+            Folded::Unknown
+        }
+    };
+
+    match op {
+        NumAdd => float(a + b),
+        NumSub => float(a - b),
+        NumMul => float(a * b),
+        NumDivFrac => float(a / b),
+        NumGt => boolean(a > b),
+        NumGte => boolean(a >= b),
+        NumLt => boolean(a < b),
+        NumLte => boolean(a <= b),
+        Eq => boolean(a == b),
+        NotEq => boolean(a != b),
+        _ => Folded::Unknown,
+    }
+}
+
+fn fold_dec(op: LowLevel, a: i128, b: i128) -> Folded {
+    use LowLevel::*;
+
+    match op {
+        NumAdd => a.checked_add(b).map_or(Folded::Overflow, dec),
+        NumSub => a.checked_sub(b).map_or(Folded::Overflow, dec),
+        NumGt => boolean(a > b),
+        NumGte => boolean(a >= b),
+        NumLt => boolean(a < b),
+        NumLte => boolean(a <= b),
+        Eq => boolean(a == b),
+        NotEq => boolean(a != b),
+        _ => Folded::Unknown,
+    }
+}
+
+fn u128_bits(value: Value) -> u128 {
+    match value {
+        Value::Int(n, _) => n as u128,
+        _ => unreachable!(),
+    }
+}
+
+fn int(n: i128, width: IntWidth) -> Folded {
+    Folded::Value(Value::Int(n, width))
+}
+
+fn dec(d: i128) -> Folded {
+    Folded::Value(Value::Dec(d))
+}
+
+fn boolean(b: bool) -> Folded {
+    Folded::Value(Value::Bool(b))
+}
+
+/// For widths below 128 bits, where the result of an `i128` operation is exact.
+fn checked_int(result: Option<i128>, width: IntWidth) -> Folded {
+    match result {
+        Some(n) if width != IntWidth::U128 && wrap(n, width) == n => int(n, width),
+        _ => Folded::Overflow,
+    }
+}
+
+/// Truncates to the width, then sign- or zero-extends back to 128 bits.
+fn wrap(n: i128, width: IntWidth) -> i128 {
+    let unused_bits = 128 - 8 * width.stack_size();
+
+    if unused_bits == 0 {
+        n
+    } else if width.is_signed() {
+        (n << unused_bits) >> unused_bits
+    } else {
+        (((n << unused_bits) as u128) >> unused_bits) as i128
+    }
+}
+
+fn int_range(width: IntWidth) -> (i128, i128) {
+    let bits = 8 * width.stack_size();
+
+    match (width.is_signed(), bits) {
+        (true, 128) => (i128::MIN, i128::MAX),
+        (true, _) => (-(1 << (bits - 1)), (1 << (bits - 1)) - 1),
+        (false, 128) => (0, i128::MAX),
+        (false, _) => (0, (1 << bits) - 1),
+    }
+}
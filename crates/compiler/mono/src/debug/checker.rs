@@ -342,7 +342,7 @@ impl<'a, 'r> Ctx<'a, 'r> {
                 self.check_modify_rc(rc);
                 self.check_stmt(rest);
             }
-            &Stmt::Dbg { remainder, .. } => {
+            &Stmt::Located { remainder, .. } | &Stmt::Dbg { remainder, .. } => {
                 self.check_stmt(remainder);
             }
             &Stmt::Expect {
@@ -630,6 +630,21 @@ fn specialize_drops_stmt<'a, 'i>(
                 remainder,
             ),
         }),
+        Stmt::Located {
+            symbol,
+            region,
+            remainder,
+        } => arena.alloc(Stmt::Located {
+            symbol: *symbol,
+            region: *region,
+            remainder: specialize_drops_stmt(
+                arena,
+                layout_interner,
+                ident_ids,
+                environment,
+                remainder,
+            ),
+        }),
         Stmt::Dbg {
             symbol,
             variable,
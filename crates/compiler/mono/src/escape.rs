@@ -0,0 +1,511 @@
+//! Escape analysis for the mono IR.
+//!
+//! A heap cell (a `Box`, a recursive tag that needs an allocation, or a list literal) that is only
+//! ever taken apart again in the procedure that builds it does not need to exist at all: every
+//! read of a field or element can use the symbol that was stored in it instead. Such a cell never
+//! escapes its procedure, so dropping the allocation also means `inc_dec` will not insert any
+//! refcounting for it. This runs before refcounting, so there are no `Refcounting` statements to
+//! remove yet.
+//!
+//! A tag cell escapes when its symbol is used for anything other than a field read
+//! (`UnionAtIndex`) or a tag id check (`GetTagId`). A list cell escapes when it is used for
+//! anything other than `List.len`, or a `ListGetUnsafe` at a known index. Any other use escapes:
+//! when it is returned, passed to a call or a join point, stored in another value, switched on,
+//! or inspected by `expect` and `dbg`.
+//!
+//! Other lists and strings are not placed on the stack. Their size is usually only known at
+//! runtime, and the backends have no representation for a list whose elements live in a stack
+//! frame. The backends emit lists of number literals and string literals as static data already,
+//! and strings of up to 23 bytes are stored inline, without an allocation.
+//!
+//! Nothing is moved into a stack frame: a cell is either removed or stays on the heap. The analysis
+//! also looks at one procedure at a time, so passing a cell to another procedure escapes it, even
+//! when the callee does not keep it. Telling those apart needs the interprocedural aliasing
+//! information of `alias_analysis`, and building such a cell on the stack needs the backends to
+//! support cells that are not refcounted; neither is done yet.
+
+use bumpalo::collections::vec::Vec;
+use bumpalo::Bump;
+
+use roc_collections::{BumpMap, MutMap, MutSet};
+use roc_module::low_level::LowLevel;
+use roc_module::symbol::Symbol;
+
+use crate::ir::{
+    substitute_in_exprs_many, Call, CallType, Expr, ListLiteralElement, Literal, Proc, ProcLayout,
+    Stmt,
+};
+use crate::layout::{InLayout, TagIdIntType, UnionLayout};
+
+/// Remove heap cells that never escape the procedure that builds them, reading their fields
+/// from the symbols they were built from instead.
+pub fn elide_local_allocations<'a>(
+    arena: &'a Bump,
+    procs: &mut MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+) {
+    for proc in procs.values_mut() {
+        // eliding a cell can make the cells stored in it non-escaping, so repeat until stable
+        loop {
+            let mut analysis = Analysis::default();
+
+            for (layout, symbol) in proc.args.iter() {
+                analysis.layouts.insert(*symbol, *layout);
+            }
+
+            analysis.visit_stmt(&proc.body);
+
+            let local_cells: MutMap<Symbol, Cell<'a>> = analysis
+                .cells
+                .into_iter()
+                .filter(|(symbol, _)| !analysis.escaping.contains(symbol))
+                .collect();
+
+            if local_cells.is_empty() {
+                break;
+            }
+
+            let mut env = ElideEnv {
+                arena,
+                local_cells: &local_cells,
+                indices: &analysis.indices,
+                substitutions: BumpMap::default(),
+            };
+
+            let mut new_body = env.elide_stmt(&proc.body).clone();
+
+            if !env.substitutions.is_empty() {
+                substitute_in_exprs_many(arena, &mut new_body, env.substitutions);
+            }
+
+            proc.body = new_body;
+        }
+    }
+}
+
+/// A heap cell built in this procedure.
+enum Cell<'a> {
+    Tag {
+        tag_id: TagIdIntType,
+        arguments: &'a [Symbol],
+    },
+    List {
+        elems: &'a [ListLiteralElement<'a>],
+    },
+}
+
+#[derive(Default)]
+struct Analysis<'a> {
+    cells: MutMap<Symbol, Cell<'a>>,
+    escaping: MutSet<Symbol>,
+    layouts: MutMap<Symbol, InLayout<'a>>,
+    /// Symbols bound to an integer literal, which can index into a list cell.
+    indices: MutMap<Symbol, u64>,
+}
+
+impl<'a> Analysis<'a> {
+    fn visit_stmt(&mut self, stmt: &Stmt<'a>) {
+        match stmt {
+            Stmt::Let(symbol, expr, layout, continuation) => {
+                self.layouts.insert(*symbol, *layout);
+                self.visit_expr(*symbol, expr, *layout);
+                self.visit_stmt(continuation);
+            }
+            Stmt::Switch {
+                cond_symbol,
+                branches,
+                default_branch,
+                ..
+            } => {
+                self.escape(*cond_symbol);
+
+                for (_, _, branch) in branches.iter() {
+                    self.visit_stmt(branch);
+                }
+
+                self.visit_stmt(default_branch.1);
+            }
+            Stmt::Ret(symbol) => self.escape(*symbol),
+            Stmt::Refcounting(modify_rc, continuation) => {
+                self.escape(modify_rc.get_symbol());
+                self.visit_stmt(continuation);
+            }
+            Stmt::Expect {
+                condition,
+                lookups,
+                remainder,
+                ..
+            }
+            | Stmt::ExpectFx {
+                condition,
+                lookups,
+                remainder,
+                ..
+            } => {
+                self.escape(*condition);
+                self.escape_all(lookups);
+                self.visit_stmt(remainder);
+            }
+            Stmt::Located { remainder, .. } => self.visit_stmt(remainder),
+            Stmt::Dbg {
+                symbol, remainder, ..
+            } => {
+                self.escape(*symbol);
+                self.visit_stmt(remainder);
+            }
+            Stmt::Join {
+                parameters,
+                body,
+                remainder,
+                ..
+            } => {
+                for param in parameters.iter() {
+                    self.layouts.insert(param.symbol, param.layout);
+                }
+
+                self.visit_stmt(body);
+                self.visit_stmt(remainder);
+            }
+            Stmt::Jump(_, arguments) => self.escape_all(arguments),
+            Stmt::Crash(symbol, _) => self.escape(*symbol),
+        }
+    }
+
+    fn visit_expr(&mut self, symbol: Symbol, expr: &Expr<'a>, layout: InLayout<'a>) {
+        match expr {
+            Expr::Literal(Literal::Int(bytes)) => {
+                if let Ok(index) = u64::try_from(i128::from_ne_bytes(*bytes)) {
+                    self.indices.insert(symbol, index);
+                }
+            }
+            Expr::Literal(_)
+            | Expr::EmptyArray
+            | Expr::NullPointer
+            | Expr::FunctionPointer { .. }
+            | Expr::RuntimeErrorFunction(_) => {}
+            Expr::Call(Call {
+                call_type,
+                arguments,
+            }) => {
+                if self.is_list_read(call_type, arguments) {
+                    return;
+                }
+
+                match call_type {
+                    CallType::ByPointer { pointer, .. } => self.escape(*pointer),
+                    CallType::HigherOrder(higher_order) => {
+                        self.escape(higher_order.passed_function.captured_environment)
+                    }
+                    CallType::ByName { .. }
+                    | CallType::Foreign { .. }
+                    | CallType::LowLevel { .. } => {}
+                }
+
+                self.escape_all(arguments);
+            }
+            Expr::Tag {
+                tag_layout,
+                tag_id,
+                arguments,
+                reuse,
+            } => {
+                self.escape_all(arguments);
+
+                if let Some(token) = reuse {
+                    self.escape(token.symbol);
+                }
+
+                if reuse.is_none() && is_heap_cell(tag_layout, *tag_id) {
+                    let cell = Cell::Tag {
+                        tag_id: *tag_id,
+                        arguments,
+                    };
+
+                    self.cells.insert(symbol, cell);
+                }
+            }
+            Expr::UnionAtIndex {
+                structure,
+                tag_id,
+                index,
+                ..
+            } => {
+                // a field read is fine, as long as the field can stand in for the read
+                let stands_in = match self.cells.get(structure) {
+                    Some(Cell::Tag {
+                        tag_id: cell_tag_id,
+                        arguments,
+                    }) => {
+                        cell_tag_id == tag_id
+                            && arguments
+                                .get(*index as usize)
+                                .and_then(|argument| self.layouts.get(argument))
+                                == Some(&layout)
+                    }
+                    _ => false,
+                };
+
+                if !stands_in {
+                    self.escape(*structure);
+                }
+            }
+            Expr::GetTagId { .. } => {}
+            Expr::Struct(fields) => self.escape_all(fields),
+            Expr::StructAtIndex { structure, .. }
+            | Expr::UnionFieldPtrAtIndex { structure, .. } => self.escape(*structure),
+            Expr::Array { elems, .. } => {
+                for elem in elems.iter() {
+                    if let ListLiteralElement::Symbol(symbol) = elem {
+                        self.escape(*symbol);
+                    }
+                }
+
+                self.cells.insert(symbol, Cell::List { elems });
+            }
+            Expr::ErasedMake { value, callee } => {
+                if let Some(value) = value {
+                    self.escape(*value);
+                }
+
+                self.escape(*callee);
+            }
+            Expr::ErasedLoad { symbol, .. }
+            | Expr::Reset { symbol, .. }
+            | Expr::ResetRef { symbol, .. } => self.escape(*symbol),
+            Expr::Alloca { initializer, .. } => {
+                if let Some(initializer) = initializer {
+                    self.escape(*initializer);
+                }
+            }
+        }
+    }
+
+    /// Whether this call is `List.len` of a list cell, or reads an element of a list cell at a
+    /// known index. Neither lets the list escape.
+    fn is_list_read(&self, call_type: &CallType<'a>, arguments: &[Symbol]) -> bool {
+        let elems = match arguments.first().and_then(|list| self.cells.get(list)) {
+            Some(Cell::List { elems }) => elems,
+            _ => return false,
+        };
+
+        match (call_type.clone().replace_lowlevel_wrapper(), arguments) {
+            (
+                CallType::LowLevel {
+                    op: LowLevel::ListLen,
+                    ..
+                },
+                [_],
+            ) => true,
+            (
+                CallType::LowLevel {
+                    op: LowLevel::ListGetUnsafe,
+                    ..
+                },
+                [_, index],
+            ) => self
+                .indices
+                .get(index)
+                .map_or(false, |index| *index < elems.len() as u64),
+            _ => false,
+        }
+    }
+
+    fn escape(&mut self, symbol: Symbol) {
+        self.escaping.insert(symbol);
+    }
+
+    fn escape_all(&mut self, symbols: &[Symbol]) {
+        self.escaping.extend(symbols.iter().copied());
+    }
+}
+
+/// Whether building this tag allocates.
+fn is_heap_cell(union_layout: &UnionLayout, tag_id: TagIdIntType) -> bool {
+    !matches!(union_layout, UnionLayout::NonRecursive(_)) && !union_layout.tag_is_null(tag_id)
+}
+
+struct ElideEnv<'a, 'r> {
+    arena: &'a Bump,
+    local_cells: &'r MutMap<Symbol, Cell<'a>>,
+    indices: &'r MutMap<Symbol, u64>,
+    /// Field reads that are replaced by the symbol stored in the field.
+    substitutions: BumpMap<Symbol, Symbol>,
+}
+
+impl<'a, 'r> ElideEnv<'a, 'r> {
+    /// Use the symbol stored in an elided cell for a read of that cell.
+    fn substitute(&mut self, read: Symbol, stored: Symbol) {
+        // the stored symbol may itself have been read from an elided cell
+        let stored = self.substitutions.get(&stored).copied().unwrap_or(stored);
+        self.substitutions.insert(read, stored);
+    }
+
+    fn elide_stmt(&mut self, stmt: &Stmt<'a>) -> &'a Stmt<'a> {
+        let arena = self.arena;
+
+        match stmt {
+            Stmt::Let(symbol, expr, layout, continuation) => {
+                let expr = match expr {
+                    Expr::Tag { .. } | Expr::Array { .. }
+                        if self.local_cells.contains_key(symbol) =>
+                    {
+                        return self.elide_stmt(continuation);
+                    }
+                    Expr::UnionAtIndex {
+                        structure, index, ..
+                    } => match self.local_cells.get(structure) {
+                        Some(Cell::Tag { arguments, .. }) => {
+                            self.substitute(*symbol, arguments[*index as usize]);
+
+                            return self.elide_stmt(continuation);
+                        }
+                        _ => expr.clone(),
+                    },
+                    Expr::GetTagId { structure, .. } => match self.local_cells.get(structure) {
+                        Some(Cell::Tag { tag_id, .. }) => {
+                            Expr::Literal(Literal::Int((*tag_id as i128).to_ne_bytes()))
+                        }
+                        _ => expr.clone(),
+                    },
+                    Expr::Call(Call {
+                        call_type,
+                        arguments,
+                    }) => match arguments
+                        .first()
+                        .and_then(|list| self.local_cells.get(list))
+                    {
+                        Some(Cell::List { elems }) => {
+                            match call_type.clone().replace_lowlevel_wrapper() {
+                                CallType::LowLevel {
+                                    op: LowLevel::ListGetUnsafe,
+                                    ..
+                                } => match elems[self.indices[&arguments[1]] as usize] {
+                                    ListLiteralElement::Literal(literal) => Expr::Literal(literal),
+                                    ListLiteralElement::Symbol(element) => {
+                                        self.substitute(*symbol, element);
+
+                                        return self.elide_stmt(continuation);
+                                    }
+                                },
+                                _ => {
+                                    let len = elems.len() as i128;
+                                    Expr::Literal(Literal::Int(len.to_ne_bytes()))
+                                }
+                            }
+                        }
+                        _ => expr.clone(),
+                    },
+                    _ => expr.clone(),
+                };
+
+                let continuation = self.elide_stmt(continuation);
+
+                arena.alloc(Stmt::Let(*symbol, expr, *layout, continuation))
+            }
+            Stmt::Switch {
+                cond_symbol,
+                cond_layout,
+                branches,
+                default_branch,
+                ret_layout,
+            } => {
+                let mut new_branches = Vec::with_capacity_in(branches.len(), arena);
+
+                for (value, info, branch) in branches.iter() {
+                    new_branches.push((*value, info.clone(), self.elide_stmt(branch).clone()));
+                }
+
+                let new_default = (default_branch.0.clone(), self.elide_stmt(default_branch.1));
+
+                arena.alloc(Stmt::Switch {
+                    cond_symbol: *cond_symbol,
+                    cond_layout: *cond_layout,
+                    branches: new_branches.into_bump_slice(),
+                    default_branch: new_default,
+                    ret_layout: *ret_layout,
+                })
+            }
+            Stmt::Refcounting(modify_rc, continuation) => {
+                let continuation = self.elide_stmt(continuation);
+
+                arena.alloc(Stmt::Refcounting(*modify_rc, continuation))
+            }
+            Stmt::Expect {
+                condition,
+                region,
+                lookups,
+                variables,
+                remainder,
+            } => {
+                let remainder = self.elide_stmt(remainder);
+
+                arena.alloc(Stmt::Expect {
+                    condition: *condition,
+                    region: *region,
+                    lookups,
+                    variables,
+                    remainder,
+                })
+            }
+            Stmt::ExpectFx {
+                condition,
+                region,
+                lookups,
+                variables,
+                remainder,
+            } => {
+                let remainder = self.elide_stmt(remainder);
+
+                arena.alloc(Stmt::ExpectFx {
+                    condition: *condition,
+                    region: *region,
+                    lookups,
+                    variables,
+                    remainder,
+                })
+            }
+            Stmt::Located {
+                symbol,
+                region,
+                remainder,
+            } => {
+                let remainder = self.elide_stmt(remainder);
+
+                arena.alloc(Stmt::Located {
+                    symbol: *symbol,
+                    region: *region,
+                    remainder,
+                })
+            }
+            Stmt::Dbg {
+                symbol,
+                variable,
+                remainder,
+            } => {
+                let remainder = self.elide_stmt(remainder);
+
+                arena.alloc(Stmt::Dbg {
+                    symbol: *symbol,
+                    variable: *variable,
+                    remainder,
+                })
+            }
+            Stmt::Join {
+                id,
+                parameters,
+                body,
+                remainder,
+            } => {
+                let body = self.elide_stmt(body);
+                let remainder = self.elide_stmt(remainder);
+
+                arena.alloc(Stmt::Join {
+                    id: *id,
+                    parameters,
+                    body,
+                    remainder,
+                })
+            }
+            Stmt::Ret(_) | Stmt::Jump(_, _) | Stmt::Crash(_, _) => arena.alloc(stmt.clone()),
+        }
+    }
+}
@@ -180,7 +180,8 @@ impl<'a, 'i> SymbolRcTypesEnv<'a, 'i> {
             Stmt::Refcounting(_, _) => unreachable!(
                 "Refcounting operations should not be present in the AST at this point."
             ),
-            Stmt::Expect { remainder, .. }
+            Stmt::Located { remainder, .. }
+            | Stmt::Expect { remainder, .. }
             | Stmt::ExpectFx { remainder, .. }
             | Stmt::Dbg { remainder, .. } => {
                 self.insert_symbols_rc_type_stmt(remainder);
@@ -688,6 +689,15 @@ fn insert_refcount_operations_stmt<'v, 'a>(
                 remainder: newer_remainder,
             })
         }
+        Stmt::Located {
+            symbol,
+            region,
+            remainder,
+        } => arena.alloc(Stmt::Located {
+            symbol: *symbol,
+            region: *region,
+            remainder: insert_refcount_operations_stmt(arena, environment, remainder),
+        }),
         Stmt::Dbg {
             symbol,
             variable,
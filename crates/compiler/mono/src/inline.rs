@@ -0,0 +1,666 @@
+//! Inlining of small procedures into their callers.
+//!
+//! The backends other than LLVM do not inline anything, so every accessor, wrapper lambda and
+//! small combinator like `Result.map` costs a full call. This pass copies the bodies of small,
+//! non-recursive procedures into their call sites. It runs before refcounting, so the inlined
+//! code takes part in the inc/dec and reuse analyses of the caller.
+//!
+//! Only the original bodies of the callees are inlined, so a chain of small procedures is
+//! inlined one level per call site, and (mutually) recursive procedures cannot make the pass
+//! loop.
+
+use bumpalo::collections::vec::Vec;
+use bumpalo::Bump;
+
+use roc_collections::MutMap;
+use roc_module::low_level::LowLevelWrapperType;
+use roc_module::symbol::{IdentIds, ModuleId, Symbol};
+
+use crate::ir::{
+    Call, CallSpecId, CallType, Expr, HigherOrderLowLevel, JoinPointId, ListLiteralElement,
+    ModifyRc, Param, PassedFunction, Proc, ProcLayout, ReuseToken, SelfRecursive, Stmt,
+    UpdateModeIds,
+};
+use crate::layout::InLayout;
+
+/// Procedures whose body has more statements than this are not inlined.
+const MAX_INLINED_SIZE: usize = 12;
+
+pub fn inline_procs<'a>(
+    arena: &'a Bump,
+    home: ModuleId,
+    ident_ids: &mut IdentIds,
+    update_mode_ids: &mut UpdateModeIds,
+    procs: &mut MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+) {
+    let candidates: MutMap<_, _> = procs
+        .iter()
+        .filter(|(_, proc)| is_inline_candidate(proc))
+        .map(|(key, proc)| (*key, &*arena.alloc(proc.clone())))
+        .collect();
+
+    if candidates.is_empty() {
+        return;
+    }
+
+    for ((symbol, _), proc) in procs.iter_mut() {
+        let mut env = InlineEnv {
+            arena,
+            home,
+            ident_ids: &mut *ident_ids,
+            update_mode_ids: &mut *update_mode_ids,
+            candidates: &candidates,
+            caller: *symbol,
+            next_call_spec_id: max_call_spec_id(&proc.body, CallSpecId::BACKEND_DUMMY).next(),
+        };
+
+        let new_body = env.inline_stmt(arena.alloc(proc.body.clone()));
+        proc.body = new_body.clone();
+    }
+}
+
+fn is_inline_candidate(proc: &Proc) -> bool {
+    matches!(proc.is_self_recursive, SelfRecursive::NotSelfRecursive)
+        && !proc.is_erased
+        // the backends replace these with the lowlevel they wrap already
+        && matches!(
+            LowLevelWrapperType::from_symbol(proc.name.name()),
+            LowLevelWrapperType::NotALowLevelWrapper
+        )
+        && matches!(stmt_size(&proc.body), Some(size) if size <= MAX_INLINED_SIZE)
+}
+
+/// The number of statements in a body, or `None` if it should never be inlined.
+fn stmt_size(stmt: &Stmt) -> Option<usize> {
+    match stmt {
+        Stmt::Let(_, _, _, continuation) => Some(1 + stmt_size(continuation)?),
+        Stmt::Switch {
+            branches,
+            default_branch,
+            ..
+        } => {
+            let mut size = 1 + stmt_size(default_branch.1)?;
+
+            for (_, _, branch) in branches.iter() {
+                size += stmt_size(branch)?;
+            }
+
+            Some(size)
+        }
+        Stmt::Join {
+            body, remainder, ..
+        } => Some(1 + stmt_size(body)? + stmt_size(remainder)?),
+        Stmt::Refcounting(_, continuation) => Some(1 + stmt_size(continuation)?),
+        // source locations are dropped when inlining, so they are free
+        Stmt::Located { remainder, .. } => stmt_size(remainder),
+        Stmt::Ret(_) | Stmt::Jump(_, _) | Stmt::Crash(_, _) => Some(1),
+        // these refer to the source of the callee
+        Stmt::Expect { .. } | Stmt::ExpectFx { .. } | Stmt::Dbg { .. } => None,
+    }
+}
+
+fn max_call_spec_id(stmt: &Stmt, max: CallSpecId) -> CallSpecId {
+    match stmt {
+        Stmt::Let(_, expr, _, continuation) => {
+            let max = match expr {
+                Expr::Call(Call {
+                    call_type:
+                        CallType::ByName {
+                            specialization_id, ..
+                        },
+                    ..
+                }) => max.max(*specialization_id),
+                Expr::Call(Call {
+                    call_type: CallType::HigherOrder(higher_order),
+                    ..
+                }) => max.max(higher_order.passed_function.specialization_id),
+                _ => max,
+            };
+
+            max_call_spec_id(continuation, max)
+        }
+        Stmt::Switch {
+            branches,
+            default_branch,
+            ..
+        } => branches.iter().fold(
+            max_call_spec_id(default_branch.1, max),
+            |max, (_, _, branch)| max_call_spec_id(branch, max),
+        ),
+        Stmt::Join {
+            body, remainder, ..
+        } => max_call_spec_id(remainder, max_call_spec_id(body, max)),
+        Stmt::Refcounting(_, continuation) => max_call_spec_id(continuation, max),
+        Stmt::Located { remainder, .. }
+        | Stmt::Expect { remainder, .. }
+        | Stmt::ExpectFx { remainder, .. }
+        | Stmt::Dbg { remainder, .. } => max_call_spec_id(remainder, max),
+        Stmt::Ret(_) | Stmt::Jump(_, _) | Stmt::Crash(_, _) => max,
+    }
+}
+
+struct InlineEnv<'a, 'r> {
+    arena: &'a Bump,
+    home: ModuleId,
+    ident_ids: &'r mut IdentIds,
+    update_mode_ids: &'r mut UpdateModeIds,
+    candidates: &'r MutMap<(Symbol, ProcLayout<'a>), &'a Proc<'a>>,
+    caller: Symbol,
+    next_call_spec_id: CallSpecId,
+}
+
+impl<'a, 'r> InlineEnv<'a, 'r> {
+    fn inline_stmt(&mut self, stmt: &'a Stmt<'a>) -> &'a Stmt<'a> {
+        let arena = self.arena;
+
+        match stmt {
+            Stmt::Let(symbol, expr, layout, continuation) => {
+                let continuation = self.inline_stmt(continuation);
+
+                match self.callee(expr) {
+                    Some((callee, arguments)) => {
+                        self.inline_call(callee, arguments, *symbol, *layout, continuation)
+                    }
+                    None => arena.alloc(Stmt::Let(*symbol, expr.clone(), *layout, continuation)),
+                }
+            }
+            Stmt::Switch {
+                cond_symbol,
+                cond_layout,
+                branches,
+                default_branch,
+                ret_layout,
+            } => {
+                let mut new_branches = Vec::with_capacity_in(branches.len(), arena);
+
+                for (value, info, branch) in branches.iter() {
+                    new_branches.push((*value, info.clone(), self.inline_stmt(branch).clone()));
+                }
+
+                let new_default = (default_branch.0.clone(), self.inline_stmt(default_branch.1));
+
+                arena.alloc(Stmt::Switch {
+                    cond_symbol: *cond_symbol,
+                    cond_layout: *cond_layout,
+                    branches: new_branches.into_bump_slice(),
+                    default_branch: new_default,
+                    ret_layout: *ret_layout,
+                })
+            }
+            Stmt::Join {
+                id,
+                parameters,
+                body,
+                remainder,
+            } => {
+                let body = self.inline_stmt(body);
+                let remainder = self.inline_stmt(remainder);
+
+                arena.alloc(Stmt::Join {
+                    id: *id,
+                    parameters,
+                    body,
+                    remainder,
+                })
+            }
+            Stmt::Refcounting(modify_rc, continuation) => {
+                let continuation = self.inline_stmt(continuation);
+
+                arena.alloc(Stmt::Refcounting(*modify_rc, continuation))
+            }
+            Stmt::Expect {
+                condition,
+                region,
+                lookups,
+                variables,
+                remainder,
+            } => arena.alloc(Stmt::Expect {
+                condition: *condition,
+                region: *region,
+                lookups,
+                variables,
+                remainder: self.inline_stmt(remainder),
+            }),
+            Stmt::ExpectFx {
+                condition,
+                region,
+                lookups,
+                variables,
+                remainder,
+            } => arena.alloc(Stmt::ExpectFx {
+                condition: *condition,
+                region: *region,
+                lookups,
+                variables,
+                remainder: self.inline_stmt(remainder),
+            }),
+            Stmt::Located {
+                symbol,
+                region,
+                remainder,
+            } => arena.alloc(Stmt::Located {
+                symbol: *symbol,
+                region: *region,
+                remainder: self.inline_stmt(remainder),
+            }),
+            Stmt::Dbg {
+                symbol,
+                variable,
+                remainder,
+            } => arena.alloc(Stmt::Dbg {
+                symbol: *symbol,
+                variable: *variable,
+                remainder: self.inline_stmt(remainder),
+            }),
+            Stmt::Ret(_) | Stmt::Jump(_, _) | Stmt::Crash(_, _) => stmt,
+        }
+    }
+
+    fn callee(&self, expr: &'a Expr<'a>) -> Option<(&'a Proc<'a>, &'a [Symbol])> {
+        match expr {
+            Expr::Call(Call {
+                call_type:
+                    CallType::ByName {
+                        name,
+                        ret_layout,
+                        arg_layouts,
+                        ..
+                    },
+                arguments,
+            }) if name.name() != self.caller => {
+                let proc_layout = ProcLayout {
+                    arguments: arg_layouts,
+                    result: *ret_layout,
+                    niche: name.niche(),
+                };
+
+                let callee = *self.candidates.get(&(name.name(), proc_layout))?;
+
+                Some((callee, *arguments))
+            }
+            _ => None,
+        }
+    }
+
+    /// Replaces `let result = callee arguments; continuation` with the body of the callee.
+    ///
+    /// When the body is a chain of let-bindings, the binding of the returned value becomes the
+    /// binding of `result`. Otherwise every `ret` of the body jumps to a join point that binds
+    /// `result` and runs the continuation.
+    fn inline_call(
+        &mut self,
+        callee: &'a Proc<'a>,
+        arguments: &'a [Symbol],
+        result: Symbol,
+        result_layout: InLayout<'a>,
+        continuation: &'a Stmt<'a>,
+    ) -> &'a Stmt<'a> {
+        let mut renames = MutMap::default();
+
+        for ((_, parameter), argument) in callee.args.iter().zip(arguments.iter()) {
+            renames.insert(*parameter, *argument);
+        }
+
+        match straight_line_result(&callee.body) {
+            Some(returned) => {
+                renames.insert(returned, result);
+
+                let mut copier = Copier {
+                    env: &mut *self,
+                    renames,
+                    join_ids: MutMap::default(),
+                    exit: Exit::Continue(continuation),
+                };
+
+                copier.copy_stmt(&callee.body)
+            }
+            None => {
+                let join_id = JoinPointId(self.fresh_symbol());
+
+                let mut copier = Copier {
+                    env: &mut *self,
+                    renames,
+                    join_ids: MutMap::default(),
+                    exit: Exit::Jump(join_id),
+                };
+
+                let remainder = copier.copy_stmt(&callee.body);
+                let parameters = self.arena.alloc([Param {
+                    symbol: result,
+                    layout: result_layout,
+                }]);
+
+                self.arena.alloc(Stmt::Join {
+                    id: join_id,
+                    parameters,
+                    body: continuation,
+                    remainder,
+                })
+            }
+        }
+    }
+
+    fn fresh_symbol(&mut self) -> Symbol {
+        Symbol::new(self.home, self.ident_ids.gen_unique())
+    }
+
+    fn fresh_call_spec_id(&mut self) -> CallSpecId {
+        let id = self.next_call_spec_id;
+        self.next_call_spec_id = id.next();
+
+        id
+    }
+}
+
+/// The symbol returned by a body that is a chain of let-bindings, if it binds that symbol.
+fn straight_line_result(body: &Stmt) -> Option<Symbol> {
+    let mut bound = std::vec::Vec::new();
+    let mut stmt = body;
+
+    loop {
+        match stmt {
+            Stmt::Let(symbol, _, _, continuation) => {
+                bound.push(*symbol);
+                stmt = *continuation;
+            }
+            Stmt::Located { remainder, .. } => stmt = *remainder,
+            Stmt::Ret(symbol) if bound.contains(symbol) => return Some(*symbol),
+            _ => return None,
+        }
+    }
+}
+
+/// What a `ret` of the inlined body turns into.
+enum Exit<'a> {
+    /// The body is a chain of let-bindings; the returned value is already bound to the
+    /// result, so continue with the rest of the caller.
+    Continue(&'a Stmt<'a>),
+    /// Jump to the join point that binds the result.
+    Jump(JoinPointId),
+}
+
+/// Copies the body of a callee, giving every binding and join point a fresh name.
+struct Copier<'a, 'r, 'e> {
+    env: &'e mut InlineEnv<'a, 'r>,
+    renames: MutMap<Symbol, Symbol>,
+    join_ids: MutMap<JoinPointId, JoinPointId>,
+    exit: Exit<'a>,
+}
+
+impl<'a, 'r, 'e> Copier<'a, 'r, 'e> {
+    fn copy_stmt(&mut self, stmt: &'a Stmt<'a>) -> &'a Stmt<'a> {
+        let arena = self.env.arena;
+
+        match stmt {
+            Stmt::Let(symbol, expr, layout, continuation) => {
+                let new_symbol = self.bind(*symbol);
+                let expr = self.copy_expr(expr);
+                let continuation = self.copy_stmt(continuation);
+
+                arena.alloc(Stmt::Let(new_symbol, expr, *layout, continuation))
+            }
+            Stmt::Switch {
+                cond_symbol,
+                cond_layout,
+                branches,
+                default_branch,
+                ret_layout,
+            } => {
+                let mut new_branches = Vec::with_capacity_in(branches.len(), arena);
+
+                for (value, info, branch) in branches.iter() {
+                    new_branches.push((*value, info.clone(), self.copy_stmt(branch).clone()));
+                }
+
+                let new_default = (default_branch.0.clone(), self.copy_stmt(default_branch.1));
+
+                arena.alloc(Stmt::Switch {
+                    cond_symbol: self.rename(*cond_symbol),
+                    cond_layout: *cond_layout,
+                    branches: new_branches.into_bump_slice(),
+                    default_branch: new_default,
+                    ret_layout: *ret_layout,
+                })
+            }
+            Stmt::Ret(symbol) => match self.exit {
+                Exit::Continue(continuation) => continuation,
+                Exit::Jump(join_id) => {
+                    let arguments = arena.alloc([self.rename(*symbol)]);
+
+                    arena.alloc(Stmt::Jump(join_id, arguments))
+                }
+            },
+            Stmt::Join {
+                id,
+                parameters,
+                body,
+                remainder,
+            } => {
+                let new_id = JoinPointId(self.env.fresh_symbol());
+                self.join_ids.insert(*id, new_id);
+
+                let parameters = Vec::from_iter_in(
+                    parameters.iter().map(|param| Param {
+                        symbol: self.bind(param.symbol),
+                        layout: param.layout,
+                    }),
+                    arena,
+                );
+
+                let body = self.copy_stmt(body);
+                let remainder = self.copy_stmt(remainder);
+
+                arena.alloc(Stmt::Join {
+                    id: new_id,
+                    parameters: parameters.into_bump_slice(),
+                    body,
+                    remainder,
+                })
+            }
+            Stmt::Jump(id, arguments) => {
+                let arguments = self.rename_all(arguments);
+
+                arena.alloc(Stmt::Jump(self.join_ids[id], arguments))
+            }
+            Stmt::Refcounting(modify_rc, continuation) => {
+                let modify_rc = match *modify_rc {
+                    ModifyRc::Inc(symbol, count) => ModifyRc::Inc(self.rename(symbol), count),
+                    ModifyRc::Dec(symbol) => ModifyRc::Dec(self.rename(symbol)),
+                    ModifyRc::DecRef(symbol) => ModifyRc::DecRef(self.rename(symbol)),
+                    ModifyRc::Free(symbol) => ModifyRc::Free(self.rename(symbol)),
+                };
+
+                let continuation = self.copy_stmt(continuation);
+
+                arena.alloc(Stmt::Refcounting(modify_rc, continuation))
+            }
+            Stmt::Crash(message, tag) => arena.alloc(Stmt::Crash(self.rename(*message), *tag)),
+            // the inlined code is attributed to the call site
+            Stmt::Located { remainder, .. } => self.copy_stmt(remainder),
+            Stmt::Expect { .. } | Stmt::ExpectFx { .. } | Stmt::Dbg { .. } => {
+                unreachable!("procedures with expects or dbgs are not inlined")
+            }
+        }
+    }
+
+    fn copy_expr(&mut self, expr: &'a Expr<'a>) -> Expr<'a> {
+        match expr {
+            Expr::Literal(_)
+            | Expr::EmptyArray
+            | Expr::NullPointer
+            | Expr::FunctionPointer { .. }
+            | Expr::RuntimeErrorFunction(_) => expr.clone(),
+            Expr::Call(Call {
+                call_type,
+                arguments,
+            }) => {
+                let call_type = match call_type {
+                    CallType::ByName {
+                        name,
+                        ret_layout,
+                        arg_layouts,
+                        ..
+                    } => CallType::ByName {
+                        name: *name,
+                        ret_layout: *ret_layout,
+                        arg_layouts,
+                        specialization_id: self.env.fresh_call_spec_id(),
+                    },
+                    CallType::ByPointer {
+                        pointer,
+                        ret_layout,
+                        arg_layouts,
+                    } => CallType::ByPointer {
+                        pointer: self.rename(*pointer),
+                        ret_layout: *ret_layout,
+                        arg_layouts,
+                    },
+                    CallType::Foreign { .. } => call_type.clone(),
+                    CallType::LowLevel { op, .. } => CallType::LowLevel {
+                        op: *op,
+                        update_mode: self.env.update_mode_ids.next_id(),
+                    },
+                    CallType::HigherOrder(higher_order) => {
+                        let passed_function = PassedFunction {
+                            specialization_id: self.env.fresh_call_spec_id(),
+                            captured_environment: self
+                                .rename(higher_order.passed_function.captured_environment),
+                            ..higher_order.passed_function
+                        };
+
+                        CallType::HigherOrder(self.env.arena.alloc(HigherOrderLowLevel {
+                            update_mode: self.env.update_mode_ids.next_id(),
+                            passed_function,
+                            ..(*higher_order).clone()
+                        }))
+                    }
+                };
+
+                Expr::Call(Call {
+                    call_type,
+                    arguments: self.rename_all(arguments),
+                })
+            }
+            Expr::Tag {
+                tag_layout,
+                tag_id,
+                arguments,
+                reuse,
+            } => Expr::Tag {
+                tag_layout: *tag_layout,
+                tag_id: *tag_id,
+                arguments: self.rename_all(arguments),
+                reuse: reuse.map(|token| ReuseToken {
+                    symbol: self.rename(token.symbol),
+                    ..token
+                }),
+            },
+            Expr::Struct(fields) => Expr::Struct(self.rename_all(fields)),
+            Expr::StructAtIndex {
+                index,
+                field_layouts,
+                structure,
+            } => Expr::StructAtIndex {
+                index: *index,
+                field_layouts,
+                structure: self.rename(*structure),
+            },
+            Expr::GetTagId {
+                structure,
+                union_layout,
+            } => Expr::GetTagId {
+                structure: self.rename(*structure),
+                union_layout: *union_layout,
+            },
+            Expr::UnionAtIndex {
+                structure,
+                tag_id,
+                union_layout,
+                index,
+            } => Expr::UnionAtIndex {
+                structure: self.rename(*structure),
+                tag_id: *tag_id,
+                union_layout: *union_layout,
+                index: *index,
+            },
+            Expr::UnionFieldPtrAtIndex {
+                structure,
+                tag_id,
+                union_layout,
+                index,
+            } => Expr::UnionFieldPtrAtIndex {
+                structure: self.rename(*structure),
+                tag_id: *tag_id,
+                union_layout: *union_layout,
+                index: *index,
+            },
+            Expr::Array { elem_layout, elems } => {
+                let elems = Vec::from_iter_in(
+                    elems.iter().map(|elem| match elem {
+                        ListLiteralElement::Literal(literal) => {
+                            ListLiteralElement::Literal(*literal)
+                        }
+                        ListLiteralElement::Symbol(symbol) => {
+                            ListLiteralElement::Symbol(self.rename(*symbol))
+                        }
+                    }),
+                    self.env.arena,
+                );
+
+                Expr::Array {
+                    elem_layout: *elem_layout,
+                    elems: elems.into_bump_slice(),
+                }
+            }
+            Expr::ErasedMake { value, callee } => Expr::ErasedMake {
+                value: value.map(|value| self.rename(value)),
+                callee: self.rename(*callee),
+            },
+            Expr::ErasedLoad { symbol, field } => Expr::ErasedLoad {
+                symbol: self.rename(*symbol),
+                field: *field,
+            },
+            Expr::Alloca {
+                element_layout,
+                initializer,
+            } => Expr::Alloca {
+                element_layout: *element_layout,
+                initializer: initializer.map(|initializer| self.rename(initializer)),
+            },
+            Expr::Reset { symbol, .. } => Expr::Reset {
+                symbol: self.rename(*symbol),
+                update_mode: self.env.update_mode_ids.next_id(),
+            },
+            Expr::ResetRef { symbol, .. } => Expr::ResetRef {
+                symbol: self.rename(*symbol),
+                update_mode: self.env.update_mode_ids.next_id(),
+            },
+        }
+    }
+
+    /// Gives a symbol bound in the callee a fresh name, unless it is the returned value of a
+    /// straight-line body, which is bound to the result of the call.
+    fn bind(&mut self, symbol: Symbol) -> Symbol {
+        match self.renames.get(&symbol) {
+            Some(renamed) => *renamed,
+            None => {
+                let fresh = self.env.fresh_symbol();
+                self.renames.insert(symbol, fresh);
+
+                fresh
+            }
+        }
+    }
+
+    fn rename(&self, symbol: Symbol) -> Symbol {
+        self.renames.get(&symbol).copied().unwrap_or(symbol)
+    }
+
+    fn rename_all(&self, symbols: &[Symbol]) -> &'a [Symbol] {
+        Vec::from_iter_in(
+            symbols.iter().map(|symbol| self.rename(*symbol)),
+            self.env.arena,
+        )
+        .into_bump_slice()
+    }
+}
@@ -41,7 +41,7 @@ use ven_pretty::{text, BoxAllocator, DocAllocator, DocBuilder};
 
 use pattern::{from_can_pattern, store_pattern, Pattern};
 
-pub use literal::{ListLiteralElement, Literal};
+pub use literal::{static_list_bytes, ListLiteralElement, Literal};
 
 mod boxed;
 mod decision_tree;
@@ -130,6 +130,35 @@ pub enum OptLevel {
     Optimize,
 }
 
+/// Optional passes over the mono IR, run after specialization and before refcounting.
+///
+/// These are independent of the [OptLevel], so that the dev backends can use them too.
+/// Tests of the backends usually turn them off, so that the code under test is not
+/// folded away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MonoOptimizations {
+    /// Evaluate arithmetic on literals and inline small top-level constants.
+    pub fold_constants: bool,
+    /// Inline calls to small, non-recursive procedures.
+    pub inline_procs: bool,
+    /// Remove heap cells that are only ever taken apart in the procedure that builds them.
+    pub elide_local_allocations: bool,
+}
+
+impl MonoOptimizations {
+    pub const NONE: Self = Self {
+        fold_constants: false,
+        inline_procs: false,
+        elide_local_allocations: false,
+    };
+
+    pub const ALL: Self = Self {
+        fold_constants: true,
+        inline_procs: true,
+        elide_local_allocations: true,
+    };
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct SingleEntryPoint<'a> {
     pub symbol: Symbol,
@@ -898,6 +927,9 @@ impl<'a> SpecializationStack<'a> {
 pub type HostExposedLambdaSets<'a> =
     std::vec::Vec<(LambdaName<'a>, Symbol, HostExposedLambdaSet<'a>)>;
 
+/// The getters glue uses to read values whose size only the app knows, like closures
+pub type GlueGetters<'a> = std::vec::Vec<(Symbol, ProcLayout<'a>)>;
+
 #[derive(Clone, Debug)]
 pub struct Procs<'a> {
     pub partial_procs: PartialProcs<'a>,
@@ -905,6 +937,7 @@ pub struct Procs<'a> {
     pending_specializations: PendingSpecializations<'a>,
     specialized: Specialized<'a>,
     host_exposed_lambda_sets: HostExposedLambdaSets<'a>,
+    glue_getters: GlueGetters<'a>,
     pub runtime_errors: BumpMap<Symbol, &'a str>,
     pub externals_we_need: BumpMap<ModuleId, ExternalSpecializations<'a>>,
     symbol_specializations: SymbolSpecializations<'a>,
@@ -925,6 +958,7 @@ impl<'a> Procs<'a> {
             runtime_errors: BumpMap::new_in(arena),
             externals_we_need: BumpMap::new_in(arena),
             host_exposed_lambda_sets: std::vec::Vec::new(),
+            glue_getters: std::vec::Vec::new(),
             symbol_specializations: Default::default(),
             specialization_stack: SpecializationStack(Vec::with_capacity_in(16, arena)),
 
@@ -993,6 +1027,7 @@ impl<'a> Procs<'a> {
     ) -> (
         MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
         HostExposedLambdaSets<'a>,
+        GlueGetters<'a>,
         ProcsBase<'a>,
     ) {
         let mut specialized_procs =
@@ -1015,6 +1050,7 @@ impl<'a> Procs<'a> {
         (
             specialized_procs,
             self.host_exposed_lambda_sets,
+            self.glue_getters,
             restored_procs_base,
         )
     }
@@ -1364,6 +1400,11 @@ pub struct Env<'a, 'i> {
     pub exposed_by_module: &'i ExposedByModule,
     pub derived_module: &'i SharedDerivedModule,
     pub struct_indexing: UsageTrackingMap<(Symbol, u64), Symbol>,
+    /// Whether to record where definitions come from with [Stmt::Located].
+    pub emit_debug_info: bool,
+    /// Where in the source the calls that bind these symbols are, so that problems found in
+    /// later passes (like constant folding) can point at the operation that caused them.
+    pub call_regions: MutMap<Symbol, Region>,
 }
 
 impl<'a, 'i> Env<'a, 'i> {
@@ -1530,6 +1571,14 @@ pub enum Stmt<'a> {
         /// what happens after the expect
         remainder: &'a Stmt<'a>,
     },
+    /// The statements in `remainder` come from the definition of `symbol` at `region`.
+    /// Only generated when debug info is requested, so that backends can map their code
+    /// back to the source.
+    Located {
+        symbol: Symbol,
+        region: Region,
+        remainder: &'a Stmt<'a>,
+    },
     Dbg {
         /// The expression we're displaying
         symbol: Symbol,
@@ -1723,7 +1772,7 @@ impl<'a> Call<'a> {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct CallSpecId {
     id: u32,
 }
@@ -1736,6 +1785,11 @@ impl CallSpecId {
     /// Dummy value for generating refcount helper procs in the backends
     /// This happens *after* specialization so it's safe
     pub const BACKEND_DUMMY: Self = Self { id: 0 };
+    /// The id after this one. Passes that copy calls after specialization use this to keep
+    /// the ids unique within a procedure.
+    pub(crate) fn next(self) -> Self {
+        Self { id: self.id + 1 }
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -2200,6 +2254,16 @@ impl<'a> Expr<'a> {
             arguments,
         })
     }
+
+    pub(crate) fn ptr_cast(symbol: &'a Symbol) -> Expr<'a> {
+        Expr::Call(Call {
+            call_type: CallType::LowLevel {
+                op: LowLevel::PtrCast,
+                update_mode: UpdateModeId::BACKEND_DUMMY,
+            },
+            arguments: std::slice::from_ref(symbol),
+        })
+    }
 }
 
 impl<'a> Stmt<'a> {
@@ -2244,6 +2308,17 @@ impl<'a> Stmt<'a> {
                 .append(alloc.hardline())
                 .append(cont.to_doc(alloc, interner, pretty)),
 
+            Located {
+                symbol,
+                region,
+                remainder,
+            } => alloc
+                .text("located ")
+                .append(symbol_to_doc(alloc, *symbol, pretty))
+                .append(format!(" at {region:?};"))
+                .append(alloc.hardline())
+                .append(remainder.to_doc(alloc, interner, pretty)),
+
             Dbg {
                 symbol, remainder, ..
             } => alloc
@@ -2421,6 +2496,19 @@ impl<'a> Stmt<'a> {
     }
 }
 
+/// Marks `stmt` as coming from the definition of `symbol` at `region`, if debug info is wanted.
+fn located<'a>(env: &Env<'a, '_>, symbol: Symbol, region: Region, stmt: Stmt<'a>) -> Stmt<'a> {
+    if env.emit_debug_info {
+        Stmt::Located {
+            symbol,
+            region,
+            remainder: env.arena.alloc(stmt),
+        }
+    } else {
+        stmt
+    }
+}
+
 fn from_can_let<'a>(
     env: &mut Env<'a, '_>,
     procs: &mut Procs<'a>,
@@ -2713,12 +2801,12 @@ fn from_can_let<'a>(
                     );
                 }
 
-                stmt
+                located(env, *symbol, def.loc_pattern.region, stmt)
             }
             _ => {
                 let rest = lower_rest!(variable, cont.value);
 
-                with_hole(
+                let stmt = with_hole(
                     env,
                     def.loc_expr.value,
                     def.expr_var,
@@ -2726,7 +2814,9 @@ fn from_can_let<'a>(
                     layout_cache,
                     *symbol,
                     env.arena.alloc(rest),
-                )
+                );
+
+                located(env, *symbol, def.loc_pattern.region, stmt)
             }
         };
     }
@@ -3228,8 +3318,11 @@ fn specialize_external_help<'a>(
                         legacy_layout_based_extern_names: _,
                     } = all_glue_procs;
 
-                    for (_layout, glue_procs) in getters {
+                    for (_layout, _tag_id, glue_procs) in getters {
                         for glue_proc in glue_procs {
+                            procs
+                                .glue_getters
+                                .push((glue_proc.name, glue_proc.proc_layout));
                             procs.specialized.insert_specialized(
                                 glue_proc.proc.name.name(),
                                 glue_proc.proc_layout,
@@ -5387,6 +5480,18 @@ pub fn with_hole<'a>(
         Call(boxed, loc_args, _) => {
             let (fn_var, loc_expr, _lambda_set_var, _ret_var) = *boxed;
 
+            if !env.home.is_builtin() {
+                // The function of a desugared binop sits between its arguments
+                let regions = || {
+                    std::iter::once(loc_expr.region).chain(loc_args.iter().map(|(_, a)| a.region))
+                };
+                let start = regions().map(|region| region.start()).min().unwrap();
+                let end = regions().map(|region| region.end()).max().unwrap();
+
+                env.call_regions
+                    .insert(assigned, Region::between(start, end));
+            }
+
             // even if a call looks like it's by name, it may in fact be by-pointer.
             // E.g. in `(\f, x -> f x)` the call is in fact by pointer.
             // So we check the function name against the list of partial procedures,
@@ -5853,7 +5958,7 @@ pub fn with_hole<'a>(
                 }
             }
         }
-        TypedHole(_) => runtime_error(env, "Hit a blank"),
+        TypedHole(..) => runtime_error(env, "Hit a type hole"),
         RuntimeError(e) => runtime_error(env, env.arena.alloc(e.runtime_message())),
         Crash { msg, ret_var: _ } => {
             let msg_sym = possible_reuse_symbol_or_specialize(
@@ -6594,6 +6699,32 @@ fn convert_tag_union<'a>(
                         reuse: None,
                     };
 
+                    (tag, union_layout)
+                }
+                NullableBoxed { nullable_name, .. } => {
+                    field_symbols = {
+                        let mut temp = Vec::with_capacity_in(field_symbols_temp.len(), arena);
+
+                        temp.extend(field_symbols_temp.iter().map(|r| r.1));
+
+                        temp.into_bump_slice()
+                    };
+
+                    let tag = match field_symbols {
+                        [boxed] => {
+                            debug_assert_ne!(nullable_name.expect_tag_ref(), &tag_name);
+
+                            // the box is already a non-null pointer to the payload
+                            Expr::ptr_cast(boxed)
+                        }
+                        _ => Expr::Tag {
+                            tag_layout: union_layout,
+                            tag_id: tag_id as _,
+                            arguments: field_symbols,
+                            reuse: None,
+                        },
+                    };
+
                     (tag, union_layout)
                 }
             };
@@ -7598,6 +7729,25 @@ fn substitute_in_stmt_help<'a>(
             }
         }
 
+        Located {
+            symbol,
+            region,
+            remainder,
+        } => {
+            let new_symbol = substitute(subs, *symbol);
+            let new_remainder = substitute_in_stmt_help(arena, remainder, subs);
+
+            if new_symbol.is_none() && new_remainder.is_none() {
+                return None;
+            }
+
+            Some(arena.alloc(Located {
+                symbol: new_symbol.unwrap_or(*symbol),
+                region: *region,
+                remainder: new_remainder.unwrap_or(remainder),
+            }))
+        }
+
         Dbg {
             symbol,
             variable,
@@ -9996,7 +10146,9 @@ pub struct GlueProc<'a> {
 }
 
 pub struct GlueProcs<'a> {
-    pub getters: Vec<'a, (Layout<'a>, Vec<'a, GlueProc<'a>>)>,
+    /// The getters for the fields of a struct, or for the payload of the tag with the given id
+    /// when the layout is a tag union.
+    pub getters: Vec<'a, (Layout<'a>, Option<TagIdIntType>, Vec<'a, GlueProc<'a>>)>,
     /// Lambda set IDs computed from the layout of the lambda set. Should be replaced by
     /// computation from type variable eventually.
     pub legacy_layout_based_extern_names: Vec<'a, (LambdaSetId, RawFunctionLayout<'a>)>,
@@ -10164,7 +10316,7 @@ where
                     $field_layouts,
                 );
 
-                answer.getters.push(($layout, procs));
+                answer.getters.push(($layout, Some($tag_id), procs));
             }
 
             for in_layout in $field_layouts.iter().rev() {
@@ -10199,7 +10351,7 @@ where
                         field_layouts,
                     );
 
-                    answer.getters.push((layout, procs));
+                    answer.getters.push((layout, None, procs));
                 }
 
                 for in_layout in field_layouts.iter().rev() {
@@ -10216,8 +10368,13 @@ where
                     }
                 }
                 UnionLayout::Recursive(tags) => {
-                    for in_layout in tags.iter().flat_map(|e| e.iter()) {
-                        stack.push(layout_interner.get(*in_layout));
+                    for (i, field_layouts) in tags.iter().enumerate() {
+                        handle_tag_field_layouts!(
+                            i as TagIdIntType,
+                            layout,
+                            union_layout,
+                            *field_layouts
+                        );
                     }
                 }
                 UnionLayout::NonNullableUnwrapped(field_layouts) => {
@@ -10233,10 +10390,12 @@ where
                         handle_tag_field_layouts!(i, layout, union_layout, *field_layouts);
                     }
                 }
-                UnionLayout::NullableUnwrapped { other_fields, .. } => {
-                    for in_layout in other_fields.iter().rev() {
-                        stack.push(layout_interner.get(*in_layout));
-                    }
+                UnionLayout::NullableUnwrapped {
+                    nullable_id,
+                    other_fields,
+                } => {
+                    let tag_id = !nullable_id as TagIdIntType;
+                    handle_tag_field_layouts!(tag_id, layout, union_layout, other_fields);
                 }
             },
             LayoutRepr::LambdaSet(lambda_set) => {
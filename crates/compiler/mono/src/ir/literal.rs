@@ -51,6 +51,51 @@ impl<'a> ListLiteralElement<'a> {
     }
 }
 
+/// The bytes of a list literal whose elements are all number literals, laid out as the elements
+/// are in memory (little-endian). Backends emit such lists as static data instead of building
+/// them on the heap.
+pub fn static_list_bytes<'a>(
+    interner: &impl LayoutInterner<'a>,
+    elem_layout: InLayout<'a>,
+    elems: &[ListLiteralElement<'a>],
+) -> Option<std::vec::Vec<u8>> {
+    let repr = interner.get_repr(elem_layout);
+    let mut bytes =
+        std::vec::Vec::with_capacity(elems.len() * interner.stack_size(elem_layout) as usize);
+
+    for elem in elems {
+        let literal = match elem {
+            ListLiteralElement::Literal(literal) => literal,
+            ListLiteralElement::Symbol(_) => return None,
+        };
+
+        match (literal, repr) {
+            (Literal::Int(n), LayoutRepr::Builtin(Builtin::Int(width))) => {
+                let n = i128::from_ne_bytes(*n).to_le_bytes();
+                bytes.extend_from_slice(&n[..width.stack_size() as usize]);
+            }
+            (Literal::U128(n), LayoutRepr::Builtin(Builtin::Int(IntWidth::U128))) => {
+                bytes.extend_from_slice(&u128::from_ne_bytes(*n).to_le_bytes());
+            }
+            (Literal::Byte(n), LayoutRepr::Builtin(Builtin::Int(IntWidth::U8))) => {
+                bytes.push(*n);
+            }
+            (Literal::Float(n), LayoutRepr::Builtin(Builtin::Float(FloatWidth::F32))) => {
+                bytes.extend_from_slice(&(*n as f32).to_le_bytes());
+            }
+            (Literal::Float(n), LayoutRepr::Builtin(Builtin::Float(FloatWidth::F64))) => {
+                bytes.extend_from_slice(&n.to_le_bytes());
+            }
+            (Literal::Decimal(n), LayoutRepr::Builtin(Builtin::Decimal)) => {
+                bytes.extend_from_slice(&i128::from_ne_bytes(*n).to_le_bytes());
+            }
+            _ => return None,
+        }
+    }
+
+    Some(bytes)
+}
+
 pub enum NumLiteral {
     Int([u8; 16], IntWidth),
     U128([u8; 16]),
@@ -795,6 +795,12 @@ fn from_can_pattern_help<'a>(
                             nullable_id,
                             nullable_name,
                             other_name: _,
+                        }
+                        | NullableBoxed {
+                            other_fields,
+                            nullable_id,
+                            nullable_name,
+                            ..
                         } => {
                             debug_assert!(!other_fields.is_empty());
 
@@ -1548,6 +1554,9 @@ fn store_tag_pattern<'a>(
 
     let mut is_productive = false;
 
+    let payload_layouts = Vec::from_iter_in(arguments.iter().map(|(_, l)| *l), env.arena);
+    let is_nullable_box = union_layout.is_nullable_box(&layout_cache.interner, &payload_layouts);
+
     for (index, (argument, arg_layout)) in arguments.iter().enumerate().rev() {
         let mut arg_layout = *arg_layout;
 
@@ -1556,11 +1565,16 @@ fn store_tag_pattern<'a>(
             arg_layout = layout_cache.put_in_direct_no_semantic(LayoutRepr::Union(union_layout));
         }
 
-        let load = Expr::UnionAtIndex {
-            index: index as u64,
-            structure,
-            tag_id,
-            union_layout,
+        let load = if is_nullable_box {
+            // the union pointer is the box
+            Expr::ptr_cast(env.arena.alloc(structure))
+        } else {
+            Expr::UnionAtIndex {
+                index: index as u64,
+                structure,
+                tag_id,
+                union_layout,
+            }
         };
 
         match argument {
@@ -1009,6 +1009,22 @@ impl<'a> UnionLayout<'a> {
         }
     }
 
+    /// Whether `payload` is a box that this union stores as a nullable pointer, like the `Some`
+    /// payload of `[None, Some (Box a)]`. Such a payload is the union pointer itself rather than
+    /// a field behind it.
+    pub fn is_nullable_box<I>(&self, interner: &I, payload: &[InLayout<'a>]) -> bool
+    where
+        I: LayoutInterner<'a>,
+    {
+        match (self, payload) {
+            (UnionLayout::NullableUnwrapped { other_fields, .. }, [payload]) => matches!(
+                interner.get_repr(*payload),
+                LayoutRepr::Union(UnionLayout::NonNullableUnwrapped(fields)) if fields == *other_fields
+            ),
+            _ => false,
+        }
+    }
+
     pub fn tag_is_null(&self, tag_id: TagIdIntType) -> bool {
         match self {
             UnionLayout::NonRecursive(_)
@@ -3660,6 +3676,17 @@ pub enum WrappedVariant<'a> {
         other_name: TagOrClosure,
         other_fields: &'a [InLayout<'a>],
     },
+    /// A non-recursive `[None, Some (Box a)]`. The box is never null, so its pointer doubles as
+    /// the whole union, with null standing in for the tag without a payload. At runtime this is a
+    /// [UnionLayout::NullableUnwrapped] over `boxed_fields`; in the IR the payload of
+    /// `other_name` is still the box itself (`other_fields`).
+    NullableBoxed {
+        nullable_id: bool,
+        nullable_name: TagOrClosure,
+        other_name: TagOrClosure,
+        other_fields: &'a [InLayout<'a>],
+        boxed_fields: &'a [InLayout<'a>],
+    },
 }
 
 impl<'a> WrappedVariant<'a> {
@@ -3706,6 +3733,13 @@ impl<'a> WrappedVariant<'a> {
                 nullable_name,
                 other_name,
                 other_fields,
+            }
+            | NullableBoxed {
+                nullable_id,
+                nullable_name,
+                other_name,
+                other_fields,
+                ..
             } => {
                 if tag_name == nullable_name.expect_tag_ref() {
                     (*nullable_id as TagIdIntType, &[] as &[_])
@@ -3733,7 +3767,7 @@ impl<'a> WrappedVariant<'a> {
 
                 sorted_tag_layouts.len() + 1
             }
-            NullableUnwrapped { .. } => 2,
+            NullableUnwrapped { .. } | NullableBoxed { .. } => 2,
             NonNullableUnwrapped { .. } => 1,
         }
     }
@@ -3919,12 +3953,20 @@ where
             let mut has_any_arguments = false;
 
             let mut inhabited_tag_ids = BitVec::<usize>::repeat(true, num_tags);
+            let mut boxed_payloads = BitVec::<usize>::repeat(false, num_tags);
 
             for &&(tag_name, arguments) in tags_list.iter() {
                 let mut arg_layouts = Vec::with_capacity_in(arguments.len() + 1, env.arena);
 
+                if let [var] = arguments {
+                    boxed_payloads.set(answer.len(), is_box_var(env.subs, *var));
+                }
+
                 for &var in arguments {
                     let Cacheable(result, criteria) = Layout::from_var(env, var);
+                    if !criteria.is_cacheable() {
+                        boxed_payloads.set(answer.len(), false);
+                    }
                     cache_criteria.and(criteria, env.subs);
                     match result {
                         Ok(layout) => {
@@ -3999,9 +4041,11 @@ where
                     Cacheable(UnionVariant::ByteUnion(tag_names), cache_criteria)
                 }
                 _ => {
-                    let variant = WrappedVariant::NonRecursive {
-                        sorted_tag_layouts: answer,
-                    };
+                    let variant =
+                        nullable_box_variant(&env.cache.interner, &answer, &boxed_payloads)
+                            .unwrap_or(WrappedVariant::NonRecursive {
+                                sorted_tag_layouts: answer,
+                            });
 
                     Cacheable(UnionVariant::Wrapped(variant), cache_criteria)
                 }
@@ -4051,6 +4095,63 @@ where
     }
 }
 
+fn is_box_var(subs: &Subs, var: Variable) -> bool {
+    match subs.get_content_without_compacting(var) {
+        Content::Structure(FlatType::Apply(Symbol::BOX_BOX_TYPE, _)) => true,
+        Content::Alias(_, _, actual, _) => is_box_var(subs, *actual),
+        _ => false,
+    }
+}
+
+/// `[None, Some (Box a)]` does not need to store a tag id: a box is never null, so the box
+/// pointer can represent the whole union, and null the tag without a payload.
+///
+/// `boxed_payloads` marks the tags whose only payload is a `Box` that does not contain a naked
+/// recursion pointer.
+///
+/// This is the only niche used for non-recursive unions. Storing the tag id in the alignment
+/// bits of a boxed payload (e.g. `[A (Box a), B (Box b)]`) is not done; such unions still get
+/// a [UnionLayout::NonRecursive] with an explicit tag id. Single-field records and opaques need
+/// no special case here, since they already have the layout of their one field.
+fn nullable_box_variant<'a, I>(
+    interner: &I,
+    answer: &[(TagOrClosure, &'a [InLayout<'a>])],
+    boxed_payloads: &BitVec,
+) -> Option<WrappedVariant<'a>>
+where
+    I: LayoutInterner<'a>,
+{
+    if !GENERATE_NULLABLE {
+        return None;
+    }
+
+    // lambda sets are taken apart by index elsewhere, so only apply this to actual tags
+    let [(name0 @ TagOrClosure::Tag(_), args0), (name1 @ TagOrClosure::Tag(_), args1)] = answer
+    else {
+        return None;
+    };
+
+    let (nullable_id, nullable_name, other_name, other_fields) =
+        match (args0.is_empty(), args1.is_empty()) {
+            (true, false) if boxed_payloads[1] => (false, name0, name1, *args1),
+            (false, true) if boxed_payloads[0] => (true, name1, name0, *args0),
+            _ => return None,
+        };
+
+    match interner.get_repr(other_fields[0]) {
+        LayoutRepr::Union(UnionLayout::NonNullableUnwrapped(boxed_fields)) => {
+            Some(WrappedVariant::NullableBoxed {
+                nullable_id,
+                nullable_name: nullable_name.clone(),
+                other_name: other_name.clone(),
+                other_fields,
+                boxed_fields,
+            })
+        }
+        _ => None,
+    }
+}
+
 fn union_sorted_tags_help<'a, L>(
     env: &mut Env<'a, '_>,
     mut tags_vec: std::vec::Vec<(L, std::vec::Vec<Variable>)>,
@@ -4129,6 +4230,7 @@ where
 
             let mut nullable = None;
             let mut inhabited_tag_ids = BitVec::<usize>::repeat(true, num_tags);
+            let mut boxed_payloads = BitVec::<usize>::repeat(false, num_tags);
 
             // only recursive tag unions can be nullable
             let is_recursive = opt_rec_var.is_some();
@@ -4145,8 +4247,15 @@ where
 
                 let mut arg_layouts = Vec::with_capacity_in(arguments.len() + 1, env.arena);
 
+                if let [var] = arguments.as_slice() {
+                    boxed_payloads.set(answer.len(), is_box_var(env.subs, *var));
+                }
+
                 for var in arguments {
                     let Cacheable(result, criteria) = Layout::from_var(env, var);
+                    if !criteria.is_cacheable() {
+                        boxed_payloads.set(answer.len(), false);
+                    }
                     cache_criteria.and(criteria, env.subs);
                     match result {
                         Ok(in_layout) => {
@@ -4261,9 +4370,10 @@ where
                             sorted_tag_layouts: answer,
                         }
                     } else {
-                        WrappedVariant::NonRecursive {
-                            sorted_tag_layouts: answer,
-                        }
+                        nullable_box_variant(&env.cache.interner, &answer, &boxed_payloads)
+                            .unwrap_or(WrappedVariant::NonRecursive {
+                                sorted_tag_layouts: answer,
+                            })
                     };
 
                     if let Some(rec_var) = opt_rec_var {
@@ -4382,6 +4492,22 @@ where
                     env.cache.put_in(layout)
                 }
 
+                NullableBoxed {
+                    nullable_id,
+                    boxed_fields,
+                    ..
+                } => {
+                    let layout = Layout {
+                        repr: LayoutRepr::Union(UnionLayout::NullableUnwrapped {
+                            nullable_id,
+                            other_fields: boxed_fields,
+                        })
+                        .direct(),
+                        semantic: SemanticRepr::NONE,
+                    };
+                    env.cache.put_in(layout)
+                }
+
                 Recursive { .. }
                 | NullableWrapped { .. }
                 | NullableUnwrapped { .. }
@@ -10,8 +10,11 @@
 #![allow(clippy::too_many_arguments)]
 
 pub mod code_gen_help;
+pub mod constant_folding;
 pub mod drop_specialization;
+pub mod escape;
 pub mod inc_dec;
+pub mod inline;
 pub mod ir;
 pub mod layout;
 pub mod low_level;
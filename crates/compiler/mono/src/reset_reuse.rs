@@ -650,6 +650,27 @@ fn insert_reset_reuse_operations_stmt<'a, 'i>(
                 remainder: new_remainder,
             })
         }
+        Stmt::Located {
+            symbol,
+            region,
+            remainder,
+        } => {
+            let new_remainder = insert_reset_reuse_operations_stmt(
+                arena,
+                layout_interner,
+                home,
+                ident_ids,
+                update_mode_ids,
+                environment,
+                remainder,
+            );
+
+            arena.alloc(Stmt::Located {
+                symbol: *symbol,
+                region: *region,
+                remainder: new_remainder,
+            })
+        }
         Stmt::Dbg {
             symbol,
             variable,
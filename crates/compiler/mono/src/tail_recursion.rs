@@ -329,6 +329,26 @@ fn insert_jumps<'a>(
             }
         }
 
+        Located {
+            symbol,
+            region,
+            remainder,
+        } => match insert_jumps(
+            arena,
+            remainder,
+            goal_id,
+            needle,
+            needle_arguments,
+            needle_result,
+        ) {
+            Some(cont) => Some(arena.alloc(Located {
+                symbol: *symbol,
+                region: *region,
+                remainder: cont,
+            })),
+            None => None,
+        },
+
         Dbg {
             symbol,
             variable,
@@ -547,7 +567,8 @@ fn trmc_candidates_help(
             }
         }
         Stmt::Refcounting(_, next) => trmc_candidates_help(function_name, next, candidates),
-        Stmt::Expect { remainder, .. }
+        Stmt::Located { remainder, .. }
+        | Stmt::Expect { remainder, .. }
         | Stmt::ExpectFx { remainder, .. }
         | Stmt::Dbg { remainder, .. } => trmc_candidates_help(function_name, remainder, candidates),
         Stmt::Join {
@@ -1018,6 +1039,15 @@ impl<'a> TrmcEnv<'a> {
                 variables,
                 remainder: arena.alloc(self.walk_stmt(env, remainder)),
             },
+            Stmt::Located {
+                symbol,
+                region,
+                remainder,
+            } => Stmt::Located {
+                symbol: *symbol,
+                region: *region,
+                remainder: arena.alloc(self.walk_stmt(env, remainder)),
+            },
             Stmt::Dbg {
                 symbol,
                 variable,
@@ -1124,7 +1154,7 @@ fn stmt_contains_symbol_nonrec(stmt: &Stmt, needle: Symbol) -> bool {
             condition, lookups, ..
         } => needle == *condition || lookups.contains(&needle),
         Stmt::Dbg { symbol, .. } => needle == *symbol,
-        Stmt::Join { .. } => false,
+        Stmt::Located { .. } | Stmt::Join { .. } => false,
         Stmt::Jump(_, arguments) => arguments.contains(&needle),
         Stmt::Crash(symbol, _) => needle == *symbol,
     }
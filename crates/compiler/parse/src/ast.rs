@@ -123,6 +123,7 @@ pub enum StrSegment<'a> {
     Unicode(Loc<&'a str>),           // e.g. "00A0" in "\u(00A0)"
     EscapedChar(EscapedChar),        // e.g. '\n' in "Hello!\n"
     Interpolated(Loc<&'a Expr<'a>>), // e.g. (name) in "Hi, \(name)!"
+    Inspected(Loc<&'a Expr<'a>>),    // e.g. {point} in "At \{point}" - any `Inspect` value
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -212,7 +213,9 @@ impl<'a> TryFrom<StrSegment<'a>> for SingleQuoteSegment<'a> {
             StrSegment::Plaintext(s) => Ok(SingleQuoteSegment::Plaintext(s)),
             StrSegment::Unicode(s) => Ok(SingleQuoteSegment::Unicode(s)),
             StrSegment::EscapedChar(s) => Ok(SingleQuoteSegment::EscapedChar(s)),
-            StrSegment::Interpolated(_) => Err(ESingleQuote::InterpolationNotAllowed),
+            StrSegment::Interpolated(_) | StrSegment::Inspected(_) => {
+                Err(ESingleQuote::InterpolationNotAllowed)
+            }
         }
     }
 }
@@ -285,6 +288,9 @@ pub enum Expr<'a> {
 
     Underscore(&'a str),
 
+    /// A named type hole, e.g. `?name`, whose inferred type gets reported
+    TypeHole(&'a str),
+
     // The "crash" keyword
     Crash,
 
@@ -378,14 +384,32 @@ pub enum Implements<'a> {
 pub struct AbilityMember<'a> {
     pub name: Loc<Spaced<'a, &'a str>>,
     pub typ: Loc<TypeAnnotation<'a>>,
+    /// The default implementation of the member, if the ability provides one.
+    pub default: Option<AbilityMemberDefault<'a>>,
 }
 
 impl AbilityMember<'_> {
     pub fn region(&self) -> Region {
-        Region::across_all([self.name.region, self.typ.region].iter())
+        match &self.default {
+            Some(default) => {
+                Region::across_all([self.name.region, self.typ.region, default.body.region].iter())
+            }
+            None => Region::across_all([self.name.region, self.typ.region].iter()),
+        }
     }
 }
 
+/// A default implementation of an ability member, given on the line after the member's signature:
+///
+///   isNotEq : a, a -> Bool where a implements Eq
+///   isNotEq = \a, b -> !(isEq a b)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AbilityMemberDefault<'a> {
+    /// The spaces between the member's signature and the start of the default implementation.
+    pub spaces_before: &'a [CommentOrNewline<'a>],
+    pub body: &'a Loc<Expr<'a>>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TypeDef<'a> {
     /// A type alias. This is like a standalone annotation, except the pattern
@@ -407,9 +431,14 @@ pub enum TypeDef<'a> {
     /// An ability definition. E.g.
     ///   Hash implements
     ///     hash : a -> U64 where a implements Hash
+    ///
+    /// An ability may require its implementors to implement other abilities too, e.g.
+    ///   Ord implements Eq
+    ///     compare : a, a -> [LT, EQ, GT] where a implements Ord
     Ability {
         header: TypeHeader<'a>,
         loc_implements: Loc<Implements<'a>>,
+        parents: &'a [AbilityName<'a>],
         members: &'a [AbilityMember<'a>],
     },
 }
@@ -645,6 +674,9 @@ pub enum TypeAnnotation<'a> {
     /// '_', indicating the compiler should infer the type
     Inferred,
 
+    /// A named type hole, e.g. `?name`; the compiler infers and reports its type
+    TypeHole(&'a str),
+
     /// The `*` type variable, e.g. in (List *)
     Wildcard,
 
@@ -1511,6 +1543,7 @@ impl<'a> Malformed for Expr<'a> {
             AccessorFunction(_) |
             Var { .. } |
             Underscore(_) |
+            TypeHole(_) |
             Tag(_) |
             OpaqueRef(_) |
             SingleQuote(_) | // This is just a &str - not a bunch of segments
@@ -1584,7 +1617,7 @@ impl<'a> Malformed for StrSegment<'a> {
     fn is_malformed(&self) -> bool {
         match self {
             StrSegment::Plaintext(_) | StrSegment::Unicode(_) | StrSegment::EscapedChar(_) => false,
-            StrSegment::Interpolated(expr) => expr.is_malformed(),
+            StrSegment::Interpolated(expr) | StrSegment::Inspected(expr) => expr.is_malformed(),
         }
     }
 }
@@ -1689,10 +1722,12 @@ impl<'a> Malformed for TypeDef<'a> {
             TypeDef::Ability {
                 header,
                 loc_implements,
+                parents,
                 members,
             } => {
                 header.is_malformed()
                     || loc_implements.is_malformed()
+                    || parents.iter().any(|parent| parent.is_malformed())
                     || members.iter().any(|member| member.is_malformed())
             }
         }
@@ -1702,6 +1737,9 @@ impl<'a> Malformed for TypeDef<'a> {
 impl<'a> Malformed for AbilityMember<'a> {
     fn is_malformed(&self) -> bool {
         self.typ.is_malformed()
+            || self
+                .default
+                .map_or(false, |default| default.body.is_malformed())
     }
 }
 
@@ -1795,6 +1833,7 @@ impl<'a> Malformed for TypeAnnotation<'a> {
             TypeAnnotation::Apply(_, _, args) => args.iter().any(|arg| arg.is_malformed()),
             TypeAnnotation::BoundVariable(_)
             | TypeAnnotation::Inferred
+            | TypeAnnotation::TypeHole(_)
             | TypeAnnotation::Wildcard => false,
             TypeAnnotation::As(ty, _, head) => ty.is_malformed() || head.is_malformed(),
             TypeAnnotation::Record { fields, ext } => {
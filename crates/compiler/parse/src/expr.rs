@@ -169,6 +169,7 @@ fn loc_term_or_underscore_or_conditional<'a>(
         loc!(specialize(EExpr::Closure, closure_help(options))),
         loc!(crash_kw()),
         loc!(underscore_expression()),
+        loc!(type_hole_expression()),
         loc!(record_literal_help()),
         loc!(specialize(EExpr::List, list_literal_help())),
         loc!(map_with_arena!(
@@ -189,6 +190,7 @@ fn loc_term_or_underscore<'a>(
         loc!(specialize(EExpr::Number, positive_number_literal_help())),
         loc!(specialize(EExpr::Closure, closure_help(options))),
         loc!(underscore_expression()),
+        loc!(type_hole_expression()),
         loc!(record_literal_help()),
         loc!(specialize(EExpr::List, list_literal_help())),
         loc!(map_with_arena!(
@@ -231,6 +233,22 @@ fn underscore_expression<'a>() -> impl Parser<'a, Expr<'a>, EExpr<'a>> {
     }
 }
 
+/// A named type hole, e.g. `?name`. The name must directly follow the `?`.
+fn type_hole_expression<'a>() -> impl Parser<'a, Expr<'a>, EExpr<'a>> {
+    move |arena: &'a Bump, state: State<'a>, min_indent: u32| {
+        let start = state.pos();
+
+        let (_, _, next_state) = word1(b'?', EExpr::TypeHole).parse(arena, state, min_indent)?;
+
+        let (_, name, final_state) =
+            specialize(move |_, _| EExpr::TypeHole(start), lowercase_ident())
+                .parse(arena, next_state, min_indent)
+                .map_err(|(_, fail)| (NoProgress, fail))?;
+
+        Ok((MadeProgress, Expr::TypeHole(name), final_state))
+    }
+}
+
 fn crash_kw<'a>() -> impl Parser<'a, Expr<'a>, EExpr<'a>> {
     move |arena: &'a Bump, state: State<'a>, min_indent: u32| {
         let (_, _, next_state) = crate::parser::keyword_e(crate::keyword::CRASH, EExpr::Crash)
@@ -1253,10 +1271,114 @@ fn finish_parsing_alias_or_opaque<'a>(
 mod ability {
     use super::*;
     use crate::{
-        ast::{AbilityMember, Spaceable, Spaced},
+        ast::{AbilityMember, AbilityMemberDefault, AbilityName, Spaceable, Spaced},
         parser::EAbility,
     };
 
+    /// Parses the abilities that implementors of an ability must also implement; for example the
+    /// `Eq & Hash` in `Ord implements Eq & Hash`. These must be on the same line as `implements`.
+    pub fn parse_parents<'a>() -> impl Parser<'a, Vec<'a, AbilityName<'a>>, EAbility<'a>> {
+        move |arena, state: State<'a>, min_indent: u32| {
+            let mut parents = Vec::new_in(arena);
+
+            // Spaces on the same line are not recorded; anything else means the first demand is
+            // on the next line, and there are no parents.
+            let (_, spaces, state) = space0_e(EAbility::DemandName)
+                .parse(arena, state, min_indent)
+                .map_err(|(_, fail)| (NoProgress, fail))?;
+            if !spaces.is_empty() {
+                return Err((NoProgress, EAbility::DemandName(state.pos())));
+            }
+
+            let start = state.pos();
+            let (_, first, mut state) = type_annotation::ability_name()
+                .parse(arena, state, min_indent)
+                .map_err(|(_, fail)| (NoProgress, EAbility::Type(fail, start)))?;
+            parents.push(first);
+
+            // Parents are separated by `&`, just like in an `implements` clause.
+            while let Ok((_, _, state_after_and)) = and!(
+                space0_e(EType::TIndentStart),
+                word1(b'&', EType::TImplementsClause)
+            )
+            .parse(arena, state.clone(), min_indent)
+            {
+                let start = state_after_and.pos();
+                match skip_first!(
+                    space0_e(EType::TIndentStart),
+                    type_annotation::ability_name()
+                )
+                .parse(arena, state_after_and, min_indent)
+                {
+                    Ok((_, parent, next_state)) => {
+                        parents.push(parent);
+                        state = next_state;
+                    }
+                    Err((_, fail)) => {
+                        return Err((MadeProgress, EAbility::Type(fail, start)));
+                    }
+                }
+            }
+
+            Ok((MadeProgress, parents, state))
+        }
+    }
+
+    /// Parses the default implementation that may follow an ability demand, like the second
+    /// line of
+    ///
+    ///   isNotEq : a, a -> Bool where a implements Eq
+    ///   isNotEq = \a, b -> !(isEq a b)
+    ///
+    /// The default must be aligned with the demand, and must use the name of the demand.
+    pub fn parse_demand_default<'a>(
+        arena: &'a Bump,
+        state: State<'a>,
+        demand_column: u32,
+        demand_name: &'a str,
+    ) -> ParseResult<'a, Option<AbilityMemberDefault<'a>>, EExpr<'a>> {
+        let initial = state.clone();
+
+        let (spaces_before, state) = match space0_e(EExpr::IndentStart).parse(arena, state, 0) {
+            Ok((_, spaces, state)) => (spaces, state),
+            Err(_) => return Ok((NoProgress, None, initial)),
+        };
+
+        if state.column() != demand_column {
+            return Ok((NoProgress, None, initial));
+        }
+
+        // Only continue if this is `demandName =`; anything else is the next demand, or the end
+        // of the ability definition.
+        let state = match lowercase_ident().parse(arena, state, demand_column) {
+            Ok((_, name, state)) if name == demand_name => state,
+            _ => return Ok((NoProgress, None, initial)),
+        };
+        let state = match and!(space0_e(EExpr::IndentEquals), operator()).parse(
+            arena,
+            state,
+            demand_column,
+        ) {
+            Ok((_, (_, BinOp::Assignment), state)) => state,
+            _ => return Ok((NoProgress, None, initial)),
+        };
+
+        let options = ExprParseOptions {
+            accept_multi_backpassing: true,
+            check_for_arrow: true,
+        };
+        let parse_body =
+            space0_before_e(increment_min_indent(expr_start(options)), EExpr::IndentEnd);
+        let (_, body, state) = parse_body.parse(arena, state, demand_column)?;
+
+        let default = AbilityMemberDefault {
+            spaces_before,
+            body: arena.alloc(body),
+        };
+
+        Ok((MadeProgress, Some(default), state))
+    }
+
     /// Parses a single ability demand line; see `parse_demand`.
     fn parse_demand_help<'a>() -> impl Parser<'a, AbilityMember<'a>, EAbility<'a>> {
         map!(
@@ -1276,6 +1398,7 @@ mod ability {
                 AbilityMember {
                     name: name.map_owned(Spaced::Item),
                     typ,
+                    default: None,
                 }
             }
         )
@@ -1378,13 +1501,31 @@ fn finish_parsing_ability_def_help<'a>(
 
     let min_indent_for_demand = start_column + 1;
 
+    // Parse the abilities this ability requires, if there are any.
+    let start = state.pos();
+    let (parents, state) = match ability::parse_parents().parse(arena, state.clone(), 0) {
+        Ok((_, parents, state)) => (parents.into_bump_slice(), state),
+        Err((NoProgress, _)) => (&[][..], state),
+        Err((MadeProgress, problem)) => {
+            return Err((MadeProgress, EExpr::Ability(problem, start)));
+        }
+    };
+
     // Parse the first demand. This will determine the indentation level all the
     // other demands must observe.
     let start = state.pos();
-    let (_, (demand_indent_level, first_demand), mut state) =
+    let (_, (demand_indent_level, mut first_demand), state) =
         ability::parse_demand(ability::IndentLevel::PendingMin(min_indent_for_demand))
             .parse(arena, state, min_indent_for_demand)
             .map_err(|(progress, err)| (progress, EExpr::Ability(err, start)))?;
+
+    let (_, default, mut state) = ability::parse_demand_default(
+        arena,
+        state,
+        demand_indent_level,
+        first_demand.name.extract_spaces().item,
+    )?;
+    first_demand.default = default;
     demands.push(first_demand);
 
     let demand_indent = ability::IndentLevel::Exact(demand_indent_level);
@@ -1392,7 +1533,15 @@ fn finish_parsing_ability_def_help<'a>(
 
     loop {
         match demand_parser.parse(arena, state.clone(), min_indent_for_demand) {
-            Ok((_, (_indent, demand), next_state)) => {
+            Ok((_, (_indent, mut demand), next_state)) => {
+                let (_, default, next_state) = ability::parse_demand_default(
+                    arena,
+                    next_state,
+                    demand_indent_level,
+                    demand.name.extract_spaces().item,
+                )?;
+                demand.default = default;
+
                 state = next_state;
                 demands.push(demand);
             }
@@ -1405,10 +1554,11 @@ fn finish_parsing_ability_def_help<'a>(
         }
     }
 
-    let def_region = Region::span_across(&name.region, &demands.last().unwrap().typ.region);
+    let def_region = Region::span_across(&name.region, &demands.last().unwrap().region());
     let type_def = TypeDef::Ability {
         header: TypeHeader { name, vars: args },
         loc_implements,
+        parents,
         members: demands.into_bump_slice(),
     };
 
@@ -1936,6 +2086,7 @@ fn expr_to_pattern_help<'a>(arena: &'a Bump, expr: &Expr<'a>) -> Result<Pattern<
         | Expr::UnappliedRecordBuilder { .. }
         | Expr::RecordUpdate { .. }
         | Expr::UnaryOp(_, _)
+        | Expr::TypeHole(_)
         | Expr::Crash => return Err(()),
 
         Expr::Str(string) => Pattern::StrLiteral(string),
@@ -349,6 +349,7 @@ pub enum EExpr<'a> {
 
     Closure(EClosure<'a>, Position),
     Underscore(Position),
+    TypeHole(Position),
     Crash(Position),
 
     InParens(EInParens<'a>, Position),
@@ -387,6 +388,7 @@ pub enum EString<'a> {
     UnknownEscape(Position),
     Format(&'a EExpr<'a>, Position),
     FormatEnd(Position),
+    InspectEnd(Position),
     MultilineInsufficientIndent(Position),
     ExpectedDoubleQuoteGotSingleQuote(Position),
 }
@@ -589,6 +591,7 @@ pub enum EType<'a> {
     TBadTypeVariable(Position),
     TWildcard(Position),
     TInferred(Position),
+    TTypeHole(Position),
     ///
     TStart(Position),
     TEnd(Position),
@@ -392,6 +392,35 @@ pub fn parse_str_like_literal<'a>() -> impl Parser<'a, StrLikeLiteral<'a>, EStri
                             segment_parsed_bytes = 0;
                             state = new_state;
                         }
+                        Some(b'{') => {
+                            // Advance past the `\{` before using the expr parser
+                            state.advance_mut(2);
+
+                            let original_byte_count = state.bytes().len();
+
+                            // This is an inspected value. Unlike `\(...)`, any expression is
+                            // allowed here; its type only has to implement `Inspect`, which
+                            // is checked during type checking.
+                            let (_progress, loc_expr, new_state) = skip_second!(
+                                specialize_ref(
+                                    EString::Format,
+                                    loc(allocated(reset_min_indent(expr::expr_help())))
+                                ),
+                                word1(b'}', EString::InspectEnd)
+                            )
+                            .parse(arena, state, min_indent)?;
+
+                            // Advance the iterator past the expr we just parsed.
+                            for _ in 0..(original_byte_count - new_state.bytes().len()) {
+                                bytes.next();
+                            }
+
+                            segments.push(StrSegment::Inspected(loc_expr));
+
+                            // Reset the segment
+                            segment_parsed_bytes = 0;
+                            state = new_state;
+                        }
                         Some(b'u') => {
                             // Advance past the `\u` before using the expr parser
                             state.advance_mut(2);
@@ -439,7 +468,7 @@ pub fn parse_str_like_literal<'a>() -> impl Parser<'a, StrLikeLiteral<'a>, EStri
                         }
                         _ => {
                             // Invalid escape! A backslash must be followed
-                            // by either an open paren or else one of the
+                            // by either an open paren or brace or else one of the
                             // escapable characters (\n, \t, \", \\, etc)
                             return Err((MadeProgress, EString::UnknownEscape(state.pos())));
                         }
@@ -114,6 +114,7 @@ fn term<'a>(stop_at_surface_has: bool) -> impl Parser<'a, Loc<TypeAnnotation<'a>
             one_of!(
                 loc_wildcard(),
                 loc_inferred(),
+                loc_type_hole(),
                 specialize(EType::TInParens, loc_type_in_parens(stop_at_surface_has)),
                 loc!(specialize(EType::TRecord, record_type(stop_at_surface_has))),
                 loc!(specialize(
@@ -195,6 +196,28 @@ fn loc_inferred<'a>() -> impl Parser<'a, Loc<TypeAnnotation<'a>>, EType<'a>> {
     }
 }
 
+/// A named type hole, e.g. the `?elem` in (List ?elem)
+fn loc_type_hole<'a>() -> impl Parser<'a, Loc<TypeAnnotation<'a>>, EType<'a>> {
+    move |arena, state: State<'a>, min_indent: u32| {
+        let start = state.pos();
+
+        let (_, _, next_state) = word1(b'?', EType::TTypeHole).parse(arena, state, min_indent)?;
+
+        let (_, name, final_state) =
+            specialize(move |_, _| EType::TTypeHole(start), lowercase_ident())
+                .parse(arena, next_state, min_indent)
+                .map_err(|(_, fail)| (NoProgress, fail))?;
+
+        let region = Region::new(start, final_state.pos());
+
+        Ok((
+            MadeProgress,
+            Loc::at(region, TypeAnnotation::TypeHole(name)),
+            final_state,
+        ))
+    }
+}
+
 fn loc_applied_arg<'a>(
     stop_at_surface_has: bool,
 ) -> impl Parser<'a, Loc<TypeAnnotation<'a>>, EType<'a>> {
@@ -204,6 +227,7 @@ fn loc_applied_arg<'a>(
             one_of!(
                 loc_wildcard(),
                 loc_inferred(),
+                loc_type_hole(),
                 specialize(EType::TInParens, loc_type_in_parens(stop_at_surface_has)),
                 loc!(specialize(EType::TRecord, record_type(stop_at_surface_has))),
                 loc!(specialize(
@@ -444,6 +468,11 @@ fn ability_chain<'a>() -> impl Parser<'a, Vec<'a, Loc<TypeAnnotation<'a>>>, ETyp
     )
 }
 
+/// Parse the name of an ability, e.g. `Hash`, or `Hash.Hash` if it is qualified.
+pub fn ability_name<'a>() -> impl Parser<'a, Loc<TypeAnnotation<'a>>, EType<'a>> {
+    specialize(EType::TApply, loc!(concrete_type()))
+}
+
 fn implements_clause<'a>() -> impl Parser<'a, Loc<ImplementsClause<'a>>, EType<'a>> {
     map!(
         // Suppose we are trying to parse "a implements Hash"
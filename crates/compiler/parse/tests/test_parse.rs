@@ -227,6 +227,43 @@ mod test_parse {
         });
     }
 
+    #[test]
+    fn string_with_inspected_value() {
+        assert_segments(r#""At \{point}!""#, |arena| {
+            let expr = arena.alloc(Var {
+                module_name: "",
+                ident: "point",
+            });
+
+            bumpalo::vec![in arena;
+                 Plaintext("At "),
+                 Inspected(Loc::new(6, 11, expr)),
+                 Plaintext("!")
+            ]
+        });
+    }
+
+    #[test]
+    fn string_with_interpolation_and_inspected_value() {
+        assert_segments(r#""\(name) is at \{point}""#, |arena| {
+            let expr1 = arena.alloc(Var {
+                module_name: "",
+                ident: "name",
+            });
+
+            let expr2 = arena.alloc(Var {
+                module_name: "",
+                ident: "point",
+            });
+
+            bumpalo::vec![in arena;
+                 Interpolated(Loc::new(3, 7, expr1)),
+                 Plaintext(" is at "),
+                 Inspected(Loc::new(17, 22, expr2))
+            ]
+        });
+    }
+
     #[test]
     fn empty_source_file() {
         assert_parsing_fails("", SyntaxError::Eof(Region::zero()));
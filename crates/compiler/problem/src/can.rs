@@ -4,6 +4,7 @@ use std::path::PathBuf;
 use roc_collections::all::MutSet;
 use roc_module::called_via::BinOp;
 use roc_module::ident::{Ident, Lowercase, ModuleName, TagName};
+use roc_module::low_level::LowLevel;
 use roc_module::symbol::{ModuleId, Symbol};
 use roc_parse::ast::Base;
 use roc_parse::pattern::PatternType;
@@ -45,6 +46,15 @@ pub enum Problem {
     /// Second symbol is the name of the argument that is unused
     UnusedArgument(Symbol, bool, Symbol, Region),
     UnusedBranchDef(Symbol, Region),
+    /// A field of a record bound to `record` that is never read; every use of `record` is an
+    /// access of one of its other fields.
+    UnusedRecordField {
+        record: Symbol,
+        field: Lowercase,
+        region: Region,
+    },
+    /// A value exposed by one of an app's own modules that no other module uses.
+    UnusedExport(Symbol, Region),
     DefsOnlyUsedInRecursion(usize, Region),
     PrecedenceProblem(PrecedenceProblem),
     // Example: (5 = 1 + 2) is an unsupported pattern in an assignment; Int patterns aren't allowed in assignments!
@@ -178,6 +188,20 @@ pub enum Problem {
         ability: Symbol,
         not_implemented: Vec<Symbol>,
     },
+    DuplicateAbilityImplementation {
+        ability: Symbol,
+        original: Region,
+        duplicate: Region,
+    },
+    AbilityRequiresItself {
+        ability: Symbol,
+        region: Region,
+    },
+    AbilityMemberDefaultNotSpecializable {
+        member: Symbol,
+        ability: Symbol,
+        region: Region,
+    },
     NotBoundInAllPatterns {
         unbound_symbol: Symbol,
         region: Region,
@@ -207,6 +231,12 @@ pub enum Problem {
     OverAppliedCrash {
         region: Region,
     },
+    /// An arithmetic operation on constants that is known to overflow at compile time.
+    /// Reported after monomorphization, when constant folding is enabled.
+    ConstantOverflow {
+        op: LowLevel,
+        region: Region,
+    },
     FileProblem {
         filename: PathBuf,
         error: io::ErrorKind,
@@ -225,6 +255,8 @@ impl Problem {
             Problem::UnknownGeneratesWith(_) => RuntimeError,
             Problem::UnusedArgument(_, _, _, _) => Warning,
             Problem::UnusedBranchDef(_, _) => Warning,
+            Problem::UnusedRecordField { .. } => Warning,
+            Problem::UnusedExport(_, _) => Warning,
             Problem::PrecedenceProblem(_) => RuntimeError,
             Problem::UnsupportedPattern(_, _) => RuntimeError,
             Problem::Shadowing { .. } => RuntimeError,
@@ -263,6 +295,9 @@ impl Problem {
             Problem::NotAnAbility(_) => Warning,
             Problem::ImplementsNonRequired { .. } => Warning,
             Problem::DoesNotImplementAbility { .. } => RuntimeError,
+            Problem::DuplicateAbilityImplementation { .. } => Warning, // First impl is used at runtime
+            Problem::AbilityRequiresItself { .. } => Warning,
+            Problem::AbilityMemberDefaultNotSpecializable { .. } => RuntimeError,
             Problem::NotBoundInAllPatterns { .. } => RuntimeError,
             Problem::NoIdentifiersIntroduced(_) => Warning,
             Problem::OverloadedSpecialization { .. } => Warning, // Ideally, will compile
@@ -275,6 +310,7 @@ impl Problem {
             // injecting a crash message
             Problem::UnappliedCrash { .. } => RuntimeError,
             Problem::OverAppliedCrash { .. } => RuntimeError,
+            Problem::ConstantOverflow { .. } => RuntimeError,
             Problem::DefsOnlyUsedInRecursion(_, _) => Warning,
             Problem::FileProblem { .. } => Fatal,
         }
@@ -298,6 +334,8 @@ impl Problem {
             | Problem::UnknownGeneratesWith(Loc { region, .. })
             | Problem::UnusedArgument(_, _, _, region)
             | Problem::UnusedBranchDef(_, region)
+            | Problem::UnusedRecordField { region, .. }
+            | Problem::UnusedExport(_, region)
             | Problem::PrecedenceProblem(PrecedenceProblem::BothNonAssociative(region, _, _))
             | Problem::UnsupportedPattern(_, region)
             | Problem::CyclicAlias(_, region, _, _)
@@ -402,6 +440,11 @@ impl Problem {
             | Problem::NotAnAbility(region)
             | Problem::ImplementsNonRequired { region, .. }
             | Problem::DoesNotImplementAbility { region, .. }
+            | Problem::DuplicateAbilityImplementation {
+                duplicate: region, ..
+            }
+            | Problem::AbilityRequiresItself { region, .. }
+            | Problem::AbilityMemberDefaultNotSpecializable { region, .. }
             | Problem::NoIdentifiersIntroduced(region)
             | Problem::OverloadedSpecialization {
                 overload: region, ..
@@ -416,6 +459,7 @@ impl Problem {
             | Problem::UnnecessaryOutputWildcard { region }
             | Problem::OverAppliedCrash { region }
             | Problem::UnappliedCrash { region }
+            | Problem::ConstantOverflow { region, .. }
             | Problem::DefsOnlyUsedInRecursion(_, region) => Some(*region),
             Problem::RuntimeError(RuntimeError::CircularDef(cycle_entries))
             | Problem::BadRecursion(cycle_entries) => {
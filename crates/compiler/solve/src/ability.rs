@@ -10,8 +10,8 @@ use roc_error_macros::internal_error;
 use roc_module::symbol::{ModuleId, Symbol};
 use roc_region::all::{Loc, Region};
 use roc_solve_problem::{
-    NotDerivableContext, NotDerivableDecode, NotDerivableEncode, NotDerivableEq, TypeError,
-    UnderivableReason, Unfulfilled,
+    NotDerivableContext, NotDerivableDecode, NotDerivableEncode, NotDerivableEq, NotDerivableSort,
+    TypeError, UnderivableReason, Unfulfilled,
 };
 use roc_solve_schema::UnificationMode;
 use roc_types::num::NumericRange;
@@ -113,6 +113,8 @@ type ObligationResult = Result<(), Unfulfilled>;
 pub struct ObligationCache {
     impl_cache: VecMap<ImplKey, ObligationResult>,
     derive_cache: VecMap<RequestedDeriveKey, ObligationResult>,
+    /// Implementations some type in the module actually required of an opaque type.
+    demanded: VecSet<ImplKey>,
 }
 
 enum ReadCache {
@@ -157,6 +159,81 @@ impl ObligationCache {
         }
     }
 
+    /// Checks that every opaque type defined in `home` implements the abilities required by the
+    /// abilities it claims to implement.
+    #[must_use]
+    pub fn check_parent_abilities(
+        &mut self,
+        abilities_store: &AbilitiesStore,
+        home: ModuleId,
+    ) -> Vec<TypeError> {
+        let mut impl_keys = VecSet::default();
+        for (key, _) in abilities_store.iter_declared_implementations() {
+            if key.opaque.module_id() != home {
+                continue;
+            }
+            if let Some(member_data) = abilities_store.member_def(key.ability_member) {
+                impl_keys.insert(ImplKey {
+                    opaque: key.opaque,
+                    ability: member_data.parent_ability,
+                });
+            }
+        }
+
+        let mut problems = vec![];
+        for impl_key in impl_keys.into_iter() {
+            self.check_impl(abilities_store, impl_key);
+
+            // Anything else will be reported where the obligation comes up.
+            if let Some(Err(unfulfilled @ Unfulfilled::OpaqueDoesNotImplementParent { .. })) =
+                self.impl_cache.get(&impl_key)
+            {
+                problems.push(TypeError::UnfulfilledAbility(unfulfilled.clone()));
+            }
+        }
+
+        problems
+    }
+
+    /// Returns the `(opaque, ability)` pairs of abilities that opaque types defined in `home`
+    /// declare to implement, but that nothing required of them while solving the module. An
+    /// ability is required whenever an ability that requires it is.
+    pub fn undemanded_implementations(
+        &self,
+        abilities_store: &AbilitiesStore,
+        home: ModuleId,
+    ) -> Vec<(Symbol, Symbol)> {
+        let mut demanded = VecSet::default();
+        for &impl_key in self.demanded.iter() {
+            demanded.insert(impl_key);
+            for ancestor in abilities_store.ancestors_of_ability(impl_key.ability) {
+                demanded.insert(ImplKey {
+                    opaque: impl_key.opaque,
+                    ability: ancestor,
+                });
+            }
+        }
+
+        let mut declared = VecSet::default();
+        for (key, _) in abilities_store.iter_declared_implementations() {
+            if key.opaque.module_id() != home {
+                continue;
+            }
+            if let Some(member_data) = abilities_store.member_def(key.ability_member) {
+                declared.insert(ImplKey {
+                    opaque: key.opaque,
+                    ability: member_data.parent_ability,
+                });
+            }
+        }
+
+        declared
+            .into_iter()
+            .filter(|impl_key| !demanded.contains(impl_key))
+            .map(|ImplKey { opaque, ability }| (opaque, ability))
+            .collect()
+    }
+
     #[must_use]
     pub fn check_obligations(
         &mut self,
@@ -167,6 +244,15 @@ impl ObligationCache {
     ) -> Vec<TypeError> {
         let must_implement = must_implement.get_unique();
 
+        for mia in must_implement.iter() {
+            if let Obligated::Opaque(opaque) = mia.typ {
+                self.demanded.insert(ImplKey {
+                    opaque,
+                    ability: mia.ability,
+                });
+            }
+        }
+
         let mut get_unfulfilled = |must_implement: &[MustImplementAbility]| {
             must_implement
                 .iter()
@@ -304,6 +390,17 @@ impl ObligationCache {
 
             Symbol::BOOL_EQ => Some(DeriveEq::is_derivable(self, abilities_store, subs, var)),
 
+            Symbol::SORT_SORT_ABILITY => {
+                Some(DeriveSort::is_derivable(self, abilities_store, subs, var))
+            }
+
+            Symbol::INSPECT_INSPECT_ABILITY => Some(DeriveInspect::is_derivable(
+                self,
+                abilities_store,
+                subs,
+                var,
+            )),
+
             _ => None,
         };
 
@@ -351,6 +448,7 @@ impl ObligationCache {
     ) -> ReadCache {
         let impl_key = ImplKey { opaque, ability };
 
+        self.demanded.insert(impl_key);
         self.check_impl(abilities_store, impl_key);
         ReadCache::Impl
     }
@@ -382,19 +480,45 @@ impl ObligationCache {
             DeriveDecoding::ABILITY => DeriveDecoding::is_derivable_builtin_opaque(opaque),
             DeriveEq::ABILITY => DeriveEq::is_derivable_builtin_opaque(opaque),
             DeriveHash::ABILITY => DeriveHash::is_derivable_builtin_opaque(opaque),
+            DeriveSort::ABILITY => DeriveSort::is_derivable_builtin_opaque(opaque),
+            DeriveInspect::ABILITY => DeriveInspect::is_derivable_builtin_opaque(opaque),
             _ => false,
         };
 
         let has_declared_impl = has_declared_impl || builtin_opaque_impl_ok();
 
-        let obligation_result = if !has_declared_impl {
-            Err(Unfulfilled::OpaqueDoesNotImplement {
-                typ: opaque,
-                ability,
-            })
-        } else {
-            Ok(())
-        };
+        if !has_declared_impl {
+            self.impl_cache.insert(
+                impl_key,
+                Err(Unfulfilled::OpaqueDoesNotImplement {
+                    typ: opaque,
+                    ability,
+                }),
+            );
+            return;
+        }
+
+        // The opaque must also implement every ability this one requires. Abilities may require
+        // each other, so assume the best while we check the parents.
+        self.impl_cache.insert(impl_key, Ok(()));
+
+        let mut obligation_result = Ok(());
+        for &parent in abilities_store.parents_of_ability(ability) {
+            let parent_key = ImplKey {
+                opaque,
+                ability: parent,
+            };
+            self.check_impl(abilities_store, parent_key);
+
+            if self.impl_cache.get(&parent_key).unwrap().is_err() {
+                obligation_result = Err(Unfulfilled::OpaqueDoesNotImplementParent {
+                    typ: opaque,
+                    ability,
+                    parent,
+                });
+                break;
+            }
+        }
 
         self.impl_cache.insert(impl_key, obligation_result);
     }
@@ -1320,6 +1444,243 @@ impl DerivableVisitor for DeriveEq {
     }
 }
 
+struct DeriveSort;
+impl DerivableVisitor for DeriveSort {
+    const ABILITY: Symbol = Symbol::SORT_SORT_ABILITY;
+    const ABILITY_SLICE: SubsSlice<Symbol> = Subs::AB_SORT;
+
+    #[inline(always)]
+    fn is_derivable_builtin_opaque(symbol: Symbol) -> bool {
+        is_builtin_fixed_int_alias(symbol)
+            || is_builtin_nat_alias(symbol)
+            || is_builtin_dec_alias(symbol)
+            || is_builtin_bool_alias(symbol)
+    }
+
+    #[inline(always)]
+    fn visit_recursion(_var: Variable) -> Result<Descend, NotDerivable> {
+        Ok(Descend(true))
+    }
+
+    #[inline(always)]
+    fn visit_apply(var: Variable, symbol: Symbol) -> Result<Descend, NotDerivable> {
+        // Sets and dictionaries have no meaningful order of their elements, so they cannot be
+        // compared lexicographically like lists and strings are.
+        if matches!(symbol, Symbol::LIST_LIST | Symbol::STR_STR) {
+            Ok(Descend(true))
+        } else {
+            Err(NotDerivable {
+                var,
+                context: NotDerivableContext::NoContext,
+            })
+        }
+    }
+
+    #[inline(always)]
+    fn visit_record(
+        subs: &Subs,
+        var: Variable,
+        fields: RecordFields,
+    ) -> Result<Descend, NotDerivable> {
+        for (field_name, _, field) in fields.iter_all() {
+            if subs[field].is_optional() {
+                return Err(NotDerivable {
+                    var,
+                    context: NotDerivableContext::Decode(NotDerivableDecode::OptionalRecordField(
+                        subs[field_name].clone(),
+                    )),
+                });
+            }
+        }
+
+        Ok(Descend(true))
+    }
+
+    #[inline(always)]
+    fn visit_tuple(
+        _subs: &Subs,
+        _var: Variable,
+        _elems: TupleElems,
+    ) -> Result<Descend, NotDerivable> {
+        Ok(Descend(true))
+    }
+
+    #[inline(always)]
+    fn visit_tag_union(_var: Variable) -> Result<Descend, NotDerivable> {
+        Ok(Descend(true))
+    }
+
+    #[inline(always)]
+    fn visit_recursive_tag_union(_var: Variable) -> Result<Descend, NotDerivable> {
+        Ok(Descend(true))
+    }
+
+    #[inline(always)]
+    fn visit_function_or_tag_union(_var: Variable) -> Result<Descend, NotDerivable> {
+        Ok(Descend(true))
+    }
+
+    #[inline(always)]
+    fn visit_empty_record(_var: Variable) -> Result<(), NotDerivable> {
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn visit_empty_tag_union(_var: Variable) -> Result<(), NotDerivable> {
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn visit_alias(var: Variable, symbol: Symbol) -> Result<Descend, NotDerivable> {
+        if is_builtin_float_alias(symbol) {
+            Err(NotDerivable {
+                var,
+                context: NotDerivableContext::Sort(NotDerivableSort::FloatingPoint),
+            })
+        } else if is_builtin_number_alias(symbol) {
+            Ok(Descend(false))
+        } else {
+            Ok(Descend(true))
+        }
+    }
+
+    fn visit_floating_point_content(
+        var: Variable,
+        subs: &mut Subs,
+        content_var: Variable,
+    ) -> Result<Descend, NotDerivable> {
+        use roc_unify::unify::unify;
+
+        // Of the floating-point types,
+        // only Dec has a total order.
+        // TODO(checkmate): pass checkmate through
+        let unified = unify(
+            &mut with_checkmate!({
+                on => UEnv::new(subs, None),
+                off => UEnv::new(subs),
+            }),
+            content_var,
+            Variable::DECIMAL,
+            UnificationMode::EQ,
+            Polarity::Pos,
+        );
+        match unified {
+            roc_unify::unify::Unified::Success { .. } => Ok(Descend(false)),
+            roc_unify::unify::Unified::Failure(..) => Err(NotDerivable {
+                var,
+                context: NotDerivableContext::Sort(NotDerivableSort::FloatingPoint),
+            }),
+        }
+    }
+
+    #[inline(always)]
+    fn visit_ranged_number(_var: Variable, _range: NumericRange) -> Result<(), NotDerivable> {
+        // Ranged numbers are allowed for the same reason as in `DeriveEq`: if something were to
+        // be a float, we would see it exactly as a float.
+        Ok(())
+    }
+}
+
+struct DeriveInspect;
+impl DerivableVisitor for DeriveInspect {
+    const ABILITY: Symbol = Symbol::INSPECT_INSPECT_ABILITY;
+    const ABILITY_SLICE: SubsSlice<Symbol> = Subs::AB_INSPECT;
+
+    #[inline(always)]
+    fn is_derivable_builtin_opaque(symbol: Symbol) -> bool {
+        is_builtin_number_alias(symbol) || is_builtin_bool_alias(symbol)
+    }
+
+    #[inline(always)]
+    fn visit_recursion(_var: Variable) -> Result<Descend, NotDerivable> {
+        Ok(Descend(true))
+    }
+
+    #[inline(always)]
+    fn visit_apply(var: Variable, symbol: Symbol) -> Result<Descend, NotDerivable> {
+        if matches!(symbol, Symbol::LIST_LIST | Symbol::STR_STR) {
+            Ok(Descend(true))
+        } else {
+            Err(NotDerivable {
+                var,
+                context: NotDerivableContext::NoContext,
+            })
+        }
+    }
+
+    #[inline(always)]
+    fn visit_record(
+        _subs: &Subs,
+        _var: Variable,
+        _fields: RecordFields,
+    ) -> Result<Descend, NotDerivable> {
+        Ok(Descend(true))
+    }
+
+    #[inline(always)]
+    fn visit_tuple(
+        _subs: &Subs,
+        _var: Variable,
+        _elems: TupleElems,
+    ) -> Result<Descend, NotDerivable> {
+        Ok(Descend(true))
+    }
+
+    #[inline(always)]
+    fn visit_tag_union(_var: Variable) -> Result<Descend, NotDerivable> {
+        Ok(Descend(true))
+    }
+
+    #[inline(always)]
+    fn visit_recursive_tag_union(_var: Variable) -> Result<Descend, NotDerivable> {
+        Ok(Descend(true))
+    }
+
+    #[inline(always)]
+    fn visit_function_or_tag_union(_var: Variable) -> Result<Descend, NotDerivable> {
+        Ok(Descend(true))
+    }
+
+    #[inline(always)]
+    fn visit_empty_record(_var: Variable) -> Result<(), NotDerivable> {
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn visit_empty_tuple(_var: Variable) -> Result<(), NotDerivable> {
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn visit_empty_tag_union(_var: Variable) -> Result<(), NotDerivable> {
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn visit_alias(_var: Variable, symbol: Symbol) -> Result<Descend, NotDerivable> {
+        // Every builtin number, including `Nat`, can be rendered.
+        if is_builtin_number_alias(symbol) {
+            Ok(Descend(false))
+        } else {
+            Ok(Descend(true))
+        }
+    }
+
+    #[inline(always)]
+    fn visit_ranged_number(_var: Variable, _range: NumericRange) -> Result<(), NotDerivable> {
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn visit_floating_point_content(
+        _var: Variable,
+        _subs: &mut Subs,
+        _content_var: Variable,
+    ) -> Result<Descend, NotDerivable> {
+        Ok(Descend(false))
+    }
+}
+
 /// Determines what type implements an ability member of a specialized signature, given the
 /// [MustImplementAbility] constraints of the signature.
 pub fn type_implementing_specialization(
@@ -87,6 +87,8 @@ pub struct SolveOutput {
     pub scope: solve::Scope,
     pub errors: Vec<TypeError>,
     pub resolved_abilities_store: AbilitiesStore,
+    /// `(opaque, ability)` implementations declared in this module that nothing required.
+    pub undemanded_implementations: Vec<(Symbol, Symbol)>,
 
     #[cfg(debug_assertions)]
     pub checkmate: Option<roc_checkmate::Collector>,
@@ -119,6 +121,7 @@ pub fn run_solve(
     let RunSolveOutput {
         solved,
         scope,
+        undemanded_implementations,
         #[cfg(debug_assertions)]
         checkmate,
     } = solve::run(
@@ -134,6 +137,7 @@ pub fn run_solve(
         scope,
         errors: problems,
         resolved_abilities_store: abilities_store,
+        undemanded_implementations,
         #[cfg(debug_assertions)]
         checkmate,
     }
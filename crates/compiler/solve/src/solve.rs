@@ -16,6 +16,7 @@ use roc_can::abilities::{AbilitiesStore, MemberSpecializationInfo};
 use roc_can::constraint::Constraint::{self, *};
 use roc_can::constraint::{Cycle, LetConstraint, OpportunisticResolve};
 use roc_can::expected::{Expected, PExpected};
+use roc_collections::soa::Index;
 use roc_debug_flags::dbg_do;
 #[cfg(debug_assertions)]
 use roc_debug_flags::ROC_VERIFY_RIGID_LET_GENERALIZED;
@@ -97,6 +98,8 @@ struct State {
 pub struct RunSolveOutput {
     pub solved: Solved<Subs>,
     pub scope: Scope,
+    /// `(opaque, ability)` implementations declared in this module that nothing required.
+    pub undemanded_implementations: Vec<(Symbol, Symbol)>,
 
     #[cfg(debug_assertions)]
     pub checkmate: Option<roc_checkmate::Collector>,
@@ -121,7 +124,7 @@ fn run_help(
 ) -> RunSolveOutput {
     let subs = &mut owned_subs;
     let SolveConfig {
-        home: _,
+        home,
         constraints,
         root_constraint,
         mut types,
@@ -174,6 +177,7 @@ fn run_help(
         problems: derives_problems,
     } = obligation_cache.check_derives(env.subs, abilities_store, pending_derives);
     problems.extend(derives_problems);
+    problems.extend(obligation_cache.check_parent_abilities(abilities_store, home));
 
     let state = solve(
         &mut env,
@@ -188,8 +192,12 @@ fn run_help(
         &mut awaiting_specializations,
     );
 
+    let undemanded_implementations =
+        obligation_cache.undemanded_implementations(abilities_store, home);
+
     RunSolveOutput {
         scope: state.scope,
+        undemanded_implementations,
         #[cfg(debug_assertions)]
         checkmate: env.checkmate,
         solved: Solved(owned_subs),
@@ -252,6 +260,7 @@ fn solve(
     };
 
     let mut stack = vec![initial];
+    let mut type_holes = Vec::new();
 
     while let Some(work_item) = stack.pop() {
         let (scope, rank, constraint) = match work_item {
@@ -1392,12 +1401,106 @@ fn solve(
                     }
                 }
             }
+            TypeHole(type_index, hole_index) => {
+                let actual = either_type_index_to_var(
+                    env,
+                    rank,
+                    problems,
+                    abilities_store,
+                    obligation_cache,
+                    &mut can_types,
+                    aliases,
+                    *type_index,
+                );
+
+                let hole = &env.constraints.type_holes[hole_index.index()];
+                let candidates = if hole.suggest_values {
+                    Some(scope.vars_by_symbol().collect())
+                } else {
+                    None
+                };
+
+                // The hole's type may still be refined by constraints we haven't solved yet, so
+                // it is only reported once solving is done.
+                type_holes.push(PendingTypeHole {
+                    var: actual,
+                    hole: *hole_index,
+                    candidates,
+                });
+
+                state
+            }
         };
     }
 
+    report_type_holes(env, problems, type_holes);
+
     state
 }
 
+struct PendingTypeHole {
+    var: Variable,
+    hole: Index<roc_can::constraint::TypeHole>,
+    /// The values in scope at the hole, if we should look for ones that fit it.
+    candidates: Option<Vec<(Symbol, Variable)>>,
+}
+
+/// Report the inferred type of each type hole, along with the values that were in scope at the
+/// hole and whose type unifies with it.
+fn report_type_holes(
+    env: &mut InferenceEnv,
+    problems: &mut Vec<TypeError>,
+    type_holes: Vec<PendingTypeHole>,
+) {
+    for PendingTypeHole {
+        var,
+        hole: hole_index,
+        candidates,
+    } in type_holes
+    {
+        let hole = &env.constraints.type_holes[hole_index.index()];
+        let (name, region) = (hole.name.clone(), hole.region);
+
+        let fits = candidates.map(|candidates| {
+            // Values bound closest to the hole come first.
+            candidates
+                .into_iter()
+                .rev()
+                .filter(|(_, candidate_var)| fits_type_hole(env, var, *candidate_var))
+                .map(|(symbol, _)| symbol)
+                .collect()
+        });
+
+        let typ = env.subs.var_to_error_type(var, Polarity::OF_VALUE);
+
+        problems.push(TypeError::TypeHole {
+            region,
+            name,
+            typ,
+            fits,
+        });
+    }
+}
+
+fn fits_type_hole(env: &mut InferenceEnv, hole_var: Variable, candidate_var: Variable) -> bool {
+    if let Content::Error = env.subs.get_content_without_compacting(candidate_var) {
+        // Erroneous values unify with anything, so they'd fit every hole.
+        return false;
+    }
+
+    let snapshot = env.subs.snapshot();
+    let unified = unify(
+        &mut env.uenv(),
+        hole_var,
+        candidate_var,
+        UnificationMode::EQ,
+        Polarity::OF_VALUE,
+    );
+    env.subs.rollback_to(snapshot);
+
+    matches!(unified, Success { .. })
+}
+
 fn chase_alias_content(subs: &Subs, mut var: Variable) -> (Variable, &Content) {
     loop {
         match subs.get_content_without_compacting(var) {
@@ -40,6 +40,7 @@ mod solve_expr {
                 prob,
                 roc_problem::can::Problem::UnusedDef(_, _)
                     | roc_problem::can::Problem::UnusedBranchDef(..)
+                    | roc_problem::can::Problem::UnusedRecordField { .. }
             )
         });
 
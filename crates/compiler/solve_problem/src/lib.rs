@@ -33,6 +33,19 @@ pub enum TypeError {
     },
     IngestedFileBadUtf8(Box<PathBuf>, Utf8Error),
     IngestedFileUnsupportedType(Box<PathBuf>, ErrorType),
+    TypeHole {
+        region: Region,
+        name: Option<Lowercase>,
+        typ: ErrorType,
+        /// Values in scope whose type fits the hole; `None` for holes in type annotations.
+        fits: Option<Vec<Symbol>>,
+    },
+    /// An opaque type implements an ability, but nothing ever uses that implementation.
+    UnusedImplementation {
+        region: Region,
+        opaque: Symbol,
+        ability: Symbol,
+    },
 }
 
 impl TypeError {
@@ -54,6 +67,8 @@ impl TypeError {
             TypeError::WrongSpecialization { .. } => RuntimeError,
             TypeError::IngestedFileBadUtf8(..) => Fatal,
             TypeError::IngestedFileUnsupportedType(..) => Fatal,
+            TypeError::TypeHole { .. } => RuntimeError,
+            TypeError::UnusedImplementation { .. } => Warning,
         }
     }
 
@@ -66,6 +81,8 @@ impl TypeError {
             | TypeError::BadExprMissingAbility(region, ..)
             | TypeError::StructuralSpecialization { region, .. }
             | TypeError::WrongSpecialization { region, .. }
+            | TypeError::TypeHole { region, .. }
+            | TypeError::UnusedImplementation { region, .. }
             | TypeError::BadPatternMissingAbility(region, ..) => Some(*region),
             TypeError::UnfulfilledAbility(ab, ..) => ab.region(),
             TypeError::Exhaustive(e) => Some(e.region()),
@@ -80,6 +97,12 @@ impl TypeError {
 pub enum Unfulfilled {
     /// No claimed implementation of an ability for an opaque type.
     OpaqueDoesNotImplement { typ: Symbol, ability: Symbol },
+    /// An opaque type implements an ability, but not an ability that it requires.
+    OpaqueDoesNotImplementParent {
+        typ: Symbol,
+        ability: Symbol,
+        parent: Symbol,
+    },
     /// Cannot derive implementation of an ability for a structural type.
     AdhocUnderivable {
         typ: ErrorType,
@@ -100,6 +123,7 @@ impl Unfulfilled {
     fn region(&self) -> Option<Region> {
         match self {
             Unfulfilled::OpaqueDoesNotImplement { .. } => None,
+            Unfulfilled::OpaqueDoesNotImplementParent { .. } => None,
             Unfulfilled::AdhocUnderivable { .. } => None,
             Unfulfilled::OpaqueUnderivable { derive_region, .. } => Some(*derive_region),
         }
@@ -124,6 +148,7 @@ pub enum NotDerivableContext {
     Encode(NotDerivableEncode),
     Decode(NotDerivableDecode),
     Eq(NotDerivableEq),
+    Sort(NotDerivableSort),
 }
 
 #[derive(PartialEq, Eq, Debug, Clone)]
@@ -141,3 +166,8 @@ pub enum NotDerivableDecode {
 pub enum NotDerivableEq {
     FloatingPoint,
 }
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum NotDerivableSort {
+    FloatingPoint,
+}
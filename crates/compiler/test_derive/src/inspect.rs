@@ -0,0 +1,115 @@
+#![cfg(test)]
+// Even with #[allow(non_snake_case)] on individual idents, rust-analyzer issues diagnostics.
+// See https://github.com/rust-lang/rust-analyzer/issues/6541.
+// For the `v!` macro we use uppercase variables when constructing tag unions.
+#![allow(non_snake_case)]
+
+use crate::{
+    test_key_eq, test_key_neq,
+    util::{check_derivable, check_immediate},
+    v,
+};
+use roc_module::symbol::Symbol;
+use roc_types::subs::Variable;
+
+use roc_derive_key::{inspect::FlatInspectableKey, DeriveBuiltin::ToInspector, DeriveKey};
+
+test_key_eq! {
+    ToInspector,
+
+    same_record:
+        v!({ a: v!(U8), }), v!({ a: v!(U8), })
+    same_record_fields_diff_types:
+        v!({ a: v!(U8), }), v!({ a: v!(STR), })
+    same_record_fields_any_order:
+        v!({ a: v!(U8), b: v!(U8), c: v!(U8), }),
+        v!({ c: v!(U8), a: v!(U8), b: v!(U8), })
+    explicit_empty_record_and_implicit_empty_record:
+        v!(EMPTY_RECORD), v!({})
+    same_record_fields_required_vs_optional:
+        v!({ a: v!(U8), b: v!(U8), }),
+        v!({ ?a: v!(U8), ?b: v!(U8), })
+
+    same_tuple:
+        v!((v!(U8), v!(U16),)), v!((v!(U8), v!(U16),))
+    same_tuple_fields_diff_types:
+        v!((v!(U8), v!(U16),)), v!((v!(U32), v!(U64),))
+
+    same_tag_union:
+        v!([ A v!(U8) v!(STR), B v!(STR) ]), v!([ A v!(U8) v!(STR), B v!(STR) ])
+    same_tag_union_tags_diff_types:
+        v!([ A v!(U8) v!(U8), B v!(U8) ]), v!([ A v!(STR) v!(STR), B v!(STR) ])
+    same_tag_union_tags_any_order:
+        v!([ A v!(U8) v!(U8), B v!(U8), C ]), v!([ C, B v!(STR), A v!(STR) v!(STR) ])
+    explicit_empty_tag_union_and_implicit_empty_tag_union:
+        v!(EMPTY_TAG_UNION), v!([])
+
+    same_recursive_tag_union:
+        v!([ Nil, Cons v!(^lst)] as lst), v!([ Nil, Cons v!(^lst)] as lst)
+    same_tag_union_and_recursive_tag_union_fields:
+        v!([ Nil, Cons v!(STR)]), v!([ Nil, Cons v!(^lst)] as lst)
+}
+
+test_key_neq! {
+    ToInspector,
+
+    different_record_fields:
+        v!({ a: v!(U8), }), v!({ b: v!(U8), })
+    record_empty_vs_nonempty:
+        v!(EMPTY_RECORD), v!({ a: v!(U8), })
+
+    different_tuple_arities:
+        v!((v!(U8), v!(U16),)), v!((v!(U8), v!(U16), v!(U32),))
+
+    different_tag_union_tags:
+        v!([ A v!(U8) ]), v!([ B v!(U8) ])
+    tag_union_empty_vs_nonempty:
+        v!(EMPTY_TAG_UNION), v!([ B v!(U8) ])
+    different_recursive_tag_union_tags:
+        v!([ Nil, Cons v!(^lst) ] as lst), v!([ Nil, Next v!(^lst) ] as lst)
+}
+
+#[test]
+fn immediates() {
+    check_immediate(ToInspector, v!(U8), Symbol::INSPECT_U8);
+    check_immediate(ToInspector, v!(U16), Symbol::INSPECT_U16);
+    check_immediate(ToInspector, v!(U32), Symbol::INSPECT_U32);
+    check_immediate(ToInspector, v!(U64), Symbol::INSPECT_U64);
+    check_immediate(ToInspector, v!(U128), Symbol::INSPECT_U128);
+    check_immediate(ToInspector, v!(I8), Symbol::INSPECT_I8);
+    check_immediate(ToInspector, v!(I16), Symbol::INSPECT_I16);
+    check_immediate(ToInspector, v!(I32), Symbol::INSPECT_I32);
+    check_immediate(ToInspector, v!(I64), Symbol::INSPECT_I64);
+    check_immediate(ToInspector, v!(I128), Symbol::INSPECT_I128);
+    check_immediate(ToInspector, v!(DEC), Symbol::INSPECT_DEC);
+    check_immediate(ToInspector, v!(F32), Symbol::INSPECT_F32);
+    check_immediate(ToInspector, v!(F64), Symbol::INSPECT_F64);
+    check_immediate(ToInspector, v!(NAT), Symbol::INSPECT_INSPECT_NAT);
+    check_immediate(ToInspector, v!(STR), Symbol::INSPECT_STR);
+    check_immediate(
+        ToInspector,
+        v!(Symbol::LIST_LIST v!(U8)),
+        Symbol::INSPECT_INSPECT_LIST,
+    );
+}
+
+#[test]
+fn derivable_record_with_record_ext() {
+    check_derivable(
+        ToInspector,
+        v!({ b: v!(STR), }{ a: v!(STR), } ),
+        DeriveKey::ToInspector(FlatInspectableKey::Record(vec!["a".into(), "b".into()])),
+    );
+}
+
+#[test]
+fn derivable_tag_with_tag_ext() {
+    check_derivable(
+        ToInspector,
+        v!([ B v!(STR) v!(U8) ][ A v!(STR) ]),
+        DeriveKey::ToInspector(FlatInspectableKey::TagUnion(vec![
+            ("A".into(), 1),
+            ("B".into(), 2),
+        ])),
+    );
+}
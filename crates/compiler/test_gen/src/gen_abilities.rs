@@ -248,6 +248,113 @@ fn bounds_to_multiple_abilities() {
     )
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn member_default_implementation() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            MEq implements
+                mEq : a, a -> Bool where a implements MEq
+                mNotEq : a, a -> Bool where a implements MEq
+                mNotEq = \x, y -> !(mEq x y)
+
+            Id := U64 implements [MEq { mEq: idEq }]
+            idEq = \@Id m, @Id n -> m == n
+
+            main = (mNotEq (@Id 1) (@Id 2), mNotEq (@Id 3) (@Id 3))
+            "#
+        ),
+        (true, false),
+        (bool, bool)
+    )
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn member_default_implementation_overridden() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            Describe implements
+                name : a -> Str where a implements Describe
+                describe : a -> Str where a implements Describe
+                describe = \x -> "a \(name x)"
+
+            Dog := {} implements [Describe { name: dogName }]
+            dogName = \@Dog {} -> "dog"
+
+            Cat := {} implements [Describe { name: catName, describe: catDescribe }]
+            catName = \@Cat {} -> "cat"
+            catDescribe = \@Cat {} -> "the cat"
+
+            main = "\(describe (@Dog {})), \(describe (@Cat {}))"
+            "#
+        ),
+        RocStr::from("a dog, the cat"),
+        RocStr
+    )
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn member_default_implementation_returning_implementor() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            Zero implements
+                zero : {} -> a where a implements Zero
+                alsoZero : {} -> a where a implements Zero
+                alsoZero = \{} -> zero {}
+
+            Count := U64 implements [Zero { zero: zeroCount }]
+            zeroCount = \{} -> @Count 7
+
+            main =
+                @Count n = alsoZero {}
+                n
+            "#
+        ),
+        7,
+        u64
+    )
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn super_ability_members_available() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            Named implements
+                name : a -> Str where a implements Named
+
+            Greet implements Named
+                greeting : a -> Str where a implements Greet
+
+            Person := Str implements [Named { name: personName }, Greet { greeting: personGreeting }]
+            personName = \@Person n -> n
+            personGreeting = \@Person _ -> "hello"
+
+            greet : a -> Str where a implements Greet
+            greet = \x -> "\(greeting x), \(name x)"
+
+            main = greet (@Person "roc")
+            "#
+        ),
+        RocStr::from("hello, roc"),
+        RocStr
+    )
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
 fn encode() {
@@ -2146,6 +2253,338 @@ mod eq {
     }
 }
 
+#[cfg(all(test, any(feature = "gen-llvm", feature = "gen-wasm")))]
+mod sort {
+    #[cfg(feature = "gen-llvm")]
+    use crate::helpers::llvm::assert_evals_to;
+
+    #[cfg(feature = "gen-wasm")]
+    use crate::helpers::wasm::assert_evals_to;
+
+    use indoc::indoc;
+    use roc_std::{RocList, RocStr};
+
+    const ORDERING_TO_STR: &str = indoc!(
+        r#"
+        orderingToStr = \ordering ->
+            when ordering is
+                LT -> "LT"
+                EQ -> "EQ"
+                GT -> "GT"
+        "#
+    );
+
+    #[test]
+    fn compare_immediates() {
+        assert_evals_to!(
+            &format!(
+                indoc!(
+                    r#"
+                    app "test" provides [main] to "./platform"
+
+                    {}
+
+                    main =
+                        [
+                            orderingToStr (Sort.compare 1u8 2u8),
+                            orderingToStr (Sort.compare -5i64 -5i64),
+                            orderingToStr (Sort.compare "b" "a"),
+                            orderingToStr (Sort.compare Bool.false Bool.true),
+                            orderingToStr (Sort.compare [1u8, 2] [1u8, 2, 3]),
+                        ]
+                    "#
+                ),
+                ORDERING_TO_STR,
+            ),
+            RocList::from_slice(&[
+                RocStr::from("LT"),
+                RocStr::from("EQ"),
+                RocStr::from("GT"),
+                RocStr::from("LT"),
+                RocStr::from("LT"),
+            ]),
+            RocList<RocStr>
+        )
+    }
+
+    #[test]
+    fn derived_compare_record() {
+        assert_evals_to!(
+            &format!(
+                indoc!(
+                    r#"
+                    app "test" provides [main] to "./platform"
+
+                    {}
+
+                    main =
+                        [
+                            orderingToStr (Sort.compare {{ a: 1u8, b: "z" }} {{ a: 2u8, b: "a" }}),
+                            orderingToStr (Sort.compare {{ a: 1u8, b: "z" }} {{ a: 1u8, b: "a" }}),
+                            orderingToStr (Sort.compare {{ a: 1u8, b: "z" }} {{ a: 1u8, b: "z" }}),
+                        ]
+                    "#
+                ),
+                ORDERING_TO_STR,
+            ),
+            RocList::from_slice(&[RocStr::from("LT"), RocStr::from("GT"), RocStr::from("EQ"),]),
+            RocList<RocStr>
+        )
+    }
+
+    #[test]
+    fn derived_compare_tuple() {
+        assert_evals_to!(
+            &format!(
+                indoc!(
+                    r#"
+                    app "test" provides [main] to "./platform"
+
+                    {}
+
+                    main =
+                        [
+                            orderingToStr (Sort.compare (1u8, "b") (1u8, "a")),
+                            orderingToStr (Sort.compare (0u8, "b") (1u8, "a")),
+                        ]
+                    "#
+                ),
+                ORDERING_TO_STR,
+            ),
+            RocList::from_slice(&[RocStr::from("GT"), RocStr::from("LT")]),
+            RocList<RocStr>
+        )
+    }
+
+    #[test]
+    fn derived_compare_tag_union() {
+        assert_evals_to!(
+            &format!(
+                indoc!(
+                    r#"
+                    app "test" provides [main] to "./platform"
+
+                    {}
+
+                    Shape : [Circle U8, Rect U8 U8, Dot]
+
+                    cmp : Shape, Shape -> Str
+                    cmp = \a, b -> orderingToStr (Sort.compare a b)
+
+                    main =
+                        [
+                            cmp (Circle 9) (Rect 1 1),
+                            cmp (Rect 2 1) (Rect 1 5),
+                            cmp Dot (Circle 0),
+                            cmp (Rect 1 1) (Rect 1 1),
+                        ]
+                    "#
+                ),
+                ORDERING_TO_STR,
+            ),
+            RocList::from_slice(&[
+                RocStr::from("LT"),
+                RocStr::from("GT"),
+                RocStr::from("GT"),
+                RocStr::from("EQ"),
+            ]),
+            RocList<RocStr>
+        )
+    }
+
+    #[test]
+    fn derived_compare_for_opaque() {
+        assert_evals_to!(
+            indoc!(
+                r#"
+                app "test" provides [main] to "./platform"
+
+                Age := U8 implements [Sort]
+
+                main =
+                    [@Age 30, @Age 4, @Age 17]
+                    |> List.sort
+                    |> List.map \@Age n -> n
+                "#
+            ),
+            RocList::from_slice(&[4u8, 17, 30]),
+            RocList<u8>
+        )
+    }
+
+    #[test]
+    fn derived_compare_for_opaque_tag_union_uses_declaration_order() {
+        // by name, Large < Medium < Small, which is exactly the opposite order
+        assert_evals_to!(
+            &format!(
+                indoc!(
+                    r#"
+                    app "test" provides [main] to "./platform"
+
+                    {}
+
+                    Size := [Small, Medium U8, Large] implements [Sort]
+
+                    cmp : Size, Size -> Str
+                    cmp = \a, b -> orderingToStr (Sort.compare a b)
+
+                    main =
+                        [
+                            cmp (@Size Small) (@Size Large),
+                            cmp (@Size Large) (@Size (Medium 1)),
+                            cmp (@Size (Medium 2)) (@Size (Medium 1)),
+                            cmp (@Size Small) (@Size Small),
+                        ]
+                    "#
+                ),
+                ORDERING_TO_STR,
+            ),
+            RocList::from_slice(&[
+                RocStr::from("LT"),
+                RocStr::from("GT"),
+                RocStr::from("GT"),
+                RocStr::from("EQ"),
+            ]),
+            RocList<RocStr>
+        )
+    }
+
+    #[test]
+    fn list_sort_records() {
+        assert_evals_to!(
+            indoc!(
+                r#"
+                app "test" provides [main] to "./platform"
+
+                main =
+                    [{ name: "b", rank: 2u8 }, { name: "a", rank: 2u8 }, { name: "c", rank: 1u8 }]
+                    |> List.sort
+                    |> List.map .name
+                "#
+            ),
+            RocList::from_slice(&[RocStr::from("a"), RocStr::from("b"), RocStr::from("c")]),
+            RocList<RocStr>
+        )
+    }
+}
+
+#[cfg(all(test, any(feature = "gen-llvm", feature = "gen-wasm")))]
+mod inspect {
+    #[cfg(feature = "gen-llvm")]
+    use crate::helpers::llvm::assert_evals_to;
+
+    #[cfg(feature = "gen-wasm")]
+    use crate::helpers::wasm::assert_evals_to;
+
+    use indoc::indoc;
+    use roc_std::RocStr;
+
+    #[test]
+    fn to_str_immediates() {
+        assert_evals_to!(
+            indoc!(
+                r#"
+                app "test" provides [main] to "./platform"
+
+                main =
+                    [
+                        Inspect.toStr 15u8,
+                        Inspect.toStr -5i64,
+                        Inspect.toStr (List.len [1, 2, 3]),
+                        Inspect.toStr Bool.true,
+                        Inspect.toStr "say \"hi\"",
+                        Inspect.toStr [1u8, 2, 3],
+                    ]
+                    |> Str.joinWith "; "
+                "#
+            ),
+            RocStr::from(r#"15; -5; 3; Bool.true; "say \"hi\""; [1, 2, 3]"#),
+            RocStr
+        )
+    }
+
+    #[test]
+    fn to_str_derived_structures() {
+        assert_evals_to!(
+            indoc!(
+                r#"
+                app "test" provides [main] to "./platform"
+
+                main =
+                    [
+                        Inspect.toStr { name: "Rocco", age: 2u8 },
+                        Inspect.toStr {},
+                        Inspect.toStr (1u8, "two"),
+                        Inspect.toStr (Ok (Pair 1u8 Blue)),
+                        Inspect.toStr [Some [1u8], None],
+                    ]
+                    |> Str.joinWith "; "
+                "#
+            ),
+            RocStr::from(
+                r#"{ age: 2, name: "Rocco" }; {}; (1, "two"); Ok (Pair 1 Blue); [Some [1], None]"#
+            ),
+            RocStr
+        )
+    }
+
+    #[test]
+    fn interpolate_inspected_values() {
+        assert_evals_to!(
+            indoc!(
+                r#"
+                app "test" provides [main] to "./platform"
+
+                main =
+                    name = "origin"
+                    point = { x: 1i64, y: -2i64 }
+
+                    "\(name) is \{point}, \{List.len [point]} away"
+                "#
+            ),
+            RocStr::from("origin is { x: 1, y: -2 }, 1 away"),
+            RocStr
+        )
+    }
+
+    #[test]
+    fn interpolate_derived_opaque() {
+        assert_evals_to!(
+            indoc!(
+                r#"
+                app "test" provides [main] to "./platform"
+
+                Id := U64 implements [Inspect]
+
+                main = "id: \{@Id 42}"
+                "#
+            ),
+            RocStr::from("id: 42"),
+            RocStr
+        )
+    }
+
+    #[test]
+    fn interpolate_custom_opaque() {
+        assert_evals_to!(
+            indoc!(
+                r#"
+                app "test" provides [main] to "./platform"
+
+                Secret := Str implements [Inspect { toInspector: secretToInspector }]
+
+                secretToInspector : Secret -> Inspector f where f implements InspectFormatter
+                secretToInspector = \@Secret _ -> Inspect.opaque "secret"
+
+                main = "password: \{@Secret "hunter2"}"
+                "#
+            ),
+            RocStr::from("password: <secret>"),
+            RocStr
+        )
+    }
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
 fn issue_4772_weakened_monomorphic_destructure() {
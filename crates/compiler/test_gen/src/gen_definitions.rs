@@ -45,3 +45,32 @@ fn def_closure_in_multiple_parens() {
         u32
     );
 }
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn debug_info_points_at_definitions() {
+    let ir = crate::helpers::llvm::llvm_ir_with_debug_info(indoc!(
+        r#"
+        double = \x -> x * 2
+
+        y = double 21
+
+        y + 1
+        "#
+    ));
+
+    let has_line = |parts: &[&str]| {
+        ir.lines()
+            .any(|line| parts.iter().all(|part| line.contains(part)))
+    };
+
+    assert!(has_line(&["!DIFile(", "filename: \"Test.roc\""]), "{ir}");
+    // `main` is defined on line 3 of the module the expression is wrapped in
+    assert!(has_line(&["!DISubprogram(", "line: 3,"]), "{ir}");
+    // `y` is defined on line 6, after the 4 spaces of indentation of the body of `main`
+    assert!(has_line(&["!DILocation(line: 6, column: 5,"]), "{ir}");
+    assert!(
+        has_line(&["!DILocalVariable(name: \"y\"", "line: 6,"]),
+        "{ir}"
+    );
+}
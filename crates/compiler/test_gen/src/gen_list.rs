@@ -1880,6 +1880,36 @@ fn list_concat_large() {
     })
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+fn branch_over_large_list_literal() {
+    // every element is copied into the list by its own instructions, so the code for this list
+    // is well over 1MB: more than an aarch64 conditional branch (`B.cond`) can jump over
+    let elements = vec!["n"; 150_000].join(", ");
+
+    with_larger_debug_stack(|| {
+        assert_evals_to!(
+            &format!(
+                indoc!(
+                    r#"
+                    f : Nat -> Nat
+                    f = \n ->
+                        if n == 0 then
+                            List.len [{}]
+                        else
+                            n
+
+                    f 0 + f 1
+                    "#
+                ),
+                elements
+            ),
+            150_001,
+            usize
+        );
+    })
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn empty_list_len() {
@@ -4040,3 +4070,48 @@ mod pattern_match {
         )
     }
 }
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn folded_constant_list_is_not_updated_in_place() {
+    // with the `fold-constants` feature, `digits` is a list of literals, which is static data
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            base : U8
+            base = 10
+
+            digits : List U8
+            digits = [base, base + 1, base + 2]
+
+            main = List.concat (List.set digits 0 99) digits
+            "#
+        ),
+        RocList::from_slice(&[99u8, 11, 12, 10, 11, 12]),
+        RocList<u8>
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn local_list_read_at_known_index() {
+    // with the `elide-local-allocations` feature, the list is never allocated
+    assert_evals_to!(
+        indoc!(
+            r#"
+            first = Str.concat "Hello, " "World! This string is too long to be stored inline"
+            second = Str.concat "Goodbye, " "World! This string is too long to be stored inline"
+
+            names = [first, second]
+
+            when List.get names 1 is
+                Ok name -> Str.concat name (Num.toStr (List.len names))
+                Err OutOfBounds -> ""
+            "#
+        ),
+        RocStr::from("Goodbye, World! This string is too long to be stored inline2"),
+        RocStr
+    );
+}
@@ -554,7 +554,7 @@ fn i64_abs() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn various_sized_abs() {
     assert_evals_to!("Num.abs -6i8", 6, i8);
     assert_evals_to!("Num.abs -6i16", 6, i16);
@@ -628,7 +628,7 @@ fn gen_float_eq() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn gen_add_dec() {
     assert_evals_to!(
         indoc!(
@@ -737,7 +737,7 @@ fn gen_div_checked_by_zero_f64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn gen_div_dec() {
     assert_evals_to!(
         indoc!(
@@ -757,7 +757,7 @@ fn gen_div_dec() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn gen_div_checked_dec() {
     assert_evals_to!(
         indoc!(
@@ -778,7 +778,7 @@ fn gen_div_checked_dec() {
     );
 }
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn gen_div_checked_by_zero_dec() {
     assert_evals_to!(
         indoc!(
@@ -846,7 +846,7 @@ fn float_greater_than() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn gen_dec_eq() {
     assert_evals_to!(
         indoc!(
@@ -866,7 +866,7 @@ fn gen_dec_eq() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn gen_dec_neq() {
     assert_evals_to!(
         indoc!(
@@ -1002,7 +1002,7 @@ fn gen_add_i64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn gen_sub_dec() {
     assert_evals_to!(
         indoc!(
@@ -1025,7 +1025,7 @@ fn gen_sub_dec() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn gen_mul_dec() {
     assert_evals_to!(
         indoc!(
@@ -1175,6 +1175,22 @@ fn gen_div_checked_by_zero_i64() {
     );
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+fn gen_div_i128() {
+    assert_evals_to!("1000i128 // 10", 100, i128);
+    assert_evals_to!("-1000i128 // 10", -100, i128);
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+fn gen_div_ceil() {
+    assert_evals_to!("Num.divCeil 7i64 2", 4, i64);
+    assert_evals_to!("Num.divCeil 7u8 2", 4, u8);
+    assert_evals_to!("Num.divCeil 7u128 2", 4, u128);
+    assert_evals_to!("Num.divCeil -7i128 2", -3, i128);
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn gen_rem_i64() {
@@ -1570,6 +1586,15 @@ fn int_negate_dev() {
     assert_evals_to!("Num.neg 0", 0, i64);
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+fn various_sized_negate() {
+    assert_evals_to!("Num.neg 6i8", -6, i8);
+    assert_evals_to!("Num.neg -6i16", 6, i16);
+    assert_evals_to!("Num.neg 6i32", -6, i32);
+    assert_evals_to!("Num.neg -6i128", 6, i128);
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
 fn int_negate() {
@@ -2025,6 +2050,49 @@ fn float_negative_mul_overflow() {
     );
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn large_immediates() {
+    // none of these fit in an aarch64 arithmetic or logical immediate, so each one needs a full
+    // sequence of moves. Passing `n` in keeps the additions from being done at compile time.
+    assert_evals_to!(
+        indoc!(
+            r#"
+            f : I64 -> I64
+            f = \n -> n + 0x1234_5678_9ABC_DEF0
+
+            f 1
+            "#
+        ),
+        0x1234_5678_9ABC_DEF1,
+        i64
+    );
+    assert_evals_to!(
+        indoc!(
+            r#"
+            f : I64 -> I64
+            f = \n -> n - 0x7EDC_BA98_7654_3210
+
+            f 0
+            "#
+        ),
+        -0x7EDC_BA98_7654_3210,
+        i64
+    );
+    assert_evals_to!(
+        indoc!(
+            r#"
+            f : U64 -> U64
+            f = \n -> Num.bitwiseXor n 0xFFFF_0000_1234_FFFF
+
+            f 0xFFFF
+            "#
+        ),
+        0xFFFF_0000_1234_0000,
+        u64
+    );
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn int_mul_wrap_i64() {
@@ -2037,6 +2105,12 @@ fn int_mul_wrap_i128() {
     assert_evals_to!("Num.mulWrap Num.maxI128 2", -2, i128);
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+fn int_mul_wrap_u128() {
+    assert_evals_to!("Num.mulWrap Num.maxU128 2", u128::MAX - 1, u128);
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn int_mul_checked() {
@@ -2348,6 +2422,7 @@ num_conversion_tests! {
         to_i16_sign_extend_i8, "-15i8", -15, ["gen-wasm", "gen-dev"]
         to_i16_truncate, "115i32", 115, ["gen-wasm", "gen-dev"]
         to_i16_truncate_wraps, "60000i32", -5536, ["gen-wasm", "gen-dev"]
+        to_i16_zero_extend_u8, "200u8", 200, ["gen-wasm", "gen-dev"]
     )
     "Num.toI32", i32, (
         to_i32_same_width, "15u32", 15, ["gen-wasm", "gen-dev"]
@@ -2363,12 +2438,14 @@ num_conversion_tests! {
         to_i64_sign_extend_i8, "-15i8", -15, ["gen-wasm", "gen-dev"]
         to_i64_sign_extend_i16, "-15i16", -15, ["gen-wasm", "gen-dev"]
         to_i64_sign_extend_i32, "-15i32", -15, ["gen-wasm", "gen-dev"]
-        to_i64_truncate, "115i128", 115
-        to_i64_truncate_wraps, "10_000_000_000_000_000_000i128", -8446744073709551616
+        to_i64_zero_extend_u32, "4000000000u32", 4000000000, ["gen-wasm", "gen-dev"]
+        to_i64_truncate, "115i128", 115, ["gen-dev"]
+        to_i64_truncate_wraps, "10_000_000_000_000_000_000i128", -8446744073709551616, ["gen-dev"]
     )
     "Num.toI128", i128, (
-        to_i128_same_width, "15u128", 15
-        to_i128_extend, "15i8", 15
+        to_i128_same_width, "15u128", 15, ["gen-dev"]
+        to_i128_extend, "15i8", 15, ["gen-dev"]
+        to_i128_sign_extend_i32, "-15i32", -15, ["gen-dev"]
     )
     "Num.toU8", u8, (
         to_u8_same_width, "15i8", 15, ["gen-wasm", "gen-dev"]
@@ -2390,47 +2467,48 @@ num_conversion_tests! {
     "Num.toU64", u64, (
         to_u64_same_width, "15i64", 15, ["gen-wasm", "gen-dev"]
         to_u64_extend, "15i8", 15, ["gen-wasm", "gen-dev"]
-        to_u64_truncate, "115i128", 115
-        to_u64_truncate_wraps, "10_000_000_000_000_000_000_000i128", 1864712049423024128
+        to_u64_truncate, "115i128", 115, ["gen-dev"]
+        to_u64_truncate_wraps, "10_000_000_000_000_000_000_000i128", 1864712049423024128, ["gen-dev"]
     )
     "Num.toU128", u128, (
-        to_u128_same_width, "15i128", 15
-        to_u128_extend, "15i8", 15
+        to_u128_same_width, "15i128", 15, ["gen-dev"]
+        to_u128_extend, "15i8", 15, ["gen-dev"]
     )
     "Num.toNat", usize, (
         to_nat_same_width, "15i64", 15, ["gen-wasm", "gen-dev"]
         to_nat_extend, "15i8", 15, ["gen-wasm", "gen-dev"]
-        to_nat_truncate, "115i128", 115
+        to_nat_truncate, "115i128", 115, ["gen-dev"]
     )
     "Num.toF32", f32, (
-        to_f32_from_i8, "15i8", 15.0
-        to_f32_from_i16, "15i16", 15.0
-        to_f32_from_i32, "15i32", 15.0
-        to_f32_from_i64, "15i64", 15.0
-        to_f32_from_i128, "15i128", 15.0
-        to_f32_from_u8, "15u8", 15.0
-        to_f32_from_u16, "15u16", 15.0
-        to_f32_from_u32, "15u32", 15.0
-        to_f32_from_u64, "15u64", 15.0
-        to_f32_from_u128, "15u128", 15.0
-        to_f32_from_nat, "15nat", 15.0
-        to_f32_from_f32, "1.5f32", 1.5
-        to_f32_from_f64, "1.5f64", 1.5
+        to_f32_from_i8, "15i8", 15.0, ["gen-dev"]
+        to_f32_from_i16, "15i16", 15.0, ["gen-dev"]
+        to_f32_from_i32, "15i32", 15.0, ["gen-dev"]
+        to_f32_from_i64, "15i64", 15.0, ["gen-dev"]
+        to_f32_from_i128, "15i128", 15.0, ["gen-dev"]
+        to_f32_from_u8, "15u8", 15.0, ["gen-dev"]
+        to_f32_from_u16, "15u16", 15.0, ["gen-dev"]
+        to_f32_from_u32, "15u32", 15.0, ["gen-dev"]
+        to_f32_from_u64, "15u64", 15.0, ["gen-dev"]
+        to_f32_from_u128, "15u128", 15.0, ["gen-dev"]
+        to_f32_from_nat, "15nat", 15.0, ["gen-dev"]
+        to_f32_from_f32, "1.5f32", 1.5, ["gen-dev"]
+        to_f32_from_f64, "1.5f64", 1.5, ["gen-dev"]
     )
     "Num.toF64", f64, (
-        to_f64_from_i8, "15i8", 15.0
-        to_f64_from_i16, "15i16", 15.0
-        to_f64_from_i32, "15i32", 15.0
-        to_f64_from_i64, "15i64", 15.0
-        to_f64_from_i128, "15i128", 15.0
-        to_f64_from_u8, "15u8", 15.0
-        to_f64_from_u16, "15u16", 15.0
-        to_f64_from_u32, "15u32", 15.0
-        to_f64_from_u64, "15u64", 15.0
-        to_f64_from_u128, "15u128", 15.0
-        to_f64_from_nat, "15nat", 15.0
-        to_f64_from_f32, "1.5f32", 1.5
-        to_f64_from_f64, "1.5f64", 1.5
+        to_f64_from_i8, "15i8", 15.0, ["gen-dev"]
+        to_f64_from_i16, "15i16", 15.0, ["gen-dev"]
+        to_f64_from_i32, "15i32", 15.0, ["gen-dev"]
+        to_f64_from_i64, "15i64", 15.0, ["gen-dev"]
+        to_f64_from_i128, "15i128", 15.0, ["gen-dev"]
+        to_f64_from_u8, "15u8", 15.0, ["gen-dev"]
+        to_f64_from_u16, "15u16", 15.0, ["gen-dev"]
+        to_f64_from_u32, "15u32", 15.0, ["gen-dev"]
+        to_f64_from_u64, "15u64", 15.0, ["gen-dev"]
+        to_f64_from_u64_high_bit, "9223372036854775808u64", 9223372036854775808.0, ["gen-dev"]
+        to_f64_from_u128, "15u128", 15.0, ["gen-dev"]
+        to_f64_from_nat, "15nat", 15.0, ["gen-dev"]
+        to_f64_from_f32, "1.5f32", 1.5, ["gen-dev"]
+        to_f64_from_f64, "1.5f64", 1.5, ["gen-dev"]
     )
 }
 
@@ -3221,7 +3299,7 @@ fn num_to_str_f64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn num_to_str_dec() {
     use roc_std::RocStr;
 
@@ -3646,7 +3724,7 @@ fn div_of_unsigned() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn dec_float_suffix() {
     assert_evals_to!(
         indoc!(
@@ -3660,7 +3738,7 @@ fn dec_float_suffix() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn dec_no_decimal() {
     assert_evals_to!(
         indoc!(
@@ -3736,7 +3814,7 @@ fn promote_u64_number_layout() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn promote_i128_number_layout() {
     assert_evals_to!(
         indoc!(
@@ -3753,7 +3831,7 @@ fn promote_i128_number_layout() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn promote_u128_number_layout() {
     assert_evals_to!(
         indoc!(
@@ -3887,7 +3965,7 @@ fn num_abs_diff_int() {
 }
 
 #[test]
-#[cfg(feature = "gen-llvm")]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
 fn num_abs_diff_large_bits() {
     assert_evals_to!(r#"Num.absDiff 0u128 0u128"#, 0, u128);
     assert_evals_to!(r#"Num.absDiff 1u128 2u128"#, 1, u128);
@@ -3895,6 +3973,167 @@ fn num_abs_diff_large_bits() {
     assert_evals_to!(r#"Num.absDiff Num.minI128 -1i128"#, i128::MAX, i128);
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+fn large_bits_arithmetic() {
+    assert_evals_to!("18446744073709551616i128 - 1", 18446744073709551615, i128);
+    assert_evals_to!("-18446744073709551616i128 * 3", -55340232221128654848, i128);
+    assert_evals_to!(
+        "Num.divTrunc -55340232221128654848i128 7",
+        -55340232221128654848 / 7,
+        i128
+    );
+    assert_evals_to!(
+        "Num.rem -55340232221128654848i128 7",
+        -55340232221128654848 % 7,
+        i128
+    );
+    assert_evals_to!("Num.divTrunc Num.maxU128 3", u128::MAX / 3, u128);
+    assert_evals_to!(
+        "Num.neg 18446744073709551616i128",
+        -18446744073709551616,
+        i128
+    );
+    assert_evals_to!(
+        "Num.abs -18446744073709551616i128",
+        18446744073709551616,
+        i128
+    );
+    assert_evals_to!("Num.addWrap Num.maxI128 1", i128::MIN, i128);
+    assert_evals_to!("Num.subWrap 0u128 1", u128::MAX, u128);
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+fn large_bits_bitwise() {
+    assert_evals_to!(
+        "Num.bitwiseAnd 0xffff_0000_0000_0000_0000_0000_0000_ffffu128 0x0ff0_0000_0000_0000_0000_0000_0000_0ff0u128",
+        0x0ff0_0000_0000_0000_0000_0000_0000_0ff0u128,
+        u128
+    );
+    assert_evals_to!(
+        "Num.bitwiseOr 0xf000_0000_0000_0000_0000_0000_0000_000fu128 0x0f00_0000_0000_0000_0000_0000_0000_00f0u128",
+        0xff00_0000_0000_0000_0000_0000_0000_00ffu128,
+        u128
+    );
+    assert_evals_to!("Num.bitwiseXor -1i128 1", -2, i128);
+    assert_evals_to!("Num.shiftLeftBy 1u128 100", 1 << 100, u128);
+    assert_evals_to!("Num.shiftRightBy -256i128 4", -16, i128);
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+fn large_bits_int_cast() {
+    assert_evals_to!("Num.toI128 -5i8", -5, i128);
+    assert_evals_to!("Num.toU128 200u8", 200, u128);
+    assert_evals_to!("Num.toU128 -1i128", u128::MAX, u128);
+    assert_evals_to!("Num.toU64 0x1_0000_0000_0000_0002u128", 2, u64);
+    assert_evals_to!("Num.toU8 0x1_0000_0000_0000_0102i128", 2, u8);
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+fn large_bits_to_frac() {
+    assert_evals_to!(
+        "Num.toF64 -18446744073709551616i128",
+        -18446744073709551616.0,
+        f64
+    );
+    assert_evals_to!("Num.toF32 300u128", 300.0, f32);
+    assert_evals_to!("Num.toF64 1.5dec", 1.5, f64);
+    assert_evals_to!(
+        "Num.toFrac 3u128 + 0.5dec",
+        RocDec::from_str_to_i128_unsafe("3.5"),
+        i128
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn large_bits_compare_and_round() {
+    assert_evals_to!("Num.compare Num.maxU128 1u128", RocOrder::Gt, RocOrder);
+    assert_evals_to!("Num.compare -1i128 1i128", RocOrder::Lt, RocOrder);
+    assert_evals_to!("Num.compare 1.5dec 1.5dec", RocOrder::Eq, RocOrder);
+    assert_evals_to!(
+        indoc!(
+            r#"
+            x : I128
+            x = Num.floor -2.5f64
+
+            y : U128
+            y = Num.ceiling 1e20f64
+
+            z : I128
+            z = Num.round 2.5f32
+
+            (x, y, z)
+            "#
+        ),
+        (-3, 100_000_000_000_000_000_000, 3),
+        (i128, u128, i128)
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn lt_large_bits() {
+    assert_evals_to!("-1i128 < 1i128", true, bool);
+    assert_evals_to!("Num.maxI128 < Num.minI128", false, bool);
+    assert_evals_to!("1u128 < Num.maxU128", true, bool);
+    assert_evals_to!("Num.maxU128 < 1u128", false, bool);
+    assert_evals_to!("-2.5dec < 1.5dec", true, bool);
+    assert_evals_to!("1.5dec < 1.5dec", false, bool);
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn lte_large_bits() {
+    assert_evals_to!("1i128 <= 1i128", true, bool);
+    assert_evals_to!("1i128 <= -1i128", false, bool);
+    assert_evals_to!("Num.maxU128 <= Num.maxU128", true, bool);
+    assert_evals_to!("Num.maxU128 <= 1u128", false, bool);
+    assert_evals_to!("1.5dec <= 1.5dec", true, bool);
+    assert_evals_to!("1.5dec <= -2.5dec", false, bool);
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn gt_large_bits() {
+    assert_evals_to!("1i128 > -1i128", true, bool);
+    assert_evals_to!("Num.minI128 > Num.maxI128", false, bool);
+    assert_evals_to!("Num.maxU128 > 1u128", true, bool);
+    assert_evals_to!("1u128 > Num.maxU128", false, bool);
+    assert_evals_to!("1.5dec > -2.5dec", true, bool);
+    assert_evals_to!("1.5dec > 1.5dec", false, bool);
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn gte_large_bits() {
+    assert_evals_to!("1i128 >= 1i128", true, bool);
+    assert_evals_to!("-1i128 >= 1i128", false, bool);
+    assert_evals_to!("Num.maxU128 >= Num.maxU128", true, bool);
+    assert_evals_to!("1u128 >= Num.maxU128", false, bool);
+    assert_evals_to!("1.5dec >= 1.5dec", true, bool);
+    assert_evals_to!("-2.5dec >= 1.5dec", false, bool);
+}
+
+#[test]
+#[cfg(feature = "gen-wasm")]
+fn to_float_checked() {
+    assert_evals_to!("Num.toF32Checked 1.5f64", RocResult::ok(1.5), RocResult<f32, ()>);
+    assert_evals_to!("Num.toF32Checked 1e300f64", RocResult::err(()), RocResult<f32, ()>);
+    assert_evals_to!(
+        "Num.toF32Checked (1f64 / 0)",
+        RocResult::ok(f32::INFINITY),
+        RocResult<f32, ()>
+    );
+    assert_evals_to!("Num.toF32Checked Num.maxU128", RocResult::err(()), RocResult<f32, ()>);
+    assert_evals_to!("Num.toF64Checked 300u128", RocResult::ok(300.0), RocResult<f64, ()>);
+    assert_evals_to!("Num.toF64Checked -7i8", RocResult::ok(-7.0), RocResult<f64, ()>);
+    assert_evals_to!("Num.toF64Checked 1.5dec", RocResult::ok(1.5), RocResult<f64, ()>);
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
 fn num_abs_diff_float() {
@@ -4072,3 +4311,50 @@ fn num_max() {
     assert_evals_to!(r#"Num.max Num.minI64 Num.maxI64"#, i64::MAX, i64);
     assert_evals_to!(r#"Num.max Num.maxI64 Num.minI64"#, i64::MAX, i64);
 }
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+fn folded_int_constants() {
+    // with the `fold-constants` feature, these are computed at compile time
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            width : U64
+            width = 3 * 4
+
+            height : U64
+            height = width - 2
+
+            main =
+                if width > height then
+                    width * height + Num.addWrap 250u64 6
+                else
+                    0
+            "#
+        ),
+        376,
+        u64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+fn folded_dec_constants() {
+    // with the `fold-constants` feature, these are computed at compile time
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            half : Dec
+            half = 0.25 + 0.25
+
+            main = half * 3 - 0.1
+            "#
+        ),
+        RocDec::from_str_to_i128_unsafe("1.4"),
+        i128
+    );
+}
@@ -4630,3 +4630,61 @@ fn many_arguments() {
         i64
     );
 }
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn inline_expect_and_dbg_continue() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            x = 40
+
+            expect x > 0
+
+            dbg x
+
+            x + 2
+            "#
+        ),
+        42,
+        i64
+    );
+}
+
+#[test]
+#[cfg(feature = "gen-wasm")]
+#[should_panic(expected = "`expect` is not supported by the wasm backend yet")]
+fn inline_expect_is_rejected_on_wasm() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            x = 40
+
+            expect x > 0
+
+            x + 2
+            "#
+        ),
+        42,
+        i64
+    );
+}
+
+#[test]
+#[cfg(feature = "gen-wasm")]
+#[should_panic(expected = "`dbg` is not supported by the wasm backend yet")]
+fn dbg_is_rejected_on_wasm() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            x = 40
+
+            dbg x
+
+            x + 2
+            "#
+        ),
+        42,
+        i64
+    );
+}
@@ -1150,3 +1150,28 @@ fn pass_record_of_u8s() {
         bool
     )
 }
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+fn record_larger_than_32k() {
+    // 4500 8-byte fields make the stack frame too big for 12-bit immediates and scaled offsets
+    let fields = (0..4500)
+        .map(|i| format!("f{i}: {i}i64"))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    assert_evals_to!(
+        &format!(
+            indoc!(
+                r#"
+                record = {{ {} }}
+
+                record.f0 + record.f1234 + record.f4499
+                "#
+            ),
+            fields
+        ),
+        5733,
+        i64
+    );
+}
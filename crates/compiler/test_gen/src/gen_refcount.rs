@@ -53,7 +53,8 @@ fn list_int_inc() {
     assert_refcounts!(
         indoc!(
             r#"
-                list = [0x111, 0x222, 0x333]
+                # a list of number literals is static data, which is not refcounted
+                list = List.map [0x111, 0x222, 0x333] \x -> x
                 [list, list, list]
             "#
         ),
@@ -71,7 +72,8 @@ fn list_int_dealloc() {
     assert_refcounts!(
         indoc!(
             r#"
-                list = [0x111, 0x222, 0x333]
+                # a list of number literals is static data, which is not refcounted
+                list = List.map [0x111, 0x222, 0x333] \x -> x
                 List.len [list, list, list]
             "#
         ),
@@ -83,6 +85,42 @@ fn list_int_dealloc() {
     );
 }
 
+#[test]
+#[cfg(feature = "gen-wasm")]
+fn list_int_literal_inc() {
+    assert_refcounts!(
+        indoc!(
+            r#"
+                list = [0x111, 0x222, 0x333]
+                [list, list, list]
+            "#
+        ),
+        RocList<RocList<i64>>,
+        &[
+            Constant, // list
+            Live(1)   // result
+        ]
+    );
+}
+
+#[test]
+#[cfg(feature = "gen-wasm")]
+fn list_int_literal_dealloc() {
+    assert_refcounts!(
+        indoc!(
+            r#"
+                list = [0x111, 0x222, 0x333]
+                List.len [list, list, list]
+            "#
+        ),
+        usize,
+        &[
+            Constant,    // list
+            Deallocated  // result
+        ]
+    );
+}
+
 #[test]
 #[cfg(feature = "gen-wasm")]
 fn list_str_inc() {
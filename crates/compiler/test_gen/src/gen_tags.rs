@@ -2310,3 +2310,54 @@ fn recursive_tag_id_in_allocation_eq() {
         bool
     );
 }
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn nullable_box_pattern_match() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            Maybe a : [Nothing, Just (Box a)]
+
+            wrap : Str -> Maybe Str
+            wrap = \s -> if Str.isEmpty s then Nothing else Just (Box.box s)
+
+            get : Maybe Str -> Str
+            get = \m ->
+                when m is
+                    Just b -> Box.unbox b
+                    Nothing -> "nothing"
+
+            main = Str.concat (get (wrap "something and ")) (get (wrap ""))
+            "#
+        ),
+        RocStr::from("something and nothing"),
+        RocStr
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn nullable_box_eq() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            Maybe a : [Nothing, Just (Box a)]
+
+            x : Maybe U64
+            x = Just (Box.box 42)
+
+            y : Maybe U64
+            y = Nothing
+
+            main = (x == Just (Box.box 42)) && (x != y) && (y == Nothing)
+            "#
+        ),
+        true,
+        bool
+    );
+}
@@ -7,6 +7,7 @@ use roc_mono::ir::SingleEntryPoint;
 use roc_packaging::cache::RocCacheDir;
 use roc_region::all::LineInfo;
 use roc_solve::FunctionKind;
+use roc_solve_problem::TypeError;
 use roc_std::RocStr;
 use std::mem::MaybeUninit;
 use tempfile::tempdir;
@@ -62,6 +63,8 @@ pub fn helper(
         palette: roc_reporting::report::DEFAULT_PALETTE,
         threading: Threading::Single,
         exec_mode: ExecutionMode::Executable,
+        mono_optimizations: crate::helpers::MONO_OPTIMIZATIONS,
+        emit_debug_info: false,
         function_kind: FunctionKind::LambdaSet,
     };
     let loaded = roc_load::load_and_monomorphize_from_str(
@@ -163,6 +166,8 @@ pub fn helper(
                     delayed_errors.push(problem);
                     continue;
                 }
+                // Lints that don't affect code generation
+                UnusedRecordField { .. } | UnusedExport(..) => {}
                 _ => {
                     let report = can_problem(&alloc, &line_info, module_path.clone(), problem);
                     let mut buf = String::new();
@@ -175,6 +180,10 @@ pub fn helper(
         }
 
         for problem in type_problems {
+            if matches!(problem, TypeError::UnusedImplementation { .. }) {
+                continue;
+            }
+
             if let Some(report) = type_problem(&alloc, &line_info, module_path.clone(), problem) {
                 let mut buf = String::new();
 
@@ -196,6 +205,7 @@ pub fn helper(
         exposed_to_host: exposed_to_host.top_level_values.keys().copied().collect(),
         lazy_literals,
         mode: roc_gen_dev::AssemblyBackendMode::Test,
+        debug_sources: None,
     };
 
     let target = target_lexicon::Triple::host();
@@ -7,6 +7,7 @@ use libloading::Library;
 use roc_build::link::llvm_module_to_dylib;
 use roc_collections::all::MutSet;
 use roc_command_utils::zig;
+use roc_gen_llvm::llvm::debug_info::DebugSources;
 use roc_gen_llvm::llvm::externs::add_default_roc_externs;
 use roc_gen_llvm::{llvm::build::LlvmBackendMode, run_roc::RocCallResult};
 use roc_load::{
@@ -16,6 +17,7 @@ use roc_mono::ir::{CrashTag, OptLevel, SingleEntryPoint};
 use roc_packaging::cache::RocCacheDir;
 use roc_region::all::LineInfo;
 use roc_reporting::report::{RenderTarget, DEFAULT_PALETTE};
+use roc_solve_problem::TypeError;
 use target_lexicon::Triple;
 
 #[cfg(feature = "gen-llvm-wasm")]
@@ -78,6 +80,8 @@ fn create_llvm_module<'a>(
         palette: DEFAULT_PALETTE,
         threading: Threading::Single,
         exec_mode: ExecutionMode::Executable,
+        mono_optimizations: crate::helpers::MONO_OPTIMIZATIONS,
+        emit_debug_info: config.add_debug_info,
     };
     let loaded = roc_load::load_and_monomorphize_from_str(
         arena,
@@ -105,6 +109,7 @@ fn create_llvm_module<'a>(
         host_exposed_lambda_sets,
         interns,
         layout_interner,
+        definition_regions,
         ..
     } = loaded;
 
@@ -112,7 +117,12 @@ fn create_llvm_module<'a>(
     // errors whose reporting we delay (so we can see that code gen generates runtime errors)
     let mut delayed_errors = Vec::new();
 
-    for (home, (module_path, src)) in loaded.sources {
+    let sources = loaded.sources;
+
+    for (home, (module_path, src)) in sources.iter() {
+        let home = *home;
+        let module_path = module_path.clone();
+
         use roc_reporting::report::{can_problem, type_problem, RocDocAllocator};
 
         let can_problems = loaded.can_problems.remove(&home).unwrap_or_default();
@@ -151,6 +161,8 @@ fn create_llvm_module<'a>(
                 }
                 // We should be able to compile even when abilities are used as types
                 AbilityUsedAsType(..) => {}
+                // Lints that don't affect code generation
+                UnusedRecordField { .. } | UnusedExport(..) => {}
                 _ => {
                     let report = can_problem(&alloc, &line_info, module_path.clone(), problem);
                     let mut buf = String::new();
@@ -163,6 +175,10 @@ fn create_llvm_module<'a>(
         }
 
         for problem in type_problems {
+            if matches!(problem, TypeError::UnusedImplementation { .. }) {
+                continue;
+            }
+
             if let Some(report) = type_problem(&alloc, &line_info, module_path.clone(), problem) {
                 let mut buf = String::new();
 
@@ -218,12 +234,27 @@ fn create_llvm_module<'a>(
         }
     }
 
+    // strip Zig debug stuff
+    module.strip_debug_info();
+
+    let debug_sources = if config.add_debug_info {
+        Some(DebugSources::new(
+            module,
+            &dibuilder,
+            &sources,
+            definition_regions,
+        ))
+    } else {
+        None
+    };
+
     // Compile and add all the Procs before adding main
     let env = roc_gen_llvm::llvm::build::Env {
         arena,
         builder: &builder,
         dibuilder: &dibuilder,
         compile_unit: &compile_unit,
+        debug_sources: debug_sources.as_ref(),
         context,
         interns,
         module,
@@ -233,9 +264,6 @@ fn create_llvm_module<'a>(
         exposed_to_host: MutSet::default(),
     };
 
-    // strip Zig debug stuff
-    module.strip_debug_info();
-
     // Add roc_alloc, roc_realloc, and roc_dealloc, since the repl has no
     // platform to provide them.
     add_default_roc_externs(&env);
@@ -279,8 +307,10 @@ fn create_llvm_module<'a>(
 
     env.dibuilder.finalize();
 
-    // strip all debug info: we don't use it at the moment and causes weird validation issues
-    module.strip_debug_info();
+    if !config.add_debug_info {
+        // strip all debug info: we don't use it at the moment and causes weird validation issues
+        module.strip_debug_info();
+    }
 
     // Uncomment this to see the module's un-optimized LLVM instruction output:
     // env.module.print_to_stderr();
@@ -343,54 +373,37 @@ pub fn helper<'a>(
     let (main_fn_name, delayed_errors, module) =
         create_llvm_module(arena, src, config, context, &target, function_kind);
 
-    let res_lib = if config.add_debug_info {
-        let module = annotate_with_debug_info(module, context);
-        llvm_module_to_dylib(&module, &target, config.opt_level)
-    } else {
-        llvm_module_to_dylib(module, &target, config.opt_level)
-    };
-
-    let lib = res_lib.expect("Error loading compiled dylib for test");
+    let lib = llvm_module_to_dylib(module, &target, config.opt_level)
+        .expect("Error loading compiled dylib for test");
 
     (main_fn_name, delayed_errors, lib)
 }
 
-fn annotate_with_debug_info<'ctx>(
-    module: &Module<'ctx>,
-    context: &'ctx inkwell::context::Context,
-) -> Module<'ctx> {
-    use std::process::Command;
-
-    let app_ll_file = "/tmp/roc-debugir.ll";
-    let app_dbg_ll_file = "/tmp/roc-debugir.dbg.ll";
-    let app_bc_file = "/tmp/roc-debugir.bc";
-
-    // write the ll code to a file, so we can modify it
-    module.print_to_file(app_ll_file).unwrap();
+/// The unoptimized LLVM IR for `src`, with the source-level debug info that `roc build --debug`
+/// would emit.
+#[allow(dead_code)]
+pub fn llvm_ir_with_debug_info(src: &str) -> String {
+    let arena = bumpalo::Bump::new();
+    let context = inkwell::context::Context::create();
+    let target = target_lexicon::Triple::host();
 
-    // run the debugir https://github.com/vaivaswatha/debugir tool
-    match Command::new("debugir")
-        .args(["-instnamer", app_ll_file])
-        .output()
-    {
-        Ok(_) => {}
-        Err(error) => {
-            use std::io::ErrorKind;
-            match error.kind() {
-                ErrorKind::NotFound => panic!(
-                    r"I could not find the `debugir` tool on the PATH, install it from https://github.com/vaivaswatha/debugir"
-                ),
-                _ => panic!("{error:?}"),
-            }
-        }
-    }
+    let config = HelperConfig {
+        mode: LlvmBackendMode::GenTest,
+        ignore_problems: false,
+        add_debug_info: true,
+        opt_level: OptLevel::Normal,
+    };
 
-    Command::new("llvm-as")
-        .args([app_dbg_ll_file, "-o", app_bc_file])
-        .output()
-        .unwrap();
+    let (_main_fn_name, _delayed_errors, module) = create_llvm_module(
+        &arena,
+        src,
+        config,
+        &context,
+        &target,
+        FunctionKind::LambdaSet,
+    );
 
-    inkwell::module::Module::parse_bitcode_from_path(app_bc_file, context).unwrap()
+    module.print_to_string().to_string()
 }
 
 #[allow(dead_code)]
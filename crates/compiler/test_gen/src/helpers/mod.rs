@@ -10,6 +10,13 @@ pub mod llvm;
 #[cfg(any(feature = "gen-wasm", feature = "gen-llvm-wasm"))]
 pub mod wasm;
 
+/// The optional passes over the mono IR that the tests run with, turned on by cargo features.
+pub(crate) const MONO_OPTIMIZATIONS: roc_load::MonoOptimizations = roc_load::MonoOptimizations {
+    fold_constants: cfg!(feature = "fold-constants"),
+    inline_procs: cfg!(feature = "inline-procs"),
+    elide_local_allocations: cfg!(feature = "elide-local-allocations"),
+};
+
 #[allow(dead_code)]
 pub(crate) fn src_hash(src: &str) -> u64 {
     use std::collections::hash_map::DefaultHasher;
@@ -27,11 +34,25 @@ pub(crate) fn save_wasm_file(app_module_bytes: &[u8], build_dir_hash: u64) {
     let debug_dir_str = format!("/tmp/roc/gen_wasm/{build_dir_hash:016x}");
     let debug_dir_path = Path::new(&debug_dir_str);
     let final_wasm_path = debug_dir_path.join("final.wasm");
+    let final_wat_path = debug_dir_path.join("final.wat");
 
     std::fs::create_dir_all(debug_dir_path).unwrap();
     std::fs::write(&final_wasm_path, app_module_bytes).unwrap();
 
+    let arena = bumpalo::Bump::new();
+    let wat = roc_wasm_module::WasmModule::preload(&arena, app_module_bytes, false)
+        .and_then(|module| module.to_wat());
+    match wat {
+        Ok(wat) => std::fs::write(&final_wat_path, wat).unwrap(),
+        // Modules built by LLVM can use instructions we don't parse. The .wasm file is still useful.
+        Err(e) => eprintln!(
+            "Failed to print the wasm file as text: {} at offset 0x{:x}",
+            e.message, e.offset
+        ),
+    }
+
     let final_wasm_str = final_wasm_path.to_str().unwrap();
+    let final_wat_str = final_wat_path.to_str().unwrap();
 
     // Get the absolute path to our HTML debug tool.
     // Use the fact that Cargo runs tests from the root of the crate
@@ -45,7 +66,7 @@ pub(crate) fn save_wasm_file(app_module_bytes: &[u8], build_dir_hash: u64) {
     println!();
     println!("Wrote wasm file to\n\t{final_wasm_str}");
     println!("Go to the Roc Wasm Debug tool in your browser\n\tfile://{debug_wasm_test_str}");
-    println!("Or analyse the generated code\n\twasm-objdump -dx {final_wasm_str}");
+    println!("Or read the generated code in text format\n\t{final_wat_str}");
     println!();
 }
 
@@ -93,6 +93,8 @@ fn compile_roc_to_wasm_bytes<'a, T: Wasm32Result>(
         palette: DEFAULT_PALETTE_HTML,
         threading: Threading::Single,
         exec_mode: ExecutionMode::Executable,
+        mono_optimizations: crate::helpers::MONO_OPTIMIZATIONS,
+        emit_debug_info: false,
         function_kind: FunctionKind::LambdaSet,
     };
     let loaded = roc_load::load_and_monomorphize_from_str(
@@ -170,6 +172,7 @@ fn compile_roc_to_wasm_bytes<'a, T: Wasm32Result>(
 
     let mut app_module_bytes = std::vec::Vec::with_capacity(module.size());
     module.serialize(&mut app_module_bytes);
+    roc_gen_wasm::debug_validate_binary(arena, &app_module_bytes);
 
     app_module_bytes
 }
@@ -19,6 +19,7 @@ use roc_load::ExecutionMode;
 use roc_load::FunctionKind;
 use roc_load::LoadConfig;
 use roc_load::LoadMonomorphizedError;
+use roc_load::MonoOptimizations;
 use roc_load::Threading;
 use roc_module::symbol::Interns;
 use roc_module::symbol::Symbol;
@@ -77,7 +78,14 @@ fn promote_expr_to_module(src: &str) -> String {
     buffer
 }
 
-fn compiles_to_ir(test_name: &str, src: &str, mode: &str, allow_type_errors: bool, no_check: bool) {
+fn compiles_to_ir(
+    test_name: &str,
+    src: &str,
+    mode: &str,
+    optimizations: &[&str],
+    allow_type_errors: bool,
+    no_check: bool,
+) {
     use roc_packaging::cache::RocCacheDir;
     use std::path::PathBuf;
 
@@ -87,6 +95,17 @@ fn compiles_to_ir(test_name: &str, src: &str, mode: &str, allow_type_errors: boo
         _ => panic!("Invalid test_mono exec mode {mode}"),
     };
 
+    let mut mono_optimizations = MonoOptimizations::NONE;
+
+    for optimization in optimizations {
+        match *optimization {
+            "fold_constants" => mono_optimizations.fold_constants = true,
+            "inline_procs" => mono_optimizations.inline_procs = true,
+            "elide_local_allocations" => mono_optimizations.elide_local_allocations = true,
+            _ => panic!("Invalid test_mono optimization {optimization}"),
+        }
+    }
+
     let arena = &Bump::new();
 
     let filename = PathBuf::from("Test.roc");
@@ -111,6 +130,8 @@ fn compiles_to_ir(test_name: &str, src: &str, mode: &str, allow_type_errors: boo
         render: roc_reporting::report::RenderTarget::Generic,
         palette: roc_reporting::report::DEFAULT_PALETTE,
         exec_mode,
+        mono_optimizations,
+        emit_debug_info: false,
     };
     let loaded = roc_load::load_and_monomorphize_from_str(
         arena,
@@ -3258,3 +3279,199 @@ fn non_nullable_unwrapped_instead_of_nullable_wrapped() {
         "#
     )
 }
+
+#[mono_test(optimize = "fold_constants")]
+fn fold_constant_arithmetic() {
+    indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        width : U64
+        width = 3 * 4
+
+        main = width + 1
+        "#
+    )
+}
+
+#[mono_test(optimize = "fold_constants")]
+fn fold_constant_list_into_static_data() {
+    indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        base : U8
+        base = 10
+
+        digits : List U8
+        digits = [base, base + 1, base + 2]
+
+        main = List.len digits
+        "#
+    )
+}
+
+#[mono_test(optimize = "fold_constants")]
+fn fold_switch_on_known_value() {
+    indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        main : Str
+        main =
+            if 1 + 1 == 2 then "two" else "not two"
+        "#
+    )
+}
+
+#[mono_test(optimize = "fold_constants")]
+fn fold_constant_overflow_left_in_place() {
+    indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        main : U8
+        main =
+            x : U8
+            x = 255
+            x + 1
+        "#
+    )
+}
+
+#[mono_test(optimize = "inline_procs")]
+fn inline_straight_line_proc() {
+    indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        double : U64 -> U64
+        double = \x -> x * 2
+
+        main = double 21 + 1
+        "#
+    )
+}
+
+#[mono_test(optimize = "inline_procs")]
+fn inline_proc_with_branches_through_join_point() {
+    indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        clamp : I64 -> I64
+        clamp = \x ->
+            if x > 10 then
+                10
+            else if x < 0 then
+                0
+            else
+                x
+
+        main = clamp 42 + 1
+        "#
+    )
+}
+
+#[mono_test(optimize = "inline_procs")]
+fn inline_proc_with_its_own_join_point() {
+    indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        weight : [Small, Large] -> U64
+        weight = \size ->
+            grams =
+                when size is
+                    Small -> 10
+                    Large -> 100
+
+            grams * 2
+
+        main = weight Small + weight Large
+        "#
+    )
+}
+
+#[mono_test(optimize = "inline_procs", optimize = "elide_local_allocations")]
+fn elide_local_box() {
+    indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        main : I64
+        main =
+            boxed = Box.box 41
+
+            Box.unbox boxed + 1
+        "#
+    )
+}
+
+#[mono_test(optimize = "elide_local_allocations")]
+fn elide_local_recursive_tags() {
+    indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        Expr : [Add Expr Expr, Num I64]
+
+        main : I64
+        main =
+            expr : Expr
+            expr = Add (Num 1) (Num 2)
+
+            when expr is
+                Add (Num a) (Num b) -> a + b
+                _ -> 0
+        "#
+    )
+}
+
+#[mono_test(optimize = "elide_local_allocations")]
+fn elide_local_list_length() {
+    indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        main : Nat
+        main =
+            greeting = Str.concat "Hello, " "World! This is long enough to be allocated"
+
+            List.len [greeting, greeting]
+        "#
+    )
+}
+
+#[mono_test(optimize = "inline_procs", optimize = "elide_local_allocations")]
+fn elide_local_list_read_at_known_index() {
+    indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        main : Str
+        main =
+            first = Str.concat "Hello, " "World!"
+            second = Str.concat "Goodbye, " "World!"
+
+            when List.get [first, second] 1 is
+                Ok greeting -> greeting
+                Err OutOfBounds -> ""
+        "#
+    )
+}
+
+#[mono_test(optimize = "elide_local_allocations")]
+fn keep_escaping_local_list() {
+    indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        main : List Str
+        main =
+            greeting = Str.concat "Hello, " "World!"
+
+            [greeting, greeting]
+        "#
+    )
+}
@@ -116,6 +116,11 @@ pub fn format_problems(
     }
 
     for problem in type_problems {
+        // Tests often implement an ability only to check the types of its implementation.
+        if matches!(problem, TypeError::UnusedImplementation { .. }) {
+            continue;
+        }
+
         if let Some(report) = type_problem(&alloc, &lines, filename.clone(), problem.clone()) {
             type_reports.push(report.pretty(&alloc));
         }
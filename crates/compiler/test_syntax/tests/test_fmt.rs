@@ -1034,6 +1034,27 @@ mod test_fmt {
         ));
     }
 
+    #[test]
+    fn inspected_value_string() {
+        expr_formats_same(indoc!(
+            r#"
+            "\(name) is at \{{ x: 1, y: 2 }}!"
+            "#
+        ));
+    }
+
+    #[test]
+    fn type_holes() {
+        expr_formats_same(indoc!(
+            r#"
+            names : List ?elem
+            names = List.map ?people _
+
+            Str.joinWith names ?separator
+            "#
+        ));
+    }
+
     #[test]
     fn escaped_quote_string() {
         expr_formats_same(indoc!(
@@ -5845,6 +5866,25 @@ mod test_fmt {
         ));
     }
 
+    #[test]
+    fn ability_parents_and_member_defaults() {
+        module_formats_same(indoc!(
+            r#"
+            interface Foo exposes [] imports []
+
+            Eq2 implements
+                isEq2 : a, a -> Bool where a implements Eq2
+                isNotEq2 : a, a -> Bool where a implements Eq2
+                isNotEq2 = \x, y -> !(isEq2 x y)
+
+            Ord2 implements Eq2 & Hash
+                compare2 : a, a -> [LT, EQ, GT] where a implements Ord2
+
+            f = g
+            "#
+        ));
+    }
+
     #[test]
     fn leading_comments_preserved() {
         module_formats_same(indoc!(
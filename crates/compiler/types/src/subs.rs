@@ -1726,6 +1726,10 @@ impl Subs {
     pub const AB_HASH: SubsSlice<Symbol>            = SubsSlice::new(3, 1);
     #[rustfmt::skip]
     pub const AB_EQ: SubsSlice<Symbol>              = SubsSlice::new(4, 1);
+    #[rustfmt::skip]
+    pub const AB_SORT: SubsSlice<Symbol>            = SubsSlice::new(5, 1);
+    #[rustfmt::skip]
+    pub const AB_INSPECT: SubsSlice<Symbol>         = SubsSlice::new(6, 1);
     // END INIT-SymbolSubsSlice
 
     pub fn new() -> Self {
@@ -1754,6 +1758,8 @@ impl Subs {
         symbol_names.push(Symbol::HASH_HASHER);
         symbol_names.push(Symbol::HASH_HASH_ABILITY);
         symbol_names.push(Symbol::BOOL_EQ);
+        symbol_names.push(Symbol::SORT_SORT_ABILITY);
+        symbol_names.push(Symbol::INSPECT_INSPECT_ABILITY);
         // END INIT-SymbolNames
 
         // IFTTT INIT-VariableSubsSlice
@@ -2234,6 +2240,11 @@ impl Subs {
         }
     }
 
+    /// Adds every alias and opaque type mentioned in the type of `var` to `aliases`.
+    pub fn collect_aliases(&self, var: Variable, aliases: &mut VecSet<Symbol>) {
+        collect_aliases(self, var, aliases)
+    }
+
     pub fn dbg(&self, var: Variable) -> impl std::fmt::Debug + '_ {
         SubsFmtContent(self.get_content_without_compacting(var), self)
     }
@@ -6084,6 +6095,55 @@ pub fn get_member_lambda_sets_at_region(subs: &Subs, var: Variable, target_regio
     internal_error!("No lambda set at region {} found", target_region);
 }
 
+/// Adds every alias and opaque type mentioned in the type of `var` to `aliases`.
+fn collect_aliases(subs: &Subs, var: Variable, aliases: &mut VecSet<Symbol>) {
+    let mut stack = vec![var];
+    while let Some(var) = stack.pop() {
+        match subs.get_content_without_compacting(var) {
+            Content::FlexVar(_)
+            | Content::RigidVar(_)
+            | Content::FlexAbleVar(_, _)
+            | Content::RigidAbleVar(_, _)
+            // The structure behind a recursion var is already being visited.
+            | Content::RecursionVar { .. } => {}
+            Content::LambdaSet(_) | Content::ErasedLambda => {}
+            Content::Structure(structure) => match structure {
+                FlatType::Apply(_, args) => stack.extend(subs.get_subs_slice(*args)),
+                FlatType::Func(args, _, ret) => {
+                    stack.extend(subs.get_subs_slice(*args));
+                    stack.push(*ret);
+                }
+                FlatType::Record(fields, ext) => {
+                    if let Ok(iter) = fields.unsorted_iterator(subs, *ext) {
+                        stack.extend(iter.map(|(_, field)| *field.as_inner()))
+                    }
+                }
+                FlatType::Tuple(elems, ext) => {
+                    if let Ok(iter) = elems.unsorted_iterator(subs, *ext) {
+                        stack.extend(iter.map(|(_, elem)| elem))
+                    }
+                }
+                FlatType::TagUnion(tags, ext) | FlatType::RecursiveTagUnion(_, tags, ext) => {
+                    for (_tag, vars) in tags.unsorted_iterator(subs, *ext) {
+                        stack.extend(vars.iter().copied());
+                    }
+                }
+                FlatType::FunctionOrTagUnion(_, _, _)
+                | FlatType::EmptyRecord
+                | FlatType::EmptyTuple
+                | FlatType::EmptyTagUnion => {}
+            },
+            Content::Alias(name, args, real_var, _) => {
+                aliases.insert(*name);
+                stack.extend(subs.get_subs_slice(args.all_variables()));
+                stack.push(*real_var);
+            }
+            Content::RangedNumber(_) => {}
+            Content::Error => {}
+        }
+    }
+}
+
 /// Returns true iff the given type is inhabited by at least one value.
 fn is_inhabited(subs: &Subs, var: Variable) -> bool {
     let mut stack = vec![var];
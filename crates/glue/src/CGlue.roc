@@ -0,0 +1,757 @@
+app "c-glue"
+    packages { pf: "../platform/main.roc" }
+    imports [
+        pf.Types.{ Types },
+        pf.Shape.{ Shape, RocFn },
+        pf.File.{ File },
+        pf.TypeId.{ TypeId },
+        "../static/roc_std.h" as rocStdHeader : Str,
+    ]
+    provides [makeGlue] to pf
+
+makeGlue : List Types -> Result (List File) Str
+makeGlue = \typesByArch ->
+    archIncludes =
+        List.walk typesByArch "" \content, types ->
+            arch = (Types.target types).architecture
+            archStr = archName arch
+            directive = if Str.isEmpty content then "#if" else "#elif"
+
+            Str.concat
+                content
+                """
+                \(directive) \(archCondition arch)
+                #include "roc_app/\(archStr).h"
+
+                """
+
+    headerFileContent =
+        """
+        \(fileHeader)
+        #ifndef ROC_APP_H
+        #define ROC_APP_H
+
+        #include "roc_std.h"
+
+        #ifdef __cplusplus
+        extern "C" {
+        #endif
+
+        \(archIncludes)#else
+        #error "roc_app.h was not generated for this target architecture"
+        #endif
+
+        #ifdef __cplusplus
+        }
+        #endif
+
+        #endif // ROC_APP_H
+
+        """
+
+    typesByArch
+    |> List.map convertTypesToFile
+    |> List.append { name: "roc_app.h", content: headerFileContent }
+    |> List.concat staticFiles
+    |> Ok
+
+## These are always included, and don't depend on the specifics of the app.
+staticFiles : List File
+staticFiles = [
+    { name: "roc_std.h", content: rocStdHeader },
+]
+
+convertTypesToFile : Types -> File
+convertTypesToFile = \types ->
+    # C requires a type to be declared before it's used, so first declare every
+    # named type. Recursive tag unions are always a pointer, so they can be
+    # fully defined up front; this lets their payloads refer to them by value.
+    declarations =
+        Types.walkShapes types "" \buf, type, _id ->
+            generateDeclaration buf type
+
+    definitions =
+        Types.walkShapes types "" \buf, type, id ->
+            when type is
+                Struct { name, fields } ->
+                    generateStruct buf types id name fields
+
+                TagUnionPayload { name, fields } ->
+                    generateStruct buf types id name (nameTagUnionPayloadFields fields)
+
+                TagUnion (Enumeration { name, tags, size }) ->
+                    generateEnumeration buf types id name tags size
+
+                TagUnion (NonRecursive { name, tags, discriminantSize, discriminantOffset }) ->
+                    if !(List.isEmpty tags) then
+                        generateNonRecursiveTagUnion buf types id name tags discriminantSize discriminantOffset
+                    else
+                        buf
+
+                TagUnion (Recursive { name, tags }) ->
+                    generateRecursiveTagUnion buf types name tags None
+
+                TagUnion (NullableWrapped { name, indexOfNullTag, tags }) ->
+                    generateRecursiveTagUnion buf types name tags (Some indexOfNullTag)
+
+                TagUnion (NullableUnwrapped { name, nullTag, nonNullTag, nonNullPayload }) ->
+                    generateNullableUnwrapped buf types name nullTag nonNullTag nonNullPayload
+
+                TagUnion (SingleTagStruct { name, tagName, payload }) ->
+                    generateSingleTagStruct buf types id name tagName payload
+
+                TagUnion (NonNullableUnwrapped { name, tagName, payload }) ->
+                    generateNonNullableUnwrapped buf types name tagName payload
+
+                RocResult okId errId ->
+                    generateResult buf types id okId errId
+
+                Function rocFn ->
+                    if rocFn.isToplevel then
+                        buf
+                    else
+                        generateFunction buf types rocFn
+
+                RecursivePointer _ ->
+                    # This is recursively pointing to a type that should already have been added,
+                    # so no extra work needs to happen.
+                    buf
+
+                Unit
+                | Unsized
+                | EmptyTagUnion
+                | Num _
+                | Bool
+                | RocStr
+                | RocDict _ _
+                | RocSet _
+                | RocList _
+                | RocBox _ ->
+                    # These are either defined in roc_std.h, or have no C representation.
+                    buf
+
+    arch = (Types.target types).architecture
+    archStr = archName arch
+
+    content =
+        """
+        \(fileHeader)
+        #pragma once
+
+        \(declarations)
+        \(definitions)
+        """
+
+    {
+        name: "roc_app/\(archStr).h",
+        content: content |> generateEntryPoints types,
+    }
+
+generateDeclaration : Str, Shape -> Str
+generateDeclaration = \buf, type ->
+    when type is
+        Struct { name } | TagUnionPayload { name } | TagUnion (NonRecursive { name }) | TagUnion (SingleTagStruct { name }) ->
+            escapedName = escapeKW name
+
+            Str.concat buf "typedef struct \(escapedName) \(escapedName);\n"
+
+        Function { functionName, isToplevel } ->
+            if isToplevel then
+                buf
+            else
+                escapedName = escapeKW functionName
+
+                Str.concat buf "typedef struct \(escapedName) \(escapedName);\n"
+
+        TagUnion (Recursive { name }) | TagUnion (NullableWrapped { name }) | TagUnion (NullableUnwrapped { name }) | TagUnion (NonNullableUnwrapped { name }) ->
+            escapedName = escapeKW name
+
+            Str.concat buf "typedef struct \(escapedName) { void* pointer; } \(escapedName);\n"
+
+        _ ->
+            buf
+
+generateEntryPoints : Str, Types -> Str
+generateEntryPoints = \buf, types ->
+    List.walk (Types.entryPoints types) buf \accum, T name id -> generateEntryPoint accum types name id
+
+generateEntryPoint : Str, Types, Str, TypeId -> Str
+generateEntryPoint = \buf, types, name, id ->
+    { arguments, ret } =
+        when Types.shape types id is
+            Function rocFn ->
+                args =
+                    toArgStr rocFn.args types \argId, shape, index ->
+                        type = typeName types argId
+                        indexStr = Num.toStr index
+
+                        if passByValue types shape then
+                            "\(type) arg\(indexStr)"
+                        else
+                            "\(type)* arg\(indexStr)"
+
+                { arguments: if Str.isEmpty args then "" else ", \(args)", ret: typeName types rocFn.ret }
+
+            _ ->
+                { arguments: "", ret: typeName types id }
+
+    """
+    \(buf)
+    extern void roc__\(name)_1_exposed_generic(\(ret)* output\(arguments));
+
+    """
+
+generateFunction : Str, Types, RocFn -> Str
+generateFunction = \buf, types, rocFn ->
+    name = escapeKW rocFn.functionName
+    externName = rocFn.externName
+    defName =
+        when Str.splitLast externName "_caller" is
+            Ok { before } -> before
+            Err NotFound -> externName
+
+    externArguments =
+        withoutUnit =
+            toArgStr rocFn.args types \argId, _shape, index ->
+                type = typeName types argId
+                indexStr = Num.toStr index
+
+                "const \(type)* arg\(indexStr)"
+
+        if Str.isEmpty withoutUnit then
+            # These always have a first argument that's a pointer, even if it's to nothing.
+            "const void* arg0"
+        else
+            withoutUnit
+
+    ret = typeName types rocFn.ret
+
+    """
+    \(buf)
+    // A Roc closure. Its captured values live in a reference-counted allocation of
+    // `\(defName)_size()` bytes; calling it consumes that allocation.
+    extern void \(externName)(\(externArguments), const \(name)* closure_data, \(ret)* output);
+    extern int64_t \(defName)_size(void);
+    extern int64_t \(defName)_result_size(void);
+
+    """
+
+generateStruct : Str, Types, TypeId, Str, _ -> Str
+generateStruct = \buf, types, id, name, structFields ->
+    escapedName = escapeKW name
+
+    when structFields is
+        HasNoClosure fields ->
+            if Types.size types id == 0 then
+                # C doesn't allow empty structs, so this stays an incomplete type.
+                buf
+            else
+                body = generateStructFields types escapedName fields
+
+                """
+                \(buf)
+                struct \(escapedName) {
+                \(body.fields)};
+
+                \(generateLayoutAsserts types id escapedName)\(body.asserts)
+                """
+
+        HasClosure fields ->
+            getters =
+                List.walk fields "" \accum, { name: fieldName, id: fieldId, accessors } ->
+                    Str.concat accum (generateGetter types escapedName fieldName fieldId accessors.getter)
+
+            """
+            \(buf)
+            // `\(escapedName)` contains a closure, so its size is only known at runtime.
+            // Values of this type are always behind a pointer to Roc-allocated memory,
+            // and each field must be read using its getter. Getters consume the value.
+            \(getters)
+            """
+
+generateStructFields : Types, Str, List { name : Str, id : TypeId } -> { fields : Str, asserts : Str }
+generateStructFields = \types, structName, fields ->
+    List.walk fields { fields: "", asserts: "", offset: 0 } \state, { name: fieldName, id } ->
+        size = Types.size types id
+
+        if size == 0 then
+            # Zero-sized fields take up no space in Roc, and can't be represented in C.
+            state
+        else
+            offset = roundUpToAlignment state.offset (Types.alignment types id)
+            escapedFieldName = escapeKW fieldName
+            typeStr = typeName types id
+            offsetStr = Num.toStr offset
+
+            {
+                fields: Str.concat state.fields "\(indent)\(typeStr) \(escapedFieldName);\n",
+                asserts: Str.concat state.asserts "_Static_assert(offsetof(\(structName), \(escapedFieldName)) == \(offsetStr), \"\(structName).\(escapedFieldName) should have the same offset as in Roc\");\n",
+                offset: offset + size,
+            }
+    |> \{ fields: fieldsStr, asserts } -> { fields: fieldsStr, asserts }
+
+nameTagUnionPayloadFields = \payloadFields ->
+    # Tag union payloads have numbered fields, so we prefix them
+    # with an "f" because C doesn't allow struct fields to be numbers.
+    when payloadFields is
+        HasNoClosure fields ->
+            renamedFields = List.map fields \{ name, id } -> { name: "f\(name)", id }
+            HasNoClosure renamedFields
+
+        HasClosure fields ->
+            renamedFields = List.map fields \{ name, id, accessors } -> { name: "f\(name)", id, accessors }
+            HasClosure renamedFields
+
+generateGetter : Types, Str, Str, TypeId, Str -> Str
+generateGetter = \types, structName, fieldName, fieldId, getter ->
+    fieldType = typeName types fieldId
+
+    """
+    // Reads `\(fieldName)` out of a `\(structName)`. The output must have room for
+    // `\(getter)_size()` bytes.
+    extern void \(getter)_generic(\(fieldType)* output, const \(structName)* value);
+    extern int64_t \(getter)_size(void);
+
+    """
+
+generateEnumeration : Str, Types, TypeId, Str, List Str, U32 -> Str
+generateEnumeration = \buf, types, id, name, tags, size ->
+    escapedName = escapeKW name
+    intTypeStr = intType size
+
+    """
+    \(buf)
+    typedef \(intTypeStr) \(escapedName);
+
+    enum {
+    \(generateEnumTags escapedName tags)};
+
+    \(generateLayoutAsserts types id escapedName)
+    """
+
+generateEnumTags : Str, List Str -> Str
+generateEnumTags = \name, tags ->
+    List.walkWithIndex tags "" \accum, tag, index ->
+        indexStr = Num.toStr index
+
+        Str.concat accum "\(indent)\(name)_\(tag) = \(indexStr),\n"
+
+generateDiscriminant : Str, List Str, U32 -> Str
+generateDiscriminant = \name, tags, size ->
+    # The discriminant is named after the tag union, so that tag names
+    # from different tag unions don't collide in C's global namespace.
+    discriminantName = "discriminant_\(name)"
+    intTypeStr = intType size
+
+    """
+    typedef \(intTypeStr) \(discriminantName);
+
+    enum {
+    \(generateEnumTags discriminantName tags)};
+
+    """
+
+generateUnion : Types, Str, List { name : Str, payload : [Some TypeId, None] } -> Str
+generateUnion = \types, unionName, tags ->
+    members =
+        List.walk tags "" \accum, { name: tagName, payload } ->
+            when payload is
+                Some payloadId ->
+                    if Types.size types payloadId == 0 then
+                        accum
+                    else
+                        typeStr = typeName types payloadId
+
+                        Str.concat accum "\(indent)\(typeStr) \(escapeKW tagName);\n"
+
+                None ->
+                    # This tag has no payload, so it doesn't need a member.
+                    accum
+
+    """
+    typedef union \(unionName) {
+    \(members)} \(unionName);
+
+    """
+
+generateNonRecursiveTagUnion : Str, Types, TypeId, Str, List { name : Str, payload : [Some TypeId, None] }, U32, U32 -> Str
+generateNonRecursiveTagUnion = \buf, types, id, name, tags, discriminantSize, discriminantOffset ->
+    escapedName = escapeKW name
+    discriminantName = "discriminant_\(escapedName)"
+    unionName = "union_\(escapedName)"
+    discriminantOffsetStr = Num.toStr discriminantOffset
+    tagNames = List.map tags \{ name: n } -> n
+
+    """
+    \(buf)
+    \(generateDiscriminant escapedName tagNames discriminantSize)\(generateUnion types unionName tags)struct \(escapedName) {
+        \(unionName) payload;
+        \(discriminantName) discriminant;
+    };
+
+    \(generateLayoutAsserts types id escapedName)_Static_assert(offsetof(\(escapedName), discriminant) == \(discriminantOffsetStr), "\(escapedName).discriminant should have the same offset as in Roc");
+
+    """
+
+generateRecursiveTagUnion : Str, Types, Str, List { name : Str, payload : [Some TypeId, None] }, [Some U16, None] -> Str
+generateRecursiveTagUnion = \buf, types, name, tags, nullTagIndex ->
+    escapedName = escapeKW name
+    discriminantName = "discriminant_\(escapedName)"
+    unionName = "union_\(escapedName)"
+    tagNames = List.map tags \{ name: n } -> n
+    mask = pointerTagMask types
+
+    nullCheck =
+        when nullTagIndex is
+            Some index ->
+                indexStr = Num.toStr index
+
+                "\(indent)if (value.pointer == NULL) {\n\(indent)\(indent)return \(indexStr);\n\(indent)}\n\n"
+
+            None ->
+                ""
+
+    # Recursive tag unions are a pointer to a heap-allocated payload, with the
+    # discriminant stored in the pointer's (otherwise always zero) low bits.
+    """
+    \(buf)
+    \(generateDiscriminant escapedName tagNames 1)\(generateUnion types unionName tags)static inline \(discriminantName) \(escapedName)_discriminant(\(escapedName) value) {
+    \(nullCheck)    return (\(discriminantName))((uintptr_t)value.pointer & \(mask));
+    }
+
+    static inline \(unionName)* \(escapedName)_payload(\(escapedName) value) {
+        return (\(unionName)*)((uintptr_t)value.pointer & ~(uintptr_t)\(mask));
+    }
+
+    """
+
+generateNullableUnwrapped : Str, Types, Str, Str, Str, TypeId -> Str
+generateNullableUnwrapped = \buf, types, name, nullTag, nonNullTag, nonNullPayload ->
+    escapedName = escapeKW name
+    payloadType = typeName types nonNullPayload
+
+    """
+    \(buf)
+    // `\(nullTag)` is represented as a NULL pointer, and `\(nonNullTag)` as a pointer to its payload.
+    static inline bool \(escapedName)_is_\(nullTag)(\(escapedName) value) {
+        return value.pointer == NULL;
+    }
+
+    static inline \(payloadType)* \(escapedName)_payload(\(escapedName) value) {
+        return (\(payloadType)*)value.pointer;
+    }
+
+    """
+
+generateNonNullableUnwrapped : Str, Types, Str, Str, TypeId -> Str
+generateNonNullableUnwrapped = \buf, types, name, tagName, payload ->
+    escapedName = escapeKW name
+    payloadType = typeName types payload
+
+    """
+    \(buf)
+    // `\(tagName)` is the only tag, so this is always a pointer to its payload.
+    static inline \(payloadType)* \(escapedName)_payload(\(escapedName) value) {
+        return (\(payloadType)*)value.pointer;
+    }
+
+    """
+
+generateSingleTagStruct : Str, Types, TypeId, Str, Str, _ -> Str
+generateSingleTagStruct = \buf, types, id, name, tagName, payload ->
+    escapedName = escapeKW name
+
+    when payload is
+        HasNoClosure payloadFields ->
+            fields = List.mapWithIndex payloadFields \{ id: fieldId }, index ->
+                indexStr = Num.toStr index
+
+                { name: "f\(indexStr)", id: fieldId }
+
+            if Types.size types id == 0 then
+                # C doesn't allow empty structs, so this stays an incomplete type.
+                buf
+            else
+                body = generateStructFields types escapedName fields
+
+                """
+                \(buf)
+                // The `\(tagName)` tag's payload.
+                struct \(escapedName) {
+                \(body.fields)};
+
+                \(generateLayoutAsserts types id escapedName)\(body.asserts)
+                """
+
+        HasClosure payloadFields ->
+            getters =
+                List.walkWithIndex payloadFields "" \accum, { name: getter, id: fieldId }, index ->
+                    indexStr = Num.toStr index
+
+                    Str.concat accum (generateGetter types escapedName "f\(indexStr)" fieldId getter)
+
+            """
+            \(buf)
+            // `\(escapedName)` contains a closure, so its size is only known at runtime.
+            // Values of this type are always behind a pointer to Roc-allocated memory,
+            // and each field of the `\(tagName)` tag must be read using its getter.
+            // Getters consume the value.
+            \(getters)
+            """
+
+generateResult : Str, Types, TypeId, TypeId, TypeId -> Str
+generateResult = \buf, types, id, okId, errId ->
+    name = typeName types id
+    guard = "ROC_DEFINED_\(name)"
+
+    members =
+        [T "ok" okId, T "err" errId]
+        |> List.walk "" \accum, T memberName memberId ->
+            if Types.size types memberId == 0 then
+                accum
+            else
+                typeStr = typeName types memberId
+
+                Str.concat accum "\(indent)\(indent)\(typeStr) \(memberName);\n"
+
+    # The same Result can show up more than once in Types,
+    # so make sure it only gets defined once.
+    """
+    \(buf)
+    #ifndef \(guard)
+    #define \(guard)
+
+    typedef struct \(name) {
+        union {
+    \(members)    } payload;
+        bool is_ok;
+    } \(name);
+
+    \(generateLayoutAsserts types id name)
+    #endif
+
+    """
+
+generateLayoutAsserts : Types, TypeId, Str -> Str
+generateLayoutAsserts = \types, id, name ->
+    size = Num.toStr (Types.size types id)
+    alignment = Num.toStr (Types.alignment types id)
+
+    """
+    _Static_assert(sizeof(\(name)) == \(size), "\(name) should have the same size as in Roc");
+    _Static_assert(_Alignof(\(name)) == \(alignment), "\(name) should have the same alignment as in Roc");
+
+    """
+
+passByValue : Types, Shape -> Bool
+passByValue = \types, type ->
+    when type is
+        Function _ -> Bool.false
+        # unsized values are heap-allocated
+        Unsized -> Bool.false
+        Unit | EmptyTagUnion | Bool | Num _ | TagUnion (Enumeration _) -> Bool.true
+        RocStr | RocList _ | RocDict _ _ | RocSet _ | RocBox _ | TagUnion (NullableUnwrapped _) | TagUnion (NullableWrapped _) | TagUnion (Recursive _) | TagUnion (NonNullableUnwrapped _) | RecursivePointer _ -> Bool.false
+        TagUnion (SingleTagStruct { payload: HasNoClosure fields }) ->
+            List.all fields \{ id } -> passByValue types (Types.shape types id)
+
+        TagUnion (SingleTagStruct { payload: HasClosure _ }) -> Bool.false
+        TagUnion (NonRecursive { tags }) ->
+            List.all tags \{ payload } ->
+                when payload is
+                    Some id -> passByValue types (Types.shape types id)
+                    None -> Bool.true
+
+        RocResult okId errId ->
+            passByValue types (Types.shape types okId)
+            && passByValue types (Types.shape types errId)
+
+        Struct { fields: HasNoClosure fields } | TagUnionPayload { fields: HasNoClosure fields } ->
+            List.all fields \{ id } -> passByValue types (Types.shape types id)
+
+        Struct { fields: HasClosure _ } | TagUnionPayload { fields: HasClosure _ } -> Bool.false
+
+typeName = \types, id ->
+    when Types.shape types id is
+        Unit -> "void"
+        Unsized -> "RocList"
+        EmptyTagUnion -> "void"
+        RocStr -> "RocStr"
+        Bool -> "bool"
+        Num U8 -> "uint8_t"
+        Num U16 -> "uint16_t"
+        Num U32 -> "uint32_t"
+        Num U64 -> "uint64_t"
+        Num U128 -> "RocU128"
+        Num I8 -> "int8_t"
+        Num I16 -> "int16_t"
+        Num I32 -> "int32_t"
+        Num I64 -> "int64_t"
+        Num I128 -> "RocI128"
+        Num F32 -> "float"
+        Num F64 -> "double"
+        Num Dec -> "RocDec"
+        RocDict _ _ -> "RocDict"
+        RocSet _ -> "RocSet"
+        RocList _ -> "RocList"
+        RocBox _ -> "RocBox"
+        RocResult ok err ->
+            # C has no generics, so each Result gets its own named struct.
+            okName = typeName types ok
+            errName = typeName types err
+
+            "RocResult_\(okName)_\(errName)"
+
+        RecursivePointer content ->
+            typeName types content
+
+        Struct { name } -> escapeKW name
+        TagUnionPayload { name } -> escapeKW name
+        TagUnion (NonRecursive { name }) -> escapeKW name
+        TagUnion (Recursive { name }) -> escapeKW name
+        TagUnion (Enumeration { name }) -> escapeKW name
+        TagUnion (NullableWrapped { name }) -> escapeKW name
+        TagUnion (NullableUnwrapped { name }) -> escapeKW name
+        TagUnion (NonNullableUnwrapped { name }) -> escapeKW name
+        TagUnion (SingleTagStruct { name }) -> escapeKW name
+        Function { functionName } -> escapeKW functionName
+
+intType : U32 -> Str
+intType = \size ->
+    when size is
+        2 -> "uint16_t"
+        4 -> "uint32_t"
+        8 -> "uint64_t"
+        _ -> "uint8_t"
+
+## The low bits of a pointer to a Roc allocation that are always zero,
+## which recursive tag unions use to store their discriminant.
+pointerTagMask : Types -> Str
+pointerTagMask = \types ->
+    when (Types.target types).architecture is
+        Aarch64 | X86x64 -> "0x7"
+        Aarch32 | Wasm32 | X86x32 -> "0x3"
+
+roundUpToAlignment = \width, alignment ->
+    when alignment is
+        0 -> width
+        1 -> width
+        _ ->
+            if width % alignment > 0 then
+                width + alignment - (width % alignment)
+            else
+                width
+
+archName = \arch ->
+    when arch is
+        Aarch32 ->
+            "arm"
+
+        Aarch64 ->
+            "aarch64"
+
+        Wasm32 ->
+            "wasm32"
+
+        X86x32 ->
+            "x86"
+
+        X86x64 ->
+            "x86_64"
+
+## The preprocessor condition under which a C compiler is targeting this architecture.
+archCondition = \arch ->
+    when arch is
+        Aarch32 ->
+            "defined(__arm__) || defined(_M_ARM)"
+
+        Aarch64 ->
+            "defined(__aarch64__) || defined(_M_ARM64)"
+
+        Wasm32 ->
+            "defined(__wasm32__)"
+
+        X86x32 ->
+            "defined(__i386__) || defined(_M_IX86)"
+
+        X86x64 ->
+            "defined(__x86_64__) || defined(_M_X64)"
+
+fileHeader =
+    """
+    // ⚠️ GENERATED CODE ⚠️ - this entire file was generated by the `roc glue` CLI command
+
+    """
+
+indent = "    "
+
+reservedKeywords = Set.fromList [
+    "auto",
+    "break",
+    "case",
+    "char",
+    "const",
+    "continue",
+    "default",
+    "do",
+    "double",
+    "else",
+    "enum",
+    "extern",
+    "float",
+    "for",
+    "goto",
+    "if",
+    "inline",
+    "int",
+    "long",
+    "register",
+    "restrict",
+    "return",
+    "short",
+    "signed",
+    "sizeof",
+    "static",
+    "struct",
+    "switch",
+    "typedef",
+    "union",
+    "unsigned",
+    "void",
+    "volatile",
+    "while",
+    "bool",
+    "true",
+    "false",
+    "NULL",
+]
+
+escapeKW = \input ->
+    # C has no raw identifiers, so add an underscore to names that are reserved keywords.
+    if Set.contains reservedKeywords input then
+        "\(input)_"
+    else
+        input
+
+isUnit : Shape -> Bool
+isUnit = \shape ->
+    when shape is
+        Unit -> Bool.true
+        _ -> Bool.false
+
+toArgStr : List TypeId, Types, (TypeId, Shape, Nat -> Str) -> Str
+toArgStr = \args, types, fmt ->
+    List.walkWithIndex args "" \state, argId, index ->
+        shape = Types.shape types argId
+
+        # Drop `()` args; they aren't FFI-safe, and nothing will get passed anyway.
+        if isUnit shape then
+            state
+        else
+            argStr = fmt argId shape index
+
+            if Str.isEmpty state then
+                argStr # Don't prepend a comma if this is the first one
+            else
+                state
+                |> Str.concat ", "
+                |> Str.concat argStr
@@ -0,0 +1,765 @@
+app "zig-glue"
+    packages { pf: "../platform/main.roc" }
+    imports [
+        pf.Types.{ Types },
+        pf.Shape.{ Shape, RocFn },
+        pf.File.{ File },
+        pf.TypeId.{ TypeId },
+        "../static/roc_std.zig" as rocStdZig : Str,
+    ]
+    provides [makeGlue] to pf
+
+makeGlue : List Types -> Result (List File) Str
+makeGlue = \typesByArch ->
+    archImports =
+        List.walk typesByArch "" \content, types ->
+            archStr = archName (Types.target types).architecture
+
+            Str.concat content "\(indent).\(archStr) => @import(\"roc_app/\(archStr).zig\"),\n"
+
+    rootFileContent =
+        """
+        \(fileHeader)
+        const builtin = @import("builtin");
+
+        pub usingnamespace @import("roc_std.zig");
+
+        pub usingnamespace switch (builtin.cpu.arch) {
+        \(archImports)    else => @compileError("roc_app.zig was not generated for this target architecture"),
+        };
+
+        """
+
+    typesByArch
+    |> List.map convertTypesToFile
+    |> List.append { name: "roc_app.zig", content: rootFileContent }
+    |> List.concat staticFiles
+    |> Ok
+
+## These are always included, and don't depend on the specifics of the app.
+staticFiles : List File
+staticFiles = [
+    { name: "roc_std.zig", content: rocStdZig },
+]
+
+convertTypesToFile : Types -> File
+convertTypesToFile = \types ->
+    content =
+        Types.walkShapes types "\(fileHeader)\(archFileImports)" \buf, type, id ->
+            when type is
+                Struct { name, fields } ->
+                    generateStruct buf types id name fields
+
+                TagUnionPayload { name, fields } ->
+                    generateStruct buf types id name (nameTagUnionPayloadFields fields)
+
+                TagUnion (Enumeration { name, tags, size }) ->
+                    generateEnumeration buf types id name tags size
+
+                TagUnion (NonRecursive { name, tags, discriminantSize, discriminantOffset }) ->
+                    if !(List.isEmpty tags) then
+                        generateNonRecursiveTagUnion buf types id name tags discriminantSize discriminantOffset
+                    else
+                        buf
+
+                TagUnion (Recursive { name, tags }) ->
+                    generateRecursiveTagUnion buf types name tags None
+
+                TagUnion (NullableWrapped { name, indexOfNullTag, tags }) ->
+                    generateRecursiveTagUnion buf types name tags (Some indexOfNullTag)
+
+                TagUnion (NullableUnwrapped { name, nullTag, nonNullTag, nonNullPayload }) ->
+                    generateNullableUnwrapped buf types name nullTag nonNullTag nonNullPayload
+
+                TagUnion (SingleTagStruct { name, tagName, payload }) ->
+                    generateSingleTagStruct buf types id name tagName payload
+
+                TagUnion (NonNullableUnwrapped { name, tagName, payload }) ->
+                    generateNonNullableUnwrapped buf types name tagName payload
+
+                Function rocFn ->
+                    if rocFn.isToplevel then
+                        buf
+                    else
+                        generateFunction buf types rocFn
+
+                RecursivePointer _ ->
+                    # This is recursively pointing to a type that should already have been added,
+                    # so no extra work needs to happen.
+                    buf
+
+                Unit
+                | Unsized
+                | EmptyTagUnion
+                | Num _
+                | Bool
+                | RocResult _ _
+                | RocStr
+                | RocDict _ _
+                | RocSet _
+                | RocList _
+                | RocBox _ ->
+                    # These are either defined in roc_std.zig, or have no Zig representation.
+                    buf
+
+    archStr = archName (Types.target types).architecture
+
+    {
+        name: "roc_app/\(archStr).zig",
+        content: content |> generateEntryPoints types,
+    }
+
+generateEntryPoints : Str, Types -> Str
+generateEntryPoints = \buf, types ->
+    List.walk (Types.entryPoints types) buf \accum, T name id -> generateEntryPoint accum types name id
+
+generateEntryPoint : Str, Types, Str, TypeId -> Str
+generateEntryPoint = \buf, types, name, id ->
+    { args, retId } =
+        when Types.shape types id is
+            Function rocFn -> { args: rocFn.args, retId: rocFn.ret }
+            _ -> { args: [], retId: id }
+
+    params =
+        toArgStr args types \argId, _shape, index ->
+            type = typeName types argId
+            indexStr = Num.toStr index
+
+            "arg\(indexStr): \(type)"
+
+    # Roc takes ownership of arguments it receives by pointer, so give it a copy it can mutate.
+    ownedArgs =
+        List.walkWithIndex args "" \accum, argId, index ->
+            shape = Types.shape types argId
+
+            if isUnit shape || passByValue types shape then
+                accum
+            else
+                indexStr = Num.toStr index
+
+                Str.concat accum "\(indent)var owned_arg\(indexStr) = arg\(indexStr);\n"
+
+    externArgs =
+        toArgStr args types \argId, shape, index ->
+            type = typeName types argId
+            indexStr = Num.toStr index
+
+            if passByValue types shape then
+                "arg\(indexStr): \(type)"
+            else
+                "arg\(indexStr): *\(type)"
+
+    callArgs =
+        toArgStr args types \_argId, shape, index ->
+            indexStr = Num.toStr index
+
+            if passByValue types shape then
+                "arg\(indexStr)"
+            else
+                "&owned_arg\(indexStr)"
+
+    externName = "roc__\(name)_1_exposed_generic"
+    comma = \str -> if Str.isEmpty str then "" else ", \(str)"
+
+    { ret, body, output } =
+        if isUnit (Types.shape types retId) then
+            {
+                ret: "void",
+                body: "\(ownedArgs)\(indent)\(externName)(null\(comma callArgs));\n",
+                output: "?*anyopaque",
+            }
+        else
+            type = typeName types retId
+
+            {
+                ret: type,
+                body: "\(indent)var ret: \(type) = undefined;\n\(ownedArgs)\(indent)\(externName)(&ret\(comma callArgs));\n\(indent)return ret;\n",
+                output: "*\(type)",
+            }
+
+    """
+    \(buf)
+    pub fn \(escapeKW name)(\(params)) \(ret) {
+    \(body)}
+
+    extern fn \(externName)(output: \(output)\(comma externArgs)) void;
+
+    """
+
+generateFunction : Str, Types, RocFn -> Str
+generateFunction = \buf, types, rocFn ->
+    name = escapeKW rocFn.functionName
+    externName = rocFn.externName
+    defName =
+        when Str.splitLast externName "_caller" is
+            Ok { before } -> before
+            Err NotFound -> externName
+
+    externArguments =
+        withoutUnit =
+            toArgStr rocFn.args types \argId, _shape, index ->
+                type = typeName types argId
+                indexStr = Num.toStr index
+
+                "arg\(indexStr): *const \(type)"
+
+        if Str.isEmpty withoutUnit then
+            # These always have a first argument that's a pointer, even if it's to nothing.
+            "arg0: *const anyopaque"
+        else
+            withoutUnit
+
+    output =
+        if isUnit (Types.shape types rocFn.ret) then
+            "?*anyopaque"
+        else
+            "*\(typeName types rocFn.ret)"
+
+    """
+    \(buf)
+    /// A Roc closure. Its captured values live in a reference-counted allocation of
+    /// `\(defName)_size()` bytes; calling it consumes that allocation.
+    pub const \(name) = opaque {};
+
+    pub extern fn \(externName)(\(externArguments), closure_data: *const \(name), output: \(output)) void;
+    pub extern fn \(defName)_size() i64;
+    pub extern fn \(defName)_result_size() i64;
+
+    """
+
+generateStruct : Str, Types, TypeId, Str, _ -> Str
+generateStruct = \buf, types, id, name, structFields ->
+    escapedName = escapeKW name
+
+    when structFields is
+        HasNoClosure fields ->
+            body = generateStructFields types name fields
+
+            """
+            \(buf)
+            pub const \(escapedName) = extern struct {
+            \(body.fields)};
+            \(generateLayoutAsserts types id escapedName body.asserts)
+            """
+
+        HasClosure fields ->
+            getters =
+                List.walk fields "" \accum, { name: fieldName, id: fieldId, accessors } ->
+                    Str.concat accum (generateGetter types escapedName fieldName fieldId accessors.getter)
+
+            """
+            \(buf)
+            /// `\(escapedName)` contains a closure, so its size is only known at runtime.
+            /// Values of this type are always behind a pointer to Roc-allocated memory,
+            /// and each field must be read using its getter. Getters consume the value.
+            pub const \(escapedName) = opaque {};
+            \(getters)
+            """
+
+generateStructFields : Types, Str, List { name : Str, id : TypeId } -> { fields : Str, asserts : Str }
+generateStructFields = \types, structName, fields ->
+    List.walk fields { fields: "", asserts: "", offset: 0 } \state, { name: fieldName, id } ->
+        size = Types.size types id
+
+        if size == 0 then
+            # Zero-sized fields take up no space in Roc, and can't be in an extern struct.
+            state
+        else
+            offset = roundUpToAlignment state.offset (Types.alignment types id)
+            typeStr = typeName types id
+            offsetStr = Num.toStr offset
+
+            {
+                fields: Str.concat state.fields "\(indent)\(escapeKW fieldName): \(typeStr),\n",
+                asserts: Str.concat state.asserts "\(indent)assertOffset(\(escapeKW structName), \"\(fieldName)\", \(offsetStr));\n",
+                offset: offset + size,
+            }
+    |> \{ fields: fieldsStr, asserts } -> { fields: fieldsStr, asserts }
+
+nameTagUnionPayloadFields = \payloadFields ->
+    # Tag union payloads have numbered fields, so we prefix them
+    # with an "f" because Zig doesn't allow struct fields to be numbers.
+    when payloadFields is
+        HasNoClosure fields ->
+            renamedFields = List.map fields \{ name, id } -> { name: "f\(name)", id }
+            HasNoClosure renamedFields
+
+        HasClosure fields ->
+            renamedFields = List.map fields \{ name, id, accessors } -> { name: "f\(name)", id, accessors }
+            HasClosure renamedFields
+
+generateGetter : Types, Str, Str, TypeId, Str -> Str
+generateGetter = \types, structName, fieldName, fieldId, getter ->
+    fieldType = typeName types fieldId
+
+    """
+
+    /// Reads `\(fieldName)` out of a `\(structName)`. The output must have room for
+    /// `\(getter)_size()` bytes.
+    pub extern fn \(getter)_generic(output: *\(fieldType), value: *const \(structName)) void;
+    pub extern fn \(getter)_size() i64;
+
+    """
+
+generateEnumeration : Str, Types, TypeId, Str, List Str, U32 -> Str
+generateEnumeration = \buf, types, id, name, tags, size ->
+    escapedName = escapeKW name
+
+    """
+    \(buf)
+    pub const \(escapedName) = \(generateEnum tags size);
+    \(generateLayoutAsserts types id escapedName "")
+    """
+
+## An enum with the given tags, whose tag values match their index.
+generateEnum : List Str, U32 -> Str
+generateEnum = \tags, size ->
+    intTypeStr = intType size
+
+    enumTags =
+        List.walkWithIndex tags "" \accum, tag, index ->
+            indexStr = Num.toStr index
+
+            Str.concat accum "\(indent)\(escapeKW tag) = \(indexStr),\n"
+
+    "enum(\(intTypeStr)) {\n\(enumTags)}"
+
+generateNonRecursiveTagUnion : Str, Types, TypeId, Str, List { name : Str, payload : [Some TypeId, None] }, U32, U32 -> Str
+generateNonRecursiveTagUnion = \buf, types, id, name, tags, discriminantSize, discriminantOffset ->
+    escapedName = escapeKW name
+    discriminantName = escapeKW "discriminant_\(name)"
+    tagNames = List.map tags \{ name: n } -> n
+    discriminantOffsetStr = Num.toStr discriminantOffset
+    members = generateUnionMembers types tags "\(indent)\(indent)"
+    discriminantAssert = "\(indent)assertOffset(\(escapedName), \"discriminant\", \(discriminantOffsetStr));\n"
+
+    helpers =
+        List.walk tags "" \accum, { name: tagName, payload } ->
+            tag = escapeKW tagName
+
+            when payloadType types payload is
+                Some type ->
+                    """
+                    \(accum)
+
+                        pub fn init\(tagName)(payload: \(type)) \(escapedName) {
+                            return .{ .payload = .{ .\(tag) = payload }, .discriminant = .\(tag) };
+                        }
+
+                        pub fn get\(tagName)(self: \(escapedName)) \(type) {
+                            std.debug.assert(self.discriminant == .\(tag));
+                            return self.payload.\(tag);
+                        }
+                    """
+
+                None ->
+                    """
+                    \(accum)
+
+                        pub fn init\(tagName)() \(escapedName) {
+                            return .{ .payload = undefined, .discriminant = .\(tag) };
+                        }
+                    """
+
+    """
+    \(buf)
+    pub const \(discriminantName) = \(generateEnum tagNames discriminantSize);
+
+    pub const \(escapedName) = extern struct {
+        payload: extern union {
+    \(members)    },
+        discriminant: \(discriminantName),\(helpers)
+    };
+    \(generateLayoutAsserts types id escapedName discriminantAssert)
+    """
+
+## The members of an extern union holding each tag's payload.
+## Tags without a (nonzero-sized) payload don't get a member.
+generateUnionMembers : Types, List { name : Str, payload : [Some TypeId, None] }, Str -> Str
+generateUnionMembers = \types, tags, indents ->
+    List.walk tags "" \accum, { name: tagName, payload } ->
+        when payloadType types payload is
+            Some type ->
+                Str.concat accum "\(indents)\(escapeKW tagName): \(type),\n"
+
+            None ->
+                accum
+
+payloadType : Types, [Some TypeId, None] -> [Some Str, None]
+payloadType = \types, payload ->
+    when payload is
+        Some id ->
+            if Types.size types id == 0 then
+                None
+            else
+                Some (typeName types id)
+
+        None ->
+            None
+
+generateRecursiveTagUnion : Str, Types, Str, List { name : Str, payload : [Some TypeId, None] }, [Some U16, None] -> Str
+generateRecursiveTagUnion = \buf, types, name, tags, nullTagIndex ->
+    escapedName = escapeKW name
+    discriminantName = escapeKW "discriminant_\(name)"
+    unionName = escapeKW "union_\(name)"
+    tagNames = List.map tags \{ name: n } -> n
+    mask = pointerTagMask types
+
+    nullCheck =
+        when nullTagIndex is
+            Some index ->
+                nullTag =
+                    when List.get tagNames (Num.toNat index) is
+                        Ok tag -> escapeKW tag
+                        Err OutOfBounds -> crash "The null tag index of \(name) was out of bounds. This should never happen, and means there was a bug in `roc glue`."
+
+                "\(indent)\(indent)if (self.pointer == null) {\n\(indent)\(indent)\(indent)return .\(nullTag);\n\(indent)\(indent)}\n\n"
+
+            None ->
+                ""
+
+    # Recursive tag unions are a pointer to a heap-allocated payload, with the
+    # discriminant stored in the pointer's (otherwise always zero) low bits.
+    """
+    \(buf)
+    pub const \(discriminantName) = \(generateEnum tagNames 1);
+
+    pub const \(unionName) = extern union {
+    \(generateUnionMembers types tags indent)};
+
+    /// A pointer to a heap-allocated `\(unionName)`, with the discriminant stored in the pointer's low bits.
+    pub const \(escapedName) = extern struct {
+        pointer: ?*anyopaque,
+
+        pub fn discriminant(self: \(escapedName)) \(discriminantName) {
+    \(nullCheck)        return @enumFromInt(@intFromPtr(self.pointer) & \(mask));
+        }
+
+        pub fn payload(self: \(escapedName)) *\(unionName) {
+            return @ptrFromInt(@intFromPtr(self.pointer) & ~@as(usize, \(mask)));
+        }
+    };
+
+    """
+
+generateNullableUnwrapped : Str, Types, Str, Str, Str, TypeId -> Str
+generateNullableUnwrapped = \buf, types, name, nullTag, nonNullTag, nonNullPayload ->
+    escapedName = escapeKW name
+    payloadTypeStr = typeName types nonNullPayload
+
+    """
+    \(buf)
+    /// `\(nullTag)` is represented as a null pointer, and `\(nonNullTag)` as a pointer to its payload.
+    pub const \(escapedName) = extern struct {
+        pointer: ?*\(payloadTypeStr),
+
+        pub fn is\(nullTag)(self: \(escapedName)) bool {
+            return self.pointer == null;
+        }
+    };
+
+    """
+
+generateNonNullableUnwrapped : Str, Types, Str, Str, TypeId -> Str
+generateNonNullableUnwrapped = \buf, types, name, tagName, payload ->
+    escapedName = escapeKW name
+    payloadTypeStr = typeName types payload
+
+    """
+    \(buf)
+    /// `\(tagName)` is the only tag, so this is always a pointer to its payload.
+    pub const \(escapedName) = extern struct {
+        pointer: *\(payloadTypeStr),
+    };
+
+    """
+
+generateSingleTagStruct : Str, Types, TypeId, Str, Str, _ -> Str
+generateSingleTagStruct = \buf, types, id, name, tagName, payload ->
+    escapedName = escapeKW name
+
+    when payload is
+        HasNoClosure payloadFields ->
+            fields = List.mapWithIndex payloadFields \{ id: fieldId }, index ->
+                indexStr = Num.toStr index
+
+                { name: "f\(indexStr)", id: fieldId }
+
+            body = generateStructFields types name fields
+
+            """
+            \(buf)
+            /// The `\(tagName)` tag's payload.
+            pub const \(escapedName) = extern struct {
+            \(body.fields)};
+            \(generateLayoutAsserts types id escapedName body.asserts)
+            """
+
+        HasClosure payloadFields ->
+            getters =
+                List.walkWithIndex payloadFields "" \accum, { name: getter, id: fieldId }, index ->
+                    indexStr = Num.toStr index
+
+                    Str.concat accum (generateGetter types escapedName "f\(indexStr)" fieldId getter)
+
+            """
+            \(buf)
+            /// `\(escapedName)` contains a closure, so its size is only known at runtime.
+            /// Values of this type are always behind a pointer to Roc-allocated memory,
+            /// and each field of the `\(tagName)` tag must be read using its getter.
+            /// Getters consume the value.
+            pub const \(escapedName) = opaque {};
+            \(getters)
+            """
+
+## Checks at compile time that the type has the same layout as in Roc.
+## Zero-sized types are skipped, since Roc doesn't give them an alignment.
+generateLayoutAsserts : Types, TypeId, Str, Str -> Str
+generateLayoutAsserts = \types, id, name, extraAsserts ->
+    size = Types.size types id
+
+    if size == 0 then
+        ""
+    else
+        sizeStr = Num.toStr size
+        alignment = Num.toStr (Types.alignment types id)
+
+        """
+
+        comptime {
+            assertLayout(\(name), \(sizeStr), \(alignment));
+        \(extraAsserts)}
+
+        """
+
+passByValue : Types, Shape -> Bool
+passByValue = \types, type ->
+    when type is
+        Function _ -> Bool.false
+        # unsized values are heap-allocated
+        Unsized -> Bool.false
+        Unit | EmptyTagUnion | Bool | Num _ | TagUnion (Enumeration _) -> Bool.true
+        RocStr | RocList _ | RocDict _ _ | RocSet _ | RocBox _ | TagUnion (NullableUnwrapped _) | TagUnion (NullableWrapped _) | TagUnion (Recursive _) | TagUnion (NonNullableUnwrapped _) | RecursivePointer _ -> Bool.false
+        TagUnion (SingleTagStruct { payload: HasNoClosure fields }) ->
+            List.all fields \{ id } -> passByValue types (Types.shape types id)
+
+        TagUnion (SingleTagStruct { payload: HasClosure _ }) -> Bool.false
+        TagUnion (NonRecursive { tags }) ->
+            List.all tags \{ payload } ->
+                when payload is
+                    Some id -> passByValue types (Types.shape types id)
+                    None -> Bool.true
+
+        RocResult okId errId ->
+            passByValue types (Types.shape types okId)
+            && passByValue types (Types.shape types errId)
+
+        Struct { fields: HasNoClosure fields } | TagUnionPayload { fields: HasNoClosure fields } ->
+            List.all fields \{ id } -> passByValue types (Types.shape types id)
+
+        Struct { fields: HasClosure _ } | TagUnionPayload { fields: HasClosure _ } -> Bool.false
+
+typeName = \types, id ->
+    when Types.shape types id is
+        Unit -> "void"
+        Unsized -> "RocList"
+        EmptyTagUnion -> "void"
+        RocStr -> "RocStr"
+        Bool -> "bool"
+        Num U8 -> "u8"
+        Num U16 -> "u16"
+        Num U32 -> "u32"
+        Num U64 -> "u64"
+        Num U128 -> "u128"
+        Num I8 -> "i8"
+        Num I16 -> "i16"
+        Num I32 -> "i32"
+        Num I64 -> "i64"
+        Num I128 -> "i128"
+        Num F32 -> "f32"
+        Num F64 -> "f64"
+        Num Dec -> "RocDec"
+        RocDict _ _ -> "RocDict"
+        RocSet _ -> "RocSet"
+        RocList _ -> "RocList"
+        RocBox _ -> "RocBox"
+        RocResult ok err ->
+            okName = typeName types ok
+            errName = typeName types err
+
+            "RocResult(\(okName), \(errName))"
+
+        RecursivePointer content ->
+            typeName types content
+
+        Struct { name } -> escapeKW name
+        TagUnionPayload { name } -> escapeKW name
+        TagUnion (NonRecursive { name }) -> escapeKW name
+        TagUnion (Recursive { name }) -> escapeKW name
+        TagUnion (Enumeration { name }) -> escapeKW name
+        TagUnion (NullableWrapped { name }) -> escapeKW name
+        TagUnion (NullableUnwrapped { name }) -> escapeKW name
+        TagUnion (NonNullableUnwrapped { name }) -> escapeKW name
+        TagUnion (SingleTagStruct { name }) -> escapeKW name
+        Function { functionName } -> escapeKW functionName
+
+intType : U32 -> Str
+intType = \size ->
+    when size is
+        2 -> "u16"
+        4 -> "u32"
+        8 -> "u64"
+        _ -> "u8"
+
+## The low bits of a pointer to a Roc allocation that are always zero,
+## which recursive tag unions use to store their discriminant.
+pointerTagMask : Types -> Str
+pointerTagMask = \types ->
+    when (Types.target types).architecture is
+        Aarch64 | X86x64 -> "0x7"
+        Aarch32 | Wasm32 | X86x32 -> "0x3"
+
+roundUpToAlignment = \width, alignment ->
+    when alignment is
+        0 -> width
+        1 -> width
+        _ ->
+            if width % alignment > 0 then
+                width + alignment - (width % alignment)
+            else
+                width
+
+## These match the names of Zig's `std.Target.Cpu.Arch` tags.
+archName = \arch ->
+    when arch is
+        Aarch32 ->
+            "arm"
+
+        Aarch64 ->
+            "aarch64"
+
+        Wasm32 ->
+            "wasm32"
+
+        X86x32 ->
+            "x86"
+
+        X86x64 ->
+            "x86_64"
+
+fileHeader =
+    """
+    // ⚠️ GENERATED CODE ⚠️ - this entire file was generated by the `roc glue` CLI command
+
+    """
+
+archFileImports =
+    """
+
+    const std = @import("std");
+    const roc_std = @import("../roc_std.zig");
+    const RocStr = roc_std.RocStr;
+    const RocList = roc_std.RocList;
+    const RocDec = roc_std.RocDec;
+    const RocDict = roc_std.RocDict;
+    const RocSet = roc_std.RocSet;
+    const RocBox = roc_std.RocBox;
+    const RocResult = roc_std.RocResult;
+    const assertLayout = roc_std.assertLayout;
+    const assertOffset = roc_std.assertOffset;
+
+    """
+
+indent = "    "
+
+reservedKeywords = Set.fromList [
+    "addrspace",
+    "align",
+    "allowzero",
+    "and",
+    "anyframe",
+    "anytype",
+    "asm",
+    "async",
+    "await",
+    "break",
+    "callconv",
+    "catch",
+    "comptime",
+    "const",
+    "continue",
+    "defer",
+    "else",
+    "enum",
+    "errdefer",
+    "error",
+    "export",
+    "extern",
+    "fn",
+    "for",
+    "if",
+    "inline",
+    "linksection",
+    "noalias",
+    "noinline",
+    "nosuspend",
+    "opaque",
+    "or",
+    "orelse",
+    "packed",
+    "pub",
+    "resume",
+    "return",
+    "struct",
+    "suspend",
+    "switch",
+    "test",
+    "threadlocal",
+    "try",
+    "union",
+    "unreachable",
+    "usingnamespace",
+    "var",
+    "volatile",
+    "while",
+    # These aren't keywords, but primitives can only be shadowed by quoted identifiers.
+    "anyopaque",
+    "bool",
+    "false",
+    "null",
+    "true",
+    "type",
+    "undefined",
+    "void",
+]
+
+escapeKW = \input ->
+    # use a quoted identifier for this, to prevent a syntax error due to using a reserved keyword.
+    if Set.contains reservedKeywords input then
+        "@\"\(input)\""
+    else
+        input
+
+isUnit : Shape -> Bool
+isUnit = \shape ->
+    when shape is
+        Unit -> Bool.true
+        _ -> Bool.false
+
+toArgStr : List TypeId, Types, (TypeId, Shape, Nat -> Str) -> Str
+toArgStr = \args, types, fmt ->
+    List.walkWithIndex args "" \state, argId, index ->
+        shape = Types.shape types argId
+
+        # Drop `()` args; they aren't FFI-safe, and nothing will get passed anyway.
+        if isUnit shape then
+            state
+        else
+            argStr = fmt argId shape index
+
+            if Str.isEmpty state then
+                argStr # Don't prepend a comma if this is the first one
+            else
+                state
+                |> Str.concat ", "
+                |> Str.concat argStr
@@ -11,7 +11,10 @@ use roc_build::{
 };
 use roc_collections::MutMap;
 use roc_error_macros::todo_lambda_erasure;
-use roc_load::{ExecutionMode, FunctionKind, LoadConfig, LoadedModule, LoadingProblem, Threading};
+use roc_load::{
+    ExecutionMode, FunctionKind, LoadConfig, LoadedModule, LoadingProblem, MonoOptimizations,
+    Threading,
+};
 use roc_mono::ir::{generate_glue_procs, GlueProc, OptLevel};
 use roc_mono::layout::{GlobalLayoutInterner, LayoutCache, LayoutInterner};
 use roc_packaging::cache::{self, RocCacheDir};
@@ -374,6 +377,8 @@ pub fn load_types(
             palette: DEFAULT_PALETTE,
             threading,
             exec_mode: ExecutionMode::Check,
+            mono_optimizations: MonoOptimizations::NONE,
+            emit_debug_info: false,
         },
     )
     .unwrap_or_else(|problem| match problem {
@@ -451,7 +456,7 @@ pub fn load_types(
                 // Even though generate_glue_procs does more work than we need it to,
                 // it's important that we use it in order to make sure we get exactly
                 // the same names that mono::ir did for code gen!
-                for (layout, glue_procs) in answer.getters {
+                for (layout, tag_id, glue_procs) in answer.getters {
                     let mut names =
                         bumpalo::collections::Vec::with_capacity_in(glue_procs.len(), arena);
 
@@ -468,7 +473,7 @@ pub fn load_types(
                         names.push(name.as_str(&interns).to_string());
                     }
 
-                    glue_procs_by_layout.insert(layout, names.into_bump_slice());
+                    glue_procs_by_layout.insert((layout, tag_id), names.into_bump_slice());
                 }
             }
         }
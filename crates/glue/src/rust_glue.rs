@@ -1,6 +1,5 @@
 use crate::types::{
-    Accessors, File, RocFn, RocNum, RocSingleTagPayload, RocStructFields, RocTagUnion, RocType,
-    TypeId, Types,
+    File, RocFn, RocNum, RocSingleTagPayload, RocStructFields, RocTagUnion, RocType, TypeId, Types,
 };
 use indexmap::IndexMap;
 use roc_target::{Architecture, TargetInfo};
@@ -308,6 +307,19 @@ fn add_single_tag_struct(
 ) {
     let name = escape_kw(name.to_string());
 
+    if let RocSingleTagPayload::HasClosure { payload_getters } = payload {
+        add_single_tag_struct_with_closure(
+            &name,
+            tag_name,
+            payload_getters,
+            types,
+            impls,
+            target_info,
+        );
+
+        return;
+    }
+
     // Store single-tag unions as structs rather than enums,
     // because they have only one alternative. However, still
     // offer the usual tag union APIs.
@@ -317,44 +329,7 @@ fn add_single_tag_struct(
         // Make a dummy RocType::Struct so that we can pass it to deriving
         // and have that work out as normal.
         let struct_type = match payload {
-            RocSingleTagPayload::HasClosure { payload_getters } => {
-                {
-                    if payload_getters.is_empty() {
-                        // A single tag with no payload is a zero-sized unit type, so
-                        // represent it as a zero-sized struct (e.g. "struct Foo()").
-                        buf.push_str("();\n");
-                    } else {
-                        buf.push_str("{\n");
-
-                        for (_index, _getter_fn) in payload_getters.iter().enumerate() {
-                            // TODO these should be added as separate functions in the impl!
-                            todo!("TODO generate payload getters");
-                        }
-
-                        buf.push_str("}\n");
-                    }
-
-                    let fields = payload_getters
-                        .iter()
-                        .map(|(type_id, getter)| {
-                            (
-                                String::new(),
-                                *type_id,
-                                Accessors {
-                                    getter: getter.clone(),
-                                },
-                            )
-                        })
-                        .collect();
-
-                    RocType::Struct {
-                        // Deriving doesn't depend on the struct's name,
-                        // so no need to clone name here.
-                        name: String::new(),
-                        fields: RocStructFields::HasClosure { fields },
-                    }
-                }
-            }
+            RocSingleTagPayload::HasClosure { .. } => unreachable!(),
             RocSingleTagPayload::HasNoClosure {
                 payload_fields: payloads,
             } => {
@@ -548,7 +523,7 @@ fn add_single_tag_struct(
                 }
             }
         }
-        RocSingleTagPayload::HasClosure { payload_getters: _ } => todo!(),
+        RocSingleTagPayload::HasClosure { .. } => unreachable!(),
     }
 
     // The Debug impl for the single-tag union
@@ -582,7 +557,43 @@ fn add_single_tag_struct(
 
             add_decl(impls, opt_impl, target_info, buf);
         }
-        RocSingleTagPayload::HasClosure { payload_getters: _ } => todo!(),
+        RocSingleTagPayload::HasClosure { .. } => unreachable!(),
+    }
+}
+
+/// A single-tag union with a function in its payload. Only the Roc app knows its size,
+/// so this stores its bytes and reads the payload with getters.
+fn add_single_tag_struct_with_closure(
+    name: &str,
+    tag_name: &str,
+    payload_getters: &[(TypeId, String)],
+    types: &Types,
+    impls: &mut Impls,
+    target_info: TargetInfo,
+) {
+    add_refcounted_bytes(
+        name,
+        &format!(
+            r#"/// A tag named `{tag_name}`. Its payload contains a function, so only the Roc app
+/// knows its size, and the payload is read with getters that call into the app."#
+        ),
+        target_info,
+        impls,
+    );
+
+    for (index, (payload_id, getter)) in payload_getters.iter().enumerate() {
+        add_refcounted_bytes_getter(
+            name,
+            &format!("get_{tag_name}_{index}"),
+            &format!(
+                "/// Since `{name}` only has one tag (namely, `{tag_name}`),\n{INDENT}/// get its payload at index {index}."
+            ),
+            *payload_id,
+            getter,
+            types,
+            impls,
+            target_info,
+        );
     }
 }
 
@@ -662,6 +673,25 @@ fn add_tag_union(
     };
     let typ = types.get_type(type_id);
     let size_rounded_to_alignment = types.size_rounded_to_alignment(type_id);
+    let is_closure_payload = |opt_payload_id: &Option<TypeId>| {
+        opt_payload_id.map_or(false, |payload_id| {
+            payload_has_closure(types.get_type(payload_id))
+        })
+    };
+    // Only the Roc app knows the size of these payloads, so it has to create these tag unions,
+    // and the payloads are read with getters that call into the app.
+    let has_closure_payloads = tags
+        .iter()
+        .any(|(_, opt_payload_id)| is_closure_payload(opt_payload_id));
+
+    if recursiveness == Recursiveness::NonRecursive && has_closure_payloads {
+        // The payloads of a non-recursive tag union are stored in the tag union itself,
+        // so the host would not even know the size of the tag union.
+        panic!(
+            "Glue does not support functions in the payloads of non-recursive tag unions like `{name}` yet."
+        );
+    }
+
     let (actual_self, actual_self_mut, actual_other, union_name) = match recursiveness {
         Recursiveness::Recursive => (
             "(&*self.union_pointer())",
@@ -703,10 +733,18 @@ pub struct {name} {{
 
         // No #[derive(...)] for unions; we have to generate each impl ourselves!
         let mut buf = format!("#[repr(C)]\n{pub_str}union {decl_union_name} {{\n");
+        let mut has_fields = false;
 
         for (tag_name, opt_payload_id) in tags {
+            // Payloads with closures are only ever read by the Roc app.
+            if is_closure_payload(opt_payload_id) {
+                continue;
+            }
+
             // If there's no payload, we don't need a discriminant for it.
             if let Some(payload_id) = opt_payload_id {
+                has_fields = true;
+
                 let payload_type = types.get_type(*payload_id);
 
                 write!(buf, "{INDENT}{tag_name}: ").unwrap();
@@ -728,7 +766,7 @@ pub struct {name} {{
             }
         }
 
-        if tags.len() > 1 {
+        if tags.len() > 1 || !has_fields {
             // When there's a discriminant (so, multiple tags) and there is
             // no alignment padding after the largest variant,
             // the compiler will make extra room for the discriminant.
@@ -738,6 +776,8 @@ pub struct {name} {{
             // (Do this even if theoretically shouldn't be necessary, since
             // there's no runtime cost and it more explicitly syncs the
             // union's size with what we think it should be.)
+            //
+            // A union needs at least one field, too, even if all of its payloads have closures.
             writeln!(buf, "{INDENT}_sizer: [u8; {size_rounded_to_alignment}],").unwrap();
         }
 
@@ -1035,68 +1075,40 @@ pub struct {name} {{
                         payload_args = answer.payload_args;
                         args_to_payload = answer.args_to_payload;
                     }
-                    RocType::Struct {
-                        fields: RocStructFields::HasClosure { fields: _ },
-                        name: _,
-                    } => {
-                        todo!("struct");
-                    }
                     RocType::TagUnionPayload {
                         fields: RocStructFields::HasClosure { fields },
-                        name: _, // TODO call this payload_struct_name and use it to define the struct...or don't define it at all, maybe, since there are only getters and setters?
+                        name: _,
                     } => {
-                        // TODO don't generate op.into_StdoutWrite() - only getters/setters instead!
-                        for (field_name, field, accessor) in fields {
-                            let getter_name = &accessor.getter;
-                            let ret = type_name(*field, types);
-                            let returns_via_pointer = true;
-
-                            let body = if let RocType::Function(_) = types.get_type(*field) {
-                                format!(
-                                    r#"
-                                    extern "C" {{
-                                        #[link_name = "{getter_name}_size"]
-                                        fn size() -> usize;
-
-                                        #[link_name = "{getter_name}_generic"]
-                                        fn getter(_: *mut u8, _: *const {name});
-                                    }}
-
-                                    // allocate memory to store this variably-sized value
-                                    // allocates with roc_alloc, but that likely still uses the heap
-                                    let it = std::iter::repeat(0xAAu8).take(size());
-                                    let mut bytes = roc_std::RocList::from_iter(it);
-
-                                    getter(bytes.as_mut_ptr(), self);
-
-                                    {ret} {{
-                                        closure_data: bytes,
-                                    }}
-                                    "#
-                                )
-                            } else if returns_via_pointer {
-                                format!(
-                                    r#"
-                                    extern "C" {{
-                                        #[link_name = "{getter_name}_generic"]
-                                        fn getter(_: *mut {ret}, _: *const {name});
-                                    }}
-
-                                    let mut ret = core::mem::MaybeUninit::uninit();
-                                    getter(ret.as_mut_ptr(), self);
-                                    ret.assume_init()
-                                    "#
+                        for (field_name, field_id, accessors) in fields {
+                            let ret = type_name(*field_id, types);
+                            let body = getter_body(
+                                &accessors.getter,
+                                *field_id,
+                                &format!("roc_std::RocBox<{name}>"),
+                                "roc_std::RocBox::new(self)",
+                                types,
+                            );
+                            let (into_doc, get_doc, assertion) = if discriminant_size == 0 {
+                                (
+                                    format!("/// Convert this `{name}` to the payload at index {field_name} of its only tag, `{tag_name}`."),
+                                    format!("/// Return the payload at index {field_name} of this `{name}`'s only tag, `{tag_name}`."),
+                                    String::new(),
                                 )
                             } else {
-                                format!(
-                                    r#"
-                                    extern "C" {{
-                                        #[link_name = "{getter_name}"]
-                                        fn getter(_: *const {name}) -> {ret};
-                                    }}
-
-                                    getter(self)
-                                    "#
+                                (
+                                    format!(
+                                        r#"/// Unsafely assume this `{name}` has a `.discriminant()` of `{tag_name}` and convert it to its payload at index {field_name}.
+    /// (Always examine `.discriminant()` first to make sure this is the correct variant!)
+    /// Panics in debug builds if the `.discriminant()` doesn't return `{tag_name}`."#
+                                    ),
+                                    format!(
+                                        r#"/// Unsafely assume this `{name}` has a `.discriminant()` of `{tag_name}` and return its payload at index {field_name}.
+    /// (Always examine `.discriminant()` first to make sure this is the correct variant!)
+    /// Panics in debug builds if the `.discriminant()` doesn't return `{tag_name}`."#
+                                    ),
+                                    format!(
+                                        "\n        debug_assert_eq!(self.discriminant(), {discriminant_name}::{tag_name});\n"
+                                    ),
                                 )
                             };
 
@@ -1105,35 +1117,43 @@ pub struct {name} {{
                                 opt_impl.clone(),
                                 target_info,
                                 format!(
-                                    r#"/// Unsafely assume this `{name}` has a `.discriminant()` of `{tag_name}` and return its payload at index {field_name}.
-    /// (Always examine `.discriminant()` first to make sure this is the correct variant!)
-    /// Panics in debug builds if the `.discriminant()` doesn't return `{tag_name}`.
-    pub unsafe fn get_{tag_name}_{field_name}(&self) -> {ret} {{
-        debug_assert_eq!(self.discriminant(), {discriminant_name}::{tag_name});
+                                    r#"{into_doc}
+    pub unsafe fn into_{tag_name}_{field_name}(self) -> {ret} {{{assertion}
+{body}
+    }}"#,
+                                ),
+                            );
 
-        {body}
+                            add_decl(
+                                impls,
+                                opt_impl.clone(),
+                                target_info,
+                                format!(
+                                    r#"{get_doc}
+    pub unsafe fn get_{tag_name}_{field_name}(&self) -> {ret} {{
+        self.clone().into_{tag_name}_{field_name}()
     }}"#,
                                 ),
                             );
                         }
 
-                        // TODO revise these - they're all copy/pasted from somewhere else
-                        owned_ret_type = type_name(*payload_id, types);
-                        borrowed_ret_type = format!("&{owned_ret_type}");
-                        owned_ret = "payload".to_string();
-                        borrowed_ret = format!("&{owned_ret}");
-                        payload_args = format!("arg: {owned_ret_type}");
-                        args_to_payload = if cannot_derive_copy(payload_type, types) {
-                            "core::mem::ManuallyDrop::new(arg)".to_string()
-                        } else {
-                            "arg".to_string()
-                        };
+                        // Only the Roc app can create this tag, and its payload
+                        // is only read through the getters above.
+                        continue;
                     }
                     RocType::Unsized => todo!(),
-                    RocType::Function(RocFn { .. }) => todo!(),
+                    RocType::Struct {
+                        fields: RocStructFields::HasClosure { .. },
+                        ..
+                    }
+                    | RocType::Function(_) => {
+                        // Recursive tag unions always put their payloads in a TagUnionPayload.
+                        unreachable!("non-recursive tag unions with closures are rejected above")
+                    }
                 };
 
-                {
+                // The host can't allocate tag unions whose size only the Roc app knows.
+                if !has_closure_payloads {
                     let body = match recursiveness {
                         Recursiveness::Recursive => {
                             let pointer_val = if discriminant_size == 0 {
@@ -1332,6 +1352,10 @@ pub struct {name} {{
                 &mut drop_payload,
                 |tag_name, opt_payload_id| {
                     match opt_payload_id {
+                        Some(payload_id) if payload_has_closure(types.get_type(payload_id)) => {
+                            // These were released by the Roc app before we got here.
+                            "unreachable!(),".to_string()
+                        }
                         Some(payload_id)
                             if cannot_derive_copy(types.get_type(payload_id), types) =>
                         {
@@ -1347,12 +1371,48 @@ pub struct {name} {{
             );
         }
 
+        // Only the Roc app knows what payloads with closures contain, so give our reference
+        // to one of its getters; that releases the payload if it was the last reference.
+        let mut release_closure_payloads = String::new();
+
+        for (tag_name, opt_payload_id) in tags {
+            if !is_closure_payload(opt_payload_id) {
+                continue;
+            }
+
+            let release =
+                format!("unsafe {{ core::mem::drop(core::ptr::read(self).into_{tag_name}_0()) }}");
+
+            write!(
+                release_closure_payloads,
+                r#"if self.discriminant() == {discriminant_name}::{tag_name} {{
+            {release};
+
+            return;
+        }}
+
+        "#
+            )
+            .unwrap();
+        }
+
         // Drop works differently for recursive vs non-recursive tag unions.
         let drop_fn = match recursiveness {
+            Recursiveness::Recursive if discriminant_size == 0 && has_closure_payloads => {
+                let (tag_name, _) = tags.first().unwrap();
+
+                format!(
+                    r#"fn drop(&mut self) {{
+        // Only the Roc app knows what the payload contains, so give our reference
+        // to one of its getters; that releases the payload if it was the last reference.
+        unsafe {{ core::mem::drop(core::ptr::read(self).into_{tag_name}_0()) }}
+    }}"#
+                )
+            }
             Recursiveness::Recursive => {
                 format!(
                     r#"fn drop(&mut self) {{
-        // We only need to do any work if there's actually a heap-allocated payload.
+        {release_closure_payloads}// We only need to do any work if there's actually a heap-allocated payload.
         if let Some(storage) = self.storage() {{
             let mut new_storage = storage.get();
 
@@ -1388,8 +1448,12 @@ pub struct {name} {{
         add_decl(impls, opt_impl, target_info, drop_fn);
     }
 
+    // Functions can't be compared or hashed, so tag unions with them in their payloads
+    // don't get the impls that would need that.
+    let has_functions = has_functions(typ, types);
+
     // The PartialEq impl for the tag union
-    {
+    if !has_functions {
         let opt_impl_prefix = if has_float(typ, types) {
             String::new()
         } else {
@@ -1444,7 +1508,7 @@ pub struct {name} {{
     }
 
     // The PartialOrd impl for the tag union
-    {
+    if !has_functions {
         let opt_impl = Some(format!("impl PartialOrd for {name}"));
 
         let body = if discriminant_size == 0 {
@@ -1499,7 +1563,7 @@ pub struct {name} {{
     }
 
     // The Ord impl for the tag union
-    if !has_float(typ, types) {
+    if !has_functions && !has_float(typ, types) {
         let opt_impl = Some(format!("impl Ord for {name}"));
 
         let body = if discriminant_size == 0 {
@@ -1625,7 +1689,7 @@ pub struct {name} {{
     }
 
     // The Hash impl for the tag union
-    if !has_float(typ, types) {
+    if !has_functions && !has_float(typ, types) {
         let opt_impl = Some(format!("impl core::hash::Hash for {name}"));
         let mut buf = r#"fn hash<H: core::hash::Hasher>(&self, state: &mut H) {"#.to_string();
 
@@ -1681,7 +1745,29 @@ pub struct {name} {{
 "#
         );
 
-        if discriminant_size == 0 {
+        if discriminant_size == 0 && has_closure_payloads {
+            let (tag_name, opt_payload_id) = tags.first().unwrap();
+            let mut fields_str = String::new();
+
+            if let RocType::TagUnionPayload {
+                fields: RocStructFields::HasClosure { fields },
+                ..
+            } = types.get_type(opt_payload_id.unwrap())
+            {
+                for (label, _, _) in fields {
+                    let _ = write!(
+                        fields_str,
+                        "\n        .field(&self.get_{tag_name}_{label}())"
+                    );
+                }
+            }
+
+            let _ = write!(
+                buf,
+                r#"f.debug_tuple("{tag_name}"){fields_str}
+        .finish()"#,
+            );
+        } else if discriminant_size == 0 {
             let (tag_name, _) = tags.first().unwrap();
 
             // There's only one tag, so there's no discriminant and no need to match;
@@ -1738,15 +1824,24 @@ pub struct {name} {{
                                     ));
                                         }
                                     }
-                                    RocStructFields::HasClosure { fields: _ } => {
-                                        buf.push("// TODO HAS CLOSURE".to_string());
+                                    RocStructFields::HasClosure { fields } => {
+                                        // Only the getters know where these fields are.
+                                        for (label, _, _) in fields {
+                                            buf.push(format!(
+                                                ".field(&self.get_{tag_name}_{label}())"
+                                            ));
+                                        }
                                     }
                                 }
 
                                 buf.join("\n")
                             }
                             RocType::Unsized => todo!(),
-                            RocType::Function(RocFn { .. }) => todo!(),
+                            RocType::Function(RocFn { .. }) => {
+                                unreachable!(
+                                    "non-recursive tag unions with closures are rejected above"
+                                )
+                            }
                         };
 
                         format!(
@@ -1839,98 +1934,394 @@ fn add_enumeration<I: ExactSizeIterator<Item = S>, S: AsRef<str> + Display>(
     add_decl(impls, None, target_info, buf);
 }
 
-fn add_function(
-    // name: &str,
-    target_info: TargetInfo,
-    roc_fn: &RocFn,
-    types: &Types,
-    impls: &mut Impls,
-) {
+fn add_function(target_info: TargetInfo, roc_fn: &RocFn, types: &Types, impls: &mut Impls) {
+    // A toplevel function is the entry point itself, which the host calls directly.
+    // There are no closures of it to wrap, and no caller to call them with.
+    if roc_fn.is_toplevel {
+        return;
+    }
+
     let name = escape_kw(roc_fn.function_name.to_string());
-    // let derive = derive_str(types.get_type(struct_id), types, true);
-    let derive = "";
-    let pub_str = "pub ";
-    let mut buf;
+    let extern_name = &roc_fn.extern_name;
+    // e.g. `roc__mainForHost_0_caller` has `roc__mainForHost_0_size` next to it
+    let def_name = extern_name.trim_end_matches("_caller");
+    let ret = type_name(roc_fn.ret, types);
+
+    add_refcounted_bytes(
+        &name,
+        r#"/// A Roc function. Its captured values are stored in a refcounted allocation, whose
+/// size only the Roc app knows. Clones share that allocation, and the captured values
+/// are released when the last of them is dropped."#,
+        target_info,
+        impls,
+    );
+
+    let opt_impl = Some(format!("impl {name}"));
+
+    add_decl(
+        impls,
+        opt_impl.clone(),
+        target_info,
+        format!(
+            r#"/// The size of the values this function captures, in bytes.
+    pub fn closure_size() -> usize {{
+        extern "C" {{
+            #[link_name = "{def_name}_size"]
+            fn size() -> i64;
+        }}
 
-    let repr = "C";
-    buf = format!("{derive}\n#[repr({repr})]\n{pub_str}struct {name} {{\n");
+        unsafe {{ size() as usize }}
+    }}"#
+        ),
+    );
 
-    let fields = [("closure_data", &roc_fn.lambda_set)];
+    add_decl(
+        impls,
+        opt_impl.clone(),
+        target_info,
+        format!(
+            r#"/// The alignment of the values this function captures, in bytes.
+    pub fn closure_alignment() -> usize {{
+        extern "C" {{
+            #[link_name = "{def_name}_alignment"]
+            fn alignment() -> i64;
+        }}
+
+        unsafe {{ alignment() as usize }}
+    }}"#
+        ),
+    );
+
+    add_decl(
+        impls,
+        opt_impl.clone(),
+        target_info,
+        format!(
+            r#"/// The size of the value this function returns, in bytes.
+    pub fn result_size() -> usize {{
+        extern "C" {{
+            #[link_name = "{def_name}_result_size"]
+            fn size() -> i64;
+        }}
+
+        unsafe {{ size() as usize }}
+    }}"#
+        ),
+    );
 
-    for (label, type_id) in fields {
-        let type_str = type_name(*type_id, types);
+    let mut args = String::new();
+    let mut extern_args = String::new();
+    let mut manually_drop_args = String::new();
+    let mut call_args = String::new();
 
-        // Tag union payloads have numbered fields, so we prefix them
-        // with an "f" because Rust doesn't allow struct fields to be numbers.
-        let label = escape_kw(label.to_string());
+    for (i, arg_id) in roc_fn.args.iter().enumerate() {
+        let arg_type = type_name(*arg_id, types);
 
-        writeln!(buf, "{INDENT}pub {label}: {type_str},",).unwrap();
+        write!(args, ", arg_{i}: {arg_type}").unwrap();
+        write!(extern_args, "arg_{i}: *const {arg_type}, ").unwrap();
+        write!(
+            manually_drop_args,
+            "\n{INDENT}{INDENT}let arg_{i} = core::mem::ManuallyDrop::new(arg_{i});"
+        )
+        .unwrap();
+        write!(call_args, "&*arg_{i}, ").unwrap();
     }
 
-    buf.push('}');
+    add_decl(
+        impls,
+        opt_impl,
+        target_info,
+        format!(
+            r#"/// Call the function. Roc takes ownership of the arguments.
+    pub fn call(&self{args}) -> {ret} {{
+        extern "C" {{
+            fn {extern_name}({extern_args}closure_data: *const u8, output: *mut {ret});
+
+            #[link_name = "{def_name}_inc"]
+            fn inc_captures(closure_data: *mut u8);
+        }}
+{manually_drop_args}
+        let mut output = core::mem::MaybeUninit::uninit();
 
-    buf.push('\n');
-    buf.push('\n');
+        unsafe {{
+            // Roc takes ownership of the captured values too,
+            // so give it references of its own and keep ours for later calls.
+            inc_captures(self.pointer);
+            {extern_name}({call_args}self.pointer, output.as_mut_ptr());
 
-    let extern_name = &roc_fn.extern_name;
+            output.assume_init()
+        }}
+    }}"#
+        ),
+    );
+}
 
-    let return_type_str = type_name(roc_fn.ret, types);
+/// Add a type which owns a refcounted copy of some Roc value's bytes,
+/// for values like closures whose size only the Roc app knows.
+fn add_refcounted_bytes(name: &str, doc_comment: &str, target_info: TargetInfo, impls: &mut Impls) {
+    add_decl(
+        impls,
+        None,
+        target_info,
+        format!(
+            "{doc_comment}\n#[repr(transparent)]\npub struct {name} {{\n{INDENT}pointer: *mut u8,\n}}"
+        ),
+    );
 
-    writeln!(buf, "impl {name} {{").unwrap();
+    let opt_impl = Some(format!("impl {name}"));
 
-    write!(buf, "{INDENT}pub fn force_thunk(mut self").unwrap();
-    for (i, argument_type) in roc_fn.args.iter().enumerate() {
-        write!(buf, ", arg_{i}: {}", type_name(*argument_type, types)).unwrap();
-    }
-    writeln!(buf, ") -> {return_type_str} {{").unwrap();
+    add_decl(
+        impls,
+        opt_impl.clone(),
+        target_info,
+        format!(
+            r#"/// Allocate `size` bytes for a `{name}` and have `init` write it there,
+    /// e.g. by passing the pointer to a Roc getter. `dec` is called on those bytes
+    /// when the last reference to them is dropped, to release the values they contain.
+    pub unsafe fn from_roc(
+        size: usize,
+        alignment: usize,
+        dec: unsafe extern "C" fn(*mut u8),
+        init: impl FnOnce(*mut u8),
+    ) -> Self {{
+        // The refcount goes right before the bytes, like it does for a Roc `Box`.
+        // Before the refcount we store what `drop` needs to release the bytes again.
+        let alignment = alignment.max(core::mem::align_of::<usize>());
+        let header_size = Self::header_size(alignment);
+        let allocation = roc_std::roc_alloc(size + header_size, alignment as u32) as *mut u8;
+        let pointer = allocation.add(header_size);
+
+        (pointer as *mut roc_std::Storage)
+            .sub(1)
+            .write(roc_std::Storage::new_reference_counted());
+        (pointer as *mut usize).sub(2).write(alignment);
+        (pointer as *mut unsafe extern "C" fn(*mut u8))
+            .sub(3)
+            .write(dec);
+
+        init(pointer);
+
+        Self {{ pointer }}
+    }}"#
+        ),
+    );
 
-    writeln!(buf, "{INDENT}{INDENT}extern \"C\" {{").unwrap();
+    add_decl(
+        impls,
+        opt_impl.clone(),
+        target_info,
+        r#"/// Internal helper
+    fn header_size(alignment: usize) -> usize {
+        // The dec function, the alignment and the refcount, padded to the alignment
+        let size = 3 * core::mem::size_of::<usize>();
 
-    // fn extern_name(output: *mut return_type, arg1: arg1_type, ..., closure_data: *mut u8);
-    write!(buf, "{INDENT}{INDENT}{INDENT} fn {extern_name}(").unwrap();
+        (size + alignment - 1) & !(alignment - 1)
+    }"#
+        .to_string(),
+    );
 
-    for (i, argument_type) in roc_fn.args.iter().enumerate() {
-        write!(buf, "arg_{i}: &{}, ", type_name(*argument_type, types)).unwrap();
-    }
+    add_decl(
+        impls,
+        opt_impl,
+        target_info,
+        r#"/// Internal helper
+    fn storage(&self) -> &core::cell::Cell<roc_std::Storage> {
+        unsafe { &*(self.pointer as *const core::cell::Cell<roc_std::Storage>).sub(1) }
+    }"#
+        .to_string(),
+    );
 
-    writeln!(
-        buf,
-        "closure_data: *mut u8, output: *mut {return_type_str});"
-    )
-    .unwrap();
+    add_decl(
+        impls,
+        Some(format!("impl Clone for {name}")),
+        target_info,
+        r#"fn clone(&self) -> Self {
+        let mut new_storage = self.storage().get();
+        new_storage.increment_reference_count();
+        self.storage().set(new_storage);
 
-    // {argument_types} "
+        Self {
+            pointer: self.pointer,
+        }
+    }"#
+        .to_string(),
+    );
 
-    writeln!(buf, "{INDENT}{INDENT}}}").unwrap();
+    add_decl(
+        impls,
+        Some(format!("impl Drop for {name}")),
+        target_info,
+        r#"fn drop(&mut self) {
+        let storage = self.storage();
+        let mut new_storage = storage.get();
 
-    writeln!(buf).unwrap();
+        // Decrement the refcount
+        let needs_dealloc = !new_storage.is_readonly() && new_storage.decrease();
 
-    writeln!(
-        buf,
-        "{INDENT}{INDENT}let mut output = std::mem::MaybeUninit::uninit();"
-    )
-    .unwrap();
+        if needs_dealloc {
+            unsafe {
+                let alignment = (self.pointer as *const usize).sub(2).read();
+                let dec = (self.pointer as *const unsafe extern "C" fn(*mut u8))
+                    .sub(3)
+                    .read();
 
-    writeln!(
-        buf,
-        "{INDENT}{INDENT}let ptr = self.closure_data.as_mut_ptr();"
+                // Only Roc knows what's in these bytes, so have it release their values.
+                dec(self.pointer);
+
+                let allocation = self.pointer.sub(Self::header_size(alignment));
+                roc_std::roc_dealloc(allocation.cast(), alignment as u32);
+            }
+        } else {
+            // Write the storage back.
+            storage.set(new_storage);
+        }
+    }"#
+        .to_string(),
+    );
+
+    add_decl(
+        impls,
+        Some(format!("impl core::fmt::Debug for {name}")),
+        target_info,
+        format!(
+            r#"fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {{
+        f.debug_struct("{name}").finish_non_exhaustive()
+    }}"#
+        ),
+    );
+}
+
+/// The body of a getter for a value whose size only the Roc app knows, such as a closure.
+/// `{getter_name}_size` and `{getter_name}_alignment` tell us how to allocate it,
+/// and `{getter_name}_dec` releases the values it contains.
+fn refcounted_bytes_getter_body(getter_name: &str, ret: &str, arg_type: &str, arg: &str) -> String {
+    format!(
+        r#"
+        extern "C" {{
+            #[link_name = "{getter_name}_size"]
+            fn size() -> i64;
+
+            #[link_name = "{getter_name}_alignment"]
+            fn alignment() -> i64;
+
+            #[link_name = "{getter_name}_dec"]
+            fn dec(_: *mut u8);
+
+            #[link_name = "{getter_name}_generic"]
+            fn getter(_: *mut u8, _: {arg_type});
+        }}
+
+        {ret}::from_roc(size() as usize, alignment() as usize, dec, |pointer| {{
+            getter(pointer, {arg})
+        }})
+        "#
     )
-    .unwrap();
+}
 
-    write!(buf, "{INDENT}{INDENT}unsafe {{ {extern_name}(").unwrap();
+/// The body of a method which passes `arg` to the given Roc getter, and returns what it gets.
+fn getter_body(
+    getter_name: &str,
+    field_id: TypeId,
+    arg_type: &str,
+    arg: &str,
+    types: &Types,
+) -> String {
+    let ret = type_name(field_id, types);
 
-    for (i, _) in roc_fn.args.iter().enumerate() {
-        write!(buf, "&arg_{i}, ").unwrap();
-    }
+    let body = if is_refcounted_bytes(types.get_type(field_id)) {
+        refcounted_bytes_getter_body(getter_name, &ret, arg_type, arg)
+    } else {
+        format!(
+            r#"
+        extern "C" {{
+            #[link_name = "{getter_name}_generic"]
+            fn getter(_: *mut {ret}, _: {arg_type});
+        }}
 
-    writeln!(buf, "ptr, output.as_mut_ptr()) }};").unwrap();
+        let mut ret = core::mem::MaybeUninit::uninit();
+        getter(ret.as_mut_ptr(), {arg});
+        ret.assume_init()
+        "#
+        )
+    };
 
-    writeln!(buf, "{INDENT}{INDENT}unsafe {{ output.assume_init() }}").unwrap();
+    body.trim_matches('\n').trim_end().to_string()
+}
 
-    writeln!(buf, "{INDENT}}}").unwrap();
-    buf.push('}');
+/// Whether only the Roc app knows the size of a tag union payload, because it has a closure in it
+fn payload_has_closure(roc_type: &RocType) -> bool {
+    is_refcounted_bytes(roc_type)
+        || matches!(
+            roc_type,
+            RocType::TagUnionPayload {
+                fields: RocStructFields::HasClosure { .. },
+                ..
+            }
+        )
+}
 
-    add_decl(impls, None, target_info, buf);
+/// Whether a type is generated with `add_refcounted_bytes`
+fn is_refcounted_bytes(roc_type: &RocType) -> bool {
+    matches!(
+        roc_type,
+        RocType::Function(RocFn {
+            is_toplevel: false,
+            ..
+        }) | RocType::Struct {
+            fields: RocStructFields::HasClosure { .. },
+            ..
+        } | RocType::TagUnion(RocTagUnion::SingleTagStruct {
+            payload: RocSingleTagPayload::HasClosure { .. },
+            ..
+        })
+    )
+}
+
+/// Add a getter to a type from `add_refcounted_bytes`, which calls the given Roc getter.
+/// Roc getters take the value as a `Box`, which is what the refcounted bytes already look like.
+#[allow(clippy::too_many_arguments)]
+fn add_refcounted_bytes_getter(
+    name: &str,
+    method_name: &str,
+    doc_comment: &str,
+    field_id: TypeId,
+    getter_name: &str,
+    types: &Types,
+    impls: &mut Impls,
+    target_info: TargetInfo,
+) {
+    let ret = type_name(field_id, types);
+    let body = getter_body(getter_name, field_id, "*mut u8", "self.pointer", types);
+
+    // Indent the body one more level, since it goes in an `unsafe` block
+    let body = body
+        .lines()
+        .map(|line| {
+            if line.is_empty() {
+                String::new()
+            } else {
+                format!("{INDENT}{line}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    add_decl(
+        impls,
+        Some(format!("impl {name}")),
+        target_info,
+        format!(
+            r#"{doc_comment}
+    pub fn {method_name}(&self) -> {ret} {{
+        // The getter takes ownership of its argument, so give it a reference of its own.
+        core::mem::forget(self.clone());
+
+        unsafe {{
+{body}
+        }}
+    }}"#
+        ),
+    );
 }
 
 fn add_struct(
@@ -1973,8 +2364,38 @@ fn add_struct(
 
             buf.push('}');
         }
-        RocStructFields::HasClosure { fields: _ } => {
-            buf = "//TODO HAS CLOSURE 2".to_string();
+        RocStructFields::HasClosure { fields } => {
+            // Tag union payloads are read through the tag union's own getters.
+            if is_tag_union_payload {
+                return;
+            }
+
+            add_refcounted_bytes(
+                &name,
+                &format!(
+                    r#"/// A `{name}` record. It contains a function, so only the Roc app knows its size,
+/// and its fields are read with getters that call into the app."#
+                ),
+                target_info,
+                impls,
+            );
+
+            for (label, field_id, accessors) in fields {
+                let label = escape_kw(label.to_string());
+
+                add_refcounted_bytes_getter(
+                    &name,
+                    &format!("get_{label}"),
+                    &format!("/// Get the `{label}` field."),
+                    *field_id,
+                    &accessors.getter,
+                    types,
+                    impls,
+                    target_info,
+                );
+            }
+
+            return;
         }
     }
 
@@ -2184,6 +2605,22 @@ fn add_nullable_unwrapped(
 
     let discriminant_name = add_discriminant(name, target_info, tag_names, 1, types, impls);
     let payload_type = types.get_type(non_null_payload);
+
+    if payload_has_closure(payload_type) {
+        add_nullable_unwrapped_with_closure(
+            name,
+            target_info,
+            &discriminant_name,
+            null_tag,
+            non_null_tag,
+            payload_type,
+            types,
+            impls,
+        );
+
+        return;
+    }
+
     let payload_type_name = type_name(non_null_payload, types);
     let cannot_derive_copy = cannot_derive_copy(payload_type, types);
 
@@ -2274,7 +2711,9 @@ pub struct {name} {{
                     borrowed_ret_type = answer.borrowed_ret_type;
                 }
 
-                RocStructFields::HasClosure { .. } => todo!(),
+                RocStructFields::HasClosure { .. } => {
+                    unreachable!("handled by add_nullable_unwrapped_with_closure")
+                }
             },
             RocType::TagUnionPayload { fields, name } => match fields {
                 RocStructFields::HasNoClosure { fields } => {
@@ -2287,9 +2726,13 @@ pub struct {name} {{
                     owned_ret_type = answer.owned_ret_type;
                     borrowed_ret_type = answer.borrowed_ret_type;
                 }
-                RocStructFields::HasClosure { .. } => todo!(),
+                RocStructFields::HasClosure { .. } => {
+                    unreachable!("handled by add_nullable_unwrapped_with_closure")
+                }
             },
-            RocType::Function { .. } => todo!(),
+            RocType::Function { .. } => {
+                unreachable!("handled by add_nullable_unwrapped_with_closure")
+            }
             RocType::Unsized => todo!(),
         };
 
@@ -2523,7 +2966,9 @@ pub struct {name} {{
                             buf.push(format!(".field(&(&*{extra_deref}self.pointer).{label})"));
                         }
                     }
-                    RocStructFields::HasClosure { fields: _ } => todo!(),
+                    RocStructFields::HasClosure { fields: _ } => {
+                        unreachable!("handled by add_nullable_unwrapped_with_closure")
+                    }
                 }
 
                 buf.join(&format!("\n{INDENT}{INDENT}{INDENT}{INDENT}{INDENT}"))
@@ -2538,13 +2983,17 @@ pub struct {name} {{
                             buf.push(format!(".field(&(&*{extra_deref}self.pointer).f{label})"));
                         }
                     }
-                    RocStructFields::HasClosure { fields: _ } => todo!(),
+                    RocStructFields::HasClosure { fields: _ } => {
+                        unreachable!("handled by add_nullable_unwrapped_with_closure")
+                    }
                 }
 
                 buf.join(&format!("\n{INDENT}{INDENT}{INDENT}{INDENT}{INDENT}"))
             }
             RocType::Unsized => todo!(),
-            RocType::Function { .. } => todo!(),
+            RocType::Function { .. } => {
+                unreachable!("handled by add_nullable_unwrapped_with_closure")
+            }
         };
 
         let body = format!(
@@ -2567,6 +3016,193 @@ pub struct {name} {{
     }
 }
 
+/// A nullable-unwrapped tag union whose non-null payload has a closure in it. Only the Roc app
+/// knows the size of that payload, so the host can't construct the non-null tag, and it reads
+/// the payload with getters that call into the app.
+#[allow(clippy::too_many_arguments)]
+fn add_nullable_unwrapped_with_closure(
+    name: &str,
+    target_info: TargetInfo,
+    discriminant_name: &str,
+    null_tag: &str,
+    non_null_tag: &str,
+    payload_type: &RocType,
+    types: &Types,
+    impls: &mut Impls,
+) {
+    let fields = match payload_type {
+        RocType::TagUnionPayload {
+            fields: RocStructFields::HasClosure { fields },
+            ..
+        } => fields,
+        _ => panic!(
+            "Glue does not support a function as the only payload of `{name}`'s `{non_null_tag}` tag yet."
+        ),
+    };
+
+    // The opaque struct for the tag union. Functions can't be compared or hashed,
+    // so there are no derives.
+    add_decl(
+        impls,
+        None,
+        target_info,
+        format!(
+            r#"#[repr(transparent)]
+pub struct {name} {{
+    pointer: *mut u8,
+}}"#
+        ),
+    );
+
+    // The impl for the tag union
+    {
+        let opt_impl = Some(format!("impl {name}"));
+
+        add_decl(
+            impls,
+            opt_impl.clone(),
+            target_info,
+            RECURSIVE_TAG_UNION_STORAGE.to_string(),
+        );
+
+        add_decl(
+            impls,
+            opt_impl.clone(),
+            target_info,
+            format!(
+                r#"{DISCRIMINANT_DOC_COMMENT}
+    pub fn discriminant(&self) -> {discriminant_name} {{
+        if self.pointer.is_null() {{
+            {discriminant_name}::{null_tag}
+        }} else {{
+            {discriminant_name}::{non_null_tag}
+        }}
+    }}"#
+            ),
+        );
+
+        for (field_name, field_id, accessors) in fields {
+            let ret = type_name(*field_id, types);
+            let body = getter_body(
+                &accessors.getter,
+                *field_id,
+                &format!("roc_std::RocBox<{name}>"),
+                "roc_std::RocBox::new(self)",
+                types,
+            );
+
+            add_decl(
+                impls,
+                opt_impl.clone(),
+                target_info,
+                format!(
+                    r#"/// Unsafely assume this `{name}` has a `.discriminant()` of `{non_null_tag}` and convert it to its payload at index {field_name}.
+    /// (Always examine `.discriminant()` first to make sure this is the correct variant!)
+    /// Panics in debug builds if the `.discriminant()` doesn't return `{non_null_tag}`.
+    pub unsafe fn into_{non_null_tag}_{field_name}(self) -> {ret} {{
+        debug_assert_eq!(self.discriminant(), {discriminant_name}::{non_null_tag});
+
+{body}
+    }}"#,
+                ),
+            );
+
+            add_decl(
+                impls,
+                opt_impl.clone(),
+                target_info,
+                format!(
+                    r#"/// Unsafely assume this `{name}` has a `.discriminant()` of `{non_null_tag}` and return its payload at index {field_name}.
+    /// (Always examine `.discriminant()` first to make sure this is the correct variant!)
+    /// Panics in debug builds if the `.discriminant()` doesn't return `{non_null_tag}`.
+    pub unsafe fn get_{non_null_tag}_{field_name}(&self) -> {ret} {{
+        self.clone().into_{non_null_tag}_{field_name}()
+    }}"#,
+                ),
+            );
+        }
+
+        add_decl(
+            impls,
+            opt_impl.clone(),
+            target_info,
+            format!(
+                r#"/// A tag named {null_tag}, which has no payload.
+    pub const {null_tag}: Self = Self {{
+        pointer: core::ptr::null_mut(),
+    }};"#,
+            ),
+        );
+
+        add_decl(
+            impls,
+            opt_impl,
+            target_info,
+            format!(
+                r#"/// Other `into_` methods return a payload, but since the {null_tag} tag
+    /// has no payload, this does nothing and is only here for completeness.
+    pub fn into_{null_tag}(self) {{
+        ()
+    }}"#,
+            ),
+        );
+    }
+
+    // The Clone impl for the tag union
+    add_decl(
+        impls,
+        Some(format!("impl Clone for {name}")),
+        target_info,
+        RECURSIVE_TAG_UNION_CLONE.to_string(),
+    );
+
+    // The Drop impl for the tag union
+    add_decl(
+        impls,
+        Some(format!("impl Drop for {name}")),
+        target_info,
+        format!(
+            r#"fn drop(&mut self) {{
+        // Only the Roc app knows what the payload contains, so give our reference
+        // to one of its getters; that releases the payload if it was the last reference.
+        if !self.pointer.is_null() {{
+            unsafe {{ core::mem::drop(core::ptr::read(self).into_{non_null_tag}_0()) }}
+        }}
+    }}"#
+        ),
+    );
+
+    // The Debug impl for the tag union
+    {
+        let fields_str = fields
+            .iter()
+            .map(|(label, _, _)| format!(".field(&self.get_{non_null_tag}_{label}())"))
+            .collect::<Vec<_>>()
+            .join(&format!("\n{INDENT}{INDENT}{INDENT}{INDENT}{INDENT}"));
+
+        add_decl(
+            impls,
+            Some(format!("impl core::fmt::Debug for {name}")),
+            target_info,
+            format!(
+                r#"fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {{
+        if self.pointer.is_null() {{
+            f.write_str("{name}::{null_tag}")
+        }} else {{
+            f.write_str("{name}::")?;
+
+            unsafe {{
+                f.debug_tuple("{non_null_tag}")
+                    {fields_str}
+                    .finish()
+            }}
+        }}
+    }}"#
+            ),
+        );
+    }
+}
+
 fn arch_to_str(architecture: Architecture) -> &'static str {
     match architecture {
         Architecture::X86_64 => "x86_64",
@@ -2785,8 +3421,7 @@ fn cannot_derive_copy(roc_type: &RocType, types: &Types) -> bool {
         | RocType::Bool
         | RocType::Num(_)
         | RocType::TagUnion(RocTagUnion::Enumeration { .. })
-        | RocType::Unsized
-        | RocType::Function { .. } => false,
+        | RocType::Unsized => false,
         RocType::RocStr
         | RocType::RocList(_)
         | RocType::RocDict(_, _)
@@ -2796,7 +3431,8 @@ fn cannot_derive_copy(roc_type: &RocType, types: &Types) -> bool {
         | RocType::TagUnion(RocTagUnion::NullableWrapped { .. })
         | RocType::TagUnion(RocTagUnion::Recursive { .. })
         | RocType::RecursivePointer { .. }
-        | RocType::TagUnion(RocTagUnion::NonNullableUnwrapped { .. }) => true,
+        | RocType::TagUnion(RocTagUnion::NonNullableUnwrapped { .. })
+        | RocType::Function { .. } => true,
         RocType::TagUnion(RocTagUnion::SingleTagStruct {
             payload: RocSingleTagPayload::HasNoClosure { payload_fields },
             ..
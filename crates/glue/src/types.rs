@@ -17,7 +17,8 @@ use roc_mono::{
     ir::LambdaSetId,
     layout::{
         cmp_fields, ext_var_is_empty_tag_union, round_up_to_alignment, Builtin, Discriminant,
-        InLayout, Layout, LayoutCache, LayoutInterner, LayoutRepr, TLLayoutInterner, UnionLayout,
+        InLayout, Layout, LayoutCache, LayoutInterner, LayoutRepr, TLLayoutInterner, TagIdIntType,
+        UnionLayout,
     },
 };
 use roc_target::{Architecture, OperatingSystem, TargetInfo};
@@ -96,7 +97,7 @@ impl Types {
         arena: &'a Bump,
         subs: &'a Subs,
         interns: &'a Interns,
-        glue_procs_by_layout: MutMap<Layout<'a>, &'a [String]>,
+        glue_procs_by_layout: MutMap<(Layout<'a>, Option<TagIdIntType>), &'a [String]>,
         layout_cache: LayoutCache<'a>,
         target: TargetInfo,
         mut entry_points: MutMap<Symbol, Variable>,
@@ -1163,7 +1164,7 @@ struct Env<'a> {
     arena: &'a Bump,
     subs: &'a Subs,
     layout_cache: LayoutCache<'a>,
-    glue_procs_by_layout: MutMap<Layout<'a>, &'a [String]>,
+    glue_procs_by_layout: MutMap<(Layout<'a>, Option<TagIdIntType>), &'a [String]>,
     lambda_set_ids: MutMap<Variable, LambdaSetId>,
     interns: &'a Interns,
     struct_names: Structs,
@@ -1178,7 +1179,7 @@ impl<'a> Env<'a> {
         subs: &'a Subs,
         interns: &'a Interns,
         layout_interner: TLLayoutInterner<'a>,
-        glue_procs_by_layout: MutMap<Layout<'a>, &'a [String]>,
+        glue_procs_by_layout: MutMap<(Layout<'a>, Option<TagIdIntType>), &'a [String]>,
         target: TargetInfo,
     ) -> Self {
         Env {
@@ -1367,7 +1368,7 @@ fn add_type_help<'a>(
                 None => env.struct_names.get_name(var),
             };
 
-            add_struct(env, name, it, types, layout, |name, fields| {
+            add_struct(env, name, it, types, layout, None, |name, fields| {
                 RocType::Struct { name, fields }
             })
         }
@@ -1383,6 +1384,25 @@ fn add_type_help<'a>(
 
             add_tag_union(env, opt_name, tags, var, types, layout, Some(rec_root))
         }
+        Content::Structure(FlatType::Apply(Symbol::BOX_BOX_TYPE, args)) => {
+            let args = subs.get_subs_slice(*args);
+            debug_assert_eq!(args.len(), 1);
+
+            let elem_layout = match env.layout_cache.get_repr(layout) {
+                LayoutRepr::Union(UnionLayout::NonNullableUnwrapped([elem_layout])) => *elem_layout,
+                other => {
+                    unreachable!("Box should have a NonNullableUnwrapped layout, not {other:?}")
+                }
+            };
+
+            let elem_id = add_type_help(env, elem_layout, args[0], None, types);
+            let box_id =
+                types.add_anonymous(&env.layout_cache.interner, RocType::RocBox(elem_id), layout);
+
+            types.depends(box_id, elem_id);
+
+            box_id
+        }
         Content::Structure(FlatType::Apply(symbol, _)) => match env.layout_cache.get_repr(layout) {
             LayoutRepr::Builtin(builtin) => {
                 add_builtin_type(env, builtin, var, opt_name, types, layout)
@@ -1802,6 +1822,7 @@ fn add_struct<'a, I, L, F>(
     fields: I,
     types: &mut Types,
     in_layout: InLayout<'a>,
+    tag_id: Option<TagIdIntType>,
     to_type: F,
 ) -> TypeId
 where
@@ -1835,10 +1856,10 @@ where
         )
     });
 
-    // This layout should have an entry in glue_procs_by_layout iff it
+    // This layout (or tag of it) should have an entry in glue_procs_by_layout iff it
     // contains closures, but we'll double-check that with a debug_assert.
     let layout = env.layout_cache.interner.get(in_layout);
-    let struct_fields = match env.glue_procs_by_layout.get(&layout) {
+    let struct_fields = match env.glue_procs_by_layout.get(&(layout, tag_id)) {
         Some(&glue_procs) => {
             debug_assert!(env
                 .layout_cache
@@ -1977,7 +1998,7 @@ fn tag_union_type_from_layout<'a>(
                 NonNullableUnwrapped(_) => {
                     let (tag_name, payload_vars) = single_tag_payload(union_tags, subs);
                     let (tag_name, opt_payload) =
-                        tag_to_type(&name, env, tag_name, payload_vars, types, layout, true);
+                        tag_to_type(&name, env, tag_name, 0, payload_vars, types, layout, true);
 
                     // A recursive tag union with just one constructor
                     // Optimization: No need to store a tag ID (the payload is "unwrapped")
@@ -2021,12 +2042,26 @@ fn tag_union_type_from_layout<'a>(
                 // A recursive tag union with only two variants, where one is empty.
                 // Optimizations: Use null for the empty variant AND don't store a tag ID for the other variant.
                 // e.g. `ConsList a : [Nil, Cons a (ConsList a)]`
+                //
+                // A non-recursive `[None, Some (Box a)]` has the same layout: the box pointer is
+                // the union, so the non-null payload is the `a` behind it.
                 NullableUnwrapped {
                     nullable_id: null_represents_first_tag,
                     other_fields: _, // TODO use this!
                 } => {
-                    let mut tags =
-                        union_tags_to_types(&name, union_tags, subs, env, types, layout, true);
+                    let is_nullable_box = matches!(
+                        subs.get_content_without_compacting(var),
+                        Content::Structure(FlatType::TagUnion(_, _))
+                    );
+                    let mut tags = union_tags_to_types(
+                        &name,
+                        union_tags,
+                        subs,
+                        env,
+                        types,
+                        layout,
+                        !is_nullable_box,
+                    );
                     // NullableUnwrapped tag unions should always have exactly 2 tags.
                     debug_assert_eq!(tags.len(), 2);
 
@@ -2044,7 +2079,14 @@ fn tag_union_type_from_layout<'a>(
                         null_tag = tags.pop().unwrap().0;
                     }
 
-                    let (non_null_tag, non_null_payload) = non_null;
+                    let (non_null_tag, mut non_null_payload) = non_null;
+
+                    if is_nullable_box {
+                        non_null_payload = match non_null_payload.map(|id| types.get_type(id)) {
+                            Some(RocType::RocBox(elem)) => Some(*elem),
+                            other => unreachable!("nullable box with payload {:?}", other),
+                        };
+                    }
 
                     RocTagUnion::NullableUnwrapped {
                         name: name.clone(),
@@ -2177,15 +2219,17 @@ fn union_tags_to_types<'a>(
         })
         .collect();
 
-    // Sort tags alphabetically by tag name
+    // Sort tags alphabetically by tag name, which makes their index their tag id
     tags.sort_by(|(name1, _), (name2, _)| name1.cmp(name2));
 
     tags.into_iter()
-        .map(|(tag_name, payload_vars)| {
+        .enumerate()
+        .map(|(tag_id, (tag_name, payload_vars))| {
             tag_to_type(
                 name,
                 env,
                 tag_name,
+                tag_id as TagIdIntType,
                 &payload_vars,
                 types,
                 layout,
@@ -2221,13 +2265,13 @@ fn single_tag_payload_fields<'a, 'b>(
     // so we shouldn't need to separately check that. Howeevr, we still do a debug_assert
     // anyway just so we have some warning in case that relationship somehow didn't hold!
     debug_assert_eq!(
-        env.glue_procs_by_layout.get(&layout).is_some(),
+        env.glue_procs_by_layout.get(&(layout, None)).is_some(),
         env.layout_cache
             .interner
             .has_varying_stack_size(in_layout, env.arena),
         "glue_procs_by_layout for {:?} was {:?}, but the layout cache said its has_varying_stack_size was {}",
             &layout,
-            env.glue_procs_by_layout.get(&layout),
+            env.glue_procs_by_layout.get(&(layout, None)),
             env.layout_cache
                 .interner
                 .has_varying_stack_size(in_layout, env.arena)
@@ -2235,7 +2279,7 @@ fn single_tag_payload_fields<'a, 'b>(
 
     let (tag_name, payload_vars) = single_tag_payload(union_tags, subs);
 
-    let payload = match env.glue_procs_by_layout.get(&layout) {
+    let payload = match env.glue_procs_by_layout.get(&(layout, None)) {
         Some(glue_procs) => {
             let payload_getters = payload_vars
                 .iter()
@@ -2264,10 +2308,12 @@ fn single_tag_payload_fields<'a, 'b>(
     (tag_name, payload)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn tag_to_type<'a, D: Display>(
     name: &str,
     env: &mut Env<'a>,
     tag_name: D,
+    tag_id: TagIdIntType,
     payload_vars: &[Variable],
     types: &mut Types,
     layout: InLayout<'a>,
@@ -2295,9 +2341,15 @@ fn tag_to_type<'a, D: Display>(
             // create a RocType for the payload and save it
             let struct_name = format!("{}_{}", &name, tag_name); // e.g. "MyUnion_MyVariant"
             let fields = payload_vars.iter().copied().enumerate();
-            let struct_id = add_struct(env, struct_name, fields, types, layout, |name, fields| {
-                RocType::TagUnionPayload { name, fields }
-            });
+            let struct_id = add_struct(
+                env,
+                struct_name,
+                fields,
+                types,
+                layout,
+                Some(tag_id),
+                |name, fields| RocType::TagUnionPayload { name, fields },
+            );
 
             (tag_name, Some(struct_id))
         }
@@ -0,0 +1,104 @@
+// C representations of Roc's builtin data structures.
+
+#ifndef ROC_STD_H
+#define ROC_STD_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+// The generated headers use C11's spellings; map them to C++'s.
+#ifdef __cplusplus
+#define _Alignas(n) alignas(n)
+#define _Alignof(t) alignof(t)
+#define _Static_assert(cond, msg) static_assert(cond, msg)
+#endif
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+// The host must provide these; the Roc application calls them for
+// memory management and to report a crash.
+void* roc_alloc(size_t size, unsigned int alignment);
+void* roc_realloc(void* ptr, size_t new_size, size_t old_size, unsigned int alignment);
+void roc_dealloc(void* ptr, unsigned int alignment);
+void roc_panic(void* msg, unsigned int tag_id);
+void* roc_memset(void* dest, int c, size_t n);
+
+// A Roc `Str`. Strings shorter than `sizeof(RocStr)` are stored inline
+// (the "small string optimization"), and a string that is a slice of another
+// one (a "seamless slice") sets the top bit of `len`; use `roc_str_len` and
+// `roc_str_bytes` rather than reading the fields directly.
+typedef struct RocStr {
+    char* bytes;
+    size_t len;
+    size_t capacity;
+} RocStr;
+
+static inline bool roc_str_is_small(const RocStr* str) {
+    return ((intptr_t)str->capacity) < 0;
+}
+
+static inline size_t roc_str_len(const RocStr* str) {
+    if (roc_str_is_small(str)) {
+        const unsigned char* bytes = (const unsigned char*)str;
+
+        return (size_t)(bytes[sizeof(RocStr) - 1] ^ 0x80);
+    } else {
+        // Clear the bit that marks a seamless slice.
+        return str->len & ~((size_t)1 << (sizeof(size_t) * 8 - 1));
+    }
+}
+
+static inline const char* roc_str_bytes(const RocStr* str) {
+    if (roc_str_is_small(str)) {
+        return (const char*)str;
+    } else {
+        return str->bytes;
+    }
+}
+
+// A Roc `List`. The element type is erased; `elements` points to
+// `len` contiguous elements of the list's element type.
+typedef struct RocList {
+    void* elements;
+    size_t len;
+    size_t capacity;
+} RocList;
+
+// A Roc `Dict`. Its key-value pairs are stored, in insertion order, in `data`.
+typedef struct RocDict {
+    RocList data;
+    RocList data_indices;
+    RocList metadata;
+    size_t size;
+} RocDict;
+
+// A Roc `Set` is stored as a `Dict` with `{}` values.
+typedef RocDict RocSet;
+
+// A Roc `Box` is a pointer to its (reference-counted) contents.
+typedef void* RocBox;
+
+typedef struct RocI128 {
+    _Alignas(16) uint64_t lo;
+    uint64_t hi;
+} RocI128;
+
+typedef struct RocU128 {
+    _Alignas(16) uint64_t lo;
+    uint64_t hi;
+} RocU128;
+
+// A Roc `Dec`: a 128-bit fixed-point number with 18 decimal places.
+typedef struct RocDec {
+    _Alignas(16) uint64_t lo;
+    uint64_t hi;
+} RocDec;
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif // ROC_STD_H
@@ -0,0 +1,62 @@
+//! Zig representations of Roc's builtin data structures.
+
+const std = @import("std");
+const glue = @import("glue");
+
+/// A Roc `Str`, as defined by the Roc builtins.
+pub const RocStr = glue.str.RocStr;
+
+/// A Roc `List`, as defined by the Roc builtins. The element type is erased;
+/// use `elements` to get a pointer to the list's elements.
+pub const RocList = glue.list.RocList;
+
+/// A Roc `Dec`: a 128-bit fixed-point number with 18 decimal places.
+pub const RocDec = glue.dec.RocDec;
+
+/// A Roc `Dict`. Its key-value pairs are stored, in insertion order, in `data`.
+pub const RocDict = extern struct {
+    data: RocList,
+    data_indices: RocList,
+    metadata: RocList,
+    size: usize,
+};
+
+/// A Roc `Set` is stored as a `Dict` with `{}` values.
+pub const RocSet = RocDict;
+
+/// A Roc `Box` is a pointer to its (reference-counted) contents.
+pub const RocBox = *anyopaque;
+
+/// A Roc `Result ok err`.
+pub fn RocResult(comptime Ok: type, comptime Err: type) type {
+    return extern struct {
+        payload: extern union {
+            ok: ResultPayload(Ok),
+            err: ResultPayload(Err),
+        },
+        is_ok: bool,
+    };
+}
+
+/// Zero-sized payloads can't be stored in an extern union, but they don't take up any space anyway.
+fn ResultPayload(comptime T: type) type {
+    return if (@sizeOf(T) == 0) [0]u8 else T;
+}
+
+/// Fails to compile if `T` doesn't have the given size and alignment.
+pub fn assertLayout(comptime T: type, comptime size: usize, comptime alignment: usize) void {
+    if (@sizeOf(T) != size) {
+        @compileError(std.fmt.comptimePrint("{s} should have a size of {d} bytes, as in Roc, but it has {d}", .{ @typeName(T), size, @sizeOf(T) }));
+    }
+
+    if (@alignOf(T) != alignment) {
+        @compileError(std.fmt.comptimePrint("{s} should have an alignment of {d} bytes, as in Roc, but it has {d}", .{ @typeName(T), alignment, @alignOf(T) }));
+    }
+}
+
+/// Fails to compile if `field` isn't at the given offset in `T`.
+pub fn assertOffset(comptime T: type, comptime field: []const u8, comptime offset: usize) void {
+    if (@offsetOf(T, field) != offset) {
+        @compileError(std.fmt.comptimePrint("{s}.{s} should be at offset {d}, as in Roc, but it is at {d}", .{ @typeName(T), field, offset, @offsetOf(T, field) }));
+    }
+}
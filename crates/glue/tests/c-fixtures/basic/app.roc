@@ -0,0 +1,10 @@
+app "app"
+    packages { pf: "platform.roc" }
+    imports []
+    provides [main] to pf
+
+main = \{ a, b, c } ->
+    if a == 1995 && b == "hello" && c then
+        Blue
+    else
+        Red
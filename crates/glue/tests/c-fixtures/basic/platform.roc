@@ -0,0 +1,13 @@
+platform "test-platform"
+    requires {} { main : _ }
+    exposes []
+    packages {}
+    imports []
+    provides [mainForHost]
+
+MyRcd : { a : U32, b : Str, c : Bool }
+
+Color : [Red, Green, Blue]
+
+mainForHost : MyRcd -> Color
+mainForHost = main
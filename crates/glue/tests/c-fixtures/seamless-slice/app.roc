@@ -0,0 +1,9 @@
+app "app"
+    packages { pf: "platform.roc" }
+    imports []
+    provides [main] to pf
+
+# Trimming leading whitespace off a heap-allocated string returns a seamless
+# slice of it, which marks its length with the slice bit.
+main = \name ->
+    Str.trim "   Hello, \(name)! This greeting is long enough to be stored on the heap.   "
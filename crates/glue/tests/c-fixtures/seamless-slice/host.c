@@ -0,0 +1,48 @@
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+// Generated by the test, from platform.roc, using CGlue.roc.
+#include "test_glue/roc_app.h"
+
+void* roc_alloc(size_t size, unsigned int alignment) { return malloc(size); }
+
+void* roc_realloc(void* ptr, size_t new_size, size_t old_size, unsigned int alignment) {
+    return realloc(ptr, new_size);
+}
+
+void roc_dealloc(void* ptr, unsigned int alignment) { free(ptr); }
+
+void roc_panic(void* msg, unsigned int tag_id) {
+    fprintf(stderr, "Roc hit a panic: %s\n", (char*)msg);
+    exit(1);
+}
+
+void* roc_memset(void* dest, int c, size_t n) { return memset(dest, c, n); }
+
+// Builds a small string, which is stored inline in the RocStr itself.
+static RocStr small_str(const char* bytes) {
+    RocStr str;
+    size_t len = strlen(bytes);
+
+    memset(&str, 0, sizeof(str));
+    memcpy(&str, bytes, len);
+    ((unsigned char*)&str)[sizeof(RocStr) - 1] = (unsigned char)len | 0x80;
+
+    return str;
+}
+
+int main(void) {
+    RocStr name = small_str("World");
+    RocStr greeting;
+
+    roc__mainForHost_1_exposed_generic(&greeting, &name);
+
+    size_t len = roc_str_len(&greeting);
+
+    printf("Greeting was: ");
+    fwrite(roc_str_bytes(&greeting), 1, len, stdout);
+    printf(" (%zu bytes)\n", len);
+
+    return 0;
+}
@@ -0,0 +1,11 @@
+app "app"
+    packages { pf: "platform.roc" }
+    imports []
+    provides [main] to pf
+
+main = printAll ["Hello", "from", "a closure!"]
+
+printAll = \lines ->
+    when List.first lines is
+        Ok line -> StdoutLine line \{} -> printAll (List.dropFirst lines 1)
+        Err ListWasEmpty -> Done
@@ -0,0 +1,11 @@
+platform "test-platform"
+    requires {} { main : _ }
+    exposes []
+    packages {}
+    imports []
+    provides [mainForHost]
+
+Op : [StdoutLine Str ({} -> Op), Done]
+
+mainForHost : Op
+mainForHost = main
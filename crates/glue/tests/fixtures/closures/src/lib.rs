@@ -0,0 +1,73 @@
+use roc_app;
+
+use roc_app::{discriminant_Op, Op};
+
+#[no_mangle]
+pub extern "C" fn rust_main() -> i32 {
+    let mut op: Op = roc_app::mainForHost();
+
+    loop {
+        match op.discriminant() {
+            discriminant_Op::StdoutLine => {
+                // `get_` leaves the op intact, so we can still take its closure afterwards.
+                let line = unsafe { op.get_StdoutLine_0() };
+
+                println!("{line}");
+
+                let next = unsafe { op.into_StdoutLine_1() };
+
+                op = next.call(());
+            }
+            discriminant_Op::Done => break,
+        }
+    }
+
+    println!("Finished with {:?}", op); // Debug
+
+    // Exit code
+    0
+}
+
+// Externs required by roc_std and by the Roc app
+
+use core::ffi::c_void;
+use std::ffi::CStr;
+use std::os::raw::c_char;
+
+#[no_mangle]
+pub unsafe extern "C" fn roc_alloc(size: usize, _alignment: u32) -> *mut c_void {
+    return libc::malloc(size);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn roc_realloc(
+    c_ptr: *mut c_void,
+    new_size: usize,
+    _old_size: usize,
+    _alignment: u32,
+) -> *mut c_void {
+    return libc::realloc(c_ptr, new_size);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn roc_dealloc(c_ptr: *mut c_void, _alignment: u32) {
+    return libc::free(c_ptr);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn roc_panic(c_ptr: *mut c_void, tag_id: u32) {
+    match tag_id {
+        0 => {
+            let slice = CStr::from_ptr(c_ptr as *const c_char);
+            let string = slice.to_str().unwrap();
+            eprintln!("Roc hit a panic: {}", string);
+            std::process::exit(1);
+        }
+        _ => todo!(),
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn roc_memset(dst: *mut c_void, c: i32, n: usize) -> *mut c_void {
+    libc::memset(dst, c, n)
+}
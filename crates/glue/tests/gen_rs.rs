@@ -246,4 +246,40 @@ mod test_gen_rs {
             }]
         );
     }
+
+    #[test]
+    fn record_with_closure() {
+        let module = indoc!(
+            r#"
+            Handler : { name : Str, run : I64 -> I64 }
+
+            main : Handler
+            main =
+                factor = 2
+
+                { name: "double", run: \x -> factor * x }
+            "#
+        );
+
+        let files = generate_bindings(module);
+        let content = &files[0].content;
+
+        // The closure's size is only known at runtime, so the record is read through getters
+        for expected in [
+            "pub struct Handler {\n    pointer: *mut u8,\n}",
+            "pub fn get_name(&self) -> roc_std::RocStr {",
+            "pub fn get_run(&self) -> RocFunction_",
+            "#[link_name = \"roc__mainForHost_0_size\"]",
+            "#[link_name = \"roc__mainForHost_0_alignment\"]",
+            "pub fn call(&self, arg_0: i64) -> i64 {",
+            "#[link_name = \"roc__mainForHost_0_inc\"]",
+            "fn dec(_: *mut u8);",
+            "fn roc__mainForHost_0_caller(arg_0: *const i64, closure_data: *const u8, output: *mut i64);",
+        ] {
+            assert!(
+                content.contains(expected),
+                "Expected the glue to contain:\n\n{expected}\n\nbut it was:\n\n{content}"
+            );
+        }
+    }
 }
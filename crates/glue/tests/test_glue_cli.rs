@@ -12,7 +12,7 @@ mod helpers;
 #[cfg(test)]
 mod glue_cli_run {
     use crate::helpers::fixtures_dir;
-    use cli_utils::helpers::{has_error, run_glue, run_roc, Out};
+    use cli_utils::helpers::{file_path_from_root, has_error, run_cmd, run_glue, run_roc, Out};
     use std::fs;
     use std::path::Path;
 
@@ -139,6 +139,182 @@ mod glue_cli_run {
             Answer was: "Hello World!"
             Answer was: discriminant_U1::None
         "#),
+        closures:"closures" => indoc!(r#"
+            Hello
+            from
+            a closure!
+            Finished with Op::Done
+        "#),
+    }
+
+    #[test]
+    fn zig_basic() {
+        check_zig_glue("basic");
+    }
+
+    #[test]
+    fn zig_payload_union() {
+        check_zig_glue("payload-union");
+    }
+
+    #[test]
+    fn c_basic() {
+        check_c_glue("basic", "Color was: Blue\n");
+    }
+
+    #[test]
+    fn c_seamless_slice() {
+        check_c_glue(
+            "seamless-slice",
+            "Greeting was: Hello, World! This greeting is long enough to be stored on the heap. (68 bytes)\n",
+        );
+    }
+
+    /// Generates Zig glue for the platform in zig-fixtures/{dir_name}, and checks that
+    /// every file in its expected/ directory (if it has one) matches what `roc glue` generated.
+    /// Then compiles the fixture's host.zig against the glue for each target, which runs the
+    /// glue's layout assertions.
+    fn check_zig_glue(dir_name: &str) {
+        let dir = fixtures_dir("")
+            .parent()
+            .unwrap()
+            .join("zig-fixtures")
+            .join(dir_name);
+
+        generate_glue_with_spec(&dir, "ZigGlue.roc");
+
+        let expected_dir = dir.join("expected");
+
+        if expected_dir.exists() {
+            check_generated_files(&expected_dir, &dir.join("test_glue"));
+        }
+
+        for target in ["x86_64-linux", "wasm32-freestanding"] {
+            build_zig_host(&dir, target);
+        }
+    }
+
+    /// Compiles host.zig in `dir` to an object file for `target`, with the `glue` module the
+    /// generated roc_std.zig imports, the same way `roc build` compiles a Zig host.
+    fn build_zig_host(dir: &Path, target: &str) {
+        let zig = std::env::var("ROC_ZIG").unwrap_or_else(|_| "zig".to_string());
+        let builtins_glue = file_path_from_root("crates/compiler/builtins/bitcode/src", "glue.zig");
+        let emit_bin = dir.join("test_glue").join(format!("host-{target}.o"));
+
+        let out = run_cmd(
+            &zig,
+            std::iter::empty(),
+            &[
+                "build-obj".to_string(),
+                dir.join("host.zig").to_str().unwrap().to_string(),
+                format!("-femit-bin={}", emit_bin.to_str().unwrap()),
+                "--mod".to_string(),
+                format!("glue::{}", builtins_glue.to_str().unwrap()),
+                "--deps".to_string(),
+                "glue".to_string(),
+                "-target".to_string(),
+                target.to_string(),
+            ],
+            std::iter::empty(),
+        );
+
+        assert!(
+            out.status.success(),
+            "The generated Zig glue did not compile for {}:\n\n{}",
+            target,
+            out.stderr
+        );
+    }
+
+    /// Generates C glue for the platform in c-fixtures/{dir_name}, then runs its app.
+    /// That app's host.c includes the generated headers, so this checks that they
+    /// compile, and that the host can call into Roc using them.
+    fn check_c_glue(dir_name: &str, expected_stdout: &str) {
+        let dir = fixtures_dir("")
+            .parent()
+            .unwrap()
+            .join("c-fixtures")
+            .join(dir_name);
+
+        generate_glue_with_spec(&dir, "CGlue.roc");
+
+        let out = run_app(&dir.join("app.roc"), std::iter::empty());
+
+        assert!(out.status.success());
+        let ignorable = "🔨 Rebuilding platform...\n";
+        let stderr = out.stderr.replacen(ignorable, "", 1);
+        assert_eq!(stderr, "");
+        assert!(
+            out.stdout.ends_with(expected_stdout),
+            "Unexpected stdout ending\n\n  expected:\n\n    {}\n\n  but stdout was:\n\n    {}",
+            expected_stdout,
+            out.stdout
+        );
+    }
+
+    /// Runs `roc glue` with the given spec from src/ on the platform in `dir`,
+    /// writing the generated files to `dir`/test_glue.
+    fn generate_glue_with_spec(dir: &Path, spec_name: &str) {
+        let glue_dir = dir.join("test_glue");
+
+        // Delete the glue dir to make sure we're actually regenerating it!
+        if glue_dir.exists() {
+            fs::remove_dir_all(&glue_dir)
+                .expect("Unable to remove test_glue dir in order to regenerate it in the test");
+        }
+
+        let glue_spec = dir
+            .parent()
+            .unwrap()
+            .parent()
+            .unwrap()
+            .parent()
+            .unwrap()
+            .join("src")
+            .join(spec_name);
+
+        let glue_out = run_glue([
+            "glue".to_string(),
+            glue_spec.to_str().unwrap().to_string(),
+            glue_dir.to_str().unwrap().to_string(),
+            dir.join("platform.roc").to_str().unwrap().to_string(),
+        ]);
+
+        if has_error(&glue_out.stderr) {
+            panic!(
+                "`roc glue` command had unexpected stderr: {}",
+                glue_out.stderr
+            );
+        }
+
+        assert!(glue_out.status.success(), "bad status {glue_out:?}");
+    }
+
+    fn check_generated_files(expected_dir: &Path, generated_dir: &Path) {
+        for entry in fs::read_dir(expected_dir).unwrap() {
+            let entry = entry.unwrap();
+            let generated_path = generated_dir.join(entry.file_name());
+
+            if entry.file_type().unwrap().is_dir() {
+                check_generated_files(&entry.path(), &generated_path);
+            } else {
+                let expected = fs::read_to_string(entry.path()).unwrap();
+                let generated = fs::read_to_string(&generated_path).unwrap_or_else(|err| {
+                    panic!(
+                        "`roc glue` did not generate {}: {}",
+                        generated_path.to_string_lossy(),
+                        err
+                    )
+                });
+
+                assert_eq!(
+                    generated,
+                    expected,
+                    "{} did not match the expected output",
+                    generated_path.to_string_lossy()
+                );
+            }
+        }
     }
 
     fn check_for_tests(all_fixtures: &mut roc_collections::VecSet<String>) {
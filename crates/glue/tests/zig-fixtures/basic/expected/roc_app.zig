@@ -0,0 +1,14 @@
+// ⚠️ GENERATED CODE ⚠️ - this entire file was generated by the `roc glue` CLI command
+
+const builtin = @import("builtin");
+
+pub usingnamespace @import("roc_std.zig");
+
+pub usingnamespace switch (builtin.cpu.arch) {
+    .arm => @import("roc_app/arm.zig"),
+    .aarch64 => @import("roc_app/aarch64.zig"),
+    .wasm32 => @import("roc_app/wasm32.zig"),
+    .x86 => @import("roc_app/x86.zig"),
+    .x86_64 => @import("roc_app/x86_64.zig"),
+    else => @compileError("roc_app.zig was not generated for this target architecture"),
+};
@@ -0,0 +1,45 @@
+// ⚠️ GENERATED CODE ⚠️ - this entire file was generated by the `roc glue` CLI command
+
+const std = @import("std");
+const roc_std = @import("../roc_std.zig");
+const RocStr = roc_std.RocStr;
+const RocList = roc_std.RocList;
+const RocDec = roc_std.RocDec;
+const RocDict = roc_std.RocDict;
+const RocSet = roc_std.RocSet;
+const RocBox = roc_std.RocBox;
+const RocResult = roc_std.RocResult;
+const assertLayout = roc_std.assertLayout;
+const assertOffset = roc_std.assertOffset;
+
+pub const MyRcd = extern struct {
+    b: RocStr,
+    a: u32,
+    c: bool,
+};
+
+comptime {
+    assertLayout(MyRcd, 32, 8);
+    assertOffset(MyRcd, "b", 0);
+    assertOffset(MyRcd, "a", 24);
+    assertOffset(MyRcd, "c", 28);
+}
+
+pub const Color = enum(u8) {
+    Blue = 0,
+    Green = 1,
+    Red = 2,
+};
+
+comptime {
+    assertLayout(Color, 1, 1);
+}
+
+pub fn mainForHost(arg0: MyRcd) Color {
+    var ret: Color = undefined;
+    var owned_arg0 = arg0;
+    roc__mainForHost_1_exposed_generic(&ret, &owned_arg0);
+    return ret;
+}
+
+extern fn roc__mainForHost_1_exposed_generic(output: *Color, arg0: *MyRcd) void;
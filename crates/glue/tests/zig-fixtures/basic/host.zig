@@ -0,0 +1,8 @@
+//! The glue tests compile this against the generated glue for each target,
+//! which checks that the glue is valid Zig and that its layout assertions hold.
+
+const roc_app = @import("test_glue/roc_app.zig");
+
+export fn colorOf(rcd: *const roc_app.MyRcd) u8 {
+    return @intFromEnum(roc_app.mainForHost(rcd.*));
+}
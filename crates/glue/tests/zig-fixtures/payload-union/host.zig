@@ -0,0 +1,21 @@
+//! The glue tests compile this against the generated glue for each target,
+//! which checks that the glue is valid Zig and that its layout assertions hold.
+
+const roc_app = @import("test_glue/roc_app.zig");
+const Shape = roc_app.Shape;
+
+export fn transform(shape: *const Shape, out: *Shape) void {
+    out.* = roc_app.mainForHost(shape.*);
+}
+
+export fn circleRadius(radius: f64) f64 {
+    return Shape.initCircle(radius).getCircle();
+}
+
+export fn isLabel(shape: *const Shape) bool {
+    return shape.discriminant == .Label;
+}
+
+export fn emptyShape(out: *Shape) void {
+    out.* = Shape.initEmpty();
+}
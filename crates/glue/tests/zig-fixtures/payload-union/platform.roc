@@ -0,0 +1,11 @@
+platform "test-platform"
+    requires {} { main : _ }
+    exposes []
+    packages {}
+    imports []
+    provides [mainForHost]
+
+Shape : [Circle F64, Rect F64 F64, Label Str, Empty]
+
+mainForHost : Shape -> Shape
+mainForHost = main
@@ -391,6 +391,7 @@ impl IterTokens for Loc<TypeAnnotation<'_>> {
                 .chain(ext.iter().flat_map(|t| t.iter_tokens(arena)))
                 .collect_in(arena),
             TypeAnnotation::Inferred => onetoken(Token::Type, self.region, arena),
+            TypeAnnotation::TypeHole(_) => onetoken(Token::Type, self.region, arena),
             TypeAnnotation::Wildcard => onetoken(Token::Type, self.region, arena),
             TypeAnnotation::Where(ty, implements) => (ty.iter_tokens(arena).into_iter())
                 .chain(implements.iter_tokens(arena))
@@ -503,10 +504,12 @@ impl IterTokens for TypeDef<'_> {
             TypeDef::Ability {
                 header: TypeHeader { name, vars },
                 loc_implements,
+                parents,
                 members,
             } => (onetoken(Token::Ability, name.region, arena).into_iter())
                 .chain(vars.iter().map(|v| v.with_value(Token::Type)))
                 .chain(loc_implements.iter_tokens(arena))
+                .chain(parents.iter().map(|p| p.with_value(Token::Ability)))
                 .chain(members.iter_tokens(arena))
                 .collect_in(arena),
         }
@@ -581,9 +584,14 @@ impl IterTokens for Loc<Implements<'_>> {
 
 impl IterTokens for AbilityMember<'_> {
     fn iter_tokens<'a>(&self, arena: &'a Bump) -> BumpVec<'a, Loc<Token>> {
-        let Self { name, typ } = self;
+        let Self { name, typ, default } = self;
         (onetoken(Token::Function, name.region, arena).into_iter())
             .chain(typ.iter_tokens(arena))
+            .chain(
+                default
+                    .iter()
+                    .flat_map(|default| default.body.iter_tokens(arena)),
+            )
             .collect_in(arena)
     }
 }
@@ -654,6 +662,7 @@ impl IterTokens for Loc<Expr<'_>> {
             Expr::IngestedFile(_path, ty) => ty.iter_tokens(arena),
             Expr::Var { .. } => onetoken(Token::Variable, region, arena),
             Expr::Underscore(_) => onetoken(Token::Variable, region, arena),
+            Expr::TypeHole(_) => onetoken(Token::Variable, region, arena),
             Expr::Crash => onetoken(Token::Keyword, region, arena),
             Expr::Tag(_) => onetoken(Token::Tag, region, arena),
             Expr::OpaqueRef(_) => onetoken(Token::Type, region, arena),
@@ -0,0 +1,43 @@
+// A stand-in for a Roc app, covering the relocations surgery has to handle.
+
+    .section __TEXT,__text,regular,pure_instructions
+    .globl _roc__mainForHost_1_exposed
+    .p2align 2
+_roc__mainForHost_1_exposed:
+    stp x29, x30, [sp, #-16]!
+    mov x0, #16
+    // A call into the host
+    bl _roc_alloc
+    adrp x1, l_.str@PAGE
+    add x1, x1, l_.str@PAGEOFF
+    str x1, [x0]
+    // A GOT load of a host symbol, which we relax to an `add`
+    adrp x2, _roc_alloc@GOTPAGE
+    ldr x2, [x2, _roc_alloc@GOTPAGEOFF]
+    // A symbol with an addend, and a load that scales its offset
+    adrp x3, _roc__mainForHost_size@PAGE+8
+    ldr x3, [x3, _roc__mainForHost_size@PAGEOFF+8]
+    // Uninitialized data
+    adrp x4, _counter@PAGE
+    ldr w5, [x4, _counter@PAGEOFF]
+    bl _helper
+    ldp x29, x30, [sp], #16
+    ret
+
+_helper:
+    ret
+
+    .section __TEXT,__cstring,cstring_literals
+l_.str:
+    .asciz "Hello"
+
+    .section __TEXT,__const
+    .globl _roc__mainForHost_size
+    .p2align 3
+_roc__mainForHost_size:
+    .quad 16
+    .quad 2
+    // The difference of two symbols needs no rebasing
+    .quad _roc__mainForHost_1_exposed - _roc__mainForHost_size
+
+.zerofill __DATA,__bss,_counter,4,2
@@ -0,0 +1,41 @@
+# A stand-in for a Roc app, covering the relocations surgery has to handle.
+
+    .section __TEXT,__text,regular,pure_instructions
+    .globl _roc__mainForHost_1_exposed
+    .p2align 4
+_roc__mainForHost_1_exposed:
+    pushq %rbp
+    movq %rsp, %rbp
+    movl $16, %edi
+    # A call into the host
+    callq _roc_alloc
+    # A local label, which gets a section-relative relocation
+    leaq L_.str(%rip), %rcx
+    movq %rcx, (%rax)
+    # A GOT load of a host symbol, which we relax to a `leaq`
+    movq _roc_alloc@GOTPCREL(%rip), %rcx
+    # A symbol with an addend
+    leaq _roc__mainForHost_size+8(%rip), %rdx
+    # Uninitialized data, and an immediate after the displacement
+    movl $1, _counter(%rip)
+    callq _helper
+    popq %rbp
+    retq
+
+_helper:
+    retq
+
+    .section __TEXT,__cstring,cstring_literals
+L_.str:
+    .asciz "Hello"
+
+    .section __TEXT,__const
+    .globl _roc__mainForHost_size
+    .p2align 3
+_roc__mainForHost_size:
+    .quad 16
+    .quad 2
+    # The difference of two symbols needs no rebasing
+    .quad _roc__mainForHost_1_exposed - _roc__mainForHost_size
+
+.zerofill __DATA,__bss,_counter,4,2
@@ -0,0 +1,602 @@
+#!/usr/bin/env python3
+"""
+Generates the Mach-O fixtures for the surgical linker tests.
+
+We can't run Apple's linker on Linux, so the host executables are put together by hand here.
+They are laid out the way ld64 lays out a small C program linked against `libapp.so`:
+
+- `host_x86_64` uses the classic `LC_DYLD_INFO_ONLY` rebase, bind and lazy bind opcodes,
+  with 6-byte stubs that jump through `__la_symbol_ptr`.
+- `host_aarch64` uses `LC_DYLD_CHAINED_FIXUPS`, with 12-byte stubs that load from `__got`.
+
+Both have a `_main` that calls the app's `_roc__mainForHost_1_exposed` through a stub, and loads
+the app's `_roc__mainForHost_size` through the GOT. They define `_roc_alloc` for the app to call.
+
+The apps are assembled from `app_*.s` with `llvm-mc`.
+
+The `linked_*` files are what the linker makes of these, and are checked by the tests in
+`src/macho.rs`. They are written by the tests, with `ROC_UPDATE_MACHO_GOLDEN=1`, not by this script.
+
+Run this from anywhere; it writes next to itself.
+"""
+
+import os
+import struct
+import subprocess
+
+BASE = 0x100000000
+
+MH_MAGIC_64 = 0xFEEDFACF
+MH_EXECUTE = 2
+# MH_NOUNDEFS | MH_DYLDLINK | MH_TWOLEVEL | MH_PIE
+MH_FLAGS = 0x1 | 0x4 | 0x80 | 0x200000
+
+CPU_TYPE_X86_64 = 0x01000007
+CPU_SUBTYPE_X86_64_ALL = 3
+CPU_TYPE_ARM64 = 0x0100000C
+
+LC_SEGMENT_64 = 0x19
+LC_SYMTAB = 0x2
+LC_DYSYMTAB = 0xB
+LC_LOAD_DYLIB = 0xC
+LC_LOAD_DYLINKER = 0xE
+LC_UUID = 0x1B
+LC_CODE_SIGNATURE = 0x1D
+LC_DYLD_INFO_ONLY = 0x80000022
+LC_FUNCTION_STARTS = 0x26
+LC_MAIN = 0x80000028
+LC_BUILD_VERSION = 0x32
+LC_DYLD_EXPORTS_TRIE = 0x80000033
+LC_DYLD_CHAINED_FIXUPS = 0x80000034
+
+SG_READ_ONLY = 0x10
+
+S_REGULAR = 0x0
+S_CSTRING_LITERALS = 0x2
+S_NON_LAZY_SYMBOL_POINTERS = 0x6
+S_LAZY_SYMBOL_POINTERS = 0x7
+S_SYMBOL_STUBS = 0x8
+S_ATTR_PURE_INSTRUCTIONS = 0x80000000
+S_ATTR_SOME_INSTRUCTIONS = 0x400
+TEXT_FLAGS = S_ATTR_PURE_INSTRUCTIONS | S_ATTR_SOME_INSTRUCTIONS
+
+N_UNDF = 0x0
+N_EXT = 0x1
+N_SECT = 0xE
+REFERENCED_DYNAMICALLY = 0x10
+
+VM_PROT_R = 1
+VM_PROT_RW = 3
+VM_PROT_RX = 5
+
+
+def uleb(value):
+    out = bytearray()
+    while True:
+        byte = value & 0x7F
+        value >>= 7
+        if value == 0:
+            out.append(byte)
+            return bytes(out)
+        out.append(byte | 0x80)
+
+
+def align(value, alignment):
+    return (value + alignment - 1) // alignment * alignment
+
+
+def pad(data, alignment):
+    return data + bytes(align(len(data), alignment) - len(data))
+
+
+def name16(name):
+    return name.encode().ljust(16, b"\0")
+
+
+class Section:
+    def __init__(self, segname, sectname, addr, data, align, flags, reserved1=0, reserved2=0):
+        self.segname = segname
+        self.sectname = sectname
+        self.addr = addr
+        self.data = data
+        self.align = align
+        self.flags = flags
+        self.reserved1 = reserved1
+        self.reserved2 = reserved2
+
+    def encode(self):
+        return struct.pack(
+            "<16s16sQQIIIIIIII",
+            name16(self.sectname),
+            name16(self.segname),
+            self.addr,
+            len(self.data),
+            self.addr - BASE,
+            self.align,
+            0,
+            0,
+            self.flags,
+            self.reserved1,
+            self.reserved2,
+            0,
+        )
+
+
+def segment_command(name, vmaddr, vmsize, fileoff, filesize, prot, sections=(), flags=0):
+    cmd = struct.pack(
+        "<II16sQQQQIIII",
+        LC_SEGMENT_64,
+        72 + 80 * len(sections),
+        name16(name),
+        vmaddr,
+        vmsize,
+        fileoff,
+        filesize,
+        prot,
+        prot,
+        len(sections),
+        flags,
+    )
+    return cmd + b"".join(section.encode() for section in sections)
+
+
+def dylib_command(path):
+    name = pad(path.encode() + b"\0", 8)
+    return struct.pack("<IIIIII", LC_LOAD_DYLIB, 24 + len(name), 24, 2, 0x10000, 0x10000) + name
+
+
+def dylinker_command():
+    # The command is 12 bytes before the name, and its size has to be a multiple of 8
+    name = pad(b"/usr/lib/dyld\0", 8) + bytes(4)
+    return struct.pack("<III", LC_LOAD_DYLINKER, 12 + len(name), 12) + name
+
+
+def linkedit_data_command(cmd, offset, size):
+    return struct.pack("<IIII", cmd, 16, offset, size)
+
+
+def export_trie(exports):
+    """
+    A trie with one edge from the root per export.
+    dyld only needs the edges out of a node to not be prefixes of each other.
+    """
+    terminals = []
+    for name, address in exports:
+        info = uleb(0) + uleb(address)
+        terminals.append(uleb(len(info)) + info + b"\0")
+
+    # The edges refer to their node by offset, and the offsets are ULEB128s,
+    # so try growing sizes for the root until everything fits.
+    root_size = 0
+    while True:
+        offsets = []
+        offset = root_size
+        for terminal in terminals:
+            offsets.append(offset)
+            offset += len(terminal)
+        root = bytearray(b"\0" + bytes([len(exports)]))
+        for (name, _), child in zip(exports, offsets):
+            root += name.encode() + b"\0" + uleb(child)
+        if len(root) == root_size:
+            return pad(bytes(root) + b"".join(terminals), 8)
+        root_size = len(root)
+
+
+def function_starts(addresses):
+    out = bytearray()
+    previous = BASE
+    for address in addresses:
+        out += uleb(address - previous)
+        previous = address
+    return pad(bytes(out) + b"\0", 8)
+
+
+def symbol_table(symbols):
+    """Returns the nlist entries and string table for (name, type, sect, desc, value) symbols."""
+    strings = bytearray(b" \0")
+    nlists = bytearray()
+    for name, n_type, n_sect, n_desc, n_value in symbols:
+        nlists += struct.pack("<IBBHQ", len(strings), n_type, n_sect, n_desc, n_value)
+        strings += name.encode() + b"\0"
+    return bytes(nlists), pad(bytes(strings), 8)
+
+
+def code_signature():
+    """Not a real signature, but it is where ld64 would put one and surgery has to drop it."""
+    return pad(struct.pack(">III", 0xFADE0CC0, 0x40, 0), 0x40)
+
+
+class Linkedit:
+    """Collects the blobs in __LINKEDIT, remembering where each one ends up in the file"""
+
+    def __init__(self, fileoff):
+        self.fileoff = fileoff
+        self.data = bytearray()
+
+    def add(self, blob, alignment=8):
+        self.data = bytearray(pad(bytes(self.data), alignment))
+        offset = self.fileoff + len(self.data)
+        self.data += blob
+        return offset, len(blob)
+
+
+def macho_file(cputype, cpusubtype, commands, contents, page_size):
+    """`contents` maps file offsets to the bytes that go there"""
+    header = struct.pack(
+        "<IIIIIIII",
+        MH_MAGIC_64,
+        cputype,
+        cpusubtype,
+        MH_EXECUTE,
+        len(commands),
+        sum(len(cmd) for cmd in commands),
+        MH_FLAGS,
+        0,
+    )
+    end = max(offset + len(data) for offset, data in contents.items())
+    out = bytearray(end)
+    out[: len(header)] = header
+    offset = len(header)
+    for cmd in commands:
+        out[offset : offset + len(cmd)] = cmd
+        offset += len(cmd)
+    for offset, data in contents.items():
+        out[offset : offset + len(data)] = data
+    return bytes(out)
+
+
+def rel32(source_next, target):
+    return struct.pack("<i", target - source_next)
+
+
+def host_x86_64():
+    page = 0x1000
+    text = BASE + 0x1000
+    main = text
+    roc_alloc = text + 0x20
+    stubs = text + 0x24
+    stub_helper = text + 0x30
+    got = BASE + 0x2000
+    la_symbol_ptr = BASE + 0x3000
+    data = BASE + 0x3010
+    linkedit = BASE + 0x4000
+
+    # symbol table indices of the imports
+    PUTS, ROC_MAIN, ROC_SIZE, BINDER = 3, 4, 5, 6
+    stub_symbols = [PUTS, ROC_MAIN]
+    got_symbols = [BINDER, ROC_SIZE]
+
+    def stub(i):
+        return stubs + 6 * i
+
+    def helper_entry(i):
+        return stub_helper + 16 + 10 * i
+
+    code = bytearray()
+    code += b"\x55"  # push %rbp
+    code += b"\x48\x89\xe5"  # mov %rsp, %rbp
+    code += b"\xe8" + rel32(main + len(code) + 5, stub(1))  # call _roc__mainForHost_1_exposed
+    code += b"\x48\x8b\x05" + rel32(main + len(code) + 7, got + 8)  # mov size@GOTPCREL(%rip), %rax
+    code += b"\xe8" + rel32(main + len(code) + 5, stub(0))  # call _puts
+    code += b"\x31\xc0"  # xor %eax, %eax
+    code += b"\x5d"  # pop %rbp
+    code += b"\xc3"  # ret
+    code = code.ljust(roc_alloc - main, b"\x90")
+    code += b"\x31\xc0\xc3"  # _roc_alloc: xor %eax, %eax; ret
+
+    stubs_code = bytearray()
+    for i in range(len(stub_symbols)):
+        # jmp *ptr(%rip)
+        stubs_code += b"\xff\x25" + rel32(stub(i) + 6, la_symbol_ptr + 8 * i)
+
+    # The lazy binding opcodes. Each stub helper entry pushes the offset of its record.
+    # The first record uses the ULEB128 form of the ordinal, so the tests cover both.
+    lazy_records = [
+        b"\x73" + uleb(0) + b"\x20" + uleb(2) + b"\x40_puts\0\x90\0",
+        b"\x73" + uleb(8) + b"\x11" + b"\x40_roc__mainForHost_1_exposed\0\x90\0",
+    ]
+    lazy_bind = b"".join(lazy_records)
+    lazy_offsets = [0, len(lazy_records[0])]
+
+    helper = bytearray()
+    # lea ImageLoaderCache(%rip), %r11; push %r11; jmp *dyld_stub_binder@GOT(%rip); nop
+    helper += b"\x4c\x8d\x1d" + rel32(stub_helper + 7, data)
+    helper += b"\x41\x53"
+    helper += b"\xff\x25" + rel32(stub_helper + 15, got) + b"\x90"
+    for i, lazy_offset in enumerate(lazy_offsets):
+        helper += b"\x68" + struct.pack("<I", lazy_offset)
+        helper += b"\xe9" + rel32(helper_entry(i) + 10, stub_helper)
+
+    got_data = bytes(16)
+    la_data = b"".join(struct.pack("<Q", helper_entry(i)) for i in range(2))
+    data_data = struct.pack("<QQ", 0, main)
+
+    text_sections = [
+        Section("__TEXT", "__text", text, bytes(code), 4, TEXT_FLAGS),
+        Section("__TEXT", "__stubs", stubs, bytes(stubs_code), 1, TEXT_FLAGS | S_SYMBOL_STUBS, 0, 6),
+        Section("__TEXT", "__stub_helper", stub_helper, bytes(helper), 2, TEXT_FLAGS),
+    ]
+    data_const_sections = [
+        Section("__DATA_CONST", "__got", got, got_data, 3, S_NON_LAZY_SYMBOL_POINTERS, 2),
+    ]
+    data_sections = [
+        Section("__DATA", "__la_symbol_ptr", la_symbol_ptr, la_data, 3, S_LAZY_SYMBOL_POINTERS, 4),
+        Section("__DATA", "__data", data, data_data, 3, S_REGULAR),
+    ]
+
+    rebase = bytes(
+        [
+            0x11,  # REBASE_OPCODE_SET_TYPE_IMM(POINTER)
+            0x23,  # REBASE_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB(__DATA, 0)
+            0x00,
+            0x52,  # REBASE_OPCODE_DO_REBASE_IMM_TIMES(2)
+            0x41,  # REBASE_OPCODE_ADD_ADDR_IMM_SCALED(1)
+            0x51,  # REBASE_OPCODE_DO_REBASE_IMM_TIMES(1)
+            0x00,  # REBASE_OPCODE_DONE
+        ]
+    )
+    bind = (
+        b"\x12\x40dyld_stub_binder\0\x51\x72\x00\x90"
+        + b"\x11\x40_roc__mainForHost_size\0\x90"
+        + b"\x00"
+    )
+
+    exports = export_trie(
+        [("__mh_execute_header", 0), ("_main", main - BASE), ("_roc_alloc", roc_alloc - BASE)]
+    )
+
+    nlists, strings = symbol_table(
+        [
+            ("__mh_execute_header", N_SECT | N_EXT, 1, REFERENCED_DYNAMICALLY, BASE),
+            ("_main", N_SECT | N_EXT, 1, 0, main),
+            ("_roc_alloc", N_SECT | N_EXT, 1, 0, roc_alloc),
+            ("_puts", N_UNDF | N_EXT, 0, 2 << 8, 0),
+            ("_roc__mainForHost_1_exposed", N_UNDF | N_EXT, 0, 1 << 8, 0),
+            ("_roc__mainForHost_size", N_UNDF | N_EXT, 0, 1 << 8, 0),
+            ("dyld_stub_binder", N_UNDF | N_EXT, 0, 2 << 8, 0),
+        ]
+    )
+    indirect = struct.pack("<6I", *(stub_symbols + got_symbols + stub_symbols))
+
+    le = Linkedit(linkedit - BASE)
+    rebase_off = le.add(rebase)
+    bind_off = le.add(bind)
+    lazy_off = le.add(lazy_bind)
+    export_off = le.add(exports)
+    starts_off = le.add(function_starts([main, roc_alloc]))
+    sym_off = le.add(nlists)
+    indirect_off = le.add(indirect)
+    str_off = le.add(strings)
+    sig_off = le.add(code_signature(), 16)
+
+    commands = [
+        segment_command("__PAGEZERO", 0, BASE, 0, 0, 0),
+        segment_command("__TEXT", BASE, 0x2000, 0, 0x2000, VM_PROT_RX, text_sections),
+        segment_command(
+            "__DATA_CONST", got, page, 0x2000, page, VM_PROT_RW, data_const_sections, SG_READ_ONLY
+        ),
+        segment_command("__DATA", la_symbol_ptr, page, 0x3000, page, VM_PROT_RW, data_sections),
+        segment_command(
+            "__LINKEDIT", linkedit, align(len(le.data), page), le.fileoff, len(le.data), VM_PROT_R
+        ),
+        struct.pack("<II10I", LC_DYLD_INFO_ONLY, 48, *rebase_off, *bind_off, 0, 0, *lazy_off, *export_off),
+        struct.pack("<IIIIII", LC_SYMTAB, 24, sym_off[0], 7, str_off[0], str_off[1]),
+        struct.pack(
+            "<II18I", LC_DYSYMTAB, 80, 0, 0, 0, 3, 3, 4, 0, 0, 0, 0, 0, 0, indirect_off[0], 6, 0, 0, 0, 0
+        ),
+        dylinker_command(),
+        struct.pack("<II16s", LC_UUID, 24, bytes(range(16))),
+        struct.pack("<IIIIII", LC_BUILD_VERSION, 24, 1, 0xB0000, 0xB0000, 0),
+        struct.pack("<IIQQ", LC_MAIN, 24, main - BASE, 0),
+        dylib_command("libapp.so"),
+        dylib_command("/usr/lib/libSystem.B.dylib"),
+        linkedit_data_command(LC_FUNCTION_STARTS, *starts_off),
+        linkedit_data_command(LC_CODE_SIGNATURE, *sig_off),
+    ]
+
+    contents = {le.fileoff: bytes(le.data)}
+    for section in text_sections + data_const_sections + data_sections:
+        contents[section.addr - BASE] = section.data
+    return macho_file(CPU_TYPE_X86_64, CPU_SUBTYPE_X86_64_ALL, commands, contents, page)
+
+
+def aarch64_inst(value):
+    return struct.pack("<I", value)
+
+
+def adrp(reg, address, target):
+    imm = ((target >> 12) - (address >> 12)) & 0x1FFFFF
+    return aarch64_inst(0x90000000 | ((imm & 3) << 29) | ((imm >> 2) << 5) | reg)
+
+
+def ldr_x(reg, base, target):
+    return aarch64_inst(0xF9400000 | (((target & 0xFFF) >> 3) << 10) | (base << 5) | reg)
+
+
+def bl(address, target):
+    return aarch64_inst(0x94000000 | (((target - address) >> 2) & 0x3FFFFFF))
+
+
+def host_aarch64():
+    page = 0x4000
+    text = BASE + 0x1000
+    main = text
+    roc_alloc = text + 0x24
+    stubs = text + 0x2C
+    got = BASE + 0x4000
+    const = BASE + 0x4018
+    data = BASE + 0x8000
+    linkedit = BASE + 0xC000
+
+    # symbol table indices of the imports
+    ZNWM, PUTS, ROC_MAIN, ROC_SIZE = 3, 4, 5, 6
+    stub_symbols = [PUTS, ROC_MAIN]
+    got_symbols = [PUTS, ROC_MAIN, ROC_SIZE]
+
+    def stub(i):
+        return stubs + 12 * i
+
+    code = bytearray()
+    code += aarch64_inst(0xA9BF7BFD)  # stp x29, x30, [sp, #-16]!
+    code += aarch64_inst(0x910003FD)  # mov x29, sp
+    code += bl(main + len(code), stub(1))  # bl _roc__mainForHost_1_exposed
+    code += adrp(8, main + len(code), got + 16)  # adrp x8, size@GOTPAGE
+    code += ldr_x(8, 8, got + 16)  # ldr x8, [x8, size@GOTPAGEOFF]
+    code += bl(main + len(code), stub(0))  # bl _puts
+    code += aarch64_inst(0x52800000)  # mov w0, #0
+    code += aarch64_inst(0xA8C17BFD)  # ldp x29, x30, [sp], #16
+    code += aarch64_inst(0xD65F03C0)  # ret
+    code += aarch64_inst(0xD2800000)  # _roc_alloc: mov x0, #0
+    code += aarch64_inst(0xD65F03C0)  # ret
+
+    stubs_code = bytearray()
+    for i in range(len(stub_symbols)):
+        # adrp x16, ptr@PAGE; ldr x16, [x16, ptr@PAGEOFF]; br x16
+        stubs_code += adrp(16, stub(i), got + 8 * i)
+        stubs_code += ldr_x(16, 16, got + 8 * i)
+        stubs_code += aarch64_inst(0xD61F0200)
+
+    # DYLD_CHAINED_PTR_64_OFFSET pointers. `next` is in units of 4 bytes.
+    def bind(import_index, next):
+        return struct.pack("<Q", (1 << 63) | (next << 51) | import_index)
+
+    def rebase(target, next):
+        return struct.pack("<Q", (next << 51) | (target - BASE))
+
+    # imports, in order: _puts, _roc__mainForHost_1_exposed, _roc__mainForHost_size, __Znwm
+    got_data = bind(0, 2) + bind(1, 2) + bind(2, 2)
+    const_data = rebase(main, 0)
+    data_data = bind(1, 2) + bind(3, 0)
+
+    text_sections = [
+        Section("__TEXT", "__text", text, bytes(code), 2, TEXT_FLAGS),
+        Section("__TEXT", "__stubs", stubs, bytes(stubs_code), 2, TEXT_FLAGS | S_SYMBOL_STUBS, 0, 12),
+    ]
+    data_const_sections = [
+        Section("__DATA_CONST", "__got", got, got_data, 3, S_NON_LAZY_SYMBOL_POINTERS, 2),
+        Section("__DATA_CONST", "__const", const, const_data, 3, S_REGULAR),
+    ]
+    data_sections = [Section("__DATA", "__data", data, data_data, 3, S_REGULAR)]
+
+    # dyld_chained_fixups_header, dyld_chained_starts_in_image, then one
+    # dyld_chained_starts_in_segment per segment with fixups, the imports and their names.
+    segment_count = 5  # __PAGEZERO, __TEXT, __DATA_CONST, __DATA, __LINKEDIT
+    starts_offset = 32
+    seg_starts_size = 22 + 2
+    data_const_starts = align(starts_offset + 4 + 4 * segment_count, 8)
+    data_starts = align(data_const_starts + seg_starts_size, 8)
+    imports_offset = align(data_starts + seg_starts_size, 4)
+
+    names = [
+        ("_puts", 1),
+        ("_roc__mainForHost_1_exposed", 2),
+        ("_roc__mainForHost_size", 2),
+        ("__Znwm", 3),
+    ]
+    symbols_offset = imports_offset + 4 * len(names)
+    symbol_pool = bytearray(b"\0")
+    imports = bytearray()
+    for name, ordinal in names:
+        imports += struct.pack("<I", ordinal | (len(symbol_pool) << 9))
+        symbol_pool += name.encode() + b"\0"
+
+    def segment_starts(segment_offset):
+        # size, page_size, DYLD_CHAINED_PTR_64_OFFSET, segment_offset, max_valid_pointer,
+        # page_count, page_start[0]
+        return struct.pack("<IHHQIHH", seg_starts_size, page, 6, segment_offset, 0, 1, 0)
+
+    fixups = bytearray(struct.pack("<7I", 0, starts_offset, imports_offset, symbols_offset, len(names), 1, 0))
+    fixups = bytearray(pad(bytes(fixups), 8))
+    fixups += struct.pack(
+        "<6I", segment_count, 0, 0, data_const_starts - starts_offset, data_starts - starts_offset, 0
+    )
+    fixups = bytearray(pad(bytes(fixups), 8))
+    fixups += segment_starts(got - BASE)
+    fixups = bytearray(pad(bytes(fixups), 8))
+    fixups += segment_starts(data - BASE)
+    fixups += imports + symbol_pool
+
+    exports = export_trie(
+        [("__mh_execute_header", 0), ("_main", main - BASE), ("_roc_alloc", roc_alloc - BASE)]
+    )
+
+    nlists, strings = symbol_table(
+        [
+            ("__mh_execute_header", N_SECT | N_EXT, 1, REFERENCED_DYNAMICALLY, BASE),
+            ("_main", N_SECT | N_EXT, 1, 0, main),
+            ("_roc_alloc", N_SECT | N_EXT, 1, 0, roc_alloc),
+            ("__Znwm", N_UNDF | N_EXT, 0, 3 << 8, 0),
+            ("_puts", N_UNDF | N_EXT, 0, 1 << 8, 0),
+            ("_roc__mainForHost_1_exposed", N_UNDF | N_EXT, 0, 2 << 8, 0),
+            ("_roc__mainForHost_size", N_UNDF | N_EXT, 0, 2 << 8, 0),
+        ]
+    )
+    indirect = struct.pack("<5I", *(stub_symbols + got_symbols))
+
+    le = Linkedit(linkedit - BASE)
+    fixups_off = le.add(pad(bytes(fixups), 8))
+    exports_off = le.add(exports)
+    starts_off = le.add(function_starts([main, roc_alloc]))
+    sym_off = le.add(nlists)
+    indirect_off = le.add(indirect)
+    str_off = le.add(strings)
+    sig_off = le.add(code_signature(), 16)
+
+    commands = [
+        segment_command("__PAGEZERO", 0, BASE, 0, 0, 0),
+        segment_command("__TEXT", BASE, page, 0, page, VM_PROT_RX, text_sections),
+        segment_command(
+            "__DATA_CONST", got, page, page, page, VM_PROT_RW, data_const_sections, SG_READ_ONLY
+        ),
+        segment_command("__DATA", data, page, 2 * page, page, VM_PROT_RW, data_sections),
+        segment_command(
+            "__LINKEDIT", linkedit, align(len(le.data), page), le.fileoff, len(le.data), VM_PROT_R
+        ),
+        linkedit_data_command(LC_DYLD_CHAINED_FIXUPS, *fixups_off),
+        linkedit_data_command(LC_DYLD_EXPORTS_TRIE, *exports_off),
+        struct.pack("<IIIIII", LC_SYMTAB, 24, sym_off[0], 7, str_off[0], str_off[1]),
+        struct.pack(
+            "<II18I", LC_DYSYMTAB, 80, 0, 0, 0, 3, 3, 4, 0, 0, 0, 0, 0, 0, indirect_off[0], 5, 0, 0, 0, 0
+        ),
+        dylinker_command(),
+        struct.pack("<II16s", LC_UUID, 24, bytes(range(16))),
+        struct.pack("<IIIIII", LC_BUILD_VERSION, 24, 1, 0xB0000, 0xB0000, 0),
+        struct.pack("<IIQQ", LC_MAIN, 24, main - BASE, 0),
+        dylib_command("/usr/lib/libSystem.B.dylib"),
+        dylib_command("libapp.so"),
+        dylib_command("/usr/lib/libc++.1.dylib"),
+        linkedit_data_command(LC_FUNCTION_STARTS, *starts_off),
+        linkedit_data_command(LC_CODE_SIGNATURE, *sig_off),
+    ]
+
+    contents = {le.fileoff: bytes(le.data)}
+    for section in text_sections + data_const_sections + data_sections:
+        contents[section.addr - BASE] = section.data
+    return macho_file(CPU_TYPE_ARM64, 0, commands, contents, page)
+
+
+def main():
+    here = os.path.dirname(os.path.abspath(__file__))
+
+    with open(os.path.join(here, "host_x86_64"), "wb") as f:
+        f.write(host_x86_64())
+    with open(os.path.join(here, "host_aarch64"), "wb") as f:
+        f.write(host_aarch64())
+
+    for arch, triple in [("x86_64", "x86_64-apple-macos11"), ("aarch64", "arm64-apple-macos11")]:
+        subprocess.run(
+            [
+                "llvm-mc",
+                "-triple",
+                triple,
+                "-filetype=obj",
+                os.path.join(here, f"app_{arch}.s"),
+                "-o",
+                os.path.join(here, f"app_{arch}.o"),
+            ],
+            check=True,
+        )
+
+
+if __name__ == "__main__":
+    main()
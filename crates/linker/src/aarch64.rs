@@ -0,0 +1,209 @@
+//! Reading and patching AArch64 instructions.
+//!
+//! Every AArch64 instruction is a 32-bit little-endian word. Unlike x86, addresses and offsets
+//! are not stored as plain integers that we can overwrite. They are split up into bit fields
+//! of the instruction, so surgery has to decode and re-encode them.
+
+/// `nop`
+pub(crate) const NOP: u32 = 0xd503_201f;
+
+/// Size of every instruction, in bytes
+pub(crate) const INSTRUCTION_SIZE: usize = 4;
+
+const BRANCH_MASK: u32 = 0x7c00_0000;
+const BRANCH_OPCODE: u32 = 0x1400_0000; // `b`, or `bl` if the top bit is set
+const IMM26_MASK: u32 = 0x03ff_ffff;
+
+const ADRP_MASK: u32 = 0x9f00_0000;
+const ADRP_OPCODE: u32 = 0x9000_0000;
+const IMMLO_MASK: u32 = 0x3 << 29;
+const IMMHI_MASK: u32 = 0x7ffff << 5;
+
+const IMM12_MASK: u32 = 0xfff << 10;
+
+const LDR_X_IMM_MASK: u32 = 0xffc0_0000;
+const LDR_X_IMM_OPCODE: u32 = 0xf940_0000;
+const ADD_X_IMM_OPCODE: u32 = 0x9100_0000;
+const LDST_IMM_MASK: u32 = 0x3b00_0000;
+const LDST_IMM_OPCODE: u32 = 0x3900_0000; // loads and stores with an unsigned immediate offset
+const REGISTER_MASK: u32 = 0x1f;
+
+pub(crate) fn read(bytes: &[u8], offset: usize) -> u32 {
+    let word = <[u8; 4]>::try_from(&bytes[offset..][..INSTRUCTION_SIZE]).unwrap();
+    u32::from_le_bytes(word)
+}
+
+pub(crate) fn write(bytes: &mut [u8], offset: usize, inst: u32) {
+    bytes[offset..][..INSTRUCTION_SIZE].copy_from_slice(&inst.to_le_bytes());
+}
+
+/// The 4 KiB page containing an address, as used by `adrp`
+pub(crate) fn page(address: i64) -> i64 {
+    address & !0xfff
+}
+
+/// If the instruction is a `b` or `bl`, return its target address
+pub(crate) fn branch_target(inst: u32, address: u64) -> Option<u64> {
+    if inst & BRANCH_MASK != BRANCH_OPCODE {
+        return None;
+    }
+    // Shift the immediate to the top of the word and back down again to sign-extend it
+    let offset = (((inst & IMM26_MASK) << 6) as i32 >> 4) as i64;
+    Some(address.wrapping_add(offset as u64))
+}
+
+/// Every `b` and `bl` in some code, as its offset in `code`, its address, and its target
+pub(crate) fn branches(code: &[u8], address: u64) -> impl Iterator<Item = (usize, u64, u64)> + '_ {
+    code.chunks_exact(INSTRUCTION_SIZE)
+        .enumerate()
+        .filter_map(move |(i, word)| {
+            let offset = i * INSTRUCTION_SIZE;
+            let inst_address = address + offset as u64;
+            branch_target(read(word, 0), inst_address).map(|target| (offset, inst_address, target))
+        })
+}
+
+/// An unconditional branch (`b`) by a byte offset
+pub(crate) fn b(offset: i64) -> Result<u32, String> {
+    set_branch_offset(BRANCH_OPCODE, offset)
+}
+
+/// Replace the offset of a `b` or `bl` instruction
+pub(crate) fn set_branch_offset(inst: u32, offset: i64) -> Result<u32, String> {
+    const RANGE: i64 = 1 << 27;
+    if offset % 4 != 0 || !(-RANGE..RANGE).contains(&offset) {
+        return Err(format!(
+            "Branch offset {offset:+x} is out of range for an AArch64 `b` or `bl` instruction"
+        ));
+    }
+    let imm26 = (offset >> 2) as u32 & IMM26_MASK;
+    Ok((inst & !IMM26_MASK) | imm26)
+}
+
+/// Replace the page offset of an `adrp` instruction
+pub(crate) fn set_adrp_page_offset(inst: u32, page_offset: i64) -> Result<u32, String> {
+    const RANGE: i64 = 1 << 32;
+    if inst & ADRP_MASK != ADRP_OPCODE {
+        return Err(format!(
+            "Expected an AArch64 `adrp` instruction, found {inst:08x}"
+        ));
+    }
+    if page_offset % 0x1000 != 0 || !(-RANGE..RANGE).contains(&page_offset) {
+        return Err(format!(
+            "Page offset {page_offset:+x} is out of range for an AArch64 `adrp` instruction"
+        ));
+    }
+    let imm21 = (page_offset >> 12) as u32;
+    let immlo = (imm21 << 29) & IMMLO_MASK;
+    let immhi = (imm21 << 3) & IMMHI_MASK;
+    Ok((inst & !(IMMLO_MASK | IMMHI_MASK)) | immlo | immhi)
+}
+
+/// Replace the low 12 bits of an address in an `add` or a load/store instruction.
+/// Loads and stores scale the immediate by the access size, so we need to shift it down.
+pub(crate) fn set_lo12(inst: u32, address: i64, scale_shift: u32) -> Result<u32, String> {
+    let lo12 = (address & 0xfff) as u32;
+    if lo12 & ((1 << scale_shift) - 1) != 0 {
+        return Err(format!(
+            "Address {address:+x} is not aligned for a {}-byte AArch64 load or store",
+            1 << scale_shift
+        ));
+    }
+    let imm12 = lo12 >> scale_shift;
+    Ok((inst & !IMM12_MASK) | (imm12 << 10))
+}
+
+/// How far a load or store scales its 12-bit immediate, as a shift.
+/// Mach-O has a single relocation for all of them, so we have to read it from the instruction.
+/// Anything that isn't a load or store (i.e. an `add`) doesn't scale.
+pub(crate) fn lo12_scale(inst: u32) -> u32 {
+    if inst & LDST_IMM_MASK != LDST_IMM_OPCODE {
+        return 0;
+    }
+    let size = inst >> 30;
+    let is_simd = inst & (1 << 26) != 0;
+    let is_128_bit = is_simd && size == 0 && inst & (1 << 23) != 0;
+    if is_128_bit {
+        4
+    } else {
+        size
+    }
+}
+
+/// Turn `ldr xT, [xN, #:got_lo12:sym]` into `add xT, xN, #:lo12:sym`.
+/// This lets us skip the global offset table for symbols we know the address of.
+/// The `adrp` before it must then point at the page of the symbol rather than its GOT entry.
+pub(crate) fn relax_got_load(inst: u32, address: i64) -> Result<u32, String> {
+    if inst & LDR_X_IMM_MASK != LDR_X_IMM_OPCODE {
+        return Err(format!(
+            "Expected an AArch64 64-bit `ldr` from the GOT, found {inst:08x}"
+        ));
+    }
+    let rn = (inst >> 5) & REGISTER_MASK;
+    let rt = inst & REGISTER_MASK;
+    set_lo12(ADD_X_IMM_OPCODE | (rn << 5) | rt, address, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn branches() {
+        let bl = 0x9400_0000;
+        let patched = set_branch_offset(bl, -0x40).unwrap();
+        assert_eq!(patched, 0x97ff_fff0);
+        assert_eq!(branch_target(patched, 0x1040), Some(0x1000));
+
+        let patched = set_branch_offset(bl, 0x1234).unwrap();
+        assert_eq!(branch_target(patched, 0x1000), Some(0x2234));
+
+        assert_eq!(b(8), Ok(0x1400_0002));
+        assert_eq!(branch_target(NOP, 0x1000), None);
+        assert!(set_branch_offset(bl, 1 << 27).is_err());
+        assert!(set_branch_offset(bl, 2).is_err());
+
+        let mut code = [0; 12];
+        write(&mut code, 0, NOP);
+        write(&mut code, 4, set_branch_offset(bl, 0x20).unwrap());
+        write(&mut code, 8, b(-8).unwrap());
+        let found: Vec<_> = super::branches(&code, 0x1000).collect();
+        assert_eq!(found, [(4, 0x1004, 0x1024), (8, 0x1008, 0x1000)]);
+    }
+
+    #[test]
+    fn adrp() {
+        // adrp x0, 0
+        let adrp_x0 = 0x9000_0000;
+        assert_eq!(set_adrp_page_offset(adrp_x0, 0x1000), Ok(0xb000_0000));
+        assert_eq!(set_adrp_page_offset(adrp_x0, 0x5000), Ok(0xb000_0020));
+        assert_eq!(set_adrp_page_offset(adrp_x0, -0x1000), Ok(0xf0ff_ffe0));
+        assert!(set_adrp_page_offset(adrp_x0, 0x800).is_err());
+        assert!(set_adrp_page_offset(NOP, 0x1000).is_err());
+    }
+
+    #[test]
+    fn lo12() {
+        // add x0, x0, #0
+        assert_eq!(set_lo12(0x9100_0000, 0x1234, 0), Ok(0x9108_d000));
+        // ldr x1, [x2, #0]
+        assert_eq!(set_lo12(0xf940_0041, 0x5678, 3), Ok(0xf9433c41));
+        assert!(set_lo12(0xf940_0041, 0x5674, 3).is_err());
+
+        assert_eq!(lo12_scale(0x9100_0000), 0);
+        // ldrb w0, [x0]
+        assert_eq!(lo12_scale(0x3940_0000), 0);
+        // ldr w0, [x0]
+        assert_eq!(lo12_scale(0xb940_0000), 2);
+        assert_eq!(lo12_scale(0xf940_0041), 3);
+        // ldr q0, [x0]
+        assert_eq!(lo12_scale(0x3dc0_0000), 4);
+    }
+
+    #[test]
+    fn relax() {
+        // ldr x1, [x2, #8] => add x1, x2, #0x678
+        assert_eq!(relax_got_load(0xf940_0441, 0x5678), Ok(0x9119_e041));
+        assert!(relax_got_load(NOP, 0x5678).is_err());
+    }
+}
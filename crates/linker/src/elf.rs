@@ -4,8 +4,8 @@ use memmap2::MmapMut;
 use object::{elf, endian};
 use object::{
     CompressedFileRange, CompressionFormat, LittleEndian as LE, Object, ObjectSection,
-    ObjectSymbol, RelocationKind, RelocationTarget, Section, SectionIndex, SectionKind, Symbol,
-    SymbolIndex, SymbolSection,
+    ObjectSymbol, Relocation, RelocationEncoding, RelocationKind, RelocationTarget, Section,
+    SectionIndex, SectionKind, Symbol, SymbolIndex, SymbolSection,
 };
 use roc_collections::all::MutMap;
 use roc_error_macros::{internal_error, user_error};
@@ -19,8 +19,10 @@ use std::{
 };
 
 use crate::{
-    align_by_constraint, align_to_offset_by_constraint, load_struct_inplace,
-    load_struct_inplace_mut, load_structs_inplace_mut, open_mmap, open_mmap_mut,
+    aarch64, align_by_constraint, align_to_offset_by_constraint,
+    inspect::{HostMetadata, PatchSite},
+    load_struct_inplace, load_struct_inplace_mut, load_structs_inplace_mut, open_mmap,
+    open_mmap_mut,
 };
 
 const MIN_SECTION_ALIGNMENT: usize = 0x40;
@@ -28,6 +30,71 @@ const MIN_SECTION_ALIGNMENT: usize = 0x40;
 // TODO: Analyze if this offset is always correct.
 const PLT_ADDRESS_OFFSET: u64 = 0x10;
 
+/// The instruction sets we can do surgery on.
+/// They differ in how calls are encoded, and in the numbering of dynamic relocations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ElfArch {
+    X86_64,
+    Aarch64,
+}
+
+impl ElfArch {
+    fn from_object(object: &object::File) -> Self {
+        match object.architecture() {
+            object::Architecture::X86_64 => ElfArch::X86_64,
+            object::Architecture::Aarch64 => ElfArch::Aarch64,
+            other => internal_error!("Surgical linking does not support {:?} executables", other),
+        }
+    }
+
+    fn from_e_machine(e_machine: u16) -> Self {
+        match e_machine {
+            elf::EM_X86_64 => ElfArch::X86_64,
+            elf::EM_AARCH64 => ElfArch::Aarch64,
+            other => internal_error!(
+                "Surgical linking does not support ELF machine type {}",
+                other
+            ),
+        }
+    }
+
+    /// The first PLT entry calls the dynamic linker. The entries for functions come after it.
+    fn plt_header_size(self) -> u64 {
+        match self {
+            ElfArch::X86_64 => 0x10,
+            ElfArch::Aarch64 => 0x20,
+        }
+    }
+
+    fn r_none(self) -> u32 {
+        match self {
+            ElfArch::X86_64 => elf::R_X86_64_NONE,
+            ElfArch::Aarch64 => elf::R_AARCH64_NONE,
+        }
+    }
+
+    fn r_relative(self) -> u32 {
+        match self {
+            ElfArch::X86_64 => elf::R_X86_64_RELATIVE,
+            ElfArch::Aarch64 => elf::R_AARCH64_RELATIVE,
+        }
+    }
+
+    fn r_glob_dat(self) -> u32 {
+        match self {
+            ElfArch::X86_64 => elf::R_X86_64_GLOB_DAT,
+            ElfArch::Aarch64 => elf::R_AARCH64_GLOB_DAT,
+        }
+    }
+
+    fn r_jump_slot(self) -> u32 {
+        match self {
+            ElfArch::X86_64 => elf::R_X86_64_JUMP_SLOT,
+            ElfArch::Aarch64 => elf::R_AARCH64_JUMP_SLOT,
+        }
+    }
+}
+
 struct ElfDynamicDeps {
     got_app_syms: Vec<(String, usize)>,
     got_sections: Vec<(usize, usize)>,
@@ -42,11 +109,20 @@ enum VirtualOffset {
     Relative(u64),
 }
 
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+enum SurgeryKind {
+    /// Overwrite `size` bytes with the (little endian) offset to the function
+    Bytes,
+    /// Patch the 26-bit word offset of an AArch64 `b` or `bl` instruction
+    Aarch64Branch,
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
 struct SurgeryEntry {
     file_offset: u64,
     virtual_offset: VirtualOffset,
     size: u8,
+    kind: SurgeryKind,
 }
 
 // TODO: Reanalyze each piece of data in this struct.
@@ -103,6 +179,51 @@ impl Metadata {
     }
 }
 
+impl SurgeryEntry {
+    fn describe(&self) -> String {
+        match (&self.kind, &self.virtual_offset) {
+            (SurgeryKind::Aarch64Branch, VirtualOffset::Relative(address)) => {
+                format!("AArch64 branch at {address:#x}")
+            }
+            (SurgeryKind::Aarch64Branch, VirtualOffset::Absolute) => "AArch64 branch".to_string(),
+            (SurgeryKind::Bytes, VirtualOffset::Relative(address)) => {
+                format!("{}-byte offset relative to {address:#x}", self.size)
+            }
+            (SurgeryKind::Bytes, VirtualOffset::Absolute) => {
+                format!("{}-byte absolute address", self.size)
+            }
+        }
+    }
+}
+
+/// The parts of the metadata that `roc inspect-host` reports
+pub(crate) fn host_metadata(metadata_path: &Path) -> HostMetadata {
+    let md = Metadata::read_from_file(metadata_path);
+
+    let patch_sites = md
+        .surgeries
+        .iter()
+        .flat_map(|(symbol, entries)| {
+            entries.iter().map(move |entry| PatchSite {
+                symbol: symbol.clone(),
+                file_offset: entry.file_offset,
+                kind: entry.describe(),
+            })
+        })
+        .collect();
+
+    HostMetadata {
+        app_symbols: md.app_functions,
+        host_symbols: md.roc_symbol_vaddresses.into_iter().collect(),
+        stubs: md
+            .plt_addresses
+            .into_iter()
+            .map(|(name, (offset, address))| (name, offset, address))
+            .collect(),
+        patch_sites,
+    }
+}
+
 fn report_timing(label: &str, duration: Duration) {
     println!("\t{:9.3} ms   {}", duration.as_secs_f64() * 1000.0, label,);
 }
@@ -162,13 +283,18 @@ fn collect_roc_definitions<'a>(object: &object::File<'a, &'a [u8]>) -> MutMap<St
 }
 
 struct Surgeries<'a> {
+    arch: ElfArch,
     surgeries: MutMap<String, Vec<SurgeryEntry>>,
     app_func_addresses: MutMap<u64, &'a str>,
     indirect_warning_given: bool,
 }
 
 impl<'a> Surgeries<'a> {
-    fn new(application_symbols: &[Symbol], app_func_addresses: MutMap<u64, &'a str>) -> Self {
+    fn new(
+        arch: ElfArch,
+        application_symbols: &[Symbol],
+        app_func_addresses: MutMap<u64, &'a str>,
+    ) -> Self {
         let mut surgeries = MutMap::default();
 
         // for each symbol that the host expects from the application
@@ -179,6 +305,7 @@ impl<'a> Surgeries<'a> {
         }
 
         Self {
+            arch,
             surgeries,
             app_func_addresses,
             indirect_warning_given: false,
@@ -239,6 +366,12 @@ impl<'a> Surgeries<'a> {
                 internal_error!("Failed to load text section, {:+x?}: {}", sec, err);
             }
         };
+
+        if self.arch == ElfArch::Aarch64 {
+            self.append_aarch64_branches(sec, &data, file_offset, compressed, verbose);
+            return;
+        }
+
         let mut decoder = Decoder::with_ip(64, &data, sec.address(), DecoderOptions::NONE);
         let mut inst = Instruction::default();
 
@@ -299,6 +432,7 @@ impl<'a> Surgeries<'a> {
                                 file_offset: offset,
                                 virtual_offset: VirtualOffset::Relative(inst.next_ip()),
                                 size: op_size,
+                                kind: SurgeryKind::Bytes,
                             });
                     }
                 }
@@ -329,6 +463,44 @@ impl<'a> Surgeries<'a> {
             }
         }
     }
+
+    /// AArch64 instructions are all the same size, so we don't need a full disassembler.
+    /// We only look for `b` and `bl` instructions that go straight to an app function's PLT entry.
+    /// Calls through registers are left alone, like indirect calls on x86.
+    fn append_aarch64_branches(
+        &mut self,
+        sec: &Section,
+        data: &[u8],
+        file_offset: u64,
+        compressed: bool,
+        verbose: bool,
+    ) {
+        for (inst_offset, address, target) in aarch64::branches(data, sec.address()) {
+            if let Some(func_name) = self.app_func_addresses.get(&target) {
+                if compressed {
+                    internal_error!(
+                        "Surgical linking does not work with compressed text sections: {:+x?}",
+                        sec
+                    );
+                }
+
+                if verbose {
+                    println!("Found branch from {address:+x} to {target:+x}({func_name})");
+                }
+
+                self.surgeries
+                    .get_mut(*func_name)
+                    .unwrap()
+                    .push(SurgeryEntry {
+                        file_offset: file_offset + inst_offset as u64,
+                        // AArch64 branches are relative to the branch itself, not the next instruction
+                        virtual_offset: VirtualOffset::Relative(address),
+                        size: aarch64::INSTRUCTION_SIZE as u8,
+                        kind: SurgeryKind::Aarch64Branch,
+                    });
+            }
+        }
+    }
 }
 
 /// Constructs a `Metadata` from a host executable binary, and writes it to disk
@@ -351,6 +523,8 @@ pub(crate) fn preprocess_elf(
         }
     };
 
+    let arch = ElfArch::from_object(&exec_obj);
+
     let mut md = Metadata {
         roc_symbol_vaddresses: collect_roc_definitions(&exec_obj),
         ..Default::default()
@@ -427,7 +601,7 @@ pub(crate) fn preprocess_elf(
                 }
             })
             .filter_map(|(_, reloc)| {
-                if let RelocationKind::Elf(7) = reloc.kind() {
+                if reloc.kind() == RelocationKind::Elf(arch.r_jump_slot()) {
                     Some(reloc)
                 } else {
                     None
@@ -436,8 +610,9 @@ pub(crate) fn preprocess_elf(
     for (i, reloc) in plt_relocs.enumerate() {
         for symbol in app_syms.iter() {
             if reloc.target() == RelocationTarget::Symbol(symbol.index()) {
-                let func_address = (i as u64 + 1) * PLT_ADDRESS_OFFSET + plt_address;
-                let func_offset = (i as u64 + 1) * PLT_ADDRESS_OFFSET + plt_offset;
+                let plt_entry_offset = arch.plt_header_size() + i as u64 * PLT_ADDRESS_OFFSET;
+                let func_address = plt_address + plt_entry_offset;
+                let func_offset = plt_offset + plt_entry_offset;
                 app_func_addresses.insert(func_address, symbol.name().unwrap());
                 md.plt_addresses.insert(
                     symbol.name().unwrap().to_string(),
@@ -473,7 +648,7 @@ pub(crate) fn preprocess_elf(
     // look at the text (i.e. code) sections and see collect work needs to be done
     let text_disassembly_start = Instant::now();
 
-    let mut surgeries = Surgeries::new(&app_syms, app_func_addresses);
+    let mut surgeries = Surgeries::new(arch, &app_syms, app_func_addresses);
     surgeries.append_text_sections(exec_data, &exec_obj, verbose);
     md.surgeries = surgeries.surgeries;
 
@@ -493,7 +668,7 @@ pub(crate) fn preprocess_elf(
                 dynamic_lib_count,
                 shared_lib_index,
             } = scan_elf_dynamic_deps(
-                &exec_obj, &mut md, &app_syms, shared_lib, exec_data, verbose,
+                &exec_obj, arch, &mut md, &app_syms, shared_lib, exec_data, verbose,
             );
 
             scanning_dynamic_deps_duration = scanning_dynamic_deps_start.elapsed();
@@ -503,6 +678,7 @@ pub(crate) fn preprocess_elf(
             // TODO little endian
             gen_elf_le(
                 exec_data,
+                arch,
                 &mut md,
                 preprocessed_path,
                 &got_app_syms,
@@ -573,6 +749,7 @@ pub(crate) fn preprocess_elf(
 #[allow(clippy::too_many_arguments)]
 fn gen_elf_le(
     exec_data: &[u8],
+    arch: ElfArch,
     md: &mut Metadata,
     preprocessed_path: &Path,
     got_app_syms: &[(String, usize)],
@@ -742,18 +919,18 @@ fn gen_elf_le(
                 rel.r_offset.set(LE, r_offset + md.added_byte_count);
                 // Deal with potential adjusts to absolute jumps.
                 // TODO: Verify other relocation types.
-                if rel.r_type(LE, false) == elf::R_X86_64_RELATIVE {
+                if rel.r_type(LE, false) == arch.r_relative() {
                     let r_addend = rel.r_addend.get(LE);
                     rel.r_addend.set(LE, r_addend + md.added_byte_count as i64);
                 }
             }
             // If the relocation goes to a roc function, we need to surgically link it and change it to relative.
             let r_type = rel.r_type(LE, false);
-            if r_type == elf::R_X86_64_GLOB_DAT {
+            if r_type == arch.r_glob_dat() {
                 let r_sym = rel.r_sym(LE, false);
                 for (name, index) in got_app_syms.iter() {
                     if *index as u32 == r_sym {
-                        rel.set_r_info(LE, false, 0, elf::R_X86_64_RELATIVE);
+                        rel.set_r_info(LE, false, 0, arch.r_relative());
                         let addend_addr = sec_offset as usize
                             + i * mem::size_of::<elf::Rela64<LE>>()
                             // This 16 skips the first 2 fields and gets to the addend field.
@@ -762,6 +939,7 @@ fn gen_elf_le(
                             file_offset: addend_addr as u64,
                             virtual_offset: VirtualOffset::Absolute,
                             size: 8,
+                            kind: SurgeryKind::Bytes,
                         });
                     }
                 }
@@ -778,8 +956,7 @@ fn gen_elf_le(
             .filter_map(|(i, rel)| {
                 let r_type = rel.r_type(LE, false);
                 let r_sym = rel.r_sym(LE, false);
-                if r_type == elf::R_X86_64_JUMP_SLOT && app_sym_indices.contains(&(r_sym as usize))
-                {
+                if r_type == arch.r_jump_slot() && app_sym_indices.contains(&(r_sym as usize)) {
                     Some(i)
                 } else {
                     None
@@ -795,7 +972,7 @@ fn gen_elf_le(
         for i in to_remove.iter() {
             relocations.swap(*i, j);
             let r_sym = relocations[j].r_sym(LE, false);
-            relocations[j].set_r_info(LE, false, r_sym, elf::R_X86_64_NONE);
+            relocations[j].set_r_info(LE, false, r_sym, arch.r_none());
             j -= 1;
         }
 
@@ -960,6 +1137,7 @@ fn gen_elf_le(
 
 fn scan_elf_dynamic_deps(
     exec_obj: &object::File,
+    arch: ElfArch,
     md: &mut Metadata,
     app_syms: &[Symbol],
     shared_lib: &Path,
@@ -1103,7 +1281,7 @@ fn scan_elf_dynamic_deps(
         }
     })
     .filter_map(|(_, reloc)| {
-        if let RelocationKind::Elf(elf::R_X86_64_GLOB_DAT) = reloc.kind() {
+        if reloc.kind() == RelocationKind::Elf(arch.r_glob_dat()) {
             for symbol in app_syms.iter() {
                 if reloc.target() == RelocationTarget::Symbol(symbol.index()) {
                     return Some((symbol.name().unwrap().to_string(), symbol.index().0));
@@ -1122,7 +1300,7 @@ fn scan_elf_dynamic_deps(
         }
     })
     .filter_map(|(_, reloc)| {
-        if let RelocationKind::Elf(elf::R_X86_64_JUMP_SLOT) = reloc.kind() {
+        if reloc.kind() == RelocationKind::Elf(arch.r_jump_slot()) {
             for symbol in app_syms.iter() {
                 if reloc.target() == RelocationTarget::Symbol(symbol.index()) {
                     return Some(symbol.index().0);
@@ -1243,6 +1421,7 @@ fn surgery_elf_help(
         internal_error!("Only 64bit little endian elf currently supported for surgery");
     }
     let exec_header = load_struct_inplace::<elf::FileHeader64<LE>>(exec_mmap, 0);
+    let arch = ElfArch::from_e_machine(exec_header.e_machine.get(LE));
 
     let ph_offset = exec_header.e_phoff.get(LE);
     let ph_ent_size = exec_header.e_phentsize.get(LE);
@@ -1455,6 +1634,26 @@ fn surgery_elf_help(
                     if let Some(target_offset) = target_offset {
                         let virt_base = section_virtual_offset + rel.0 as usize;
                         let base = section_offset + rel.0 as usize;
+                        if arch == ElfArch::Aarch64 && is_aarch64_instruction_relocation(&rel.1) {
+                            let target_address = target_offset + rel.1.addend();
+                            if verbose {
+                                println!(
+                                    "\t\tRelocation base location: {base:+x} (virt: {virt_base:+x})",
+                                );
+                                println!(
+                                    "\t\tFinal relocation target address: {target_address:+x}"
+                                );
+                            }
+                            apply_aarch64_relocation(
+                                exec_mmap,
+                                &rel.1,
+                                base,
+                                virt_base as i64,
+                                target_address,
+                            )
+                            .unwrap_or_else(|e| internal_error!("{}: {:+x?}", e, rel));
+                            continue;
+                        }
                         let target: i64 = match rel.1.kind() {
                             RelocationKind::Relative | RelocationKind::PltRelative => {
                                 target_offset - virt_base as i64 + rel.1.addend()
@@ -1654,6 +1853,18 @@ fn surgery_elf_help(
                 VirtualOffset::Relative(vs) => (vs + md.added_byte_count) as i64,
                 VirtualOffset::Absolute => 0,
             };
+            if s.kind == SurgeryKind::Aarch64Branch {
+                let offset = func_virt_offset as i64 - surgery_virt_offset;
+                if verbose {
+                    println!("\tTarget Jump: {offset:+x}");
+                }
+                let file_offset = (s.file_offset + md.added_byte_count) as usize;
+                let inst = aarch64::read(exec_mmap, file_offset);
+                let inst = aarch64::set_branch_offset(inst, offset)
+                    .unwrap_or_else(|e| internal_error!("{}", e));
+                aarch64::write(exec_mmap, file_offset, inst);
+                continue;
+            }
             match s.size {
                 4 => {
                     let target = (func_virt_offset as i64 - surgery_virt_offset) as i32;
@@ -1684,18 +1895,33 @@ fn surgery_elf_help(
         if let Some((plt_off, plt_vaddr)) = md.plt_addresses.get(func_name) {
             let plt_off = (*plt_off + md.added_byte_count) as usize;
             let plt_vaddr = *plt_vaddr + md.added_byte_count;
-            let jmp_inst_len = 5;
-            let target =
-                (func_virt_offset as i64 - (plt_vaddr as i64 + jmp_inst_len as i64)) as i32;
-            if verbose {
-                println!("\tPLT: {plt_off:+x}, {plt_vaddr:+x}");
-                println!("\tTarget Jump: {target:+x}");
-            }
-            let data = target.to_le_bytes();
-            exec_mmap[plt_off] = 0xE9;
-            exec_mmap[plt_off + 1..plt_off + jmp_inst_len].copy_from_slice(&data);
-            for i in jmp_inst_len..PLT_ADDRESS_OFFSET as usize {
-                exec_mmap[plt_off + i] = 0x90;
+            if arch == ElfArch::Aarch64 {
+                let offset = func_virt_offset as i64 - plt_vaddr as i64;
+                if verbose {
+                    println!("\tPLT: {plt_off:+x}, {plt_vaddr:+x}");
+                    println!("\tTarget Jump: {offset:+x}");
+                }
+                let b = aarch64::b(offset).unwrap_or_else(|e| internal_error!("{}", e));
+                aarch64::write(exec_mmap, plt_off, b);
+                for i in (aarch64::INSTRUCTION_SIZE..PLT_ADDRESS_OFFSET as usize)
+                    .step_by(aarch64::INSTRUCTION_SIZE)
+                {
+                    aarch64::write(exec_mmap, plt_off + i, aarch64::NOP);
+                }
+            } else {
+                let jmp_inst_len = 5;
+                let target =
+                    (func_virt_offset as i64 - (plt_vaddr as i64 + jmp_inst_len as i64)) as i32;
+                if verbose {
+                    println!("\tPLT: {plt_off:+x}, {plt_vaddr:+x}");
+                    println!("\tTarget Jump: {target:+x}");
+                }
+                let data = target.to_le_bytes();
+                exec_mmap[plt_off] = 0xE9;
+                exec_mmap[plt_off + 1..plt_off + jmp_inst_len].copy_from_slice(&data);
+                for i in jmp_inst_len..PLT_ADDRESS_OFFSET as usize {
+                    exec_mmap[plt_off + i] = 0x90;
+                }
             }
         }
 
@@ -1737,6 +1963,53 @@ fn surgery_elf_help(
     *offset_ref = offset;
 }
 
+/// AArch64 code relocations are packed into instruction bits, rather than being plain integers.
+/// Relocations in data (like jump tables) are still plain integers.
+fn is_aarch64_instruction_relocation(rel: &Relocation) -> bool {
+    matches!(rel.kind(), RelocationKind::Elf(_))
+        || rel.encoding() == RelocationEncoding::AArch64Call
+}
+
+fn apply_aarch64_relocation(
+    exec_mmap: &mut [u8],
+    rel: &Relocation,
+    base: usize,
+    virt_base: i64,
+    target_address: i64,
+) -> Result<(), String> {
+    let r_type = match rel.kind() {
+        RelocationKind::Elf(r_type) => r_type,
+        // The object crate decodes calls into a generic form
+        _ => elf::R_AARCH64_CALL26,
+    };
+
+    let inst = aarch64::read(exec_mmap, base);
+    let inst = match r_type {
+        elf::R_AARCH64_CALL26 | elf::R_AARCH64_JUMP26 => {
+            aarch64::set_branch_offset(inst, target_address - virt_base)
+        }
+        elf::R_AARCH64_ADR_PREL_PG_HI21 | elf::R_AARCH64_ADR_GOT_PAGE => {
+            // We don't have a GOT for the app, so we relax GOT accesses to use the address directly
+            let page_offset = aarch64::page(target_address) - aarch64::page(virt_base);
+            aarch64::set_adrp_page_offset(inst, page_offset)
+        }
+        elf::R_AARCH64_LD64_GOT_LO12_NC => aarch64::relax_got_load(inst, target_address),
+        elf::R_AARCH64_ADD_ABS_LO12_NC | elf::R_AARCH64_LDST8_ABS_LO12_NC => {
+            aarch64::set_lo12(inst, target_address, 0)
+        }
+        elf::R_AARCH64_LDST16_ABS_LO12_NC => aarch64::set_lo12(inst, target_address, 1),
+        elf::R_AARCH64_LDST32_ABS_LO12_NC => aarch64::set_lo12(inst, target_address, 2),
+        elf::R_AARCH64_LDST64_ABS_LO12_NC => aarch64::set_lo12(inst, target_address, 3),
+        elf::R_AARCH64_LDST128_ABS_LO12_NC => aarch64::set_lo12(inst, target_address, 4),
+        other => Err(format!(
+            "AArch64 relocation type not yet supported: {other}"
+        )),
+    }?;
+    aarch64::write(exec_mmap, base, inst);
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1825,6 +2098,7 @@ mod tests {
         );
 
         let zig = std::env::var("ROC_ZIG").unwrap_or_else(|_| "zig".into());
+        let zig_target = format!("{}-linux-gnu", target.architecture);
 
         std::fs::write(dir.join("host.zig"), host_zig.as_bytes()).unwrap();
         std::fs::write(dir.join("app.zig"), app_zig.as_bytes()).unwrap();
@@ -1837,7 +2111,7 @@ mod tests {
                 "app.zig",
                 "-fPIC",
                 "-target",
-                "x86_64-linux-gnu",
+                &zig_target,
                 "-OReleaseFast",
             ])
             .output()
@@ -1866,7 +2140,7 @@ mod tests {
                 .collect()
         };
 
-        let dylib_bytes = crate::generate_dylib::create_dylib_elf64(&names).unwrap();
+        let dylib_bytes = crate::generate_dylib::create_dylib_elf64(&names, target).unwrap();
         std::fs::write(dir.join("libapp.so"), dylib_bytes).unwrap();
 
         // now we can compile the host (it uses libapp.so, hence the order here)
@@ -1879,7 +2153,7 @@ mod tests {
                 "-fPIE",
                 "-lc",
                 "-target",
-                "x86_64-linux-gnu",
+                &zig_target,
                 "-OReleaseFast",
             ])
             .output()
@@ -1945,4 +2219,41 @@ mod tests {
 
         assert_eq!("Hello foo\n", output);
     }
+
+    /// We can't run an aarch64 executable on every CI machine, so instead we check
+    /// that the host's calls to the app now branch straight to the app's code.
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn zig_host_app_aarch64() {
+        use std::str::FromStr;
+
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+
+        zig_host_app_help(dir, &Triple::from_str("aarch64-unknown-linux-gnu").unwrap());
+
+        let final_bytes = std::fs::read(dir.join("final")).unwrap();
+        let final_obj = object::File::parse(final_bytes.as_slice()).unwrap();
+        assert_eq!(final_obj.architecture(), object::Architecture::Aarch64);
+
+        let app_fn_address = final_obj
+            .dynamic_symbols()
+            .find(|sym| sym.name().ok() == Some("roc_magic1"))
+            .unwrap()
+            .address();
+
+        let host_text = final_obj.section_by_name(".text").unwrap();
+        let host_code = host_text.data().unwrap();
+        let branches_to_app = host_code
+            .chunks_exact(aarch64::INSTRUCTION_SIZE)
+            .enumerate()
+            .filter_map(|(i, word)| {
+                let address = host_text.address() + (i * aarch64::INSTRUCTION_SIZE) as u64;
+                aarch64::branch_target(aarch64::read(word, 0), address)
+            })
+            .filter(|target| *target == app_fn_address)
+            .count();
+
+        assert!(branches_to_app > 0);
+    }
 }
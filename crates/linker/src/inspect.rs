@@ -0,0 +1,272 @@
+//! Dumping what the surgical linker knows about a preprocessed host.
+//!
+//! When surgical linking fails, the `.rh` file and its metadata are otherwise opaque.
+//! This prints the app symbols the host expects, every place surgery will patch,
+//! and the layout of the host. Given an app, it also checks that the two agree.
+
+use object::{Object, ObjectSection};
+use roc_error_macros::user_error;
+use std::collections::BTreeSet;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// The parts of a host's metadata that every binary format has in common
+#[derive(Debug, Default)]
+pub(crate) struct HostMetadata {
+    /// Symbols the host expects the app to define, like `roc__mainForHost_1_exposed`.
+    /// Names are spelled the way Roc spells them, without Mach-O's leading underscore.
+    pub(crate) app_symbols: Vec<String>,
+    /// Symbols the host defines for the app, like `roc_alloc`, and their addresses
+    pub(crate) host_symbols: Vec<(String, u64)>,
+    /// Stubs in the host that jump to app functions, with their file offset and address
+    pub(crate) stubs: Vec<(String, u64, u64)>,
+    /// Every place in the host that surgery will patch
+    pub(crate) patch_sites: Vec<PatchSite>,
+}
+
+#[derive(Debug)]
+pub(crate) struct PatchSite {
+    /// The app symbol that the patched bytes will refer to
+    pub(crate) symbol: String,
+    pub(crate) file_offset: u64,
+    /// What gets written there, e.g. "4-byte relative offset"
+    pub(crate) kind: String,
+}
+
+/// The symbols an app exposes to its host
+pub(crate) struct AppSymbols {
+    /// The symbols in the stub library that the host is dynamically linked against
+    pub(crate) stub_dll_symbols: Vec<String>,
+    /// Everything the app defines for the host, including the stub library symbols
+    pub(crate) defined: BTreeSet<String>,
+}
+
+/// The metadata for `{triple}.rh` is `metadata_{triple}.rm`, right next to it
+pub(crate) fn metadata_path(preprocessed_host_path: &Path) -> Option<std::path::PathBuf> {
+    let stem = preprocessed_host_path.file_stem()?.to_str()?;
+    Some(preprocessed_host_path.with_file_name(format!("metadata_{stem}.rm")))
+}
+
+pub(crate) fn read_host_metadata(
+    format: object::BinaryFormat,
+    metadata_path: &Path,
+) -> HostMetadata {
+    match format {
+        object::BinaryFormat::Elf => crate::elf::host_metadata(metadata_path),
+        object::BinaryFormat::MachO => crate::macho::host_metadata(metadata_path),
+        object::BinaryFormat::Pe => crate::pe::host_metadata(metadata_path),
+        other => user_error!("The surgical linker does not support {other:?} hosts"),
+    }
+}
+
+/// Write a report about a preprocessed host, and optionally check it against an app.
+/// Returns the exit code: 1 if the app is missing symbols that the host needs.
+pub(crate) fn write_report(
+    out: &mut impl Write,
+    preprocessed_host_path: &Path,
+    metadata_path: &Path,
+    app: Option<&AppSymbols>,
+) -> io::Result<i32> {
+    if !preprocessed_host_path.exists() {
+        user_error!(
+            "There is no preprocessed host at {}. Build an app with `--linker=surgical` to create one.",
+            preprocessed_host_path.display()
+        );
+    }
+    if !metadata_path.exists() {
+        user_error!(
+            "The preprocessed host at {} has no metadata. I expected it at {}.",
+            preprocessed_host_path.display(),
+            metadata_path.display()
+        );
+    }
+
+    let host_data = std::fs::read(preprocessed_host_path)?;
+    let host = object::File::parse(host_data.as_slice()).unwrap_or_else(|err| {
+        user_error!(
+            "Failed to parse the preprocessed host at {}: {err}",
+            preprocessed_host_path.display()
+        )
+    });
+    let md = read_host_metadata(host.format(), metadata_path);
+
+    writeln!(
+        out,
+        "Preprocessed host: {} ({:?}, {:?})",
+        preprocessed_host_path.display(),
+        host.format(),
+        host.architecture()
+    )?;
+    writeln!(out, "Metadata: {}", metadata_path.display())?;
+
+    writeln!(out, "\nSymbols the host expects from the app:")?;
+    for name in md.app_symbols.iter().collect::<BTreeSet<_>>() {
+        writeln!(out, "    {name}")?;
+    }
+
+    writeln!(out, "\nSymbols the host provides to the app:")?;
+    let mut host_symbols = md.host_symbols.clone();
+    host_symbols.sort_unstable();
+    for (name, address) in host_symbols {
+        writeln!(out, "    {name:<40} {address:#x}")?;
+    }
+
+    if !md.stubs.is_empty() {
+        writeln!(out, "\nStubs that will jump to the app:")?;
+        let mut stubs = md.stubs.clone();
+        stubs.sort_unstable();
+        for (name, file_offset, address) in stubs {
+            writeln!(
+                out,
+                "    {name:<40} file offset {file_offset:#x}, address {address:#x}"
+            )?;
+        }
+    }
+
+    writeln!(out, "\nRelocation sites to patch:")?;
+    let mut patch_sites: Vec<_> = md.patch_sites.iter().collect();
+    patch_sites.sort_by(|a, b| (&a.symbol, a.file_offset).cmp(&(&b.symbol, b.file_offset)));
+    let mut previous_symbol = None;
+    for site in patch_sites {
+        if previous_symbol != Some(&site.symbol) {
+            writeln!(out, "    {}", site.symbol)?;
+            previous_symbol = Some(&site.symbol);
+        }
+        writeln!(out, "        {:#010x}  {}", site.file_offset, site.kind)?;
+    }
+
+    writeln!(out, "\nSections:")?;
+    writeln!(
+        out,
+        "    {:<32} {:>18} {:>12} {:>12}",
+        "name", "address", "file offset", "size"
+    )?;
+    for section in host.sections() {
+        let name = section.name().unwrap_or("<unknown>");
+        // Mach-O section names are only unique within their segment
+        let name = match section.segment_name() {
+            Ok(Some(segment)) => format!("{segment},{name}"),
+            _ => name.to_string(),
+        };
+        let file_offset = match section.file_range() {
+            Some((offset, _)) => format!("{offset:#x}"),
+            None => "-".to_string(),
+        };
+        writeln!(
+            out,
+            "    {:<32} {:>#18x} {:>12} {:>#12x}",
+            name,
+            section.address(),
+            file_offset,
+            section.size()
+        )?;
+    }
+
+    let app = match app {
+        Some(app) => app,
+        None => return Ok(0),
+    };
+
+    writeln!(out, "\nStub library symbols for the app:")?;
+    for name in &app.stub_dll_symbols {
+        writeln!(out, "    {name}")?;
+    }
+
+    let (missing, unused) = compare_symbols(&md, app);
+
+    if !unused.is_empty() {
+        writeln!(
+            out,
+            "\nThe app exposes these symbols, but the host never uses them:"
+        )?;
+        for name in &unused {
+            writeln!(out, "    {name}")?;
+        }
+    }
+
+    if missing.is_empty() {
+        writeln!(out, "\nThe app exposes every symbol that the host expects.")?;
+        Ok(0)
+    } else {
+        writeln!(
+            out,
+            "\nThe host expects these symbols, but the app does not expose them:"
+        )?;
+        for name in &missing {
+            writeln!(out, "    {name}")?;
+        }
+        writeln!(
+            out,
+            "\nSurgical linking will fail. Check that the platform's `provides` and the host's `extern` declarations agree."
+        )?;
+        Ok(1)
+    }
+}
+
+/// The symbols the host expects that the app doesn't define,
+/// and the stub library symbols that the host doesn't use
+fn compare_symbols(md: &HostMetadata, app: &AppSymbols) -> (Vec<String>, Vec<String>) {
+    let expected: BTreeSet<_> = md.app_symbols.iter().cloned().collect();
+
+    let missing = expected
+        .iter()
+        .filter(|name| !app.defined.contains(*name))
+        .cloned()
+        .collect();
+    let unused = app
+        .stub_dll_symbols
+        .iter()
+        .filter(|name| !expected.contains(*name))
+        .cloned()
+        .collect();
+
+    (missing, unused)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app(stub_dll_symbols: &[&str], others: &[&str]) -> AppSymbols {
+        let stub_dll_symbols: Vec<_> = stub_dll_symbols.iter().map(|s| s.to_string()).collect();
+        let mut defined: BTreeSet<_> = others.iter().map(|s| s.to_string()).collect();
+        defined.extend(stub_dll_symbols.iter().cloned());
+        AppSymbols {
+            stub_dll_symbols,
+            defined,
+        }
+    }
+
+    #[test]
+    fn compare() {
+        let md = HostMetadata {
+            app_symbols: vec![
+                "roc__mainForHost_1_exposed_generic".to_string(),
+                "roc__mainForHost_1_exposed_size".to_string(),
+            ],
+            ..Default::default()
+        };
+
+        let (missing, unused) = compare_symbols(
+            &md,
+            &app(
+                &[
+                    "roc__mainForHost_1_exposed",
+                    "roc__mainForHost_1_exposed_generic",
+                ],
+                &["roc__mainForHost_1_exposed_size"],
+            ),
+        );
+        assert!(missing.is_empty());
+        assert_eq!(unused, ["roc__mainForHost_1_exposed"]);
+
+        let (missing, _) = compare_symbols(&md, &app(&["roc__main_1_exposed_generic"], &[]));
+        assert_eq!(
+            missing,
+            [
+                "roc__mainForHost_1_exposed_generic",
+                "roc__mainForHost_1_exposed_size"
+            ]
+        );
+    }
+}
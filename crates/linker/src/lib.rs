@@ -5,8 +5,11 @@
 //! practical to use a regular linker.
 use memmap2::{Mmap, MmapMut};
 use object::Object;
-use roc_error_macros::internal_error;
-use roc_load::{EntryPoint, ExecutionMode, ExposedToHost, LoadConfig, Threading};
+use roc_error_macros::{internal_error, user_error};
+use roc_load::{
+    EntryPoint, ExecutionMode, ExposedToHost, LoadConfig, MonoOptimizations, MonomorphizedModule,
+    Threading,
+};
 use roc_module::symbol::Interns;
 use roc_packaging::cache::RocCacheDir;
 use roc_reporting::report::{RenderTarget, DEFAULT_PALETTE};
@@ -17,7 +20,9 @@ use std::mem;
 use std::path::{Path, PathBuf};
 use target_lexicon::Triple;
 
+mod aarch64;
 mod elf;
+mod inspect;
 mod macho;
 mod pe;
 
@@ -41,7 +46,14 @@ pub fn supported(link_type: LinkType, target: &Triple) -> bool {
                 ..
             } => true,
 
-            // macho support is incomplete
+            Triple {
+                architecture: target_lexicon::Architecture::Aarch64(_),
+                operating_system: target_lexicon::OperatingSystem::Linux,
+                binary_format: target_lexicon::BinaryFormat::Elf,
+                ..
+            } => true,
+
+            // macho support is only tested against fixtures so far, see `supported_on_request`
             Triple {
                 operating_system: target_lexicon::OperatingSystem::Darwin,
                 binary_format: target_lexicon::BinaryFormat::Macho,
@@ -62,6 +74,25 @@ pub fn supported(link_type: LinkType, target: &Triple) -> bool {
     }
 }
 
+/// Whether the surgical linker can be used when it is asked for explicitly, with `--linker=surgical`.
+/// This includes targets that are not used by default yet, because they have not been run on CI.
+pub fn supported_on_request(link_type: LinkType, target: &Triple) -> bool {
+    supported(link_type, target)
+        || matches!(
+            (link_type, target),
+            (
+                LinkType::Executable,
+                Triple {
+                    architecture: target_lexicon::Architecture::X86_64
+                        | target_lexicon::Architecture::Aarch64(_),
+                    operating_system: target_lexicon::OperatingSystem::Darwin,
+                    binary_format: target_lexicon::BinaryFormat::Macho,
+                    ..
+                }
+            )
+        )
+}
+
 pub const PRECOMPILED_HOST_EXT: &str = "rh"; // Short for "roc host"
 
 pub fn preprocessed_host_filename(target: &Triple) -> Option<String> {
@@ -91,13 +122,80 @@ pub fn generate_stub_lib(
     triple: &Triple,
     function_kind: FunctionKind,
 ) -> std::io::Result<i32> {
+    let arena = &bumpalo::Bump::new();
+    let loaded = load_app(arena, input_path, roc_cache_dir, triple, function_kind);
+    let exposed_symbols = ExposedSymbols::from_loaded(&loaded);
+
+    if let EntryPoint::Executable { platform_path, .. } = &loaded.entry_point {
+        let stub_lib = if let target_lexicon::OperatingSystem::Windows = triple.operating_system {
+            platform_path.with_file_name("libapp.obj")
+        } else {
+            platform_path.with_file_name("libapp.so")
+        };
+
+        let stub_dll_symbols = exposed_symbols.stub_dll_symbols();
+        generate_dynamic_lib(triple, &stub_dll_symbols, &stub_lib);
+    } else {
+        unreachable!();
+    };
+    Ok(0)
+}
+
+/// Print what the surgical linker knows about a preprocessed host (a `.rh` file).
+/// If an app is given, also check that it exposes every symbol that the host expects.
+pub fn inspect_host(
+    preprocessed_host_path: &Path,
+    app_path: Option<&Path>,
+    roc_cache_dir: RocCacheDir<'_>,
+    triple: &Triple,
+    function_kind: FunctionKind,
+) -> std::io::Result<i32> {
+    let metadata_path = inspect::metadata_path(preprocessed_host_path).unwrap_or_else(|| {
+        user_error!(
+            "{} does not look like a preprocessed host. I expected a `.{PRECOMPILED_HOST_EXT}` file.",
+            preprocessed_host_path.display()
+        )
+    });
+
+    let app_symbols = app_path.map(|app_path| {
+        let arena = &bumpalo::Bump::new();
+        let loaded = load_app(arena, app_path, roc_cache_dir, triple, function_kind);
+        let stub_dll_symbols = ExposedSymbols::from_loaded(&loaded).stub_dll_symbols();
+
+        // `roc build` names some of the symbols differently, and the app defines both
+        let mut defined: std::collections::BTreeSet<_> =
+            ExposedSymbols::from_exposed_to_host(&loaded.interns, &loaded.exposed_to_host)
+                .into_iter()
+                .collect();
+        defined.extend(stub_dll_symbols.iter().cloned());
+
+        inspect::AppSymbols {
+            stub_dll_symbols,
+            defined,
+        }
+    });
+
+    inspect::write_report(
+        &mut std::io::stdout().lock(),
+        preprocessed_host_path,
+        &metadata_path,
+        app_symbols.as_ref(),
+    )
+}
+
+fn load_app<'a>(
+    arena: &'a bumpalo::Bump,
+    input_path: &Path,
+    roc_cache_dir: RocCacheDir<'_>,
+    triple: &Triple,
+    function_kind: FunctionKind,
+) -> MonomorphizedModule<'a> {
     // Note: this should theoretically just be able to load the host, I think.
     // Instead, I am loading an entire app because that was simpler and had example code.
     // If this was expected to stay around for the the long term, we should change it.
     // But hopefully it will be removable once we have surgical linking on all platforms.
     let target_info = triple.into();
-    let arena = &bumpalo::Bump::new();
-    let loaded = roc_load::load_and_monomorphize(
+    roc_load::load_and_monomorphize(
         arena,
         input_path.to_path_buf(),
         roc_cache_dir,
@@ -108,48 +206,11 @@ pub fn generate_stub_lib(
             palette: DEFAULT_PALETTE,
             threading: Threading::AllAvailable,
             exec_mode: ExecutionMode::Executable,
+            mono_optimizations: MonoOptimizations::NONE,
+            emit_debug_info: false,
         },
     )
-    .unwrap_or_else(|problem| todo!("{:?}", problem));
-
-    let exposed_to_host = loaded
-        .exposed_to_host
-        .top_level_values
-        .keys()
-        .map(|x| x.as_str(&loaded.interns).to_string())
-        .collect();
-
-    let exported_closure_types = loaded
-        .exposed_to_host
-        .closure_types
-        .iter()
-        .map(|x| {
-            format!(
-                "{}_{}",
-                x.module_string(&loaded.interns),
-                x.as_str(&loaded.interns)
-            )
-        })
-        .collect();
-
-    let exposed_symbols = ExposedSymbols {
-        top_level_values: exposed_to_host,
-        exported_closure_types,
-    };
-
-    if let EntryPoint::Executable { platform_path, .. } = &loaded.entry_point {
-        let stub_lib = if let target_lexicon::OperatingSystem::Windows = triple.operating_system {
-            platform_path.with_file_name("libapp.obj")
-        } else {
-            platform_path.with_file_name("libapp.so")
-        };
-
-        let stub_dll_symbols = exposed_symbols.stub_dll_symbols();
-        generate_dynamic_lib(triple, &stub_dll_symbols, &stub_lib);
-    } else {
-        unreachable!();
-    };
-    Ok(0)
+    .unwrap_or_else(|problem| todo!("{:?}", problem))
 }
 
 pub fn generate_stub_lib_from_loaded(
@@ -177,6 +238,33 @@ pub struct ExposedSymbols {
 }
 
 impl ExposedSymbols {
+    fn from_loaded(loaded: &MonomorphizedModule) -> Self {
+        let top_level_values = loaded
+            .exposed_to_host
+            .top_level_values
+            .keys()
+            .map(|x| x.as_str(&loaded.interns).to_string())
+            .collect();
+
+        let exported_closure_types = loaded
+            .exposed_to_host
+            .closure_types
+            .iter()
+            .map(|x| {
+                format!(
+                    "{}_{}",
+                    x.module_string(&loaded.interns),
+                    x.as_str(&loaded.interns)
+                )
+            })
+            .collect();
+
+        Self {
+            top_level_values,
+            exported_closure_types,
+        }
+    }
+
     pub fn from_exposed_to_host(interns: &Interns, exposed_to_host: &ExposedToHost) -> Vec<String> {
         let mut custom_names = Vec::new();
 
@@ -209,19 +297,31 @@ impl ExposedSymbols {
                 sym.to_string(),
                 format!("{sym}_generic"),
                 format!("{sym}_size"),
+                format!("{sym}_alignment"),
+                format!("{sym}_dec"),
             ]);
         }
 
         for (top_level_value, lambda_set_id) in &exposed_to_host.lambda_sets {
             let sym = top_level_value.as_str(interns);
             let id = lambda_set_id.0;
-            custom_names.extend([format!("roc__{sym}_{id}_caller")]);
+            custom_names.extend([
+                format!("roc__{sym}_{id}_caller"),
+                format!("roc__{sym}_{id}_size"),
+                format!("roc__{sym}_{id}_result_size"),
+                format!("roc__{sym}_{id}_alignment"),
+                format!("roc__{sym}_{id}_inc"),
+                format!("roc__{sym}_{id}_dec"),
+            ]);
         }
 
         // on windows (PE) binary search is used on the symbols,
         // so they must be in alphabetical order
         custom_names.sort_unstable();
 
+        // closure types and lambda sets can name the same size functions
+        custom_names.dedup();
+
         custom_names
     }
 
@@ -348,6 +448,7 @@ fn object_matches_target<'a>(target: &Triple, object: &object::File<'a, &'a [u8]
             let target_format = match target.operating_system {
                 TLO::Linux => object::BinaryFormat::Elf,
                 TLO::Windows => object::BinaryFormat::Pe,
+                TLO::Darwin => object::BinaryFormat::MachO,
                 _ => todo!("surgical linker does not support target {:?}", target),
             };
 
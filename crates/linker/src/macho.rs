@@ -4,13 +4,14 @@ use memmap2::MmapMut;
 use object::macho;
 use object::{
     CompressedFileRange, CompressionFormat, LittleEndian as LE, Object, ObjectSection,
-    ObjectSymbol, RelocationKind, RelocationTarget, Section, SectionIndex, SectionKind, Symbol,
-    SymbolIndex, SymbolSection,
+    ObjectSymbol, Relocation, RelocationKind, RelocationTarget, Section, SectionIndex, SectionKind,
+    Symbol, SymbolIndex, SymbolSection,
 };
 use roc_collections::all::MutMap;
 use roc_error_macros::internal_error;
 use serde::{Deserialize, Serialize};
 use std::{
+    cmp::Ordering,
     ffi::{c_char, CStr},
     io::{BufReader, BufWriter},
     mem,
@@ -20,23 +21,90 @@ use std::{
 use target_lexicon::Triple;
 
 use crate::{
-    align_by_constraint, align_to_offset_by_constraint, load_struct_inplace,
-    load_struct_inplace_mut, load_structs_inplace, load_structs_inplace_mut, open_mmap,
-    open_mmap_mut,
+    aarch64, align_by_constraint,
+    inspect::{HostMetadata, PatchSite},
+    load_struct_inplace, load_struct_inplace_mut, load_structs_inplace, load_structs_inplace_mut,
+    open_mmap, open_mmap_mut,
 };
 
 const MIN_SECTION_ALIGNMENT: usize = 0x40;
 
-// TODO: Analyze if this offset is always correct.
-const PLT_ADDRESS_OFFSET: u64 = 0x10;
-const STUB_ADDRESS_OFFSET: u64 = 0x06;
+/// The segments we add for the app, each with a single section.
+/// They go between the host's segments and `__LINKEDIT`, which has to stay last.
+const APP_SEGMENTS: [AppSegment; 3] = [
+    AppSegment {
+        segname: *b"__ROC_CONST\0\0\0\0\0",
+        sectname: *b"__const\0\0\0\0\0\0\0\0\0",
+        prot: macho::VM_PROT_READ,
+        flags: macho::S_REGULAR,
+    },
+    AppSegment {
+        segname: *b"__ROC_BSS\0\0\0\0\0\0\0",
+        sectname: *b"__bss\0\0\0\0\0\0\0\0\0\0\0",
+        prot: macho::VM_PROT_READ | macho::VM_PROT_WRITE,
+        flags: macho::S_ZEROFILL,
+    },
+    AppSegment {
+        segname: *b"__ROC_TEXT\0\0\0\0\0\0",
+        sectname: *b"__text\0\0\0\0\0\0\0\0\0\0",
+        prot: macho::VM_PROT_READ | macho::VM_PROT_EXECUTE,
+        flags: macho::S_REGULAR | macho::S_ATTR_PURE_INSTRUCTIONS | macho::S_ATTR_SOME_INSTRUCTIONS,
+    },
+];
+
+const APP_SEGMENT_CMD_SIZE: usize =
+    mem::size_of::<macho::SegmentCommand64<LE>>() + mem::size_of::<macho::Section64<LE>>();
+
+struct AppSegment {
+    segname: [u8; 16],
+    sectname: [u8; 16],
+    prot: u32,
+    flags: u32,
+}
+
+// These aren't in the object crate yet. See <mach-o/fixup-chains.h>.
+const DYLD_CHAINED_IMPORT: u32 = 1;
+const DYLD_CHAINED_IMPORT_ADDEND: u32 = 2;
+const DYLD_CHAINED_IMPORT_ADDEND64: u32 = 3;
+const DYLD_CHAINED_PTR_64: u16 = 2;
+const DYLD_CHAINED_PTR_64_OFFSET: u16 = 6;
+const DYLD_CHAINED_PTR_START_NONE: u16 = 0xffff;
+
+#[derive(Default, Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+enum MachoArch {
+    #[default]
+    X86_64,
+    Aarch64,
+}
+
+impl MachoArch {
+    fn from_cputype(cputype: u32) -> Self {
+        match cputype {
+            macho::CPU_TYPE_X86_64 => MachoArch::X86_64,
+            macho::CPU_TYPE_ARM64 => MachoArch::Aarch64,
+            other => {
+                internal_error!("Surgical linking does not support Mach-O CPU type {other:#x}")
+            }
+        }
+    }
+
+    /// Segments have to be page aligned. Apple Silicon uses 16 KiB pages.
+    fn page_size(self) -> u64 {
+        match self {
+            MachoArch::X86_64 => 0x1000,
+            MachoArch::Aarch64 => 0x4000,
+        }
+    }
 
-// struct MachoDynamicDeps {
-//     got_app_syms: Vec<(String, usize)>,
-//     got_sections: Vec<(usize, usize)>,
-//     dynamic_lib_count: usize,
-//     shared_lib_index: usize,
-// }
+    /// Size of an entry in `__stubs`.
+    /// On x86_64 that's `jmp *slot(%rip)`, on arm64 it's `adrp`, `ldr` and `br`.
+    fn stub_size(self) -> u64 {
+        match self {
+            MachoArch::X86_64 => 6,
+            MachoArch::Aarch64 => 12,
+        }
+    }
+}
 
 #[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
 enum VirtualOffset {
@@ -44,17 +112,28 @@ enum VirtualOffset {
     Relative(u64),
 }
 
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+enum SurgeryKind {
+    /// Overwrite `size` bytes with the (little endian) offset to the function
+    Bytes,
+    /// Patch the 26-bit word offset of an AArch64 `b` or `bl` instruction
+    Aarch64Branch,
+    /// Turn a chained fixup that binds to the app into a rebase that points at the function.
+    /// The pointer format decides whether the target is stored relative to the image `base`.
+    ChainedRebase { base: u64 },
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
 struct SurgeryEntry {
     file_offset: u64,
     virtual_offset: VirtualOffset,
     size: u8,
+    kind: SurgeryKind,
 }
 
 // TODO: Reanalyze each piece of data in this struct.
 // I think a number of them can be combined to reduce string duplication.
 // Also I think a few of them aren't need.
-// For example, I think preprocessing can deal with all shifting and remove the need for added_byte_count.
 // TODO: we probably should be storing numbers in an endian neutral way.
 #[derive(Default, Serialize, Deserialize, PartialEq, Eq, Debug)]
 struct Metadata {
@@ -62,17 +141,11 @@ struct Metadata {
     // offset followed by address.
     plt_addresses: MutMap<String, (u64, u64)>,
     surgeries: MutMap<String, Vec<SurgeryEntry>>,
-    dynamic_symbol_indices: MutMap<String, u64>,
-    _static_symbol_indices: MutMap<String, u64>,
+    symbol_indices: MutMap<String, u64>,
     roc_symbol_vaddresses: MutMap<String, u64>,
     exec_len: u64,
     load_align_constraint: u64,
-    added_byte_count: u64,
-    last_vaddr: u64,
-    _dynamic_section_offset: u64,
-    _dynamic_symbol_table_section_offset: u64,
-    _symbol_table_section_offset: u64,
-    _symbol_table_size: u64,
+    arch: MachoArch,
     macho_cmd_loc: u64,
 }
 
@@ -105,6 +178,55 @@ impl Metadata {
     }
 }
 
+impl SurgeryEntry {
+    fn describe(&self) -> String {
+        match (&self.kind, &self.virtual_offset) {
+            (SurgeryKind::Aarch64Branch, VirtualOffset::Relative(address)) => {
+                format!("AArch64 branch at {address:#x}")
+            }
+            (SurgeryKind::Aarch64Branch, VirtualOffset::Absolute) => "AArch64 branch".to_string(),
+            (SurgeryKind::ChainedRebase { .. }, _) => "chained fixup pointer".to_string(),
+            (SurgeryKind::Bytes, VirtualOffset::Relative(address)) => {
+                format!("{}-byte offset relative to {address:#x}", self.size)
+            }
+            (SurgeryKind::Bytes, VirtualOffset::Absolute) => {
+                format!("{}-byte absolute address", self.size)
+            }
+        }
+    }
+}
+
+/// The parts of the metadata that `roc inspect-host` reports
+pub(crate) fn host_metadata(metadata_path: &Path) -> HostMetadata {
+    let md = Metadata::read_from_file(metadata_path);
+
+    // Mach-O symbol names have an extra leading underscore
+    let roc_name = |name: &str| name.strip_prefix('_').unwrap_or(name).to_string();
+
+    let patch_sites = md
+        .surgeries
+        .iter()
+        .flat_map(|(symbol, entries)| {
+            entries.iter().map(move |entry| PatchSite {
+                symbol: roc_name(symbol),
+                file_offset: entry.file_offset,
+                kind: entry.describe(),
+            })
+        })
+        .collect();
+
+    HostMetadata {
+        app_symbols: md.app_functions.iter().map(|name| roc_name(name)).collect(),
+        host_symbols: md.roc_symbol_vaddresses.into_iter().collect(),
+        stubs: md
+            .plt_addresses
+            .iter()
+            .map(|(name, (offset, address))| (roc_name(name), *offset, *address))
+            .collect(),
+        patch_sites,
+    }
+}
+
 fn report_timing(label: &str, duration: Duration) {
     println!("\t{:9.3} ms   {}", duration.as_secs_f64() * 1000.0, label,);
 }
@@ -155,10 +277,15 @@ struct Surgeries<'a> {
     surgeries: MutMap<String, Vec<SurgeryEntry>>,
     app_func_addresses: MutMap<u64, &'a str>,
     indirect_warning_given: bool,
+    arch: MachoArch,
 }
 
 impl<'a> Surgeries<'a> {
-    fn new(application_symbols: &[Symbol], app_func_addresses: MutMap<u64, &'a str>) -> Self {
+    fn new(
+        application_symbols: &[Symbol],
+        app_func_addresses: MutMap<u64, &'a str>,
+        arch: MachoArch,
+    ) -> Self {
         let mut surgeries = MutMap::default();
 
         // for each symbol that the host expects from the application
@@ -172,6 +299,7 @@ impl<'a> Surgeries<'a> {
             surgeries,
             app_func_addresses,
             indirect_warning_given: false,
+            arch,
         }
     }
 
@@ -229,6 +357,12 @@ impl<'a> Surgeries<'a> {
                 internal_error!("Failed to load text section, {:+x?}: {}", sec, err);
             }
         };
+
+        if self.arch == MachoArch::Aarch64 {
+            self.append_aarch64_branches(sec, &data, file_offset, compressed, verbose);
+            return;
+        }
+
         let mut decoder = Decoder::with_ip(64, &data, sec.address(), DecoderOptions::NONE);
         let mut inst = Instruction::default();
 
@@ -289,6 +423,7 @@ impl<'a> Surgeries<'a> {
                                 file_offset: offset,
                                 virtual_offset: VirtualOffset::Relative(inst.next_ip()),
                                 size: op_size,
+                                kind: SurgeryKind::Bytes,
                             });
                     }
                 }
@@ -319,6 +454,647 @@ impl<'a> Surgeries<'a> {
             }
         }
     }
+
+    /// We only look for `b` and `bl` instructions that go straight to an app function's stub.
+    /// Calls through registers are left alone, like indirect calls on x86.
+    fn append_aarch64_branches(
+        &mut self,
+        sec: &Section,
+        data: &[u8],
+        file_offset: u64,
+        compressed: bool,
+        verbose: bool,
+    ) {
+        for (inst_offset, address, target) in aarch64::branches(data, sec.address()) {
+            if let Some(func_name) = self.app_func_addresses.get(&target) {
+                if compressed {
+                    internal_error!(
+                        "Surgical linking does not work with compressed text sections: {:+x?}",
+                        sec
+                    );
+                }
+
+                if verbose {
+                    println!("Found branch from {address:+x} to {target:+x}({func_name})");
+                }
+
+                self.surgeries
+                    .get_mut(*func_name)
+                    .unwrap()
+                    .push(SurgeryEntry {
+                        file_offset: file_offset + inst_offset as u64,
+                        // AArch64 branches are relative to the branch itself, not the next instruction
+                        virtual_offset: VirtualOffset::Relative(address),
+                        size: aarch64::INSTRUCTION_SIZE as u8,
+                        kind: SurgeryKind::Aarch64Branch,
+                    });
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct LoadCommandInfo {
+    offset: usize,
+    cmd: u32,
+    size: usize,
+}
+
+fn load_commands(data: &[u8]) -> Vec<LoadCommandInfo> {
+    let header = load_struct_inplace::<macho::MachHeader64<LE>>(data, 0);
+    let mut offset = mem::size_of_val(header);
+
+    (0..header.ncmds.get(LE))
+        .map(|_| {
+            let info = load_struct_inplace::<macho::LoadCommand<LE>>(data, offset);
+            let cmd = LoadCommandInfo {
+                offset,
+                cmd: info.cmd.get(LE),
+                size: info.cmdsize.get(LE) as usize,
+            };
+            offset += cmd.size;
+            cmd
+        })
+        .collect()
+}
+
+fn segment_sections(
+    data: &[u8],
+    cmd_offset: usize,
+) -> (&macho::SegmentCommand64<LE>, &[macho::Section64<LE>]) {
+    let segment = load_struct_inplace::<macho::SegmentCommand64<LE>>(data, cmd_offset);
+    let sections = load_structs_inplace::<macho::Section64<LE>>(
+        data,
+        cmd_offset + mem::size_of_val(segment),
+        segment.nsects.get(LE) as usize,
+    );
+
+    (segment, sections)
+}
+
+fn is_dylib_command(cmd: u32) -> bool {
+    matches!(
+        cmd,
+        macho::LC_LOAD_DYLIB
+            | macho::LC_LOAD_WEAK_DYLIB
+            | macho::LC_REEXPORT_DYLIB
+            | macho::LC_LOAD_UPWARD_DYLIB
+            | macho::LC_LAZY_LOAD_DYLIB
+    )
+}
+
+fn dylib_path(data: &[u8], cmd: LoadCommandInfo) -> &Path {
+    let info = load_struct_inplace::<macho::DylibCommand<LE>>(data, cmd.offset);
+    let name_offset = info.dylib.name.offset.get(LE) as usize;
+    let str_bytes = &data[cmd.offset + name_offset..cmd.offset + cmd.size];
+
+    if str_bytes[str_bytes.len() - 1] == 0 {
+        // If it's nul-terminated, it's a C String.
+        // Use the unchecked version because these are
+        // padded with 0s at the end, so since we don't
+        // know the exact length, using the checked version
+        // of this can fail due to the interior nul bytes.
+        //
+        // Also, we have to use from_ptr instead of
+        // from_bytes_with_nul_unchecked because currently
+        // std::ffi::CStr is actually not a char* under
+        // the hood (!) but rather an array, so to strip
+        // the trailing null bytes we have to use from_ptr.
+        let c_str = unsafe { CStr::from_ptr(str_bytes.as_ptr() as *const c_char) };
+
+        Path::new(c_str.to_str().unwrap())
+    } else {
+        // It wasn't nul-terminated, so treat all the bytes
+        // as the string
+
+        Path::new(std::str::from_utf8(str_bytes).unwrap())
+    }
+}
+
+/// Dylib ordinals are 1-based indices into the dylib load commands.
+/// Removing the app's dylib shifts all the ones after it down by one.
+/// Special ordinals (zero and negative) refer to the executable itself or to a flat lookup.
+fn shift_ordinal(ordinal: i64, app_ordinal: i64) -> i64 {
+    if ordinal > app_ordinal {
+        ordinal - 1
+    } else {
+        ordinal
+    }
+}
+
+fn read_uleb128(bytes: &[u8], pos: &mut usize) -> Result<u64, String> {
+    let mut result = 0;
+    let mut shift = 0;
+    loop {
+        let byte = *bytes
+            .get(*pos)
+            .ok_or_else(|| "ULEB128 runs past the end of the opcodes".to_string())?;
+        *pos += 1;
+        if shift < 64 {
+            result |= ((byte & 0x7f) as u64) << shift;
+        }
+        shift += 7;
+        if byte & 0x80 == 0 {
+            return Ok(result);
+        }
+    }
+}
+
+fn read_sleb128(bytes: &[u8], pos: &mut usize) -> Result<i64, String> {
+    let mut result = 0;
+    let mut shift = 0;
+    loop {
+        let byte = *bytes
+            .get(*pos)
+            .ok_or_else(|| "SLEB128 runs past the end of the opcodes".to_string())?;
+        *pos += 1;
+        if shift < 64 {
+            result |= ((byte & 0x7f) as i64) << shift;
+        }
+        shift += 7;
+        if byte & 0x80 == 0 {
+            if shift < 64 && byte & 0x40 != 0 {
+                result |= -1 << shift;
+            }
+            return Ok(result);
+        }
+    }
+}
+
+fn write_uleb128(out: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn write_sleb128(out: &mut Vec<u8>, mut value: i64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        let done = (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0);
+        if done {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+/// Overwrite a ULEB128 in place, padding it out to the same number of bytes
+fn write_padded_uleb128(out: &mut [u8], mut value: u64) -> Result<(), String> {
+    let len = out.len();
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if i + 1 < len {
+            *byte |= 0x80;
+        }
+    }
+    if value != 0 {
+        return Err(format!("Value does not fit in a {len}-byte ULEB128"));
+    }
+    Ok(())
+}
+
+/// The state of the dyld bind opcode interpreter at each pointer it binds
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Bind {
+    segment: u8,
+    segment_offset: u64,
+    ordinal: i64,
+    name: String,
+    flags: u8,
+    bind_type: u8,
+    addend: i64,
+    /// Where the opcode that set the ordinal is, including its operand
+    ordinal_opcode: (usize, usize),
+    /// Where the opcode that set the symbol name is
+    symbol_opcode: usize,
+}
+
+/// Run the bind opcodes from `LC_DYLD_INFO`.
+/// Lazy binding opcodes are a sequence of separate records, each ending in `DONE`.
+fn parse_binds(opcodes: &[u8], lazy: bool) -> Result<Vec<Bind>, String> {
+    let mut binds = Vec::new();
+    let mut state = Bind {
+        segment: 0,
+        segment_offset: 0,
+        ordinal: 0,
+        name: String::new(),
+        flags: 0,
+        bind_type: macho::BIND_TYPE_POINTER,
+        addend: 0,
+        ordinal_opcode: (0, 0),
+        symbol_opcode: 0,
+    };
+    let pointer_size = mem::size_of::<u64>() as u64;
+
+    let mut pos = 0;
+    while pos < opcodes.len() {
+        let start = pos;
+        let byte = opcodes[pos];
+        let immediate = byte & macho::BIND_IMMEDIATE_MASK;
+        pos += 1;
+
+        match byte & macho::BIND_OPCODE_MASK {
+            macho::BIND_OPCODE_DONE if lazy => {}
+            macho::BIND_OPCODE_DONE => break,
+            macho::BIND_OPCODE_SET_DYLIB_ORDINAL_IMM => {
+                state.ordinal = immediate as i64;
+                state.ordinal_opcode = (start, 1);
+            }
+            macho::BIND_OPCODE_SET_DYLIB_ORDINAL_ULEB => {
+                state.ordinal = read_uleb128(opcodes, &mut pos)? as i64;
+                state.ordinal_opcode = (start, pos - start);
+            }
+            macho::BIND_OPCODE_SET_DYLIB_SPECIAL_IMM => {
+                // Sign extend the immediate
+                state.ordinal = if immediate == 0 {
+                    0
+                } else {
+                    (immediate | macho::BIND_OPCODE_MASK) as i8 as i64
+                };
+                state.ordinal_opcode = (start, 1);
+            }
+            macho::BIND_OPCODE_SET_SYMBOL_TRAILING_FLAGS_IMM => {
+                let len = opcodes[pos..]
+                    .iter()
+                    .position(|b| *b == 0)
+                    .ok_or_else(|| "Unterminated symbol name in bind opcodes".to_string())?;
+                state.name = String::from_utf8_lossy(&opcodes[pos..pos + len]).into_owned();
+                state.flags = immediate;
+                state.symbol_opcode = start;
+                pos += len + 1;
+            }
+            macho::BIND_OPCODE_SET_TYPE_IMM => state.bind_type = immediate,
+            macho::BIND_OPCODE_SET_ADDEND_SLEB => state.addend = read_sleb128(opcodes, &mut pos)?,
+            macho::BIND_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB => {
+                state.segment = immediate;
+                state.segment_offset = read_uleb128(opcodes, &mut pos)?;
+            }
+            macho::BIND_OPCODE_ADD_ADDR_ULEB => {
+                let delta = read_uleb128(opcodes, &mut pos)?;
+                state.segment_offset = state.segment_offset.wrapping_add(delta);
+            }
+            macho::BIND_OPCODE_DO_BIND => {
+                binds.push(state.clone());
+                state.segment_offset += pointer_size;
+            }
+            macho::BIND_OPCODE_DO_BIND_ADD_ADDR_ULEB => {
+                binds.push(state.clone());
+                let delta = read_uleb128(opcodes, &mut pos)?;
+                state.segment_offset = state
+                    .segment_offset
+                    .wrapping_add(delta)
+                    .wrapping_add(pointer_size);
+            }
+            macho::BIND_OPCODE_DO_BIND_ADD_ADDR_IMM_SCALED => {
+                binds.push(state.clone());
+                state.segment_offset += (immediate as u64 + 1) * pointer_size;
+            }
+            macho::BIND_OPCODE_DO_BIND_ULEB_TIMES_SKIPPING_ULEB => {
+                let count = read_uleb128(opcodes, &mut pos)?;
+                let skip = read_uleb128(opcodes, &mut pos)?;
+                for _ in 0..count {
+                    binds.push(state.clone());
+                    state.segment_offset += skip + pointer_size;
+                }
+            }
+            other => {
+                return Err(format!("Unsupported bind opcode {other:#x} at {start:#x}"));
+            }
+        }
+    }
+
+    Ok(binds)
+}
+
+fn encode_binds(binds: &[Bind]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut addend = 0;
+
+    for bind in binds {
+        match bind.ordinal {
+            ordinal if ordinal <= 0 => out.push(
+                macho::BIND_OPCODE_SET_DYLIB_SPECIAL_IMM
+                    | (ordinal as u8 & macho::BIND_IMMEDIATE_MASK),
+            ),
+            ordinal if ordinal <= macho::BIND_IMMEDIATE_MASK as i64 => {
+                out.push(macho::BIND_OPCODE_SET_DYLIB_ORDINAL_IMM | ordinal as u8)
+            }
+            ordinal => {
+                out.push(macho::BIND_OPCODE_SET_DYLIB_ORDINAL_ULEB);
+                write_uleb128(&mut out, ordinal as u64);
+            }
+        }
+
+        out.push(macho::BIND_OPCODE_SET_SYMBOL_TRAILING_FLAGS_IMM | bind.flags);
+        out.extend_from_slice(bind.name.as_bytes());
+        out.push(0);
+
+        out.push(macho::BIND_OPCODE_SET_TYPE_IMM | bind.bind_type);
+
+        if bind.addend != addend {
+            addend = bind.addend;
+            out.push(macho::BIND_OPCODE_SET_ADDEND_SLEB);
+            write_sleb128(&mut out, addend);
+        }
+
+        out.push(macho::BIND_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB | bind.segment);
+        write_uleb128(&mut out, bind.segment_offset);
+        out.push(macho::BIND_OPCODE_DO_BIND);
+    }
+
+    out.push(macho::BIND_OPCODE_DONE);
+    out
+}
+
+/// Find the `DONE` opcode at the end of some rebase opcodes, so we can add more after it
+fn rebase_opcodes_end(opcodes: &[u8]) -> Result<usize, String> {
+    let mut pos = 0;
+    while pos < opcodes.len() {
+        let start = pos;
+        pos += 1;
+        match opcodes[start] & macho::REBASE_OPCODE_MASK {
+            macho::REBASE_OPCODE_DONE => return Ok(start),
+            macho::REBASE_OPCODE_SET_TYPE_IMM
+            | macho::REBASE_OPCODE_ADD_ADDR_IMM_SCALED
+            | macho::REBASE_OPCODE_DO_REBASE_IMM_TIMES => {}
+            macho::REBASE_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB
+            | macho::REBASE_OPCODE_ADD_ADDR_ULEB
+            | macho::REBASE_OPCODE_DO_REBASE_ULEB_TIMES
+            | macho::REBASE_OPCODE_DO_REBASE_ADD_ADDR_ULEB => {
+                read_uleb128(opcodes, &mut pos)?;
+            }
+            macho::REBASE_OPCODE_DO_REBASE_ULEB_TIMES_SKIPPING_ULEB => {
+                read_uleb128(opcodes, &mut pos)?;
+                read_uleb128(opcodes, &mut pos)?;
+            }
+            other => {
+                return Err(format!(
+                    "Unsupported rebase opcode {other:#x} at {start:#x}"
+                ));
+            }
+        }
+    }
+
+    Ok(opcodes.len())
+}
+
+fn append_rebases(opcodes: &[u8], rebases: &[(u8, u64)]) -> Result<Vec<u8>, String> {
+    let mut out = opcodes[..rebase_opcodes_end(opcodes)?].to_vec();
+
+    out.push(macho::REBASE_OPCODE_SET_TYPE_IMM | macho::REBASE_TYPE_POINTER);
+    for (segment, segment_offset) in rebases {
+        out.push(macho::REBASE_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB | segment);
+        write_uleb128(&mut out, *segment_offset);
+        out.push(macho::REBASE_OPCODE_DO_REBASE_IMM_TIMES | 1);
+    }
+    out.push(macho::REBASE_OPCODE_DONE);
+
+    Ok(out)
+}
+
+/// Lazy binding opcodes are referenced by offset from `__stub_helper`, so we can't re-encode them.
+/// Instead we patch them in place: the app's records become weak flat lookups, which dyld
+/// is happy to leave unresolved, and the other ordinals are shifted down.
+/// We never go through these stubs anyway, because surgery turns them into direct jumps.
+fn patch_lazy_binds(opcodes: &mut [u8], app_ordinal: i64) -> Result<(), String> {
+    const FILLER: u8 = macho::BIND_OPCODE_SET_TYPE_IMM | macho::BIND_TYPE_POINTER;
+
+    for bind in parse_binds(opcodes, true)? {
+        let (start, len) = bind.ordinal_opcode;
+        let ordinal_opcode = &mut opcodes[start..start + len];
+
+        match bind.ordinal.cmp(&app_ordinal) {
+            Ordering::Equal => {
+                ordinal_opcode[0] = macho::BIND_OPCODE_SET_DYLIB_SPECIAL_IMM
+                    | (macho::BIND_SPECIAL_DYLIB_FLAT_LOOKUP as u8 & macho::BIND_IMMEDIATE_MASK);
+                ordinal_opcode[1..].fill(FILLER);
+                opcodes[bind.symbol_opcode] |= macho::BIND_SYMBOL_FLAGS_WEAK_IMPORT;
+            }
+            Ordering::Greater => {
+                let ordinal = shift_ordinal(bind.ordinal, app_ordinal);
+                if len == 1 {
+                    ordinal_opcode[0] = macho::BIND_OPCODE_SET_DYLIB_ORDINAL_IMM | ordinal as u8;
+                } else {
+                    write_padded_uleb128(&mut ordinal_opcode[1..], ordinal as u64)?;
+                }
+            }
+            Ordering::Less => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes(data[offset..offset + 2].try_into().unwrap())
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+/// An entry in the imports table of `LC_DYLD_CHAINED_FIXUPS`
+struct ChainedImport {
+    offset: usize,
+    format: u32,
+    ordinal: i64,
+    name: String,
+}
+
+fn parse_chained_imports(fixups: &[u8]) -> Result<Vec<ChainedImport>, String> {
+    let imports_offset = read_u32(fixups, 8) as usize;
+    let symbols_offset = read_u32(fixups, 12) as usize;
+    let imports_count = read_u32(fixups, 16) as usize;
+    let imports_format = read_u32(fixups, 20);
+    let symbols_format = read_u32(fixups, 24);
+
+    if symbols_format != 0 {
+        return Err("Compressed chained fixup symbol names are not supported".to_string());
+    }
+
+    let (import_size, ordinal_bits) = match imports_format {
+        DYLD_CHAINED_IMPORT => (4, 8),
+        DYLD_CHAINED_IMPORT_ADDEND => (8, 8),
+        DYLD_CHAINED_IMPORT_ADDEND64 => (16, 16),
+        other => return Err(format!("Unknown chained fixup import format {other}")),
+    };
+
+    (0..imports_count)
+        .map(|i| {
+            let offset = imports_offset + i * import_size;
+            let (raw_ordinal, name_offset) = if ordinal_bits == 8 {
+                let import = read_u32(fixups, offset);
+                (import as u64 & 0xff, (import >> 9) as usize)
+            } else {
+                let import = read_u64(fixups, offset);
+                (import & 0xffff, (import >> 32) as usize)
+            };
+
+            // Ordinals are signed, so that the special ones are negative
+            let shift = 64 - ordinal_bits;
+            let ordinal = ((raw_ordinal << shift) as i64) >> shift;
+
+            let name_start = symbols_offset + name_offset;
+            let name_len = fixups[name_start..]
+                .iter()
+                .position(|b| *b == 0)
+                .ok_or_else(|| "Unterminated chained fixup symbol name".to_string())?;
+            let name = String::from_utf8_lossy(&fixups[name_start..name_start + name_len]);
+
+            Ok(ChainedImport {
+                offset,
+                format: imports_format,
+                ordinal,
+                name: name.into_owned(),
+            })
+        })
+        .collect()
+}
+
+impl ChainedImport {
+    /// Point the import at a different dylib. Optionally mark it as weak,
+    /// so that dyld doesn't complain when it can't find the symbol.
+    fn patch(&self, fixups: &mut [u8], ordinal: i64, weak: bool) {
+        if self.format == DYLD_CHAINED_IMPORT_ADDEND64 {
+            let import = read_u64(fixups, self.offset);
+            let import = (import & !0x1ffff) | (ordinal as u64 & 0xffff) | ((weak as u64) << 16);
+            fixups[self.offset..self.offset + 8].copy_from_slice(&import.to_le_bytes());
+        } else {
+            let import = read_u32(fixups, self.offset);
+            let import = (import & !0x1ff) | (ordinal as u32 & 0xff) | ((weak as u32) << 8);
+            fixups[self.offset..self.offset + 4].copy_from_slice(&import.to_le_bytes());
+        }
+    }
+}
+
+/// Add `count` empty segments to the chained fixups, before the segment at `index`.
+/// Everything after the table of segments moves down, so the offsets to it are adjusted.
+fn insert_chained_segments(fixups: &[u8], index: usize, count: usize) -> Result<Vec<u8>, String> {
+    let starts_offset = read_u32(fixups, 4) as usize;
+    let seg_count = read_u32(fixups, starts_offset) as usize;
+    let old_end = starts_offset + 4 + 4 * seg_count;
+    // Keep the 8-byte alignment of the segment starts that follow the table
+    let shift = align_by_constraint(4 * count, 8);
+
+    if index > seg_count {
+        return Err(format!(
+            "Chained fixups only have {seg_count} segments, but __LINKEDIT is segment {index}"
+        ));
+    }
+    if (read_u32(fixups, 8) as usize) < old_end || (read_u32(fixups, 12) as usize) < old_end {
+        return Err("Chained fixup imports are expected after the segment starts".to_string());
+    }
+
+    let mut seg_info_offsets: Vec<u32> = (0..seg_count)
+        .map(|i| match read_u32(fixups, starts_offset + 4 + 4 * i) {
+            0 => 0,
+            offset => offset + shift as u32,
+        })
+        .collect();
+    seg_info_offsets.splice(index..index, std::iter::repeat(0).take(count));
+
+    let mut new_fixups = Vec::with_capacity(fixups.len() + shift);
+    new_fixups.extend_from_slice(&fixups[..starts_offset]);
+    new_fixups.extend_from_slice(&(seg_info_offsets.len() as u32).to_le_bytes());
+    for offset in seg_info_offsets {
+        new_fixups.extend_from_slice(&offset.to_le_bytes());
+    }
+    new_fixups.resize(old_end + shift, 0);
+    new_fixups.extend_from_slice(&fixups[old_end..]);
+
+    // The imports and symbol names
+    for field in [8, 12] {
+        let offset = read_u32(&new_fixups, field) + shift as u32;
+        new_fixups[field..field + 4].copy_from_slice(&offset.to_le_bytes());
+    }
+
+    Ok(new_fixups)
+}
+
+/// A pointer in a fixup chain that dyld would bind to an import
+struct ChainedBind {
+    file_offset: u64,
+    import: usize,
+    addend: u64,
+    pointer_format: u16,
+}
+
+/// Walk the fixup chains of every page, and collect the binds.
+/// `segments` are the (vmaddr, fileoff) of every segment, in load command order.
+fn parse_chained_binds(
+    exec_data: &[u8],
+    fixups: &[u8],
+    segments: &[(u64, u64)],
+    image_base: u64,
+) -> Result<Vec<ChainedBind>, String> {
+    let mut binds = Vec::new();
+
+    let starts_offset = read_u32(fixups, 4) as usize;
+    let seg_count = read_u32(fixups, starts_offset) as usize;
+
+    for (seg_index, (vmaddr, fileoff)) in segments.iter().enumerate().take(seg_count) {
+        let seg_info_offset = read_u32(fixups, starts_offset + 4 + 4 * seg_index) as usize;
+        if seg_info_offset == 0 {
+            continue;
+        }
+
+        let starts = starts_offset + seg_info_offset;
+        let page_size = read_u16(fixups, starts + 4) as u64;
+        let pointer_format = read_u16(fixups, starts + 6);
+        let segment_offset = read_u64(fixups, starts + 8);
+        let page_count = read_u16(fixups, starts + 20) as usize;
+
+        if pointer_format != DYLD_CHAINED_PTR_64 && pointer_format != DYLD_CHAINED_PTR_64_OFFSET {
+            return Err(format!(
+                "Chained fixup pointer format {pointer_format} is not supported"
+            ));
+        }
+
+        for page_index in 0..page_count {
+            let page_start = read_u16(fixups, starts + 22 + 2 * page_index);
+            if page_start == DYLD_CHAINED_PTR_START_NONE {
+                continue;
+            }
+
+            let page_address = image_base + segment_offset + page_index as u64 * page_size;
+            let mut file_offset = fileoff + (page_address - vmaddr) + page_start as u64;
+            loop {
+                let pointer = read_u64(exec_data, file_offset as usize);
+                let is_bind = pointer >> 63 != 0;
+                let next = (pointer >> 51) & 0xfff;
+
+                if is_bind {
+                    binds.push(ChainedBind {
+                        file_offset,
+                        import: (pointer & 0xff_ffff) as usize,
+                        addend: (pointer >> 24) & 0xff,
+                        pointer_format,
+                    });
+                }
+
+                if next == 0 {
+                    break;
+                }
+                // Both of the pointer formats we support have a stride of 4 bytes
+                file_offset += next * 4;
+            }
+        }
+    }
+
+    Ok(binds)
 }
 
 /// Constructs a `Metadata` from a host executable binary, and writes it to disk
@@ -341,8 +1117,13 @@ pub(crate) fn preprocess_macho(
         }
     };
 
+    let exec_header = load_struct_inplace::<macho::MachHeader64<LE>>(exec_data, 0);
+    let arch = MachoArch::from_cputype(exec_header.cputype.get(LE));
+
     let mut md = Metadata {
         roc_symbol_vaddresses: collect_roc_definitions(&exec_obj),
+        load_align_constraint: arch.page_size(),
+        arch,
         ..Default::default()
     };
 
@@ -355,177 +1136,79 @@ pub(crate) fn preprocess_macho(
 
     let exec_parsing_duration = exec_parsing_start.elapsed();
 
-    // PLT stands for Procedure Linkage Table which is, put simply, used to call external
-    // procedures/functions whose address isn't known in the time of linking, and is left
-    // to be resolved by the dynamic linker at run time.
+    // Stubs are Mach-O's version of the PLT (Procedure Linkage Table).
+    // They are used to call external procedures/functions whose address isn't known
+    // at link time, and is left to be resolved by the dynamic linker at run time.
     let symbol_and_plt_processing_start = Instant::now();
-    let plt_section_name = "__stubs";
-
-    let (plt_address, plt_offset) = match exec_obj.section_by_name(plt_section_name) {
-        Some(section) => {
-            let file_offset = match section.compressed_file_range() {
-                Ok(
-                    range @ CompressedFileRange {
-                        format: CompressionFormat::None,
-                        ..
-                    },
-                ) => range.offset,
-                _ => {
-                    internal_error!("Surgical linking does not work with compressed plt section");
-                }
-            };
-            (section.address(), file_offset)
-        }
-        None => {
-            internal_error!("Failed to find PLT section. Probably an malformed executable.");
-        }
-    };
-    if verbose {
-        println!("PLT Address: {plt_address:+x}");
-        println!("PLT File Offset: {plt_offset:+x}");
-    }
 
     let app_syms: Vec<_> = exec_obj.symbols().filter(is_roc_undefined).collect();
 
     let mut app_func_addresses: MutMap<u64, &str> = MutMap::default();
-    let mut macho_load_so_offset = None;
-
-    {
-        use macho::{DyldInfoCommand, DylibCommand, Section64, SegmentCommand64};
-
-        let exec_header = load_struct_inplace::<macho::MachHeader64<LE>>(exec_data, 0);
-        let num_load_cmds = exec_header.ncmds.get(LE);
-
-        let mut offset = mem::size_of_val(exec_header);
-
-        let mut stubs_symbol_index = None;
-        let mut stubs_symbol_count = None;
-
-        'cmds: for _ in 0..num_load_cmds {
-            let info = load_struct_inplace::<macho::LoadCommand<LE>>(exec_data, offset);
-            let cmd = info.cmd.get(LE);
-            let cmdsize = info.cmdsize.get(LE);
 
-            if cmd == macho::LC_SEGMENT_64 {
-                let info = load_struct_inplace::<SegmentCommand64<LE>>(exec_data, offset);
+    let commands = load_commands(exec_data);
 
-                if &info.segname[0..6] == b"__TEXT" {
-                    let sections = info.nsects.get(LE);
-
-                    let sections_info = load_structs_inplace::<Section64<LE>>(
-                        exec_data,
-                        offset + mem::size_of_val(info),
-                        sections as usize,
-                    );
-
-                    for section_info in sections_info {
-                        if &section_info.sectname[0..7] == b"__stubs" {
-                            stubs_symbol_index = Some(section_info.reserved1.get(LE));
-                            stubs_symbol_count =
-                                Some(section_info.size.get(LE) / STUB_ADDRESS_OFFSET);
-
-                            break 'cmds;
-                        }
-                    }
-                }
-            }
-
-            offset += cmdsize as usize;
+    // The indirect symbol table says which symbol each stub is for
+    let indirect_symbols: &[u32] = match commands.iter().find(|c| c.cmd == macho::LC_DYSYMTAB) {
+        Some(cmd) => {
+            let dysymtab = load_struct_inplace::<macho::DysymtabCommand<LE>>(exec_data, cmd.offset);
+            load_structs_inplace(
+                exec_data,
+                dysymtab.indirectsymoff.get(LE) as usize,
+                dysymtab.nindirectsyms.get(LE) as usize,
+            )
         }
+        None => &[],
+    };
 
-        let stubs_symbol_index = stubs_symbol_index.unwrap_or_else(|| {
-            panic!("Could not find stubs symbol index.");
-        });
-        let stubs_symbol_count = stubs_symbol_count.unwrap_or_else(|| {
-            panic!("Could not find stubs symbol count.");
-        });
-
-        // Reset offset before looping through load commands again
-        offset = mem::size_of_val(exec_header);
-
-        let shared_lib_filename = shared_lib.file_name();
-
-        for _ in 0..num_load_cmds {
-            let info = load_struct_inplace::<macho::LoadCommand<LE>>(exec_data, offset);
-            let cmd = info.cmd.get(LE);
-            let cmdsize = info.cmdsize.get(LE);
-
-            if cmd == macho::LC_DYLD_INFO_ONLY {
-                let info = load_struct_inplace::<DyldInfoCommand<LE>>(exec_data, offset);
+    for cmd in commands.iter().filter(|c| c.cmd == macho::LC_SEGMENT_64) {
+        let (_, sections) = segment_sections(exec_data, cmd.offset);
 
-                let lazy_bind_offset = info.lazy_bind_off.get(LE) as usize;
+        for section in sections {
+            if section.flags.get(LE) & macho::SECTION_TYPE != macho::S_SYMBOL_STUBS {
+                continue;
+            }
 
-                let lazy_bind_symbols = mach_object::LazyBind::parse(
-                    &exec_data[lazy_bind_offset..],
-                    mem::size_of::<usize>(),
+            let stub_size = section.reserved2.get(LE) as u64;
+            if stub_size != arch.stub_size() {
+                internal_error!(
+                    "Expected stubs of {} bytes for {:?}, but found {} bytes",
+                    arch.stub_size(),
+                    arch,
+                    stub_size
                 );
+            }
 
-                // Find all the lazily-bound roc symbols
-                // (e.g. "_roc__mainForHost_1_exposed")
-                // For Macho, we may need to deal with some GOT stuff here as well.
-                for (i, symbol) in lazy_bind_symbols
-                    .skip(stubs_symbol_index as usize)
-                    .take(stubs_symbol_count as usize)
-                    .enumerate()
-                {
-                    if let Some(sym) = app_syms
-                        .iter()
-                        .find(|app_sym| app_sym.name() == Ok(&symbol.name))
-                    {
-                        let func_address = (i as u64 + 1) * STUB_ADDRESS_OFFSET + plt_address;
-                        let func_offset = (i as u64 + 1) * STUB_ADDRESS_OFFSET + plt_offset;
-                        app_func_addresses.insert(func_address, sym.name().unwrap());
-                        md.plt_addresses
-                            .insert(sym.name().unwrap().to_string(), (func_offset, func_address));
-                    }
+            let first_indirect_symbol = section.reserved1.get(LE) as usize;
+            for i in 0..section.size.get(LE) / stub_size {
+                let symbol_index = indirect_symbols[first_indirect_symbol + i as usize];
+                if symbol_index & (macho::INDIRECT_SYMBOL_LOCAL | macho::INDIRECT_SYMBOL_ABS) != 0 {
+                    continue;
                 }
-            } else if cmd == macho::LC_LOAD_DYLIB {
-                let info = load_struct_inplace::<DylibCommand<LE>>(exec_data, offset);
-                let name_offset = info.dylib.name.offset.get(LE) as usize;
-                let str_start_index = offset + name_offset;
-                let str_end_index = offset + cmdsize as usize;
-                let str_bytes = &exec_data[str_start_index..str_end_index];
-                let path = {
-                    if str_bytes[str_bytes.len() - 1] == 0 {
-                        // If it's nul-terminated, it's a C String.
-                        // Use the unchecked version because these are
-                        // padded with 0s at the end, so since we don't
-                        // know the exact length, using the checked version
-                        // of this can fail due to the interior nul bytes.
-                        //
-                        // Also, we have to use from_ptr instead of
-                        // from_bytes_with_nul_unchecked because currently
-                        // std::ffi::CStr is actually not a char* under
-                        // the hood (!) but rather an array, so to strip
-                        // the trailing null bytes we have to use from_ptr.
-                        let c_str = unsafe { CStr::from_ptr(str_bytes.as_ptr() as *const c_char) };
-
-                        Path::new(c_str.to_str().unwrap())
-                    } else {
-                        // It wasn't nul-terminated, so treat all the bytes
-                        // as the string
 
-                        Path::new(std::str::from_utf8(str_bytes).unwrap())
-                    }
+                let name = match exec_obj.symbol_by_index(SymbolIndex(symbol_index as usize)) {
+                    Ok(sym) => sym.name().unwrap_or_default(),
+                    Err(_) => continue,
                 };
 
-                if path.file_name() == shared_lib_filename {
-                    macho_load_so_offset = Some(offset);
+                if let Some(sym) = app_syms.iter().find(|app_sym| app_sym.name() == Ok(name)) {
+                    let func_address = section.addr.get(LE) + i * stub_size;
+                    let func_offset = section.offset.get(LE) as u64 + i * stub_size;
+                    app_func_addresses.insert(func_address, sym.name().unwrap());
+                    md.plt_addresses
+                        .insert(sym.name().unwrap().to_string(), (func_offset, func_address));
                 }
             }
-
-            offset += cmdsize as usize;
         }
     }
 
     for sym in app_syms.iter() {
         let name = sym.name().unwrap().to_string();
         md.app_functions.push(name.clone());
-        md.dynamic_symbol_indices.insert(name, sym.index().0 as u64);
+        md.symbol_indices.insert(name, sym.index().0 as u64);
     }
     if verbose {
         println!();
-        println!("PLT Symbols for App Functions");
+        println!("Symbols for App Functions");
         for symbol in app_syms.iter() {
             println!("{}: {:+x?}", symbol.index().0, symbol);
         }
@@ -538,7 +1221,7 @@ pub(crate) fn preprocess_macho(
     // look at the text (i.e. code) sections and see collect work needs to be done
     let text_disassembly_start = Instant::now();
 
-    let mut surgeries = Surgeries::new(&app_syms, app_func_addresses);
+    let mut surgeries = Surgeries::new(&app_syms, app_func_addresses, arch);
     surgeries.append_text_sections(exec_data, &exec_obj, verbose);
     md.surgeries = surgeries.surgeries;
 
@@ -555,38 +1238,29 @@ pub(crate) fn preprocess_macho(
             target_lexicon::Endianness::Little => {
                 let scanning_dynamic_deps_start = Instant::now();
 
-                // let ElfDynamicDeps {
-                //     got_app_syms,
-                //     got_sections,
-                //     dynamic_lib_count,
-                //     shared_lib_index,
-                // } = scan_elf_dynamic_deps(
-                //     &exec_obj, &mut md, &app_syms, shared_lib, exec_data, verbose,
-                // );
+                let shared_lib_filename = shared_lib.file_name();
+                let dylibs: Vec<_> = commands
+                    .iter()
+                    .filter(|c| is_dylib_command(c.cmd))
+                    .collect();
+                let app_dylib_index = dylibs
+                    .iter()
+                    .position(|cmd| dylib_path(exec_data, **cmd).file_name() == shared_lib_filename)
+                    .unwrap_or_else(|| {
+                        internal_error!("Host does not link library `{}`!", shared_lib.display());
+                    });
 
                 scanning_dynamic_deps_duration = scanning_dynamic_deps_start.elapsed();
 
                 platform_gen_start = Instant::now();
 
-                // TODO little endian
-                let macho_load_so_offset = match macho_load_so_offset {
-                    Some(offset) => offset,
-                    None => {
-                        internal_error!("Host does not link library `{}`!", shared_lib.display());
-                    }
-                };
-
-                // TODO this is correct on modern Macs (they align to the page size)
-                // but maybe someone can override the alignment somehow? Maybe in the
-                // future this could change? Is there some way to make this more future-proof?
-                md.load_align_constraint = 4096;
-
                 gen_macho_le(
                     exec_data,
                     &mut md,
                     preprocessed_path,
-                    macho_load_so_offset,
-                    target,
+                    &commands,
+                    *dylibs[app_dylib_index],
+                    app_dylib_index as i64 + 1,
                     verbose,
                 )
             }
@@ -651,9 +1325,10 @@ fn gen_macho_le(
     exec_data: &[u8],
     md: &mut Metadata,
     out_filename: &Path,
-    macho_load_so_offset: usize,
-    _target: &Triple,
-    _verbose: bool,
+    commands: &[LoadCommandInfo],
+    app_dylib: LoadCommandInfo,
+    app_ordinal: i64,
+    verbose: bool,
 ) -> MmapMut {
     // Just adding some extra context/useful info here.
     // I was talking to Jakub from the Zig team about macho linking and here are some useful comments:
@@ -665,470 +1340,471 @@ fn gen_macho_le(
     // 3) Jakub wants to make apple tooling absolute is working on zignature for code signing and zig-deploy for ios apps
     // https://github.com/kubkon/zignature
     // https://github.com/kubkon/zig-deploy
+    //
+    // So we don't move any of the host's code or data. The new load commands go in the padding
+    // between the load commands and the first section, and the app goes where `__LINKEDIT` is now.
+    // Surgery moves `__LINKEDIT` after the app, since it has to stay at the end of the file.
+
+    let header_size = mem::size_of::<macho::MachHeader64<LE>>();
+
+    let mut segments = Vec::new();
+    let mut linkedit = None;
+    let mut first_section_offset = exec_data.len();
+    for cmd in commands.iter().filter(|c| c.cmd == macho::LC_SEGMENT_64) {
+        let (segment, sections) = segment_sections(exec_data, cmd.offset);
+        if &segment.segname[..11] == b"__LINKEDIT\0" {
+            linkedit = Some((*cmd, segment, segments.len()));
+        }
+        segments.push((segment.vmaddr.get(LE), segment.fileoff.get(LE)));
 
-    use macho::{Section64, SegmentCommand64};
-
-    let exec_header = load_struct_inplace::<macho::MachHeader64<LE>>(exec_data, 0);
-    let num_load_cmds = exec_header.ncmds.get(LE);
-    let size_of_cmds = exec_header.sizeofcmds.get(LE) as usize;
-
-    // Add a new text segment and data segment
-    let segment_cmd_size = mem::size_of::<SegmentCommand64<LE>>();
-    let section_size = mem::size_of::<Section64<LE>>();
-
-    // We need the full command size, including the dynamic-length string at the end.
-    // To get that, we need to load the command.
-    let info = load_struct_inplace::<macho::LoadCommand<LE>>(exec_data, macho_load_so_offset);
-    let total_cmd_size = info.cmdsize.get(LE) as usize;
-
-    // ======================== Important TODO ==========================
-    // TODO: we accidentally instroduced a big change here.
-    // We use a mix of added_bytes and md.added_byte_count.
-    // Theses should proabably be the same variable.
-    // Also, I just realized that I have been shifting a lot of virtual offsets.
-    // This should not be necessary. If we add a fully 4k of buffer to the file, all of the virtual offsets will still stay aligned.
-    // So a lot of the following work can probably be commented out if we fix that.
-    // Of coruse, it will cost about 4k bytes to a final executable.
-    // This is what the elf version currently does.
-    // Theoretically it is not needed (if we update all virtual addresses in the binary),
-    // but I definitely ran into problems with elf when not adding this extra buffering.
-
-    // Copy header and shift everything to enable more program sections.
-    let added_byte_count = ((2 * segment_cmd_size) + (2 * section_size) - total_cmd_size) as u64;
-    md.added_byte_count = added_byte_count
-        // add some alignment padding
-        + (MIN_SECTION_ALIGNMENT as u64 - added_byte_count % MIN_SECTION_ALIGNMENT as u64);
-
-    md.exec_len = exec_data.len() as u64 + md.added_byte_count;
-
-    let mut out_mmap = open_mmap_mut(out_filename, md.exec_len as usize);
-    let end_of_cmds = size_of_cmds + mem::size_of_val(exec_header);
-
-    // "Delete" the dylib load command - by copying all the bytes before it
-    // and all the bytes after it, while skipping over its bytes.
-    // It has a dynamic-length string at the end that we also need to delete,
-    // in addition to the header.
-    out_mmap[..macho_load_so_offset].copy_from_slice(&exec_data[..macho_load_so_offset]);
-
-    out_mmap[macho_load_so_offset..end_of_cmds - total_cmd_size]
-        .copy_from_slice(&exec_data[macho_load_so_offset + total_cmd_size..end_of_cmds]);
+        for section in sections {
+            let is_zerofill = matches!(
+                section.flags.get(LE) & macho::SECTION_TYPE,
+                macho::S_ZEROFILL | macho::S_GB_ZEROFILL | macho::S_THREAD_LOCAL_ZEROFILL
+            );
+            if !is_zerofill && section.size.get(LE) > 0 {
+                first_section_offset = first_section_offset.min(section.offset.get(LE) as usize);
+            }
+        }
+    }
 
-    // Copy the rest of the file, leaving a gap for the surgical linking to add our 2 commands
-    // (which happens after preprocessing), and some zero padding at the end for alignemnt.
-    // (It seems to cause bugs if that padding isn't there!)
-    let rest_of_data = &exec_data[end_of_cmds..];
-    let start_index = end_of_cmds + md.added_byte_count as usize;
+    let (linkedit_cmd, linkedit, linkedit_index) = match linkedit {
+        Some(linkedit) => linkedit,
+        None => internal_error!("The host has no __LINKEDIT segment"),
+    };
+    let linkedit_fileoff = linkedit.fileoff.get(LE) as usize;
+    let mut linkedit_end = linkedit_fileoff + linkedit.filesize.get(LE) as usize;
 
-    out_mmap[start_index..start_index + rest_of_data.len()].copy_from_slice(rest_of_data);
+    // The image base is the address of the mach header, at the start of __TEXT
+    let image_base = segments
+        .iter()
+        .find(|(vmaddr, fileoff)| *fileoff == 0 && *vmaddr != 0)
+        .map(|(vmaddr, _)| *vmaddr)
+        .unwrap_or_default();
+
+    // The app will change the code, so the signature won't be valid anymore.
+    // We drop it, and re-sign after surgery if the platform needs a signature.
+    if let Some(cmd) = commands.iter().find(|c| c.cmd == macho::LC_CODE_SIGNATURE) {
+        let signature =
+            load_struct_inplace::<macho::LinkeditDataCommand<LE>>(exec_data, cmd.offset);
+        let end = (signature.dataoff.get(LE) + signature.datasize.get(LE)) as usize;
+        if end == linkedit_end {
+            linkedit_end = signature.dataoff.get(LE) as usize;
+        }
+    }
 
-    let out_header = load_struct_inplace_mut::<macho::MachHeader64<LE>>(&mut out_mmap, 0);
+    // Binding opcodes can't easily be patched in place, because removing the app's symbols
+    // changes their lengths. We re-encode them and append them to the end of __LINKEDIT.
+    let mut appended_linkedit = Vec::new();
+    let mut new_dyld_info = None;
 
-    // TODO: this needs to change to adding the 2 new commands when we are ready.
-    // -1 because we're deleting 1 load command and then NOT adding 2 new ones.
+    if let Some(cmd) = commands
+        .iter()
+        .find(|c| c.cmd == macho::LC_DYLD_INFO || c.cmd == macho::LC_DYLD_INFO_ONLY)
     {
-        let added_bytes = -(total_cmd_size as isize); // TODO: Change when add the new sections.
-        out_header.ncmds.set(LE, num_load_cmds - 1);
-        out_header
-            .sizeofcmds
-            .set(LE, (size_of_cmds as isize + added_bytes) as u32);
-    }
-
-    // Go through every command and shift it by added_bytes if it's absolute, unless it's inside the command header
-    let mut offset = mem::size_of_val(exec_header);
-
-    // TODO: Add this back in the future when needed.
-    // let cpu_type = match target.architecture {
-    //     target_lexicon::Architecture::X86_64 => macho::CPU_TYPE_X86_64,
-    //     target_lexicon::Architecture::Aarch64(_) => macho::CPU_TYPE_ARM64,
-    //     _ => {
-    //         // We should have verified this via supported() before calling this function
-    //         unreachable!()
-    //     }
-    // };
-
-    // minus one because we "deleted" a load command
-    for _ in 0..(num_load_cmds - 1) {
-        let info = load_struct_inplace::<macho::LoadCommand<LE>>(&out_mmap, offset);
-        let cmd_size = info.cmdsize.get(LE) as usize;
-
-        match info.cmd.get(LE) {
-            macho::LC_SEGMENT_64 => {
-                let cmd =
-                    load_struct_inplace_mut::<macho::SegmentCommand64<LE>>(&mut out_mmap, offset);
-
-                // Ignore page zero, it never moves.
-                if cmd.segname == "__PAGEZERO\0\0\0\0\0\0".as_bytes() || cmd.vmaddr.get(LE) == 0 {
-                    offset += cmd_size;
-                    continue;
+        let dyld_info = load_struct_inplace::<macho::DyldInfoCommand<LE>>(exec_data, cmd.offset);
+        let bind_off = dyld_info.bind_off.get(LE) as usize;
+        let bind_size = dyld_info.bind_size.get(LE) as usize;
+        let rebase_off = dyld_info.rebase_off.get(LE) as usize;
+        let rebase_size = dyld_info.rebase_size.get(LE) as usize;
+
+        let binds = parse_binds(&exec_data[bind_off..bind_off + bind_size], false)
+            .unwrap_or_else(|e| internal_error!("Failed to parse the host's bind opcodes: {e}"));
+
+        let mut kept_binds = Vec::with_capacity(binds.len());
+        let mut new_rebases = Vec::new();
+        for mut bind in binds {
+            if bind.ordinal == app_ordinal {
+                if bind.addend != 0 {
+                    internal_error!("Binding {} with an addend is not yet supported", bind.name);
                 }
 
-                let old_file_offest = cmd.fileoff.get(LE);
-                // The segment with file offset zero also includes the header.
-                // As such, its file offset does not change.
-                // Instead, its file size should be increased.
-                if old_file_offest > 0 {
-                    cmd.fileoff.set(LE, old_file_offest + md.added_byte_count);
-                    cmd.vmaddr.set(LE, cmd.vmaddr.get(LE) + md.added_byte_count);
-                } else {
-                    cmd.filesize
-                        .set(LE, cmd.filesize.get(LE) + md.added_byte_count);
-                    cmd.vmsize.set(LE, cmd.vmsize.get(LE) + md.added_byte_count);
+                // The pointer will hold the address of the app function after surgery,
+                // which has to be rebased like any other pointer into the executable.
+                let (vmaddr, fileoff) = segments[bind.segment as usize];
+                let file_offset = fileoff + bind.segment_offset;
+                if verbose {
+                    println!(
+                        "Pointer to {} at {:+x}",
+                        bind.name,
+                        vmaddr + bind.segment_offset
+                    );
                 }
-
-                // let num_sections = cmd.nsects.get(LE);
-                // let sections = load_structs_inplace_mut::<macho::Section64<LE >>(
-                //     &mut out_mmap,
-                //     offset + mem::size_of::<macho::SegmentCommand64<LE >>(),
-                //     num_sections as usize,
-                // );
-                // struct Relocation {
-                //     offset: u32,
-                //     num_relocations: u32,
-                // }
-
-                // let mut relocation_offsets = Vec::with_capacity(sections.len());
-
-                // for section in sections {
-                //     section.addr.set(
-                //         LE ,
-                //         section.addr.get(LE) + md.added_byte_count as u64,
-                //     );
-
-                //     // If offset is zero, don't update it.
-                //     // Zero is used for things like BSS that don't exist in the file.
-                //     let old_offset = section.offset.get(LE);
-                //     if old_offset > 0 {
-                //         section
-                //             .offset
-                //             .set(LE , old_offset + md.added_byte_count as u32);
-                //     }
-
-                //     // dbg!(&section.reloff.get(LE));
-                //     // dbg!(section.reloff.get(LE) as i32);
-                //     // dbg!(&section);
-                //     // dbg!(&md.added_byte_count);
-                //     // dbg!(String::from_utf8_lossy(&section.sectname));
-                //     if section.nreloc.get(LE) > 0 {
-                //         section.reloff.set(
-                //             LE ,
-                //             section.reloff.get(LE) + md.added_byte_count as u32,
-                //         );
-                //     }
-
-                //     relocation_offsets.push(Relocation {
-                //         offset: section.reloff.get(LE),
-                //         num_relocations: section.nreloc.get(LE),
-                //     });
-                // }
-
-                // TODO FIXME this is necessary for ARM, but seems to be broken. Skipping for now since we're just targeting x86
-                // for Relocation {
-                //     offset,
-                //     num_relocations,
-                // } in relocation_offsets
-                // {
-                //     let relos = load_structs_inplace_mut::<macho::Relocation<LE >>(
-                //         &mut out_mmap,
-                //         offset as usize,
-                //         num_relocations as usize,
-                //     );
-
-                //     // TODO this has never been tested, because scattered relocations only come up on ARM!
-                //     for relo in relos.iter_mut() {
-                //         if relo.r_scattered(LE , cpu_type) {
-                //             let mut scattered_info = relo.scattered_info(LE);
-
-                //             if !scattered_info.r_pcrel {
-                //                 scattered_info.r_value += md.added_byte_count as u32;
-
-                //                 let new_info = scattered_info.relocation(LE );
-
-                //                 relo.r_word0 = new_info.r_word0;
-                //                 relo.r_word1 = new_info.r_word1;
-                //             }
-                //         }
-                //     }
-                // }
-
-                // TODO this seems to be wrong and unnecessary, and should probably be deleted.
-                // offset += num_sections as usize * mem::size_of::<macho::Section64<LE >>();
+                md.surgeries
+                    .entry(bind.name)
+                    .or_default()
+                    .push(SurgeryEntry {
+                        file_offset,
+                        virtual_offset: VirtualOffset::Absolute,
+                        size: 8,
+                        kind: SurgeryKind::Bytes,
+                    });
+                new_rebases.push((bind.segment, bind.segment_offset));
+            } else {
+                bind.ordinal = shift_ordinal(bind.ordinal, app_ordinal);
+                kept_binds.push(bind);
             }
-            macho::LC_SYMTAB => {
-                let cmd =
-                    load_struct_inplace_mut::<macho::SymtabCommand<LE>>(&mut out_mmap, offset);
+        }
 
-                let sym_offset = cmd.symoff.get(LE);
-                let num_syms = cmd.nsyms.get(LE);
+        let rebases = append_rebases(
+            &exec_data[rebase_off..rebase_off + rebase_size],
+            &new_rebases,
+        )
+        .unwrap_or_else(|e| internal_error!("Failed to parse the host's rebase opcodes: {e}"));
+        let binds = encode_binds(&kept_binds);
+
+        let start = align_by_constraint(linkedit_end, 8);
+        let rebase_off = start;
+        let bind_off = align_by_constraint(rebase_off + rebases.len(), 8);
+        appended_linkedit.resize(bind_off - start, 0);
+        appended_linkedit[rebase_off - start..][..rebases.len()].copy_from_slice(&rebases);
+        appended_linkedit.extend_from_slice(&binds);
+        appended_linkedit.resize(align_by_constraint(appended_linkedit.len(), 8), 0);
+
+        new_dyld_info = Some((rebase_off, rebases.len(), bind_off, binds.len()));
+    }
 
-                if num_syms > 0 {
-                    cmd.symoff.set(LE, sym_offset + md.added_byte_count as u32);
-                }
+    // dyld wants an entry in the chained fixups for every segment, including the app's.
+    // That makes the table grow, so it gets appended to the end of __LINKEDIT too.
+    let mut new_chained_fixups = None;
 
-                if cmd.strsize.get(LE) > 0 {
-                    cmd.stroff
-                        .set(LE, cmd.stroff.get(LE) + md.added_byte_count as u32);
-                }
+    if let Some(cmd) = commands
+        .iter()
+        .find(|c| c.cmd == macho::LC_DYLD_CHAINED_FIXUPS)
+    {
+        let fixups_cmd =
+            load_struct_inplace::<macho::LinkeditDataCommand<LE>>(exec_data, cmd.offset);
+        let dataoff = fixups_cmd.dataoff.get(LE) as usize;
+        let datasize = fixups_cmd.datasize.get(LE) as usize;
+        let fixups = &exec_data[dataoff..dataoff + datasize];
+
+        let mut new_fixups = insert_chained_segments(fixups, linkedit_index, APP_SEGMENTS.len())
+            .unwrap_or_else(|e| internal_error!("Failed to parse chained fixups: {e}"));
+        let imports = parse_chained_imports(&new_fixups)
+            .unwrap_or_else(|e| internal_error!("Failed to parse chained fixups: {e}"));
+        for import in imports.iter() {
+            match import.ordinal.cmp(&app_ordinal) {
+                Ordering::Equal => import.patch(
+                    &mut new_fixups,
+                    macho::BIND_SPECIAL_DYLIB_FLAT_LOOKUP as i64,
+                    true,
+                ),
+                Ordering::Greater => import.patch(
+                    &mut new_fixups,
+                    shift_ordinal(import.ordinal, app_ordinal),
+                    false,
+                ),
+                Ordering::Less => {}
+            }
+        }
 
-                let table = load_structs_inplace_mut::<macho::Nlist64<LE>>(
-                    &mut out_mmap,
-                    sym_offset as usize + md.added_byte_count as usize,
-                    num_syms as usize,
+        let binds = parse_chained_binds(&exec_data[..linkedit_end], fixups, &segments, image_base)
+            .unwrap_or_else(|e| internal_error!("Failed to parse chained fixups: {e}"));
+
+        for bind in binds {
+            let import = &imports[bind.import];
+            if import.ordinal != app_ordinal {
+                continue;
+            }
+            if bind.addend != 0 {
+                internal_error!(
+                    "Binding {} with an addend is not yet supported",
+                    import.name
                 );
+            }
 
-                for entry in table {
-                    let entry_type = entry.n_type & macho::N_TYPE;
-                    if entry_type == macho::N_ABS || entry_type == macho::N_SECT {
-                        entry
-                            .n_value
-                            .set(LE, entry.n_value.get(LE) + md.added_byte_count);
-                    }
-                }
+            if verbose {
+                println!(
+                    "Chained pointer to {} at file offset {:+x}",
+                    import.name, bind.file_offset
+                );
             }
-            macho::LC_DYSYMTAB => {
-                let cmd =
-                    load_struct_inplace_mut::<macho::DysymtabCommand<LE>>(&mut out_mmap, offset);
+            let base = match bind.pointer_format {
+                DYLD_CHAINED_PTR_64_OFFSET => image_base,
+                _ => 0,
+            };
+            md.surgeries
+                .entry(import.name.clone())
+                .or_default()
+                .push(SurgeryEntry {
+                    file_offset: bind.file_offset,
+                    virtual_offset: VirtualOffset::Absolute,
+                    size: 8,
+                    kind: SurgeryKind::ChainedRebase { base },
+                });
+        }
 
-                if cmd.ntoc.get(LE) > 0 {
-                    cmd.tocoff
-                        .set(LE, cmd.tocoff.get(LE) + md.added_byte_count as u32);
-                }
+        let offset = align_by_constraint(linkedit_end, 8) + appended_linkedit.len();
+        appended_linkedit.extend_from_slice(&new_fixups);
+        appended_linkedit.resize(align_by_constraint(appended_linkedit.len(), 8), 0);
+        new_chained_fixups = Some((offset, new_fixups.len()));
+    }
 
-                if cmd.nmodtab.get(LE) > 0 {
-                    cmd.modtaboff
-                        .set(LE, cmd.modtaboff.get(LE) + md.added_byte_count as u32);
-                }
+    md.exec_len = if appended_linkedit.is_empty() {
+        linkedit_end
+    } else {
+        align_by_constraint(linkedit_end, 8) + appended_linkedit.len()
+    } as u64;
 
-                if cmd.nextrefsyms.get(LE) > 0 {
-                    cmd.extrefsymoff
-                        .set(LE, cmd.extrefsymoff.get(LE) + md.added_byte_count as u32);
-                }
+    let mut out_mmap = open_mmap_mut(out_filename, md.exec_len as usize);
+    out_mmap[..linkedit_end].copy_from_slice(&exec_data[..linkedit_end]);
+    out_mmap[md.exec_len as usize - appended_linkedit.len()..].copy_from_slice(&appended_linkedit);
+
+    // Rebuild the load commands, without the app's dylib and the code signature,
+    // and with room for the app's segments right before __LINKEDIT
+    let mut new_cmds = Vec::new();
+    let mut ncmds = 0;
+    for cmd in commands {
+        if cmd.offset == app_dylib.offset || cmd.cmd == macho::LC_CODE_SIGNATURE {
+            continue;
+        }
+        if cmd.offset == linkedit_cmd.offset {
+            md.macho_cmd_loc = (header_size + new_cmds.len()) as u64;
+            new_cmds.resize(
+                new_cmds.len() + APP_SEGMENTS.len() * APP_SEGMENT_CMD_SIZE,
+                0,
+            );
+            ncmds += APP_SEGMENTS.len() as u32;
+        }
+        new_cmds.extend_from_slice(&exec_data[cmd.offset..cmd.offset + cmd.size]);
+        ncmds += 1;
+    }
 
-                if cmd.nindirectsyms.get(LE) > 0 {
-                    cmd.indirectsymoff
-                        .set(LE, cmd.indirectsymoff.get(LE) + md.added_byte_count as u32);
-                }
+    if header_size + new_cmds.len() > first_section_offset {
+        internal_error!(
+            "There is not enough room after the host's load commands to add the app. \
+             Please link the host with `-headerpad 0x1000` or `-headerpad_max_install_names`."
+        );
+    }
 
-                if cmd.nextrel.get(LE) > 0 {
-                    cmd.extreloff
-                        .set(LE, cmd.extreloff.get(LE) + md.added_byte_count as u32);
-                }
+    out_mmap[header_size..first_section_offset].fill(0);
+    out_mmap[header_size..header_size + new_cmds.len()].copy_from_slice(&new_cmds);
 
-                if cmd.nlocrel.get(LE) > 0 {
-                    cmd.locreloff
-                        .set(LE, cmd.locreloff.get(LE) + md.added_byte_count as u32);
-                }
+    let out_header = load_struct_inplace_mut::<macho::MachHeader64<LE>>(&mut out_mmap, 0);
+    out_header.ncmds.set(LE, ncmds);
+    out_header.sizeofcmds.set(LE, new_cmds.len() as u32);
+
+    // Until surgery, the app's segments are empty, so that the preprocessed host is still valid
+    let linkedit_vmaddr = linkedit.vmaddr.get(LE);
+    write_app_segments(
+        &mut out_mmap,
+        md.macho_cmd_loc as usize,
+        [AppSegmentLayout {
+            vmaddr: linkedit_vmaddr,
+            fileoff: linkedit_fileoff as u64,
+            ..Default::default()
+        }; 3],
+    );
 
-                // TODO maybe we need to update something else too - relocations maybe?
-                // I think this also has symbols that need to get moved around.
-                // Look at otool -I at least for the indirect symbols.
-            }
-            macho::LC_TWOLEVEL_HINTS => {
-                let cmd = load_struct_inplace_mut::<macho::TwolevelHintsCommand<LE>>(
+    for cmd in load_commands(&out_mmap) {
+        match cmd.cmd {
+            macho::LC_SEGMENT_64 => {
+                let segment = load_struct_inplace_mut::<macho::SegmentCommand64<LE>>(
                     &mut out_mmap,
-                    offset,
+                    cmd.offset,
                 );
-
-                if cmd.nhints.get(LE) > 0 {
-                    cmd.offset
-                        .set(LE, cmd.offset.get(LE) + md.added_byte_count as u32);
+                if &segment.segname[..11] == b"__LINKEDIT\0" {
+                    let filesize = md.exec_len - linkedit_fileoff as u64;
+                    segment.filesize.set(LE, filesize);
+                    segment.vmsize.set(
+                        LE,
+                        align_by_constraint(filesize as usize, md.load_align_constraint as usize)
+                            as u64,
+                    );
                 }
             }
-            macho::LC_FUNCTION_STARTS => {
-                let cmd = load_struct_inplace_mut::<macho::LinkeditDataCommand<LE>>(
+            macho::LC_DYLD_INFO | macho::LC_DYLD_INFO_ONLY => {
+                let dyld_info = load_struct_inplace_mut::<macho::DyldInfoCommand<LE>>(
                     &mut out_mmap,
-                    offset,
+                    cmd.offset,
                 );
-
-                if cmd.datasize.get(LE) > 0 {
-                    cmd.dataoff
-                        .set(LE, cmd.dataoff.get(LE) + md.added_byte_count as u32);
-                    // TODO: This lists the start of every function. Which, of course, have moved.
-                    // That being said, to my understanding this section is optional and may just be debug information.
-                    // As such, updating it should not be required.
-                    // It will be more work to update due to being in "DWARF-style ULEB128" values.
+                if let Some((rebase_off, rebase_size, bind_off, bind_size)) = new_dyld_info {
+                    dyld_info.rebase_off.set(LE, rebase_off as u32);
+                    dyld_info.rebase_size.set(LE, rebase_size as u32);
+                    dyld_info.bind_off.set(LE, bind_off as u32);
+                    dyld_info.bind_size.set(LE, bind_size as u32);
                 }
-            }
-            macho::LC_DATA_IN_CODE => {
-                let (offset, size) = {
-                    let cmd = load_struct_inplace_mut::<macho::LinkeditDataCommand<LE>>(
-                        &mut out_mmap,
-                        offset,
-                    );
-
-                    if cmd.datasize.get(LE) > 0 {
-                        cmd.dataoff
-                            .set(LE, cmd.dataoff.get(LE) + md.added_byte_count as u32);
-                    }
-                    (cmd.dataoff.get(LE), cmd.datasize.get(LE))
-                };
 
-                // Update every data in code entry.
-                if size > 0 {
-                    let entry_size = mem::size_of::<macho::DataInCodeEntry<LE>>();
-                    let entries = load_structs_inplace_mut::<macho::DataInCodeEntry<LE>>(
+                let lazy_bind_off = dyld_info.lazy_bind_off.get(LE) as usize;
+                let lazy_bind_size = dyld_info.lazy_bind_size.get(LE) as usize;
+                patch_lazy_binds(
+                    &mut out_mmap[lazy_bind_off..lazy_bind_off + lazy_bind_size],
+                    app_ordinal,
+                )
+                .unwrap_or_else(|e| internal_error!("Failed to patch lazy bind opcodes: {e}"));
+            }
+            macho::LC_DYLD_CHAINED_FIXUPS => {
+                if let Some((dataoff, datasize)) = new_chained_fixups {
+                    let fixups_cmd = load_struct_inplace_mut::<macho::LinkeditDataCommand<LE>>(
                         &mut out_mmap,
-                        offset as usize,
-                        size as usize / entry_size,
+                        cmd.offset,
                     );
-                    for entry in entries.iter_mut() {
-                        entry
-                            .offset
-                            .set(LE, entry.offset.get(LE) + md.added_byte_count as u32)
-                    }
+                    fixups_cmd.dataoff.set(LE, dataoff as u32);
+                    fixups_cmd.datasize.set(LE, datasize as u32);
                 }
             }
-            macho::LC_CODE_SIGNATURE
-            | macho::LC_SEGMENT_SPLIT_INFO
-            | macho::LC_DYLIB_CODE_SIGN_DRS
-            | macho::LC_LINKER_OPTIMIZATION_HINT
-            | macho::LC_DYLD_EXPORTS_TRIE
-            | macho::LC_DYLD_CHAINED_FIXUPS => {
-                let cmd = load_struct_inplace_mut::<macho::LinkeditDataCommand<LE>>(
-                    &mut out_mmap,
-                    offset,
-                );
-
-                if cmd.datasize.get(LE) > 0 {
-                    cmd.dataoff
-                        .set(LE, cmd.dataoff.get(LE) + md.added_byte_count as u32);
-                }
-            }
-            macho::LC_ENCRYPTION_INFO_64 => {
-                let cmd = load_struct_inplace_mut::<macho::EncryptionInfoCommand64<LE>>(
+            macho::LC_SYMTAB => {
+                // Two-level namespace symbols record which dylib they come from
+                let symtab = load_struct_inplace::<macho::SymtabCommand<LE>>(&out_mmap, cmd.offset);
+                let (symoff, nsyms) = (symtab.symoff.get(LE), symtab.nsyms.get(LE));
+                let symbols = load_structs_inplace_mut::<macho::Nlist64<LE>>(
                     &mut out_mmap,
-                    offset,
+                    symoff as usize,
+                    nsyms as usize,
                 );
-
-                if cmd.cryptsize.get(LE) > 0 {
-                    cmd.cryptoff
-                        .set(LE, cmd.cryptoff.get(LE) + md.added_byte_count as u32);
+                for symbol in symbols {
+                    if symbol.n_type & macho::N_TYPE != macho::N_UNDF {
+                        continue;
+                    }
+                    let desc = symbol.n_desc.get(LE);
+                    let ordinal = (desc >> 8) as i64;
+                    if ordinal > macho::MAX_LIBRARY_ORDINAL as i64 {
+                        continue;
+                    }
+                    let ordinal = if ordinal == app_ordinal {
+                        macho::DYNAMIC_LOOKUP_ORDINAL as i64
+                    } else {
+                        shift_ordinal(ordinal, app_ordinal)
+                    };
+                    symbol
+                        .n_desc
+                        .set(LE, (desc & 0xff) | ((ordinal as u16) << 8));
                 }
             }
-            macho::LC_DYLD_INFO | macho::LC_DYLD_INFO_ONLY => {
-                let cmd =
-                    load_struct_inplace_mut::<macho::DyldInfoCommand<LE>>(&mut out_mmap, offset);
+            _ => {}
+        }
+    }
 
-                if cmd.rebase_size.get(LE) > 0 {
-                    cmd.rebase_off
-                        .set(LE, cmd.rebase_off.get(LE) + md.added_byte_count as u32);
-                }
+    out_mmap
+}
 
-                if cmd.bind_size.get(LE) > 0 {
-                    cmd.bind_off
-                        .set(LE, cmd.bind_off.get(LE) + md.added_byte_count as u32);
-                }
+/// Fill in the load commands for the app's segments.
+/// Each one is given as (vmaddr, vmsize, fileoff, filesize, align).
+/// Where one of the app's segments ends up. Each segment has a single section.
+#[derive(Debug, Default, Clone, Copy)]
+struct AppSegmentLayout {
+    vmaddr: u64,
+    vmsize: u64,
+    fileoff: u64,
+    filesize: u64,
+    section_size: u64,
+    /// As a power of two
+    align: u32,
+}
 
-                if cmd.weak_bind_size.get(LE) > 0 {
-                    cmd.weak_bind_off
-                        .set(LE, cmd.weak_bind_off.get(LE) + md.added_byte_count as u32);
-                }
+fn write_app_segments(exec_mmap: &mut [u8], cmd_loc: usize, layout: [AppSegmentLayout; 3]) {
+    for (i, (app_segment, layout)) in APP_SEGMENTS.iter().zip(layout).enumerate() {
+        let AppSegmentLayout {
+            vmaddr,
+            vmsize,
+            fileoff,
+            filesize,
+            section_size,
+            align,
+        } = layout;
+
+        let offset = cmd_loc + i * APP_SEGMENT_CMD_SIZE;
+        let segment = load_struct_inplace_mut::<macho::SegmentCommand64<LE>>(exec_mmap, offset);
+        segment.cmd.set(LE, macho::LC_SEGMENT_64);
+        segment.cmdsize.set(LE, APP_SEGMENT_CMD_SIZE as u32);
+        segment.segname = app_segment.segname;
+        segment.vmaddr.set(LE, vmaddr);
+        segment.vmsize.set(LE, vmsize);
+        segment.fileoff.set(LE, fileoff);
+        segment.filesize.set(LE, filesize);
+        segment.maxprot.set(LE, app_segment.prot);
+        segment.initprot.set(LE, app_segment.prot);
+        segment.nsects.set(LE, 1);
+        segment.flags.set(LE, 0);
+
+        let is_zerofill = app_segment.flags == macho::S_ZEROFILL;
+        let section = load_struct_inplace_mut::<macho::Section64<LE>>(
+            exec_mmap,
+            offset + mem::size_of::<macho::SegmentCommand64<LE>>(),
+        );
+        section.sectname = app_segment.sectname;
+        section.segname = app_segment.segname;
+        section.addr.set(LE, vmaddr);
+        section.size.set(LE, section_size);
+        section.offset.set(
+            LE,
+            if is_zerofill || filesize == 0 {
+                0
+            } else {
+                fileoff as u32
+            },
+        );
+        section.align.set(LE, align);
+        section.reloff.set(LE, 0);
+        section.nreloc.set(LE, 0);
+        section.flags.set(LE, app_segment.flags);
+        section.reserved1.set(LE, 0);
+        section.reserved2.set(LE, 0);
+        section.reserved3.set(LE, 0);
+    }
+}
 
-                if cmd.lazy_bind_size.get(LE) > 0 {
-                    cmd.lazy_bind_off
-                        .set(LE, cmd.lazy_bind_off.get(LE) + md.added_byte_count as u32);
-                }
+/// Move everything in `__LINKEDIT` by `delta` bytes in the file
+fn shift_linkedit_offsets(exec_mmap: &mut [u8], delta: u64) {
+    fn shift(field: &mut object::U32<LE>, size: u32, delta: u64) {
+        if size > 0 {
+            field.set(LE, field.get(LE) + delta as u32);
+        }
+    }
 
-                // TODO: Parse and update the related tables here.
-                // It is possible we may just need to delete things that point to stuff that will be in the roc app.
-                // We also may just be able to ignore it (lazy bindings should never run).
-                // This definitely has a list of virtual address that need to be updated.
-                // Some of them definitely will point to the roc app and should probably be removed.
-                // Also `xcrun dyldinfo` is useful for debugging this.
-            }
-            macho::LC_SYMSEG => {
+    for cmd in load_commands(exec_mmap) {
+        match cmd.cmd {
+            macho::LC_SYMTAB => {
                 let cmd =
-                    load_struct_inplace_mut::<macho::SymsegCommand<LE>>(&mut out_mmap, offset);
-
-                if cmd.size.get(LE) > 0 {
-                    cmd.offset
-                        .set(LE, cmd.offset.get(LE) + md.added_byte_count as u32);
-                }
+                    load_struct_inplace_mut::<macho::SymtabCommand<LE>>(exec_mmap, cmd.offset);
+                shift(&mut cmd.symoff, cmd.nsyms.get(LE), delta);
+                shift(&mut cmd.stroff, cmd.strsize.get(LE), delta);
             }
-            macho::LC_MAIN => {
+            macho::LC_DYSYMTAB => {
                 let cmd =
-                    load_struct_inplace_mut::<macho::EntryPointCommand<LE>>(&mut out_mmap, offset);
-
-                cmd.entryoff
-                    .set(LE, cmd.entryoff.get(LE) + md.added_byte_count);
+                    load_struct_inplace_mut::<macho::DysymtabCommand<LE>>(exec_mmap, cmd.offset);
+                shift(&mut cmd.tocoff, cmd.ntoc.get(LE), delta);
+                shift(&mut cmd.modtaboff, cmd.nmodtab.get(LE), delta);
+                shift(&mut cmd.extrefsymoff, cmd.nextrefsyms.get(LE), delta);
+                shift(&mut cmd.indirectsymoff, cmd.nindirectsyms.get(LE), delta);
+                shift(&mut cmd.extreloff, cmd.nextrel.get(LE), delta);
+                shift(&mut cmd.locreloff, cmd.nlocrel.get(LE), delta);
             }
-            macho::LC_NOTE => {
-                let cmd = load_struct_inplace_mut::<macho::NoteCommand<LE>>(&mut out_mmap, offset);
-
-                if cmd.size.get(LE) > 0 {
-                    cmd.offset.set(LE, cmd.offset.get(LE) + md.added_byte_count);
-                }
+            macho::LC_TWOLEVEL_HINTS => {
+                let cmd = load_struct_inplace_mut::<macho::TwolevelHintsCommand<LE>>(
+                    exec_mmap, cmd.offset,
+                );
+                shift(&mut cmd.offset, cmd.nhints.get(LE), delta);
             }
-            macho::LC_ID_DYLIB
-            | macho::LC_LOAD_WEAK_DYLIB
-            | macho::LC_LOAD_DYLIB
-            | macho::LC_REEXPORT_DYLIB
-            | macho::LC_SOURCE_VERSION
-            | macho::LC_IDENT
-            | macho::LC_LINKER_OPTION
-            | macho::LC_BUILD_VERSION
-            | macho::LC_VERSION_MIN_MACOSX
-            | macho::LC_VERSION_MIN_IPHONEOS
-            | macho::LC_VERSION_MIN_WATCHOS
-            | macho::LC_VERSION_MIN_TVOS
-            | macho::LC_UUID
-            | macho::LC_RPATH
-            | macho::LC_ID_DYLINKER
-            | macho::LC_LOAD_DYLINKER
-            | macho::LC_DYLD_ENVIRONMENT
-            | macho::LC_ROUTINES_64
-            | macho::LC_THREAD
-            | macho::LC_UNIXTHREAD
-            | macho::LC_PREBOUND_DYLIB
-            | macho::LC_SUB_FRAMEWORK
-            | macho::LC_SUB_CLIENT
-            | macho::LC_SUB_UMBRELLA
-            | macho::LC_SUB_LIBRARY => {
-                // These don't involve file offsets, so no change is needed for these.
-                // Just continue the loop!
-            }
-            macho::LC_PREBIND_CKSUM => {
-                // We don't *think* we need to change this, but
-                // maybe what we're doing will break checksums?
-            }
-            macho::LC_SEGMENT | macho::LC_ROUTINES | macho::LC_ENCRYPTION_INFO => {
-                // These are 32-bit and unsuppoted
-                unreachable!();
-            }
-            macho::LC_FVMFILE | macho::LC_IDFVMLIB | macho::LC_LOADFVMLIB => {
-                // These are obsolete and unsupported
-                unreachable!()
-            }
-            cmd => {
-                eprintln!(
-                    "- - - Unrecognized Mach-O command during linker preprocessing: 0x{cmd:x?}"
+            macho::LC_CODE_SIGNATURE
+            | macho::LC_SEGMENT_SPLIT_INFO
+            | macho::LC_FUNCTION_STARTS
+            | macho::LC_DATA_IN_CODE
+            | macho::LC_DYLIB_CODE_SIGN_DRS
+            | macho::LC_LINKER_OPTIMIZATION_HINT
+            | macho::LC_DYLD_EXPORTS_TRIE
+            | macho::LC_DYLD_CHAINED_FIXUPS => {
+                let cmd = load_struct_inplace_mut::<macho::LinkeditDataCommand<LE>>(
+                    exec_mmap, cmd.offset,
                 );
-                // panic!(
-                //     "Unrecognized Mach-O command during linker preprocessing: 0x{:x?}",
-                //     cmd
-                // );
+                shift(&mut cmd.dataoff, cmd.datasize.get(LE), delta);
+            }
+            macho::LC_DYLD_INFO | macho::LC_DYLD_INFO_ONLY => {
+                let cmd =
+                    load_struct_inplace_mut::<macho::DyldInfoCommand<LE>>(exec_mmap, cmd.offset);
+                shift(&mut cmd.rebase_off, cmd.rebase_size.get(LE), delta);
+                shift(&mut cmd.bind_off, cmd.bind_size.get(LE), delta);
+                shift(&mut cmd.weak_bind_off, cmd.weak_bind_size.get(LE), delta);
+                shift(&mut cmd.lazy_bind_off, cmd.lazy_bind_size.get(LE), delta);
+                shift(&mut cmd.export_off, cmd.export_size.get(LE), delta);
+            }
+            _ => {
+                // Everything else is either not in __LINKEDIT or doesn't refer to the file at all
             }
         }
-
-        offset += dbg!(cmd_size);
     }
-
-    // cmd_loc should be where the last offset ended
-    md.macho_cmd_loc = offset as u64;
-
-    out_mmap
 }
 
-// fn scan_macho_dynamic_deps(
-//     _exec_obj: &object::File,
-//     _md: &mut Metadata,
-//     _app_syms: &[Symbol],
-//     _shared_lib: &str,
-//     _exec_data: &[u8],
-//     _verbose: bool,
-// ) {
-//     // TODO
-// }
-
 pub(crate) fn surgery_macho(
     roc_app_bytes: &[u8],
     metadata_path: &Path,
@@ -1149,24 +1825,26 @@ pub(crate) fn surgery_macho(
     let md = Metadata::read_from_file(metadata_path);
     let loading_metadata_duration = loading_metadata_start.elapsed();
 
+    if app_sections(&app_obj)
+        .iter()
+        .flatten()
+        .any(|sec| has_absolute_pointers(sec, md.arch))
+    {
+        eprintln!("The surgical linker currently has issue #3609 and would fail linking your app.");
+        eprintln!("Please use `--linker=legacy` to avoid the issue for now.");
+        std::process::exit(1);
+    }
+
     let load_and_mmap_start = Instant::now();
-    let max_out_len = md.exec_len + roc_app_bytes.len() as u64 + md.load_align_constraint;
+    // Each of the 3 app segments and __LINKEDIT can need up to a page of alignment
+    let max_out_len = md.exec_len + roc_app_bytes.len() as u64 + 4 * md.load_align_constraint;
     let mut exec_mmap = open_mmap_mut(executable_path, max_out_len as usize);
     let load_and_mmap_duration = load_and_mmap_start.elapsed();
 
     let out_gen_start = Instant::now();
     let mut offset = 0;
 
-    surgery_macho_help(
-        metadata_path,
-        executable_path,
-        verbose,
-        time,
-        &md,
-        &mut exec_mmap,
-        &mut offset,
-        app_obj,
-    );
+    surgery_macho_help(verbose, &md, &mut exec_mmap, &mut offset, app_obj);
 
     let out_gen_duration = out_gen_start.elapsed();
     let flushing_data_start = Instant::now();
@@ -1178,6 +1856,13 @@ pub(crate) fn surgery_macho(
     // Also drop files to to ensure data is fully written here.
     drop(exec_mmap);
 
+    // __LINKEDIT has to end exactly at the end of the file, or codesign will reject it
+    std::fs::OpenOptions::new()
+        .write(true)
+        .open(executable_path)
+        .and_then(|file| file.set_len(offset as u64))
+        .unwrap_or_else(|e| internal_error!("{}", e));
+
     let flushing_data_duration = flushing_data_start.elapsed();
 
     // Make sure the final executable has permision to execute.
@@ -1193,6 +1878,22 @@ pub(crate) fn surgery_macho(
         fs::set_permissions(executable_path, perms).unwrap_or_else(|e| internal_error!("{}", e));
     }
 
+    // Apple Silicon refuses to run unsigned code, and we dropped the host's signature
+    #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
+    if md.arch == MachoArch::Aarch64 {
+        let output = std::process::Command::new("codesign")
+            .args(["--force", "--sign", "-"])
+            .arg(executable_path)
+            .output()
+            .unwrap_or_else(|e| internal_error!("Failed to run codesign: {e}"));
+        if !output.status.success() {
+            internal_error!(
+                "codesign failed:\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+    }
+
     let total_duration = total_start.elapsed();
 
     if verbose || time {
@@ -1212,30 +1913,96 @@ pub(crate) fn surgery_macho(
     }
 }
 
-#[allow(clippy::too_many_arguments)]
+/// The app sections we copy into the executable, in the order of our segments
+fn app_sections<'a, 'b>(app_obj: &'b object::File<'a>) -> [Vec<Section<'a, 'b>>; 3] {
+    // TODO: In the future Roc may use a data section to store memoized toplevel thunks
+    // in development builds for caching the results of top-level constants
+    let rodata_sections: Vec<Section> = app_obj
+        .sections()
+        .filter(|sec| {
+            matches!(
+                sec.kind(),
+                SectionKind::ReadOnlyData | SectionKind::ReadOnlyString
+            )
+        })
+        .collect();
+
+    // bss section is like rodata section, but it has zero file size and non-zero virtual size.
+    let bss_sections: Vec<Section> = app_obj
+        .sections()
+        .filter(|sec| sec.kind() == SectionKind::UninitializedData)
+        .collect();
+
+    let text_sections: Vec<Section> = app_obj
+        .sections()
+        .filter(|sec| sec.kind() == SectionKind::Text)
+        .collect();
+
+    [rodata_sections, bss_sections, text_sections]
+}
+
+/// Absolute pointers would need rebasing, which we don't do for the app yet.
+/// The difference of two symbols (a `SUBTRACTOR` followed by an `UNSIGNED`) is fine though.
+fn has_absolute_pointers(sec: &Section, arch: MachoArch) -> bool {
+    let mut after_subtractor = false;
+    sec.relocations().any(|(_, rel)| {
+        let is_absolute = rel.kind() == RelocationKind::Absolute && !after_subtractor;
+        after_subtractor = is_subtractor(&rel, arch);
+        is_absolute
+    })
+}
+
+/// The relocation types are numbered separately for each architecture
+fn is_subtractor(rel: &Relocation, arch: MachoArch) -> bool {
+    let subtractor = match arch {
+        MachoArch::X86_64 => macho::X86_64_RELOC_SUBTRACTOR,
+        MachoArch::Aarch64 => macho::ARM64_RELOC_SUBTRACTOR,
+    };
+    matches!(rel.kind(), RelocationKind::MachO { value, .. } if value == subtractor)
+}
+
 fn surgery_macho_help(
-    _metadata_filename: &Path,
-    _out_filename: &Path,
     verbose: bool,
-    _time: bool,
     md: &Metadata,
     exec_mmap: &mut MmapMut,
     offset_ref: &mut usize, // TODO return this instead of taking a mutable reference to it
     app_obj: object::File,
 ) {
-    let mut offset = align_by_constraint(md.exec_len as usize, MIN_SECTION_ALIGNMENT);
-    // let new_rodata_section_offset = offset;
-
-    // Align physical and virtual address of new segment.
-    let mut virt_offset = align_to_offset_by_constraint(
-        md.last_vaddr as usize,
-        offset,
-        md.load_align_constraint as usize,
-    );
-    let new_rodata_section_vaddr = virt_offset;
+    let page_size = md.load_align_constraint as usize;
+    let commands = load_commands(exec_mmap);
+
+    // The app goes where __LINKEDIT is now, and __LINKEDIT moves after it
+    let mut section_number = 0;
+    let mut linkedit = None;
+    for cmd in commands.iter().filter(|c| c.cmd == macho::LC_SEGMENT_64) {
+        if cmd.offset < md.macho_cmd_loc as usize {
+            let (_, sections) = segment_sections(exec_mmap, cmd.offset);
+            section_number += sections.len() as u8;
+        }
+
+        let (segment, _) = segment_sections(exec_mmap, cmd.offset);
+        if &segment.segname[..11] == b"__LINKEDIT\0" {
+            linkedit = Some((
+                cmd.offset,
+                segment.fileoff.get(LE) as usize,
+                segment.filesize.get(LE) as usize,
+                segment.vmaddr.get(LE) as usize,
+            ));
+        }
+    }
+    let (linkedit_cmd_offset, linkedit_fileoff, linkedit_filesize, linkedit_vmaddr) = match linkedit
+    {
+        Some(linkedit) => linkedit,
+        None => internal_error!("The preprocessed host has no __LINKEDIT segment"),
+    };
+    let linkedit_data = exec_mmap[linkedit_fileoff..linkedit_fileoff + linkedit_filesize].to_vec();
+    exec_mmap[linkedit_fileoff..].fill(0);
+
+    let mut offset = linkedit_fileoff;
+    let mut virt_offset = linkedit_vmaddr;
     if verbose {
         println!();
-        println!("New Virtual Rodata Section Address: {new_rodata_section_vaddr:+x?}");
+        println!("New Virtual Rodata Section Address: {virt_offset:+x?}");
     }
 
     // First decide on sections locations and then recode every exact symbol locations.
@@ -1244,95 +2011,97 @@ fn surgery_macho_help(
     let symbols = app_obj.symbols().collect::<Vec<Symbol>>();
     let mut section_offset_map: MutMap<SectionIndex, (usize, usize)> = MutMap::default();
     let mut symbol_vaddr_map: MutMap<SymbolIndex, usize> = MutMap::default();
-    let mut app_func_vaddr_map: MutMap<String, usize> = MutMap::default();
-    let mut app_func_size_map: MutMap<String, u64> = MutMap::default();
-
-    // TODO: In the future Roc may use a data section to store memoized toplevel thunks
-    // in development builds for caching the results of top-level constants
-
-    let rodata_sections: Vec<Section> = app_obj
-        .sections()
-        .filter(|sec| sec.kind() == SectionKind::ReadOnlyData)
-        .collect();
-
-    // bss section is like rodata section, but it has zero file size and non-zero virtual size.
-    let bss_sections: Vec<Section> = app_obj
-        .sections()
-        .filter(|sec| sec.kind() == SectionKind::UninitializedData)
-        .collect();
+    let mut app_func_vaddr_map: MutMap<String, (usize, u8)> = MutMap::default();
 
-    let text_sections: Vec<Section> = app_obj
-        .sections()
-        .filter(|sec| sec.kind() == SectionKind::Text)
-        .collect();
-    if text_sections.is_empty() {
+    let sections = app_sections(&app_obj);
+    if sections[2].is_empty() {
         internal_error!("No text sections found. This application has no code.");
     }
 
     // Calculate addresses and load symbols.
-    // Note, it is important the bss sections come after the rodata sections.
-    for sec in rodata_sections
-        .iter()
-        .chain(bss_sections.iter())
-        .chain(text_sections.iter())
-    {
-        offset = align_by_constraint(offset, MIN_SECTION_ALIGNMENT);
-        virt_offset =
-            align_to_offset_by_constraint(virt_offset, offset, md.load_align_constraint as usize);
-        if verbose {
-            println!(
-                "Section, {}, is being put at offset: {:+x}(virt: {:+x})",
-                sec.name().unwrap(),
-                offset,
-                virt_offset
-            )
-        }
-        section_offset_map.insert(sec.index(), (offset, virt_offset));
-        for sym in symbols.iter() {
-            if sym.section() == SymbolSection::Section(sec.index()) {
-                let name = sym.name().unwrap_or_default().to_string();
-                if !md.roc_symbol_vaddresses.contains_key(&name) {
-                    symbol_vaddr_map.insert(sym.index(), virt_offset + sym.address() as usize);
+    // Each group of sections gets its own page aligned segment, with the protection it needs.
+    let mut segment_layout = [AppSegmentLayout::default(); 3];
+    for (i, segment_sections) in sections.iter().enumerate() {
+        let is_zerofill = APP_SEGMENTS[i].flags == macho::S_ZEROFILL;
+        offset = align_by_constraint(offset, page_size);
+        virt_offset = align_by_constraint(virt_offset, page_size);
+        let (segment_offset, segment_vaddr) = (offset, virt_offset);
+        let mut max_align = MIN_SECTION_ALIGNMENT;
+
+        for sec in segment_sections {
+            let align = MIN_SECTION_ALIGNMENT.max(sec.align() as usize);
+            max_align = max_align.max(align);
+            if is_zerofill {
+                virt_offset = align_by_constraint(virt_offset, align);
+            } else {
+                offset = align_by_constraint(offset, align);
+                virt_offset = segment_vaddr + (offset - segment_offset);
+            }
+            if verbose {
+                println!(
+                    "Section, {}, is being put at offset: {:+x}(virt: {:+x})",
+                    sec.name().unwrap(),
+                    offset,
+                    virt_offset
+                )
+            }
+            section_offset_map.insert(sec.index(), (offset, virt_offset));
+            for sym in symbols.iter() {
+                if sym.section() == SymbolSection::Section(sec.index()) {
+                    let name = sym.name().unwrap_or_default().to_string();
+                    let address = virt_offset + (sym.address() - sec.address()) as usize;
+                    if !md
+                        .roc_symbol_vaddresses
+                        .contains_key(name.trim_start_matches('_'))
+                    {
+                        symbol_vaddr_map.insert(sym.index(), address);
+                    }
+                    if md.app_functions.contains(&name) {
+                        app_func_vaddr_map.insert(name, (address, section_number + 1 + i as u8));
+                    }
                 }
-                if md.app_functions.contains(&name) {
-                    app_func_vaddr_map.insert(name.clone(), virt_offset + sym.address() as usize);
-                    app_func_size_map.insert(name, sym.size());
+            }
+
+            if is_zerofill {
+                // bss sections only modify the virtual size.
+                virt_offset += sec.size() as usize;
+            } else {
+                let section_size = match sec.file_range() {
+                    Some((_, size)) => size,
+                    None => 0,
+                };
+                if section_size != sec.size() {
+                    internal_error!( "We do not deal with non bss sections that have different on disk and in memory sizes");
                 }
+                offset += section_size as usize;
+                virt_offset += sec.size() as usize;
             }
         }
-        let section_size = match sec.file_range() {
-            Some((_, size)) => size,
-            None => 0,
+
+        // Empty segments are unusual, so every segment gets at least a page
+        let vmsize = align_by_constraint((virt_offset - segment_vaddr).max(1), page_size);
+        let filesize = offset - segment_offset;
+        segment_layout[i] = AppSegmentLayout {
+            vmaddr: segment_vaddr as u64,
+            vmsize: vmsize as u64,
+            fileoff: segment_offset as u64,
+            filesize: filesize as u64,
+            section_size: (virt_offset - segment_vaddr) as u64,
+            align: max_align.trailing_zeros(),
         };
-        if sec.name().unwrap_or_default().starts_with("__BSS") {
-            // bss sections only modify the virtual size.
-            virt_offset += sec.size() as usize;
-        } else if section_size != sec.size() {
-            internal_error!( "We do not deal with non bss sections that have different on disk and in memory sizes");
-        } else {
-            offset += section_size as usize;
-            virt_offset += sec.size() as usize;
-        }
+        virt_offset = segment_vaddr + vmsize;
     }
     if verbose {
         println!("Data Relocation Offsets: {symbol_vaddr_map:+x?}");
         println!("Found App Function Symbols: {app_func_vaddr_map:+x?}");
     }
 
-    // let (new_text_section_offset, new_text_section_vaddr) = text_sections
-    //     .iter()
-    //     .map(|sec| section_offset_map.get(&sec.index()).unwrap())
-    //     .min()
-    //     .unwrap();
-    // let (new_text_section_offset, new_text_section_vaddr) =
-    //     (*new_text_section_offset, *new_text_section_vaddr);
-
     // Move data and deal with relocations.
-    for sec in rodata_sections
-        .iter()
-        .chain(bss_sections.iter())
-        .chain(text_sections.iter())
-    {
+    for sec in sections.iter().flatten() {
+        if sec.kind() == SectionKind::UninitializedData {
+            continue;
+        }
+
         let data = match sec.data() {
             Ok(data) => data,
             Err(err) => {
@@ -1354,13 +2123,32 @@ fn surgery_macho_help(
                 "Processing Relocations for Section: 0x{sec:+x?} @ {section_offset:+x} (virt: {section_virtual_offset:+x})"
             );
         }
-        for rel in sec.relocations() {
+
+        // Mach-O relocations can come in pairs. The first one modifies the second.
+        let mut pending_addend = 0;
+        let mut pending_subtrahend = None;
+
+        for (rel_offset, rel) in sec.relocations() {
             if verbose {
-                println!("\tFound Relocation: {rel:+x?}");
+                println!("\tFound Relocation: {:+x?}", (rel_offset, &rel));
             }
-            match rel.1.target() {
+
+            let virt_base = section_virtual_offset as i64 + rel_offset as i64;
+            let base = section_offset + rel_offset as usize;
+
+            if let (RelocationKind::MachO { value, .. }, RelocationTarget::Section(index)) =
+                (rel.kind(), rel.target())
+            {
+                if md.arch == MachoArch::Aarch64 && value == macho::ARM64_RELOC_ADDEND {
+                    // The addend is a 24-bit signed number stored where the symbol index would be
+                    pending_addend = ((index.0 as i64) << 40) >> 40;
+                    continue;
+                }
+            }
+
+            let target_offset = match rel.target() {
                 RelocationTarget::Symbol(index) => {
-                    let target_offset = if let Some(target_offset) = symbol_vaddr_map.get(&index) {
+                    if let Some(target_offset) = symbol_vaddr_map.get(&index) {
                         if verbose {
                             println!("\t\tRelocation targets symbol in app at: {target_offset:+x}");
                         }
@@ -1371,171 +2159,124 @@ fn surgery_macho_help(
                             .and_then(|sym| sym.name())
                             .ok()
                             .and_then(|name| {
-                                md.roc_symbol_vaddresses.get(name).map(|address| {
-                                    let vaddr = (*address + md.added_byte_count) as i64;
-                                    if verbose {
-                                        println!(
+                                md.roc_symbol_vaddresses
+                                    .get(name.trim_start_matches('_'))
+                                    .map(|address| {
+                                        let vaddr = *address as i64;
+                                        if verbose {
+                                            println!(
                                             "\t\tRelocation targets symbol in host: {name} @ {vaddr:+x}"
                                         );
-                                    }
-                                    vaddr
-                                })
+                                        }
+                                        vaddr
+                                    })
                             })
-                    };
-
-                    if let Some(target_offset) = target_offset {
-                        let virt_base = section_virtual_offset + rel.0 as usize;
-                        let base = section_offset + rel.0 as usize;
-                        let target: i64 = match rel.1.kind() {
-                            RelocationKind::Relative | RelocationKind::PltRelative => {
-                                target_offset - virt_base as i64 + rel.1.addend()
-                            }
-                            x => {
-                                internal_error!("Relocation Kind not yet support: {:?}", x);
-                            }
-                        };
-                        if verbose {
-                            println!(
-                                "\t\tRelocation base location: {base:+x} (virt: {virt_base:+x})"
-                            );
-                            println!("\t\tFinal relocation target offset: {target:+x}");
-                        }
-                        match rel.1.size() {
-                            32 => {
-                                let data = (target as i32).to_le_bytes();
-                                exec_mmap[base..base + 4].copy_from_slice(&data);
-                            }
-                            64 => {
-                                let data = target.to_le_bytes();
-                                exec_mmap[base..base + 8].copy_from_slice(&data);
-                            }
-                            x => {
-                                internal_error!("Relocation size not yet supported: {}", x);
-                            }
+                    }
+                }
+                RelocationTarget::Section(index) => {
+                    // The target address is already in the data, relative to where the sections were in the object file.
+                    // Shifting it by how far that section moved gives us our target.
+                    // Only x86_64 does this; arm64 always uses symbols.
+                    match (
+                        section_offset_map.get(&index),
+                        app_obj.section_by_index(index),
+                    ) {
+                        (Some((_, target_virt)), Ok(target_sec)) => {
+                            Some(*target_virt as i64 - target_sec.address() as i64)
                         }
-                    } else if matches!(app_obj.symbol_by_index(index), Ok(sym) if ["__divti3", "__udivti3", "___divti3", "___udivti3"].contains(&sym.name().unwrap_or_default()))
-                    {
-                        // Explicitly ignore some symbols that are currently always linked.
-                        continue;
-                    } else {
-                        internal_error!(
-                            "Undefined Symbol in relocation, {:+x?}: {:+x?}",
-                            rel,
-                            app_obj.symbol_by_index(index)
-                        );
+                        _ => None,
                     }
                 }
-
                 _ => {
                     internal_error!("Relocation target not yet support: {:+x?}", rel);
                 }
+            };
+
+            let target_offset = match target_offset {
+                Some(target_offset) => target_offset,
+                None => {
+                    if let RelocationTarget::Symbol(index) = rel.target() {
+                        if matches!(app_obj.symbol_by_index(index), Ok(sym) if ["__divti3", "__udivti3", "___divti3", "___udivti3"].contains(&sym.name().unwrap_or_default()))
+                        {
+                            // Explicitly ignore some symbols that are currently always linked.
+                            continue;
+                        }
+                    }
+                    internal_error!(
+                        "Undefined Symbol in relocation, {:+x?}: {:+x?}",
+                        rel,
+                        rel.target()
+                    );
+                }
+            };
+
+            if is_subtractor(&rel, md.arch) {
+                pending_subtrahend = Some(target_offset);
+                continue;
+            }
+
+            let is_section_relative = matches!(rel.target(), RelocationTarget::Section(_));
+            let result = match md.arch {
+                MachoArch::X86_64 => apply_x86_64_relocation(
+                    exec_mmap,
+                    &rel,
+                    base,
+                    virt_base,
+                    target_offset,
+                    is_section_relative,
+                    section_virtual_offset as i64 - sec.address() as i64,
+                    pending_subtrahend.take(),
+                ),
+                MachoArch::Aarch64 => apply_aarch64_relocation(
+                    exec_mmap,
+                    &rel,
+                    base,
+                    virt_base,
+                    target_offset + mem::take(&mut pending_addend),
+                    pending_subtrahend.take(),
+                ),
+            };
+            if let Err(err) = result {
+                internal_error!("Failed to apply relocation {:+x?}: {}", rel, err);
             }
         }
     }
 
     // Flush app only data to speed up write to disk.
     exec_mmap
-        .flush_async_range(md.exec_len as usize, offset - md.exec_len as usize)
+        .flush_async_range(linkedit_fileoff, offset - linkedit_fileoff)
         .unwrap_or_else(|e| internal_error!("{}", e));
 
     // TODO: look into merging symbol tables, debug info, and eh frames to enable better debugger experience.
 
-    // let mut cmd_offset = md.macho_cmd_loc as usize;
-
-    // // Load this section (we made room for it earlier) and then mutate all its data to make it the desired command
-    // {
-    //     let cmd =
-    //         load_struct_inplace_mut::<macho::SegmentCommand64<LE >>(exec_mmap, cmd_offset);
-    //     let size_of_section = mem::size_of::<macho::Section64<LE >>() as u32;
-    //     let size_of_cmd = mem::size_of_val(cmd);
-
-    //     cmd_offset += size_of_cmd;
-
-    //     cmd.cmd.set(LE , macho::LC_SEGMENT_64);
-    //     cmd.cmdsize
-    //         .set(LE , size_of_section + size_of_cmd as u32);
-    //     cmd.segname = *b"__DATA_CONST\0\0\0\0";
-    //     cmd.vmaddr
-    //         .set(LE , new_rodata_section_vaddr as u64);
-    //     cmd.vmsize.set(
-    //         LE ,
-    //         (new_text_section_vaddr - new_rodata_section_vaddr) as u64,
-    //     );
-    //     cmd.fileoff
-    //         .set(LE , new_rodata_section_offset as u64);
-    //     cmd.filesize.set(
-    //         LE ,
-    //         (new_text_section_offset - new_rodata_section_offset) as u64,
-    //     );
-    //     cmd.nsects.set(LE , 1);
-    //     cmd.maxprot.set(LE , 0x00000003);
-    //     cmd.initprot.set(LE , 0x00000003);
-
-    //     // TODO set protection
-    // }
-
-    // {
-    //     let cmd = load_struct_inplace_mut::<macho::Section64<LE >>(exec_mmap, cmd_offset);
-    //     let size_of_cmd = mem::size_of_val(cmd);
-
-    //     cmd_offset += size_of_cmd;
-
-    //     cmd.sectname = *b"__const\0\0\0\0\0\0\0\0\0";
-    //     cmd.segname = *b"__DATA_CONST\0\0\0\0";
-    //     cmd.addr.set(LE , new_rodata_section_vaddr as u64);
-    //     cmd.size.set(
-    //         LE ,
-    //         (new_text_section_offset - new_rodata_section_offset) as u64,
-    //     );
-    //     cmd.offset.set(LE , 0); // TODO is this offset since the start of the file, or segment offset?
-    //     cmd.align.set(LE , 12); // TODO should this be 4096?
-    //     cmd.reloff.set(LE , 264); // TODO this should NOT be hardcoded! Should get it from somewhere.
-    // }
-
-    // {
-    //     let cmd =
-    //         load_struct_inplace_mut::<macho::SegmentCommand64<LE >>(exec_mmap, cmd_offset);
-    //     let size_of_section = mem::size_of::<macho::Section64<LE >>() as u32;
-    //     let size_of_cmd = mem::size_of_val(cmd);
-
-    //     cmd_offset += size_of_cmd;
-
-    //     cmd.cmd.set(LE , macho::LC_SEGMENT_64);
-    //     cmd.cmdsize
-    //         .set(LE , size_of_section + size_of_cmd as u32);
-    //     cmd.segname = *b"__TEXT\0\0\0\0\0\0\0\0\0\0";
-    //     cmd.vmaddr.set(LE , new_text_section_vaddr as u64);
-    //     cmd.vmsize
-    //         .set(LE , (offset - new_text_section_offset) as u64);
-    //     cmd.fileoff
-    //         .set(LE , new_text_section_offset as u64);
-    //     cmd.filesize
-    //         .set(LE , (offset - new_text_section_offset) as u64);
-    //     cmd.nsects.set(LE , 1);
-    //     cmd.maxprot.set(LE , 0x00000005); // this is what a zig-generated host had
-    //     cmd.initprot.set(LE , 0x00000005); // this is what a zig-generated host had
-    // }
-
-    // {
-    //     let cmd = load_struct_inplace_mut::<macho::Section64<LE >>(exec_mmap, cmd_offset);
-
-    //     cmd.segname = *b"__TEXT\0\0\0\0\0\0\0\0\0\0";
-    //     cmd.sectname = *b"__text\0\0\0\0\0\0\0\0\0\0";
-    //     cmd.addr.set(LE , new_text_section_vaddr as u64);
-    //     cmd.size
-    //         .set(LE , (offset - new_text_section_offset) as u64);
-    //     cmd.offset.set(LE , 0); // TODO is this offset since the start of the file, or segment offset?
-    //     cmd.align.set(LE , 12); // TODO this is 4096 (2^12) - which load_align_constraint does, above - but should it?
-    //     cmd.flags.set(LE , 0x80000400); // TODO this is what a zig-generated host had
-    //     cmd.reloff.set(LE , 264); // TODO this should NOT be hardcoded! Should get it from somewhere.
-    // }
+    write_app_segments(exec_mmap, md.macho_cmd_loc as usize, segment_layout);
+
+    // Put __LINKEDIT back after the app
+    let new_linkedit_fileoff = align_by_constraint(offset, page_size);
+    let new_linkedit_vmaddr = align_by_constraint(virt_offset, page_size);
+    exec_mmap[new_linkedit_fileoff..new_linkedit_fileoff + linkedit_filesize]
+        .copy_from_slice(&linkedit_data);
+    {
+        let segment =
+            load_struct_inplace_mut::<macho::SegmentCommand64<LE>>(exec_mmap, linkedit_cmd_offset);
+        segment.fileoff.set(LE, new_linkedit_fileoff as u64);
+        segment.vmaddr.set(LE, new_linkedit_vmaddr as u64);
+    }
+    shift_linkedit_offsets(exec_mmap, (new_linkedit_fileoff - linkedit_fileoff) as u64);
+    offset = new_linkedit_fileoff + linkedit_filesize;
 
     // Update calls from platform and dynamic symbols.
-    // let dynsym_offset = md.dynamic_symbol_table_section_offset + md.added_byte_count;
+    let symtab = commands
+        .iter()
+        .find(|c| c.cmd == macho::LC_SYMTAB)
+        .map(|cmd| {
+            let symtab = load_struct_inplace::<macho::SymtabCommand<LE>>(exec_mmap, cmd.offset);
+            symtab.symoff.get(LE) as usize
+        });
 
     for func_name in md.app_functions.iter() {
-        let func_virt_offset = match app_func_vaddr_map.get(func_name) {
-            Some(offset) => *offset as u64,
+        let (func_virt_offset, func_section_number) = match app_func_vaddr_map.get(func_name) {
+            Some((offset, section_number)) => (*offset as u64, *section_number),
             None => {
                 internal_error!("Function, {}, was not defined by the app", &func_name);
             }
@@ -1552,74 +2293,654 @@ fn surgery_macho_help(
                 println!("\tPerforming surgery: {s:+x?}");
             }
             let surgery_virt_offset = match s.virtual_offset {
-                VirtualOffset::Relative(vs) => (vs + md.added_byte_count) as i64,
+                VirtualOffset::Relative(vs) => vs as i64,
                 VirtualOffset::Absolute => 0,
             };
-            match s.size {
-                4 => {
+            let file_offset = s.file_offset as usize;
+            match (&s.kind, s.size) {
+                (SurgeryKind::Aarch64Branch, _) => {
+                    let target = func_virt_offset as i64 - surgery_virt_offset;
+                    let inst = aarch64::read(exec_mmap, file_offset);
+                    let inst = aarch64::set_branch_offset(inst, target)
+                        .unwrap_or_else(|e| internal_error!("{e}"));
+                    aarch64::write(exec_mmap, file_offset, inst);
+                }
+                (SurgeryKind::ChainedRebase { base }, _) => {
+                    // Keep the offset to the next fixup in the chain, but clear the bind bit
+                    const NEXT_MASK: u64 = 0xfff << 51;
+                    const TARGET_MASK: u64 = (1 << 36) - 1;
+                    let pointer = read_u64(exec_mmap, file_offset);
+                    let target = (func_virt_offset - base) & TARGET_MASK;
+                    let pointer = (pointer & NEXT_MASK) | target;
+                    exec_mmap[file_offset..file_offset + 8].copy_from_slice(&pointer.to_le_bytes());
+                }
+                (SurgeryKind::Bytes, 4) => {
                     let target = (func_virt_offset as i64 - surgery_virt_offset) as i32;
                     if verbose {
                         println!("\tTarget Jump: {target:+x}");
                     }
                     let data = target.to_le_bytes();
-                    exec_mmap[(s.file_offset + md.added_byte_count) as usize
-                        ..(s.file_offset + md.added_byte_count) as usize + 4]
-                        .copy_from_slice(&data);
+                    exec_mmap[file_offset..file_offset + 4].copy_from_slice(&data);
                 }
-                8 => {
+                (SurgeryKind::Bytes, 8) => {
                     let target = func_virt_offset as i64 - surgery_virt_offset;
                     if verbose {
                         println!("\tTarget Jump: {target:+x}");
                     }
                     let data = target.to_le_bytes();
-                    exec_mmap[(s.file_offset + md.added_byte_count) as usize
-                        ..(s.file_offset + md.added_byte_count) as usize + 8]
-                        .copy_from_slice(&data);
+                    exec_mmap[file_offset..file_offset + 8].copy_from_slice(&data);
                 }
-                x => {
+                (_, x) => {
                     internal_error!("Surgery size not yet supported: {}", x);
                 }
             }
         }
 
-        // Replace plt call code with just a jump.
-        // This is a backup incase we missed a call to the plt.
+        // Replace the stub with just a jump.
+        // This is a backup incase we missed a call to the stub.
         if let Some((plt_off, plt_vaddr)) = md.plt_addresses.get(func_name) {
-            let plt_off = (*plt_off + md.added_byte_count) as usize;
-            let plt_vaddr = *plt_vaddr + md.added_byte_count;
-            let jmp_inst_len = 5;
-            let target =
-                (func_virt_offset as i64 - (plt_vaddr as i64 + jmp_inst_len as i64)) as i32;
+            let plt_off = *plt_off as usize;
+            let stub_size = md.arch.stub_size() as usize;
             if verbose {
                 println!("\tPLT: {plt_off:+x}, {plt_vaddr:+x}");
-                println!("\tTarget Jump: {target:+x}");
-            }
-            let data = target.to_le_bytes();
-            exec_mmap[plt_off] = 0xE9;
-            exec_mmap[plt_off + 1..plt_off + jmp_inst_len].copy_from_slice(&data);
-            for i in jmp_inst_len..PLT_ADDRESS_OFFSET as usize {
-                exec_mmap[plt_off + i] = 0x90;
-            }
-        }
-
-        // Commented out because it doesn't apply to mach-o
-        // if let Some(i) = md.dynamic_symbol_indices.get(func_name) {
-        //     let sym = load_struct_inplace_mut::<elf::Sym64<LE >>(
-        //         exec_mmap,
-        //         dynsym_offset as usize + *i as usize * mem::size_of::<elf::Sym64<LE >>(),
-        //     );
-        //     sym.st_value.set(LE , func_virt_offset as u64);
-        //     sym.st_size.set(
-        //         LE ,
-        //         match app_func_size_map.get(func_name) {
-        //             Some(size) => *size,
-        //             None => {
-        //                 internal_error!("Size missing for: {}", func_name);
-        //             }
-        //         },
-        //     );
-        // }
+            }
+            match md.arch {
+                MachoArch::X86_64 => {
+                    let jmp_inst_len = 5;
+                    let target = (func_virt_offset as i64
+                        - (*plt_vaddr as i64 + jmp_inst_len as i64))
+                        as i32;
+                    if verbose {
+                        println!("\tTarget Jump: {target:+x}");
+                    }
+                    let data = target.to_le_bytes();
+                    exec_mmap[plt_off] = 0xE9;
+                    exec_mmap[plt_off + 1..plt_off + jmp_inst_len].copy_from_slice(&data);
+                    exec_mmap[plt_off + jmp_inst_len..plt_off + stub_size].fill(0x90);
+                }
+                MachoArch::Aarch64 => {
+                    let inst = aarch64::b(func_virt_offset as i64 - *plt_vaddr as i64)
+                        .unwrap_or_else(|e| internal_error!("{e}"));
+                    aarch64::write(exec_mmap, plt_off, inst);
+                    for i in
+                        (aarch64::INSTRUCTION_SIZE..stub_size).step_by(aarch64::INSTRUCTION_SIZE)
+                    {
+                        aarch64::write(exec_mmap, plt_off + i, aarch64::NOP);
+                    }
+                }
+            }
+        }
+
+        // The symbol used to be undefined. Now it's defined in one of our sections.
+        if let (Some(symoff), Some(i)) = (symtab, md.symbol_indices.get(func_name)) {
+            let sym = load_struct_inplace_mut::<macho::Nlist64<LE>>(
+                exec_mmap,
+                symoff + *i as usize * mem::size_of::<macho::Nlist64<LE>>(),
+            );
+            sym.n_type = macho::N_SECT | macho::N_EXT;
+            sym.n_sect = func_section_number;
+            sym.n_desc.set(LE, 0);
+            sym.n_value.set(LE, func_virt_offset);
+        }
     }
 
     *offset_ref = offset;
 }
+
+/// x86_64 relocations keep their addend in the data they relocate
+#[allow(clippy::too_many_arguments)]
+fn apply_x86_64_relocation(
+    exec_mmap: &mut [u8],
+    rel: &Relocation,
+    base: usize,
+    virt_base: i64,
+    target_address: i64,
+    is_section_relative: bool,
+    section_shift: i64,
+    subtrahend: Option<i64>,
+) -> Result<(), String> {
+    let implicit_addend = match rel.size() {
+        32 => i32::from_le_bytes(exec_mmap[base..base + 4].try_into().unwrap()) as i64,
+        64 => i64::from_le_bytes(exec_mmap[base..base + 8].try_into().unwrap()),
+        x => return Err(format!("Relocation size not yet supported: {x}")),
+    };
+
+    // The object crate only gives a generic kind to some of the pc-relative relocations.
+    // The others differ in how far the end of the instruction is from the displacement,
+    // but that is already accounted for in the implicit addend.
+    let is_relative = matches!(
+        rel.kind(),
+        RelocationKind::Relative
+            | RelocationKind::PltRelative
+            | RelocationKind::MachO {
+                value: macho::X86_64_RELOC_SIGNED_1
+                    | macho::X86_64_RELOC_SIGNED_2
+                    | macho::X86_64_RELOC_SIGNED_4,
+                relative: true,
+            }
+    );
+
+    let target = match (rel.kind(), subtrahend) {
+        (RelocationKind::Absolute, Some(subtrahend)) => {
+            target_address - subtrahend + implicit_addend
+        }
+        _ if is_relative && is_section_relative => {
+            // `target_address` is how far the target section moved,
+            // and the data already has the target relative to this relocation.
+            implicit_addend + target_address - section_shift
+        }
+        _ if is_relative => target_address - virt_base + rel.addend() + implicit_addend,
+        (RelocationKind::GotRelative, _)
+            if rel.encoding() == object::RelocationEncoding::X86RipRelativeMovq =>
+        {
+            // We know where the symbol is, so we don't need a GOT.
+            // Turn `movq sym@GOTPCREL(%rip), %reg` into `leaq sym(%rip), %reg`.
+            if base < 2 || exec_mmap[base - 2] != 0x8b {
+                return Err("Expected a `movq` from the GOT".to_string());
+            }
+            exec_mmap[base - 2] = 0x8d;
+            target_address - virt_base + rel.addend() + implicit_addend
+        }
+        (x, _) => return Err(format!("Relocation Kind not yet support: {x:?}")),
+    };
+
+    match rel.size() {
+        32 => exec_mmap[base..base + 4].copy_from_slice(&(target as i32).to_le_bytes()),
+        _ => exec_mmap[base..base + 8].copy_from_slice(&target.to_le_bytes()),
+    }
+
+    Ok(())
+}
+
+/// arm64 relocations keep their addend in a separate `ARM64_RELOC_ADDEND`,
+/// except in data, where it is still stored in place.
+fn apply_aarch64_relocation(
+    exec_mmap: &mut [u8],
+    rel: &Relocation,
+    base: usize,
+    virt_base: i64,
+    target_address: i64,
+    subtrahend: Option<i64>,
+) -> Result<(), String> {
+    let r_type = match (rel.kind(), rel.encoding()) {
+        (RelocationKind::MachO { value, .. }, _) => value,
+        (RelocationKind::Absolute, _) => macho::ARM64_RELOC_UNSIGNED,
+        // The object crate decodes calls into a generic form
+        (_, object::RelocationEncoding::AArch64Call) => macho::ARM64_RELOC_BRANCH26,
+        (x, _) => return Err(format!("Relocation Kind not yet support: {x:?}")),
+    };
+
+    if r_type == macho::ARM64_RELOC_UNSIGNED {
+        let subtrahend = subtrahend.ok_or("Absolute pointers are not yet supported")?;
+        match rel.size() {
+            32 => {
+                let addend = i32::from_le_bytes(exec_mmap[base..base + 4].try_into().unwrap());
+                let value = target_address - subtrahend + addend as i64;
+                exec_mmap[base..base + 4].copy_from_slice(&(value as i32).to_le_bytes());
+            }
+            64 => {
+                let addend = i64::from_le_bytes(exec_mmap[base..base + 8].try_into().unwrap());
+                let value = target_address - subtrahend + addend;
+                exec_mmap[base..base + 8].copy_from_slice(&value.to_le_bytes());
+            }
+            x => return Err(format!("Relocation size not yet supported: {x}")),
+        }
+        return Ok(());
+    }
+
+    let inst = aarch64::read(exec_mmap, base);
+    let inst = match r_type {
+        macho::ARM64_RELOC_BRANCH26 => aarch64::set_branch_offset(inst, target_address - virt_base),
+        macho::ARM64_RELOC_PAGE21 | macho::ARM64_RELOC_GOT_LOAD_PAGE21 => {
+            // We don't have a GOT for the app, so we relax GOT accesses to use the address directly
+            let page_offset = aarch64::page(target_address) - aarch64::page(virt_base);
+            aarch64::set_adrp_page_offset(inst, page_offset)
+        }
+        macho::ARM64_RELOC_GOT_LOAD_PAGEOFF12 => aarch64::relax_got_load(inst, target_address),
+        macho::ARM64_RELOC_PAGEOFF12 => {
+            aarch64::set_lo12(inst, target_address, aarch64::lo12_scale(inst))
+        }
+        other => Err(format!(
+            "AArch64 relocation type not yet supported: {other}"
+        )),
+    }?;
+    aarch64::write(exec_mmap, base, inst);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use object::ObjectSegment;
+    use std::str::FromStr;
+
+    // See fixtures/macho/generate.py for how these are made
+    const HOST_X86_64: &[u8] = include_bytes!("../fixtures/macho/host_x86_64") as &[_];
+    const APP_X86_64: &[u8] = include_bytes!("../fixtures/macho/app_x86_64.o") as &[_];
+    const HOST_AARCH64: &[u8] = include_bytes!("../fixtures/macho/host_aarch64") as &[_];
+    const APP_AARCH64: &[u8] = include_bytes!("../fixtures/macho/app_aarch64.o") as &[_];
+
+    const IMAGE_BASE: u64 = 0x1_0000_0000;
+
+    /// Writes `host.rh` and `metadata` to the directory
+    fn preprocess(dir: &Path, target: &str, host: &[u8]) {
+        std::fs::write(dir.join("host"), host).unwrap();
+
+        preprocess_macho(
+            &Triple::from_str(target).unwrap(),
+            &dir.join("host"),
+            &dir.join("metadata"),
+            &dir.join("host.rh"),
+            Path::new("libapp.so"),
+            false,
+            false,
+        );
+    }
+
+    fn link(target: &str, host: &[u8], app: &[u8]) -> Vec<u8> {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+
+        preprocess(dir, target, host);
+        std::fs::copy(dir.join("host.rh"), dir.join("final")).unwrap();
+
+        surgery_macho(app, &dir.join("metadata"), &dir.join("final"), false, false);
+
+        std::fs::read(dir.join("final")).unwrap()
+    }
+
+    fn file_offset(object: &object::File, address: u64) -> usize {
+        let segment = object
+            .segments()
+            .find(|seg| (seg.address()..seg.address() + seg.size()).contains(&address))
+            .unwrap();
+        (segment.file_range().0 + address - segment.address()) as usize
+    }
+
+    /// The address of a symbol, and the segment it is in
+    fn symbol(object: &object::File, name: &str) -> (u64, String) {
+        let sym = object
+            .symbols()
+            .find(|sym| sym.name().ok() == Some(name))
+            .unwrap();
+        let section = object
+            .section_by_index(sym.section_index().unwrap())
+            .unwrap();
+        let segment = section.segment_name().unwrap().unwrap().to_string();
+        (sym.address(), segment)
+    }
+
+    fn dylib_names(data: &[u8]) -> Vec<String> {
+        load_commands(data)
+            .into_iter()
+            .filter(|cmd| is_dylib_command(cmd.cmd))
+            .map(|cmd| dylib_path(data, cmd).display().to_string())
+            .collect()
+    }
+
+    /// Check the parts of the load commands that both architectures have in common
+    fn check_load_commands(data: &[u8], object: &object::File) {
+        let commands = load_commands(data);
+        assert!(commands
+            .iter()
+            .all(|cmd| cmd.cmd != macho::LC_CODE_SIGNATURE));
+
+        let segments: Vec<_> = object
+            .segments()
+            .map(|seg| seg.name().unwrap().unwrap().to_string())
+            .collect();
+        let n = segments.len();
+        assert_eq!(
+            segments[n - 4..],
+            ["__ROC_CONST", "__ROC_BSS", "__ROC_TEXT", "__LINKEDIT"]
+        );
+
+        // __LINKEDIT must be the last thing in the file, and all segments must be in order
+        let linkedit = object.segments().last().unwrap();
+        let (fileoff, filesize) = linkedit.file_range();
+        assert_eq!(fileoff + filesize, data.len() as u64);
+        let addresses: Vec<_> = object.segments().map(|seg| seg.address()).collect();
+        assert!(addresses.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    /// Compare the linked output and its symbol table with the files checked in next to the
+    /// fixtures, so that every change to what the linker produces shows up in review.
+    /// Set `ROC_UPDATE_MACHO_GOLDEN=1` to write them again after an intended change.
+    fn check_golden(arch: &str, data: &[u8], object: &object::File) {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/macho");
+        let binary_path = dir.join(format!("linked_{arch}"));
+        let symbols_path = dir.join(format!("linked_{arch}.symbols"));
+
+        let mut symbols: Vec<_> = object
+            .symbols()
+            .map(|sym| {
+                let segment = sym
+                    .section_index()
+                    .and_then(|index| object.section_by_index(index).ok())
+                    .and_then(|section| section.segment_name().ok().flatten().map(String::from))
+                    .unwrap_or_default();
+
+                (sym.name().unwrap().to_string(), sym.address(), segment)
+            })
+            .collect();
+        symbols.sort();
+
+        let symbols: String = symbols
+            .iter()
+            .map(|(name, address, segment)| format!("{address:#018x} {segment:<12} {name}\n"))
+            .collect();
+
+        if std::env::var_os("ROC_UPDATE_MACHO_GOLDEN").is_some() {
+            std::fs::write(&binary_path, data).unwrap();
+            std::fs::write(&symbols_path, &symbols).unwrap();
+        }
+
+        assert_eq!(symbols, std::fs::read_to_string(&symbols_path).unwrap());
+        assert!(
+            data == std::fs::read(&binary_path).unwrap(),
+            "The linked output differs from {}",
+            binary_path.display()
+        );
+    }
+
+    #[test]
+    fn leb128() {
+        for value in [0, 1, 0x7f, 0x80, 0x3fff, 0x4000, u64::MAX] {
+            let mut out = Vec::new();
+            write_uleb128(&mut out, value);
+            assert_eq!(read_uleb128(&out, &mut 0), Ok(value));
+        }
+        for value in [0, 1, -1, 0x3f, 0x40, -0x40, -0x41, i64::MIN, i64::MAX] {
+            let mut out = Vec::new();
+            write_sleb128(&mut out, value);
+            assert_eq!(read_sleb128(&out, &mut 0), Ok(value));
+        }
+
+        let mut out = [0; 3];
+        write_padded_uleb128(&mut out, 2).unwrap();
+        assert_eq!(out, [0x82, 0x80, 0x00]);
+        assert_eq!(read_uleb128(&out, &mut 0), Ok(2));
+        assert!(write_padded_uleb128(&mut out[..1], 0x80).is_err());
+    }
+
+    #[test]
+    fn bind_opcodes_round_trip() {
+        let bind = |ordinal, name: &str, segment_offset, addend| Bind {
+            segment: 2,
+            segment_offset,
+            ordinal,
+            name: name.to_string(),
+            flags: 0,
+            bind_type: macho::BIND_TYPE_POINTER,
+            addend,
+            ordinal_opcode: (0, 0),
+            symbol_opcode: 0,
+        };
+        let binds = [
+            bind(1, "_puts", 0, 0),
+            bind(-2, "_flat", 8, 16),
+            bind(300, "_far", 0x1000, 0),
+        ];
+
+        let parsed = parse_binds(&encode_binds(&binds), false).unwrap();
+        let without_positions: Vec<_> = parsed
+            .into_iter()
+            .map(|bind| Bind {
+                ordinal_opcode: (0, 0),
+                symbol_opcode: 0,
+                ..bind
+            })
+            .collect();
+        assert_eq!(without_positions, binds);
+    }
+
+    #[test]
+    fn macho_x86_64_host_app() {
+        let data = link("x86_64-apple-darwin", HOST_X86_64, APP_X86_64);
+        let object = object::File::parse(data.as_slice()).unwrap();
+        check_load_commands(&data, &object);
+        check_golden("x86_64", &data, &object);
+        assert_eq!(dylib_names(&data), ["/usr/lib/libSystem.B.dylib"]);
+
+        let (main, _) = symbol(&object, "_main");
+        let (roc_alloc, _) = symbol(&object, "_roc_alloc");
+        let (app_main, segment) = symbol(&object, "_roc__mainForHost_1_exposed");
+        assert_eq!(segment, "__ROC_TEXT");
+        let (app_size, segment) = symbol(&object, "_roc__mainForHost_size");
+        assert_eq!(segment, "__ROC_CONST");
+
+        let rel32_target = |address: u64| {
+            let offset = file_offset(&object, address);
+            let rel = i32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
+            (address as i64 + 4 + rel as i64) as u64
+        };
+        let read_u64_at = |address: u64| read_u64(&data, file_offset(&object, address));
+
+        // The host calls the app directly now, and the stub jumps there too
+        assert_eq!(rel32_target(main + 5), app_main);
+        let stub = file_offset(&object, 0x1_0000_102a);
+        assert_eq!(data[stub], 0xe9);
+        assert_eq!(rel32_target(0x1_0000_102b), app_main);
+        assert_eq!(data[stub + 5], 0x90);
+
+        // The GOT points at the app's data, and gets rebased instead of bound
+        assert_eq!(read_u64_at(0x1_0000_2008), app_size);
+
+        let dyld_info = load_commands(&data)
+            .into_iter()
+            .find(|cmd| cmd.cmd == macho::LC_DYLD_INFO_ONLY)
+            .unwrap();
+        let dyld_info = load_struct_inplace::<macho::DyldInfoCommand<LE>>(&data, dyld_info.offset);
+        let opcodes = |offset: &object::U32<LE>, size: &object::U32<LE>| {
+            &data[offset.get(LE) as usize..][..size.get(LE) as usize]
+        };
+
+        let rebases = opcodes(&dyld_info.rebase_off, &dyld_info.rebase_size);
+        // REBASE_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB(__DATA_CONST, 8), REBASE_OPCODE_DO_REBASE_IMM_TIMES(1)
+        assert!(rebases.ends_with(&[0x22, 0x08, 0x51, macho::REBASE_OPCODE_DONE]));
+
+        let binds = parse_binds(opcodes(&dyld_info.bind_off, &dyld_info.bind_size), false).unwrap();
+        assert_eq!(binds.len(), 1);
+        assert_eq!(
+            (binds[0].name.as_str(), binds[0].ordinal),
+            ("dyld_stub_binder", 1)
+        );
+
+        let lazy_binds = opcodes(&dyld_info.lazy_bind_off, &dyld_info.lazy_bind_size);
+        let lazy_binds = parse_binds(lazy_binds, true).unwrap();
+        let summary: Vec<_> = lazy_binds
+            .iter()
+            .map(|bind| (bind.name.as_str(), bind.ordinal, bind.flags))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("_puts", 1, 0),
+                (
+                    "_roc__mainForHost_1_exposed",
+                    macho::BIND_SPECIAL_DYLIB_FLAT_LOOKUP as i64,
+                    macho::BIND_SYMBOL_FLAGS_WEAK_IMPORT
+                ),
+            ]
+        );
+
+        // The app's references to itself and the host
+        assert_eq!(rel32_target(app_main + 0xa), roc_alloc);
+        let hello = rel32_target(app_main + 0x11);
+        assert_eq!(&data[file_offset(&object, hello)..][..6], b"Hello\0");
+        assert_eq!(data[file_offset(&object, app_main + 0x19)], 0x8d);
+        assert_eq!(rel32_target(app_main + 0x1b), roc_alloc);
+        assert_eq!(rel32_target(app_main + 0x22), app_size + 8);
+        // `movl $1, _counter(%rip)` has 4 bytes of immediate after the displacement
+        let counter = rel32_target(app_main + 0x28) + 4;
+        let bss = object
+            .segments()
+            .find(|seg| seg.name() == Ok(Some("__ROC_BSS")));
+        assert_eq!(counter, bss.unwrap().address());
+        assert_eq!(read_u64_at(app_size + 16), app_main.wrapping_sub(app_size));
+    }
+
+    #[test]
+    fn macho_aarch64_host_app() {
+        let data = link("aarch64-apple-darwin", HOST_AARCH64, APP_AARCH64);
+        let object = object::File::parse(data.as_slice()).unwrap();
+        check_load_commands(&data, &object);
+        check_golden("aarch64", &data, &object);
+        assert_eq!(
+            dylib_names(&data),
+            ["/usr/lib/libSystem.B.dylib", "/usr/lib/libc++.1.dylib"]
+        );
+
+        let (main, _) = symbol(&object, "_main");
+        let (roc_alloc, _) = symbol(&object, "_roc_alloc");
+        let (app_main, segment) = symbol(&object, "_roc__mainForHost_1_exposed");
+        assert_eq!(segment, "__ROC_TEXT");
+        let (app_size, segment) = symbol(&object, "_roc__mainForHost_size");
+        assert_eq!(segment, "__ROC_CONST");
+
+        let inst_at = |address: u64| aarch64::read(&data, file_offset(&object, address));
+        let branch_target = |address: u64| aarch64::branch_target(inst_at(address), address);
+        let read_u64_at = |address: u64| read_u64(&data, file_offset(&object, address));
+
+        // The host calls the app directly now, and the stub branches there too
+        assert_eq!(branch_target(main + 8), Some(app_main));
+        assert_eq!(branch_target(0x1_0000_1038), Some(app_main));
+        assert_eq!(inst_at(0x1_0000_103c), aarch64::NOP);
+        assert_eq!(inst_at(0x1_0000_1040), aarch64::NOP);
+
+        // Pointers to the app are rebases now, but stay in their chains
+        let rebase = |target: u64, next: u64| (next << 51) | (target - IMAGE_BASE);
+        assert_eq!(read_u64_at(0x1_0000_4008), rebase(app_main, 2));
+        assert_eq!(read_u64_at(0x1_0000_4010), rebase(app_size, 2));
+        assert_eq!(read_u64_at(0x1_0000_4018), rebase(main, 0));
+        assert_eq!(read_u64_at(0x1_0000_8000), rebase(app_main, 2));
+        // bind to import 3, `__Znwm`
+        assert_eq!(read_u64_at(0x1_0000_8008), 1 << 63 | 3);
+
+        let fixups = load_commands(&data)
+            .into_iter()
+            .find(|cmd| cmd.cmd == macho::LC_DYLD_CHAINED_FIXUPS)
+            .unwrap();
+        let fixups = load_struct_inplace::<macho::LinkeditDataCommand<LE>>(&data, fixups.offset);
+        let fixups = &data[fixups.dataoff.get(LE) as usize..][..fixups.datasize.get(LE) as usize];
+        let imports: Vec<_> = parse_chained_imports(fixups)
+            .unwrap()
+            .into_iter()
+            .map(|import| {
+                let weak = read_u32(fixups, import.offset) & 0x100 != 0;
+                (import.name, import.ordinal, weak)
+            })
+            .collect();
+        let flat = macho::BIND_SPECIAL_DYLIB_FLAT_LOOKUP as i64;
+        assert_eq!(
+            imports,
+            [
+                ("_puts".to_string(), 1, false),
+                ("_roc__mainForHost_1_exposed".to_string(), flat, true),
+                ("_roc__mainForHost_size".to_string(), flat, true),
+                ("__Znwm".to_string(), 2, false),
+            ]
+        );
+
+        // dyld expects an entry for every segment, including the app's,
+        // and the chains have to stay intact with the new segments in between.
+        // Only the binds to `_puts` and `__Znwm` are left.
+        let segments: Vec<_> = object
+            .segments()
+            .map(|segment| (segment.address(), segment.file_range().0))
+            .collect();
+        let starts_offset = read_u32(fixups, 4) as usize;
+        assert_eq!(read_u32(fixups, starts_offset) as usize, segments.len());
+        let binds = parse_chained_binds(&data, fixups, &segments, IMAGE_BASE).unwrap();
+        let binds: Vec<_> = binds.iter().map(|bind| bind.import).collect();
+        assert_eq!(binds, [0, 3]);
+
+        let znwm = object
+            .symbols()
+            .find(|sym| sym.name() == Ok("__Znwm"))
+            .unwrap();
+        match znwm.flags() {
+            object::SymbolFlags::MachO { n_desc } => assert_eq!(n_desc >> 8, 2),
+            flags => panic!("unexpected symbol flags {flags:?}"),
+        }
+
+        // The app's references to itself and the host
+        assert_eq!(branch_target(app_main + 8), Some(roc_alloc));
+        let page = |address: u64| {
+            let inst = inst_at(address);
+            let imm = ((inst >> 29) & 0x3) | ((inst >> 3) & 0x1f_fffc);
+            let offset = ((imm << 11) as i32 >> 11) as i64;
+            (aarch64::page(address as i64) + (offset << 12)) as u64
+        };
+        let imm12 = |address: u64| ((inst_at(address) >> 10) & 0xfff) as u64;
+
+        let hello = page(app_main + 0xc) + imm12(app_main + 0x10);
+        assert_eq!(&data[file_offset(&object, hello)..][..6], b"Hello\0");
+        // `ldr x2, [x2, _roc_alloc@GOTPAGEOFF]` became `add x2, x2, _roc_alloc@PAGEOFF`
+        assert_eq!(inst_at(app_main + 0x1c) & 0xffc0_0000, 0x9100_0000);
+        assert_eq!(page(app_main + 0x18) + imm12(app_main + 0x1c), roc_alloc);
+        // `ldr x3` scales its offset by 8
+        assert_eq!(
+            page(app_main + 0x20) + imm12(app_main + 0x24) * 8,
+            app_size + 8
+        );
+        let bss = object
+            .segments()
+            .find(|seg| seg.name() == Ok(Some("__ROC_BSS")));
+        assert_eq!(page(app_main + 0x28), bss.unwrap().address());
+        assert_eq!(read_u64_at(app_size + 16), app_main.wrapping_sub(app_size));
+    }
+    #[test]
+    fn inspect_aarch64_host() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        preprocess(dir, "aarch64-apple-darwin", HOST_AARCH64);
+
+        let md = host_metadata(&dir.join("metadata"));
+        let mut app_symbols = md.app_symbols.clone();
+        app_symbols.sort();
+        assert_eq!(
+            app_symbols,
+            ["roc__mainForHost_1_exposed", "roc__mainForHost_size"]
+        );
+        assert!(md.host_symbols.iter().any(|(name, _)| name == "roc_alloc"));
+        assert_eq!(md.stubs.len(), 1);
+
+        let mut kinds: Vec<_> = md
+            .patch_sites
+            .iter()
+            .filter(|site| site.symbol == "roc__mainForHost_1_exposed")
+            .map(|site| site.kind.as_str())
+            .collect();
+        kinds.sort();
+        kinds.dedup();
+        assert_eq!(
+            kinds,
+            ["AArch64 branch at 0x100001008", "chained fixup pointer"]
+        );
+
+        let app = crate::inspect::AppSymbols {
+            stub_dll_symbols: vec!["roc__mainForHost_1_exposed".to_string()],
+            defined: ["roc__mainForHost_1_exposed".to_string()].into(),
+        };
+        let mut out = Vec::new();
+        let exit_code = crate::inspect::write_report(
+            &mut out,
+            &dir.join("host.rh"),
+            &dir.join("metadata"),
+            Some(&app),
+        )
+        .unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert_eq!(exit_code, 1);
+        assert!(out.contains("__ROC_TEXT"));
+        assert!(out.ends_with(
+            "The host expects these symbols, but the app does not expose them:\n    \
+             roc__mainForHost_size\n\nSurgical linking will fail. Check that the platform's \
+             `provides` and the host's `extern` declarations agree.\n"
+        ));
+    }
+}
@@ -19,8 +19,10 @@ use roc_collections::{MutMap, VecMap};
 use roc_error_macros::internal_error;
 
 use crate::{
-    generate_dylib::APP_DLL, load_struct_inplace, load_struct_inplace_mut,
-    load_structs_inplace_mut, open_mmap, open_mmap_mut,
+    generate_dylib::APP_DLL,
+    inspect::{HostMetadata, PatchSite},
+    load_struct_inplace, load_struct_inplace_mut, load_structs_inplace_mut, open_mmap,
+    open_mmap_mut,
 };
 
 /// The metadata stores information about/from the host .exe because
@@ -285,6 +287,33 @@ fn relocate_to(
     }
 }
 
+/// The parts of the metadata that `roc inspect-host` reports
+pub(crate) fn host_metadata(metadata_path: &Path) -> HostMetadata {
+    let md = PeMetadata::read_from_file(metadata_path);
+
+    let patch_sites = md
+        .dynamic_relocations
+        .address_and_offset
+        .iter()
+        .map(|(symbol, (virtual_address, offset_in_file))| PatchSite {
+            symbol: symbol.clone(),
+            file_offset: *offset_in_file as u64,
+            kind: format!("8-byte import thunk at RVA {virtual_address:#x}"),
+        })
+        .collect();
+
+    HostMetadata {
+        app_symbols: md.imports,
+        host_symbols: md
+            .exports
+            .into_iter()
+            .map(|(name, address)| (name, address as u64))
+            .collect(),
+        stubs: Vec::new(),
+        patch_sites,
+    }
+}
+
 pub(crate) fn surgery_pe(executable_path: &Path, metadata_path: &Path, roc_app_bytes: &[u8]) {
     let md = PeMetadata::read_from_file(metadata_path);
 
@@ -215,6 +215,7 @@ fn mono_module_to_dylib_llvm<'a>(
         builder: &builder,
         dibuilder: &dibuilder,
         compile_unit: &compile_unit,
+        debug_sources: None,
         context: &context,
         interns,
         module,
@@ -319,6 +320,7 @@ fn mono_module_to_dylib_asm<'a>(
         exposed_to_host: exposed_to_host.top_level_values.keys().copied().collect(),
         lazy_literals,
         mode: roc_gen_dev::AssemblyBackendMode::Repl,
+        debug_sources: None,
     };
 
     let target = target_lexicon::Triple::host();
@@ -824,6 +824,42 @@ fn addr_to_ast<'a, M: ReplAppMemory>(
                 when_recursive,
             )
         }
+        (
+            Content::Structure(FlatType::TagUnion(tags, _)),
+            LayoutRepr::Union(UnionLayout::NullableUnwrapped { .. }),
+        ) => {
+            // a non-recursive `[None, Some (Box a)]`, where the box pointer is the union
+            let (vars_of_tag, union_variant) = get_tags_vars_and_variant(env, tags, None);
+
+            let (nullable_name, other_name, other_arg_layouts) = match union_variant {
+                UnionVariant::Wrapped(WrappedVariant::NullableBoxed {
+                    nullable_name,
+                    other_name,
+                    other_fields,
+                    ..
+                }) => (
+                    nullable_name.expect_tag(),
+                    other_name.expect_tag(),
+                    other_fields,
+                ),
+                _ => unreachable!("any other variant would have a different layout"),
+            };
+
+            if mem.deref_usize(addr) == 0 {
+                tag_name_to_expr(env, &nullable_name)
+            } else {
+                // the box payload lives where the union does
+                expr_of_tag(
+                    env,
+                    mem,
+                    addr,
+                    &other_name,
+                    other_arg_layouts,
+                    &vars_of_tag[&other_name],
+                    WhenRecursive::Unreachable,
+                )
+            }
+        }
         (_, LayoutRepr::Union(UnionLayout::NullableUnwrapped { .. })) => {
             let (rec_var, tags) = match unroll_recursion_var(env, raw_content) {
                 Content::Structure(FlatType::RecursiveTagUnion(rec_var, tags, _)) => {
@@ -95,7 +95,10 @@ mod test {
     use pretty_assertions::assert_eq;
     use roc_error_macros::internal_error;
     use roc_gen_llvm::{llvm::build::LlvmBackendMode, run_roc::RocCallResult, run_roc_dylib};
-    use roc_load::{ExecutionMode, FunctionKind, LoadConfig, LoadMonomorphizedError, Threading};
+    use roc_load::{
+        ExecutionMode, FunctionKind, LoadConfig, LoadMonomorphizedError, MonoOptimizations,
+        Threading,
+    };
     use roc_packaging::cache::RocCacheDir;
     use roc_reporting::report::{RenderTarget, DEFAULT_PALETTE};
     use target_lexicon::Triple;
@@ -128,6 +131,8 @@ mod test {
             palette: DEFAULT_PALETTE,
             threading: Threading::Single,
             exec_mode: ExecutionMode::Test,
+            mono_optimizations: MonoOptimizations::NONE,
+            emit_debug_info: false,
         };
         let loaded = match roc_load::load_and_monomorphize_from_str(
             arena,
@@ -740,6 +740,7 @@ pub fn expect_mono_module_to_dylib<'a>(
         builder: &builder,
         dibuilder: &dibuilder,
         compile_unit: &compile_unit,
+        debug_sources: None,
         context: &context,
         interns,
         module,
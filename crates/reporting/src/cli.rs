@@ -22,6 +22,12 @@ impl Problems {
         }
     }
 
+    /// Count every warning as an error instead.
+    pub fn promote_warnings(&mut self) {
+        self.errors += self.warnings;
+        self.warnings = 0;
+    }
+
     pub fn print_to_stdout(&self, total_time: std::time::Duration) {
         const GREEN: usize = 32;
         const YELLOW: usize = 33;
@@ -1,5 +1,6 @@
 use roc_collections::all::MutSet;
 use roc_module::ident::{Ident, Lowercase, ModuleName};
+use roc_module::low_level::LowLevel;
 use roc_module::symbol::DERIVABLE_ABILITIES;
 use roc_problem::can::PrecedenceProblem::BothNonAssociative;
 use roc_problem::can::{
@@ -20,6 +21,8 @@ const NAMING_PROBLEM: &str = "NAMING PROBLEM";
 const UNRECOGNIZED_NAME: &str = "UNRECOGNIZED NAME";
 const UNUSED_DEF: &str = "UNUSED DEFINITION";
 const UNUSED_IMPORT: &str = "UNUSED IMPORT";
+const UNUSED_RECORD_FIELD: &str = "UNUSED RECORD FIELD";
+const UNUSED_EXPORT: &str = "UNUSED EXPORT";
 const UNUSED_ALIAS_PARAM: &str = "UNUSED TYPE ALIAS PARAMETER";
 const UNBOUND_TYPE_VARIABLE: &str = "UNBOUND TYPE VARIABLE";
 const UNUSED_ARG: &str = "UNUSED ARGUMENT";
@@ -59,6 +62,10 @@ const ABILITY_IMPLEMENTATION_NOT_IDENTIFIER: &str = "ABILITY IMPLEMENTATION NOT
 const DUPLICATE_IMPLEMENTATION: &str = "DUPLICATE IMPLEMENTATION";
 const UNNECESSARY_IMPLEMENTATIONS: &str = "UNNECESSARY IMPLEMENTATIONS";
 const INCOMPLETE_ABILITY_IMPLEMENTATION: &str = "INCOMPLETE ABILITY IMPLEMENTATION";
+const DUPLICATE_ABILITY_IMPLEMENTATION: &str = "DUPLICATE ABILITY IMPLEMENTATION";
+const ABILITY_REQUIRES_ITSELF: &str = "ABILITY REQUIRES ITSELF";
+const UNUSABLE_DEFAULT_IMPLEMENTATION: &str = "UNUSABLE DEFAULT IMPLEMENTATION";
+const CONSTANT_OVERFLOW: &str = "CONSTANT OVERFLOW";
 
 pub fn can_problem<'b>(
     alloc: &'b RocDocAllocator<'b>,
@@ -230,6 +237,43 @@ pub fn can_problem<'b>(
 
             title = UNUSED_DEF.to_string();
         }
+        Problem::UnusedRecordField {
+            record,
+            field,
+            region,
+        } => {
+            doc = alloc.stack([
+                alloc.concat([
+                    alloc.reflow("The "),
+                    alloc.record_field(field),
+                    alloc.reflow(" field of "),
+                    alloc.symbol_unqualified(record),
+                    alloc.reflow(" is never used:"),
+                ]),
+                alloc.region(lines.convert_region(region)),
+                alloc.reflow("If you don't need this field, remove it from the record."),
+            ]);
+
+            title = UNUSED_RECORD_FIELD.to_string();
+        }
+        Problem::UnusedExport(symbol, region) => {
+            doc = alloc.stack([
+                alloc.concat([
+                    alloc.symbol_unqualified(symbol),
+                    alloc.reflow(" is exposed, but no other module uses it:"),
+                ]),
+                alloc.region(lines.convert_region(region)),
+                alloc.concat([
+                    alloc.reflow("If nothing outside this module needs "),
+                    alloc.symbol_unqualified(symbol),
+                    alloc.reflow(", remove it from the "),
+                    alloc.keyword("exposes"),
+                    alloc.reflow(" list."),
+                ]),
+            ]);
+
+            title = UNUSED_EXPORT.to_string();
+        }
         Problem::PrecedenceProblem(BothNonAssociative(region, left_bin_op, right_bin_op)) => {
             doc = alloc.stack([
                 if left_bin_op.value == right_bin_op.value {
@@ -944,6 +988,60 @@ pub fn can_problem<'b>(
             ]);
             title = INCOMPLETE_ABILITY_IMPLEMENTATION.to_string();
         }
+        Problem::DuplicateAbilityImplementation {
+            ability,
+            original,
+            duplicate,
+        } => {
+            doc = alloc.stack([
+                alloc.concat([
+                    alloc.reflow("This type implements the "),
+                    alloc.symbol_unqualified(ability),
+                    alloc.reflow(" ability more than once:"),
+                ]),
+                alloc.region(lines.convert_region(duplicate)),
+                alloc.reflow("The first implementation was listed here:"),
+                alloc.region(lines.convert_region(original)),
+                alloc.reflow("Only the first implementation will be used. I'd suggest removing the duplicate."),
+            ]);
+            title = DUPLICATE_ABILITY_IMPLEMENTATION.to_string();
+        }
+        Problem::AbilityRequiresItself { ability, region } => {
+            doc = alloc.stack([
+                alloc.concat([
+                    alloc.reflow("The "),
+                    alloc.symbol_unqualified(ability),
+                    alloc.reflow(" ability requires itself, possibly through other abilities:"),
+                ]),
+                alloc.region(lines.convert_region(region)),
+                alloc.reflow(
+                    "An ability can only require abilities that do not, in turn, require it.",
+                ),
+            ]);
+            title = ABILITY_REQUIRES_ITSELF.to_string();
+        }
+        Problem::AbilityMemberDefaultNotSpecializable {
+            member,
+            ability,
+            region,
+        } => {
+            doc = alloc.stack([
+                alloc.concat([
+                    alloc.reflow("The default implementation of "),
+                    alloc.symbol_unqualified(member),
+                    alloc.reflow(" cannot be used by types implementing "),
+                    alloc.symbol_unqualified(ability),
+                    alloc.reflow(":"),
+                ]),
+                alloc.region(lines.convert_region(region)),
+                alloc.concat([
+                    alloc.reflow("Default implementations can only be given to members that are functions mentioning the "),
+                    alloc.symbol_unqualified(ability),
+                    alloc.reflow(" type variable directly as an argument or as the return value."),
+                ]),
+            ]);
+            title = UNUSABLE_DEFAULT_IMPLEMENTATION.to_string();
+        }
         Problem::NotBoundInAllPatterns {
             unbound_symbol,
             region,
@@ -1089,6 +1187,34 @@ pub fn can_problem<'b>(
             ]);
             title = "OVERAPPLIED CRASH".to_string();
         }
+        Problem::ConstantOverflow { op, region } => {
+            let operation = match op {
+                LowLevel::NumAdd => "addition",
+                LowLevel::NumSub => "subtraction",
+                LowLevel::NumMul => "multiplication",
+                LowLevel::NumNeg => "negation",
+                LowLevel::NumAbs => "absolute value",
+                _ => "arithmetic",
+            };
+
+            doc = alloc.stack([
+                alloc.concat([
+                    alloc.reflow("This "),
+                    alloc.reflow(operation),
+                    alloc.reflow(" always overflows:"),
+                ]),
+                alloc.region(lines.convert_region(region)),
+                alloc.reflow(
+                    "All of its inputs are known at compile time, and the result does not fit in its type. The program will crash if this code is ever reached.",
+                ),
+                alloc.tip().append(alloc.concat([
+                    alloc.reflow("Use a wider number type, or one of the wrapping or saturating operations like "),
+                    alloc.parser_suggestion("Num.addWrap"),
+                    alloc.reflow(" if overflow is expected."),
+                ])),
+            ]);
+            title = CONSTANT_OVERFLOW.to_string();
+        }
         Problem::FileProblem { filename, error } => {
             let report = to_file_problem_report(alloc, &filename, error);
             doc = report.doc;
@@ -958,6 +958,7 @@ fn to_str_report<'a>(
                         suggestion("An escaped backslash: ", "\\\\"),
                         suggestion("A unicode code point: ", "\\u(00FF)"),
                         suggestion("An interpolated string: ", "\\(myVariable)"),
+                        suggestion("An inspected value: ", "\\{myValue}"),
                     ])
                     .indent(4),
             ]);
@@ -1016,6 +1017,27 @@ fn to_str_report<'a>(
                 severity: Severity::RuntimeError,
             }
         }
+        EString::InspectEnd(pos) => {
+            let surroundings = Region::new(start, pos);
+            let region = LineColumnRegion::from_pos(lines.convert_pos(pos));
+
+            let doc = alloc.stack([
+                alloc.reflow(r"I cannot find the end of this inspected value:"),
+                alloc.region_with_subregion(lines.convert_region(surroundings), region),
+                alloc.concat([
+                    alloc.reflow(r"You could change it to something like "),
+                    alloc.parser_suggestion("\"The point is \\{point}\""),
+                    alloc.reflow("."),
+                ]),
+            ]);
+
+            Report {
+                filename,
+                doc,
+                title: "ENDLESS FORMAT".to_string(),
+                severity: Severity::RuntimeError,
+            }
+        }
         EString::EndlessSingleQuote(pos) => {
             let surroundings = Region::new(start, pos);
             let region = LineColumnRegion::from_pos(lines.convert_pos(pos));
@@ -15,8 +15,8 @@ use roc_module::symbol::Symbol;
 use roc_problem::Severity;
 use roc_region::all::{LineInfo, Region};
 use roc_solve_problem::{
-    NotDerivableContext, NotDerivableDecode, NotDerivableEncode, NotDerivableEq, TypeError,
-    UnderivableReason, Unfulfilled,
+    NotDerivableContext, NotDerivableDecode, NotDerivableEncode, NotDerivableEq, NotDerivableSort,
+    TypeError, UnderivableReason, Unfulfilled,
 };
 use roc_std::RocDec;
 use roc_types::pretty_print::{Parens, WILDCARD};
@@ -248,6 +248,113 @@ pub fn type_problem<'b>(
                 severity,
             })
         }
+        TypeHole {
+            region,
+            name,
+            typ,
+            fits,
+        } => Some(to_type_hole_report(
+            alloc, lines, filename, severity, region, name, typ, fits,
+        )),
+        UnusedImplementation {
+            region,
+            opaque,
+            ability,
+        } => {
+            let stack = [
+                alloc.concat([
+                    alloc.reflow("The "),
+                    alloc.symbol_unqualified(opaque),
+                    alloc.reflow(" opaque type implements "),
+                    alloc.symbol_unqualified(ability),
+                    alloc.reflow(", but nothing ever uses that:"),
+                ]),
+                alloc.region(lines.convert_region(region)),
+                alloc.concat([
+                    alloc.reflow("If "),
+                    alloc.symbol_unqualified(opaque),
+                    alloc.reflow(" doesn't need to implement "),
+                    alloc.symbol_unqualified(ability),
+                    alloc.reflow(", remove it from the "),
+                    alloc.keyword("implements"),
+                    alloc.reflow(" list."),
+                ]),
+            ];
+
+            Some(Report {
+                title: "UNUSED IMPLEMENTATION".to_string(),
+                filename,
+                doc: alloc.stack(stack),
+                severity,
+            })
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn to_type_hole_report<'b>(
+    alloc: &'b RocDocAllocator<'b>,
+    lines: &LineInfo,
+    filename: PathBuf,
+    severity: Severity,
+    region: Region,
+    name: Option<Lowercase>,
+    typ: ErrorType,
+    fits: Option<Vec<Symbol>>,
+) -> Report<'b> {
+    let found = match &name {
+        Some(name) => alloc.concat([
+            alloc.reflow("I found the "),
+            text!(alloc, "?{}", name).annotate(Annotation::Symbol),
+            alloc.reflow(" type hole here:"),
+        ]),
+        None => alloc.reflow("I found a type hole here:"),
+    };
+
+    let mut stack = vec![
+        found,
+        alloc.region(lines.convert_region(region)),
+        alloc.reflow("Based on how it is used, its type is:"),
+        alloc.type_block(error_type_to_doc(alloc, typ)),
+    ];
+
+    // Holes in type annotations don't have values that could fit them.
+    if let Some(fits) = fits {
+        // Generated names (e.g. for desugared closures) can't be written by the user.
+        let fits: Vec<_> = fits
+            .into_iter()
+            .filter(|symbol| {
+                symbol
+                    .as_str(alloc.interns)
+                    .starts_with(|c: char| c.is_ascii_lowercase())
+            })
+            .collect();
+
+        if fits.is_empty() {
+            stack.push(alloc.reflow("None of the values in scope fit this hole."));
+        } else {
+            // Listed plainly, like the suggestions for a misspelled name, but qualified the way
+            // they would be written at the hole.
+            let fits = fits.into_iter().map(|symbol| {
+                let name = symbol.as_str(alloc.interns);
+
+                if symbol.module_id() == alloc.home {
+                    alloc.string(name.to_string())
+                } else {
+                    text!(alloc, "{}.{}", symbol.module_string(alloc.interns), name)
+                }
+            });
+
+            stack.push(alloc.reflow("These values in scope fit this hole:"));
+            stack.push(alloc.vcat(fits).indent(4));
+        }
+    }
+
+    Report {
+        title: "TYPE HOLE".to_string(),
+        filename,
+        doc: alloc.stack(stack),
+        severity,
     }
 }
 
@@ -268,6 +375,34 @@ fn report_unfulfilled_ability<'a>(
 
             alloc.stack(stack)
         }
+        Unfulfilled::OpaqueDoesNotImplementParent {
+            typ,
+            ability,
+            parent,
+        } => {
+            let stack = vec![
+                alloc.concat([
+                    alloc.reflow("The type "),
+                    alloc.symbol_unqualified(typ),
+                    alloc.reflow(" implements the ability "),
+                    alloc.symbol_unqualified(ability),
+                    alloc.reflow(", but not the ability "),
+                    alloc.symbol_unqualified(parent),
+                    alloc.reflow(", which "),
+                    alloc.symbol_unqualified(ability),
+                    alloc.reflow(" requires."),
+                ]),
+                alloc.tip().append(alloc.concat([
+                    alloc.reflow("Add "),
+                    alloc.symbol_unqualified(parent),
+                    alloc.reflow(" to the abilities "),
+                    alloc.symbol_unqualified(typ),
+                    alloc.reflow(" implements."),
+                ])),
+            ];
+
+            alloc.stack(stack)
+        }
         Unfulfilled::AdhocUnderivable {
             typ,
             ability,
@@ -461,6 +596,18 @@ fn underivable_hint<'b>(
                 ])))
             }
         },
+        NotDerivableContext::Sort(reason) => match reason {
+            NotDerivableSort::FloatingPoint => {
+                Some(alloc.note("").append(alloc.concat([
+                    alloc.reflow("I can't derive "),
+                    alloc.symbol_qualified(Symbol::SORT_COMPARE),
+                    alloc.reflow(" for floating-point types. That's because Roc's floating-point numbers cannot be put in a total order - in Roc, `NaN` is neither less than, equal to, nor greater than any number."),
+                    alloc.reflow(" If a type doesn't have a total order, it cannot support the "),
+                    alloc.symbol_unqualified(Symbol::SORT_SORT_ABILITY),
+                    alloc.reflow(" ability!"),
+                ])))
+            }
+        },
     }
 }
 
@@ -13,7 +13,9 @@ mod test_reporting {
     use indoc::indoc;
     use roc_can::abilities::AbilitiesStore;
     use roc_can::expr::PendingDerives;
-    use roc_load::{self, ExecutionMode, LoadConfig, LoadedModule, LoadingProblem, Threading};
+    use roc_load::{
+        self, ExecutionMode, LoadConfig, LoadedModule, LoadingProblem, MonoOptimizations, Threading,
+    };
     use roc_module::symbol::{Interns, ModuleId};
     use roc_packaging::cache::RocCacheDir;
     use roc_parse::module::parse_header;
@@ -128,6 +130,8 @@ mod test_reporting {
                 palette: DEFAULT_PALETTE,
                 threading: Threading::Single,
                 exec_mode: ExecutionMode::Check,
+                mono_optimizations: MonoOptimizations::NONE,
+                emit_debug_info: false,
                 function_kind: FunctionKind::LambdaSet,
             };
             let result = roc_load::load_and_typecheck(
@@ -436,6 +440,68 @@ mod test_reporting {
         check_render(buf.as_str());
     }
 
+    /// Like `__new_report_problem_as`, but specializes the program with constant folding on, as
+    /// `roc build` does, to get the problems that are only found after type checking.
+    fn __new_mono_report_problem_as(src: &str, check_render: impl FnOnce(&str)) {
+        use ven_pretty::DocAllocator;
+
+        let arena = Bump::new();
+        let module_src = if src.starts_with("app") {
+            src.to_string()
+        } else {
+            promote_expr_to_module(src)
+        };
+
+        let load_config = LoadConfig {
+            target_info: roc_target::TargetInfo::default_x86_64(),
+            render: RenderTarget::Generic,
+            palette: DEFAULT_PALETTE,
+            threading: Threading::Single,
+            exec_mode: ExecutionMode::Executable,
+            mono_optimizations: MonoOptimizations {
+                fold_constants: true,
+                ..MonoOptimizations::NONE
+            },
+            emit_debug_info: false,
+            function_kind: FunctionKind::LambdaSet,
+        };
+        let loaded = roc_load::load_and_monomorphize_from_str(
+            &arena,
+            PathBuf::from("Test.roc"),
+            &module_src,
+            PathBuf::from("fake/test/path"),
+            RocCacheDir::Disallowed,
+            load_config,
+        );
+
+        let mut loaded = match loaded {
+            Ok(loaded) => loaded,
+            Err(problem) => panic!("failed to load: {problem:?}"),
+        };
+
+        let home = loaded.module_id;
+        let can_problems = loaded.can_problems.remove(&home).unwrap_or_default();
+
+        let lines = LineInfo::new(&module_src);
+        let src_lines: Vec<&str> = module_src.split('\n').collect();
+        let alloc = RocDocAllocator::new(&src_lines, home, &loaded.interns);
+        let filename = filename_from_string(r"/code/proj/Main.roc");
+
+        let reports = can_problems
+            .into_iter()
+            .map(|problem| can_problem(&alloc, &lines, filename.clone(), problem).pretty(&alloc));
+
+        let mut buf = String::new();
+        alloc
+            .stack(reports)
+            .append(alloc.line())
+            .1
+            .render_raw(70, &mut roc_reporting::report::CiWrite::new(&mut buf))
+            .expect("list_reports");
+
+        check_render(buf.as_str());
+    }
+
     macro_rules! test_report {
         ($(#[$meta:meta])* $test_name:ident, $program:expr, @$output:literal) => {
             test_report!($(#[$meta])* $test_name, $program, |golden| insta::assert_snapshot!(golden, @$output) );
@@ -449,6 +515,16 @@ mod test_reporting {
         }
     }
 
+    macro_rules! test_mono_report {
+        ($(#[$meta:meta])* $test_name:ident, $program:expr, @$output:literal) => {
+            #[test]
+            $(#[$meta])*
+            fn $test_name() {
+                __new_mono_report_problem_as($program, |golden| insta::assert_snapshot!(golden, @$output))
+            }
+        };
+    }
+
     macro_rules! test_no_problem {
         ($(#[$meta:meta])* $test_name: ident, $program:expr) => {
             #[test]
@@ -3227,6 +3303,41 @@ mod test_reporting {
     "###
     );
 
+    test_report!(
+        unused_record_field,
+        indoc!(
+            r#"
+            config = { name: "roc", verbose: Bool.true }
+
+            config.name
+            "#
+        ),
+        @r###"
+    ── UNUSED RECORD FIELD ─────────────────────────────────── /code/proj/Main.roc ─
+
+    The `.verbose` field of `config` is never used:
+
+    4│      config = { name: "roc", verbose: Bool.true }
+                                    ^^^^^^^^^^^^^^^^^^
+
+    If you don't need this field, remove it from the record.
+    "###
+    );
+
+    test_report!(
+        record_passed_along_has_no_unused_fields,
+        indoc!(
+            r#"
+            config = { name: "roc", verbose: Bool.true }
+
+            getName = \c -> c.name
+
+            getName config
+            "#
+        ),
+        @""
+    );
+
     test_report!(
         invalid_opaque_rigid_var_pattern,
         indoc!(
@@ -5427,6 +5538,7 @@ mod test_reporting {
         - An escaped backslash: \\
         - A unicode code point: \u(00FF)
         - An interpolated string: \(myVariable)
+        - An inspected value: \{myValue}
     "###
     );
 
@@ -8724,6 +8836,86 @@ In roc, functions are always written as a lambda, like{}
     "###
     );
 
+    test_report!(
+        ability_missing_parent_implementation,
+        indoc!(
+            r#"
+            app "test" provides [A] to "./platform"
+
+            MEq implements
+                eq : a, a -> Bool where a implements MEq
+
+            MOrd implements MEq
+                le : a, a -> Bool where a implements MOrd
+
+            A := U8 implements [MOrd {le}]
+
+            le = \@A m, @A n -> m <= n
+            "#
+        ),
+        @r###"
+    ── INCOMPLETE ABILITY IMPLEMENTATION ───────────────────── /code/proj/Main.roc ─
+
+    The type `A` implements the ability `MOrd`, but not the ability `MEq`, which
+    `MOrd` requires.
+
+    Tip: Add `MEq` to the abilities `A` implements.
+    "###
+    );
+
+    test_report!(
+        ability_implemented_twice_for_opaque,
+        indoc!(
+            r#"
+            app "test" provides [A] to "./platform"
+
+            A := U8 implements [Eq, Hash, Eq]
+            "#
+        ),
+        @r###"
+    ── DUPLICATE ABILITY IMPLEMENTATION ────────────────────── /code/proj/Main.roc ─
+
+    This type implements the `Eq` ability more than once:
+
+    3│  A := U8 implements [Eq, Hash, Eq]
+                                      ^^
+
+    The first implementation was listed here:
+
+    3│  A := U8 implements [Eq, Hash, Eq]
+                            ^^
+
+    Only the first implementation will be used. I'd suggest removing the
+    duplicate.
+    "###
+    );
+
+    test_report!(
+        ability_member_default_not_specializable,
+        indoc!(
+            r#"
+            app "test" provides [zero] to "./platform"
+
+            MZero implements
+                zero : {} -> List a where a implements MZero
+                zero = \{} -> []
+            "#
+        ),
+        @r###"
+    ── UNUSABLE DEFAULT IMPLEMENTATION ─────────────────────── /code/proj/Main.roc ─
+
+    The default implementation of `zero` cannot be used by types
+    implementing `MZero`:
+
+    4│      zero : {} -> List a where a implements MZero
+            ^^^^
+
+    Default implementations can only be given to members that are
+    functions mentioning the `MZero` type variable directly as an argument
+    or as the return value.
+    "###
+    );
+
     test_report!(
         ability_specialization_is_unused,
         indoc!(
@@ -9068,6 +9260,37 @@ In roc, functions are always written as a lambda, like{}
     "###
     );
 
+    test_report!(
+        unused_ability_implementation,
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            MHash implements
+                hash : a -> U64 where a implements MHash
+
+            Id := U64 implements [MHash {hash: hashId}]
+            hashId = \@Id n -> n
+
+            main =
+                id = @Id 42
+                when id is
+                    @Id n -> n
+            "#
+        ),
+        @r###"
+    ── UNUSED IMPLEMENTATION ───────────────────────────────── /code/proj/Main.roc ─
+
+    The `Id` opaque type implements `MHash`, but nothing ever uses that:
+
+    6│  Id := U64 implements [MHash {hash: hashId}]
+        ^^
+
+    If `Id` doesn't need to implement `MHash`, remove it from the `implements`
+    list.
+    "###
+    );
+
     test_report!(
         branches_have_more_cases_than_condition,
         indoc!(
@@ -9744,7 +9967,8 @@ In roc, functions are always written as a lambda, like{}
 
     Only builtin abilities can be derived.
 
-    Note: The builtin abilities are `Encoding`, `Decoding`, `Hash`, `Eq`
+    Note: The builtin abilities are `Encoding`, `Decoding`, `Hash`, `Eq`, `Sort`,
+    `Inspect`
     "###
     );
 
@@ -10243,20 +10467,103 @@ In roc, functions are always written as a lambda, like{}
         |golden| pretty_assertions::assert_eq!(
             golden,
             indoc!(
-                r###"── SYNTAX PROBLEM ──────────────────────────────────────── /code/proj/Main.roc ─
+                r###"── TYPE HOLE ───────────────────────────────────────────── /code/proj/Main.roc ─
 
-                An underscore is being used as a variable here:
+                I found a type hole here:
 
                 6│      f 1 _ 1
                             ^
 
-                An underscore can be used to ignore a value when pattern matching, but
-                it cannot be used as a variable.
+                Based on how it is used, its type is:
+
+                    Num *
+
+                None of the values in scope fit this hole.
                 "###
             ),
         )
     );
 
+    test_report!(
+        named_type_hole_with_fitting_values,
+        indoc!(
+            r#"
+            greeting : Str
+            greeting = "Hello"
+
+            Str.concat greeting ?suffix
+            "#
+        ),
+        @r###"
+    ── TYPE HOLE ───────────────────────────────────────────── /code/proj/Main.roc ─
+
+    I found the `?suffix` type hole here:
+
+    7│      Str.concat greeting ?suffix
+                                ^^^^^^^
+
+    Based on how it is used, its type is:
+
+        Str
+
+    These values in scope fit this hole:
+
+        greeting
+    "###
+    );
+
+    test_report!(
+        type_hole_fit_by_builtin_is_qualified,
+        indoc!(
+            r#"
+            shout = \n -> Str.concat (Num.toStr n) "!"
+
+            Str.concat (shout 3u8) (Str.joinWith (List.map [1u8, 2u8] ?toText) ", ")
+            "#
+        ),
+        @r###"
+    ── TYPE HOLE ───────────────────────────────────────────── /code/proj/Main.roc ─
+
+    I found the `?toText` type hole here:
+
+    6│      Str.concat (shout 3u8) (Str.joinWith (List.map [1u8, 2u8] ?toText) ", ")
+                                                                      ^^^^^^^
+
+    Based on how it is used, its type is:
+
+        U8 -> Str
+
+    These values in scope fit this hole:
+
+        shout
+        Num.toStr
+    "###
+    );
+
+    test_report!(
+        type_hole_in_annotation,
+        indoc!(
+            r#"
+            names : List ?elem
+            names = ["Ada", "Grace"]
+
+            names
+            "#
+        ),
+        @r###"
+    ── TYPE HOLE ───────────────────────────────────────────── /code/proj/Main.roc ─
+
+    I found the `?elem` type hole here:
+
+    4│      names : List ?elem
+                         ^^^^^
+
+    Based on how it is used, its type is:
+
+        Str
+    "###
+    );
+
     test_report!(
         call_with_declared_identifier_starting_with_underscore,
         indoc!(
@@ -13931,4 +14238,66 @@ In roc, functions are always written as a lambda, like{}
     make partial application explicit.
     "###
     );
+
+    test_mono_report!(
+        constant_overflow,
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            limit : U8
+            limit = 255
+
+            next : U8
+            next = limit + 1
+
+            main = next
+            "#
+        ),
+        @r###"
+    ── CONSTANT OVERFLOW ───────────────────────────────────── /code/proj/Main.roc ─
+
+    This addition always overflows:
+
+    7│  next = limit + 1
+               ^^^^^^^^^
+
+    All of its inputs are known at compile time, and the result does not
+    fit in its type. The program will crash if this code is ever reached.
+
+    Tip: Use a wider number type, or one of the wrapping or saturating
+    operations like Num.addWrap if overflow is expected.
+    "###
+    );
+
+    test_mono_report!(
+        constant_overflow_inside_expression,
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            limit : U8
+            limit = 255
+
+            main =
+                label = "next"
+
+                "\(label): \(Num.toStr (limit + 1))"
+            "#
+        ),
+        @r###"
+    ── CONSTANT OVERFLOW ───────────────────────────────────── /code/proj/Main.roc ─
+
+    This addition always overflows:
+
+    9│      "\(label): \(Num.toStr (limit + 1))"
+                                    ^^^^^^^^^
+
+    All of its inputs are known at compile time, and the result does not
+    fit in its type. The program will crash if this code is ever reached.
+
+    Tip: Use a wider number type, or one of the wrapping or saturating
+    operations like Num.addWrap if overflow is expected.
+    "###
+    );
 }
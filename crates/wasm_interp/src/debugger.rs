@@ -0,0 +1,182 @@
+//! Interactive stepping debugger for the interpreter.
+//!
+//! This module only decides *when* to pause and parses the user's commands.
+//! The `Instance` does the prompting, since it owns all the state we want to display.
+
+pub const HELP: &str = "\
+Commands:
+  s,  step              Execute one instruction, stepping into calls
+  n,  next              Execute one instruction, stepping over calls
+  f,  finish            Run until the current function returns
+  c,  continue          Run until the next breakpoint
+  b,  break <FUNCTION>  Pause whenever a function is called (by name or index)
+  d,  delete <FUNCTION> Remove a breakpoint
+      breakpoints       List breakpoints
+  st, stack             Show the value stack of the current function
+  l,  locals            Show the arguments and locals of the current function
+  bt, backtrace         Show the call stack
+  x,  memory <ADDR> [LENGTH]
+                        Dump linear memory (addresses can be decimal or 0x hex)
+  h,  help              Show this message
+  q,  quit              Stop the program";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RunMode {
+    /// Pause before every instruction
+    Step,
+    /// Pause when we're back at this call depth or shallower
+    Next(usize),
+    /// Pause when we're shallower than this call depth
+    Finish(usize),
+    /// Only pause at breakpoints
+    Continue,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Step,
+    Next,
+    Finish,
+    Continue,
+    Break(String),
+    Delete(String),
+    Breakpoints,
+    Stack,
+    Locals,
+    Backtrace,
+    Memory { addr: u32, len: u32 },
+    Help,
+    Quit,
+}
+
+impl Command {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let mut words = line.split_whitespace();
+        let name = words.next().unwrap_or("");
+        let mut operand = |what: &str| {
+            words
+                .next()
+                .map(String::from)
+                .ok_or_else(|| format!("`{name}` needs {what}"))
+        };
+
+        let command = match name {
+            // An empty line repeats the default action, like stepping in gdb
+            "" | "s" | "step" => Command::Step,
+            "n" | "next" => Command::Next,
+            "f" | "finish" => Command::Finish,
+            "c" | "continue" => Command::Continue,
+            "b" | "break" => Command::Break(operand("a function name or index")?),
+            "d" | "delete" => Command::Delete(operand("a function name or index")?),
+            "breakpoints" => Command::Breakpoints,
+            "st" | "stack" => Command::Stack,
+            "l" | "locals" => Command::Locals,
+            "bt" | "backtrace" => Command::Backtrace,
+            "x" | "memory" => {
+                let addr = parse_u32(&operand("an address")?)?;
+                let len = match words.next() {
+                    Some(len) => parse_u32(len)?,
+                    None => 64,
+                };
+                Command::Memory { addr, len }
+            }
+            "h" | "help" => Command::Help,
+            "q" | "quit" => Command::Quit,
+            _ => return Err(format!("Unknown command `{name}`. Type `help` for a list.")),
+        };
+
+        Ok(command)
+    }
+}
+
+fn parse_u32(s: &str) -> Result<u32, String> {
+    let result = match s.strip_prefix("0x") {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => s.parse(),
+    };
+    result.map_err(|_| format!("`{s}` is not a valid number"))
+}
+
+#[derive(Debug)]
+pub struct Debugger {
+    mode: RunMode,
+    /// Function indices to pause at when they're called
+    breakpoints: Vec<usize>,
+    /// Call depth before the instruction we just executed
+    call_depth: usize,
+}
+
+impl Debugger {
+    pub fn new() -> Self {
+        Debugger {
+            mode: RunMode::Step,
+            breakpoints: Vec::new(),
+            call_depth: 0,
+        }
+    }
+
+    /// Decide whether to pause before the next instruction
+    pub fn should_pause(&mut self, call_depth: usize, fn_index: usize) -> bool {
+        let entered_function = call_depth > self.call_depth;
+        self.call_depth = call_depth;
+
+        let at_breakpoint = entered_function && self.breakpoints.contains(&fn_index);
+
+        at_breakpoint
+            || match self.mode {
+                RunMode::Step => true,
+                RunMode::Next(depth) => call_depth <= depth,
+                RunMode::Finish(depth) => call_depth < depth,
+                RunMode::Continue => false,
+            }
+    }
+
+    /// Handle a command that resumes execution. Returns false for other commands.
+    pub fn resume(&mut self, command: &Command) -> bool {
+        self.mode = match command {
+            Command::Step => RunMode::Step,
+            Command::Next => RunMode::Next(self.call_depth),
+            Command::Finish => RunMode::Finish(self.call_depth),
+            Command::Continue => RunMode::Continue,
+            _ => return false,
+        };
+        true
+    }
+
+    pub fn breakpoints(&self) -> &[usize] {
+        &self.breakpoints
+    }
+
+    pub fn add_breakpoint(&mut self, fn_index: usize) {
+        if !self.breakpoints.contains(&fn_index) {
+            self.breakpoints.push(fn_index);
+        }
+    }
+
+    pub fn delete_breakpoint(&mut self, fn_index: usize) -> bool {
+        let len_before = self.breakpoints.len();
+        self.breakpoints.retain(|b| *b != fn_index);
+        self.breakpoints.len() != len_before
+    }
+}
+
+/// Hex dump of some memory, 16 bytes per line, with printable ASCII on the right
+pub fn hex_dump(memory: &[u8], addr: u32) -> String {
+    let mut dump = String::new();
+    for (i, line) in memory.chunks(16).enumerate() {
+        let line_addr = addr as usize + 16 * i;
+        let hex: Vec<String> = line.iter().map(|b| format!("{b:02x}")).collect();
+        let ascii: String = line
+            .iter()
+            .map(|b| {
+                if b.is_ascii_graphic() || *b == b' ' {
+                    *b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        dump.push_str(&format!("{line_addr:08x}  {:47}  {ascii}\n", hex.join(" ")));
+    }
+    dump
+}
@@ -15,8 +15,8 @@ pub struct Frame {
     pub locals_start: usize,
     /// Number of args & locals in the frame
     pub locals_count: usize,
-    /// Expected return type, if any
-    pub return_type: Option<ValueType>,
+    /// Number of values returned (more than one with the multi-value proposal)
+    pub return_count: usize,
 }
 
 impl Frame {
@@ -27,7 +27,7 @@ impl Frame {
             body_block_index: 0,
             locals_start: 0,
             locals_count: 0,
-            return_type: None,
+            return_count: 0,
         }
     }
 
@@ -37,7 +37,7 @@ impl Frame {
         return_addr: usize,
         body_block_index: usize,
         n_args: usize,
-        return_type: Option<ValueType>,
+        return_count: usize,
         code_bytes: &[u8],
         value_store: &mut ValueStore<'_>,
         pc: &mut usize,
@@ -66,7 +66,7 @@ impl Frame {
             body_block_index,
             locals_start,
             locals_count,
-            return_type,
+            return_count,
         }
     }
 
@@ -1,14 +1,17 @@
 use bumpalo::{collections::Vec, Bump};
 use std::fmt::{self, Write};
+use std::io;
 use std::iter::{self, once, Iterator};
 
-use roc_wasm_module::opcodes::{MemoryInstruction, OpCode};
+use roc_wasm_module::opcodes::{self, MemoryInstruction, OpCode};
 use roc_wasm_module::parse::{Parse, SkipBytes};
-use roc_wasm_module::sections::{ImportDesc, MemorySection, SignatureParamsIter};
+use roc_wasm_module::sections::{DataMode, ImportDesc, MemorySection, SignatureParamsIter};
 use roc_wasm_module::{ExportType, WasmModule};
 use roc_wasm_module::{Value, ValueType};
 
+use crate::debugger::{self, Command, Debugger};
 use crate::frame::Frame;
+use crate::profiler::Profiler;
 use crate::value_store::ValueStore;
 use crate::{Error, ImportDispatcher};
 
@@ -30,6 +33,8 @@ enum BlockType {
 struct Block {
     ty: BlockType,
     vstack: usize,
+    /// Number of values a branch to this block carries: the results of a block, or the params of a loop
+    branch_arity: usize,
 }
 
 #[derive(Debug, Clone)]
@@ -64,8 +69,14 @@ pub struct Instance<'a, I: ImportDispatcher> {
     pub import_dispatcher: I,
     /// Temporary storage for import arguments
     import_arguments: Vec<'a, Value>,
+    /// Passive data segments for `memory.init`. Active segments, and dropped ones, are `None`.
+    data_segments: Vec<'a, Option<&'a [u8]>>,
     /// temporary storage for output using the --debug option
     debug_string: Option<String>,
+    /// Interactive debugger, if enabled
+    debugger: Option<Debugger>,
+    /// Instruction counts and timings, if enabled
+    profiler: Option<Profiler>,
 }
 
 impl<'a, I: ImportDispatcher> Instance<'a, I> {
@@ -94,7 +105,10 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
             import_count: 0,
             import_dispatcher,
             import_arguments: Vec::new_in(arena),
+            data_segments: Vec::new_in(arena),
             debug_string: Some(String::new()),
+            debugger: None,
+            profiler: None,
         }
     }
 
@@ -126,6 +140,20 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
 
         let globals = module.global.initial_values(arena);
 
+        let data_segments = {
+            let segments = module.data.segments().map_err(|e| {
+                format!(
+                    "Error parsing Data section at offset {:#x}:\n{}",
+                    e.offset, e.message
+                )
+            })?;
+            let passive_segments = segments.into_iter().map(|(mode, init)| match mode {
+                DataMode::Passive => Some(init),
+                DataMode::Active { .. } => None,
+            });
+            Vec::from_iter_in(passive_segments, arena)
+        };
+
         // We don't handle non-function import types (memories, tables, and globals),
         // and it's nice for lookups to assume they're all functions, so let's assert that.
         let all_imports_are_functions = module.import.imports.iter().all(|imp| imp.is_function());
@@ -162,15 +190,48 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
             import_count,
             import_dispatcher,
             import_arguments: Vec::new_in(arena),
+            data_segments,
             debug_string,
+            debugger: None,
+            profiler: None,
         })
     }
 
+    /// Pause before the first instruction, and prompt for debugger commands on stdin
+    pub fn enable_debugger(&mut self) {
+        self.debugger = Some(Debugger::new());
+    }
+
+    /// Make the debugger pause whenever a function is called, by name or index.
+    /// If the debugger isn't enabled yet, this enables it without pausing at the start.
+    pub fn add_breakpoint(&mut self, function: &str) -> Result<(), String> {
+        let fn_index = self.debug_find_function(function)?;
+        let debugger = self.debugger.get_or_insert_with(|| {
+            let mut debugger = Debugger::new();
+            debugger.resume(&Command::Continue);
+            debugger
+        });
+        debugger.add_breakpoint(fn_index);
+        Ok(())
+    }
+
+    /// Count instructions and time spent in each function
+    pub fn enable_profiler(&mut self) {
+        self.profiler = Some(Profiler::new(self.branch_cache.len()));
+    }
+
+    /// A flat profile of the program so far, if the profiler is enabled
+    pub fn profile_report(&mut self) -> Option<String> {
+        let module = self.module;
+        let profiler = self.profiler.as_mut()?;
+        Some(profiler.report(|fn_index| debug_function_name(module, fn_index)))
+    }
+
     pub fn call_export<A>(&mut self, fn_name: &str, arg_values: A) -> Result<Option<Value>, String>
     where
         A: IntoIterator<Item = Value>,
     {
-        let (fn_index, param_type_iter, ret_type) =
+        let (fn_index, param_type_iter, return_count) =
             self.call_export_help_before_arg_load(self.module, fn_name)?;
         let n_args = param_type_iter.len();
 
@@ -184,7 +245,7 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
             self.value_store.push(value);
         }
 
-        self.call_export_help_after_arg_load(self.module, fn_index, n_args, ret_type)
+        self.call_export_help_after_arg_load(self.module, fn_index, n_args, return_count)
     }
 
     pub fn call_export_from_cli(
@@ -206,7 +267,7 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
 
         // Implement the "basic numbers" CLI
         // Check if the called Wasm function takes numeric arguments, and if so, try to parse them from the CLI.
-        let (fn_index, param_type_iter, ret_type) =
+        let (fn_index, param_type_iter, return_count) =
             self.call_export_help_before_arg_load(module, fn_name)?;
         let n_args = param_type_iter.len();
         for (value_bytes, value_type) in arg_strings
@@ -225,14 +286,14 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
             self.value_store.push(value);
         }
 
-        self.call_export_help_after_arg_load(module, fn_index, n_args, ret_type)
+        self.call_export_help_after_arg_load(module, fn_index, n_args, return_count)
     }
 
     fn call_export_help_before_arg_load<'m>(
         &mut self,
         module: &'m WasmModule<'a>,
         fn_name: &str,
-    ) -> Result<(usize, SignatureParamsIter<'m>, Option<ValueType>), String> {
+    ) -> Result<(usize, SignatureParamsIter<'m>, usize), String> {
         let fn_index = {
             let mut export_iter = module.export.exports.iter();
             export_iter
@@ -272,7 +333,7 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
             cursor
         };
 
-        let (param_type_iter, return_type) = {
+        let (param_type_iter, result_type_iter) = {
             let signature_index = module.function.signatures[internal_fn_index];
             module.types.look_up(signature_index)
         };
@@ -286,7 +347,7 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
             );
         }
 
-        Ok((fn_index, param_type_iter, return_type))
+        Ok((fn_index, param_type_iter, result_type_iter.len()))
     }
 
     fn call_export_help_after_arg_load(
@@ -294,20 +355,21 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
         module: &WasmModule<'a>,
         fn_index: usize,
         n_args: usize,
-        return_type: Option<ValueType>,
+        return_count: usize,
     ) -> Result<Option<Value>, String> {
         self.previous_frames.clear();
         self.blocks.clear();
         self.blocks.push(Block {
             ty: BlockType::Locals(fn_index),
             vstack: self.value_store.depth(),
+            branch_arity: 0,
         });
         self.current_frame = Frame::enter(
             fn_index,
             0, // return_addr
             self.blocks.len(),
             n_args,
-            return_type,
+            return_count,
             &module.code.bytes,
             &mut self.value_store,
             &mut self.program_counter,
@@ -315,9 +377,23 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
         self.blocks.push(Block {
             ty: BlockType::FunctionBody(fn_index),
             vstack: self.value_store.depth(),
+            branch_arity: return_count,
         });
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.count_call(fn_index);
+        }
 
         loop {
+            if let Some(profiler) = self.profiler.as_mut() {
+                profiler.count_instruction(self.current_frame.fn_index);
+            }
+            if let Some(debugger) = self.debugger.as_mut() {
+                let call_depth = self.previous_frames.len() + 1;
+                if debugger.should_pause(call_depth, self.current_frame.fn_index) {
+                    self.debugger_prompt()?;
+                }
+            }
+
             match self.execute_next_instruction(module) {
                 Ok(Action::Continue) => {}
                 Ok(Action::Break) => {
@@ -349,27 +425,36 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
         x
     }
 
+    /// Read the type of a `block`, `loop` or `if`, and return its number of params and results
+    fn fetch_block_type(&mut self, module: &WasmModule<'a>) -> (usize, usize) {
+        let block_type =
+            opcodes::BlockType::parse((), &module.code.bytes, &mut self.program_counter).unwrap();
+        self.write_debug(block_type);
+        match block_type {
+            opcodes::BlockType::Empty => (0, 0),
+            opcodes::BlockType::Value(_) => (0, 1),
+            opcodes::BlockType::Signature(signature_index) => {
+                let (params, results) = module.types.look_up(signature_index);
+                (params.len(), results.len())
+            }
+        }
+    }
+
     fn do_return(&mut self) -> Action {
         // self.debug_values_and_blocks("start do_return");
 
         let Frame {
             return_addr,
             body_block_index,
-            return_type,
+            return_count,
             ..
         } = self.current_frame;
 
-        // Throw away all locals and values except the return value
+        // Throw away all locals and values except the return values
         let locals_block_index = body_block_index - 1;
         let locals_block = &self.blocks[locals_block_index];
-        let new_stack_depth = if return_type.is_some() {
-            self.value_store
-                .set(locals_block.vstack, self.value_store.peek());
-            locals_block.vstack + 1
-        } else {
-            locals_block.vstack
-        };
-        self.value_store.truncate(new_stack_depth);
+        self.value_store
+            .truncate_keeping(locals_block.vstack, return_count);
 
         // Resume executing at the next instruction in the caller function
         let new_block_len = locals_block_index; // don't need a -1 because one is a length and the other is an index!
@@ -420,6 +505,19 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
         }
     }
 
+    /// Bulk memory instructions trap if any part of the range is out of bounds
+    fn check_memory_range(&self, addr: u32, size: u32) -> Result<(), Error> {
+        let memory_size = self.memory.len() as u64;
+        if addr as u64 + size as u64 > memory_size {
+            Err(Error::MemoryAccessOutOfBounds(
+                addr.max(memory_size as u32),
+                memory_size as u32,
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
     fn write_debug<T: fmt::Debug>(&mut self, value: T) {
         if let Some(debug_string) = self.debug_string.as_mut() {
             std::write!(debug_string, "{value:?} ").unwrap();
@@ -428,16 +526,20 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
 
     fn do_break(&mut self, relative_blocks_outward: u32, module: &WasmModule<'a>) {
         let block_index = self.blocks.len() - 1 - relative_blocks_outward as usize;
-        let Block { ty, vstack } = self.blocks[block_index];
+        let Block {
+            ty,
+            vstack,
+            branch_arity,
+        } = self.blocks[block_index];
         match ty {
             BlockType::Loop(start_addr) => {
                 self.blocks.truncate(block_index + 1);
-                self.value_store.truncate(vstack);
+                self.value_store.truncate_keeping(vstack, branch_arity);
                 self.program_counter = start_addr;
             }
             BlockType::FunctionBody(_) | BlockType::Normal => {
                 self.break_forward(relative_blocks_outward, module);
-                self.value_store.truncate(vstack);
+                self.value_store.truncate_keeping(vstack, branch_arity);
             }
             BlockType::Locals(_) => unreachable!(),
         }
@@ -512,10 +614,14 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
             );
         }
 
-        let (arg_type_iter, ret_type) = module.types.look_up(signature_index);
+        let (arg_type_iter, result_type_iter) = module.types.look_up(signature_index);
         let n_args = arg_type_iter.len();
+        let return_count = result_type_iter.len();
         if self.debug_string.is_some() {
-            self.debug_call(n_args, ret_type);
+            self.debug_call(n_args, result_type_iter);
+        }
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.count_call(fn_index);
         }
 
         if let Some(import) = opt_import {
@@ -531,6 +637,11 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
                 self.import_arguments[i] = arg;
             }
 
+            if let Some(profiler) = self.profiler.as_mut() {
+                // Charge the host's time to the import. The caller's next instruction switches back.
+                profiler.switch_to(fn_index);
+            }
+
             let optional_return_val = self.import_dispatcher.dispatch(
                 import.module,
                 import.name,
@@ -554,6 +665,7 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
             self.blocks.push(Block {
                 ty: BlockType::Locals(fn_index),
                 vstack: self.value_store.depth() - n_args,
+                branch_arity: 0,
             });
             let body_block_index = self.blocks.len();
 
@@ -562,7 +674,7 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
                 return_addr,
                 body_block_index,
                 n_args,
-                ret_type,
+                return_count,
                 &module.code.bytes,
                 &mut self.value_store,
                 &mut self.program_counter,
@@ -573,6 +685,7 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
             self.blocks.push(Block {
                 ty: BlockType::FunctionBody(fn_index),
                 vstack: self.value_store.depth(),
+                branch_arity: return_count,
             });
         }
         // self.debug_values_and_blocks("end do_call");
@@ -580,7 +693,7 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
         Ok(())
     }
 
-    fn debug_call(&mut self, n_args: usize, return_type: Option<ValueType>) {
+    fn debug_call(&mut self, n_args: usize, result_types: SignatureParamsIter) {
         if let Some(debug_string) = self.debug_string.as_mut() {
             write!(debug_string, "         args=[").unwrap();
             let arg_iter = self
@@ -596,7 +709,8 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
                 }
                 write!(debug_string, "{arg:x?}").unwrap();
             }
-            writeln!(debug_string, "] return_type={return_type:?}").unwrap();
+            let result_types: std::vec::Vec<ValueType> = result_types.collect();
+            writeln!(debug_string, "] result_types={result_types:?}").unwrap();
         }
     }
 
@@ -624,25 +738,28 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
             }
             NOP => {}
             BLOCK => {
-                self.fetch_immediate_u32(module); // blocktype (ignored)
+                let (n_params, n_results) = self.fetch_block_type(module);
                 self.blocks.push(Block {
                     ty: BlockType::Normal,
-                    vstack: self.value_store.depth(),
+                    vstack: self.value_store.depth() - n_params,
+                    branch_arity: n_results,
                 });
             }
             LOOP => {
-                self.fetch_immediate_u32(module); // blocktype (ignored)
+                let (n_params, _) = self.fetch_block_type(module);
                 self.blocks.push(Block {
                     ty: BlockType::Loop(self.program_counter),
-                    vstack: self.value_store.depth(),
+                    vstack: self.value_store.depth() - n_params,
+                    branch_arity: n_params,
                 });
             }
             IF => {
-                self.fetch_immediate_u32(module); // blocktype (ignored)
+                let (n_params, n_results) = self.fetch_block_type(module);
                 let condition = self.value_store.pop_i32()?;
                 self.blocks.push(Block {
                     ty: BlockType::Normal,
-                    vstack: self.value_store.depth(),
+                    vstack: self.value_store.depth() - n_params,
+                    branch_arity: n_results,
                 });
                 if condition == 0 {
                     let addr = self.program_counter as u32;
@@ -973,29 +1090,64 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
                 // the first argument determines exactly which memory operation we have
                 match MemoryInstruction::try_from(module.code.bytes[self.program_counter]) {
                     Ok(op) => match op {
-                        MemoryInstruction::MemoryInit => todo!("WASM instruction: memory.init"),
-                        MemoryInstruction::DataDrop => todo!("WASM instruction: data.drop"),
+                        MemoryInstruction::MemoryInit => {
+                            self.program_counter += 1;
+                            let segment_index = self.fetch_immediate_u32(module);
+                            // skip the memory index (always zero for now)
+                            self.program_counter += 1;
+
+                            let size = self.value_store.pop_u32()?;
+                            let source = self.value_store.pop_u32()?;
+                            let destination = self.value_store.pop_u32()?;
+
+                            // Active segments count as dropped once they've been loaded
+                            let segment = self.data_segments[segment_index as usize].unwrap_or(&[]);
+                            if source as usize + size as usize > segment.len() {
+                                return Err(Error::DataSegmentAccessOutOfBounds(
+                                    segment_index,
+                                    source,
+                                    segment.len() as u32,
+                                ));
+                            }
+                            self.check_memory_range(destination, size)?;
+
+                            let bytes = &segment[source as usize..][..size as usize];
+                            self.memory[destination as usize..][..size as usize]
+                                .copy_from_slice(bytes);
+                        }
+                        MemoryInstruction::DataDrop => {
+                            self.program_counter += 1;
+                            let segment_index = self.fetch_immediate_u32(module);
+                            self.data_segments[segment_index as usize] = None;
+                        }
                         MemoryInstruction::MemoryCopy => {
-                            let size = self.value_store.pop_u32()? as usize;
-                            let source = self.value_store.pop_u32()? as usize;
-                            let destination = self.value_store.pop_u32()? as usize;
+                            let size = self.value_store.pop_u32()?;
+                            let source = self.value_store.pop_u32()?;
+                            let destination = self.value_store.pop_u32()?;
 
                             // skip the op byte and an extra two zero bytes.
                             // in future versions of WebAssembly this byte may be used to index additional memories
                             self.program_counter += 1 + 2;
 
-                            self.memory.copy_within(source..source + size, destination)
+                            self.check_memory_range(source, size)?;
+                            self.check_memory_range(destination, size)?;
+
+                            let source = source as usize;
+                            self.memory
+                                .copy_within(source..source + size as usize, destination as usize)
                         }
                         MemoryInstruction::MemoryFill => {
-                            let size = self.value_store.pop_u32()? as usize;
+                            let size = self.value_store.pop_u32()?;
                             let byte_value = self.value_store.pop_u32()? as u8;
-                            let destination = self.value_store.pop_u32()? as usize;
+                            let destination = self.value_store.pop_u32()?;
 
                             // skip the op byte and an extra zero byte.
                             // in future versions of WebAssembly this byte may be used to index additional memories
                             self.program_counter += 1 + 1;
 
-                            self.memory[destination..][..size].fill(byte_value);
+                            self.check_memory_range(destination, size)?;
+
+                            self.memory[destination as usize..][..size as usize].fill(byte_value);
                         }
                     },
                     Err(other) => unreachable!("invalid memory instruction {other:?}"),
@@ -1783,7 +1935,7 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
 
         let mut print_blocks = |i| {
             block_str.clear();
-            while let Some((b, Block { vstack, ty })) = block {
+            while let Some((b, Block { vstack, ty, .. })) = block {
                 if *vstack > i {
                     break;
                 }
@@ -1804,6 +1956,129 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
         eprintln!();
     }
 
+    /// Read debugger commands from stdin until one of them resumes execution
+    fn debugger_prompt(&mut self) -> Result<(), String> {
+        let fn_index = self.current_frame.fn_index;
+        let op_code = OpCode::from(self.module.code.bytes[self.program_counter]);
+        eprintln!(
+            "func[{}] {}  {:06x}  {:?}",
+            fn_index,
+            debug_function_name(self.module, fn_index),
+            self.program_counter + self.module.code.section_offset as usize,
+            op_code
+        );
+
+        let mut line = String::new();
+        loop {
+            eprint!("(wasm) ");
+            line.clear();
+            match io::stdin().read_line(&mut line) {
+                Ok(0) | Err(_) => {
+                    return Err("Debugger input ended, so I stopped the program.".into())
+                }
+                Ok(_) => {}
+            }
+
+            let command = match Command::parse(&line) {
+                Ok(command) => command,
+                Err(message) => {
+                    eprintln!("{message}");
+                    continue;
+                }
+            };
+
+            let debugger = self.debugger.as_mut().unwrap();
+            if debugger.resume(&command) {
+                return Ok(());
+            }
+
+            match command {
+                Command::Break(function) => match self.debug_find_function(&function) {
+                    Ok(index) => {
+                        self.debugger.as_mut().unwrap().add_breakpoint(index);
+                        eprintln!(
+                            "Breakpoint at func[{index}] {}",
+                            debug_function_name(self.module, index)
+                        );
+                    }
+                    Err(message) => eprintln!("{message}"),
+                },
+                Command::Delete(function) => match self.debug_find_function(&function) {
+                    Ok(index) => {
+                        if !self.debugger.as_mut().unwrap().delete_breakpoint(index) {
+                            eprintln!("There's no breakpoint at func[{index}]");
+                        }
+                    }
+                    Err(message) => eprintln!("{message}"),
+                },
+                Command::Breakpoints => {
+                    for index in self.debugger.as_ref().unwrap().breakpoints() {
+                        eprintln!("func[{index}] {}", debug_function_name(self.module, *index));
+                    }
+                }
+                Command::Stack => {
+                    let base = self.current_frame.locals_start + self.current_frame.locals_count;
+                    let stack: std::vec::Vec<&Value> = self.value_store.iter().skip(base).collect();
+                    eprintln!("{stack:?}");
+                }
+                Command::Locals => {
+                    for local_index in 0..self.current_frame.locals_count as u32 {
+                        let value = self.current_frame.get_local(&self.value_store, local_index);
+                        eprintln!("{local_index:3}: {value:?}");
+                    }
+                }
+                Command::Backtrace => {
+                    let mut trace = String::new();
+                    self.debug_stack_trace(&mut trace).unwrap();
+                    eprint!("{trace}");
+                }
+                Command::Memory { addr, len } => {
+                    let start = (addr as usize).min(self.memory.len());
+                    let end = start.saturating_add(len as usize).min(self.memory.len());
+                    if start == end {
+                        eprintln!("Memory size is {:#x}", self.memory.len());
+                    } else {
+                        eprint!("{}", debugger::hex_dump(&self.memory[start..end], addr));
+                    }
+                }
+                Command::Help => eprintln!("{}", debugger::HELP),
+                Command::Quit => return Err("Stopped by the debugger.".into()),
+                Command::Step | Command::Next | Command::Finish | Command::Continue => {
+                    unreachable!()
+                }
+            }
+        }
+    }
+
+    /// Look up a function by name (from exports or the name section) or by index
+    fn debug_find_function(&self, function: &str) -> Result<usize, String> {
+        let function_count = self.branch_cache.len();
+        let by_index = function.parse::<usize>().ok();
+        let by_export = || {
+            self.module
+                .export
+                .exports
+                .iter()
+                .find(|ex| ex.ty == ExportType::Func && ex.name == function)
+                .map(|ex| ex.index as usize)
+        };
+        let by_debug_name = || {
+            self.module
+                .names
+                .function_names
+                .iter()
+                .find(|(_, name)| *name == function)
+                .map(|(index, _)| *index as usize)
+        };
+
+        match by_index.or_else(by_export).or_else(by_debug_name) {
+            Some(index) if index < function_count => Ok(index),
+            _ => Err(format!(
+                "I couldn't find a function '{function}' in this WebAssembly module"
+            )),
+        }
+    }
+
     /// Dump a stack trace when an error occurs
     /// --------------
     /// func[123]
@@ -1853,14 +2128,7 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
                 self.module.types.look_up(signature_index).0.len()
             };
 
-            let fn_name = self
-                .module
-                .names
-                .function_names
-                .iter()
-                .find(|(idx, _)| *idx == *fn_index as u32)
-                .map(|(_, name)| *name)
-                .unwrap_or("");
+            let fn_name = debug_function_name(self.module, *fn_index);
 
             // Function and address match wasm-objdump formatting, for easy copy & find
             writeln!(buffer, "func[{fn_index}]  {fn_name}")?;
@@ -1913,3 +2181,14 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
         call_addr
     }
 }
+
+/// The function's name from the name section, if there is one
+fn debug_function_name<'a>(module: &WasmModule<'a>, fn_index: usize) -> &'a str {
+    module
+        .names
+        .function_names
+        .iter()
+        .find(|(idx, _)| *idx == fn_index as u32)
+        .map(|(_, name)| *name)
+        .unwrap_or("")
+}
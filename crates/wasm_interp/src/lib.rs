@@ -1,5 +1,7 @@
+mod debugger;
 mod frame;
 mod instance;
+mod profiler;
 mod tests;
 mod value_store;
 pub mod wasi;
@@ -65,6 +67,7 @@ pub(crate) enum Error {
     Type(ValueType, ValueType),
     StackEmpty,
     MemoryAccessOutOfBounds(u32, u32),
+    DataSegmentAccessOutOfBounds(u32, u32, u32),
     UnreachableOp,
 }
 
@@ -87,6 +90,11 @@ impl Error {
                     file_offset, addr, memory_size-1
                 )
             }
+            Error::DataSegmentAccessOutOfBounds(segment_index, offset, segment_size) => {
+                format!(
+                    "ERROR: A Wasm instruction at file offset {file_offset:#x} tried to read data segment {segment_index} at offset {offset:#x} but it only has {segment_size:#x} bytes\n"
+                )
+            }
             Error::UnreachableOp => {
                 format!("WebAssembly `unreachable` instruction at file offset {file_offset:#x}.\n")
             }
@@ -4,6 +4,7 @@ use clap::{Arg, Command};
 use std::fs;
 use std::io;
 use std::iter::once;
+use std::path::Path;
 use std::process;
 
 use roc_wasm_interp::{DefaultImportDispatcher, Instance};
@@ -12,6 +13,10 @@ use roc_wasm_module::WasmModule;
 pub const FLAG_FUNCTION: &str = "function";
 pub const FLAG_DEBUG: &str = "debug";
 pub const FLAG_HEX: &str = "hex";
+pub const FLAG_DEBUGGER: &str = "debugger";
+pub const FLAG_BREAK: &str = "break";
+pub const FLAG_PROFILE: &str = "profile";
+pub const FLAG_DIR: &str = "dir";
 pub const WASM_FILE: &str = "WASM_FILE";
 pub const ARGS_FOR_APP: &str = "ARGS_FOR_APP";
 
@@ -38,6 +43,30 @@ fn main() -> io::Result<()> {
         .action(ArgAction::SetTrue)
         .required(false);
 
+    let flag_debugger = Arg::new(FLAG_DEBUGGER)
+        .long(FLAG_DEBUGGER)
+        .help("Pause before the first instruction and read debugger commands from stdin.\nType `help` at the prompt for a list of commands.")
+        .action(ArgAction::SetTrue)
+        .required(false);
+
+    let flag_break = Arg::new(FLAG_BREAK)
+        .long(FLAG_BREAK)
+        .help("Pause in the debugger whenever this function is called (by name or index).\nCan be given more than once.")
+        .action(ArgAction::Append)
+        .required(false);
+
+    let flag_profile = Arg::new(FLAG_PROFILE)
+        .long(FLAG_PROFILE)
+        .help("Count the instructions executed and time spent in each function, and print a flat profile at the end.")
+        .action(ArgAction::SetTrue)
+        .required(false);
+
+    let flag_dir = Arg::new(FLAG_DIR)
+        .long(FLAG_DIR)
+        .help("Give the app access to a host directory, as HOST_PATH:GUEST_PATH\ne.g. `--dir ./data:/data`. Can be given more than once.")
+        .action(ArgAction::Append)
+        .required(false);
+
     let wasm_file_to_run = Arg::new(WASM_FILE)
         .help("The .wasm file to run")
         .required(true);
@@ -51,6 +80,10 @@ fn main() -> io::Result<()> {
         .arg(flag_function)
         .arg(flag_debug)
         .arg(flag_hex)
+        .arg(flag_debugger)
+        .arg(flag_break)
+        .arg(flag_profile)
+        .arg(flag_dir)
         .arg(wasm_file_to_run)
         .arg(args_for_app);
 
@@ -60,6 +93,10 @@ fn main() -> io::Result<()> {
     let start_fn_name = matches.get_one::<String>(FLAG_FUNCTION).unwrap();
     let is_debug_mode = matches.get_flag(FLAG_DEBUG);
     let is_hex_format = matches.get_flag(FLAG_HEX);
+    let is_debugger_enabled = matches.get_flag(FLAG_DEBUGGER);
+    let breakpoints = matches.get_many::<String>(FLAG_BREAK).unwrap_or_default();
+    let is_profile_enabled = matches.get_flag(FLAG_PROFILE);
+    let preopened_dirs = matches.get_many::<String>(FLAG_DIR).unwrap_or_default();
     let start_arg_strings = matches.get_many::<String>(ARGS_FOR_APP).unwrap_or_default();
     let wasm_path = matches.get_one::<String>(WASM_FILE).unwrap();
     // WASI expects the .wasm file to be argv[0]
@@ -87,17 +124,44 @@ fn main() -> io::Result<()> {
 
     // Create an execution instance
 
-    let dispatcher = DefaultImportDispatcher::new(&wasi_argv);
+    let mut dispatcher = DefaultImportDispatcher::new(&wasi_argv);
+    for dir in preopened_dirs {
+        // A directory with no guest path is visible to the app under its host path
+        let (host_path, guest_path) = dir.split_once(':').unwrap_or((dir.as_str(), dir.as_str()));
+        if let Err(e) = dispatcher
+            .wasi
+            .preopen_dir(Path::new(host_path), guest_path)
+        {
+            eprintln!("I couldn't give the app access to {host_path}: {e}");
+            process::exit(1);
+        }
+    }
     let mut inst =
         Instance::for_module(&arena, &module, dispatcher, is_debug_mode).unwrap_or_else(|e| {
             eprintln!("{e}");
             process::exit(2);
         });
+    if is_debugger_enabled {
+        inst.enable_debugger();
+    }
+    for function in breakpoints {
+        if let Err(e) = inst.add_breakpoint(function) {
+            eprintln!("{e}");
+            process::exit(2);
+        }
+    }
+    if is_profile_enabled {
+        inst.enable_profiler();
+    }
 
     // Run
 
     let result = inst.call_export_from_cli(&module, start_fn_name, &wasi_argv);
 
+    if let Some(profile) = inst.profile_report() {
+        eprintln!("{profile}");
+    }
+
     // Print out return value, if any
 
     match result {
@@ -0,0 +1,95 @@
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Default, Clone, Copy)]
+struct FunctionProfile {
+    instructions: u64,
+    calls: u64,
+    /// Time spent in this function itself, not counting its callees
+    self_time: Duration,
+}
+
+/// Flat profile of instruction counts and time spent per function.
+/// Imported functions are included too, so we can see how long the host spends in them.
+#[derive(Debug)]
+pub struct Profiler {
+    functions: Vec<FunctionProfile>,
+    current_fn: usize,
+    current_fn_start: Instant,
+}
+
+impl Profiler {
+    pub fn new(function_count: usize) -> Self {
+        Profiler {
+            functions: vec![FunctionProfile::default(); function_count],
+            current_fn: 0,
+            current_fn_start: Instant::now(),
+        }
+    }
+
+    /// Start charging time to a different function
+    pub fn switch_to(&mut self, fn_index: usize) {
+        if fn_index != self.current_fn {
+            self.charge_current_fn();
+            self.current_fn = fn_index;
+        }
+    }
+
+    fn charge_current_fn(&mut self) {
+        let now = Instant::now();
+        self.functions[self.current_fn].self_time += now - self.current_fn_start;
+        self.current_fn_start = now;
+    }
+
+    pub fn count_instruction(&mut self, fn_index: usize) {
+        self.switch_to(fn_index);
+        self.functions[fn_index].instructions += 1;
+    }
+
+    pub fn count_call(&mut self, fn_index: usize) {
+        self.functions[fn_index].calls += 1;
+    }
+
+    /// Flat profile, with the most expensive functions first
+    pub fn report<'n>(&mut self, fn_name: impl Fn(usize) -> &'n str) -> String {
+        self.charge_current_fn();
+
+        let total_time: Duration = self.functions.iter().map(|f| f.self_time).sum();
+        let total_secs = total_time.as_secs_f64().max(f64::MIN_POSITIVE);
+
+        let mut rows: Vec<(usize, &FunctionProfile)> = self
+            .functions
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| f.calls > 0 || f.instructions > 0)
+            .collect();
+        rows.sort_by(|(_, a), (_, b)| {
+            b.self_time
+                .cmp(&a.self_time)
+                .then(b.instructions.cmp(&a.instructions))
+        });
+
+        let mut report = String::new();
+        writeln!(
+            report,
+            "{:>7}  {:>10}  {:>14}  {:>10}  function",
+            "% time", "self ms", "instructions", "calls"
+        )
+        .unwrap();
+        for (fn_index, f) in rows {
+            let secs = f.self_time.as_secs_f64();
+            writeln!(
+                report,
+                "{:>7.2}  {:>10.3}  {:>14}  {:>10}  func[{}] {}",
+                100.0 * secs / total_secs,
+                1000.0 * secs,
+                f.instructions,
+                f.calls,
+                fn_index,
+                fn_name(fn_index)
+            )
+            .unwrap();
+        }
+        report
+    }
+}
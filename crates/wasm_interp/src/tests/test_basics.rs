@@ -508,6 +508,102 @@ impl ImportDispatcher for TestDispatcher {
     }
 }
 
+#[test]
+fn test_multi_value() {
+    // Signature doesn't support multiple results, so write the module bytes by hand
+    let i32 = ValueType::I32 as u8;
+    let types = [
+        3, // type count
+        0x60, 0, 1, i32, // 0: () -> i32
+        0x60, 0, 2, i32, i32, // 1: () -> (i32, i32)
+        0x60, 1, i32, 2, i32, i32, // 2: (i32) -> (i32, i32)
+    ];
+    let functions = [2, 0, 1];
+    let exports = [1, 4, b't', b'e', b's', b't', ExportType::Func as u8, 0];
+
+    // Function 0: exported
+    let func0 = [
+        0, // no locals
+        // call a function that returns two values: (10, 3)
+        OpCode::CALL as u8,
+        1,
+        OpCode::I32SUB as u8, // 7
+        // a block with a param and two results. Branching out of it keeps only the top two values
+        OpCode::I32CONST as u8,
+        100,
+        OpCode::BLOCK as u8,
+        2, // type index
+        OpCode::I32CONST as u8,
+        5,
+        OpCode::I32CONST as u8,
+        20,
+        OpCode::BR as u8,
+        0,
+        OpCode::END as u8,
+        OpCode::I32ADD as u8, // 7, 25
+        OpCode::I32ADD as u8, // 32
+        // an if/else where both branches return two values
+        OpCode::I32CONST as u8,
+        1,
+        OpCode::IF as u8,
+        1, // type index
+        OpCode::I32CONST as u8,
+        2,
+        OpCode::I32CONST as u8,
+        3,
+        OpCode::ELSE as u8,
+        OpCode::I32CONST as u8,
+        0,
+        OpCode::I32CONST as u8,
+        0,
+        OpCode::END as u8,
+        OpCode::I32MUL as u8, // 32, 6
+        OpCode::I32ADD as u8, // 38
+        OpCode::END as u8,
+    ];
+
+    // Function 1: return two values
+    let func1 = [
+        0, // no locals
+        OpCode::I32CONST as u8,
+        10,
+        OpCode::I32CONST as u8,
+        3,
+        OpCode::END as u8,
+    ];
+
+    let mut code = std::vec::Vec::new();
+    code.push(2);
+    code.push(func0.len() as u8);
+    code.extend(func0);
+    code.push(func1.len() as u8);
+    code.extend(func1);
+
+    let mut module_bytes = std::vec::Vec::from(*b"\0asm\x01\0\0\0");
+    for (id, body) in [
+        (1, &types[..]),
+        (3, &functions[..]),
+        (7, &exports[..]),
+        (10, &code[..]),
+    ] {
+        module_bytes.push(id);
+        module_bytes.push(body.len() as u8);
+        module_bytes.extend(body);
+    }
+
+    let arena = Bump::new();
+    let mut inst = Instance::from_bytes(
+        &arena,
+        &module_bytes,
+        DefaultImportDispatcher::default(),
+        false,
+    )
+    .unwrap();
+    let result = inst.call_export("test", []).unwrap();
+
+    assert_eq!(result, Some(Value::I32(38)));
+}
+
 #[test]
 fn test_call_import() {
     let arena = Bump::new();
@@ -839,13 +935,13 @@ fn test_set_get_local() {
     let return_addr = 0x1234;
     let return_block_depth = 0;
     let n_args = 0;
-    let ret_type = Some(ValueType::I32);
+    let return_count = 1;
     inst.current_frame = Frame::enter(
         fn_index,
         return_addr,
         return_block_depth,
         n_args,
-        ret_type,
+        return_count,
         &buffer,
         &mut inst.value_store,
         &mut cursor,
@@ -886,13 +982,13 @@ fn test_tee_get_local() {
     let return_addr = 0x1234;
     let return_block_depth = 0;
     let n_args = 0;
-    let ret_type = Some(ValueType::I32);
+    let return_count = 1;
     inst.current_frame = Frame::enter(
         fn_index,
         return_addr,
         return_block_depth,
         n_args,
-        ret_type,
+        return_count,
         &buffer,
         &mut inst.value_store,
         &mut cursor,
@@ -0,0 +1,133 @@
+use crate::debugger::{Command, Debugger};
+use crate::{DefaultImportDispatcher, Instance};
+use bumpalo::{collections::Vec, Bump};
+use roc_wasm_module::{
+    opcodes::OpCode, Export, ExportType, Serialize, Signature, Value, ValueType, WasmModule,
+};
+
+#[test]
+fn test_parse_commands() {
+    assert_eq!(Command::parse(""), Ok(Command::Step));
+    assert_eq!(Command::parse("n\n"), Ok(Command::Next));
+    assert_eq!(Command::parse("  finish "), Ok(Command::Finish));
+    assert_eq!(
+        Command::parse("b roc__main_1_exposed"),
+        Ok(Command::Break("roc__main_1_exposed".into()))
+    );
+    assert_eq!(
+        Command::parse("x 0x100 16"),
+        Ok(Command::Memory {
+            addr: 0x100,
+            len: 16
+        })
+    );
+    assert_eq!(
+        Command::parse("memory 256"),
+        Ok(Command::Memory { addr: 256, len: 64 })
+    );
+    assert!(Command::parse("break").is_err());
+    assert!(Command::parse("x nowhere").is_err());
+    assert!(Command::parse("frobnicate").is_err());
+}
+
+#[test]
+fn test_step_next_finish() {
+    let mut debugger = Debugger::new();
+
+    // Step pauses before every instruction
+    assert!(debugger.should_pause(1, 5));
+    assert!(debugger.should_pause(2, 6));
+
+    // Next steps over the call at depth 2
+    debugger.resume(&Command::Next);
+    assert!(!debugger.should_pause(3, 7));
+    assert!(!debugger.should_pause(3, 7));
+    assert!(debugger.should_pause(2, 6));
+
+    // Finish runs until we return from depth 2
+    debugger.resume(&Command::Finish);
+    assert!(!debugger.should_pause(2, 6));
+    assert!(!debugger.should_pause(3, 7));
+    assert!(debugger.should_pause(1, 5));
+}
+
+#[test]
+fn test_breakpoint() {
+    let mut debugger = Debugger::new();
+    debugger.add_breakpoint(7);
+    debugger.resume(&Command::Continue);
+
+    assert!(!debugger.should_pause(1, 5));
+    assert!(debugger.should_pause(2, 7));
+    // Only pause when the function is entered, not on every instruction in it
+    assert!(!debugger.should_pause(2, 7));
+    assert!(!debugger.should_pause(1, 5));
+
+    assert!(debugger.delete_breakpoint(7));
+    assert!(!debugger.delete_breakpoint(7));
+    assert!(!debugger.should_pause(2, 7));
+}
+
+#[test]
+fn test_profile() {
+    let arena = Bump::new();
+    let mut module = WasmModule::new(&arena);
+    let start_fn_name = "test";
+
+    module.code.function_count = 2;
+
+    // Function 0 calls function 1 twice
+    let func0_offset = module.code.bytes.len() as u32;
+    module.code.function_offsets.push(func0_offset);
+    module.add_function_signature(Signature {
+        param_types: Vec::new_in(&arena),
+        ret_type: Some(ValueType::I32),
+    });
+    module.export.append(Export {
+        name: start_fn_name,
+        ty: ExportType::Func,
+        index: 0,
+    });
+    [
+        0, // no locals
+        OpCode::CALL as u8,
+        1, // function 1
+        OpCode::CALL as u8,
+        1, // function 1
+        OpCode::I32ADD as u8,
+        OpCode::END as u8,
+    ]
+    .serialize(&mut module.code.bytes);
+
+    // Function 1
+    let func1_offset = module.code.bytes.len() as u32;
+    module.code.function_offsets.push(func1_offset);
+    module.add_function_signature(Signature {
+        param_types: Vec::new_in(&arena),
+        ret_type: Some(ValueType::I32),
+    });
+    [
+        0, // no locals
+        OpCode::I32CONST as u8,
+        21, // constant value (<64 so that LEB-128 is just one byte)
+        OpCode::END as u8,
+    ]
+    .serialize(&mut module.code.bytes);
+
+    let mut inst =
+        Instance::for_module(&arena, &module, DefaultImportDispatcher::default(), false).unwrap();
+    inst.enable_profiler();
+
+    let return_val = inst.call_export(start_fn_name, []).unwrap().unwrap();
+    assert_eq!(return_val, Value::I32(42));
+
+    // Columns are: % time, self ms, instructions, calls, function
+    let report = inst.profile_report().unwrap();
+    let counts = |function: &str| {
+        let line = report.lines().find(|line| line.contains(function)).unwrap();
+        let columns: std::vec::Vec<&str> = line.split_whitespace().collect();
+        (columns[2].to_string(), columns[3].to_string())
+    };
+    assert_eq!(counts("func[0]"), ("4".into(), "1".into()));
+    assert_eq!(counts("func[1]"), ("4".into(), "2".into()));
+}
@@ -119,6 +119,83 @@ fn test_memory_copy() {
     assert_eq!(dest_slice, &[0xAA; SIZE as usize])
 }
 
+/// Copy part of a passive data segment into memory, then read it back.
+/// The module goes through a serialize/parse round trip, so the DataCount section is covered too.
+fn test_memory_init_help(drop_first: bool) -> Result<Option<Value>, String> {
+    let arena = Bump::new();
+    let mut module = WasmModule::new(&arena);
+    module.memory = MemorySection::new(&arena, MemorySection::PAGE_SIZE);
+
+    module.data.append_segment(DataSegment {
+        mode: DataMode::Active {
+            offset: ConstExpr::I32(0),
+        },
+        init: Vec::from_iter_in("xx".bytes(), &arena),
+    });
+    let passive_segment = module.data.append_segment(DataSegment {
+        mode: DataMode::Passive,
+        init: Vec::from_iter_in("hello world".bytes(), &arena),
+    });
+
+    let signature = Signature {
+        param_types: bumpalo::vec![in &arena],
+        ret_type: Some(ValueType::I32),
+    };
+    create_exported_function_no_locals(&mut module, "test", signature, |buf| {
+        if drop_first {
+            buf.append_u8(OpCode::MEMORY as u8);
+            buf.append_u8(9); // data.drop
+            buf.encode_u32(passive_segment);
+        }
+
+        buf.append_u8(OpCode::I32CONST as u8);
+        buf.encode_i32(0x20); // destination
+        buf.append_u8(OpCode::I32CONST as u8);
+        buf.encode_i32(6); // source offset in the segment
+        buf.append_u8(OpCode::I32CONST as u8);
+        buf.encode_i32(5); // size
+        buf.append_u8(OpCode::MEMORY as u8);
+        buf.append_u8(8); // memory.init
+        buf.encode_u32(passive_segment);
+        buf.append_u8(0); // memory index
+
+        buf.append_u8(OpCode::I32CONST as u8);
+        buf.encode_i32(0x20);
+        buf.append_u8(OpCode::I32LOAD as u8);
+        buf.encode_u32(0); // align
+        buf.encode_u32(0); // offset
+        buf.append_u8(OpCode::END as u8);
+    });
+
+    let mut module_bytes = Vec::new_in(&arena);
+    module.serialize(&mut module_bytes);
+
+    let mut inst = Instance::from_bytes(
+        &arena,
+        &module_bytes,
+        DefaultImportDispatcher::default(),
+        false,
+    )?;
+    let result = inst.call_export("test", []);
+
+    // The active segment was loaded at instantiation, and memory.init didn't touch it
+    assert_eq!(&inst.memory[0..2], "xx".as_bytes());
+
+    result
+}
+
+#[test]
+fn test_memory_init() {
+    let result = test_memory_init_help(false).unwrap();
+    assert_eq!(result, Some(Value::I32(i32::from_le_bytes(*b"worl"))));
+}
+
+#[test]
+fn test_memory_init_after_data_drop() {
+    let error = test_memory_init_help(true).unwrap_err();
+    assert!(error.contains("data segment"), "{error}");
+}
+
 fn test_load(load_op: OpCode, ty: ValueType, data: &[u8], addr: u32, offset: u32) -> Value {
     let arena = Bump::new();
     let mut module = WasmModule::new(&arena);
@@ -0,0 +1,257 @@
+use crate::wasi::{Errno, WasiDispatcher};
+use roc_wasm_module::Value;
+use std::fs;
+use std::path::PathBuf;
+
+const PREOPEN_FD: i32 = 3;
+const PTR_PATH: i32 = 0x100;
+const PTR_IOVS: i32 = 0x200;
+const PTR_DATA: i32 = 0x300;
+const PTR_OUT: i32 = 0x400;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("roc_wasm_interp_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn set_path(memory: &mut [u8], path: &str) -> Value {
+    memory[PTR_PATH as usize..][..path.len()].copy_from_slice(path.as_bytes());
+    Value::I32(path.len() as i32)
+}
+
+fn set_iov(memory: &mut [u8], len: usize) {
+    memory[PTR_IOVS as usize..][..4].copy_from_slice(&(PTR_DATA as u32).to_le_bytes());
+    memory[PTR_IOVS as usize + 4..][..4].copy_from_slice(&(len as u32).to_le_bytes());
+}
+
+fn errno(e: Errno) -> Option<Value> {
+    Some(Value::I32(e as i32))
+}
+
+#[test]
+fn test_file_roundtrip() {
+    let dir = temp_dir("file_roundtrip");
+    let mut wasi = WasiDispatcher::default();
+    wasi.preopen_dir(&dir, "/data").unwrap();
+    let mut memory = vec![0; 0x1000];
+
+    // path_open with CREAT, and rights to read and write
+    let path_len = set_path(&mut memory, "hello.txt");
+    let rights = Value::I64((1 << 1) | (1 << 6));
+    let open_args = [
+        Value::I32(PREOPEN_FD),
+        Value::I32(0),
+        Value::I32(PTR_PATH),
+        path_len,
+        Value::I32(1),
+        rights,
+        rights,
+        Value::I32(0),
+        Value::I32(PTR_OUT),
+    ];
+    let result = wasi.dispatch("path_open", &open_args, &mut memory);
+    assert_eq!(result, errno(Errno::Success));
+    let fd = Value::I32(i32::from_le_bytes(
+        memory[PTR_OUT as usize..][..4].try_into().unwrap(),
+    ));
+
+    let text = b"Hello, WASI!";
+    memory[PTR_DATA as usize..][..text.len()].copy_from_slice(text);
+    set_iov(&mut memory, text.len());
+    let io_args = [fd, Value::I32(PTR_IOVS), Value::I32(1), Value::I32(PTR_OUT)];
+    let result = wasi.dispatch("fd_write", &io_args, &mut memory);
+    assert_eq!(result, errno(Errno::Success));
+    assert_eq!(fs::read(dir.join("hello.txt")).unwrap(), text);
+
+    // Seek back to the start and read it again
+    let seek_args = [fd, Value::I64(0), Value::I32(0), Value::I32(PTR_OUT)];
+    let result = wasi.dispatch("fd_seek", &seek_args, &mut memory);
+    assert_eq!(result, errno(Errno::Success));
+
+    memory[PTR_DATA as usize..][..text.len()].fill(0);
+    let result = wasi.dispatch("fd_read", &io_args, &mut memory);
+    assert_eq!(result, errno(Errno::Success));
+    assert_eq!(
+        &memory[PTR_OUT as usize..][..4],
+        &(text.len() as u32).to_le_bytes()
+    );
+    assert_eq!(&memory[PTR_DATA as usize..][..text.len()], text);
+
+    let result = wasi.dispatch("fd_close", &[fd], &mut memory);
+    assert_eq!(result, errno(Errno::Success));
+    let result = wasi.dispatch("fd_close", &[fd], &mut memory);
+    assert_eq!(result, errno(Errno::Badf));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_path_sandbox() {
+    let dir = temp_dir("path_sandbox");
+    let mut wasi = WasiDispatcher::default();
+    wasi.preopen_dir(&dir, "/data").unwrap();
+    let mut memory = vec![0; 0x1000];
+
+    for (path, expected) in [
+        ("new_dir", Errno::Success),
+        ("new_dir/../other_dir", Errno::Success),
+        ("../escaped", Errno::Notcapable),
+        ("new_dir/../../escaped", Errno::Notcapable),
+        ("/tmp/escaped", Errno::Notcapable),
+    ] {
+        let path_len = set_path(&mut memory, path);
+        let args = [Value::I32(PREOPEN_FD), Value::I32(PTR_PATH), path_len];
+        let result = wasi.dispatch("path_create_directory", &args, &mut memory);
+        assert_eq!(result, errno(expected), "{path}");
+    }
+
+    assert!(dir.join("new_dir").is_dir());
+    assert!(dir.join("other_dir").is_dir());
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+#[cfg(unix)]
+fn test_symlink_sandbox() {
+    let dir = temp_dir("symlink_sandbox");
+    let outside = temp_dir("symlink_sandbox_outside");
+    fs::write(outside.join("secret.txt"), "secret").unwrap();
+    std::os::unix::fs::symlink(&outside, dir.join("escape")).unwrap();
+    std::os::unix::fs::symlink(outside.join("secret.txt"), dir.join("secret_link")).unwrap();
+
+    let mut wasi = WasiDispatcher::default();
+    wasi.preopen_dir(&dir, "/data").unwrap();
+    let mut memory = vec![0; 0x1000];
+
+    let open = |wasi: &mut WasiDispatcher, memory: &mut [u8], path: &str, lookup_flags: i32| {
+        let path_len = set_path(memory, path);
+        let args = [
+            Value::I32(PREOPEN_FD),
+            Value::I32(lookup_flags),
+            Value::I32(PTR_PATH),
+            path_len,
+            Value::I32(1),
+            Value::I64(1 << 1),
+            Value::I64(0),
+            Value::I32(0),
+            Value::I32(PTR_OUT),
+        ];
+        wasi.dispatch("path_open", &args, memory)
+    };
+
+    for (path, lookup_flags, expected) in [
+        ("escape/secret.txt", 1, Errno::Notcapable),
+        ("escape/new.txt", 1, Errno::Notcapable),
+        ("secret_link", 1, Errno::Notcapable),
+        ("secret_link", 0, Errno::Loop),
+    ] {
+        let result = open(&mut wasi, &mut memory, path, lookup_flags);
+        assert_eq!(result, errno(expected), "{path}");
+    }
+    assert!(!outside.join("new.txt").exists());
+
+    // The app can still see and remove the symlinks themselves
+    let path_len = set_path(&mut memory, "secret_link");
+    let args = [
+        Value::I32(PREOPEN_FD),
+        Value::I32(PTR_PATH),
+        path_len,
+        Value::I32(PTR_DATA),
+        Value::I32(0x100),
+        Value::I32(PTR_OUT),
+    ];
+    let result = wasi.dispatch("path_readlink", &args, &mut memory);
+    assert_eq!(result, errno(Errno::Success));
+    let target = outside.join("secret.txt");
+    let target = target.to_str().unwrap();
+    assert_eq!(
+        &memory[PTR_OUT as usize..][..4],
+        &(target.len() as u32).to_le_bytes()
+    );
+    assert_eq!(
+        &memory[PTR_DATA as usize..][..target.len()],
+        target.as_bytes()
+    );
+
+    let args = [Value::I32(PREOPEN_FD), Value::I32(PTR_PATH), path_len];
+    let result = wasi.dispatch("path_unlink_file", &args, &mut memory);
+    assert_eq!(result, errno(Errno::Success));
+    assert!(outside.join("secret.txt").exists());
+
+    fs::remove_dir_all(dir).unwrap();
+    fs::remove_dir_all(outside).unwrap();
+}
+
+#[test]
+fn test_unsupported_syscalls() {
+    let mut wasi = WasiDispatcher::default();
+    let mut memory = vec![0; 0x1000];
+
+    let stdout = [Value::I32(1), Value::I32(0), Value::I32(0)];
+    let result = wasi.dispatch("sock_shutdown", &stdout, &mut memory);
+    assert_eq!(result, errno(Errno::Notsock));
+    let result = wasi.dispatch("fd_fdstat_set_flags", &stdout, &mut memory);
+    assert_eq!(result, errno(Errno::Notsup));
+    let result = wasi.dispatch("proc_raise", &[Value::I32(6)], &mut memory);
+    assert_eq!(result, errno(Errno::Nosys));
+
+    let closed = [Value::I32(42), Value::I32(0), Value::I32(0)];
+    let result = wasi.dispatch("sock_shutdown", &closed, &mut memory);
+    assert_eq!(result, errno(Errno::Badf));
+
+    // Move stderr onto stdout
+    let args = [Value::I32(2), Value::I32(1)];
+    let result = wasi.dispatch("fd_renumber", &args, &mut memory);
+    assert_eq!(result, errno(Errno::Success));
+    let result = wasi.dispatch("fd_close", &[Value::I32(2)], &mut memory);
+    assert_eq!(result, errno(Errno::Badf));
+}
+
+#[test]
+fn test_prestat() {
+    let dir = temp_dir("prestat");
+    let mut wasi = WasiDispatcher::default();
+    wasi.preopen_dir(&dir, "/data").unwrap();
+    let mut memory = vec![0; 0x1000];
+
+    let args = [Value::I32(PREOPEN_FD), Value::I32(PTR_OUT)];
+    let result = wasi.dispatch("fd_prestat_get", &args, &mut memory);
+    assert_eq!(result, errno(Errno::Success));
+    assert_eq!(&memory[PTR_OUT as usize + 4..][..4], &5u32.to_le_bytes());
+
+    let args = [Value::I32(PREOPEN_FD), Value::I32(PTR_PATH), Value::I32(5)];
+    let result = wasi.dispatch("fd_prestat_dir_name", &args, &mut memory);
+    assert_eq!(result, errno(Errno::Success));
+    assert_eq!(&memory[PTR_PATH as usize..][..5], b"/data");
+
+    // wasi-libc stops looking for preopens at the first Badf
+    let args = [Value::I32(PREOPEN_FD + 1), Value::I32(PTR_OUT)];
+    let result = wasi.dispatch("fd_prestat_get", &args, &mut memory);
+    assert_eq!(result, errno(Errno::Badf));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_clock_time_get() {
+    let mut wasi = WasiDispatcher::default();
+    let mut memory = vec![0; 0x1000];
+
+    let read_time =
+        |memory: &[u8]| u64::from_le_bytes(memory[PTR_OUT as usize..][..8].try_into().unwrap());
+
+    let args = [Value::I32(1), Value::I64(1), Value::I32(PTR_OUT)];
+    wasi.dispatch("clock_time_get", &args, &mut memory);
+    let first = read_time(&memory);
+    wasi.dispatch("clock_time_get", &args, &mut memory);
+    let second = read_time(&memory);
+    assert!(second >= first);
+
+    // realtime is nanoseconds since 1970, so it's well past 2020
+    let args = [Value::I32(0), Value::I64(1), Value::I32(PTR_OUT)];
+    wasi.dispatch("clock_time_get", &args, &mut memory);
+    assert!(read_time(&memory) > 1_577_836_800_000_000_000);
+}
@@ -108,8 +108,14 @@ impl<'a> ValueStore<'a> {
         self.values.iter()
     }
 
-    pub(crate) fn truncate(&mut self, depth: usize) {
-        self.values.truncate(depth)
+    /// Truncate to `depth`, but keep the top `n_kept` values, moving them down to `depth`.
+    /// Used when breaking out of a block or returning from a function with results.
+    pub(crate) fn truncate_keeping(&mut self, depth: usize, n_kept: usize) {
+        let kept_start = self.values.len() - n_kept;
+        if kept_start != depth {
+            self.values.copy_within(kept_start.., depth);
+        }
+        self.values.truncate(depth + n_kept)
     }
 
     pub(crate) fn get_slice(&mut self, from: usize) -> &[Value] {
@@ -1,14 +1,58 @@
 use rand::prelude::*;
 use roc_wasm_module::Value;
-use std::io::{self, Read, StderrLock, StdoutLock, Write};
+use std::fs::{self, File, Metadata, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, StderrLock, StdoutLock, Write};
+use std::path::{Component, Path, PathBuf};
 use std::process::exit;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 pub const MODULE_NAME: &str = "wasi_snapshot_preview1";
 
+const CLOCKID_REALTIME: i32 = 0;
+const CLOCKID_MONOTONIC: i32 = 1;
+const CLOCKID_PROCESS_CPUTIME_ID: i32 = 2;
+const CLOCKID_THREAD_CPUTIME_ID: i32 = 3;
+
+const FILETYPE_UNKNOWN: u8 = 0;
+const FILETYPE_CHARACTER_DEVICE: u8 = 2;
+const FILETYPE_DIRECTORY: u8 = 3;
+const FILETYPE_REGULAR_FILE: u8 = 4;
+const FILETYPE_SYMBOLIC_LINK: u8 = 7;
+
+const OFLAGS_CREAT: i32 = 1 << 0;
+const OFLAGS_DIRECTORY: i32 = 1 << 1;
+const OFLAGS_EXCL: i32 = 1 << 2;
+const OFLAGS_TRUNC: i32 = 1 << 3;
+
+const FDFLAGS_APPEND: i32 = 1 << 0;
+
+const LOOKUPFLAGS_SYMLINK_FOLLOW: i32 = 1 << 0;
+
+const RIGHTS_FD_READ: i64 = 1 << 1;
+const RIGHTS_FD_WRITE: i64 = 1 << 6;
+const RIGHTS_ALL: i64 = (1 << 29) - 1;
+
+const PREOPENTYPE_DIR: u8 = 0;
+
+const EVENTTYPE_CLOCK: u8 = 0;
+const SUBCLOCKFLAGS_SUBSCRIPTION_CLOCK_ABSTIME: u16 = 1 << 0;
+
+/// Size of a `filestat` struct in Wasm memory
+const FILESTAT_SIZE: usize = 64;
+/// Size of a `dirent` header in Wasm memory. The entry's name follows it.
+const DIRENT_SIZE: usize = 24;
+/// Size of a `subscription` struct in Wasm memory
+const SUBSCRIPTION_SIZE: usize = 48;
+/// Size of an `event` struct in Wasm memory
+const EVENT_SIZE: usize = 32;
+
 pub struct WasiDispatcher<'a> {
     pub args: &'a [&'a [u8]],
     pub rng: ThreadRng,
     pub files: Vec<WasiFile>,
+    /// Reference point for the monotonic clock
+    clock_start: Instant,
 }
 
 impl Default for WasiDispatcher<'_> {
@@ -22,12 +66,24 @@ pub enum WasiFile {
     WriteOnly(Vec<u8>),
     ReadWrite(Vec<u8>),
     HostSystemFile,
+    /// A file on the host, opened with `path_open`
+    HostFile(File),
+    /// A directory on the host. The app can only reach paths inside `sandbox_root`.
+    Directory {
+        host_path: PathBuf,
+        sandbox_root: PathBuf,
+        /// The name the app sees for a preopened directory (`None` if opened with `path_open`)
+        guest_path: Option<String>,
+    },
+    /// A file descriptor that has been closed, and can be reused by `path_open`
+    Closed,
 }
 
 enum WriteLock<'a> {
     StdOut(StdoutLock<'a>),
     Stderr(StderrLock<'a>),
     RegularFile(&'a mut Vec<u8>),
+    HostFile(&'a mut File),
 }
 
 /// Implementation of WASI syscalls
@@ -44,9 +100,30 @@ impl<'a> WasiDispatcher<'a> {
                 WasiFile::HostSystemFile,
                 WasiFile::HostSystemFile,
             ],
+            clock_start: Instant::now(),
         }
     }
 
+    /// Give the app access to the host directory `host_path`, under the name `guest_path`.
+    /// Everything the app opens through it must stay inside `host_path`.
+    pub fn preopen_dir(&mut self, host_path: &Path, guest_path: &str) -> io::Result<()> {
+        let host_path = host_path.canonicalize()?;
+        if !host_path.is_dir() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is not a directory", host_path.display()),
+            ));
+        }
+
+        self.files.push(WasiFile::Directory {
+            sandbox_root: host_path.clone(),
+            host_path,
+            guest_path: Some(guest_path.to_string()),
+        });
+
+        Ok(())
+    }
+
     pub fn dispatch(
         &mut self,
         function_name: &str,
@@ -107,12 +184,73 @@ impl<'a> WasiDispatcher<'a> {
 
                 success_code
             }
-            "clock_res_get" => success_code, // this dummy implementation seems to be good enough for some functions
-            "clock_time_get" => success_code,
-            "fd_advise" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_allocate" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_close" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_datasync" => todo!("WASI {}({:?})", function_name, arguments),
+            "clock_res_get" => {
+                let clock_id = arguments[0].expect_i32().unwrap();
+                // Out param: the resolution of the clock, in nanoseconds
+                let ptr_resolution = arguments[1].expect_i32().unwrap() as usize;
+
+                let result = self.clock_time(clock_id).map(|_| {
+                    write_u64(memory, ptr_resolution, 1);
+                });
+
+                errno_result(result)
+            }
+            "clock_time_get" => {
+                let clock_id = arguments[0].expect_i32().unwrap();
+                // arguments[1] is the maximum lag the app will accept. We always give the exact time.
+                // Out param: the time value of the clock, in nanoseconds
+                let ptr_time = arguments[2].expect_i32().unwrap() as usize;
+
+                let result = self.clock_time(clock_id).map(|time| {
+                    write_u64(memory, ptr_time, time);
+                });
+
+                errno_result(result)
+            }
+            "fd_advise" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                // arguments[1..4] are the offset, length and kind of access the app expects.
+                // They are only a hint, and we don't need one.
+
+                errno_result(self.host_file(fd, Errno::Spipe).map(|_| ()))
+            }
+            "fd_allocate" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let offset = arguments[1].expect_i64().unwrap() as u64;
+                let len = arguments[2].expect_i64().unwrap() as u64;
+
+                // Make sure the file is at least `offset + len` bytes long
+                let result = self.host_file(fd, Errno::Spipe).and_then(|file| {
+                    let end = offset.checked_add(len).ok_or(Errno::Fbig)?;
+                    let size = file.metadata().map_err(errno_from_io)?.len();
+                    if size < end {
+                        file.set_len(end).map_err(errno_from_io)?;
+                    }
+                    Ok(())
+                });
+
+                errno_result(result)
+            }
+            "fd_close" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+
+                match self.files.get_mut(fd) {
+                    Some(WasiFile::Closed) | None => Some(Value::I32(Errno::Badf as i32)),
+                    Some(file) => {
+                        *file = WasiFile::Closed;
+                        success_code
+                    }
+                }
+            }
+            "fd_datasync" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+
+                let result = self
+                    .host_file(fd, Errno::Inval)
+                    .and_then(|file| file.sync_data().map_err(errno_from_io));
+
+                errno_result(result)
+            }
             "fd_fdstat_get" => {
                 // (i32, i32) -> i32
 
@@ -121,9 +259,20 @@ impl<'a> WasiDispatcher<'a> {
                 // ptr to a wasi_fdstat_t
                 let stat_mut_ptr = arguments[1].expect_i32().unwrap() as usize;
 
-                match fd {
-                    1 => {
-                        // Tell WASI that stdout is a tty (no seek or tell)
+                // struct fdstat {
+                //     filetype: u8,           // offset 0
+                //     flags: u16,             // offset 2
+                //     rights_base: u64,       // offset 8
+                //     rights_inheriting: u64, // offset 16
+                // }
+                let (filetype, rights_base, rights_inheriting) = match self.files.get(fd) {
+                    Some(
+                        WasiFile::HostSystemFile
+                        | WasiFile::ReadOnly(_)
+                        | WasiFile::WriteOnly(_)
+                        | WasiFile::ReadWrite(_),
+                    ) => {
+                        // Tell WASI that stdio is a tty (no seek or tell)
                         // https://github.com/WebAssembly/wasi-libc/blob/659ff414560721b1660a19685110e484a081c3d4/libc-bottom-half/sources/isatty.c
                         // *Not* a tty if:
                         //     (statbuf.fs_filetype != __WASI_FILETYPE_CHARACTER_DEVICE ||
@@ -131,25 +280,92 @@ impl<'a> WasiDispatcher<'a> {
                         // So it's sufficient to set:
                         //     .fs_filetype = __WASI_FILETYPE_CHARACTER_DEVICE
                         //     .fs_rights_base = 0
+                        (FILETYPE_CHARACTER_DEVICE, 0, 0)
+                    }
+                    Some(WasiFile::HostFile(_)) => (FILETYPE_REGULAR_FILE, RIGHTS_ALL, 0),
+                    Some(WasiFile::Directory { .. }) => {
+                        (FILETYPE_DIRECTORY, RIGHTS_ALL, RIGHTS_ALL)
+                    }
+                    Some(WasiFile::Closed) | None => return Some(Value::I32(Errno::Badf as i32)),
+                };
 
-                        const WASI_FILETYPE_CHARACTER_DEVICE: u8 = 2;
-                        memory[stat_mut_ptr] = WASI_FILETYPE_CHARACTER_DEVICE;
+                memory[stat_mut_ptr..][..24].fill(0);
+                memory[stat_mut_ptr] = filetype;
+                write_u64(memory, stat_mut_ptr + 8, rights_base as u64);
+                write_u64(memory, stat_mut_ptr + 16, rights_inheriting as u64);
 
-                        for b in memory[stat_mut_ptr + 1..stat_mut_ptr + 24].iter_mut() {
-                            *b = 0;
-                        }
+                success_code
+            }
+            "fd_fdstat_set_flags" | "fd_fdstat_set_rights" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+
+                // The host can't change the flags of a file that is already open, and we don't
+                // restrict rights, so there is nothing to drop.
+                match self.files.get(fd) {
+                    Some(WasiFile::Closed) | None => Some(Value::I32(Errno::Badf as i32)),
+                    Some(_) => Some(Value::I32(Errno::Notsup as i32)),
+                }
+            }
+            "fd_filestat_get" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                // Out param: the file's attributes
+                let ptr_filestat = arguments[1].expect_i32().unwrap() as usize;
+
+                let result = match self.files.get(fd) {
+                    Some(WasiFile::HostFile(file)) => file.metadata().map_err(errno_from_io),
+                    Some(WasiFile::Directory { host_path, .. }) => {
+                        fs::metadata(host_path).map_err(errno_from_io)
                     }
-                    _ => todo!("WASI {}({:?})", function_name, arguments),
+                    Some(WasiFile::Closed) | None => Err(Errno::Badf),
+                    Some(_) => {
+                        // stdio and in-memory files have no metadata on the host
+                        memory[ptr_filestat..][..FILESTAT_SIZE].fill(0);
+                        memory[ptr_filestat + 16] = FILETYPE_CHARACTER_DEVICE;
+                        return success_code;
+                    }
+                };
+
+                errno_result(result.map(|metadata| write_filestat(memory, ptr_filestat, &metadata)))
+            }
+            "fd_filestat_set_size" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let size = arguments[1].expect_i64().unwrap() as u64;
+
+                let result = self
+                    .host_file(fd, Errno::Inval)
+                    .and_then(|file| file.set_len(size).map_err(errno_from_io));
+
+                errno_result(result)
+            }
+            "fd_filestat_set_times" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+
+                // Our Rust version can't set file times without another dependency
+                match self.files.get(fd) {
+                    Some(WasiFile::Closed) | None => Some(Value::I32(Errno::Badf as i32)),
+                    Some(_) => Some(Value::I32(Errno::Notsup as i32)),
                 }
+            }
+            "fd_pread" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let ptr_iovs = arguments[1].expect_i32().unwrap() as usize;
+                let iovs_len = arguments[2].expect_i32().unwrap();
+                let offset = arguments[3].expect_i64().unwrap() as u64;
+                // Out param: number of bytes read
+                let ptr_nread = arguments[4].expect_i32().unwrap() as usize;
 
-                success_code
+                // Read at the offset, without moving the file's own position
+                let result = self.host_file(fd, Errno::Spipe).and_then(|file| {
+                    let position = file.stream_position().map_err(errno_from_io)?;
+                    file.seek(SeekFrom::Start(offset)).map_err(errno_from_io)?;
+                    let n_read = read_iovs(file, memory, ptr_iovs, iovs_len);
+                    file.seek(SeekFrom::Start(position))
+                        .map_err(errno_from_io)?;
+                    n_read
+                });
+
+                errno_result(result.map(|n_read| write_u32(memory, ptr_nread, n_read as u32)))
             }
-            "fd_fdstat_set_flags" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_fdstat_set_rights" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_filestat_get" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_filestat_set_size" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_filestat_set_times" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_pread" => todo!("WASI {}({:?})", function_name, arguments),
             "fd_prestat_get" => {
                 // The preopened file descriptor to query
                 let fd = arguments[0].expect_i32().unwrap() as usize;
@@ -157,19 +373,67 @@ impl<'a> WasiDispatcher<'a> {
                 //  preopen type: 4 bytes, where 0=dir is the only one supported, it seems
                 //  preopen name length: 4 bytes
                 let ptr_buf = arguments[1].expect_i32().unwrap() as usize;
-                memory[ptr_buf..][..8].copy_from_slice(&0u64.to_le_bytes());
-                if fd < self.files.len() {
-                    success_code
-                } else {
-                    println!("WASI warning: file descriptor {fd} does not exist");
-                    Some(Value::I32(Errno::Badf as i32))
+
+                // wasi-libc calls this for every fd from 3 upwards, until it gets Badf
+                match self.files.get(fd) {
+                    Some(WasiFile::Directory {
+                        guest_path: Some(guest_path),
+                        ..
+                    }) => {
+                        memory[ptr_buf..][..8].fill(0);
+                        memory[ptr_buf] = PREOPENTYPE_DIR;
+                        write_u32(memory, ptr_buf + 4, guest_path.len() as u32);
+                        success_code
+                    }
+                    _ => Some(Value::I32(Errno::Badf as i32)),
                 }
             }
             "fd_prestat_dir_name" => {
-                // We're not giving names to any of our files so just return success
-                success_code
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                // Out param: the name of the preopened directory (not zero-terminated)
+                let ptr_path = arguments[1].expect_i32().unwrap() as usize;
+                let path_len = arguments[2].expect_i32().unwrap() as usize;
+
+                match self.files.get(fd) {
+                    Some(WasiFile::Directory {
+                        guest_path: Some(guest_path),
+                        ..
+                    }) => {
+                        let len = path_len.min(guest_path.len());
+                        memory[ptr_path..][..len].copy_from_slice(&guest_path.as_bytes()[..len]);
+                        success_code
+                    }
+                    _ => Some(Value::I32(Errno::Badf as i32)),
+                }
+            }
+            "fd_pwrite" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let ptr_iovs = arguments[1].expect_i32().unwrap() as usize;
+                let iovs_len = arguments[2].expect_i32().unwrap();
+                let offset = arguments[3].expect_i64().unwrap() as u64;
+                // Out param: number of bytes written
+                let ptr_nwritten = arguments[4].expect_i32().unwrap() as usize;
+
+                // Write at the offset, without moving the file's own position
+                let result = self.host_file(fd, Errno::Spipe).and_then(|file| {
+                    let position = file.stream_position().map_err(errno_from_io)?;
+                    file.seek(SeekFrom::Start(offset)).map_err(errno_from_io)?;
+                    let mut n_written = 0;
+                    for i in 0..iovs_len as usize {
+                        let (iov_base, iov_len) = read_iov(memory, ptr_iovs, i);
+                        file.write_all(&memory[iov_base..][..iov_len])
+                            .map_err(errno_from_io)?;
+                        n_written += iov_len;
+                    }
+                    file.seek(SeekFrom::Start(position))
+                        .map_err(errno_from_io)?;
+                    Ok(n_written)
+                });
+
+                errno_result(
+                    result.map(|n_written| write_u32(memory, ptr_nwritten, n_written as u32)),
+                )
             }
-            "fd_pwrite" => todo!("WASI {}({:?})", function_name, arguments),
             "fd_read" => {
                 use WasiFile::*;
 
@@ -189,11 +453,10 @@ impl<'a> WasiDispatcher<'a> {
                 // };
 
                 let mut n_read: usize = 0;
-                match self.files.get(fd) {
+                match self.files.get_mut(fd) {
                     Some(ReadOnly(content) | ReadWrite(content)) => {
-                        for _ in 0..iovs_len {
-                            let iov_base = read_u32(memory, ptr_iovs) as usize;
-                            let iov_len = read_i32(memory, ptr_iovs + 4) as usize;
+                        for i in 0..iovs_len as usize {
+                            let (iov_base, iov_len) = read_iov(memory, ptr_iovs, i);
                             let remaining = content.len() - n_read;
                             let len = remaining.min(iov_len);
                             if len == 0 {
@@ -205,9 +468,8 @@ impl<'a> WasiDispatcher<'a> {
                     }
                     Some(HostSystemFile) if fd == 0 => {
                         let mut stdin = io::stdin();
-                        for _ in 0..iovs_len {
-                            let iov_base = read_u32(memory, ptr_iovs) as usize;
-                            let iov_len = read_i32(memory, ptr_iovs + 4) as usize;
+                        for i in 0..iovs_len as usize {
+                            let (iov_base, iov_len) = read_iov(memory, ptr_iovs, i);
                             match stdin.read(&mut memory[iov_base..][..iov_len]) {
                                 Ok(n) => {
                                     n_read += n;
@@ -218,17 +480,128 @@ impl<'a> WasiDispatcher<'a> {
                             }
                         }
                     }
+                    Some(HostFile(file)) => match read_iovs(file, memory, ptr_iovs, iovs_len) {
+                        Ok(n) => n_read = n,
+                        Err(errno) => return Some(Value::I32(errno as i32)),
+                    },
+                    Some(Directory { .. }) => return Some(Value::I32(Errno::Isdir as i32)),
                     _ => return Some(Value::I32(Errno::Badf as i32)),
                 };
 
                 memory[ptr_nread..][..4].copy_from_slice(&(n_read as u32).to_le_bytes());
                 success_code
             }
-            "fd_readdir" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_renumber" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_seek" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_sync" => todo!("WASI {}({:?})", function_name, arguments),
-            "fd_tell" => todo!("WASI {}({:?})", function_name, arguments),
+            "fd_readdir" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                // Buffer where directory entries are stored
+                let ptr_buf = arguments[1].expect_i32().unwrap() as usize;
+                let buf_len = arguments[2].expect_i32().unwrap() as usize;
+                // Location within the directory to start reading
+                let cookie = arguments[3].expect_i64().unwrap() as usize;
+                // Out param: number of bytes stored in the buffer.
+                // If less than the size of the buffer, the end of the directory has been reached.
+                let ptr_bufused = arguments[4].expect_i32().unwrap() as usize;
+
+                let host_path = match self.files.get(fd) {
+                    Some(WasiFile::Directory { host_path, .. }) => host_path,
+                    Some(WasiFile::Closed) | None => return Some(Value::I32(Errno::Badf as i32)),
+                    Some(_) => return Some(Value::I32(Errno::Notdir as i32)),
+                };
+
+                let mut entries = match read_dir_sorted(host_path) {
+                    Ok(entries) => entries,
+                    Err(errno) => return Some(Value::I32(errno as i32)),
+                };
+
+                // Every directory has these, but the host's `read_dir` leaves them out
+                entries.insert(0, (String::from(".."), FILETYPE_DIRECTORY));
+                entries.insert(0, (String::from("."), FILETYPE_DIRECTORY));
+
+                // struct dirent {
+                //     d_next: u64,   // offset 0, the cookie of the next entry
+                //     d_ino: u64,    // offset 8
+                //     d_namlen: u32, // offset 16
+                //     d_type: u8,    // offset 20
+                // }
+                // The name follows each dirent. Both may be truncated at the end of the buffer.
+                let mut buf_used = 0;
+                for (index, (name, filetype)) in entries.iter().enumerate().skip(cookie) {
+                    let mut dirent = [0; DIRENT_SIZE];
+                    dirent[0..8].copy_from_slice(&(index as u64 + 1).to_le_bytes());
+                    dirent[16..20].copy_from_slice(&(name.len() as u32).to_le_bytes());
+                    dirent[20] = *filetype;
+
+                    for byte in dirent.iter().chain(name.as_bytes()) {
+                        if buf_used == buf_len {
+                            break;
+                        }
+                        memory[ptr_buf + buf_used] = *byte;
+                        buf_used += 1;
+                    }
+                }
+
+                write_u32(memory, ptr_bufused, buf_used as u32);
+                success_code
+            }
+            "fd_renumber" => {
+                let from_fd = arguments[0].expect_i32().unwrap() as usize;
+                let to_fd = arguments[1].expect_i32().unwrap() as usize;
+
+                // Move `from_fd` to `to_fd`, closing whatever was open there
+                let is_open = |fd| !matches!(self.files.get(fd), Some(WasiFile::Closed) | None);
+                if !is_open(from_fd) || !is_open(to_fd) {
+                    return Some(Value::I32(Errno::Badf as i32));
+                }
+
+                if from_fd != to_fd {
+                    let file = std::mem::replace(&mut self.files[from_fd], WasiFile::Closed);
+                    self.files[to_fd] = file;
+                }
+
+                success_code
+            }
+            "fd_seek" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                // The number of bytes to move
+                let offset = arguments[1].expect_i64().unwrap();
+                // The base from which the offset is relative
+                let whence = arguments[2].expect_i32().unwrap();
+                // Out param: the new offset of the file descriptor, relative to the start of the file
+                let ptr_newoffset = arguments[3].expect_i32().unwrap() as usize;
+
+                let seek_from = match whence {
+                    0 => SeekFrom::Start(offset as u64),
+                    1 => SeekFrom::Current(offset),
+                    2 => SeekFrom::End(offset),
+                    _ => return Some(Value::I32(Errno::Inval as i32)),
+                };
+
+                let result = self
+                    .host_file(fd, Errno::Spipe)
+                    .and_then(|file| file.seek(seek_from).map_err(errno_from_io));
+
+                errno_result(result.map(|position| write_u64(memory, ptr_newoffset, position)))
+            }
+            "fd_sync" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+
+                let result = self
+                    .host_file(fd, Errno::Inval)
+                    .and_then(|file| file.sync_all().map_err(errno_from_io));
+
+                errno_result(result)
+            }
+            "fd_tell" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                // Out param: the current offset of the file descriptor, relative to the start of the file
+                let ptr_offset = arguments[1].expect_i32().unwrap() as usize;
+
+                let result = self
+                    .host_file(fd, Errno::Spipe)
+                    .and_then(|file| file.stream_position().map_err(errno_from_io));
+
+                errno_result(result.map(|position| write_u64(memory, ptr_offset, position)))
+            }
             "fd_write" => {
                 use WasiFile::*;
 
@@ -252,6 +625,8 @@ impl<'a> WasiDispatcher<'a> {
                     Some(WriteOnly(content) | ReadWrite(content)) => {
                         WriteLock::RegularFile(content)
                     }
+                    Some(HostFile(file)) => WriteLock::HostFile(file),
+                    Some(Directory { .. }) => return Some(Value::I32(Errno::Isdir as i32)),
                     _ => return Some(Value::I32(Errno::Badf as i32)),
                 };
 
@@ -280,6 +655,7 @@ impl<'a> WasiDispatcher<'a> {
                         WriteLock::StdOut(stdout) => stdout.write_all(bytes),
                         WriteLock::Stderr(stderr) => stderr.write_all(bytes),
                         WriteLock::RegularFile(content) => content.write_all(bytes),
+                        WriteLock::HostFile(file) => file.write_all(bytes),
                     };
                     if write_result.is_err() {
                         break;
@@ -297,26 +673,217 @@ impl<'a> WasiDispatcher<'a> {
 
                 match write_result {
                     Ok(()) => success_code,
-                    Err(_) => Some(Value::I32(Errno::Io as i32)),
+                    Err(e) => Some(Value::I32(errno_from_io(e) as i32)),
                 }
             }
-            "path_create_directory" => todo!("WASI {}({:?})", function_name, arguments),
-            "path_filestat_get" => todo!("WASI {}({:?})", function_name, arguments),
-            "path_filestat_set_times" => todo!("WASI {}({:?})", function_name, arguments),
-            "path_link" => todo!("WASI {}({:?})", function_name, arguments),
-            "path_open" => todo!("WASI {}({:?})", function_name, arguments),
-            "path_readlink" => todo!("WASI {}({:?})", function_name, arguments),
-            "path_remove_directory" => todo!("WASI {}({:?})", function_name, arguments),
-            "path_rename" => todo!("WASI {}({:?})", function_name, arguments),
-            "path_symlink" => todo!("WASI {}({:?})", function_name, arguments),
-            "path_unlink_file" => todo!("WASI {}({:?})", function_name, arguments),
-            "poll_oneoff" => todo!("WASI {}({:?})", function_name, arguments),
+            "path_create_directory" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let ptr_path = arguments[1].expect_i32().unwrap() as usize;
+                let path_len = arguments[2].expect_i32().unwrap() as usize;
+
+                let result = self
+                    .resolve_path(fd, memory, ptr_path, path_len, false)
+                    .and_then(|path| fs::create_dir(path).map_err(errno_from_io));
+
+                errno_result(result)
+            }
+            "path_filestat_get" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let lookup_flags = arguments[1].expect_i32().unwrap();
+                let ptr_path = arguments[2].expect_i32().unwrap() as usize;
+                let path_len = arguments[3].expect_i32().unwrap() as usize;
+                // Out param: the file's attributes
+                let ptr_filestat = arguments[4].expect_i32().unwrap() as usize;
+
+                let follow_symlinks = lookup_flags & LOOKUPFLAGS_SYMLINK_FOLLOW != 0;
+
+                let result = self
+                    .resolve_path(fd, memory, ptr_path, path_len, follow_symlinks)
+                    .and_then(|path| {
+                        if follow_symlinks {
+                            fs::metadata(path)
+                        } else {
+                            fs::symlink_metadata(path)
+                        }
+                        .map_err(errno_from_io)
+                    });
+
+                errno_result(result.map(|metadata| write_filestat(memory, ptr_filestat, &metadata)))
+            }
+            "path_filestat_set_times" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+
+                // See `fd_filestat_set_times`
+                match self.files.get(fd) {
+                    Some(WasiFile::Directory { .. }) => Some(Value::I32(Errno::Notsup as i32)),
+                    Some(WasiFile::Closed) | None => Some(Value::I32(Errno::Badf as i32)),
+                    Some(_) => Some(Value::I32(Errno::Notdir as i32)),
+                }
+            }
+            "path_link" => {
+                let old_fd = arguments[0].expect_i32().unwrap() as usize;
+                let old_lookup_flags = arguments[1].expect_i32().unwrap();
+                let ptr_old_path = arguments[2].expect_i32().unwrap() as usize;
+                let old_path_len = arguments[3].expect_i32().unwrap() as usize;
+                let new_fd = arguments[4].expect_i32().unwrap() as usize;
+                let ptr_new_path = arguments[5].expect_i32().unwrap() as usize;
+                let new_path_len = arguments[6].expect_i32().unwrap() as usize;
+
+                let follow_symlinks = old_lookup_flags & LOOKUPFLAGS_SYMLINK_FOLLOW != 0;
+
+                let result = self
+                    .resolve_path(old_fd, memory, ptr_old_path, old_path_len, follow_symlinks)
+                    .and_then(|old_path| {
+                        let old_path = if follow_symlinks {
+                            old_path.canonicalize().map_err(errno_from_io)?
+                        } else {
+                            old_path
+                        };
+                        let new_path =
+                            self.resolve_path(new_fd, memory, ptr_new_path, new_path_len, false)?;
+                        fs::hard_link(old_path, new_path).map_err(errno_from_io)
+                    });
+
+                errno_result(result)
+            }
+            "path_open" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let lookup_flags = arguments[1].expect_i32().unwrap();
+                let ptr_path = arguments[2].expect_i32().unwrap() as usize;
+                let path_len = arguments[3].expect_i32().unwrap() as usize;
+                let oflags = arguments[4].expect_i32().unwrap();
+                let rights_base = arguments[5].expect_i64().unwrap();
+                // arguments[6] is the rights for fds opened from this one. We don't restrict those.
+                let fdflags = arguments[7].expect_i32().unwrap();
+                // Out param: the file descriptor of the opened file
+                let ptr_fd = arguments[8].expect_i32().unwrap() as usize;
+
+                let follow_symlinks = lookup_flags & LOOKUPFLAGS_SYMLINK_FOLLOW != 0;
+
+                let result = self
+                    .resolve_path(fd, memory, ptr_path, path_len, follow_symlinks)
+                    .and_then(|host_path| {
+                        // Like O_NOFOLLOW, refuse to open a symlink we were told not to follow
+                        if !follow_symlinks && host_path.is_symlink() {
+                            return Err(Errno::Loop);
+                        }
+
+                        let sandbox_root = match &self.files[fd] {
+                            WasiFile::Directory { sandbox_root, .. } => sandbox_root.clone(),
+                            _ => unreachable!("resolve_path only succeeds for directories"),
+                        };
+                        open_file(host_path, sandbox_root, oflags, rights_base, fdflags)
+                    })
+                    .map(|file| self.insert_file(file));
+
+                errno_result(result.map(|new_fd| write_u32(memory, ptr_fd, new_fd as u32)))
+            }
+            "path_readlink" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let ptr_path = arguments[1].expect_i32().unwrap() as usize;
+                let path_len = arguments[2].expect_i32().unwrap() as usize;
+                // Out param: the contents of the symlink, truncated to fit, with no zero terminator
+                let ptr_buf = arguments[3].expect_i32().unwrap() as usize;
+                let buf_len = arguments[4].expect_i32().unwrap() as usize;
+                // Out param: the number of bytes written to the buffer
+                let ptr_bufused = arguments[5].expect_i32().unwrap() as usize;
+
+                let result = self
+                    .resolve_path(fd, memory, ptr_path, path_len, false)
+                    .and_then(|path| fs::read_link(path).map_err(errno_from_io))
+                    .map(|target| {
+                        let target = target.to_string_lossy();
+                        let bytes = &target.as_bytes()[..target.len().min(buf_len)];
+                        memory[ptr_buf..][..bytes.len()].copy_from_slice(bytes);
+                        write_u32(memory, ptr_bufused, bytes.len() as u32);
+                    });
+
+                errno_result(result)
+            }
+            "path_remove_directory" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let ptr_path = arguments[1].expect_i32().unwrap() as usize;
+                let path_len = arguments[2].expect_i32().unwrap() as usize;
+
+                let result = self
+                    .resolve_path(fd, memory, ptr_path, path_len, false)
+                    .and_then(|path| fs::remove_dir(path).map_err(errno_from_io));
+
+                errno_result(result)
+            }
+            "path_rename" => {
+                let old_fd = arguments[0].expect_i32().unwrap() as usize;
+                let ptr_old_path = arguments[1].expect_i32().unwrap() as usize;
+                let old_path_len = arguments[2].expect_i32().unwrap() as usize;
+                let new_fd = arguments[3].expect_i32().unwrap() as usize;
+                let ptr_new_path = arguments[4].expect_i32().unwrap() as usize;
+                let new_path_len = arguments[5].expect_i32().unwrap() as usize;
+
+                let result = self
+                    .resolve_path(old_fd, memory, ptr_old_path, old_path_len, false)
+                    .and_then(|old_path| {
+                        let new_path =
+                            self.resolve_path(new_fd, memory, ptr_new_path, new_path_len, false)?;
+                        fs::rename(old_path, new_path).map_err(errno_from_io)
+                    });
+
+                errno_result(result)
+            }
+            "path_symlink" => {
+                // The contents of the new symlink. Where it leads is checked whenever it's followed.
+                let ptr_old_path = arguments[0].expect_i32().unwrap() as usize;
+                let old_path_len = arguments[1].expect_i32().unwrap() as usize;
+                let fd = arguments[2].expect_i32().unwrap() as usize;
+                let ptr_new_path = arguments[3].expect_i32().unwrap() as usize;
+                let new_path_len = arguments[4].expect_i32().unwrap() as usize;
+
+                let result = std::str::from_utf8(&memory[ptr_old_path..][..old_path_len])
+                    .map_err(|_| Errno::Ilseq)
+                    .and_then(|target| {
+                        let new_path =
+                            self.resolve_path(fd, memory, ptr_new_path, new_path_len, false)?;
+                        create_symlink(target, &new_path)
+                    });
+
+                errno_result(result)
+            }
+            "path_unlink_file" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+                let ptr_path = arguments[1].expect_i32().unwrap() as usize;
+                let path_len = arguments[2].expect_i32().unwrap() as usize;
+
+                let result = self
+                    .resolve_path(fd, memory, ptr_path, path_len, false)
+                    .and_then(|path| fs::remove_file(path).map_err(errno_from_io));
+
+                errno_result(result)
+            }
+            "poll_oneoff" => {
+                // Array of subscriptions to wait for
+                let ptr_subscriptions = arguments[0].expect_i32().unwrap() as usize;
+                // Out param: array of events that have occurred
+                let ptr_events = arguments[1].expect_i32().unwrap() as usize;
+                let n_subscriptions = arguments[2].expect_i32().unwrap() as usize;
+                // Out param: number of events stored
+                let ptr_nevents = arguments[3].expect_i32().unwrap() as usize;
+
+                let result =
+                    self.poll_oneoff(memory, ptr_subscriptions, ptr_events, n_subscriptions);
+
+                errno_result(result.map(|n_events| write_u32(memory, ptr_nevents, n_events as u32)))
+            }
             "proc_exit" => {
                 let exit_code = arguments[0].expect_i32().unwrap();
                 exit(exit_code);
             }
-            "proc_raise" => todo!("WASI {}({:?})", function_name, arguments),
-            "sched_yield" => todo!("WASI {}({:?})", function_name, arguments),
+            "proc_raise" => {
+                // This was removed from later versions of WASI, and nothing we run uses it
+                Some(Value::I32(Errno::Nosys as i32))
+            }
+            "sched_yield" => {
+                thread::yield_now();
+                success_code
+            }
             "random_get" => {
                 // A pointer to a buffer where the random bytes will be written
                 let ptr_buf = arguments[0].expect_i32().unwrap() as usize;
@@ -327,12 +894,395 @@ impl<'a> WasiDispatcher<'a> {
                 }
                 success_code
             }
-            "sock_recv" => todo!("WASI {}({:?})", function_name, arguments),
-            "sock_send" => todo!("WASI {}({:?})", function_name, arguments),
-            "sock_shutdown" => todo!("WASI {}({:?})", function_name, arguments),
+            "sock_recv" | "sock_send" | "sock_shutdown" => {
+                let fd = arguments[0].expect_i32().unwrap() as usize;
+
+                // There's no way to open a socket, so no file descriptor is one
+                match self.files.get(fd) {
+                    Some(WasiFile::Closed) | None => Some(Value::I32(Errno::Badf as i32)),
+                    Some(_) => Some(Value::I32(Errno::Notsock as i32)),
+                }
+            }
             _ => panic!("Unknown WASI function {function_name}({arguments:?})"),
         }
     }
+
+    /// The current time of a clock, in nanoseconds
+    fn clock_time(&self, clock_id: i32) -> Result<u64, Errno> {
+        match clock_id {
+            CLOCKID_REALTIME => SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|time| time.as_nanos() as u64)
+                .map_err(|_| Errno::Overflow),
+            CLOCKID_MONOTONIC | CLOCKID_PROCESS_CPUTIME_ID | CLOCKID_THREAD_CPUTIME_ID => {
+                // The interpreter runs on one thread, so CPU time is close enough to elapsed time
+                Ok(self.clock_start.elapsed().as_nanos() as u64)
+            }
+            _ => Err(Errno::Inval),
+        }
+    }
+
+    /// The host file behind a file descriptor, for syscalls that only make sense on real files.
+    /// Other open file descriptors get the `not_a_file` error.
+    fn host_file(&mut self, fd: usize, not_a_file: Errno) -> Result<&mut File, Errno> {
+        match self.files.get_mut(fd) {
+            Some(WasiFile::HostFile(file)) => Ok(file),
+            Some(WasiFile::Closed) | None => Err(Errno::Badf),
+            Some(_) => Err(not_a_file),
+        }
+    }
+
+    /// Add a newly-opened file, reusing a closed file descriptor if there is one
+    fn insert_file(&mut self, file: WasiFile) -> usize {
+        match self
+            .files
+            .iter()
+            .position(|f| matches!(f, WasiFile::Closed))
+        {
+            Some(fd) => {
+                self.files[fd] = file;
+                fd
+            }
+            None => {
+                self.files.push(file);
+                self.files.len() - 1
+            }
+        }
+    }
+
+    /// Resolve a path the app gave us, relative to one of its directory file descriptors.
+    /// The result must stay inside that directory's sandbox, so absolute paths are rejected,
+    /// and so is any `..` that would climb out of the sandbox.
+    /// Symlinks are resolved on the host to check where the path really leads. If the syscall
+    /// acts on a symlink itself rather than on its target, pass `follow_symlinks: false` so that
+    /// only the directories leading up to it are checked.
+    fn resolve_path(
+        &self,
+        dir_fd: usize,
+        memory: &[u8],
+        ptr_path: usize,
+        path_len: usize,
+        follow_symlinks: bool,
+    ) -> Result<PathBuf, Errno> {
+        let (dir_path, sandbox_root) = match self.files.get(dir_fd) {
+            Some(WasiFile::Directory {
+                host_path,
+                sandbox_root,
+                ..
+            }) => (host_path, sandbox_root),
+            Some(WasiFile::Closed) | None => return Err(Errno::Badf),
+            Some(_) => return Err(Errno::Notdir),
+        };
+
+        let guest_path =
+            std::str::from_utf8(&memory[ptr_path..][..path_len]).map_err(|_| Errno::Ilseq)?;
+
+        // Normalize lexically, since the path may not exist yet
+        let mut resolved = dir_path.clone();
+        for component in Path::new(guest_path).components() {
+            match component {
+                Component::Normal(name) => resolved.push(name),
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    if resolved == *sandbox_root {
+                        return Err(Errno::Notcapable);
+                    }
+                    resolved.pop();
+                }
+                Component::RootDir | Component::Prefix(_) => return Err(Errno::Notcapable),
+            }
+        }
+
+        if !resolved.starts_with(sandbox_root) {
+            return Err(Errno::Notcapable);
+        }
+
+        let checked = match resolved.parent() {
+            Some(parent) if !follow_symlinks && resolved != *sandbox_root => parent,
+            _ => &resolved,
+        };
+
+        if canonicalize_existing(checked)?.starts_with(sandbox_root) {
+            Ok(resolved)
+        } else {
+            Err(Errno::Notcapable)
+        }
+    }
+
+    /// Wait until at least one of the subscriptions has an event, and return the number of events.
+    /// File descriptors are always ready, since all our I/O is blocking.
+    fn poll_oneoff(
+        &self,
+        memory: &mut [u8],
+        ptr_subscriptions: usize,
+        ptr_events: usize,
+        n_subscriptions: usize,
+    ) -> Result<usize, Errno> {
+        if n_subscriptions == 0 {
+            return Err(Errno::Inval);
+        }
+
+        // struct subscription {
+        //     userdata: u64,   // offset 0
+        //     tag: u8,         // offset 8, 0=clock, 1=fd_read, 2=fd_write
+        //     // for clocks:
+        //     id: u32,         // offset 16
+        //     timeout: u64,    // offset 24
+        //     precision: u64,  // offset 32
+        //     flags: u16,      // offset 40
+        // }
+        // struct event {
+        //     userdata: u64,   // offset 0
+        //     error: u16,      // offset 8
+        //     type: u8,        // offset 10
+        //     // for fd_read and fd_write, bytes available and flags follow
+        // }
+        let mut fd_events = Vec::new();
+        let mut earliest_timeout: Option<(u64, Duration)> = None;
+
+        for i in 0..n_subscriptions {
+            let ptr_subscription = ptr_subscriptions + i * SUBSCRIPTION_SIZE;
+            let userdata = read_u64(memory, ptr_subscription);
+            let tag = memory[ptr_subscription + 8];
+
+            if tag == EVENTTYPE_CLOCK {
+                let clock_id = read_i32(memory, ptr_subscription + 16);
+                let timeout = read_u64(memory, ptr_subscription + 24);
+                let flags = read_u16(memory, ptr_subscription + 40);
+
+                let wait = if flags & SUBCLOCKFLAGS_SUBSCRIPTION_CLOCK_ABSTIME != 0 {
+                    timeout.saturating_sub(self.clock_time(clock_id)?)
+                } else {
+                    self.clock_time(clock_id)?;
+                    timeout
+                };
+                let wait = Duration::from_nanos(wait);
+
+                match earliest_timeout {
+                    Some((_, earliest)) if earliest <= wait => {}
+                    _ => earliest_timeout = Some((userdata, wait)),
+                }
+            } else {
+                fd_events.push((userdata, tag));
+            }
+        }
+
+        let events = if !fd_events.is_empty() {
+            fd_events
+        } else {
+            let (userdata, wait) = earliest_timeout.unwrap();
+            thread::sleep(wait);
+            vec![(userdata, EVENTTYPE_CLOCK)]
+        };
+
+        for (i, (userdata, event_type)) in events.iter().enumerate() {
+            let ptr_event = ptr_events + i * EVENT_SIZE;
+            memory[ptr_event..][..EVENT_SIZE].fill(0);
+            write_u64(memory, ptr_event, *userdata);
+            write_u16(memory, ptr_event + 8, Errno::Success as u16);
+            memory[ptr_event + 10] = *event_type;
+        }
+
+        Ok(events.len())
+    }
+}
+
+/// Open a file or directory on the host, with `path_open` flags
+fn open_file(
+    host_path: PathBuf,
+    sandbox_root: PathBuf,
+    oflags: i32,
+    rights_base: i64,
+    fdflags: i32,
+) -> Result<WasiFile, Errno> {
+    let create = oflags & OFLAGS_CREAT != 0;
+
+    if oflags & OFLAGS_DIRECTORY != 0 || (!create && host_path.is_dir()) {
+        if !host_path.is_dir() {
+            return Err(Errno::Notdir);
+        }
+
+        return Ok(WasiFile::Directory {
+            host_path,
+            sandbox_root,
+            guest_path: None,
+        });
+    }
+
+    let truncate = oflags & OFLAGS_TRUNC != 0;
+    let append = fdflags & FDFLAGS_APPEND != 0;
+    // The host won't create or truncate a file that isn't opened for writing
+    let write = rights_base & RIGHTS_FD_WRITE != 0 || create || truncate;
+    let read = rights_base & RIGHTS_FD_READ != 0 || !(write || append);
+
+    let file = OpenOptions::new()
+        .read(read)
+        .write(write)
+        .append(append)
+        .truncate(truncate)
+        .create(create)
+        .create_new(create && oflags & OFLAGS_EXCL != 0)
+        .open(host_path)
+        .map_err(errno_from_io)?;
+
+    Ok(WasiFile::HostFile(file))
+}
+
+#[cfg(unix)]
+fn create_symlink(target: &str, link: &Path) -> Result<(), Errno> {
+    std::os::unix::fs::symlink(target, link).map_err(errno_from_io)
+}
+
+#[cfg(not(unix))]
+fn create_symlink(_target: &str, _link: &Path) -> Result<(), Errno> {
+    // Windows needs to know whether the target is a file or a directory, and it may not exist yet
+    Err(Errno::Notsup)
+}
+
+/// Canonicalize the deepest part of `path` that exists, and add the rest back on.
+/// A dangling symlink could lead anywhere once its target is created, so it is rejected.
+fn canonicalize_existing(path: &Path) -> Result<PathBuf, Errno> {
+    let mut existing = path;
+    let mut missing = Vec::new();
+
+    loop {
+        match fs::symlink_metadata(existing) {
+            Ok(_) => break,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                missing.push(existing.file_name().ok_or(Errno::Noent)?);
+                existing = existing.parent().ok_or(Errno::Noent)?;
+            }
+            Err(e) => return Err(errno_from_io(e)),
+        }
+    }
+
+    let mut canonical = existing.canonicalize().map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => Errno::Notcapable,
+        _ => errno_from_io(e),
+    })?;
+    canonical.extend(missing.into_iter().rev());
+
+    Ok(canonical)
+}
+
+/// Read from a host file into an array of IO vectors, returning the number of bytes read
+fn read_iovs(
+    file: &mut File,
+    memory: &mut [u8],
+    ptr_iovs: usize,
+    iovs_len: i32,
+) -> Result<usize, Errno> {
+    let mut n_read = 0;
+    for i in 0..iovs_len as usize {
+        let (iov_base, iov_len) = read_iov(memory, ptr_iovs, i);
+        let n = file
+            .read(&mut memory[iov_base..][..iov_len])
+            .map_err(errno_from_io)?;
+        n_read += n;
+        if n < iov_len {
+            break;
+        }
+    }
+    Ok(n_read)
+}
+
+/// Read the base address and length of the IO vector at `index` in an array
+fn read_iov(memory: &[u8], ptr_iovs: usize, index: usize) -> (usize, usize) {
+    let ptr_iov = ptr_iovs + 8 * index;
+    let iov_base = read_u32(memory, ptr_iov) as usize;
+    let iov_len = read_u32(memory, ptr_iov + 4) as usize;
+    (iov_base, iov_len)
+}
+
+/// The entries of a host directory, with their WASI file types, sorted by name
+fn read_dir_sorted(path: &Path) -> Result<Vec<(String, u8)>, Errno> {
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(path).map_err(errno_from_io)? {
+        let entry = entry.map_err(errno_from_io)?;
+        let filetype = entry
+            .file_type()
+            .map_or(FILETYPE_UNKNOWN, |file_type| wasi_filetype(&file_type));
+        entries.push((entry.file_name().to_string_lossy().into_owned(), filetype));
+    }
+    entries.sort();
+    Ok(entries)
+}
+
+fn wasi_filetype(file_type: &fs::FileType) -> u8 {
+    if file_type.is_dir() {
+        FILETYPE_DIRECTORY
+    } else if file_type.is_file() {
+        FILETYPE_REGULAR_FILE
+    } else if file_type.is_symlink() {
+        FILETYPE_SYMBOLIC_LINK
+    } else {
+        FILETYPE_UNKNOWN
+    }
+}
+
+fn write_filestat(memory: &mut [u8], ptr_filestat: usize, metadata: &Metadata) {
+    // struct filestat {
+    //     dev: u64,      // offset 0
+    //     ino: u64,      // offset 8
+    //     filetype: u8,  // offset 16
+    //     nlink: u64,    // offset 24
+    //     size: u64,     // offset 32
+    //     atim: u64,     // offset 40
+    //     mtim: u64,     // offset 48
+    //     ctim: u64,     // offset 56
+    // }
+    let nanos = |time: io::Result<SystemTime>| {
+        time.ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |time| time.as_nanos() as u64)
+    };
+
+    memory[ptr_filestat..][..FILESTAT_SIZE].fill(0);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        write_u64(memory, ptr_filestat, metadata.dev());
+        write_u64(memory, ptr_filestat + 8, metadata.ino());
+        write_u64(memory, ptr_filestat + 24, metadata.nlink());
+    }
+    #[cfg(not(unix))]
+    write_u64(memory, ptr_filestat + 24, 1);
+
+    memory[ptr_filestat + 16] = wasi_filetype(&metadata.file_type());
+    write_u64(memory, ptr_filestat + 32, metadata.len());
+    write_u64(memory, ptr_filestat + 40, nanos(metadata.accessed()));
+    write_u64(memory, ptr_filestat + 48, nanos(metadata.modified()));
+    // The host's status change time isn't portable, so use the modification time
+    write_u64(memory, ptr_filestat + 56, nanos(metadata.modified()));
+}
+
+fn errno_from_io(error: io::Error) -> Errno {
+    match error.kind() {
+        io::ErrorKind::NotFound => Errno::Noent,
+        io::ErrorKind::PermissionDenied => Errno::Access,
+        io::ErrorKind::AlreadyExists => Errno::Exist,
+        io::ErrorKind::InvalidInput => Errno::Inval,
+        io::ErrorKind::Interrupted => Errno::Intr,
+        io::ErrorKind::BrokenPipe => Errno::Pipe,
+        io::ErrorKind::Unsupported => Errno::Notsup,
+        _ => Errno::Io,
+    }
+}
+
+/// The return value of a syscall whose results have already been written to memory
+fn errno_result(result: Result<(), Errno>) -> Option<Value> {
+    let errno = match result {
+        Ok(()) => Errno::Success,
+        Err(errno) => errno,
+    };
+    Some(Value::I32(errno as i32))
+}
+
+fn read_u16(memory: &[u8], addr: usize) -> u16 {
+    let mut bytes = [0; 2];
+    bytes.copy_from_slice(&memory[addr..][..2]);
+    u16::from_le_bytes(bytes)
 }
 
 fn read_u32(memory: &[u8], addr: usize) -> u32 {
@@ -347,6 +1297,16 @@ fn read_i32(memory: &[u8], addr: usize) -> i32 {
     i32::from_le_bytes(bytes)
 }
 
+fn read_u64(memory: &[u8], addr: usize) -> u64 {
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&memory[addr..][..8]);
+    u64::from_le_bytes(bytes)
+}
+
+fn write_u16(memory: &mut [u8], addr: usize, value: u16) {
+    memory[addr..][..2].copy_from_slice(&value.to_le_bytes());
+}
+
 fn write_u32(memory: &mut [u8], addr: usize, value: u32) {
     memory[addr..][..4].copy_from_slice(&value.to_le_bytes());
 }
@@ -355,6 +1315,10 @@ fn write_i32(memory: &mut [u8], addr: usize, value: i32) {
     memory[addr..][..4].copy_from_slice(&value.to_le_bytes());
 }
 
+fn write_u64(memory: &mut [u8], addr: usize, value: u64) {
+    memory[addr..][..8].copy_from_slice(&value.to_le_bytes());
+}
+
 /// Error codes returned by functions.
 /// Not all of these error codes are returned by the functions provided by this
 /// API; some are used in higher-level library layers, and others are provided
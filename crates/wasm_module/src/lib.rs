@@ -3,6 +3,8 @@ pub mod opcodes;
 pub mod parse;
 pub mod sections;
 pub mod serialize;
+pub mod validate;
+mod wat;
 
 use std::iter::repeat;
 
@@ -93,6 +95,7 @@ impl<'a> WasmModule<'a> {
         self.export.serialize(buffer);
         self.start.serialize(buffer);
         self.element.serialize(buffer);
+        self.data.serialize_data_count(buffer);
         self.code.serialize(buffer);
         self.data.serialize(buffer);
         self.names.serialize(buffer);
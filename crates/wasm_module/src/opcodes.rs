@@ -1,4 +1,4 @@
-use crate::Serialize;
+use crate::{SerialBuffer, Serialize, ValueType};
 
 use super::parse::{Parse, ParseError, SkipBytes};
 
@@ -421,6 +421,100 @@ impl TryFrom<u8> for MemoryInstruction {
     }
 }
 
+/// The saturating float-to-int truncations, which share the 0xFC prefix with the bulk memory
+/// instructions. We never generate them, but host code compiled by Zig or Clang can contain them.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TruncSatInstruction {
+    I32TruncSatF32S = 0,
+    I32TruncSatF32U = 1,
+    I32TruncSatF64S = 2,
+    I32TruncSatF64U = 3,
+    I64TruncSatF32S = 4,
+    I64TruncSatF32U = 5,
+    I64TruncSatF64S = 6,
+    I64TruncSatF64U = 7,
+}
+
+impl TruncSatInstruction {
+    /// The float type consumed and the integer type produced
+    pub fn signature(&self) -> (ValueType, ValueType) {
+        use TruncSatInstruction::*;
+        match self {
+            I32TruncSatF32S | I32TruncSatF32U => (ValueType::F32, ValueType::I32),
+            I32TruncSatF64S | I32TruncSatF64U => (ValueType::F64, ValueType::I32),
+            I64TruncSatF32S | I64TruncSatF32U => (ValueType::F32, ValueType::I64),
+            I64TruncSatF64S | I64TruncSatF64U => (ValueType::F64, ValueType::I64),
+        }
+    }
+}
+
+impl TryFrom<u8> for TruncSatInstruction {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        use TruncSatInstruction::*;
+        match value {
+            0 => Ok(I32TruncSatF32S),
+            1 => Ok(I32TruncSatF32U),
+            2 => Ok(I32TruncSatF64S),
+            3 => Ok(I32TruncSatF64U),
+            4 => Ok(I64TruncSatF32S),
+            5 => Ok(I64TruncSatF32U),
+            6 => Ok(I64TruncSatF64S),
+            7 => Ok(I64TruncSatF64U),
+            _ => Err(value),
+        }
+    }
+}
+
+/// The type of a `block`, `loop` or `if`, encoded as a signed LEB-128 (s33)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlockType {
+    /// No parameters or results
+    Empty,
+    /// No parameters and a single result
+    Value(ValueType),
+    /// Parameters and results from a signature in the Type section (multi-value proposal)
+    Signature(u32),
+}
+
+impl Parse<()> for BlockType {
+    fn parse(_: (), bytes: &[u8], cursor: &mut usize) -> Result<Self, ParseError> {
+        let byte = bytes[*cursor];
+        if byte == ValueType::VOID {
+            *cursor += 1;
+            Ok(BlockType::Empty)
+        } else if (ValueType::F64 as u8..=ValueType::I32 as u8).contains(&byte) {
+            *cursor += 1;
+            Ok(BlockType::Value(ValueType::from(byte)))
+        } else {
+            let start = *cursor;
+            match i64::parse((), bytes, cursor)? {
+                index if (0..=u32::MAX as i64).contains(&index) => {
+                    Ok(BlockType::Signature(index as u32))
+                }
+                _ => Err(ParseError {
+                    offset: start,
+                    message: format!("Invalid block type 0x{byte:02x}"),
+                }),
+            }
+        }
+    }
+}
+
+impl Serialize for BlockType {
+    fn serialize<T: SerialBuffer>(&self, buffer: &mut T) {
+        match self {
+            BlockType::Empty => buffer.append_u8(ValueType::VOID),
+            BlockType::Value(ty) => ty.serialize(buffer),
+            BlockType::Signature(index) => {
+                buffer.encode_i64(*index as i64);
+            }
+        }
+    }
+}
+
 /// The format of the *immediate* operands of an operator
 /// Immediates appear directly in the byte stream after the opcode,
 /// rather than being popped off the value stack. These are the possible forms.
@@ -428,6 +522,7 @@ impl TryFrom<u8> for MemoryInstruction {
 enum OpImmediates {
     NoImmediate,
     Byte1,
+    Block,
     Bytes4,
     Bytes8,
     Leb32x1,
@@ -444,7 +539,7 @@ fn immediates_for(op: OpCode) -> Result<OpImmediates, String> {
     let imm = match op {
         UNREACHABLE => NoImmediate,
         NOP => NoImmediate,
-        BLOCK | LOOP | IF => Byte1,
+        BLOCK | LOOP | IF => Block,
         ELSE => NoImmediate,
         END => NoImmediate,
         BR | BRIF => Leb32x1,
@@ -517,6 +612,10 @@ impl SkipBytes for OpCode {
             Byte1 => {
                 *cursor += 1 + 1;
             }
+            Block => {
+                *cursor += 1;
+                u64::skip_bytes(bytes, cursor)?;
+            }
             Bytes4 => {
                 *cursor += 1 + 4;
             }
@@ -543,27 +642,38 @@ impl SkipBytes for OpCode {
                     u32::skip_bytes(bytes, cursor)?;
                 }
             }
+            Memory if TruncSatInstruction::try_from(bytes[*cursor + 1]).is_ok() => {
+                *cursor += 1 + 1;
+            }
             Memory => {
                 match MemoryInstruction::try_from(bytes[*cursor + 1]) {
                     Ok(op) => match op {
                         MemoryInstruction::MemoryInit => {
-                            // memory.init
-                            todo!("WASM instruction: memory.init")
+                            // memory.init dataidx 0x00
+                            *cursor += 1 + 1;
+                            u32::skip_bytes(bytes, cursor)?;
+                            *cursor += 1;
                         }
                         MemoryInstruction::DataDrop => {
-                            // data.drop x
-                            todo!("WASM instruction: data.drop")
+                            // data.drop dataidx
+                            *cursor += 1 + 1;
+                            u32::skip_bytes(bytes, cursor)?;
                         }
                         MemoryInstruction::MemoryCopy => {
-                            // memory.copy
+                            // memory.copy 0x00 0x00
                             *cursor += 1 + 1 + 2;
                         }
                         MemoryInstruction::MemoryFill => {
-                            // memory.fill
+                            // memory.fill 0x00
                             *cursor += 1 + 1 + 1;
                         }
                     },
-                    Err(other) => unreachable!("invalid memory instruction {other:?}"),
+                    Err(other) => {
+                        return Err(ParseError {
+                            offset: *cursor,
+                            message: format!("Unknown Wasm instruction 0xfc 0x{other:02x}"),
+                        })
+                    }
                 }
             }
         }
@@ -571,6 +681,142 @@ impl SkipBytes for OpCode {
     }
 }
 
+/// The immediate operands of an instruction, decoded from the bytes following the opcode
+#[derive(Clone, Debug, PartialEq)]
+pub enum Immediates {
+    None,
+    Block(BlockType),
+    /// A label, function, local or global index
+    Index(u32),
+    CallIndirect {
+        type_index: u32,
+        table_index: u32,
+    },
+    /// Alignment (as a power of 2) and offset of a load or store
+    MemArg {
+        align: u32,
+        offset: u32,
+    },
+    /// Memory index for `memory.size` and `memory.grow` (always 0 until multi-memory)
+    MemoryIndex(u8),
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+    BrTable {
+        targets: std::vec::Vec<u32>,
+        default: u32,
+    },
+    /// A bulk memory instruction. Only `memory.init` and `data.drop` refer to a data segment.
+    Memory {
+        op: MemoryInstruction,
+        data_index: u32,
+    },
+    /// A saturating truncation, the other kind of 0xFC-prefixed instruction
+    TruncSat(TruncSatInstruction),
+}
+
+/// An instruction with its immediates decoded, for tools that need more than `skip_bytes`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Instruction {
+    pub op: OpCode,
+    pub immediates: Immediates,
+}
+
+impl Parse<()> for Instruction {
+    fn parse(_: (), bytes: &[u8], cursor: &mut usize) -> Result<Self, ParseError> {
+        let start = *cursor;
+        let err = |message: String| ParseError {
+            offset: start,
+            message,
+        };
+
+        let opcode_byte = *bytes
+            .get(start)
+            .ok_or_else(|| err("Unexpected end of code".into()))?;
+        let op = LOOKUP_TABLE[opcode_byte as usize]
+            .ok_or_else(|| err(format!("Unknown Wasm instruction 0x{opcode_byte:02x}")))?;
+        *cursor += 1;
+
+        let immediates = match immediates_for(op).map_err(err)? {
+            OpImmediates::NoImmediate => Immediates::None,
+            OpImmediates::Byte1 => Immediates::MemoryIndex(u8::parse((), bytes, cursor)?),
+            OpImmediates::Block => Immediates::Block(BlockType::parse((), bytes, cursor)?),
+            OpImmediates::Bytes4 => {
+                let mut b = [0; 4];
+                b.copy_from_slice(
+                    bytes
+                        .get(*cursor..*cursor + 4)
+                        .ok_or_else(|| err("Unexpected end of code in f32.const".into()))?,
+                );
+                *cursor += 4;
+                Immediates::F32(f32::from_le_bytes(b))
+            }
+            OpImmediates::Bytes8 => {
+                let mut b = [0; 8];
+                b.copy_from_slice(
+                    bytes
+                        .get(*cursor..*cursor + 8)
+                        .ok_or_else(|| err("Unexpected end of code in f64.const".into()))?,
+                );
+                *cursor += 8;
+                Immediates::F64(f64::from_le_bytes(b))
+            }
+            OpImmediates::Leb32x1 if op == OpCode::I32CONST => {
+                Immediates::I32(i32::parse((), bytes, cursor)?)
+            }
+            OpImmediates::Leb32x1 => Immediates::Index(u32::parse((), bytes, cursor)?),
+            OpImmediates::Leb64x1 => Immediates::I64(i64::parse((), bytes, cursor)?),
+            OpImmediates::Leb32x2 if op == OpCode::CALLINDIRECT => Immediates::CallIndirect {
+                type_index: u32::parse((), bytes, cursor)?,
+                table_index: u32::parse((), bytes, cursor)?,
+            },
+            OpImmediates::Leb32x2 => Immediates::MemArg {
+                align: u32::parse((), bytes, cursor)?,
+                offset: u32::parse((), bytes, cursor)?,
+            },
+            OpImmediates::BrTable => {
+                let n_targets = u32::parse((), bytes, cursor)?;
+                let mut targets = std::vec::Vec::with_capacity(n_targets as usize);
+                for _ in 0..n_targets {
+                    targets.push(u32::parse((), bytes, cursor)?);
+                }
+                let default = u32::parse((), bytes, cursor)?;
+                Immediates::BrTable { targets, default }
+            }
+            OpImmediates::Memory => {
+                let sub_op = u8::parse((), bytes, cursor)?;
+                if let Ok(trunc_sat) = TruncSatInstruction::try_from(sub_op) {
+                    Immediates::TruncSat(trunc_sat)
+                } else {
+                    let op = MemoryInstruction::try_from(sub_op).map_err(|other| {
+                        err(format!("Unknown Wasm instruction 0xfc 0x{other:02x}"))
+                    })?;
+                    let data_index = match op {
+                        MemoryInstruction::MemoryInit => {
+                            let data_index = u32::parse((), bytes, cursor)?;
+                            *cursor += 1;
+                            data_index
+                        }
+                        MemoryInstruction::DataDrop => u32::parse((), bytes, cursor)?,
+                        MemoryInstruction::MemoryCopy => {
+                            *cursor += 2;
+                            0
+                        }
+                        MemoryInstruction::MemoryFill => {
+                            *cursor += 1;
+                            0
+                        }
+                    };
+                    Immediates::Memory { op, data_index }
+                }
+            }
+        };
+
+        Ok(Instruction { op, immediates })
+    }
+}
+
 impl Serialize for OpCode {
     fn serialize<T: crate::SerialBuffer>(&self, buffer: &mut T) {
         (*self as u8).serialize(buffer)
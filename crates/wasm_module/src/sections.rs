@@ -34,8 +34,8 @@ pub enum SectionId {
     Element = 9,
     Code = 10,
     Data = 11,
-    /// DataCount section is unused. Only needed for single-pass validation of
-    /// memory.init and data.drop, which we don't use
+    /// DataCount section is only needed for single-pass validation of
+    /// memory.init and data.drop. See DataSection::serialize_data_count
     DataCount = 12,
 }
 
@@ -298,7 +298,14 @@ impl<'a> TypeSection<'a> {
         self.bytes.is_empty()
     }
 
-    pub fn look_up(&'a self, sig_index: u32) -> (SignatureParamsIter<'a>, Option<ValueType>) {
+    /// Number of signatures
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// Look up the parameter and result types of a signature.
+    /// With the multi-value proposal, a function can return more than one value.
+    pub fn look_up(&'a self, sig_index: u32) -> (SignatureParamsIter<'a>, SignatureParamsIter<'a>) {
         let mut offset = self.offsets[sig_index as usize];
         offset += 1; // separator
         let param_count = u32::parse((), &self.bytes, &mut offset).unwrap() as usize;
@@ -309,12 +316,13 @@ impl<'a> TypeSection<'a> {
         };
         offset += param_count;
 
-        let return_type = if self.bytes[offset] == 0 {
-            None
-        } else {
-            Some(ValueType::from(self.bytes[offset + 1]))
+        let result_count = u32::parse((), &self.bytes, &mut offset).unwrap() as usize;
+        let results_iter = SignatureParamsIter {
+            bytes: &self.bytes[offset..][..result_count],
+            index: 0,
+            end: result_count,
         };
-        (params_iter, return_type)
+        (params_iter, results_iter)
     }
 }
 
@@ -349,8 +357,8 @@ impl<'a> Parse<&'a Bump> for TypeSection<'a> {
             let n_params = u32::parse((), &bytes, &mut i).unwrap();
             i += n_params as usize; // skip over one byte per param type
 
-            let n_return_values = bytes[i];
-            i += 1 + n_return_values as usize;
+            let n_return_values = u32::parse((), &bytes, &mut i).unwrap();
+            i += n_return_values as usize; // multi-value functions can return more than one
         }
 
         Ok(TypeSection {
@@ -964,11 +972,13 @@ impl Parse<()> for ConstExpr {
             OpCode::F32CONST => {
                 let mut b = [0; 4];
                 b.copy_from_slice(&bytes[*cursor..][..4]);
+                *cursor += 4;
                 Ok(ConstExpr::F32(f32::from_le_bytes(b)))
             }
             OpCode::F64CONST => {
                 let mut b = [0; 8];
                 b.copy_from_slice(&bytes[*cursor..][..8]);
+                *cursor += 8;
                 Ok(ConstExpr::F64(f64::from_le_bytes(b)))
             }
             _ => Err(ParseError {
@@ -1067,6 +1077,18 @@ impl<'a> GlobalSection<'a> {
         self.count += 1;
     }
 
+    /// The type and initial value of each global, in index order
+    pub fn globals(&self) -> Result<std::vec::Vec<Global>, ParseError> {
+        let mut cursor = 0;
+        let mut globals = std::vec::Vec::with_capacity(self.count as usize);
+        for _ in 0..self.count {
+            let ty = GlobalType::parse((), &self.bytes, &mut cursor)?;
+            let init = ConstExpr::parse((), &self.bytes, &mut cursor)?;
+            globals.push(Global { ty, init });
+        }
+        Ok(globals)
+    }
+
     pub fn initial_values<'b>(&self, arena: &'b Bump) -> Vec<'b, Value> {
         let mut cursor = 0;
         let iter = (0..self.count)
@@ -1485,6 +1507,8 @@ pub enum DataMode {
 impl DataMode {
     const ACTIVE: u8 = 0;
     const PASSIVE: u8 = 1;
+    /// Active segment with an explicit memory index, which must be 0 until multi-memory
+    const ACTIVE_WITH_MEMORY_INDEX: u8 = 2;
 
     pub fn active_at(offset: u32) -> Self {
         DataMode::Active {
@@ -1519,6 +1543,18 @@ impl Parse<()> for DataMode {
             })
         } else if variant_id == Self::PASSIVE {
             Ok(DataMode::Passive)
+        } else if variant_id == Self::ACTIVE_WITH_MEMORY_INDEX {
+            let memory_index = u32::parse((), bytes, cursor)?;
+            if memory_index != 0 {
+                return Err(ParseError {
+                    offset: *cursor,
+                    message: format!("Data section: memory index {memory_index} is not supported"),
+                });
+            }
+            let offset = ConstExpr::parse_u32(bytes, cursor)?;
+            Ok(DataMode::Active {
+                offset: ConstExpr::I32(offset as i32),
+            })
         } else {
             Err(ParseError {
                 offset: *cursor - 1,
@@ -1560,7 +1596,8 @@ impl<'a> DataSection<'a> {
     }
 
     pub fn size(&self) -> usize {
-        MAX_SIZE_SECTION_HEADER + self.bytes.len()
+        // Leave room for a DataCount section too
+        2 * MAX_SIZE_SECTION_HEADER + self.bytes.len()
     }
 
     pub fn append_segment(&mut self, segment: DataSegment<'a>) -> u32 {
@@ -1570,11 +1607,21 @@ impl<'a> DataSection<'a> {
         index
     }
 
-    pub fn load_into(&self, memory: &mut [u8]) -> Result<(), String> {
+    /// The mode and initial bytes of each segment, in index order
+    pub fn segments(&self) -> Result<std::vec::Vec<(DataMode, &[u8])>, ParseError> {
         let mut cursor = 0;
+        let mut segments = std::vec::Vec::with_capacity(self.count as usize);
         for _ in 0..self.count {
-            let mode =
-                DataMode::parse((), &self.bytes, &mut cursor).map_err(|e| format!("{e:?}"))?;
+            let mode = DataMode::parse((), &self.bytes, &mut cursor)?;
+            let len = u32::parse((), &self.bytes, &mut cursor)? as usize;
+            segments.push((mode, &self.bytes[cursor..][..len]));
+            cursor += len;
+        }
+        Ok(segments)
+    }
+
+    pub fn load_into(&self, memory: &mut [u8]) -> Result<(), String> {
+        for (mode, init) in self.segments().map_err(|e| format!("{e:?}"))? {
             let start = match mode {
                 DataMode::Active {
                     offset: ConstExpr::I32(addr),
@@ -1583,16 +1630,30 @@ impl<'a> DataSection<'a> {
                     continue;
                 }
             };
-            let len32 = u32::parse((), &self.bytes, &mut cursor).map_err(|e| format!("{e:?}"))?;
-            let len = len32 as usize;
-            let mut target_slice = &mut memory[start..][..len];
-            target_slice
-                .write(&self.bytes[cursor..][..len])
-                .map_err(|e| format!("{e:?}"))?;
-            cursor += len;
+            let mut target_slice = &mut memory[start..][..init.len()];
+            target_slice.write(init).map_err(|e| format!("{e:?}"))?;
         }
         Ok(())
     }
+
+    /// The DataCount section is required if the code refers to data segments by index,
+    /// in `memory.init` or `data.drop`. That only makes sense for passive segments.
+    pub fn serialize_data_count<B: SerialBuffer>(&self, buffer: &mut B) {
+        let has_passive_segments = self
+            .segments()
+            .map(|segments| {
+                segments
+                    .iter()
+                    .any(|(mode, _)| matches!(mode, DataMode::Passive))
+            })
+            .unwrap_or(false);
+
+        if has_passive_segments {
+            let header_indices = write_section_header(buffer, SectionId::DataCount);
+            buffer.encode_u32(self.count);
+            update_section_size(buffer, header_indices);
+        }
+    }
 }
 
 impl<'a> Parse<&'a Bump> for DataSection<'a> {
@@ -0,0 +1,1208 @@
+//! Check a module against the validation rules of the WebAssembly spec
+//! https://webassembly.github.io/spec/core/valid/index.html
+//!
+//! Function bodies are type-checked using the algorithm from the spec's appendix, which tracks
+//! the types on the value stack and the stack height at each block boundary.
+//! https://webassembly.github.io/spec/core/appendix/algorithm.html
+//!
+//! This is a debugging aid for code generation, so it only needs to support the instructions
+//! we can parse. Anything else is reported as an error rather than skipped.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::opcodes::{BlockType, Immediates, Instruction, MemoryInstruction, OpCode};
+use crate::parse::{Parse, ParseError};
+use crate::sections::{DataMode, GlobalType, ImportDesc, Limits};
+use crate::{ConstExpr, ExportType, ValueType, WasmModule};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// The function containing the error, if it's in the Code section
+    pub fn_index: Option<u32>,
+    /// Offset of the invalid instruction. This is a file offset if the module was parsed from a
+    /// file, or an offset into the Code section bytes if it was generated in memory.
+    pub offset: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.fn_index, self.offset) {
+            (Some(fn_index), Some(offset)) => write!(
+                f,
+                "Invalid Wasm in function {fn_index} at offset {offset:#x}: {}",
+                self.message
+            ),
+            (Some(fn_index), None) => {
+                write!(f, "Invalid Wasm in function {fn_index}: {}", self.message)
+            }
+            _ => write!(f, "Invalid Wasm module: {}", self.message),
+        }
+    }
+}
+
+fn module_error<T>(message: String) -> Result<T, ValidationError> {
+    Err(ValidationError {
+        fn_index: None,
+        offset: None,
+        message,
+    })
+}
+
+impl<'a> WasmModule<'a> {
+    /// Check that the module is valid WebAssembly: index bounds in every section,
+    /// and types and stack heights in every function body.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let ctx = ModuleContext::new(self)?;
+        ctx.validate_sections()?;
+
+        let code = &self.code;
+        let first_fn_index = ctx.import_fn_count + code.dead_import_dummy_count as usize;
+        for (i, offset) in code.function_offsets.iter().enumerate() {
+            let fn_index = first_fn_index + i;
+            let signature = ctx.fn_signatures[fn_index];
+            FunctionValidator::new(&ctx, signature)
+                .validate(&code.bytes, *offset as usize)
+                .map_err(|(offset, message)| ValidationError {
+                    fn_index: Some(fn_index as u32),
+                    offset: Some(code.section_offset as usize + offset),
+                    message,
+                })?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Everything about the module that instructions can refer to
+struct ModuleContext<'m> {
+    module: &'m WasmModule<'m>,
+    /// Parameter and result types of each entry in the Type section
+    types: Vec<(Vec<ValueType>, Vec<ValueType>)>,
+    /// Signature index of every function, including imports
+    fn_signatures: Vec<usize>,
+    import_fn_count: usize,
+    globals: Vec<GlobalType>,
+    has_memory: bool,
+    has_table: bool,
+    data_count: usize,
+}
+
+impl<'m> ModuleContext<'m> {
+    fn new(module: &'m WasmModule<'m>) -> Result<Self, ValidationError> {
+        let types: Vec<_> = (0..module.types.len() as u32)
+            .map(|sig_index| {
+                let (params, results) = module.types.look_up(sig_index);
+                (params.collect(), results.collect())
+            })
+            .collect();
+
+        let mut fn_signatures = Vec::with_capacity(module.function.signatures.len());
+        let mut globals = Vec::new();
+        let mut memory_count = module.memory.count;
+        let mut has_table = !module.element.is_empty()
+            || module.table.function_table.limits != Limits::MinMax(0, 0);
+
+        for import in module.import.imports.iter() {
+            match &import.description {
+                ImportDesc::Func { signature_index } => {
+                    fn_signatures.push(*signature_index as usize);
+                }
+                ImportDesc::Table { .. } => has_table = true,
+                ImportDesc::Mem { .. } => memory_count += 1,
+                ImportDesc::Global { ty } => globals.push(*ty),
+            }
+        }
+        let import_fn_count = fn_signatures.len();
+
+        fn_signatures.extend(module.function.signatures.iter().map(|sig| *sig as usize));
+
+        let parse_error = |section: &str, e: ParseError| ValidationError {
+            fn_index: None,
+            offset: None,
+            message: format!("Could not parse {section} section: {}", e.message),
+        };
+        let global_section = module
+            .global
+            .globals()
+            .map_err(|e| parse_error("Global", e))?;
+        for global in global_section.iter() {
+            let init_type = const_expr_type(&global.init);
+            if init_type != global.ty.value_type {
+                return module_error(format!(
+                    "Global {} has type {:?} but its initial value is {:?}",
+                    globals.len(),
+                    global.ty.value_type,
+                    init_type
+                ));
+            }
+            globals.push(global.ty);
+        }
+
+        let data_count = module
+            .data
+            .segments()
+            .map_err(|e| parse_error("Data", e))?
+            .len();
+
+        if memory_count > 1 {
+            return module_error(format!(
+                "Found {memory_count} memories but only one is allowed"
+            ));
+        }
+
+        Ok(ModuleContext {
+            module,
+            types,
+            fn_signatures,
+            import_fn_count,
+            globals,
+            has_memory: memory_count == 1,
+            has_table,
+            data_count,
+        })
+    }
+
+    fn validate_sections(&self) -> Result<(), ValidationError> {
+        let module = self.module;
+
+        for (fn_index, sig) in self.fn_signatures.iter().enumerate() {
+            if *sig >= self.types.len() {
+                return module_error(format!(
+                    "Function {fn_index} has signature {sig} but there are only {} signatures",
+                    self.types.len()
+                ));
+            }
+        }
+
+        let code = &module.code;
+        let defined_fn_count = self.fn_signatures.len() - self.import_fn_count;
+        let code_fn_count = code.dead_import_dummy_count as usize + code.function_offsets.len();
+        if code_fn_count != defined_fn_count {
+            return module_error(format!(
+                "The Function section declares {defined_fn_count} functions but the Code section has {code_fn_count}"
+            ));
+        }
+
+        let mut export_names = HashSet::new();
+        for export in module.export.exports.iter() {
+            let (kind, count) = match export.ty {
+                ExportType::Func => ("function", self.fn_signatures.len()),
+                ExportType::Table => ("table", self.has_table as usize),
+                ExportType::Mem => ("memory", self.has_memory as usize),
+                ExportType::Global => ("global", self.globals.len()),
+            };
+            if export.index as usize >= count {
+                return module_error(format!(
+                    "Export \"{}\" refers to {kind} {} but there are only {count}",
+                    export.name, export.index
+                ));
+            }
+            if !export_names.insert(export.name) {
+                return module_error(format!("Duplicate export name \"{}\"", export.name));
+            }
+        }
+
+        for (i, segment) in module.element.segments.iter().enumerate() {
+            if !self.has_table {
+                return module_error(format!("Element segment {i} needs a table"));
+            }
+            if const_expr_type(&segment.offset) != ValueType::I32 {
+                return module_error(format!("Element segment {i} has a non-i32 offset"));
+            }
+            for fn_index in segment.fn_indices.iter() {
+                self.check_fn_index(*fn_index).or_else(module_error)?;
+            }
+        }
+
+        // The segments were already parsed in ModuleContext::new
+        let segments = module.data.segments().unwrap();
+        for (i, (mode, _)) in segments.iter().enumerate() {
+            if let DataMode::Active { offset } = mode {
+                if !self.has_memory {
+                    return module_error(format!("Data segment {i} needs a memory"));
+                }
+                if const_expr_type(offset) != ValueType::I32 {
+                    return module_error(format!("Data segment {i} has a non-i32 offset"));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_fn_index(&self, fn_index: u32) -> Result<usize, String> {
+        let count = self.fn_signatures.len();
+        if (fn_index as usize) < count {
+            Ok(fn_index as usize)
+        } else {
+            Err(format!(
+                "Function index {fn_index} is out of bounds. There are {count} functions."
+            ))
+        }
+    }
+
+    fn signature(&self, sig_index: u32) -> Result<&(Vec<ValueType>, Vec<ValueType>), String> {
+        self.types.get(sig_index as usize).ok_or_else(|| {
+            format!(
+                "Type index {sig_index} is out of bounds. There are {} types.",
+                self.types.len()
+            )
+        })
+    }
+}
+
+fn const_expr_type(expr: &ConstExpr) -> ValueType {
+    match expr {
+        ConstExpr::I32(_) => ValueType::I32,
+        ConstExpr::I64(_) => ValueType::I64,
+        ConstExpr::F32(_) => ValueType::F32,
+        ConstExpr::F64(_) => ValueType::F64,
+    }
+}
+
+struct ControlFrame {
+    op: OpCode,
+    params: Vec<ValueType>,
+    results: Vec<ValueType>,
+    /// Height of the value stack when the block was entered (after popping its params)
+    height: usize,
+    /// Whether the rest of the block is unreachable, making the stack polymorphic
+    unreachable: bool,
+}
+
+impl ControlFrame {
+    /// The types a branch to this block has to provide
+    fn label_types(&self) -> &[ValueType] {
+        if self.op == OpCode::LOOP {
+            &self.params
+        } else {
+            &self.results
+        }
+    }
+}
+
+struct FunctionValidator<'c, 'm> {
+    ctx: &'c ModuleContext<'m>,
+    results: &'c [ValueType],
+    locals: Vec<ValueType>,
+    /// Types on the value stack. `None` is a value of unknown type, popped from an unreachable stack.
+    values: Vec<Option<ValueType>>,
+    controls: Vec<ControlFrame>,
+}
+
+type FnResult<T> = Result<T, String>;
+
+impl<'c, 'm> FunctionValidator<'c, 'm> {
+    fn new(ctx: &'c ModuleContext<'m>, sig_index: usize) -> Self {
+        let (params, results) = &ctx.types[sig_index];
+        FunctionValidator {
+            ctx,
+            results,
+            locals: params.clone(),
+            values: Vec::new(),
+            controls: Vec::new(),
+        }
+    }
+
+    /// Validate the function starting at `fn_offset` in the code bytes.
+    /// On error, return the offset of the invalid instruction along with the message.
+    fn validate(mut self, bytes: &[u8], fn_offset: usize) -> Result<(), (usize, String)> {
+        let mut cursor = fn_offset;
+        let parse_error = |e: ParseError| (e.offset, e.message);
+
+        let body_len = u32::parse((), bytes, &mut cursor).map_err(parse_error)? as usize;
+        let body_end = cursor + body_len;
+        if body_end > bytes.len() {
+            return Err((
+                fn_offset,
+                "Function body extends past the end of the code".into(),
+            ));
+        }
+        let bytes = &bytes[..body_end];
+
+        let n_local_groups = u32::parse((), bytes, &mut cursor).map_err(parse_error)?;
+        for _ in 0..n_local_groups {
+            let group_offset = cursor;
+            let count = u32::parse((), bytes, &mut cursor).map_err(parse_error)?;
+            let ty = value_type(bytes.get(cursor).copied()).map_err(|e| (cursor, e))?;
+            cursor += 1;
+            if self.locals.len() + count as usize > u32::MAX as usize {
+                return Err((group_offset, "Too many locals".into()));
+            }
+            self.locals.resize(self.locals.len() + count as usize, ty);
+        }
+
+        let results = self.results.to_vec();
+        self.push_ctrl(OpCode::BLOCK, vec![], results);
+
+        while !self.controls.is_empty() {
+            let offset = cursor;
+            if cursor >= body_end {
+                return Err((offset, "Missing `end` at the end of the function".into()));
+            }
+            let instruction = Instruction::parse((), bytes, &mut cursor).map_err(parse_error)?;
+            self.instruction(instruction).map_err(|e| (offset, e))?;
+        }
+
+        if cursor != body_end {
+            return Err((
+                cursor,
+                "Found instructions after the end of the function".into(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn push(&mut self, ty: Option<ValueType>) {
+        self.values.push(ty);
+    }
+
+    fn push_all(&mut self, types: &[ValueType]) {
+        self.values.extend(types.iter().map(|ty| Some(*ty)));
+    }
+
+    fn pop(&mut self) -> FnResult<Option<ValueType>> {
+        let frame = self.controls.last().unwrap();
+        if self.values.len() == frame.height {
+            if frame.unreachable {
+                Ok(None)
+            } else {
+                Err("Expected a value but the stack is empty at this block depth".into())
+            }
+        } else {
+            Ok(self.values.pop().unwrap())
+        }
+    }
+
+    fn pop_expect(&mut self, expected: ValueType) -> FnResult<Option<ValueType>> {
+        match self.pop()? {
+            Some(actual) if actual != expected => Err(format!(
+                "Type mismatch: expected {expected:?} but found {actual:?}"
+            )),
+            Some(actual) => Ok(Some(actual)),
+            None => Ok(Some(expected)),
+        }
+    }
+
+    fn pop_all(&mut self, types: &[ValueType]) -> FnResult<Vec<Option<ValueType>>> {
+        let mut popped = vec![None; types.len()];
+        for (i, ty) in types.iter().enumerate().rev() {
+            popped[i] = self.pop_expect(*ty)?;
+        }
+        Ok(popped)
+    }
+
+    /// Enter a block. Its params must already have been popped from the enclosing block.
+    fn push_ctrl(&mut self, op: OpCode, params: Vec<ValueType>, results: Vec<ValueType>) {
+        let height = self.values.len();
+        self.push_all(&params);
+        self.controls.push(ControlFrame {
+            op,
+            params,
+            results,
+            height,
+            unreachable: false,
+        });
+    }
+
+    fn pop_ctrl(&mut self) -> FnResult<ControlFrame> {
+        let results = self.controls.last().unwrap().results.clone();
+        self.pop_all(&results)?;
+        let frame = self.controls.pop().unwrap();
+        if self.values.len() != frame.height {
+            let extra = self.values.len() - frame.height;
+            return Err(format!(
+                "Expected {} values at the end of the block, but found {extra} more",
+                results.len()
+            ));
+        }
+        Ok(frame)
+    }
+
+    fn set_unreachable(&mut self) {
+        let frame = self.controls.last_mut().unwrap();
+        self.values.truncate(frame.height);
+        frame.unreachable = true;
+    }
+
+    fn label(&self, depth: u32) -> FnResult<Vec<ValueType>> {
+        let n_controls = self.controls.len();
+        if (depth as usize) < n_controls {
+            let frame = &self.controls[n_controls - 1 - depth as usize];
+            Ok(frame.label_types().to_vec())
+        } else {
+            Err(format!(
+                "Branch to block depth {depth} but we are only {n_controls} blocks deep"
+            ))
+        }
+    }
+
+    fn block_type(&self, block_type: BlockType) -> FnResult<(Vec<ValueType>, Vec<ValueType>)> {
+        match block_type {
+            BlockType::Empty => Ok((vec![], vec![])),
+            BlockType::Value(ty) => Ok((vec![], vec![ty])),
+            BlockType::Signature(sig_index) => self.ctx.signature(sig_index).cloned(),
+        }
+    }
+
+    fn check_memory(&self) -> FnResult<()> {
+        if self.ctx.has_memory {
+            Ok(())
+        } else {
+            Err("Memory instruction in a module with no memory".into())
+        }
+    }
+
+    fn instruction(&mut self, instruction: Instruction) -> FnResult<()> {
+        use OpCode::*;
+        use ValueType::*;
+
+        let Instruction { op, immediates } = instruction;
+
+        if let Some((params, result)) = numeric_signature(op) {
+            self.pop_all(params)?;
+            self.push(Some(result));
+            return Ok(());
+        }
+
+        if let Some((ty, natural_align, is_store)) = memory_access(op) {
+            self.check_memory()?;
+            if let Immediates::MemArg { align, .. } = immediates {
+                if align > natural_align {
+                    return Err(format!(
+                        "Alignment of {} bytes is larger than the {} bytes accessed",
+                        1u64 << align.min(63),
+                        1 << natural_align
+                    ));
+                }
+            }
+            if is_store {
+                self.pop_expect(ty)?;
+                self.pop_expect(I32)?;
+            } else {
+                self.pop_expect(I32)?;
+                self.push(Some(ty));
+            }
+            return Ok(());
+        }
+
+        match (op, immediates) {
+            (UNREACHABLE, _) => self.set_unreachable(),
+            (NOP, _) => {}
+            (BLOCK | LOOP, Immediates::Block(block_type)) => {
+                let (params, results) = self.block_type(block_type)?;
+                self.pop_all(&params)?;
+                self.push_ctrl(op, params, results);
+            }
+            (IF, Immediates::Block(block_type)) => {
+                let (params, results) = self.block_type(block_type)?;
+                self.pop_expect(I32)?;
+                self.pop_all(&params)?;
+                self.push_ctrl(op, params, results);
+            }
+            (ELSE, _) => {
+                let frame = self.pop_ctrl()?;
+                if frame.op != IF {
+                    return Err("Found `else` outside of an `if` block".into());
+                }
+                self.push_ctrl(ELSE, frame.params, frame.results);
+            }
+            (END, _) => {
+                let frame = self.pop_ctrl()?;
+                if frame.op == IF && frame.params != frame.results {
+                    return Err(format!(
+                        "An `if` with no `else` must produce its params {:?} but it has results {:?}",
+                        frame.params, frame.results
+                    ));
+                }
+                if !self.controls.is_empty() {
+                    self.push_all(&frame.results);
+                }
+            }
+            (BR, Immediates::Index(depth)) => {
+                let label = self.label(depth)?;
+                self.pop_all(&label)?;
+                self.set_unreachable();
+            }
+            (BRIF, Immediates::Index(depth)) => {
+                self.pop_expect(I32)?;
+                let label = self.label(depth)?;
+                self.pop_all(&label)?;
+                self.push_all(&label);
+            }
+            (BRTABLE, Immediates::BrTable { targets, default }) => {
+                self.pop_expect(I32)?;
+                let default_label = self.label(default)?;
+                for depth in targets {
+                    let label = self.label(depth)?;
+                    if label.len() != default_label.len() {
+                        return Err(format!(
+                            "br_table targets carry different numbers of values ({} and {})",
+                            label.len(),
+                            default_label.len()
+                        ));
+                    }
+                    let popped = self.pop_all(&label)?;
+                    self.values.extend(popped);
+                }
+                self.pop_all(&default_label)?;
+                self.set_unreachable();
+            }
+            (RETURN, _) => {
+                let results = self.results;
+                self.pop_all(results)?;
+                self.set_unreachable();
+            }
+            (CALL, Immediates::Index(fn_index)) => {
+                let fn_index = self.ctx.check_fn_index(fn_index)?;
+                let ctx = self.ctx;
+                let (params, results) = &ctx.types[ctx.fn_signatures[fn_index]];
+                self.pop_all(params)?;
+                self.push_all(results);
+            }
+            (
+                CALLINDIRECT,
+                Immediates::CallIndirect {
+                    type_index,
+                    table_index,
+                },
+            ) => {
+                if table_index != 0 || !self.ctx.has_table {
+                    return Err(format!(
+                        "call_indirect uses table {table_index}, which does not exist"
+                    ));
+                }
+                let ctx = self.ctx;
+                let (params, results) = ctx.signature(type_index)?;
+                self.pop_expect(I32)?;
+                self.pop_all(params)?;
+                self.push_all(results);
+            }
+            (DROP, _) => {
+                self.pop()?;
+            }
+            (SELECT, _) => {
+                self.pop_expect(I32)?;
+                let t1 = self.pop()?;
+                let t2 = self.pop()?;
+                match (t1, t2) {
+                    (Some(a), Some(b)) if a != b => {
+                        return Err(format!(
+                            "select operands have different types {a:?} and {b:?}"
+                        ));
+                    }
+                    _ => self.push(t1.or(t2)),
+                }
+            }
+            (GETLOCAL, Immediates::Index(index)) => {
+                let ty = self.local(index)?;
+                self.push(Some(ty));
+            }
+            (SETLOCAL, Immediates::Index(index)) => {
+                let ty = self.local(index)?;
+                self.pop_expect(ty)?;
+            }
+            (TEELOCAL, Immediates::Index(index)) => {
+                let ty = self.local(index)?;
+                self.pop_expect(ty)?;
+                self.push(Some(ty));
+            }
+            (GETGLOBAL, Immediates::Index(index)) => {
+                let global = self.global(index)?;
+                self.push(Some(global.value_type));
+            }
+            (SETGLOBAL, Immediates::Index(index)) => {
+                let global = self.global(index)?;
+                if !global.is_mutable {
+                    return Err(format!("global.set on immutable global {index}"));
+                }
+                self.pop_expect(global.value_type)?;
+            }
+            (CURRENTMEMORY | GROWMEMORY, Immediates::MemoryIndex(index)) => {
+                self.check_memory()?;
+                if index != 0 {
+                    return Err(format!("Memory index {index} does not exist"));
+                }
+                if op == GROWMEMORY {
+                    self.pop_expect(I32)?;
+                }
+                self.push(Some(I32));
+            }
+            (MEMORY, Immediates::Memory { op, data_index }) => {
+                self.check_memory()?;
+                match op {
+                    MemoryInstruction::MemoryInit | MemoryInstruction::DataDrop => {
+                        if data_index as usize >= self.ctx.data_count {
+                            return Err(format!(
+                                "Data segment {data_index} is out of bounds. There are {} segments.",
+                                self.ctx.data_count
+                            ));
+                        }
+                    }
+                    MemoryInstruction::MemoryCopy | MemoryInstruction::MemoryFill => {}
+                }
+                if op != MemoryInstruction::DataDrop {
+                    self.pop_all(&[I32, I32, I32])?;
+                }
+            }
+            (MEMORY, Immediates::TruncSat(op)) => {
+                let (param, result) = op.signature();
+                self.pop_expect(param)?;
+                self.push(Some(result));
+            }
+            (I32CONST, _) => self.push(Some(I32)),
+            (I64CONST, _) => self.push(Some(I64)),
+            (F32CONST, _) => self.push(Some(F32)),
+            (F64CONST, _) => self.push(Some(F64)),
+            (op, immediates) => {
+                // Instruction::parse always produces the right immediates for the opcode
+                unreachable!("{op:?} with immediates {immediates:?}")
+            }
+        }
+
+        Ok(())
+    }
+
+    fn local(&self, index: u32) -> FnResult<ValueType> {
+        self.locals.get(index as usize).copied().ok_or_else(|| {
+            format!(
+                "Local {index} is out of bounds. The function has {} params and locals.",
+                self.locals.len()
+            )
+        })
+    }
+
+    fn global(&self, index: u32) -> FnResult<GlobalType> {
+        self.ctx
+            .globals
+            .get(index as usize)
+            .copied()
+            .ok_or_else(|| {
+                format!(
+                    "Global {index} is out of bounds. There are {} globals.",
+                    self.ctx.globals.len()
+                )
+            })
+    }
+}
+
+fn value_type(byte: Option<u8>) -> FnResult<ValueType> {
+    match byte {
+        Some(b) if (ValueType::F64 as u8..=ValueType::I32 as u8).contains(&b) => {
+            Ok(ValueType::from(b))
+        }
+        Some(b) => Err(format!("Invalid value type 0x{b:02x}")),
+        None => Err("Unexpected end of code".into()),
+    }
+}
+
+/// Params and result of instructions that just operate on the value stack
+fn numeric_signature(op: OpCode) -> Option<(&'static [ValueType], ValueType)> {
+    use OpCode::*;
+    use ValueType::*;
+
+    let signature: (&'static [ValueType], ValueType) = match op {
+        I32EQZ => (&[I32], I32),
+        I32EQ | I32NE | I32LTS | I32LTU | I32GTS | I32GTU | I32LES | I32LEU | I32GES | I32GEU => {
+            (&[I32, I32], I32)
+        }
+        I64EQZ => (&[I64], I32),
+        I64EQ | I64NE | I64LTS | I64LTU | I64GTS | I64GTU | I64LES | I64LEU | I64GES | I64GEU => {
+            (&[I64, I64], I32)
+        }
+        F32EQ | F32NE | F32LT | F32GT | F32LE | F32GE => (&[F32, F32], I32),
+        F64EQ | F64NE | F64LT | F64GT | F64LE | F64GE => (&[F64, F64], I32),
+
+        I32CLZ | I32CTZ | I32POPCNT | I32EXTEND8S | I32EXTEND16S => (&[I32], I32),
+        I32ADD | I32SUB | I32MUL | I32DIVS | I32DIVU | I32REMS | I32REMU | I32AND | I32OR
+        | I32XOR | I32SHL | I32SHRS | I32SHRU | I32ROTL | I32ROTR => (&[I32, I32], I32),
+        I64CLZ | I64CTZ | I64POPCNT | I64EXTEND8S | I64EXTEND16S | I64EXTEND32S => (&[I64], I64),
+        I64ADD | I64SUB | I64MUL | I64DIVS | I64DIVU | I64REMS | I64REMU | I64AND | I64OR
+        | I64XOR | I64SHL | I64SHRS | I64SHRU | I64ROTL | I64ROTR => (&[I64, I64], I64),
+        F32ABS | F32NEG | F32CEIL | F32FLOOR | F32TRUNC | F32NEAREST | F32SQRT => (&[F32], F32),
+        F32ADD | F32SUB | F32MUL | F32DIV | F32MIN | F32MAX | F32COPYSIGN => (&[F32, F32], F32),
+        F64ABS | F64NEG | F64CEIL | F64FLOOR | F64TRUNC | F64NEAREST | F64SQRT => (&[F64], F64),
+        F64ADD | F64SUB | F64MUL | F64DIV | F64MIN | F64MAX | F64COPYSIGN => (&[F64, F64], F64),
+
+        I32WRAPI64 => (&[I64], I32),
+        I32TRUNCSF32 | I32TRUNCUF32 | I32REINTERPRETF32 => (&[F32], I32),
+        I32TRUNCSF64 | I32TRUNCUF64 => (&[F64], I32),
+        I64EXTENDSI32 | I64EXTENDUI32 => (&[I32], I64),
+        I64TRUNCSF32 | I64TRUNCUF32 => (&[F32], I64),
+        I64TRUNCSF64 | I64TRUNCUF64 | I64REINTERPRETF64 => (&[F64], I64),
+        F32CONVERTSI32 | F32CONVERTUI32 | F32REINTERPRETI32 => (&[I32], F32),
+        F32CONVERTSI64 | F32CONVERTUI64 => (&[I64], F32),
+        F32DEMOTEF64 => (&[F64], F32),
+        F64CONVERTSI32 | F64CONVERTUI32 => (&[I32], F64),
+        F64CONVERTSI64 | F64CONVERTUI64 | F64REINTERPRETI64 => (&[I64], F64),
+        F64PROMOTEF32 => (&[F32], F64),
+
+        _ => return None,
+    };
+
+    Some(signature)
+}
+
+/// Value type, natural alignment (as a power of 2), and direction of loads and stores
+fn memory_access(op: OpCode) -> Option<(ValueType, u32, bool)> {
+    use OpCode::*;
+    use ValueType::*;
+
+    let access = match op {
+        I32LOAD => (I32, 2, false),
+        I64LOAD => (I64, 3, false),
+        F32LOAD => (F32, 2, false),
+        F64LOAD => (F64, 3, false),
+        I32LOAD8S | I32LOAD8U => (I32, 0, false),
+        I32LOAD16S | I32LOAD16U => (I32, 1, false),
+        I64LOAD8S | I64LOAD8U => (I64, 0, false),
+        I64LOAD16S | I64LOAD16U => (I64, 1, false),
+        I64LOAD32S | I64LOAD32U => (I64, 2, false),
+        I32STORE => (I32, 2, true),
+        I64STORE => (I64, 3, true),
+        F32STORE => (F32, 2, true),
+        F64STORE => (F64, 3, true),
+        I32STORE8 => (I32, 0, true),
+        I32STORE16 => (I32, 1, true),
+        I64STORE8 => (I64, 0, true),
+        I64STORE16 => (I64, 1, true),
+        I64STORE32 => (I64, 2, true),
+        _ => return None,
+    };
+
+    Some(access)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sections::{DataSegment, Global, MemorySection};
+    use crate::{Export, SerialBuffer, Signature};
+    use bumpalo::{collections::Vec, Bump};
+
+    /// A module with one exported function `() -> i32` with the given body (excluding locals)
+    fn module_with_body<'a>(arena: &'a Bump, body: &[u8]) -> WasmModule<'a> {
+        let mut module = WasmModule::new(arena);
+        module.add_function_signature(Signature {
+            param_types: Vec::new_in(arena),
+            ret_type: Some(ValueType::I32),
+        });
+        module.export.append(Export {
+            name: "test",
+            ty: ExportType::Func,
+            index: 0,
+        });
+
+        let code = &mut module.code;
+        code.function_offsets.push(code.bytes.len() as u32);
+        code.bytes.encode_u32(body.len() as u32 + 1);
+        code.bytes.push(0); // no locals
+        code.bytes.extend_from_slice(body);
+        code.function_count = 1;
+
+        module
+    }
+
+    fn validate_body(body: &[u8]) -> Result<(), String> {
+        let arena = Bump::new();
+        let module = module_with_body(&arena, body);
+        module.validate().map_err(|e| e.message)
+    }
+
+    /// Offset of the body's first instruction, after the body size and locals count
+    const BODY_START: usize = 2;
+
+    /// Check that `module_with_body` rejects `body` at `index` bytes into the body
+    fn assert_body_error(module: WasmModule, index: usize, message: &str) {
+        assert_eq!(
+            module.validate(),
+            Err(ValidationError {
+                fn_index: Some(0),
+                offset: Some(BODY_START + index),
+                message: message.into(),
+            })
+        );
+    }
+
+    fn assert_module_error(module: WasmModule, message: &str) {
+        assert_eq!(
+            module.validate(),
+            Err(ValidationError {
+                fn_index: None,
+                offset: None,
+                message: message.into(),
+            })
+        );
+    }
+
+    fn valid_body() -> [u8; 3] {
+        [OpCode::I32CONST as u8, 0, OpCode::END as u8]
+    }
+
+    #[test]
+    fn test_valid_function() {
+        use OpCode::*;
+        let body = [
+            I32CONST as u8,
+            1,
+            IF as u8,
+            ValueType::I32 as u8,
+            I32CONST as u8,
+            2,
+            ELSE as u8,
+            I32CONST as u8,
+            3,
+            END as u8,
+            BLOCK as u8,
+            ValueType::VOID,
+            BR as u8,
+            0,
+            I64CONST as u8, // unreachable code can be badly typed
+            I32ADD as u8,
+            DROP as u8,
+            END as u8,
+            END as u8,
+        ];
+        assert_eq!(validate_body(&body), Ok(()));
+    }
+
+    #[test]
+    fn test_type_mismatch() {
+        use OpCode::*;
+        let body = [
+            I32CONST as u8,
+            1,
+            I64CONST as u8,
+            2,
+            I32ADD as u8,
+            END as u8,
+        ];
+        let message = validate_body(&body).unwrap_err();
+        assert_eq!(message, "Type mismatch: expected I32 but found I64");
+    }
+
+    #[test]
+    fn test_trunc_sat() {
+        use OpCode::*;
+        let mut body = vec![F64CONST as u8];
+        body.extend_from_slice(&1.5f64.to_le_bytes());
+        body.extend_from_slice(&[MEMORY as u8, 3, END as u8]); // i32.trunc_sat_f64_u
+        assert_eq!(validate_body(&body), Ok(()));
+
+        let body = [I32CONST as u8, 1, MEMORY as u8, 0, END as u8]; // i32.trunc_sat_f32_s
+        let message = validate_body(&body).unwrap_err();
+        assert_eq!(message, "Type mismatch: expected F32 but found I32");
+    }
+
+    #[test]
+    fn test_extra_value_at_end_of_block() {
+        use OpCode::*;
+        let body = [
+            BLOCK as u8,
+            ValueType::VOID,
+            I32CONST as u8,
+            1,
+            END as u8,
+            I32CONST as u8,
+            2,
+            END as u8,
+        ];
+        let message = validate_body(&body).unwrap_err();
+        assert_eq!(
+            message,
+            "Expected 0 values at the end of the block, but found 1 more"
+        );
+    }
+
+    #[test]
+    fn test_stack_underflow() {
+        use OpCode::*;
+        let body = [I32CONST as u8, 1, I32ADD as u8, END as u8];
+        let message = validate_body(&body).unwrap_err();
+        assert_eq!(
+            message,
+            "Expected a value but the stack is empty at this block depth"
+        );
+    }
+
+    #[test]
+    fn test_call_out_of_bounds() {
+        use OpCode::*;
+        let body = [CALL as u8, 5, END as u8];
+        let message = validate_body(&body).unwrap_err();
+        assert_eq!(
+            message,
+            "Function index 5 is out of bounds. There are 1 functions."
+        );
+    }
+
+    #[test]
+    fn test_error_location() {
+        use OpCode::*;
+        let arena = Bump::new();
+        let module = module_with_body(&arena, &[NOP as u8, GETLOCAL as u8, 0, END as u8]);
+        let error = module.validate().unwrap_err();
+        assert_eq!(error.fn_index, Some(0));
+        assert_eq!(error.offset, Some(3)); // after the body size, locals count and nop
+    }
+
+    #[test]
+    fn test_local_out_of_bounds() {
+        use OpCode::*;
+        let arena = Bump::new();
+        let module = module_with_body(&arena, &[GETLOCAL as u8, 3, END as u8]);
+        assert_body_error(
+            module,
+            0,
+            "Local 3 is out of bounds. The function has 0 params and locals.",
+        );
+    }
+
+    #[test]
+    fn test_branch_too_deep() {
+        use OpCode::*;
+        let arena = Bump::new();
+        let module = module_with_body(&arena, &[BR as u8, 2, END as u8]);
+        assert_body_error(
+            module,
+            0,
+            "Branch to block depth 2 but we are only 1 blocks deep",
+        );
+    }
+
+    #[test]
+    fn test_else_outside_if() {
+        use OpCode::*;
+        let arena = Bump::new();
+        let body = [
+            BLOCK as u8,
+            ValueType::VOID,
+            ELSE as u8,
+            END as u8,
+            I32CONST as u8,
+            0,
+            END as u8,
+        ];
+        let module = module_with_body(&arena, &body);
+        assert_body_error(module, 2, "Found `else` outside of an `if` block");
+    }
+
+    #[test]
+    fn test_if_without_else_needs_matching_results() {
+        use OpCode::*;
+        let arena = Bump::new();
+        let body = [
+            I32CONST as u8,
+            1,
+            IF as u8,
+            ValueType::I32 as u8,
+            I32CONST as u8,
+            2,
+            END as u8,
+            END as u8,
+        ];
+        let module = module_with_body(&arena, &body);
+        assert_body_error(
+            module,
+            6,
+            "An `if` with no `else` must produce its params [] but it has results [I32]",
+        );
+    }
+
+    #[test]
+    fn test_missing_end() {
+        use OpCode::*;
+        let arena = Bump::new();
+        let module = module_with_body(&arena, &[I32CONST as u8, 1]);
+        assert_body_error(module, 2, "Missing `end` at the end of the function");
+    }
+
+    #[test]
+    fn test_instructions_after_end() {
+        use OpCode::*;
+        let arena = Bump::new();
+        let module = module_with_body(&arena, &[I32CONST as u8, 1, END as u8, NOP as u8]);
+        assert_body_error(
+            module,
+            3,
+            "Found instructions after the end of the function",
+        );
+    }
+
+    #[test]
+    fn test_select_type_mismatch() {
+        use OpCode::*;
+        let arena = Bump::new();
+        let body = [
+            I32CONST as u8,
+            1,
+            I64CONST as u8,
+            2,
+            I32CONST as u8,
+            0,
+            SELECT as u8,
+            DROP as u8,
+            I32CONST as u8,
+            0,
+            END as u8,
+        ];
+        let module = module_with_body(&arena, &body);
+        assert_body_error(
+            module,
+            6,
+            "select operands have different types I64 and I32",
+        );
+    }
+
+    #[test]
+    fn test_call_indirect_type_out_of_bounds() {
+        use OpCode::*;
+        let arena = Bump::new();
+        let body = [I32CONST as u8, 0, CALLINDIRECT as u8, 5, 0, END as u8];
+        let module = module_with_body(&arena, &body);
+        assert_body_error(
+            module,
+            2,
+            "Type index 5 is out of bounds. There are 1 types.",
+        );
+    }
+
+    #[test]
+    fn test_load_without_memory() {
+        use OpCode::*;
+        let arena = Bump::new();
+        let body = [I32CONST as u8, 0, I32LOAD as u8, 2, 0, END as u8];
+        let module = module_with_body(&arena, &body);
+        assert_body_error(module, 2, "Memory instruction in a module with no memory");
+    }
+
+    #[test]
+    fn test_load_over_aligned() {
+        use OpCode::*;
+        let arena = Bump::new();
+        let body = [I32CONST as u8, 0, I32LOAD as u8, 3, 0, END as u8];
+        let mut module = module_with_body(&arena, &body);
+        module.memory = MemorySection::new(&arena, MemorySection::PAGE_SIZE);
+        assert_body_error(
+            module,
+            2,
+            "Alignment of 8 bytes is larger than the 4 bytes accessed",
+        );
+    }
+
+    #[test]
+    fn test_set_immutable_global() {
+        use OpCode::*;
+        let arena = Bump::new();
+        let body = [
+            I32CONST as u8,
+            1,
+            SETGLOBAL as u8,
+            0,
+            I32CONST as u8,
+            0,
+            END as u8,
+        ];
+        let mut module = module_with_body(&arena, &body);
+        module.global.append(Global {
+            ty: GlobalType {
+                value_type: ValueType::I32,
+                is_mutable: false,
+            },
+            init: ConstExpr::I32(0),
+        });
+        assert_body_error(module, 2, "global.set on immutable global 0");
+    }
+
+    #[test]
+    fn test_global_init_type_mismatch() {
+        let arena = Bump::new();
+        let mut module = module_with_body(&arena, &valid_body());
+        module.global.append(Global {
+            ty: GlobalType {
+                value_type: ValueType::I64,
+                is_mutable: true,
+            },
+            init: ConstExpr::I32(0),
+        });
+        assert_module_error(module, "Global 0 has type I64 but its initial value is I32");
+    }
+
+    #[test]
+    fn test_export_out_of_bounds() {
+        let arena = Bump::new();
+        let mut module = module_with_body(&arena, &valid_body());
+        module.export.append(Export {
+            name: "missing",
+            ty: ExportType::Func,
+            index: 3,
+        });
+        assert_module_error(
+            module,
+            "Export \"missing\" refers to function 3 but there are only 1",
+        );
+    }
+
+    #[test]
+    fn test_duplicate_export() {
+        let arena = Bump::new();
+        let mut module = module_with_body(&arena, &valid_body());
+        module.export.append(Export {
+            name: "test",
+            ty: ExportType::Func,
+            index: 0,
+        });
+        assert_module_error(module, "Duplicate export name \"test\"");
+    }
+
+    #[test]
+    fn test_function_without_body() {
+        let arena = Bump::new();
+        let mut module = module_with_body(&arena, &valid_body());
+        module.add_function_signature(Signature {
+            param_types: Vec::new_in(&arena),
+            ret_type: None,
+        });
+        assert_module_error(
+            module,
+            "The Function section declares 2 functions but the Code section has 1",
+        );
+    }
+
+    #[test]
+    fn test_data_segment_without_memory() {
+        let arena = Bump::new();
+        let mut module = module_with_body(&arena, &valid_body());
+        module.data.append_segment(DataSegment {
+            mode: DataMode::active_at(0),
+            init: Vec::from_iter_in("hi".bytes(), &arena),
+        });
+        assert_module_error(module, "Data segment 0 needs a memory");
+    }
+
+    #[test]
+    fn test_error_display() {
+        let in_function = ValidationError {
+            fn_index: Some(2),
+            offset: Some(0x1c),
+            message: "Type mismatch: expected I32 but found I64".into(),
+        };
+        assert_eq!(
+            in_function.to_string(),
+            "Invalid Wasm in function 2 at offset 0x1c: Type mismatch: expected I32 but found I64"
+        );
+
+        let in_module = ValidationError {
+            fn_index: None,
+            offset: None,
+            message: "Data segment 0 needs a memory".into(),
+        };
+        assert_eq!(
+            in_module.to_string(),
+            "Invalid Wasm module: Data segment 0 needs a memory"
+        );
+    }
+}
@@ -0,0 +1,794 @@
+//! Print a module in the WebAssembly text format (WAT), in roughly the same layout as `wasm2wat`.
+//! https://webassembly.github.io/spec/core/text/index.html
+//!
+//! This is for reading, not for round-tripping through other tools. Sections we don't
+//! model (like linking data) are left out, and float constants are printed in decimal.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::opcodes::{
+    BlockType, Immediates, Instruction, MemoryInstruction, OpCode, TruncSatInstruction,
+};
+use crate::parse::{Parse, ParseError};
+use crate::sections::{DataMode, GlobalType, ImportDesc, Limits, RefType, SignatureParamsIter};
+use crate::{ConstExpr, ExportType, ValueType, WasmModule};
+
+impl<'a> WasmModule<'a> {
+    /// Print the module in WebAssembly text format, for debugging
+    pub fn to_wat(&self) -> Result<String, ParseError> {
+        let mut printer = WatPrinter {
+            module: self,
+            fn_names: self
+                .names
+                .function_names
+                .iter()
+                .map(|(index, name)| (*index, *name))
+                .collect(),
+            out: String::new(),
+        };
+        printer.module()?;
+        Ok(printer.out)
+    }
+}
+
+struct WatPrinter<'m> {
+    module: &'m WasmModule<'m>,
+    fn_names: HashMap<u32, &'m str>,
+    out: String,
+}
+
+impl<'m> WatPrinter<'m> {
+    fn module(&mut self) -> Result<(), ParseError> {
+        let module = self.module;
+        self.out.push_str("(module\n");
+
+        for sig_index in 0..module.types.len() as u32 {
+            let (params, results) = module.types.look_up(sig_index);
+            write!(self.out, "  (type (;{sig_index};) (func").unwrap();
+            self.params_and_results(params, results);
+            self.out.push_str("))\n");
+        }
+
+        let mut fn_index = 0;
+        let mut global_index = 0;
+        for import in module.import.imports.iter() {
+            write!(
+                self.out,
+                "  (import \"{}\" \"{}\" ",
+                import.module, import.name
+            )
+            .unwrap();
+            match &import.description {
+                ImportDesc::Func { signature_index } => {
+                    self.out.push_str("(func ");
+                    self.fn_label(fn_index);
+                    write!(self.out, "(type {signature_index}))").unwrap();
+                    fn_index += 1;
+                }
+                ImportDesc::Table { ty } => {
+                    self.out.push_str("(table (;0;) ");
+                    self.limits(&ty.limits);
+                    self.out.push_str(ref_type(ty.ref_type));
+                    self.out.push(')');
+                }
+                ImportDesc::Mem { limits } => {
+                    self.out.push_str("(memory (;0;) ");
+                    self.limits(limits);
+                    self.out.pop(); // trailing space
+                    self.out.push(')');
+                }
+                ImportDesc::Global { ty } => {
+                    write!(self.out, "(global (;{global_index};) ").unwrap();
+                    self.global_type(ty);
+                    self.out.push(')');
+                    global_index += 1;
+                }
+            }
+            self.out.push_str(")\n");
+        }
+
+        let code = &module.code;
+        for _ in 0..code.dead_import_dummy_count {
+            self.function_header(fn_index);
+            self.out.push_str("\n    unreachable)\n");
+            fn_index += 1;
+        }
+        for offset in code.function_offsets.iter() {
+            self.function(fn_index, &code.bytes, *offset as usize)?;
+            fn_index += 1;
+        }
+
+        if !module.element.is_empty() {
+            self.out.push_str("  (table (;0;) ");
+            self.limits(&module.table.function_table.limits);
+            self.out.push_str("funcref)\n");
+        }
+
+        if module.memory.count > 0 {
+            let mut cursor = 0;
+            let limits = Limits::parse((), &module.memory.bytes, &mut cursor)?;
+            self.out.push_str("  (memory (;0;) ");
+            self.limits(&limits);
+            self.out.pop();
+            self.out.push_str(")\n");
+        }
+
+        for global in module.global.globals()? {
+            write!(self.out, "  (global (;{global_index};) ").unwrap();
+            self.global_type(&global.ty);
+            self.out.push(' ');
+            self.const_expr(&global.init);
+            self.out.push_str(")\n");
+            global_index += 1;
+        }
+
+        for export in module.export.exports.iter() {
+            write!(self.out, "  (export \"{}\" ", export.name).unwrap();
+            match export.ty {
+                ExportType::Func => {
+                    self.out.push_str("(func ");
+                    self.fn_ref(export.index);
+                }
+                ExportType::Table => write!(self.out, "(table {}", export.index).unwrap(),
+                ExportType::Mem => write!(self.out, "(memory {}", export.index).unwrap(),
+                ExportType::Global => write!(self.out, "(global {}", export.index).unwrap(),
+            }
+            self.out.push_str("))\n");
+        }
+
+        for (i, segment) in module.element.segments.iter().enumerate() {
+            write!(self.out, "  (elem (;{i};) ").unwrap();
+            self.const_expr(&segment.offset);
+            self.out.push_str(" func");
+            for fn_index in segment.fn_indices.iter() {
+                self.out.push(' ');
+                self.fn_ref(*fn_index);
+            }
+            self.out.push_str(")\n");
+        }
+
+        for (i, (mode, init)) in module.data.segments()?.into_iter().enumerate() {
+            write!(self.out, "  (data (;{i};) ").unwrap();
+            if let DataMode::Active { offset } = &mode {
+                self.const_expr(offset);
+                self.out.push(' ');
+            }
+            self.out.push('"');
+            for byte in init {
+                match byte {
+                    b'"' | b'\\' => write!(self.out, "\\{}", *byte as char).unwrap(),
+                    0x20..=0x7e => self.out.push(*byte as char),
+                    _ => write!(self.out, "\\{byte:02x}").unwrap(),
+                }
+            }
+            self.out.push_str("\")\n");
+        }
+
+        self.out.push_str(")\n");
+        Ok(())
+    }
+
+    fn function_header(&mut self, fn_index: u32) {
+        let module = self.module;
+        let import_fn_count = module.import.function_count() as u32;
+        let sig_index = module.function.signatures[(fn_index - import_fn_count) as usize];
+        let (params, results) = module.types.look_up(sig_index);
+
+        self.out.push_str("  (func ");
+        self.fn_label(fn_index);
+        write!(self.out, "(type {sig_index})").unwrap();
+        self.params_and_results(params, results);
+    }
+
+    fn function(&mut self, fn_index: u32, bytes: &[u8], offset: usize) -> Result<(), ParseError> {
+        self.function_header(fn_index);
+
+        let mut cursor = offset;
+        let body_len = u32::parse((), bytes, &mut cursor)? as usize;
+        let body_end = cursor + body_len;
+
+        let n_local_groups = u32::parse((), bytes, &mut cursor)?;
+        if n_local_groups > 0 {
+            self.out.push_str("\n    (local");
+            for _ in 0..n_local_groups {
+                let (count, ty) = <(u32, ValueType)>::parse((), bytes, &mut cursor)?;
+                for _ in 0..count {
+                    write!(self.out, " {}", value_type(ty)).unwrap();
+                }
+            }
+            self.out.push(')');
+        }
+
+        // Block depth, for indentation and branch target comments
+        let mut depth: u32 = 0;
+        while cursor < body_end {
+            let Instruction { op, immediates } = Instruction::parse((), bytes, &mut cursor)?;
+
+            // The function's own `end` is implied by the closing paren
+            if op == OpCode::END && depth == 0 {
+                break;
+            }
+            if matches!(op, OpCode::END | OpCode::ELSE) {
+                depth = depth.saturating_sub(1);
+            }
+
+            self.out.push('\n');
+            for _ in 0..(depth + 2) {
+                self.out.push_str("  ");
+            }
+            self.out.push_str(mnemonic(op, &immediates));
+            self.immediates(op, immediates, depth);
+
+            if matches!(op, OpCode::BLOCK | OpCode::LOOP | OpCode::IF | OpCode::ELSE) {
+                depth += 1;
+            }
+        }
+
+        self.out.push_str(")\n");
+        Ok(())
+    }
+
+    fn immediates(&mut self, op: OpCode, immediates: Immediates, depth: u32) {
+        match immediates {
+            Immediates::None | Immediates::MemoryIndex(_) | Immediates::TruncSat(_) => {}
+            Immediates::Block(block_type) => {
+                match block_type {
+                    BlockType::Empty => {}
+                    BlockType::Value(ty) => {
+                        write!(self.out, " (result {})", value_type(ty)).unwrap()
+                    }
+                    BlockType::Signature(sig_index) => {
+                        write!(self.out, " (type {sig_index})").unwrap()
+                    }
+                }
+                write!(self.out, "  ;; label = @{}", depth + 1).unwrap();
+            }
+            Immediates::Index(index) => match op {
+                OpCode::BR | OpCode::BRIF => {
+                    write!(self.out, " {index} (;@{};)", depth.saturating_sub(index)).unwrap()
+                }
+                OpCode::CALL => {
+                    self.out.push(' ');
+                    self.fn_ref(index);
+                }
+                _ => write!(self.out, " {index}").unwrap(),
+            },
+            Immediates::CallIndirect { type_index, .. } => {
+                write!(self.out, " (type {type_index})").unwrap()
+            }
+            Immediates::MemArg { align, offset } => {
+                if offset != 0 {
+                    write!(self.out, " offset={offset}").unwrap();
+                }
+                if Some(align) != natural_alignment(op) {
+                    write!(self.out, " align={}", 1u64 << align.min(63)).unwrap();
+                }
+            }
+            Immediates::I32(x) => write!(self.out, " {x}").unwrap(),
+            Immediates::I64(x) => write!(self.out, " {x}").unwrap(),
+            Immediates::F32(x) => write!(self.out, " {}", float(x as f64, x.is_nan())).unwrap(),
+            Immediates::F64(x) => write!(self.out, " {}", float(x, x.is_nan())).unwrap(),
+            Immediates::BrTable { targets, default } => {
+                for index in targets.iter().chain(std::iter::once(&default)) {
+                    write!(self.out, " {index} (;@{};)", depth.saturating_sub(*index)).unwrap();
+                }
+            }
+            Immediates::Memory { op, data_index } => match op {
+                MemoryInstruction::MemoryInit | MemoryInstruction::DataDrop => {
+                    write!(self.out, " {data_index}").unwrap()
+                }
+                MemoryInstruction::MemoryCopy | MemoryInstruction::MemoryFill => {}
+            },
+        }
+    }
+
+    /// The name (if any) and index of a function, where it's defined or imported
+    fn fn_label(&mut self, fn_index: u32) {
+        if self.fn_names.contains_key(&fn_index) {
+            self.fn_ref(fn_index);
+            self.out.push(' ');
+        }
+        write!(self.out, "(;{fn_index};) ").unwrap();
+    }
+
+    /// A function's name if it has one, otherwise its index
+    fn fn_ref(&mut self, fn_index: u32) {
+        match self.fn_names.get(&fn_index) {
+            Some(name) => {
+                self.out.push('$');
+                // Replace anything that isn't allowed in a WAT identifier
+                self.out.extend(name.chars().map(|c| {
+                    if c.is_ascii_alphanumeric() || "!#$%&'*+-./:<=>?@\\^_`|~".contains(c) {
+                        c
+                    } else {
+                        '_'
+                    }
+                }));
+            }
+            None => write!(self.out, "{fn_index}").unwrap(),
+        }
+    }
+
+    fn params_and_results(&mut self, params: SignatureParamsIter, results: SignatureParamsIter) {
+        for (keyword, types) in [("param", params), ("result", results)] {
+            let names: Vec<&str> = types.map(value_type).collect();
+            if !names.is_empty() {
+                write!(self.out, " ({keyword} {})", names.join(" ")).unwrap();
+            }
+        }
+    }
+
+    fn limits(&mut self, limits: &Limits) {
+        match limits {
+            Limits::Min(min) => write!(self.out, "{min} ").unwrap(),
+            Limits::MinMax(min, max) => write!(self.out, "{min} {max} ").unwrap(),
+        }
+    }
+
+    fn global_type(&mut self, ty: &GlobalType) {
+        if ty.is_mutable {
+            write!(self.out, "(mut {})", value_type(ty.value_type)).unwrap();
+        } else {
+            self.out.push_str(value_type(ty.value_type));
+        }
+    }
+
+    fn const_expr(&mut self, expr: &ConstExpr) {
+        match expr {
+            ConstExpr::I32(x) => write!(self.out, "(i32.const {x})").unwrap(),
+            ConstExpr::I64(x) => write!(self.out, "(i64.const {x})").unwrap(),
+            ConstExpr::F32(x) => {
+                write!(self.out, "(f32.const {})", float(*x as f64, x.is_nan())).unwrap()
+            }
+            ConstExpr::F64(x) => write!(self.out, "(f64.const {})", float(*x, x.is_nan())).unwrap(),
+        }
+    }
+}
+
+fn value_type(ty: ValueType) -> &'static str {
+    match ty {
+        ValueType::I32 => "i32",
+        ValueType::I64 => "i64",
+        ValueType::F32 => "f32",
+        ValueType::F64 => "f64",
+    }
+}
+
+fn ref_type(ty: RefType) -> &'static str {
+    match ty {
+        RefType::Func => "funcref",
+        RefType::Extern => "externref",
+    }
+}
+
+fn float(x: f64, is_nan: bool) -> String {
+    if is_nan {
+        "nan".into()
+    } else if x.is_infinite() {
+        if x > 0.0 { "inf" } else { "-inf" }.into()
+    } else {
+        format!("{x:?}")
+    }
+}
+
+/// Natural alignment of a load or store, as a power of 2
+fn natural_alignment(op: OpCode) -> Option<u32> {
+    use OpCode::*;
+
+    let align = match op {
+        I32LOAD8S | I32LOAD8U | I64LOAD8S | I64LOAD8U | I32STORE8 | I64STORE8 => 0,
+        I32LOAD16S | I32LOAD16U | I64LOAD16S | I64LOAD16U | I32STORE16 | I64STORE16 => 1,
+        I32LOAD | F32LOAD | I64LOAD32S | I64LOAD32U | I32STORE | F32STORE | I64STORE32 => 2,
+        I64LOAD | F64LOAD | I64STORE | F64STORE => 3,
+        _ => return None,
+    };
+
+    Some(align)
+}
+
+fn mnemonic(op: OpCode, immediates: &Immediates) -> &'static str {
+    use OpCode::*;
+
+    match op {
+        UNREACHABLE => "unreachable",
+        NOP => "nop",
+        BLOCK => "block",
+        LOOP => "loop",
+        IF => "if",
+        ELSE => "else",
+        END => "end",
+        BR => "br",
+        BRIF => "br_if",
+        BRTABLE => "br_table",
+        RETURN => "return",
+        CALL => "call",
+        CALLINDIRECT => "call_indirect",
+        DROP => "drop",
+        SELECT => "select",
+        GETLOCAL => "local.get",
+        SETLOCAL => "local.set",
+        TEELOCAL => "local.tee",
+        GETGLOBAL => "global.get",
+        SETGLOBAL => "global.set",
+        I32LOAD => "i32.load",
+        I64LOAD => "i64.load",
+        F32LOAD => "f32.load",
+        F64LOAD => "f64.load",
+        I32LOAD8S => "i32.load8_s",
+        I32LOAD8U => "i32.load8_u",
+        I32LOAD16S => "i32.load16_s",
+        I32LOAD16U => "i32.load16_u",
+        I64LOAD8S => "i64.load8_s",
+        I64LOAD8U => "i64.load8_u",
+        I64LOAD16S => "i64.load16_s",
+        I64LOAD16U => "i64.load16_u",
+        I64LOAD32S => "i64.load32_s",
+        I64LOAD32U => "i64.load32_u",
+        I32STORE => "i32.store",
+        I64STORE => "i64.store",
+        F32STORE => "f32.store",
+        F64STORE => "f64.store",
+        I32STORE8 => "i32.store8",
+        I32STORE16 => "i32.store16",
+        I64STORE8 => "i64.store8",
+        I64STORE16 => "i64.store16",
+        I64STORE32 => "i64.store32",
+        CURRENTMEMORY => "memory.size",
+        GROWMEMORY => "memory.grow",
+        MEMORY => match immediates {
+            Immediates::Memory { op, .. } => match op {
+                MemoryInstruction::MemoryInit => "memory.init",
+                MemoryInstruction::DataDrop => "data.drop",
+                MemoryInstruction::MemoryCopy => "memory.copy",
+                MemoryInstruction::MemoryFill => "memory.fill",
+            },
+            Immediates::TruncSat(op) => match op {
+                TruncSatInstruction::I32TruncSatF32S => "i32.trunc_sat_f32_s",
+                TruncSatInstruction::I32TruncSatF32U => "i32.trunc_sat_f32_u",
+                TruncSatInstruction::I32TruncSatF64S => "i32.trunc_sat_f64_s",
+                TruncSatInstruction::I32TruncSatF64U => "i32.trunc_sat_f64_u",
+                TruncSatInstruction::I64TruncSatF32S => "i64.trunc_sat_f32_s",
+                TruncSatInstruction::I64TruncSatF32U => "i64.trunc_sat_f32_u",
+                TruncSatInstruction::I64TruncSatF64S => "i64.trunc_sat_f64_s",
+                TruncSatInstruction::I64TruncSatF64U => "i64.trunc_sat_f64_u",
+            },
+            _ => unreachable!("{op:?} with immediates {immediates:?}"),
+        },
+        I32CONST => "i32.const",
+        I64CONST => "i64.const",
+        F32CONST => "f32.const",
+        F64CONST => "f64.const",
+        I32EQZ => "i32.eqz",
+        I32EQ => "i32.eq",
+        I32NE => "i32.ne",
+        I32LTS => "i32.lt_s",
+        I32LTU => "i32.lt_u",
+        I32GTS => "i32.gt_s",
+        I32GTU => "i32.gt_u",
+        I32LES => "i32.le_s",
+        I32LEU => "i32.le_u",
+        I32GES => "i32.ge_s",
+        I32GEU => "i32.ge_u",
+        I64EQZ => "i64.eqz",
+        I64EQ => "i64.eq",
+        I64NE => "i64.ne",
+        I64LTS => "i64.lt_s",
+        I64LTU => "i64.lt_u",
+        I64GTS => "i64.gt_s",
+        I64GTU => "i64.gt_u",
+        I64LES => "i64.le_s",
+        I64LEU => "i64.le_u",
+        I64GES => "i64.ge_s",
+        I64GEU => "i64.ge_u",
+        F32EQ => "f32.eq",
+        F32NE => "f32.ne",
+        F32LT => "f32.lt",
+        F32GT => "f32.gt",
+        F32LE => "f32.le",
+        F32GE => "f32.ge",
+        F64EQ => "f64.eq",
+        F64NE => "f64.ne",
+        F64LT => "f64.lt",
+        F64GT => "f64.gt",
+        F64LE => "f64.le",
+        F64GE => "f64.ge",
+        I32CLZ => "i32.clz",
+        I32CTZ => "i32.ctz",
+        I32POPCNT => "i32.popcnt",
+        I32ADD => "i32.add",
+        I32SUB => "i32.sub",
+        I32MUL => "i32.mul",
+        I32DIVS => "i32.div_s",
+        I32DIVU => "i32.div_u",
+        I32REMS => "i32.rem_s",
+        I32REMU => "i32.rem_u",
+        I32AND => "i32.and",
+        I32OR => "i32.or",
+        I32XOR => "i32.xor",
+        I32SHL => "i32.shl",
+        I32SHRS => "i32.shr_s",
+        I32SHRU => "i32.shr_u",
+        I32ROTL => "i32.rotl",
+        I32ROTR => "i32.rotr",
+        I64CLZ => "i64.clz",
+        I64CTZ => "i64.ctz",
+        I64POPCNT => "i64.popcnt",
+        I64ADD => "i64.add",
+        I64SUB => "i64.sub",
+        I64MUL => "i64.mul",
+        I64DIVS => "i64.div_s",
+        I64DIVU => "i64.div_u",
+        I64REMS => "i64.rem_s",
+        I64REMU => "i64.rem_u",
+        I64AND => "i64.and",
+        I64OR => "i64.or",
+        I64XOR => "i64.xor",
+        I64SHL => "i64.shl",
+        I64SHRS => "i64.shr_s",
+        I64SHRU => "i64.shr_u",
+        I64ROTL => "i64.rotl",
+        I64ROTR => "i64.rotr",
+        F32ABS => "f32.abs",
+        F32NEG => "f32.neg",
+        F32CEIL => "f32.ceil",
+        F32FLOOR => "f32.floor",
+        F32TRUNC => "f32.trunc",
+        F32NEAREST => "f32.nearest",
+        F32SQRT => "f32.sqrt",
+        F32ADD => "f32.add",
+        F32SUB => "f32.sub",
+        F32MUL => "f32.mul",
+        F32DIV => "f32.div",
+        F32MIN => "f32.min",
+        F32MAX => "f32.max",
+        F32COPYSIGN => "f32.copysign",
+        F64ABS => "f64.abs",
+        F64NEG => "f64.neg",
+        F64CEIL => "f64.ceil",
+        F64FLOOR => "f64.floor",
+        F64TRUNC => "f64.trunc",
+        F64NEAREST => "f64.nearest",
+        F64SQRT => "f64.sqrt",
+        F64ADD => "f64.add",
+        F64SUB => "f64.sub",
+        F64MUL => "f64.mul",
+        F64DIV => "f64.div",
+        F64MIN => "f64.min",
+        F64MAX => "f64.max",
+        F64COPYSIGN => "f64.copysign",
+        I32WRAPI64 => "i32.wrap_i64",
+        I32TRUNCSF32 => "i32.trunc_f32_s",
+        I32TRUNCUF32 => "i32.trunc_f32_u",
+        I32TRUNCSF64 => "i32.trunc_f64_s",
+        I32TRUNCUF64 => "i32.trunc_f64_u",
+        I64EXTENDSI32 => "i64.extend_i32_s",
+        I64EXTENDUI32 => "i64.extend_i32_u",
+        I64TRUNCSF32 => "i64.trunc_f32_s",
+        I64TRUNCUF32 => "i64.trunc_f32_u",
+        I64TRUNCSF64 => "i64.trunc_f64_s",
+        I64TRUNCUF64 => "i64.trunc_f64_u",
+        F32CONVERTSI32 => "f32.convert_i32_s",
+        F32CONVERTUI32 => "f32.convert_i32_u",
+        F32CONVERTSI64 => "f32.convert_i64_s",
+        F32CONVERTUI64 => "f32.convert_i64_u",
+        F32DEMOTEF64 => "f32.demote_f64",
+        F64CONVERTSI32 => "f64.convert_i32_s",
+        F64CONVERTUI32 => "f64.convert_i32_u",
+        F64CONVERTSI64 => "f64.convert_i64_s",
+        F64CONVERTUI64 => "f64.convert_i64_u",
+        F64PROMOTEF32 => "f64.promote_f32",
+        I32REINTERPRETF32 => "i32.reinterpret_f32",
+        I64REINTERPRETF64 => "i64.reinterpret_f64",
+        F32REINTERPRETI32 => "f32.reinterpret_i32",
+        F64REINTERPRETI64 => "f64.reinterpret_i64",
+        I32EXTEND8S => "i32.extend8_s",
+        I32EXTEND16S => "i32.extend16_s",
+        I64EXTEND8S => "i64.extend8_s",
+        I64EXTEND16S => "i64.extend16_s",
+        I64EXTEND32S => "i64.extend32_s",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::opcodes::OpCode;
+    use crate::sections::DataMode;
+    use crate::sections::{
+        DataSegment, ElementSegment, Global, GlobalType, Import, ImportDesc, Limits, MemorySection,
+    };
+    use crate::{ConstExpr, Export, ExportType, SerialBuffer, Signature, ValueType, WasmModule};
+    use bumpalo::{collections::Vec, Bump};
+
+    #[test]
+    fn test_to_wat() {
+        use OpCode::*;
+        let arena = Bump::new();
+        let mut module = WasmModule::new(&arena);
+        module.memory = MemorySection::new(&arena, MemorySection::PAGE_SIZE);
+        module.add_function_signature(Signature {
+            param_types: bumpalo::vec![in &arena; ValueType::I32],
+            ret_type: Some(ValueType::I32),
+        });
+        module.export.append(Export {
+            name: "double",
+            ty: ExportType::Func,
+            index: 0,
+        });
+        module.names.append_function(0, "double");
+        module.data.append_segment(DataSegment {
+            mode: DataMode::active_at(16),
+            init: Vec::from_iter_in("hi\n".bytes(), &arena),
+        });
+
+        let body = [
+            0, // no locals
+            BLOCK as u8,
+            ValueType::I32 as u8,
+            GETLOCAL as u8,
+            0,
+            GETLOCAL as u8,
+            0,
+            I32LOAD as u8,
+            3, // align 8, which is over-aligned
+            4, // offset
+            I32ADD as u8,
+            BRIF as u8,
+            0,
+            END as u8,
+            END as u8,
+        ];
+        let code = &mut module.code;
+        code.function_offsets.push(0);
+        code.bytes.encode_u32(body.len() as u32);
+        code.bytes.extend_from_slice(&body);
+        code.function_count = 1;
+
+        let expected = r#"(module
+  (type (;0;) (func (param i32) (result i32)))
+  (func $double (;0;) (type 0) (param i32) (result i32)
+    block (result i32)  ;; label = @1
+      local.get 0
+      local.get 0
+      i32.load offset=4 align=8
+      i32.add
+      br_if 0 (;@1;)
+    end)
+  (memory (;0;) 1)
+  (export "double" (func $double))
+  (data (;0;) (i32.const 16) "hi\0a")
+)
+"#;
+        assert_eq!(module.to_wat().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_to_wat_imports_and_control_flow() {
+        use OpCode::*;
+        let arena = Bump::new();
+        let mut module = WasmModule::new(&arena);
+        module.types.insert(Signature {
+            param_types: bumpalo::vec![in &arena; ValueType::I32],
+            ret_type: None,
+        });
+        module.import.imports.push(Import {
+            module: "env",
+            name: "log",
+            description: ImportDesc::Func { signature_index: 0 },
+        });
+        module.import.imports.push(Import {
+            module: "env",
+            name: "stack_pointer",
+            description: ImportDesc::Global {
+                ty: GlobalType {
+                    value_type: ValueType::I32,
+                    is_mutable: true,
+                },
+            },
+        });
+        module.names.append_function(0, "log");
+        module.add_function_signature(Signature {
+            param_types: bumpalo::vec![in &arena; ValueType::I32],
+            ret_type: Some(ValueType::I32),
+        });
+        module.global.append(Global {
+            ty: GlobalType {
+                value_type: ValueType::F64,
+                is_mutable: false,
+            },
+            init: ConstExpr::F64(1.5),
+        });
+        module.export.append(Export {
+            name: "stack_pointer",
+            ty: ExportType::Global,
+            index: 0,
+        });
+        module.table.function_table.limits = Limits::MinMax(2, 2);
+        module.element.segments.push(ElementSegment {
+            offset: ConstExpr::I32(1),
+            fn_indices: bumpalo::vec![in &arena; 1],
+        });
+        module.data.append_segment(DataSegment {
+            mode: DataMode::Passive,
+            init: Vec::from_iter_in("a\"b\\\0".bytes(), &arena),
+        });
+
+        let body = [
+            1, // one group of locals
+            2,
+            ValueType::I64 as u8,
+            LOOP as u8,
+            ValueType::VOID,
+            GETLOCAL as u8,
+            0,
+            IF as u8,
+            ValueType::I32 as u8,
+            I32CONST as u8,
+            1,
+            ELSE as u8,
+            I32CONST as u8,
+            0x7f, // -1
+            END as u8,
+            CALL as u8,
+            0,
+            GETLOCAL as u8,
+            0,
+            BRTABLE as u8,
+            1, // one target, then the default
+            0,
+            1,
+            END as u8,
+            GETGLOBAL as u8,
+            1,
+            DROP as u8,
+            I32CONST as u8,
+            0,
+            I32CONST as u8,
+            0,
+            CALLINDIRECT as u8,
+            0, // type
+            0, // table
+            GETLOCAL as u8,
+            0,
+            END as u8,
+        ];
+        let code = &mut module.code;
+        code.function_offsets.push(0);
+        code.bytes.encode_u32(body.len() as u32);
+        code.bytes.extend_from_slice(&body);
+        code.function_count = 1;
+
+        let expected = r#"(module
+  (type (;0;) (func (param i32)))
+  (type (;1;) (func (param i32) (result i32)))
+  (import "env" "log" (func $log (;0;) (type 0)))
+  (import "env" "stack_pointer" (global (;0;) (mut i32)))
+  (func (;1;) (type 1) (param i32) (result i32)
+    (local i64 i64)
+    loop  ;; label = @1
+      local.get 0
+      if (result i32)  ;; label = @2
+        i32.const 1
+      else
+        i32.const -1
+      end
+      call $log
+      local.get 0
+      br_table 0 (;@1;) 1 (;@0;)
+    end
+    global.get 1
+    drop
+    i32.const 0
+    i32.const 0
+    call_indirect (type 0)
+    local.get 0)
+  (table (;0;) 2 2 funcref)
+  (global (;1;) f64 (f64.const 1.5))
+  (export "stack_pointer" (global 0))
+  (elem (;0;) (i32.const 1) func 1)
+  (data (;0;) "a\"b\\\00")
+)
+"#;
+        assert_eq!(module.to_wat().unwrap(), expected);
+    }
+}